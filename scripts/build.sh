@@ -24,12 +24,13 @@ fi
 
 # Function to print help instructions
 print_help() {
-    echo "Usage: $0 [all|evm|evm_testnet|signature_verification|spender|minter]"
+    echo "Usage: $0 [all|evm|evm_testnet|signature_verification|spender|minter|rune-bridge-previous]"
     echo "Examples:"
     echo "  $0                      # Build all canisters, download binaries and build tools (default)"
     echo "  $0 all                  # Build all canisters and download binaries and build tools"
     echo "  $0 evm_testnet          # Build only the EVM canister for testnet"
     echo "  $0 spender minter       # Build the spender and minter canisters"
+    echo "  $0 rune-bridge-previous # Build the rune-bridge of PREVIOUS_REF (default: origin/main) for the upgrade tests"
 }
 
 # Initial setup
@@ -96,7 +97,7 @@ build_canister() {
     mkdir -p "$WASM_DIR"
 
     # Generate the did file
-    cargo run -p "$canister_name" --features "$features" >"$WASM_DIR/$did_file_name.did"
+    cargo run -p "$canister_name" --features "$features" --bin generate_did -- "$WASM_DIR/$did_file_name.did"
 
     echo "Building $canister_name Canister with features: $features"
 
@@ -105,6 +106,28 @@ build_canister() {
     gzip -k "$WASM_DIR/$output_wasm" --force
 }
 
+# Function to build a canister from a previous revision, used by the upgrade tests.
+# The revision is taken from the PREVIOUS_REF variable and defaults to the main branch.
+build_previous_canister() {
+    local canister_name="$1"
+    local previous_ref="${PREVIOUS_REF:-origin/main}"
+    local worktree_dir="target/previous-worktree"
+
+    mkdir -p "$WASM_DIR"
+    rm -rf "$worktree_dir"
+    git worktree prune
+    git worktree add --detach "$worktree_dir" "$previous_ref"
+    git -C "$worktree_dir" submodule update --init --recursive
+
+    echo "Building $canister_name Canister of $previous_ref"
+
+    (cd "$worktree_dir" && cargo build --target wasm32-unknown-unknown --release --package "$canister_name" --features "export-api")
+    ic-wasm "$worktree_dir/target/wasm32-unknown-unknown/release/$canister_name.wasm" -o "$WASM_DIR/$canister_name-previous.wasm" shrink
+    gzip -k "$WASM_DIR/$canister_name-previous.wasm" --force
+
+    git worktree remove --force "$worktree_dir"
+}
+
 # Function to determine which canisters to build based on input
 build_requested_canisters() {
     if [ $# -eq 0 ]; then
@@ -151,6 +174,9 @@ build_requested_canisters() {
             btc-bridge | rune-bridge | icrc2-minter | erc20-minter)
                 build_canister "${canister}" "export-api" "${canister}.wasm" "${canister}"
                 ;;
+            rune-bridge-previous)
+                build_previous_canister "rune-bridge"
+                ;;
             *)
                 echo "Error: Unknown canister '$canister'."
                 print_help
@@ -10,7 +10,7 @@ function usage() {
   echo "Usage: $0 [options]"
   echo "Options:"
   echo "  -h, --help                                      Display this help message"
-  echo "  -b, --bitcoin-network <network>                 Bitcoin network (regtest, testnet, mainnet) (default: regtest)"
+  echo "  -b, --bitcoin-network <network>                 Bitcoin network (regtest, signet, testnet, testnet4, mainnet) (default: regtest)"
   echo "  -e, --evm-principal <principal>                 EVM Principal"
   echo "  -i, --ic-network <network>                      Internet Computer network (local, ic) (default: local)"
   echo "  -m, --install-mode <mode>                       Install mode (create, init, reinstall, upgrade)"
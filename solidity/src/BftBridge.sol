@@ -3,6 +3,7 @@ pragma solidity ^0.8.7;
 
 import "@openzeppelin/contracts/utils/cryptography/ECDSA.sol";
 import "@openzeppelin/contracts/token/ERC20/utils/SafeERC20.sol";
+import "@openzeppelin/contracts/token/ERC20/extensions/IERC20Permit.sol";
 import "src/WrappedToken.sol";
 import "src/interfaces/IFeeCharge.sol";
 import { RingBuffer } from "src/libraries/RingBuffer.sol";
@@ -36,6 +37,12 @@ contract BFTBridge is TokenManager, UUPSUpgradeable, OwnableUpgradeable, Pausabl
     // todo: estimate better: https://infinityswap.atlassian.net/browse/EPROD-919
     uint256 constant additionalGasFee = 1000;
 
+    // EIP-712 types of the burn permits signed by the token holders.
+    bytes32 constant EIP712_DOMAIN_TYPEHASH =
+        keccak256("EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)");
+    bytes32 constant BURN_PERMIT_TYPEHASH =
+        keccak256("BurnPermit(uint256 amount,address fromERC20,bytes recipientID,uint256 deadline,uint256 nonce)");
+
     // Has a user's transaction nonce been used?
     mapping(bytes32 => mapping(uint32 => bool)) private _isNonceUsed;
 
@@ -57,6 +64,13 @@ contract BFTBridge is TokenManager, UUPSUpgradeable, OwnableUpgradeable, Pausabl
     /// Allowed implementations hash list
     mapping(bytes32 => bool) public allowedImplementations;
 
+    /// Nonces of the burn permits of the token holders.
+    mapping(address => uint256) public burnPermitNonces;
+
+    // Pre-registry ids of the sender chains and back, see `setSenderChainIDAlias`.
+    mapping(uint32 => uint32) private _senderChainIDAliases;
+    mapping(uint32 => bool) private _hasSenderChainIDAlias;
+
     // Event for mint operation
     event MintTokenEvent(
         uint256 amount, bytes32 fromToken, bytes32 senderID, address toERC20, address recipient, uint32 nonce
@@ -114,6 +128,20 @@ contract BFTBridge is TokenManager, UUPSUpgradeable, OwnableUpgradeable, Pausabl
         _unpause();
     }
 
+    /// Sets the `legacyChainID` the sender chain `chainID` was identified by in the mint orders
+    /// signed before the minter moved to the canonical chain ids. The nonces of an EVM address
+    /// sender are shared by its ids with both chain ids, so a mint order signed with the legacy
+    /// id cannot be minted again when re-issued with the canonical one.
+    /// Can be called only by the owner
+    function setSenderChainIDAlias(uint32 chainID, uint32 legacyChainID) external onlyOwner {
+        require(chainID != legacyChainID, "Chain ID cannot be its own alias");
+
+        _senderChainIDAliases[chainID] = legacyChainID;
+        _senderChainIDAliases[legacyChainID] = chainID;
+        _hasSenderChainIDAlias[chainID] = true;
+        _hasSenderChainIDAlias[legacyChainID] = true;
+    }
+
     /// Add a new implementation to the allowed list
     function addAllowedImplementation(address newImplementation) external onlyOwner {
         require(newImplementation != address(0), "Invalid implementation address");
@@ -180,6 +208,18 @@ contract BFTBridge is TokenManager, UUPSUpgradeable, OwnableUpgradeable, Pausabl
         emit MintTokenEvent(order.amount, order.fromTokenID, order.senderID, toToken, order.recipient, order.nonce);
     }
 
+    /// Updates the metadata of a wrapped token deployed by the bridge.
+    /// Only the minter canister can call this function.
+    function updateWrappedTokenMetadata(address wrappedToken, bytes32 name, bytes16 symbol, uint8 decimals)
+        external
+    {
+        require(msg.sender == minterCanisterAddress, "Only minter canister can update metadata");
+        require(isWrappedSide, "Metadata can be updated only on the wrapped side");
+        require(_baseTokenRegistry[wrappedToken] != bytes32(0), "Unknown wrapped token");
+
+        updateTokenMetadata(wrappedToken, name, symbol, decimals);
+    }
+
     /// Getter function for block numbers
     function getDepositBlocks() external view returns (uint32[] memory blockNumbers) {
         blockNumbers = _lastUserBurns[msg.sender].getAll();
@@ -189,9 +229,67 @@ contract BFTBridge is TokenManager, UUPSUpgradeable, OwnableUpgradeable, Pausabl
     /// Caller should approve transfer in the given `from_erc20` token for the bridge contract.
     /// Returns operation ID if operation is succesfull.
     function burn(uint256 amount, address fromERC20, bytes memory recipientID) public whenNotPaused returns (uint32) {
+        return _burnFrom(msg.sender, amount, fromERC20, recipientID);
+    }
+
+    /// Burn ERC 20 tokens of the `from` address approved with an ERC-2612 permit for the bridge
+    /// contract, so the user doesn't have to send an approve transaction.
+    /// The `burnSignature` of the `from` address over `burnPermitDigest` binds the burn to the
+    /// `recipientID`. The burn is relayed by the minter canister.
+    /// Returns operation ID if operation is succesfull.
+    function burnWithPermit(
+        address from,
+        uint256 amount,
+        address fromERC20,
+        bytes memory recipientID,
+        uint256 deadline,
+        uint8 v,
+        bytes32 r,
+        bytes32 s,
+        bytes memory burnSignature
+    ) external whenNotPaused returns (uint32) {
+        require(msg.sender == minterCanisterAddress, "Only minter canister can burn with permit");
+        require(block.timestamp <= deadline, "Burn permit is expired");
+
+        bytes32 digest = burnPermitDigest(from, amount, fromERC20, recipientID, deadline);
+        require(ECDSA.recover(digest, burnSignature) == from, "Invalid burn permit signature");
+        burnPermitNonces[from]++;
+
+        // The permit could be submitted by anyone watching the mempool, using up its nonce. The
+        // allowance is checked by the transfer of the burn anyway, so a failed permit is ignored.
+        try IERC20Permit(fromERC20).permit(from, address(this), amount, deadline, v, r, s) {} catch {}
+
+        return _burnFrom(from, amount, fromERC20, recipientID);
+    }
+
+    /// Returns the EIP-712 digest of the burn permit to be signed by the `from` address with its
+    /// current burn permit nonce.
+    function burnPermitDigest(
+        address from,
+        uint256 amount,
+        address fromERC20,
+        bytes memory recipientID,
+        uint256 deadline
+    ) public view returns (bytes32) {
+        bytes32 domainSeparator = keccak256(
+            abi.encode(EIP712_DOMAIN_TYPEHASH, keccak256("BftBridge"), keccak256("1"), block.chainid, address(this))
+        );
+        bytes32 structHash = keccak256(
+            abi.encode(
+                BURN_PERMIT_TYPEHASH, amount, fromERC20, keccak256(recipientID), deadline, burnPermitNonces[from]
+            )
+        );
+
+        return keccak256(abi.encodePacked("\x19\x01", domainSeparator, structHash));
+    }
+
+    function _burnFrom(address from, uint256 amount, address fromERC20, bytes memory recipientID)
+        private
+        returns (uint32)
+    {
         require(fromERC20 != address(this), "From address must not be BFT bridge address");
 
-        IERC20(fromERC20).safeTransferFrom(msg.sender, address(this), amount);
+        IERC20(fromERC20).safeTransferFrom(from, address(this), amount);
 
         bytes32 toTokenID = _baseTokenRegistry[fromERC20];
 
@@ -199,7 +297,7 @@ contract BFTBridge is TokenManager, UUPSUpgradeable, OwnableUpgradeable, Pausabl
         require(fromERC20 != address(0), "Invalid from address");
 
         // Update user information about burn operations.
-        _lastUserBurns[msg.sender].push(uint32(block.number));
+        _lastUserBurns[from].push(uint32(block.number));
 
         // get the token details
         TokenMetadata memory meta = getTokenMetadata(fromERC20);
@@ -207,7 +305,7 @@ contract BFTBridge is TokenManager, UUPSUpgradeable, OwnableUpgradeable, Pausabl
         uint32 operationID = operationIDCounter++;
 
         emit BurnTokenEvent(
-            msg.sender, amount, fromERC20, recipientID, toTokenID, operationID, meta.name, meta.symbol, meta.decimals
+            from, amount, fromERC20, recipientID, toTokenID, operationID, meta.name, meta.symbol, meta.decimals
         );
 
         return operationID;
@@ -218,6 +316,34 @@ contract BFTBridge is TokenManager, UUPSUpgradeable, OwnableUpgradeable, Pausabl
         return minterCanisterAddress;
     }
 
+    /// Returns true if the mint order with the given sender and nonce is minted already
+    function isNonceUsed(bytes32 senderID, uint32 nonce) public view returns (bool) {
+        if (_isNonceUsed[senderID][nonce]) {
+            return true;
+        }
+
+        bytes32 senderIDAlias = _senderIDAlias(senderID);
+        return senderIDAlias != bytes32(0) && _isNonceUsed[senderIDAlias][nonce];
+    }
+
+    /// Returns the id of the EVM address sender with the alias of its chain ID, or zero if the
+    /// sender is not an EVM address or its chain has no alias.
+    function _senderIDAlias(bytes32 senderID) private view returns (bytes32) {
+        // EVM address ids are laid out as `0x01 | chainID | address`.
+        if (uint8(senderID[0]) != 1) {
+            return bytes32(0);
+        }
+
+        uint32 chainID = uint32(bytes4(senderID << 8));
+        if (!_hasSenderChainIDAlias[chainID]) {
+            return bytes32(0);
+        }
+
+        bytes32 chainIDMask = bytes32(uint256(type(uint32).max) << 216);
+        bytes32 aliasChainID = bytes32(bytes4(_senderChainIDAliases[chainID])) >> 8;
+        return (senderID & ~chainIDMask) | aliasChainID;
+    }
+
     /// Function to decode and validate the order data
     function _decodeAndValidateOrder(bytes calldata encodedOrder) private view returns (MintOrderData memory order) {
         // Decode order data
@@ -242,8 +368,8 @@ contract BFTBridge is TokenManager, UUPSUpgradeable, OwnableUpgradeable, Pausabl
         // Check if amount is greater than zero
         require(order.amount > 0, "Invalid order amount");
 
-        // Check if nonce is not stored in the list
-        require(!_isNonceUsed[order.senderID][order.nonce], "Invalid nonce");
+        // Check if nonce is not stored in the list, also with the legacy sender chain ID
+        require(!isNonceUsed(order.senderID, order.nonce), "Invalid nonce");
 
         // Check if withdrawal is happening on the correct chain
         require(block.chainid == recipientChainID, "Invalid chain ID");
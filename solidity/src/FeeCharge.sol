@@ -38,6 +38,7 @@ contract FeeCharge is IFeeCharge {
         balance = _userBalance[to];
         balance += msg.value;
         _userBalance[to] = balance;
+        emit BalanceUpdated(to, balance);
     }
 
     // Withdraw the amount of native token to user's address.
@@ -50,6 +51,7 @@ contract FeeCharge is IFeeCharge {
         require(balance >= amount, "insufficient balance to withdraw");
         balance -= amount;
         _userBalance[to] = balance;
+        emit BalanceUpdated(to, balance);
         payable(to).transfer(amount);
     }
 
@@ -78,6 +80,7 @@ contract FeeCharge is IFeeCharge {
 
         uint256 newBalance = balance - amount;
         _userBalance[from] = newBalance;
+        emit BalanceUpdated(from, newBalance);
         to.transfer(amount);
     }
 
@@ -3,9 +3,11 @@
 pragma solidity ^0.8.7;
 
 import "@openzeppelin/contracts/token/ERC20/ERC20.sol";
+import "@openzeppelin/contracts/token/ERC20/extensions/ERC20Permit.sol";
 
 // Custom token contract based on ERC 20,
-contract WrappedToken is ERC20 {
+// with ERC-2612 permits, so the holders can approve burns with a signature.
+contract WrappedToken is ERC20Permit {
 
     address public immutable owner;
     string private _name;
@@ -13,7 +15,8 @@ contract WrappedToken is ERC20 {
     uint8 private _decimals;
 
     // Initializes contract with the given name and symbl
-    constructor(string memory name_, string memory symbol_, address _owner) ERC20(name_, symbol_) {
+    // The permit domain keeps the initial name after the metadata updates.
+    constructor(string memory name_, string memory symbol_, address _owner) ERC20(name_, symbol_) ERC20Permit(name_) {
         owner = _owner;
         _name = name_;
         _symbol = symbol_;
@@ -4,6 +4,9 @@ pragma solidity >=0.5.0;
 // Allows to depsit/withdraw native tokens and use the deposit to charge fee.
 interface IFeeCharge {
 
+    // Emitted when the native token deposit of the user changes.
+    event BalanceUpdated(address indexed user, uint256 balance);
+
     // Deposit `msg.value` amount of native token to user's address.
     // The deposit could be used to pay fees by the approvedSenderIDs.
     // Returns user's balance after the operation.
@@ -75,9 +75,13 @@ contract BftBridgeTest is Test {
         MintOrder memory order = _createDefaultMintOrder();
         bytes memory encodedOrder = _encodeMintOrder(order, _OWNER_KEY);
 
+        assertFalse(_bridge.isNonceUsed(order.senderID, order.nonce));
+
         _bridge.mint(encodedOrder);
 
         assertEq(WrappedToken(order.toERC20).balanceOf(order.recipient), order.amount);
+        assertTrue(_bridge.isNonceUsed(order.senderID, order.nonce));
+        assertFalse(_bridge.isNonceUsed(order.senderID, order.nonce + 1));
     }
 
     function testMintERC20FromICRC2InvalidChainID() public {
@@ -163,6 +167,33 @@ contract BftBridgeTest is Test {
         assertEq(base_token_id, _bridge.getBaseToken(wrapped_address));
     }
 
+    function testUpdateWrappedTokenMetadata() public {
+        bytes32 base_token_id = _createIdFromPrincipal(abi.encodePacked(uint8(1)));
+        address wrapped_address = _bridge.deployERC20("Token", "TKN", base_token_id);
+
+        vm.prank(_owner);
+        _bridge.updateWrappedTokenMetadata(wrapped_address, bytes32(bytes("NewToken")), bytes16(bytes("NTKN")), 9);
+
+        assertEq(WrappedToken(wrapped_address).name(), string(abi.encodePacked(bytes32(bytes("NewToken")))));
+        assertEq(WrappedToken(wrapped_address).symbol(), string(abi.encodePacked(bytes16(bytes("NTKN")))));
+        assertEq(WrappedToken(wrapped_address).decimals(), 9);
+    }
+
+    function testUpdateWrappedTokenMetadataOnlyMinter() public {
+        bytes32 base_token_id = _createIdFromPrincipal(abi.encodePacked(uint8(1)));
+        address wrapped_address = _bridge.deployERC20("Token", "TKN", base_token_id);
+
+        vm.prank(_alice);
+        vm.expectRevert("Only minter canister can update metadata");
+        _bridge.updateWrappedTokenMetadata(wrapped_address, bytes32(bytes("NewToken")), bytes16(bytes("NTKN")), 9);
+    }
+
+    function testUpdateMetadataOfUnknownToken() public {
+        vm.prank(_owner);
+        vm.expectRevert("Unknown wrapped token");
+        _bridge.updateWrappedTokenMetadata(address(42), bytes32(bytes("NewToken")), bytes16(bytes("NTKN")), 9);
+    }
+
     function testListTokenPairs() public {
         bytes32[3] memory base_token_ids = [
             _createIdFromPrincipal(abi.encodePacked(uint8(1))),
@@ -200,6 +231,122 @@ contract BftBridgeTest is Test {
         _bridge.mint(_encodeMintOrder(mintOrder, _OWNER_KEY));
     }
 
+    function testMintWithAliasedSenderChainIDUsedNonce() public {
+        uint32 legacyChainID = 0;
+        uint32 chainID = 0xFFFF0100;
+        vm.prank(_owner);
+        _bridge.setSenderChainIDAlias(chainID, legacyChainID);
+
+        MintOrder memory order = _createDefaultMintOrder();
+        order.senderID = _createIdFromAddress(_bob, legacyChainID);
+        order.senderChainID = legacyChainID;
+        _bridge.mint(_encodeMintOrder(order, _OWNER_KEY));
+
+        order.senderID = _createIdFromAddress(_bob, chainID);
+        order.senderChainID = chainID;
+        assertTrue(_bridge.isNonceUsed(order.senderID, order.nonce));
+        vm.expectRevert(bytes("Invalid nonce"));
+        _bridge.mint(_encodeMintOrder(order, _OWNER_KEY));
+
+        order.nonce = 1;
+        _bridge.mint(_encodeMintOrder(order, _OWNER_KEY));
+        assertTrue(_bridge.isNonceUsed(_createIdFromAddress(_bob, legacyChainID), 1));
+    }
+
+    function testSetSenderChainIDAliasOnlyOwner() public {
+        vm.prank(_bob);
+        vm.expectRevert();
+        _bridge.setSenderChainIDAlias(0xFFFF0100, 0);
+    }
+
+    function testBurnWithPermit() public {
+        MintOrder memory order = _createDefaultMintOrder();
+        _bridge.mint(_encodeMintOrder(order, _OWNER_KEY));
+
+        uint256 deadline = block.timestamp + 60;
+        (uint8 v, bytes32 r, bytes32 s) = _signPermit(order.toERC20, _ALICE_KEY, order.amount, deadline);
+        bytes memory burnSignature =
+            _signBurnPermit(_ALICE_KEY, order.amount, order.toERC20, abi.encodePacked(_bob), deadline);
+
+        vm.prank(_owner);
+        _bridge.burnWithPermit(
+            _alice, order.amount, order.toERC20, abi.encodePacked(_bob), deadline, v, r, s, burnSignature
+        );
+
+        assertEq(WrappedToken(order.toERC20).balanceOf(_alice), 0);
+        assertEq(WrappedToken(order.toERC20).balanceOf(address(_bridge)), order.amount);
+        assertEq(_bridge.burnPermitNonces(_alice), 1);
+    }
+
+    function testBurnWithPermitForAnotherRecipient() public {
+        MintOrder memory order = _createDefaultMintOrder();
+        _bridge.mint(_encodeMintOrder(order, _OWNER_KEY));
+
+        uint256 deadline = block.timestamp + 60;
+        (uint8 v, bytes32 r, bytes32 s) = _signPermit(order.toERC20, _ALICE_KEY, order.amount, deadline);
+        bytes memory burnSignature =
+            _signBurnPermit(_ALICE_KEY, order.amount, order.toERC20, abi.encodePacked(_bob), deadline);
+
+        vm.prank(_owner);
+        vm.expectRevert("Invalid burn permit signature");
+        _bridge.burnWithPermit(
+            _alice, order.amount, order.toERC20, abi.encodePacked(_owner), deadline, v, r, s, burnSignature
+        );
+    }
+
+    function testBurnWithFrontRunPermit() public {
+        MintOrder memory order = _createDefaultMintOrder();
+        _bridge.mint(_encodeMintOrder(order, _OWNER_KEY));
+
+        uint256 deadline = block.timestamp + 60;
+        (uint8 v, bytes32 r, bytes32 s) = _signPermit(order.toERC20, _ALICE_KEY, order.amount, deadline);
+        bytes memory burnSignature =
+            _signBurnPermit(_ALICE_KEY, order.amount, order.toERC20, abi.encodePacked(_bob), deadline);
+
+        vm.prank(_bob);
+        WrappedToken(order.toERC20).permit(_alice, address(_bridge), order.amount, deadline, v, r, s);
+
+        vm.prank(_owner);
+        _bridge.burnWithPermit(
+            _alice, order.amount, order.toERC20, abi.encodePacked(_bob), deadline, v, r, s, burnSignature
+        );
+
+        assertEq(WrappedToken(order.toERC20).balanceOf(_alice), 0);
+        assertEq(WrappedToken(order.toERC20).balanceOf(address(_bridge)), order.amount);
+    }
+
+    function testBurnWithPermitOnlyMinter() public {
+        MintOrder memory order = _createDefaultMintOrder();
+        _bridge.mint(_encodeMintOrder(order, _OWNER_KEY));
+
+        uint256 deadline = block.timestamp + 60;
+        (uint8 v, bytes32 r, bytes32 s) = _signPermit(order.toERC20, _ALICE_KEY, order.amount, deadline);
+        bytes memory burnSignature =
+            _signBurnPermit(_ALICE_KEY, order.amount, order.toERC20, abi.encodePacked(_bob), deadline);
+
+        vm.prank(_bob);
+        vm.expectRevert("Only minter canister can burn with permit");
+        _bridge.burnWithPermit(
+            _alice, order.amount, order.toERC20, abi.encodePacked(_bob), deadline, v, r, s, burnSignature
+        );
+    }
+
+    function testBurnWithInvalidPermit() public {
+        MintOrder memory order = _createDefaultMintOrder();
+        _bridge.mint(_encodeMintOrder(order, _OWNER_KEY));
+
+        uint256 deadline = block.timestamp + 60;
+        (uint8 v, bytes32 r, bytes32 s) = _signPermit(order.toERC20, _BOB_KEY, order.amount, deadline);
+        bytes memory burnSignature =
+            _signBurnPermit(_ALICE_KEY, order.amount, order.toERC20, abi.encodePacked(_bob), deadline);
+
+        vm.prank(_owner);
+        vm.expectRevert();
+        _bridge.burnWithPermit(
+            _alice, order.amount, order.toERC20, abi.encodePacked(_bob), deadline, v, r, s, burnSignature
+        );
+    }
+
     function testAddAllowedImplementation() public {
         vm.startPrank(_owner);
 
@@ -365,6 +512,38 @@ contract BftBridgeTest is Test {
         return abi.encodePacked(encodedOrder, r, s, v);
     }
 
+    function _signPermit(address token, uint256 privateKey, uint256 amount, uint256 deadline)
+        private
+        view
+        returns (uint8 v, bytes32 r, bytes32 s)
+    {
+        address holder = vm.addr(privateKey);
+        bytes32 structHash = keccak256(
+            abi.encode(
+                keccak256("Permit(address owner,address spender,uint256 value,uint256 nonce,uint256 deadline)"),
+                holder,
+                address(_bridge),
+                amount,
+                WrappedToken(token).nonces(holder),
+                deadline
+            )
+        );
+        bytes32 digest = keccak256(abi.encodePacked("\x19\x01", WrappedToken(token).DOMAIN_SEPARATOR(), structHash));
+        (v, r, s) = vm.sign(privateKey, digest);
+    }
+
+    function _signBurnPermit(
+        uint256 privateKey,
+        uint256 amount,
+        address token,
+        bytes memory recipientID,
+        uint256 deadline
+    ) private view returns (bytes memory) {
+        bytes32 digest = _bridge.burnPermitDigest(vm.addr(privateKey), amount, token, recipientID, deadline);
+        (uint8 v, bytes32 r, bytes32 s) = vm.sign(privateKey, digest);
+        return abi.encodePacked(r, s, v);
+    }
+
     function _createIdFromPrincipal(bytes memory principal) private pure returns (bytes32) {
         return bytes32(abi.encodePacked(uint8(0), uint8(principal.length), principal));
     }
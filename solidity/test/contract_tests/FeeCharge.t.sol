@@ -21,6 +21,8 @@ contract ChargeFeeTest is Test {
 
     FeeCharge _feeCharge;
 
+    event BalanceUpdated(address indexed user, uint256 balance);
+
     function setUp() public {
         address[] memory chargers = new address[](1);
         chargers[0] = _charger;
@@ -81,4 +83,18 @@ contract ChargeFeeTest is Test {
         _feeCharge.chargeFee(_alice, payable(_recepient), _bobSender1, fee);
     }
 
+    function testBalanceUpdatedEvents() public {
+        uint256 amount = 1000;
+
+        vm.expectEmit(true, false, false, true, address(_feeCharge));
+        emit BalanceUpdated(_alice, _aliceInitDeposit - amount);
+        vm.prank(_alice);
+        _feeCharge.nativeTokenWithdraw(amount);
+
+        vm.expectEmit(true, false, false, true, address(_feeCharge));
+        emit BalanceUpdated(_alice, _aliceInitDeposit - amount * 2);
+        vm.prank(_charger);
+        _feeCharge.chargeFee(_alice, payable(_recepient), _aliceSender1, amount);
+    }
+
 }
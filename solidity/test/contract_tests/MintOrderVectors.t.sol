@@ -0,0 +1,81 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.7;
+
+import "forge-std/Test.sol";
+import "@openzeppelin/contracts/utils/cryptography/ECDSA.sol";
+
+/// Checks the mint orders signed in Rust against the contract side encoding and signature
+/// recovery. The vectors are generated by `minter-contract-utils::mint_order_vectors`.
+contract MintOrderVectorsTest is Test {
+
+    string constant _VECTORS_PATH = "test/vectors/mint_orders.json";
+
+    uint256 constant _ENCODED_DATA_SIZE = 269;
+
+    string _json;
+
+    function setUp() public {
+        _json = vm.readFile(string.concat(vm.projectRoot(), "/", _VECTORS_PATH));
+    }
+
+    function testSignerMatchesKey() public view {
+        uint256 signerKey = vm.parseJsonUint(_json, ".signerKey");
+        assertEq(vm.addr(signerKey), vm.parseJsonAddress(_json, ".signer"));
+    }
+
+    function testVectorsEncodingAndSignatures() public view {
+        uint256 signerKey = vm.parseJsonUint(_json, ".signerKey");
+        address signer = vm.parseJsonAddress(_json, ".signer");
+        bytes[] memory signedOrders = vm.parseJsonBytesArray(_json, ".vectors[*].signedOrder");
+        assertGt(signedOrders.length, 0);
+
+        for (uint256 i = 0; i < signedOrders.length; i++) {
+            string memory key = string.concat(".vectors[", vm.toString(i), "]");
+            bytes memory encodedOrder = _encodeOrder(key);
+            bytes32 hash = keccak256(encodedOrder);
+            assertEq(hash, vm.parseJsonBytes32(_json, string.concat(key, ".hash")));
+
+            bytes memory signedOrder = signedOrders[i];
+            assertEq(signedOrder.length, _ENCODED_DATA_SIZE + 65);
+            assertEq(_slice(signedOrder, 0, _ENCODED_DATA_SIZE), encodedOrder);
+
+            bytes memory signature = _slice(signedOrder, _ENCODED_DATA_SIZE, signedOrder.length);
+            assertEq(ECDSA.recover(hash, signature), signer);
+
+            (uint8 v, bytes32 r, bytes32 s) = vm.sign(signerKey, hash);
+            assertEq(signedOrder, abi.encodePacked(encodedOrder, r, s, v));
+        }
+    }
+
+    function _encodeOrder(string memory key) private view returns (bytes memory) {
+        // Encoding splitted in two parts to avoid problems with stack overflow.
+        bytes memory head = abi.encodePacked(
+            vm.parseJsonUint(_json, string.concat(key, ".amount")),
+            vm.parseJsonBytes32(_json, string.concat(key, ".sender")),
+            vm.parseJsonBytes32(_json, string.concat(key, ".srcToken")),
+            vm.parseJsonAddress(_json, string.concat(key, ".recipient")),
+            vm.parseJsonAddress(_json, string.concat(key, ".dstToken")),
+            uint32(vm.parseJsonUint(_json, string.concat(key, ".nonce"))),
+            uint32(vm.parseJsonUint(_json, string.concat(key, ".senderChainId"))),
+            uint32(vm.parseJsonUint(_json, string.concat(key, ".recipientChainId")))
+        );
+        bytes memory tail = abi.encodePacked(
+            vm.parseJsonBytes32(_json, string.concat(key, ".name")),
+            bytes16(vm.parseJsonBytes(_json, string.concat(key, ".symbol"))),
+            uint8(vm.parseJsonUint(_json, string.concat(key, ".decimals"))),
+            vm.parseJsonAddress(_json, string.concat(key, ".approveSpender")),
+            vm.parseJsonUint(_json, string.concat(key, ".approveAmount")),
+            vm.parseJsonAddress(_json, string.concat(key, ".feePayer"))
+        );
+
+        return abi.encodePacked(head, tail);
+    }
+
+    function _slice(bytes memory data, uint256 start, uint256 end) private pure returns (bytes memory) {
+        bytes memory result = new bytes(end - start);
+        for (uint256 i = start; i < end; i++) {
+            result[i - start] = data[i];
+        }
+        return result;
+    }
+}
@@ -7,16 +7,21 @@ use did::constant::EIP1559_INITIAL_BASE_FEE;
 use did::{BlockNumber, Transaction, TransactionReceipt, H256, U256};
 use eth_signer::transaction::{SigningMethod, TransactionBuilder};
 use eth_signer::{Signer, Wallet};
+use ethereum_json_rpc_client::EthJsonRpcClient;
 use ethereum_types::H160;
 use ethers_core::abi::Token;
 use ethers_core::k256::ecdsa::SigningKey;
 use evm_canister_client::EvmCanisterClient;
 use ic_canister_client::IcAgentClient;
+use minter_contract_utils::bft_bridge_api::BridgeEvent;
+use minter_contract_utils::btc_address::parse_btc_address;
 use minter_contract_utils::build_data::{
     BFT_BRIDGE_SMART_CONTRACT_CODE, FEE_CHARGE_SMART_CONTRACT_CODE, UUPS_PROXY_SMART_CONTRACT_CODE,
 };
+use minter_contract_utils::mint_order_codec::decode_signed_mint_order;
 use minter_contract_utils::{bft_bridge_api, fee_charge_api, wrapped_token_api};
 use minter_did::id256::Id256;
+use minter_did::order::MintOrder;
 use minter_did::reason::Icrc2Burn;
 use tokio::time::Instant;
 
@@ -46,6 +51,66 @@ enum CliCommand {
     DepositIcrc(DepositIcrcArgs),
     /// Get wallet nonce
     GetNonce(GetNonceArgs),
+    /// Decode a signed mint order and print its fields.
+    DecodeMintOrder(DecodeMintOrderArgs),
+    /// Send a signed mint order to the BFT bridge `mint()`, e.g. if the minter signed the order
+    /// but failed to send it.
+    SubmitMintOrder(SubmitMintOrderArgs),
+    /// Poll the EVM for the BFT bridge events and print them as JSON lines.
+    WatchEvents(WatchEventsArgs),
+}
+
+#[derive(Debug, Parser)]
+struct WatchEventsArgs {
+    /// Principal of the EVM canister.
+    #[arg(long)]
+    evm_canister: Principal,
+
+    /// ETH address of the BFT bridge contract.
+    #[arg(long)]
+    bridge: String,
+
+    /// Block to start watching from. If not set, only the events of new blocks are printed.
+    #[arg(long)]
+    from_block: Option<u64>,
+
+    /// Interval between the EVM polls in seconds.
+    #[arg(long, default_value_t = 2)]
+    poll_interval_secs: u64,
+
+    /// IC host
+    #[arg(long)]
+    ic_host: Option<String>,
+}
+
+#[derive(Debug, Parser)]
+struct DecodeMintOrderArgs {
+    /// Hex-encoded signed mint order.
+    #[arg(long)]
+    order: String,
+}
+
+#[derive(Debug, Parser)]
+struct SubmitMintOrderArgs {
+    /// Hex-encoded signed mint order.
+    #[arg(long)]
+    order: String,
+
+    /// Principal of the EVM canister.
+    #[arg(long)]
+    evm_canister: Principal,
+
+    /// ETH address of the BFT bridge contract.
+    #[arg(long)]
+    bft_bridge: String,
+
+    /// IC host
+    #[arg(long)]
+    ic_host: Option<String>,
+
+    /// Hex-encoded PK to use to sign transaction. If not set, a random wallet will be created.
+    #[arg(long)]
+    wallet: Option<String>,
 }
 
 #[derive(Debug, Parser)]
@@ -227,6 +292,11 @@ struct BurnWrappedArgs {
     #[arg(long)]
     address: String,
 
+    /// BTC network of the bridge (`bitcoin`, `testnet`, `signet` or `regtest`). If set, the
+    /// address is checked to belong to this network before the tokens are burnt.
+    #[arg(long)]
+    btc_network: Option<bitcoin::Network>,
+
     /// Amount to transfer.
     #[arg(long)]
     amount: u128,
@@ -255,6 +325,9 @@ async fn main() {
         CliCommand::ExpectedContractAddress(args) => expected_contract_address(args),
         CliCommand::DepositIcrc(args) => deposit_icrc(args).await,
         CliCommand::GetNonce(args) => get_nonce(args).await,
+        CliCommand::DecodeMintOrder(args) => decode_mint_order(args),
+        CliCommand::SubmitMintOrder(args) => submit_mint_order(args).await,
+        CliCommand::WatchEvents(args) => watch_events(args).await,
     }
 }
 
@@ -714,6 +787,11 @@ fn wallet_address(args: WalletAddressArgs) {
 }
 
 async fn burn_wrapped(args: BurnWrappedArgs) {
+    if let Some(network) = args.btc_network {
+        parse_btc_address(&args.address, network)
+            .unwrap_or_else(|err| panic!("Invalid BTC address: {err}"));
+    }
+
     let client = EvmCanisterClient::new(
         IcAgentClient::with_identity(
             args.evm_canister,
@@ -824,6 +902,173 @@ async fn burn_wrapped(args: BurnWrappedArgs) {
     wait_for_tx_success(&client, hash).await;
 }
 
+/// Decodes the hex-encoded signed mint order, returning its bytes and the decoded order.
+fn parse_signed_mint_order(order_hex: &str) -> (Vec<u8>, MintOrder) {
+    let bytes =
+        hex::decode(order_hex.trim_start_matches("0x")).expect("invalid hex string for mint order");
+    let order =
+        decode_signed_mint_order(&bytes).unwrap_or_else(|err| panic!("Invalid mint order: {err}"));
+    (bytes, order)
+}
+
+fn print_mint_order(order: &MintOrder) {
+    let fixed_str = |bytes: &[u8]| {
+        let len = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+        String::from_utf8_lossy(&bytes[..len]).into_owned()
+    };
+
+    println!("amount: {}", order.amount.0);
+    println!("sender: 0x{}", hex::encode(order.sender.0));
+    println!("src_token: 0x{}", hex::encode(order.src_token.0));
+    println!("recipient: {:#x}", order.recipient.0);
+    println!("dst_token: {:#x}", order.dst_token.0);
+    println!("nonce: {}", order.nonce);
+    println!("sender_chain_id: {}", order.sender_chain_id);
+    println!("recipient_chain_id: {}", order.recipient_chain_id);
+    println!("name: {}", fixed_str(&order.name));
+    println!("symbol: {}", fixed_str(&order.symbol));
+    println!("decimals: {}", order.decimals);
+    println!("approve_spender: {:#x}", order.approve_spender.0);
+    println!("approve_amount: {}", order.approve_amount.0);
+    println!("fee_payer: {:#x}", order.fee_payer.0);
+}
+
+fn decode_mint_order(args: DecodeMintOrderArgs) {
+    let (_, order) = parse_signed_mint_order(&args.order);
+    print_mint_order(&order);
+}
+
+async fn submit_mint_order(args: SubmitMintOrderArgs) {
+    let (encoded_order, order) = parse_signed_mint_order(&args.order);
+    print_mint_order(&order);
+
+    let bft_bridge = H160::from_slice(
+        &hex::decode(args.bft_bridge.trim_start_matches("0x"))
+            .expect("failed to parse bft bridge address"),
+    );
+
+    let host = args.ic_host.as_deref().unwrap_or("http://127.0.0.1:4943");
+    let client = EvmCanisterClient::new(
+        IcAgentClient::with_identity(args.evm_canister, IDENTITY_PATH, host, None)
+            .await
+            .expect("Failed to create client"),
+    );
+
+    let wallet = get_wallet(&args.wallet, &client).await;
+    let chain_id = client.eth_chain_id().await.expect("failed to get chain id");
+    if u64::from(order.recipient_chain_id) != chain_id {
+        panic!(
+            "Mint order is for chain {}, but the EVM chain id is {chain_id}",
+            order.recipient_chain_id
+        );
+    }
+
+    let input = bft_bridge_api::MINT
+        .encode_input(&[Token::Bytes(encoded_order)])
+        .unwrap();
+
+    let nonce = client
+        .account_basic(wallet.address().into())
+        .await
+        .expect("Failed to get account info.")
+        .nonce;
+    let mint_tx = TransactionBuilder {
+        from: &wallet.address().into(),
+        to: Some(bft_bridge.into()),
+        nonce,
+        value: 0u64.into(),
+        gas: 5_000_000u64.into(),
+        gas_price: Some((EIP1559_INITIAL_BASE_FEE * 2).into()),
+        input,
+        signature: SigningMethod::SigningKey(wallet.signer()),
+        chain_id,
+    }
+    .calculate_hash_and_build()
+    .expect("failed to sign the transaction");
+
+    let hash = client
+        .send_raw_transaction(mint_tx)
+        .await
+        .expect("Failed to send raw transaction")
+        .expect("Failed to execute mint transaction");
+    wait_for_tx_success(&client, hash.clone()).await;
+
+    eprintln!("Mint order submitted");
+    println!("{hash}");
+}
+
+async fn watch_events(args: WatchEventsArgs) {
+    let bridge = H160::from_slice(
+        &hex::decode(args.bridge.trim_start_matches("0x"))
+            .expect("failed to parse bft bridge address"),
+    );
+
+    let host = args.ic_host.as_deref().unwrap_or("http://127.0.0.1:4943");
+    let client = EthJsonRpcClient::new(
+        IcAgentClient::with_identity(args.evm_canister, IDENTITY_PATH, host, None)
+            .await
+            .expect("Failed to create client"),
+    );
+
+    let mut next_block = match args.from_block {
+        Some(block) => block,
+        None => {
+            client
+                .get_block_number()
+                .await
+                .expect("Failed to get block number")
+                + 1
+        }
+    };
+    eprintln!("Watching events of {bridge:#x} from block {next_block}");
+
+    let poll_interval = Duration::from_secs(args.poll_interval_secs);
+    loop {
+        let last_block = match client.get_block_number().await {
+            Ok(block) => block,
+            Err(err) => {
+                eprintln!("Failed to get block number: {err:?}");
+                tokio::time::sleep(poll_interval).await;
+                continue;
+            }
+        };
+
+        if last_block >= next_block {
+            match BridgeEvent::collect_logs(&client, next_block, last_block, bridge).await {
+                Ok(logs) => {
+                    for log in logs {
+                        print_event_log(log);
+                    }
+                    next_block = last_block + 1;
+                }
+                Err(err) => eprintln!("Failed to collect logs: {err:?}"),
+            }
+        }
+
+        tokio::time::sleep(poll_interval).await;
+    }
+}
+
+fn print_event_log(log: ethers_core::types::Log) {
+    let block_number = log.block_number.map(|number| number.as_u64());
+    let transaction_hash = log.transaction_hash.map(|hash| format!("{hash:#x}"));
+
+    let event = match BridgeEvent::from_log(log) {
+        Ok(event) => event,
+        Err(err) => {
+            eprintln!("Failed to decode log of transaction {transaction_hash:?}: {err}");
+            return;
+        }
+    };
+
+    let line = serde_json::json!({
+        "block_number": block_number,
+        "transaction_hash": transaction_hash,
+        "event": event,
+    });
+    println!("{line}");
+}
+
 fn decode_token_id(id_string: &str) -> Option<Id256> {
     if let Ok(hex) = hex::decode(id_string) {
         if hex.len() == 32 {
@@ -17,6 +17,8 @@ struct BurnRequestInfo {
     address: String,
     amount: u64,
     is_transferred: bool,
+    /// Reason of the failure of the request, if it can't be completed.
+    failure: Option<String>,
 }
 
 impl Storable for BurnRequestInfo {
@@ -47,6 +49,26 @@ impl BurnRequestStore {
                 address,
                 amount,
                 is_transferred: false,
+                failure: None,
+            },
+        );
+    }
+
+    /// Records the request which can't be completed, e.g. because of an invalid recipient.
+    pub fn set_failed(
+        &mut self,
+        request_id: BurnRequestId,
+        address: String,
+        amount: u64,
+        failure: String,
+    ) {
+        self.inner.insert(
+            request_id,
+            BurnRequestInfo {
+                address,
+                amount,
+                is_transferred: false,
+                failure: Some(failure),
             },
         );
     }
@@ -1,26 +1,59 @@
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::time::Duration;
 
 use candid::Principal;
-use did::H160;
-use eth_signer::sign_strategy::TransactionSigner;
+use did::build::BuildData;
+use did::{H160, H256};
+use eth_signer::sign_strategy::{SigningStrategy, TransactionSigner};
 use ic_canister::{
     generate_idl, init, post_upgrade, query, update, virtual_canister_call, Canister, Idl,
     PreUpdate,
 };
 use ic_ckbtc_minter::updates::get_btc_address::GetBtcAddressArgs;
 use ic_exports::ic_kit::ic;
-use ic_exports::ledger::Subaccount;
+use ic_exports::icrc_types::icrc1::account::Account as IcrcAccount;
 use ic_metrics::{Metrics, MetricsStorage};
-use ic_stable_structures::CellStructure;
+use ic_stable_structures::stable_structures::DefaultMemoryImpl;
+use ic_stable_structures::{BTreeMapStructure, CellStructure, VirtualMemory};
 use ic_task_scheduler::retry::BackoffPolicy;
 use ic_task_scheduler::scheduler::TaskScheduler;
 use ic_task_scheduler::task::{InnerScheduledTask, ScheduledTask, TaskOptions, TaskStatus};
+use ic_task_scheduler::SchedulerError;
+use minter_contract_utils::account_mapping::eth_address_to_subaccount;
+use minter_contract_utils::admin_approvals::{
+    ApprovalConfig, ApprovalError, ApprovalStore, Proposal,
+};
+use minter_contract_utils::bft_bridge_deploy::BftBridgeDeployStatus;
+use minter_contract_utils::bridge_tx_log::{BridgeTxLog, GetBridgeTransactionsResponse};
+use minter_contract_utils::burn_notification::{self, NotifiedEvents, NotifyBurnError};
+use minter_contract_utils::burn_permit::{BurnPermit, BurnPermitError};
+use minter_contract_utils::canister_status::{stable_structures_usage, CanisterStatusInfo};
+use minter_contract_utils::certified_data::{Certified, CONFIG_LABEL};
+use minter_contract_utils::config_guard::{ConfigGuard, Reconfiguration};
+use minter_contract_utils::config_validation::{format_config_errors, ConfigError};
+use minter_contract_utils::confirmation_policy::ConfirmationPolicy;
+use minter_contract_utils::event_subscribers::EventSubscribers;
+use minter_contract_utils::gas_limits::GasLimits;
+use minter_contract_utils::health::HealthReport;
+use minter_contract_utils::mint_completion::DepositStatus;
+use minter_contract_utils::pagination::{CursorPage, CursorRequest, InvalidCursor};
+use minter_contract_utils::soft_caps::{Lane, QueuePosition, SoftCap, SoftCapStore};
+use minter_contract_utils::task_limits::{is_deferral_error, TaskLimits, DEFERRED_TASK_DELAY_SECS};
+use minter_did::id256::Id256;
+use minter_did::order::SignedMintOrder;
 
-use crate::interface::{Erc20MintError, Erc20MintStatus};
-use crate::memory::{MEMORY_MANAGER, PENDING_TASKS_MEMORY_ID};
+use crate::ck_btc_interface::{RetrieveBtcError, UpdateBalanceError};
+use crate::interface::{AdminOperation, DepositAccount, Erc20MintError, Erc20MintStatus};
+use crate::memory::{
+    APPROVAL_PROPOSALS_MEMORY_ID, APPROVAL_SETTINGS_MEMORY_ID, BRIDGE_TX_LOG_MEMORY_ID,
+    CONFIG_REVISION_MEMORY_ID, EVENT_SUBSCRIBERS_MEMORY_ID, MEMORY_MANAGER,
+    NOTIFIED_EVENTS_MEMORY_ID, PENDING_TASKS_MEMORY_ID, SOFT_CAP_LANES_MEMORY_ID,
+    SOFT_CAP_QUEUE_MEMORY_ID, STABLE_STRUCTURES,
+};
 use crate::scheduler::{BtcTask, PersistentScheduler, TasksStorage};
-use crate::state::{BftBridgeConfig, BtcBridgeConfig, State};
+use crate::state::{BftBridgeConfig, BridgeConfigInfo, BtcBridgeConfig, State};
+use crate::withdrawal_fee::WithdrawalFeeEstimate;
 use crate::{
     EVM_INFO_INITIALIZATION_RETRIES, EVM_INFO_INITIALIZATION_RETRY_DELAY_SEC,
     EVM_INFO_INITIALIZATION_RETRY_MULTIPLIER,
@@ -38,16 +71,16 @@ impl BtcBridge {
     fn set_timers(&mut self) {
         #[cfg(target_family = "wasm")]
         {
-            use std::time::Duration;
             const METRICS_UPDATE_INTERVAL_SEC: u64 = 60 * 60;
 
             self.update_metrics_timer(std::time::Duration::from_secs(METRICS_UPDATE_INTERVAL_SEC));
 
             const GLOBAL_TIMER_INTERVAL: Duration = Duration::from_secs(1);
             ic_exports::ic_cdk_timers::set_timer_interval(GLOBAL_TIMER_INTERVAL, move || {
-                get_scheduler()
-                    .borrow_mut()
-                    .append_task(Self::collect_evm_events_task());
+                BtcTask::CollectEvmEvents.append_unique(
+                    &*get_scheduler().borrow(),
+                    Self::collect_evm_events_options(),
+                );
 
                 let task_execution_result = get_scheduler().borrow_mut().run();
 
@@ -55,22 +88,71 @@ impl BtcBridge {
                     log::error!("task execution failed: {err}",);
                 }
             });
+
+            ic_exports::ic_cdk_timers::set_timer_interval(
+                minter_contract_utils::health::HEALTH_CHECK_INTERVAL,
+                || {
+                    BtcTask::CheckHealth
+                        .append_unique(&*get_scheduler().borrow(), TaskOptions::default());
+
+                    // The deposits and refunds left pending by a failed call are retried here.
+                    let has_pending = {
+                        let state = get_state();
+                        let state = state.borrow();
+                        !state.pending_ckbtc_deposits().is_empty()
+                            || !state.pending_ckbtc_refunds().is_empty()
+                    };
+                    if has_pending {
+                        BtcTask::RetryCkBtcDeposits.append_unique(
+                            &*get_scheduler().borrow(),
+                            BtcTask::retry_ckbtc_deposits_options(),
+                        );
+                    }
+                },
+            );
+
+            ic_exports::ic_cdk_timers::set_timer_interval(
+                minter_contract_utils::gas_price::GAS_PRICE_REFRESH_INTERVAL,
+                || {
+                    BtcTask::RefreshGasPrice
+                        .append_unique(&*get_scheduler().borrow(), TaskOptions::default());
+                },
+            );
+
+            ic_exports::ic_cdk_timers::set_timer_interval(
+                minter_contract_utils::soft_caps::SOFT_CAP_DRAIN_INTERVAL,
+                || {
+                    BtcTask::DrainSoftCapQueue
+                        .append_unique(&*get_scheduler().borrow(), TaskOptions::default());
+                },
+            );
         }
     }
 
+    /// Configures the bridge. Panics if the bridge is configured already, e.g. when it is
+    /// installed over the stable memory of a configured bridge, unless `force` is set.
     #[init]
-    pub fn init(&mut self, config: BtcBridgeConfig) {
+    pub fn init(&mut self, config: BtcBridgeConfig, force: Option<bool>) {
         let admin = config.admin;
 
         Self::check_anonymous_principal(admin).expect("admin principal is anonymous");
 
-        get_state().borrow_mut().configure(config);
+        let mut config_guard = get_config_guard();
+        if let Err(err) = config_guard.check_init(force.unwrap_or_default()) {
+            panic!("{err}");
+        }
+
+        if let Err(err) = get_state().borrow_mut().configure(config.clone()) {
+            panic!("{err}");
+        }
+        config_guard.record(&config);
 
         {
             let scheduler = get_scheduler();
             let mut borrowed_scheduler = scheduler.borrow_mut();
-            borrowed_scheduler.on_completion_callback(log_task_execution_error);
-            borrowed_scheduler.append_task(Self::init_evm_info_task());
+            borrowed_scheduler.on_completion_callback(on_task_completed);
+            BtcTask::InitEvmState
+                .append_unique(&*borrowed_scheduler, Self::init_evm_info_options());
         }
 
         self.set_timers();
@@ -78,6 +160,15 @@ impl BtcBridge {
 
     #[post_upgrade]
     pub fn post_upgrade(&mut self) {
+        get_scheduler()
+            .borrow_mut()
+            .on_completion_callback(on_task_completed);
+        get_state().borrow_mut().reload_config();
+        get_state()
+            .borrow_mut()
+            .mint_orders_mut()
+            .migrate_legacy_orders();
+        get_bridge_tx_log().certify_tip();
         self.set_timers();
     }
 
@@ -98,12 +189,11 @@ impl BtcBridge {
     /// Here is a sample Rust code:
     ///
     /// ```ignore
-    /// let mut caller_subaccount = [0; 32];
-    /// caller_subaccount[0..caller_eth_address.0.0.len()].copy_from_slice(caller_eth_address.0.as_bytes());
+    /// use minter_contract_utils::account_mapping::eth_address_to_subaccount;
     ///
     /// let argument = Account {
     ///   owner: btc_bridge_canister_principal,
-    ///   subaccount: Some(caller_subaccount),
+    ///   subaccount: Some(eth_address_to_subaccount(&caller_eth_address)),
     /// }
     /// ```
     ///
@@ -123,14 +213,29 @@ impl BtcBridge {
         crate::ops::btc_to_erc20(get_state(), eth_address).await
     }
 
-    fn init_evm_info_task() -> ScheduledTask<BtcTask> {
-        let init_options = TaskOptions::default()
+    /// Converts ckBTC of the caller to wrapped tokens at the `eth_address` without a BTC transfer.
+    ///
+    /// The caller must approve the `amount` of ckBTC for the BtcBridge canister with the
+    /// `icrc2_approve` method of the ckBTC ledger first. The ledger fee is subtracted from the
+    /// `amount`, as for the BTC deposits, and the rest is minted as wrapped tokens. If the mint
+    /// order cannot be signed after the ckBTC are transferred, the bridge keeps signing it in the
+    /// background, and the order can be found with `list_mint_orders` of the caller.
+    #[update]
+    pub async fn deposit_ckbtc(
+        &self,
+        amount: u64,
+        eth_address: H160,
+    ) -> Result<Erc20MintStatus, Erc20MintError> {
+        crate::ops::deposit_ckbtc(&get_state(), ic::caller(), eth_address, amount).await
+    }
+
+    fn init_evm_info_options() -> TaskOptions {
+        TaskOptions::default()
             .with_max_retries_policy(EVM_INFO_INITIALIZATION_RETRIES)
             .with_backoff_policy(BackoffPolicy::Exponential {
                 secs: EVM_INFO_INITIALIZATION_RETRY_DELAY_SEC,
                 multiplier: EVM_INFO_INITIALIZATION_RETRY_MULTIPLIER,
-            });
-        BtcTask::InitEvmState.into_scheduled(init_options)
+            })
     }
 
     /// Returns bridge contract address for EVM.
@@ -153,31 +258,532 @@ impl BtcBridge {
         }
     }
 
+    /// Same as `get_btc_address_checked`, but traps if the ckBTC minter cannot be reached. Kept
+    /// for the clients of the original interface.
     #[update]
     pub async fn get_btc_address(&self, args: GetBtcAddressArgs) -> String {
+        self.get_btc_address_checked(args)
+            .await
+            .expect("failed to get btc address")
+    }
+
+    #[update]
+    pub async fn get_btc_address_checked(
+        &self,
+        args: GetBtcAddressArgs,
+    ) -> Result<String, Erc20MintError> {
         let ck_btc_minter = get_state().borrow().ck_btc_minter();
         virtual_canister_call!(ck_btc_minter, "get_btc_address", (args,), String)
             .await
-            .unwrap()
+            .map_err(|err| {
+                Erc20MintError::CkBtcMinter(UpdateBalanceError::TemporarilyUnavailable(format!(
+                    "Failed to connect to ckBTC minter: {err:?}"
+                )))
+            })
+    }
+
+    /// Returns the ckBTC account the BTC deposits for the `eth_address` are sent to, and its BTC
+    /// address if it was already received from the ckBTC minter. The address is received and
+    /// cached on the first `request_deposit_account` or `btc_to_erc20` call for the `eth_address`.
+    #[query]
+    pub fn get_deposit_account(&self, eth_address: H160) -> DepositAccount {
+        DepositAccount {
+            account: Self::deposit_account(&eth_address),
+            btc_address: get_state().borrow().deposit_addresses().get(&eth_address),
+        }
+    }
+
+    /// Same as `get_deposit_account`, but requests the BTC address from the ckBTC minter if it is
+    /// not cached yet.
+    #[update]
+    pub async fn request_deposit_account(
+        &self,
+        eth_address: H160,
+    ) -> Result<DepositAccount, Erc20MintError> {
+        let state = get_state();
+        let ck_btc_minter = state.borrow().ck_btc_minter();
+        let btc_address =
+            crate::ops::get_deposit_btc_address(&state, ck_btc_minter, &eth_address).await?;
+
+        Ok(DepositAccount {
+            account: Self::deposit_account(&eth_address),
+            btc_address: Some(btc_address),
+        })
+    }
+
+    fn deposit_account(eth_address: &H160) -> IcrcAccount {
+        IcrcAccount {
+            owner: ic::id(),
+            subaccount: Some(eth_address_to_subaccount(eth_address)),
+        }
+    }
+
+    /// Returns the progress of the deposit whose mint order was issued by the `sender` with the
+    /// `nonce`, as given in the signed mint order. The deposit is `Completed` once the `Minted`
+    /// event of the order is collected from the EVM, whether the order was sent by the bridge or
+    /// by the user.
+    /// Burns the wrapped tokens approved with an ERC-2612 `permit` of their holder, so the
+    /// holder doesn't need to send the approve and burn transactions. The holder also signs the
+    /// `burnPermitDigest` of the BftBridge, which binds the burn to its `recipient_id`. The burn is
+    /// then processed as any other burn of the BftBridge.
+    #[update]
+    pub async fn burn_with_permit(&mut self, permit: BurnPermit) -> Result<H256, BurnPermitError> {
+        crate::ops::burn_with_permit(&get_state(), permit).await
+    }
+
+    /// Schedules the BTC transfers of the `Burnt` events of the transaction without waiting for
+    /// the next scan of the BftBridge logs. The events collected by the scan already are rejected
+    /// as `AlreadyProcessed`.
+    #[update]
+    pub async fn notify_burn(&mut self, tx_hash: H256) -> Result<(), NotifyBurnError> {
+        let evm_info = get_state().borrow().get_evm_info();
+        if evm_info.params.is_none() {
+            return Err(NotifyBurnError::NotInitialized);
+        }
+
+        let client = evm_info.link.get_json_rpc_client();
+        let logs =
+            burn_notification::burnt_logs(&client, &tx_hash, &evm_info.bridge_contract).await?;
+
+        // The scan may have passed the blocks of the logs while the receipt was fetched.
+        let next_block = get_state()
+            .borrow()
+            .get_evm_params()
+            .as_ref()
+            .map(|params| params.next_block)
+            .ok_or(NotifyBurnError::NotInitialized)?;
+        let logs =
+            get_notified_events().accept(logs, next_block, get_state().borrow().clock().now())?;
+
+        let tasks = logs.into_iter().filter_map(BtcTask::task_by_log).collect();
+        get_scheduler().borrow_mut().append_tasks(tasks);
+
+        Ok(())
+    }
+
+    /// Estimates the fees of a withdrawal of the burned `amount` of wrapped tokens, or of a
+    /// typical withdrawal if the amount is not given: the ckBTC ledger fee taken by the bridge and
+    /// the fees of the ckBTC minter.
+    #[update]
+    pub async fn estimate_withdrawal_fee(
+        &self,
+        amount: Option<u64>,
+    ) -> Result<WithdrawalFeeEstimate, RetrieveBtcError> {
+        crate::ops::estimate_withdrawal_fee(&get_state(), amount).await
+    }
+
+    /// Minimum amount of wrapped tokens a burn must have to be withdrawn: the
+    /// `retrieve_btc_min_amount` of the ckBTC minter and the ckBTC ledger fee.
+    #[update]
+    pub async fn retrieve_btc_min_amount(&self) -> Result<u64, RetrieveBtcError> {
+        crate::ops::min_burn_amount(&get_state()).await
+    }
+
+    /// Estimate of a typical withdrawal from the cached ckBTC minter fees, if there are any. The
+    /// estimate may be stale, see its `fetched_at`.
+    #[query]
+    pub fn get_cached_withdrawal_fee(&self) -> Option<WithdrawalFeeEstimate> {
+        let state = get_state();
+        let state = state.borrow();
+        state
+            .withdrawal_fee_cache()
+            .last_estimate(state.ck_btc_ledger_fee())
+    }
+
+    #[query]
+    pub fn get_deposit_status(&self, sender: Id256, nonce: u32) -> Option<DepositStatus> {
+        get_state().borrow().deposit_statuses().get(&sender, nonce)
+    }
+
+    /// Returns the number of BTC confirmations required for deposits depending on their size,
+    /// additionally to the confirmations required by the ckBTC minter.
+    /// Returns `(nonce, mint_order)` pairs of the signed mint orders of the sender, which are not
+    /// minted yet. The sender is the id of the principal for the ckBTC deposits, or of the EVM
+    /// address for the BTC deposits.
+    #[query]
+    pub fn list_mint_orders(&self, sender: Id256) -> Vec<(u32, SignedMintOrder)> {
+        get_state().borrow().mint_orders().get_all(sender)
+    }
+
+    /// Same as `list_mint_orders`, but returns only the requested page of the list.
+    #[query]
+    pub fn list_mint_orders_by_cursor(
+        &self,
+        sender: Id256,
+        request: Option<CursorRequest>,
+    ) -> Result<CursorPage<(u32, SignedMintOrder)>, InvalidCursor> {
+        get_state()
+            .borrow()
+            .mint_orders()
+            .page(sender, &request.unwrap_or_default())
+    }
+
+    #[query]
+    pub fn get_mint_order(&self, sender: Id256, nonce: u32) -> Option<SignedMintOrder> {
+        get_state().borrow().mint_orders().get(sender, nonce)
+    }
+
+    #[query]
+    pub fn get_confirmation_policy(&self) -> ConfirmationPolicy {
+        get_state().borrow().confirmation_policy()
+    }
+
+    /// Returns the configuration of the bridge. The response is certified, see `certified_data`.
+    #[query]
+    pub fn get_bridge_config(&self) -> Certified<BridgeConfigInfo> {
+        Certified::new(CONFIG_LABEL, get_state().borrow().config_info())
+    }
+
+    /// Returns the results of the latest checks of the EVM RPC, ckBTC minter and signer. The
+    /// checks are performed periodically, so the dependencies are reported as `Unknown` until
+    /// the first check after the canister installation or upgrade.
+    #[query]
+    pub fn health(&self) -> HealthReport {
+        get_state().borrow().health.report()
+    }
+
+    /// Returns the cycles balance, the heap and stable memory usage and the number of the
+    /// scheduled tasks of the canister.
+    #[query]
+    pub fn canister_status_info(&self) -> CanisterStatusInfo {
+        MEMORY_MANAGER.with(|mm| {
+            let stable_structures = stable_structures_usage(STABLE_STRUCTURES, |id| mm.get(id));
+            let scheduled_tasks = TasksStorage::new(mm.get(PENDING_TASKS_MEMORY_ID)).len();
+            CanisterStatusInfo::collect(stable_structures, scheduled_tasks)
+        })
+    }
+
+    /// Returns the version, the git commit, the build timestamp and the enabled features of the
+    /// canister build.
+    #[query]
+    pub fn get_build_data(&self) -> BuildData {
+        minter_contract_utils::canister_build_data!()
+    }
+
+    /// Checks the configuration with the same rules as the canister init, returning all the
+    /// errors found. The list is empty if the configuration is valid.
+    #[query]
+    pub fn validate_config(&self, config: BtcBridgeConfig) -> Vec<ConfigError> {
+        config.validate().err().unwrap_or_default()
+    }
+
+    /// Dry-runs the configuration of the bridge: checks it with the init rules and against the
+    /// EVM of its link without applying it. Returns all the errors found, or an empty list.
+    ///
+    /// The EVM checks need outcalls, so this is an update, but it doesn't change the state.
+    #[update]
+    pub async fn admin_validate_bridge_config(
+        &self,
+        config: BtcBridgeConfig,
+    ) -> minter_did::error::Result<Vec<ConfigError>> {
+        get_state().borrow().check_admin(ic::caller())?;
+        let bft_config = get_state().borrow().bft_config.clone();
+        Ok(config
+            .validate_with_evm(&bft_config)
+            .await
+            .err()
+            .unwrap_or_default())
     }
 
+    /// Dry-runs `admin_configure_bft_bridge`: checks the chain id and the contracts of the
+    /// configuration against the EVM of the bridge without applying it. Returns all the errors
+    /// found, or an empty list.
     #[update]
-    pub fn admin_configure_bft_bridge(&self, config: BftBridgeConfig) {
-        get_state().borrow().check_admin(ic::caller());
-        get_state().borrow_mut().configure_bft(config);
+    pub async fn admin_validate_bft_config(
+        &self,
+        config: BftBridgeConfig,
+    ) -> minter_did::error::Result<Vec<ConfigError>> {
+        get_state().borrow().check_admin(ic::caller())?;
+        Ok(validate_bft_config(&config).await.err().unwrap_or_default())
+    }
+
+    /// Replaces the configuration of the bridge. The `nonce` is the one returned by
+    /// `get_config_nonce`, so a change based on an outdated configuration is rejected. Retrying
+    /// an applied change with the same nonce and configuration succeeds without changes.
+    ///
+    /// Returns the nonce of the next reconfiguration. The signing strategy and the log settings
+    /// of the `config` are ignored, they are changed with their own endpoints.
+    ///
+    /// With the approvals enabled, the configuration is changed with an approved proposal.
+    #[update]
+    pub fn admin_reconfigure(
+        &self,
+        config: BtcBridgeConfig,
+        nonce: u64,
+    ) -> minter_did::error::Result<u64> {
+        check_direct_admin(ic::caller())?;
+        reconfigure(config, nonce)
+    }
+
+    /// Nonce the next `admin_reconfigure` call must be made with.
+    #[query]
+    pub fn get_config_nonce(&self) -> u64 {
+        get_config_guard().nonce()
+    }
+
+    /// Sets the BTC volume in satoshis deposited or withdrawn per hour. The deposits and
+    /// withdrawals beyond it are queued instead of rejected. `None` removes the cap, and the
+    /// queued operations are processed with the next drain of the queue.
+    #[update]
+    pub fn admin_set_soft_cap(
+        &self,
+        lane: Lane,
+        cap_per_hour: Option<u128>,
+    ) -> minter_did::error::Result<()> {
+        check_direct_admin(ic::caller())?;
+        set_soft_cap(lane, cap_per_hour)
+    }
+
+    #[query]
+    pub fn get_soft_caps(&self) -> Vec<SoftCap> {
+        get_soft_cap_store().caps(get_state().borrow().clock().now())
+    }
+
+    /// Returns the position of the deposit of the `eth_address` with the `nonce` in the soft cap
+    /// queue, or `None` if it is not queued.
+    #[query]
+    pub fn get_deposit_queue_position(
+        &self,
+        eth_address: H160,
+        nonce: u32,
+    ) -> Option<QueuePosition> {
+        get_soft_cap_store().position(
+            &crate::ops::deposit_ticket(&eth_address, nonce),
+            get_state().borrow().clock().now(),
+        )
+    }
+
+    /// Returns the position of the withdrawal of the burn with the `operation_id` in the soft cap
+    /// queue, or `None` if it is not queued.
+    #[query]
+    pub fn get_withdrawal_queue_position(&self, operation_id: u32) -> Option<QueuePosition> {
+        get_soft_cap_store().position(
+            &crate::ops::withdrawal_ticket(operation_id),
+            get_state().borrow().clock().now(),
+        )
+    }
+
+    /// Sets the BftBridge configuration after checking it against the EVM: the contracts must be
+    /// deployed, the BftBridge must mint with the EVM address of the canister, and the token must
+    /// be deployed by the BftBridge or have the metadata of the configuration.
+    #[update]
+    pub async fn admin_configure_bft_bridge(
+        &self,
+        config: BftBridgeConfig,
+    ) -> minter_did::error::Result<()> {
+        check_direct_admin(ic::caller())?;
+        configure_bft_bridge(config).await
+    }
+
+    /// Deploys the BftBridge contract with the EVM address of the canister as the minter. The
+    /// contract becomes the bridge contract once `admin_check_bft_bridge_deployment` finds it
+    /// deployed.
+    #[update]
+    pub async fn admin_deploy_bft_bridge(
+        &mut self,
+        fee_charge: H160,
+    ) -> minter_did::error::Result<BftBridgeDeployStatus> {
+        check_direct_admin(ic::caller())?;
+        crate::ops::deploy_bft_bridge(&get_state(), fee_charge).await
+    }
+
+    #[update]
+    pub async fn admin_check_bft_bridge_deployment(
+        &mut self,
+    ) -> minter_did::error::Result<BftBridgeDeployStatus> {
+        get_state().borrow().check_admin(ic::caller())?;
+        crate::ops::check_bft_bridge_deployment(&get_state()).await
+    }
+
+    #[query]
+    pub fn get_bft_bridge_deploy_status(&self) -> Option<BftBridgeDeployStatus> {
+        get_state().borrow().bft_deploy_status.clone()
+    }
+
+    /// Replaces the signing strategy of the bridge. The signed mint orders that are not minted
+    /// yet remain valid only while the BftBridge accepts the signatures of the previous key.
+    ///
+    /// With the approvals enabled, the signer is rotated with an approved proposal.
+    #[update]
+    pub fn admin_set_signing_strategy(
+        &self,
+        signing_strategy: SigningStrategy,
+    ) -> minter_did::error::Result<()> {
+        check_direct_admin(ic::caller())?;
+        get_state()
+            .borrow_mut()
+            .set_signing_strategy(signing_strategy)
+    }
+
+    /// Sets the approvers of the dangerous operations, or disables the approvals if `config` is
+    /// `None`. Once the approvals are enabled, the operations of [`AdminOperation`], e.g. the
+    /// signer rotation, the reconfiguration and the change of the approvers, are applied only
+    /// with the approved proposals.
+    #[update]
+    pub fn admin_set_approval_config(
+        &self,
+        config: Option<ApprovalConfig>,
+    ) -> minter_did::error::Result<()> {
+        check_direct_admin(ic::caller())?;
+        get_admin_approvals()
+            .configure(config)
+            .map_err(approval_error)
+    }
+
+    #[query]
+    pub fn get_approval_config(&self) -> Option<ApprovalConfig> {
+        get_admin_approvals().config()
+    }
+
+    /// Proposes the dangerous `operation`, approved by the caller. Returns the id of the
+    /// proposal.
+    #[update]
+    pub fn admin_propose_operation(
+        &self,
+        operation: AdminOperation,
+    ) -> minter_did::error::Result<u64> {
+        get_admin_approvals()
+            .propose(ic::caller(), operation, get_state().borrow().clock().now())
+            .map_err(approval_error)
+    }
+
+    /// Approves the proposal. Returns the number of its approvals.
+    #[update]
+    pub fn admin_approve_operation(&self, proposal_id: u64) -> minter_did::error::Result<u32> {
+        get_admin_approvals()
+            .approve(
+                ic::caller(),
+                proposal_id,
+                get_state().borrow().clock().now(),
+            )
+            .map_err(approval_error)
+    }
+
+    /// Applies the operation of the proposal if it has the approvals of the threshold number of
+    /// the approvers.
+    #[update]
+    pub async fn admin_execute_operation(&self, proposal_id: u64) -> minter_did::error::Result<()> {
+        let operation = get_admin_approvals()
+            .approved_operation(
+                ic::caller(),
+                proposal_id,
+                get_state().borrow().clock().now(),
+            )
+            .map_err(approval_error)?;
+        get_admin_approvals().mark_executed(proposal_id, get_state().borrow().clock().now());
+        let result = apply_admin_operation(operation).await;
+        if result.is_err() {
+            get_admin_approvals().reopen(proposal_id);
+        }
+        result
+    }
+
+    /// Returns the proposals which are not executed and not expired. The proposals may carry the
+    /// signing strategy, so they are returned to the approvers only.
+    #[query]
+    pub fn get_operation_proposals(
+        &self,
+    ) -> minter_did::error::Result<Vec<Proposal<AdminOperation>>> {
+        let approvals = get_admin_approvals();
+        if !approvals
+            .config()
+            .is_some_and(|config| config.is_approver(&ic::caller()))
+        {
+            return Err(minter_did::error::Error::NotAuthorized);
+        }
+
+        Ok(approvals.open_proposals(get_state().borrow().clock().now()))
+    }
+
+    /// Updates the name, symbol and decimals of the wrapped token with a transaction to the
+    /// BftBridge contract. Returns the hash of the transaction.
+    ///
+    /// With the approvals enabled, the metadata is updated with an approved proposal, and the
+    /// call returns `NotAuthorized`.
+    #[update]
+    pub async fn admin_update_token_metadata(
+        &mut self,
+        name: [u8; 32],
+        symbol: [u8; 16],
+        decimals: u8,
+    ) -> Result<H256, Erc20MintError> {
+        check_direct_admin(ic::caller()).map_err(|_| Erc20MintError::NotAuthorized)?;
+        crate::ops::update_token_metadata(&get_state(), name, symbol, decimals).await
+    }
+
+    /// Sets priorities and concurrency limits of the canister tasks. Task types are named after
+    /// the `BtcTask` variants, e.g. `MintErc20`.
+    #[update]
+    pub fn admin_set_task_limits(&self, limits: TaskLimits) -> minter_did::error::Result<()> {
+        check_direct_admin(ic::caller())?;
+        get_state().borrow_mut().configure_task_limits(limits)
+    }
+
+    #[query]
+    pub fn get_task_limits(&self) -> TaskLimits {
+        get_state().borrow().task_limiter.limits().clone()
+    }
+
+    /// Sets gas limits of the EVM transactions by the operation. The limit of an operation is
+    /// bumped automatically when its transaction runs out of gas.
+    #[update]
+    pub fn admin_set_gas_limits(&self, limits: GasLimits) -> minter_did::error::Result<()> {
+        check_direct_admin(ic::caller())?;
+        get_state().borrow_mut().configure_gas_limits(limits)
+    }
+
+    #[query]
+    pub fn get_gas_limits(&self) -> GasLimits {
+        get_state().borrow().gas_limits.clone()
+    }
+
+    /// Subscribes the canister to bridge events. The subscriber is notified about every
+    /// processed `Minted` and `Burnt` event with a one-way call of its `on_bridge_event` method.
+    #[update]
+    pub fn admin_add_event_subscriber(
+        &self,
+        subscriber: Principal,
+    ) -> minter_did::error::Result<()> {
+        check_direct_admin(ic::caller())?;
+        get_event_subscribers().add(subscriber)
+    }
+
+    #[update]
+    pub fn admin_remove_event_subscriber(
+        &self,
+        subscriber: Principal,
+    ) -> minter_did::error::Result<()> {
+        check_direct_admin(ic::caller())?;
+        get_event_subscribers().remove(&subscriber);
+        Ok(())
+    }
+
+    #[query]
+    pub fn get_event_subscribers(&self) -> Vec<Principal> {
+        get_event_subscribers().get_all()
+    }
+
+    /// Returns up to `length` entries of the log of the wrapped token mints and burns, starting
+    /// from the `start` entry. The hash of the last entry is certified, so the query response
+    /// can be verified with the returned certificate.
+    #[query]
+    pub fn get_bridge_transactions(
+        &self,
+        start: u64,
+        length: u64,
+    ) -> GetBridgeTransactionsResponse {
+        get_bridge_tx_log().get_transactions(start, length)
     }
 
     #[cfg(target_family = "wasm")]
-    fn collect_evm_events_task() -> ScheduledTask<BtcTask> {
+    fn collect_evm_events_options() -> TaskOptions {
         const EVM_EVENTS_COLLECTING_DELAY: u32 = 1;
 
-        let options = TaskOptions::default()
+        TaskOptions::default()
             .with_retry_policy(ic_task_scheduler::retry::RetryPolicy::Infinite)
             .with_backoff_policy(BackoffPolicy::Fixed {
                 secs: EVM_EVENTS_COLLECTING_DELAY,
-            });
-
-        BtcTask::CollectEvmEvents.into_scheduled(options)
+            })
     }
 
     fn check_anonymous_principal(principal: Principal) -> minter_did::error::Result<()> {
@@ -193,13 +799,6 @@ impl BtcBridge {
     }
 }
 
-pub fn eth_address_to_subaccount(eth_address: &H160) -> Subaccount {
-    let mut subaccount = [0; 32];
-    subaccount[0..eth_address.0 .0.len()].copy_from_slice(eth_address.0.as_bytes());
-
-    Subaccount(subaccount)
-}
-
 impl Metrics for BtcBridge {
     fn metrics(&self) -> Rc<RefCell<MetricsStorage>> {
         use ic_storage::IcStorage;
@@ -207,7 +806,147 @@ impl Metrics for BtcBridge {
     }
 }
 
-fn log_task_execution_error(task: InnerScheduledTask<BtcTask>) {
+/// Checks that the `caller` may apply a dangerous operation without an approved proposal.
+fn check_direct_admin(caller: Principal) -> minter_did::error::Result<()> {
+    get_state().borrow().check_admin(caller)?;
+    get_admin_approvals().check_direct().map_err(approval_error)
+}
+
+fn approval_error(err: ApprovalError) -> minter_did::error::Error {
+    match err {
+        ApprovalError::NotApprover => minter_did::error::Error::NotAuthorized,
+        err => minter_did::error::Error::Internal(err.to_string()),
+    }
+}
+
+async fn apply_admin_operation(operation: AdminOperation) -> minter_did::error::Result<()> {
+    match operation {
+        AdminOperation::SetSigningStrategy(signing_strategy) => get_state()
+            .borrow_mut()
+            .set_signing_strategy(signing_strategy),
+        AdminOperation::Reconfigure { config, nonce } => reconfigure(config, nonce).map(|_| ()),
+        AdminOperation::SetApprovalConfig(config) => get_admin_approvals()
+            .configure(config)
+            .map_err(approval_error),
+        AdminOperation::ConfigureBftBridge(config) => configure_bft_bridge(config).await,
+        AdminOperation::DeployBftBridge { fee_charge } => {
+            crate::ops::deploy_bft_bridge(&get_state(), fee_charge)
+                .await
+                .map(|_| ())
+        }
+        AdminOperation::SetTaskLimits(limits) => {
+            get_state().borrow_mut().configure_task_limits(limits)
+        }
+        AdminOperation::SetGasLimits(limits) => {
+            get_state().borrow_mut().configure_gas_limits(limits)
+        }
+        AdminOperation::AddEventSubscriber(subscriber) => get_event_subscribers().add(subscriber),
+        AdminOperation::RemoveEventSubscriber(subscriber) => {
+            get_event_subscribers().remove(&subscriber);
+            Ok(())
+        }
+        AdminOperation::SetSoftCap { lane, cap_per_hour } => set_soft_cap(lane, cap_per_hour),
+        AdminOperation::UpdateTokenMetadata {
+            name,
+            symbol,
+            decimals,
+        } => {
+            let tx_hash = crate::ops::update_token_metadata(&get_state(), name, symbol, decimals)
+                .await
+                .map_err(|err| {
+                    minter_did::error::Error::Internal(format!(
+                        "failed to update token metadata: {err:?}"
+                    ))
+                })?;
+            log::info!(
+                "Token metadata is updated with the transaction {:#x}",
+                tx_hash.0
+            );
+            Ok(())
+        }
+    }
+}
+
+fn set_soft_cap(lane: Lane, cap_per_hour: Option<u128>) -> minter_did::error::Result<()> {
+    get_soft_cap_store()
+        .set_cap(lane, cap_per_hour)
+        .map_err(|err| minter_did::error::Error::Internal(format!("Invalid soft cap: {err}")))
+}
+
+/// Sets the BftBridge configuration once it is checked against the EVM.
+async fn configure_bft_bridge(config: BftBridgeConfig) -> minter_did::error::Result<()> {
+    validate_bft_config(&config)
+        .await
+        .map_err(|errors| minter_did::error::Error::Internal(format_config_errors(&errors)))?;
+    get_state().borrow_mut().configure_bft(config);
+    Ok(())
+}
+
+/// Replaces the configuration made with the `nonce`. Returns the nonce of the next
+/// reconfiguration.
+fn reconfigure(config: BtcBridgeConfig, nonce: u64) -> minter_did::error::Result<u64> {
+    let mut config_guard = get_config_guard();
+    match config_guard
+        .check(&config, nonce)
+        .map_err(minter_did::error::Error::Internal)?
+    {
+        Reconfiguration::AlreadyApplied => return Ok(config_guard.nonce()),
+        Reconfiguration::Apply => {}
+    }
+
+    get_state().borrow_mut().reconfigure(config.clone())?;
+    Ok(config_guard.record(&config))
+}
+
+/// Checks the BftBridge configuration against the EVM of the bridge, the wiring of the contracts
+/// to the canister included.
+async fn validate_bft_config(config: &BftBridgeConfig) -> Result<(), Vec<ConfigError>> {
+    let (evm_link, signer) = {
+        let state = get_state();
+        let state = state.borrow();
+        (state.get_evm_info().link, state.signer().get().clone())
+    };
+    config.validate_with_evm(&evm_link).await?;
+    config.validate_wiring(&evm_link, &signer).await
+}
+
+fn on_task_completed(task: InnerScheduledTask<BtcTask>) {
+    if reschedule_deferred_task(&task) {
+        return;
+    }
+
+    task.task().release();
+    log_task_execution_error(&task);
+}
+
+/// Appends a task whose retries were used up by the deferrals of the task limits again, with the
+/// options it was appended with. Returns `false` if the task is completed or has failed for
+/// another reason. The scheduler is borrowed while it reports the completed tasks, so the task is
+/// appended by a timer after [`DEFERRED_TASK_DELAY_SECS`].
+fn reschedule_deferred_task(task: &InnerScheduledTask<BtcTask>) -> bool {
+    let TaskStatus::Failed {
+        error: SchedulerError::TaskExecutionFailed(error),
+        ..
+    } = task.status()
+    else {
+        return false;
+    };
+    if !is_deferral_error(error.as_str()) {
+        return false;
+    }
+
+    log::debug!("task #{} is rescheduled after its deferrals", task.id());
+    let scheduled = ScheduledTask::with_options(task.task().clone(), task.options().clone());
+    ic_exports::ic_cdk_timers::set_timer(
+        Duration::from_secs(DEFERRED_TASK_DELAY_SECS),
+        move || {
+            get_scheduler().borrow().append_task(scheduled);
+        },
+    );
+    true
+}
+
+fn log_task_execution_error(task: &InnerScheduledTask<BtcTask>) {
     match task.status() {
         TaskStatus::Failed {
             timestamp_secs,
@@ -243,6 +982,40 @@ pub fn get_scheduler() -> Rc<RefCell<PersistentScheduler>> {
     SCHEDULER.with(|scheduler| scheduler.clone())
 }
 
+pub fn get_event_subscribers() -> EventSubscribers<VirtualMemory<DefaultMemoryImpl>> {
+    MEMORY_MANAGER.with(|mm| EventSubscribers::new(mm.get(EVENT_SUBSCRIBERS_MEMORY_ID)))
+}
+
+pub fn get_bridge_tx_log() -> BridgeTxLog<VirtualMemory<DefaultMemoryImpl>> {
+    MEMORY_MANAGER.with(|mm| BridgeTxLog::new(mm.get(BRIDGE_TX_LOG_MEMORY_ID)))
+}
+
+pub fn get_notified_events() -> NotifiedEvents<VirtualMemory<DefaultMemoryImpl>> {
+    MEMORY_MANAGER.with(|mm| NotifiedEvents::new(mm.get(NOTIFIED_EVENTS_MEMORY_ID)))
+}
+
+pub fn get_config_guard() -> ConfigGuard<VirtualMemory<DefaultMemoryImpl>> {
+    MEMORY_MANAGER.with(|mm| ConfigGuard::new(mm.get(CONFIG_REVISION_MEMORY_ID)))
+}
+
+pub fn get_admin_approvals() -> ApprovalStore<AdminOperation, VirtualMemory<DefaultMemoryImpl>> {
+    MEMORY_MANAGER.with(|mm| {
+        ApprovalStore::new(
+            mm.get(APPROVAL_SETTINGS_MEMORY_ID),
+            mm.get(APPROVAL_PROPOSALS_MEMORY_ID),
+        )
+    })
+}
+
+pub fn get_soft_cap_store() -> SoftCapStore<VirtualMemory<DefaultMemoryImpl>> {
+    MEMORY_MANAGER.with(|mm| {
+        SoftCapStore::new(
+            mm.get(SOFT_CAP_LANES_MEMORY_ID),
+            mm.get(SOFT_CAP_QUEUE_MEMORY_ID),
+        )
+    })
+}
+
 #[cfg(test)]
 mod test {
     use candid::Principal;
@@ -264,6 +1037,8 @@ mod test {
             admin: Principal::anonymous(),
             ..Default::default()
         };
-        canister_call!(canister.init(init_data), ()).await.unwrap();
+        canister_call!(canister.init(init_data, None), ())
+            .await
+            .unwrap();
     }
 }
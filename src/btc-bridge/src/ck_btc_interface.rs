@@ -131,3 +131,23 @@ pub enum RetrieveBtcError {
         error_code: u64,
     },
 }
+
+/// The arguments of the [estimate_withdrawal_fee] endpoint.
+#[derive(CandidType, Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct EstimateWithdrawalFeeArgs {
+    pub amount: Option<u64>,
+}
+
+#[derive(CandidType, Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+pub struct WithdrawalFee {
+    pub minter_fee: u64,
+    pub bitcoin_fee: u64,
+}
+
+/// The result of the [get_minter_info] endpoint.
+#[derive(CandidType, Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+pub struct MinterInfo {
+    pub min_confirmations: u32,
+    pub retrieve_btc_min_amount: u64,
+    pub kyt_fee: u64,
+}
@@ -0,0 +1,58 @@
+use did::H160;
+use ic_stable_structures::stable_structures::DefaultMemoryImpl;
+use ic_stable_structures::{BTreeMapStructure, StableBTreeMap, VirtualMemory};
+
+use crate::memory::{DEPOSIT_ADDRESSES_MEMORY_ID, MEMORY_MANAGER};
+
+/// BTC deposit addresses received from the ckBTC minter by the EVM address they belong to.
+///
+/// The address of a ckBTC account never changes, so once received it is kept forever and can be
+/// returned by queries without calling the minter.
+pub struct DepositAddressStore {
+    inner: StableBTreeMap<String, String, VirtualMemory<DefaultMemoryImpl>>,
+}
+
+impl Default for DepositAddressStore {
+    fn default() -> Self {
+        Self {
+            inner: StableBTreeMap::new(
+                MEMORY_MANAGER.with(|mm| mm.get(DEPOSIT_ADDRESSES_MEMORY_ID)),
+            ),
+        }
+    }
+}
+
+impl DepositAddressStore {
+    pub fn get(&self, eth_address: &H160) -> Option<String> {
+        self.inner.get(&Self::key(eth_address))
+    }
+
+    pub fn insert(&mut self, eth_address: &H160, btc_address: String) {
+        self.inner.insert(Self::key(eth_address), btc_address);
+    }
+
+    fn key(eth_address: &H160) -> String {
+        format!("{:x}", eth_address.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ic_exports::ic_kit::MockContext;
+
+    use super::*;
+
+    #[test]
+    fn addresses_are_stored_by_eth_address() {
+        MockContext::new().inject();
+        let mut store = DepositAddressStore::default();
+        let alice = H160::from_slice(&[1; 20]);
+        let bob = H160::from_slice(&[2; 20]);
+
+        assert_eq!(store.get(&alice), None);
+
+        store.insert(&alice, "bc1qalice".to_string());
+        assert_eq!(store.get(&alice), Some("bc1qalice".to_string()));
+        assert_eq!(store.get(&bob), None);
+    }
+}
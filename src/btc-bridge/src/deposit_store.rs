@@ -0,0 +1,255 @@
+use std::borrow::Cow;
+
+use candid::{CandidType, Decode, Encode, Principal};
+use did::H160;
+use ethers_core::types::H256 as EthH256;
+use ic_stable_structures::stable_structures::DefaultMemoryImpl;
+use ic_stable_structures::{BTreeMapStructure, Bound, StableBTreeMap, Storable, VirtualMemory};
+use minter_contract_utils::mint_completion::{DepositStatus, MintTx};
+use minter_did::id256::Id256;
+use serde::Deserialize;
+
+use crate::memory::{
+    DEPOSIT_STATUS_MEMORY_ID, MEMORY_MANAGER, PENDING_CKBTC_DEPOSITS_MEMORY_ID,
+    PENDING_CKBTC_REFUNDS_MEMORY_ID,
+};
+
+/// Statuses of the deposits by the sender and the nonce of their mint orders.
+///
+/// A deposit is recorded as pending when its mint order is signed, and is completed when the
+/// `Minted` event of the order is collected from the EVM.
+pub struct DepositStatusStore {
+    inner: StableBTreeMap<String, DepositStatus, VirtualMemory<DefaultMemoryImpl>>,
+}
+
+impl Default for DepositStatusStore {
+    fn default() -> Self {
+        Self {
+            inner: StableBTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(DEPOSIT_STATUS_MEMORY_ID))),
+        }
+    }
+}
+
+impl DepositStatusStore {
+    pub fn get(&self, sender: &Id256, nonce: u32) -> Option<DepositStatus> {
+        self.inner.get(&Self::key(sender, nonce))
+    }
+
+    pub fn insert_pending(&mut self, sender: &Id256, nonce: u32) {
+        self.inner
+            .insert(Self::key(sender, nonce), DepositStatus::Pending);
+    }
+
+    /// Marks the deposit as completed by the `mint_tx`. The deposits whose mint orders were
+    /// signed before the statuses were recorded are added as completed.
+    pub fn complete(&mut self, sender: &Id256, nonce: u32, mint_tx: Option<MintTx>) {
+        self.inner.insert(
+            Self::key(sender, nonce),
+            DepositStatus::Completed {
+                mints: mint_tx.into_iter().collect(),
+            },
+        );
+    }
+
+    fn key(sender: &Id256, nonce: u32) -> String {
+        format!("{:x}:{nonce}", EthH256(sender.0))
+    }
+}
+
+/// ckBTC deposit whose ckBTC are transferred to the bridge, but whose mint order is not signed
+/// yet.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct PendingCkBtcDeposit {
+    pub caller: Principal,
+    pub eth_address: H160,
+    /// Amount of the mint order, without the ledger fee.
+    pub amount: u64,
+}
+
+impl Storable for PendingCkBtcDeposit {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("serialization failed"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).expect("deserialization failed")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// ckBTC deposits waiting for their mint orders by the index of the ledger transfer, which is
+/// the nonce of the mint order.
+///
+/// A deposit is recorded right after the transfer, so the mint order is signed again by the
+/// retry task if the signing fails, and is removed once the order is stored.
+pub struct PendingCkBtcDeposits {
+    inner: StableBTreeMap<u32, PendingCkBtcDeposit, VirtualMemory<DefaultMemoryImpl>>,
+}
+
+impl Default for PendingCkBtcDeposits {
+    fn default() -> Self {
+        Self {
+            inner: StableBTreeMap::new(
+                MEMORY_MANAGER.with(|mm| mm.get(PENDING_CKBTC_DEPOSITS_MEMORY_ID)),
+            ),
+        }
+    }
+}
+
+impl PendingCkBtcDeposits {
+    pub fn insert(&mut self, nonce: u32, deposit: PendingCkBtcDeposit) {
+        self.inner.insert(nonce, deposit);
+    }
+
+    /// Removes the deposit. Returns `None` if it is not pending, e.g. if its mint order is
+    /// stored already.
+    pub fn remove(&mut self, nonce: u32) -> Option<PendingCkBtcDeposit> {
+        self.inner.remove(&nonce)
+    }
+
+    /// Returns the `(nonce, deposit)` pairs of the pending deposits.
+    pub fn get_all(&self) -> Vec<(u32, PendingCkBtcDeposit)> {
+        self.inner.iter().collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.len() == 0
+    }
+}
+
+/// ckBTC transferred to the bridge by a deposit which cannot be minted, to be returned to the
+/// caller.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct PendingCkBtcRefund {
+    pub caller: Principal,
+    /// Amount transferred to the bridge, the ledger fee of the refund is subtracted from it.
+    pub amount: u64,
+    /// Creation time of the refund transfer, so the ledger deduplicates its retries.
+    pub created_at: u64,
+}
+
+impl Storable for PendingCkBtcRefund {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("serialization failed"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).expect("deserialization failed")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// Refunds of the ckBTC deposits by the index of their ledger transfer.
+///
+/// A deposit is refunded if the index of its transfer cannot be used as the mint order nonce.
+/// The refund is recorded before it is transferred, so a failed refund is retried by the retry
+/// task of the pending deposits.
+pub struct PendingCkBtcRefunds {
+    inner: StableBTreeMap<String, PendingCkBtcRefund, VirtualMemory<DefaultMemoryImpl>>,
+}
+
+impl Default for PendingCkBtcRefunds {
+    fn default() -> Self {
+        Self {
+            inner: StableBTreeMap::new(
+                MEMORY_MANAGER.with(|mm| mm.get(PENDING_CKBTC_REFUNDS_MEMORY_ID)),
+            ),
+        }
+    }
+}
+
+impl PendingCkBtcRefunds {
+    pub fn insert(&mut self, block_index: String, refund: PendingCkBtcRefund) {
+        self.inner.insert(block_index, refund);
+    }
+
+    /// Removes the refund. Returns `None` if it is not pending, e.g. if it is being transferred.
+    pub fn remove(&mut self, block_index: &str) -> Option<PendingCkBtcRefund> {
+        self.inner.remove(&block_index.to_string())
+    }
+
+    /// Returns the `(block_index, refund)` pairs of the pending refunds.
+    pub fn get_all(&self) -> Vec<(String, PendingCkBtcRefund)> {
+        self.inner.iter().collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use did::H256;
+    use ic_exports::ic_kit::MockContext;
+
+    use super::*;
+
+    #[test]
+    fn deposit_is_completed_by_mint_tx() {
+        MockContext::new().inject();
+        let mut store = DepositStatusStore::default();
+        let sender = Id256([1; 32]);
+        let mint_tx = MintTx {
+            tx_hash: H256::from_slice(&[2; 32]),
+            block_number: Some(7),
+        };
+
+        assert_eq!(store.get(&sender, 1), None);
+
+        store.insert_pending(&sender, 1);
+        store.insert_pending(&sender, 2);
+        assert_eq!(store.get(&sender, 1), Some(DepositStatus::Pending));
+
+        store.complete(&sender, 1, Some(mint_tx.clone()));
+        assert_eq!(
+            store.get(&sender, 1),
+            Some(DepositStatus::Completed {
+                mints: vec![mint_tx]
+            })
+        );
+        assert_eq!(store.get(&sender, 2), Some(DepositStatus::Pending));
+        assert_eq!(store.get(&Id256([3; 32]), 1), None);
+    }
+
+    #[test]
+    fn pending_ckbtc_deposit_is_removed_once() {
+        MockContext::new().inject();
+        let mut store = PendingCkBtcDeposits::default();
+        let deposit = PendingCkBtcDeposit {
+            caller: Principal::management_canister(),
+            eth_address: H160::from_slice(&[4; 20]),
+            amount: 1_000,
+        };
+
+        assert!(store.is_empty());
+        store.insert(7, deposit.clone());
+        assert_eq!(store.get_all(), vec![(7, deposit.clone())]);
+
+        assert_eq!(store.remove(7), Some(deposit));
+        assert_eq!(store.remove(7), None);
+        assert!(store.is_empty());
+    }
+
+    #[test]
+    fn pending_ckbtc_refund_is_removed_once() {
+        MockContext::new().inject();
+        let mut store = PendingCkBtcRefunds::default();
+        let refund = PendingCkBtcRefund {
+            caller: Principal::management_canister(),
+            amount: 1_000,
+            created_at: 42,
+        };
+        let block_index = (u32::MAX as u64 + 1).to_string();
+
+        assert!(store.is_empty());
+        store.insert(block_index.clone(), refund.clone());
+        assert_eq!(store.get_all(), vec![(block_index.clone(), refund.clone())]);
+
+        assert_eq!(store.remove(&block_index), Some(refund));
+        assert_eq!(store.remove(&block_index), None);
+        assert!(store.is_empty());
+    }
+}
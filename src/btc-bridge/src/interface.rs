@@ -1,11 +1,55 @@
-use candid::CandidType;
-use did::H256;
+use candid::{CandidType, Principal};
+use did::{H160, H256};
+use eth_signer::sign_strategy::SigningStrategy;
 use ic_exports::ic_cdk::api::management_canister::bitcoin::Utxo;
+use ic_exports::icrc_types::icrc1::account::Account;
 use ic_exports::icrc_types::icrc1::transfer::TransferError;
+use ic_exports::icrc_types::icrc2::transfer_from::TransferFromError;
+use minter_contract_utils::admin_approvals::ApprovalConfig;
+use minter_contract_utils::gas_limits::GasLimits;
+use minter_contract_utils::soft_caps::Lane;
+use minter_contract_utils::task_limits::TaskLimits;
 use minter_did::order::SignedMintOrder;
 use serde::Deserialize;
 
 use crate::ck_btc_interface::{PendingUtxo, UpdateBalanceError};
+use crate::state::{BftBridgeConfig, BtcBridgeConfig};
+
+/// Dangerous operation applied through an approved proposal when the approvals are enabled.
+#[derive(Debug, Clone, CandidType, Deserialize)]
+pub enum AdminOperation {
+    SetSigningStrategy(SigningStrategy),
+    Reconfigure {
+        config: BtcBridgeConfig,
+        nonce: u64,
+    },
+    SetApprovalConfig(Option<ApprovalConfig>),
+    ConfigureBftBridge(BftBridgeConfig),
+    DeployBftBridge {
+        fee_charge: H160,
+    },
+    SetTaskLimits(TaskLimits),
+    SetGasLimits(GasLimits),
+    AddEventSubscriber(Principal),
+    RemoveEventSubscriber(Principal),
+    SetSoftCap {
+        lane: Lane,
+        cap_per_hour: Option<u128>,
+    },
+    UpdateTokenMetadata {
+        name: [u8; 32],
+        symbol: [u8; 16],
+        decimals: u8,
+    },
+}
+
+/// ckBTC account the BTC deposits of an EVM address are sent to.
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct DepositAccount {
+    pub account: Account,
+    /// BTC address of the account. `None` if it was not yet received from the ckBTC minter.
+    pub btc_address: Option<String>,
+}
 
 /// Status of a pending BTC to ERC20 transfer.
 #[derive(Debug, CandidType, Deserialize, PartialEq, Eq)]
@@ -16,7 +60,8 @@ pub enum Erc20MintStatus {
     Scheduled {
         /// Current confirmations of the transaction.
         current_confirmations: u32,
-        /// Number of confirmations required by ckBTC minter canister to mint ckBTC tokens.
+        /// Number of confirmations required by ckBTC minter canister or by the BtcBridge
+        /// confirmation policy to mint tokens.
         required_confirmations: u32,
         /// Pending transactions.
         pending_utxos: Option<Vec<PendingUtxo>>,
@@ -32,6 +77,15 @@ pub enum Erc20MintStatus {
         /// EVM transaction ID.
         tx_id: H256,
     },
+    /// The ckBTC tokens are minted, but the deposit exceeds the hourly soft cap of the deposits.
+    /// The deposit waits in the queue and its mint order is created when the queue is drained up
+    /// to it.
+    Queued {
+        /// Number of the deposits ahead in the queue.
+        position: u64,
+        /// Estimated time the deposit is processed at, in nanoseconds.
+        eta: Option<u64>,
+    },
 }
 
 /// Error during BTC to ERC20 transfer.
@@ -45,8 +99,12 @@ pub enum Erc20MintError {
     Tainted(Utxo),
     /// Error while connecting to ckBTC.
     CkBtcMinter(UpdateBalanceError),
+    /// Error while requesting deposit utxos from the Bitcoin canister.
+    Bitcoin(String),
     /// Error transferring ckBTC tokens with ledger.
     CkBtcLedger(TransferError),
+    /// Error transferring the approved ckBTC tokens from the caller account.
+    CkBtcLedgerTransferFrom(TransferFromError),
     /// Error while signing the mint order.
     Sign(String),
     /// Error connecting to the EVM.
@@ -55,6 +113,8 @@ pub enum Erc20MintError {
     NotInitialized,
     /// No pending transactions.
     NothingToMint,
+    /// The caller is not allowed to perform the operation.
+    NotAuthorized,
 }
 
 impl From<TransferError> for Erc20MintError {
@@ -62,3 +122,9 @@ impl From<TransferError> for Erc20MintError {
         Self::CkBtcLedger(value)
     }
 }
+
+impl From<TransferFromError> for Erc20MintError {
+    fn from(value: TransferFromError) -> Self {
+        Self::CkBtcLedgerTransferFrom(value)
+    }
+}
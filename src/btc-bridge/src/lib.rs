@@ -1,12 +1,15 @@
 pub mod burn_request_store;
 pub mod canister;
 pub mod ck_btc_interface;
+pub mod deposit_accounts;
+pub mod deposit_store;
 pub mod interface;
 pub mod memory;
 pub mod ops;
 pub mod orders_store;
 pub mod scheduler;
 pub mod state;
+pub mod withdrawal_fee;
 
 use ic_metrics::Metrics;
 
@@ -16,10 +19,6 @@ const EVM_INFO_INITIALIZATION_RETRIES: u32 = 5;
 const EVM_INFO_INITIALIZATION_RETRY_DELAY_SEC: u32 = 2;
 const EVM_INFO_INITIALIZATION_RETRY_MULTIPLIER: u32 = 2;
 
-const MAINNET_CHAIN_ID: u32 = 0;
-const TESTNET_CHAIN_ID: u32 = 1;
-const REGTEST_CHAIN_ID: u32 = 2;
-
 pub fn idl() -> String {
     let btc_bridge_idl = BtcBridge::idl();
     let mut metrics_idl = <BtcBridge as Metrics>::get_idl();
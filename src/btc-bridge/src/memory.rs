@@ -4,9 +4,47 @@ use ic_stable_structures::{IcMemoryManager, MemoryId};
 pub const CONFIG_MEMORY_ID: MemoryId = MemoryId::new(0);
 pub const PENDING_TASKS_MEMORY_ID: MemoryId = MemoryId::new(1);
 pub const SIGNER_MEMORY_ID: MemoryId = MemoryId::new(2);
-pub const MINT_ORDERS_MEMORY_ID: MemoryId = MemoryId::new(3);
+/// Mint orders kept by the earlier versions, moved to [`MINT_ORDERS_MEMORY_ID`] on upgrade.
+pub const LEGACY_MINT_ORDERS_MEMORY_ID: MemoryId = MemoryId::new(3);
 pub const LOGGER_SETTINGS_MEMORY_ID: MemoryId = MemoryId::new(4);
 pub const BURN_REQUEST_MEMORY_ID: MemoryId = MemoryId::new(5);
+pub const EVENT_SUBSCRIBERS_MEMORY_ID: MemoryId = MemoryId::new(6);
+pub const DEPOSIT_ADDRESSES_MEMORY_ID: MemoryId = MemoryId::new(7);
+pub const DEPOSIT_STATUS_MEMORY_ID: MemoryId = MemoryId::new(8);
+pub const BRIDGE_TX_LOG_MEMORY_ID: MemoryId = MemoryId::new(9);
+pub const NOTIFIED_EVENTS_MEMORY_ID: MemoryId = MemoryId::new(10);
+pub const SOFT_CAP_LANES_MEMORY_ID: MemoryId = MemoryId::new(11);
+pub const SOFT_CAP_QUEUE_MEMORY_ID: MemoryId = MemoryId::new(12);
+pub const CONFIG_REVISION_MEMORY_ID: MemoryId = MemoryId::new(13);
+pub const APPROVAL_SETTINGS_MEMORY_ID: MemoryId = MemoryId::new(14);
+pub const APPROVAL_PROPOSALS_MEMORY_ID: MemoryId = MemoryId::new(15);
+pub const MINT_ORDERS_MEMORY_ID: MemoryId = MemoryId::new(16);
+pub const PENDING_CKBTC_DEPOSITS_MEMORY_ID: MemoryId = MemoryId::new(17);
+pub const TASK_LIMITS_MEMORY_ID: MemoryId = MemoryId::new(18);
+pub const PENDING_CKBTC_REFUNDS_MEMORY_ID: MemoryId = MemoryId::new(19);
+
+/// Stable structures reported by the `canister_status_info` query.
+pub const STABLE_STRUCTURES: &[(&str, MemoryId)] = &[
+    ("config", CONFIG_MEMORY_ID),
+    ("pending_tasks", PENDING_TASKS_MEMORY_ID),
+    ("signer", SIGNER_MEMORY_ID),
+    ("mint_orders", MINT_ORDERS_MEMORY_ID),
+    ("logger_settings", LOGGER_SETTINGS_MEMORY_ID),
+    ("burn_request", BURN_REQUEST_MEMORY_ID),
+    ("event_subscribers", EVENT_SUBSCRIBERS_MEMORY_ID),
+    ("deposit_addresses", DEPOSIT_ADDRESSES_MEMORY_ID),
+    ("deposit_status", DEPOSIT_STATUS_MEMORY_ID),
+    ("bridge_tx_log", BRIDGE_TX_LOG_MEMORY_ID),
+    ("notified_events", NOTIFIED_EVENTS_MEMORY_ID),
+    ("soft_cap_lanes", SOFT_CAP_LANES_MEMORY_ID),
+    ("soft_cap_queue", SOFT_CAP_QUEUE_MEMORY_ID),
+    ("config_revision", CONFIG_REVISION_MEMORY_ID),
+    ("approval_settings", APPROVAL_SETTINGS_MEMORY_ID),
+    ("approval_proposals", APPROVAL_PROPOSALS_MEMORY_ID),
+    ("pending_ckbtc_deposits", PENDING_CKBTC_DEPOSITS_MEMORY_ID),
+    ("task_limits", TASK_LIMITS_MEMORY_ID),
+    ("pending_ckbtc_refunds", PENDING_CKBTC_REFUNDS_MEMORY_ID),
+];
 
 thread_local! {
     pub static MEMORY_MANAGER: IcMemoryManager<DefaultMemoryImpl> = IcMemoryManager::init(DefaultMemoryImpl::default());
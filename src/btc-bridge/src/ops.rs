@@ -1,32 +1,56 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
-use candid::{Nat, Principal};
+use candid::{Encode, Nat, Principal};
 use did::{H160, H256};
 use eth_signer::sign_strategy::TransactionSigner;
 use ic_canister::virtual_canister_call;
+use ic_ckbtc_minter::updates::get_btc_address::GetBtcAddressArgs;
+use ic_exports::ic_cdk::api::management_canister::bitcoin::{
+    bitcoin_get_utxos, GetUtxosRequest, UtxoFilter,
+};
 use ic_exports::ic_kit::ic;
 use ic_exports::icrc_types::icrc1::account::Account as IcrcAccount;
 use ic_exports::icrc_types::icrc1::transfer::{TransferArg, TransferError};
+use ic_exports::icrc_types::icrc2::transfer_from::{TransferFromArgs, TransferFromError};
+use ic_exports::ledger::Subaccount;
 use ic_stable_structures::CellStructure;
 use ic_task_scheduler::scheduler::TaskScheduler;
 use ic_task_scheduler::task::TaskOptions;
+use minter_contract_utils::account_mapping::eth_address_to_subaccount;
+use minter_contract_utils::bft_bridge_deploy::{
+    check_deployment, BftBridgeDeployStatus, BftBridgeDeployment,
+};
+use minter_contract_utils::burn_permit::{BurnPermit, BurnPermitError};
+use minter_contract_utils::gas_limits::GasOperation;
+use minter_contract_utils::soft_caps::{Admission, Lane};
 use minter_did::id256::Id256;
 use minter_did::order::{MintOrder, SignedMintOrder};
 
-use crate::canister::{eth_address_to_subaccount, get_scheduler};
+use crate::canister::{get_scheduler, get_soft_cap_store};
 use crate::ck_btc_interface::{
-    RetrieveBtcArgs, RetrieveBtcError, RetrieveBtcOk, UpdateBalanceArgs, UpdateBalanceError,
-    UtxoStatus,
+    EstimateWithdrawalFeeArgs, MinterInfo, RetrieveBtcArgs, RetrieveBtcError, RetrieveBtcOk,
+    UpdateBalanceArgs, UpdateBalanceError, UtxoStatus, WithdrawalFee,
 };
+use crate::deposit_store::{PendingCkBtcDeposit, PendingCkBtcRefund};
 use crate::interface::{Erc20MintError, Erc20MintStatus};
 use crate::scheduler::BtcTask;
 use crate::state::State;
+use crate::withdrawal_fee::{self, WithdrawalFeeEstimate};
 
 pub async fn btc_to_erc20(
     state: Rc<RefCell<State>>,
     eth_address: H160,
 ) -> Vec<Result<Erc20MintStatus, Erc20MintError>> {
+    match check_deposit_confirmations(&state, &eth_address).await {
+        Ok(None) => {}
+        Ok(Some(status)) => {
+            schedule_mint(eth_address);
+            return vec![Ok(status)];
+        }
+        Err(err) => return vec![Err(err)],
+    }
+
     match request_update_balance(&state, &eth_address).await {
         Ok(minted_utxos) => {
             let mut results = vec![];
@@ -72,13 +96,121 @@ pub async fn btc_to_erc20(
     }
 }
 
+/// Checks if the BTC deposited for the `eth_address` has enough confirmations according to the
+/// confirmation policy of the bridge. Returns `Scheduled` status if more confirmations are required.
+///
+/// This check must be done before requesting ckBTC minter to update balance, as the minter mints
+/// ckBTC as soon as its own confirmations requirement is satisfied.
+async fn check_deposit_confirmations(
+    state: &RefCell<State>,
+    eth_address: &H160,
+) -> Result<Option<Erc20MintStatus>, Erc20MintError> {
+    let (policy, network, ck_btc_minter) = {
+        let state_ref = state.borrow();
+        (
+            state_ref.confirmation_policy(),
+            state_ref.network().ic_network(),
+            state_ref.ck_btc_minter(),
+        )
+    };
+
+    if policy.tiers.is_empty() {
+        return Ok(None);
+    }
+
+    let address = get_deposit_btc_address(state, ck_btc_minter, eth_address).await?;
+
+    let max_confirmations = policy.max_confirmations();
+    let mut filter = None;
+    let mut utxos = vec![];
+    loop {
+        let response = bitcoin_get_utxos(GetUtxosRequest {
+            address: address.clone(),
+            network,
+            filter,
+        })
+        .await
+        .map(|value| value.0)
+        .map_err(|err| Erc20MintError::Bitcoin(format!("{err:?}")))?;
+
+        utxos.extend(response.utxos.iter().map(|utxo| {
+            (
+                utxo.value,
+                response.tip_height.saturating_sub(utxo.height) + 1,
+            )
+        }));
+        match response.next_page {
+            None => break,
+            Some(page) => filter = Some(UtxoFilter::Page(page)),
+        }
+    }
+
+    let pending = utxos
+        .into_iter()
+        .filter(|(_, confirmations)| *confirmations < max_confirmations);
+
+    let (deposit_value, current_confirmations) = pending.fold(
+        (0u64, u32::MAX),
+        |(value, min_confirmations), (utxo_value, confirmations)| {
+            (
+                value.saturating_add(utxo_value),
+                min_confirmations.min(confirmations),
+            )
+        },
+    );
+
+    let required_confirmations = policy.required_confirmations(deposit_value);
+    if deposit_value == 0 || current_confirmations >= required_confirmations {
+        return Ok(None);
+    }
+
+    log::trace!("Deposit of {deposit_value} sats for {eth_address} has {current_confirmations} confirmations out of {required_confirmations} required.");
+
+    Ok(Some(Erc20MintStatus::Scheduled {
+        current_confirmations,
+        required_confirmations,
+        pending_utxos: None,
+    }))
+}
+
+/// Returns the BTC address of the deposit account of the `eth_address`, requesting it from the
+/// ckBTC minter if it is not cached yet.
+pub async fn get_deposit_btc_address(
+    state: &RefCell<State>,
+    ck_btc_minter: Principal,
+    eth_address: &H160,
+) -> Result<String, Erc20MintError> {
+    if let Some(address) = state.borrow().deposit_addresses().get(eth_address) {
+        return Ok(address);
+    }
+
+    let args = GetBtcAddressArgs {
+        owner: Some(ic::id()),
+        subaccount: Some(eth_address_to_subaccount(eth_address)),
+    };
+    let address = virtual_canister_call!(ck_btc_minter, "get_btc_address", (args,), String)
+        .await
+        .map_err(|err| {
+            Erc20MintError::CkBtcMinter(UpdateBalanceError::TemporarilyUnavailable(format!(
+                "Failed to connect to ckBTC minter: {err:?}"
+            )))
+        })?;
+
+    state
+        .borrow_mut()
+        .deposit_addresses_mut()
+        .insert(eth_address, address.clone());
+
+    Ok(address)
+}
+
 async fn request_update_balance(
     state: &RefCell<State>,
     eth_address: &H160,
 ) -> Result<Vec<UtxoStatus>, UpdateBalanceError> {
     let self_id = ic::id();
     let ck_btc_minter = state.borrow().ck_btc_minter();
-    let subaccount = eth_address_to_subaccount(eth_address);
+    let subaccount = Subaccount(eth_address_to_subaccount(eth_address));
 
     let args = UpdateBalanceArgs {
         owner: Some(self_id),
@@ -107,6 +239,19 @@ fn schedule_mint(eth_address: H160) {
     scheduler.append_task(task.into_scheduled(options));
 }
 
+/// Ticket of the deposit in the soft cap queue.
+pub fn deposit_ticket(eth_address: &H160, nonce: u32) -> String {
+    format!("deposit:{:#x}:{nonce}", eth_address.0)
+}
+
+/// Ticket of the withdrawal in the soft cap queue.
+pub fn withdrawal_ticket(operation_id: u32) -> String {
+    format!("withdrawal:{operation_id}")
+}
+
+/// Mints the wrapped tokens for the ckBTC `amount` minted to the subaccount of the
+/// `eth_address`. If the deposit exceeds the soft cap, the ckBTC are left in the subaccount
+/// until the deposit is resumed by [`BtcTask::ResumeDeposit`].
 pub async fn mint_erc20(
     state: &RefCell<State>,
     eth_address: H160,
@@ -122,21 +267,244 @@ pub async fn mint_erc20(
         return Err(Erc20MintError::ValueTooSmall);
     }
 
+    let payload = Encode!(&eth_address, &amount, &nonce).expect("serialization failed");
+    let admission = get_soft_cap_store().admit(
+        &deposit_ticket(&eth_address, nonce),
+        Lane::Deposit,
+        amount.into(),
+        payload,
+        state.borrow().clock().now(),
+    );
+    if let Admission::Queued(position) = admission {
+        return Ok(Erc20MintStatus::Queued {
+            position: position.position,
+            eta: position.eta,
+        });
+    }
+
+    let sender_chain_id = state.borrow().btc_chain_id();
+    let sender = Id256::from_evm_address(&eth_address, sender_chain_id);
     let mint_order =
-        prepare_mint_order(state, eth_address.clone(), amount_minus_fee, nonce).await?;
+        prepare_mint_order(state, sender, eth_address.clone(), amount_minus_fee, nonce).await?;
     transfer_ckbtc_from_subaccount(state, &eth_address, amount_minus_fee).await?;
-    store_mint_order(state, mint_order, &eth_address, nonce);
+    store_mint_order(state, mint_order, sender, nonce);
+
+    Ok(send_and_report(state, mint_order, amount_minus_fee).await)
+}
+
+/// Transfers `amount` of ckBTC approved by the `caller` to the bridge and issues a mint order of
+/// the wrapped tokens to the `eth_address`.
+///
+/// As for the deposits through the ckBTC minter, the ledger fee is subtracted from the `amount`,
+/// so the caller is charged `amount` ckBTC in total. The mint order is issued on behalf of the
+/// caller principal, with the index of the ledger transfer as the nonce.
+///
+/// The deposit is recorded as pending once the ckBTC are transferred, so if the mint order is
+/// not signed, it is signed later by [`BtcTask::RetryCkBtcDeposits`]. If the index of the
+/// transfer cannot be used as the nonce, the ckBTC are refunded to the caller instead.
+pub async fn deposit_ckbtc(
+    state: &RefCell<State>,
+    caller: Principal,
+    eth_address: H160,
+    amount: u64,
+) -> Result<Erc20MintStatus, Erc20MintError> {
+    let fee = state.borrow().ck_btc_ledger_fee();
+    let amount_minus_fee = amount
+        .checked_sub(fee)
+        .filter(|amount| *amount > 0)
+        .ok_or(Erc20MintError::ValueTooSmall)?;
 
-    Ok(match send_mint_order(state, mint_order).await {
-        Ok(tx_id) => Erc20MintStatus::Minted {
+    let block_index = transfer_ckbtc_from_caller(state, caller, amount_minus_fee).await?;
+    let Ok(nonce) = u32::try_from(&block_index.0) else {
+        let refund = PendingCkBtcRefund {
+            caller,
             amount: amount_minus_fee,
-            tx_id,
+            created_at: state.borrow().clock().now(),
+        };
+        let block_index = block_index.to_string();
+        state
+            .borrow_mut()
+            .pending_ckbtc_refunds_mut()
+            .insert(block_index.clone(), refund);
+        if let Err(err) = refund_ckbtc_deposit(state, &block_index).await {
+            log::warn!("Failed to refund ckBTC deposit {block_index}: {err:?}");
+        }
+
+        return Err(Erc20MintError::CkBtcLedger(TransferError::GenericError {
+            error_code: 0u64.into(),
+            message: format!(
+                "transfer index {block_index} cannot be used as a mint order nonce, the ckBTC are refunded"
+            ),
+        }));
+    };
+
+    let deposit = PendingCkBtcDeposit {
+        caller,
+        eth_address,
+        amount: amount_minus_fee,
+    };
+    state
+        .borrow_mut()
+        .pending_ckbtc_deposits_mut()
+        .insert(nonce, deposit.clone());
+
+    sign_ckbtc_deposit(state, nonce, deposit).await
+}
+
+/// Returns the ckBTC of the pending refund transferred by `block_index` to the caller, less the
+/// ledger fee. If the transfer fails, the refund stays pending and
+/// [`BtcTask::RetryCkBtcDeposits`] is scheduled.
+pub async fn refund_ckbtc_deposit(
+    state: &RefCell<State>,
+    block_index: &str,
+) -> Result<(), TransferError> {
+    // The refund may be transferred concurrently by the deposit call and the retry task. Only the
+    // one removing the pending refund transfers it.
+    let Some(mut refund) = state
+        .borrow_mut()
+        .pending_ckbtc_refunds_mut()
+        .remove(block_index)
+    else {
+        return Ok(());
+    };
+
+    let (ledger, fee) = {
+        let state_ref = state.borrow();
+        (state_ref.ck_btc_ledger(), state_ref.ck_btc_ledger_fee())
+    };
+    let Some(amount) = refund.amount.checked_sub(fee).filter(|amount| *amount > 0) else {
+        log::warn!(
+            "ckBTC deposit {block_index} of {} is too small to be refunded",
+            refund.amount
+        );
+        return Ok(());
+    };
+
+    let args = TransferArg {
+        from_subaccount: None,
+        to: IcrcAccount {
+            owner: refund.caller,
+            subaccount: None,
         },
+        fee: Some(fee.into()),
+        created_at_time: Some(refund.created_at),
+        memo: None,
+        amount: amount.into(),
+    };
+    let result =
+        virtual_canister_call!(ledger, "icrc1_transfer", (args,), Result<Nat, TransferError>)
+            .await
+            .unwrap_or(Err(TransferError::TemporarilyUnavailable));
+
+    match result {
+        // The duplicate is a refund whose result was lost.
+        Ok(_) | Err(TransferError::Duplicate { .. }) => {
+            log::info!(
+                "ckBTC deposit {block_index} is refunded to {}",
+                refund.caller
+            );
+            Ok(())
+        }
+        Err(err) => {
+            // The ledger doesn't deduplicate the old transfers, so the refund is sent as a new one.
+            if matches!(err, TransferError::TooOld) {
+                refund.created_at = state.borrow().clock().now();
+            }
+            state
+                .borrow_mut()
+                .pending_ckbtc_refunds_mut()
+                .insert(block_index.to_string(), refund);
+            BtcTask::RetryCkBtcDeposits.append_unique(
+                &*get_scheduler().borrow(),
+                BtcTask::retry_ckbtc_deposits_options(),
+            );
+            Err(err)
+        }
+    }
+}
+
+/// Signs and sends the mint order of the pending ckBTC deposit. If the signing fails, the
+/// deposit stays pending and [`BtcTask::RetryCkBtcDeposits`] is scheduled.
+pub async fn sign_ckbtc_deposit(
+    state: &RefCell<State>,
+    nonce: u32,
+    deposit: PendingCkBtcDeposit,
+) -> Result<Erc20MintStatus, Erc20MintError> {
+    let sender = Id256::from(&deposit.caller);
+    let mint_order =
+        match prepare_mint_order(state, sender, deposit.eth_address, deposit.amount, nonce).await {
+            Ok(mint_order) => mint_order,
+            Err(err) => {
+                BtcTask::RetryCkBtcDeposits.append_unique(
+                    &*get_scheduler().borrow(),
+                    BtcTask::retry_ckbtc_deposits_options(),
+                );
+                return Err(err);
+            }
+        };
+
+    // The order may be signed concurrently by the deposit call and the retry task. Only the
+    // first one removing the pending deposit stores and sends its order.
+    if state
+        .borrow_mut()
+        .pending_ckbtc_deposits_mut()
+        .remove(nonce)
+        .is_none()
+    {
+        let stored_order = state.borrow().mint_orders().get(sender, nonce);
+        return Ok(Erc20MintStatus::Signed(Box::new(
+            stored_order.unwrap_or(mint_order),
+        )));
+    }
+    store_mint_order(state, mint_order, sender, nonce);
+
+    Ok(send_and_report(state, mint_order, deposit.amount).await)
+}
+
+async fn transfer_ckbtc_from_caller(
+    state: &RefCell<State>,
+    caller: Principal,
+    amount: u64,
+) -> Result<Nat, TransferFromError> {
+    let (ledger, fee) = {
+        let state_ref = state.borrow();
+        (state_ref.ck_btc_ledger(), state_ref.ck_btc_ledger_fee())
+    };
+
+    let args = TransferFromArgs {
+        spender_subaccount: None,
+        from: IcrcAccount {
+            owner: caller,
+            subaccount: None,
+        },
+        to: IcrcAccount {
+            owner: ic::id(),
+            subaccount: None,
+        },
+        amount: amount.into(),
+        fee: Some(fee.into()),
+        memo: None,
+        created_at_time: None,
+    };
+
+    virtual_canister_call!(ledger, "icrc2_transfer_from", (args,), Result<Nat, TransferFromError>)
+        .await
+        .unwrap_or(Err(TransferFromError::TemporarilyUnavailable))
+}
+
+/// Sends the mint order to the EVM, returning the signed order if it cannot be sent.
+async fn send_and_report(
+    state: &RefCell<State>,
+    mint_order: SignedMintOrder,
+    amount: u64,
+) -> Erc20MintStatus {
+    match send_mint_order(state, mint_order).await {
+        Ok(tx_id) => Erc20MintStatus::Minted { amount, tx_id },
         Err(err) => {
             log::warn!("Failed to send mint order: {err:?}");
             Erc20MintStatus::Signed(Box::new(mint_order))
         }
-    })
+    }
 }
 
 async fn transfer_ckbtc_from_subaccount(
@@ -152,7 +520,7 @@ async fn transfer_ckbtc_from_subaccount(
     };
 
     let args = TransferArg {
-        from_subaccount: Some(eth_address_to_subaccount(eth_address).0),
+        from_subaccount: Some(eth_address_to_subaccount(eth_address)),
         to: ic_exports::icrc_types::icrc1::account::Account {
             owner: ic::id(),
             subaccount: None,
@@ -170,6 +538,7 @@ async fn transfer_ckbtc_from_subaccount(
 
 async fn prepare_mint_order(
     state: &RefCell<State>,
+    sender: Id256,
     eth_address: H160,
     amount: u64,
     nonce: u32,
@@ -180,7 +549,6 @@ async fn prepare_mint_order(
         let state_ref = state.borrow();
 
         let sender_chain_id = state_ref.btc_chain_id();
-        let sender = Id256::from_evm_address(&eth_address, sender_chain_id);
         let src_token = (&state_ref.ck_btc_ledger()).into();
 
         let recipient_chain_id = state_ref.erc20_chain_id();
@@ -218,15 +586,14 @@ async fn prepare_mint_order(
 fn store_mint_order(
     state: &RefCell<State>,
     signed_mint_order: SignedMintOrder,
-    eth_address: &H160,
+    sender: Id256,
     nonce: u32,
 ) {
     let mut state = state.borrow_mut();
-    let sender_chain_id = state.btc_chain_id();
-    let sender = Id256::from_evm_address(eth_address, sender_chain_id);
     state
         .mint_orders_mut()
         .push(sender, nonce, signed_mint_order);
+    state.deposit_statuses_mut().insert_pending(&sender, nonce);
 
     log::trace!("Mint order added");
 }
@@ -237,13 +604,133 @@ async fn send_mint_order(
 ) -> Result<H256, Erc20MintError> {
     log::trace!("Sending mint transaction");
 
+    let id = send_bridge_transaction(
+        state,
+        GasOperation::Mint,
+        |sender, bridge, nonce, gas_price, gas_limit, chain_id| {
+            minter_contract_utils::bft_bridge_api::mint_transaction(
+                sender,
+                bridge,
+                nonce,
+                gas_price,
+                gas_limit,
+                &mint_order.to_vec(),
+                chain_id,
+            )
+        },
+    )
+    .await?;
+
+    log::trace!("Mint transaction sent");
+
+    Ok(id)
+}
+
+/// Sends a transaction updating the metadata of the wrapped token, and stores the new metadata in
+/// the BftBridge config, so the following mint orders carry it too.
+pub async fn update_token_metadata(
+    state: &RefCell<State>,
+    name: [u8; 32],
+    symbol: [u8; 16],
+    decimals: u8,
+) -> Result<H256, Erc20MintError> {
+    log::trace!("Sending token metadata update transaction");
+
+    let token_address = state.borrow().token_address().clone();
+    let id = send_bridge_transaction(
+        state,
+        GasOperation::UpdateTokenMetadata,
+        |sender, bridge, nonce, gas_price, gas_limit, chain_id| {
+            minter_contract_utils::bft_bridge_api::update_wrapped_token_metadata_transaction(
+                sender,
+                bridge,
+                nonce,
+                gas_price,
+                gas_limit,
+                token_address.0,
+                name,
+                symbol,
+                decimals,
+                chain_id,
+            )
+        },
+    )
+    .await?;
+
+    state
+        .borrow_mut()
+        .set_token_metadata(name, symbol, decimals);
+
+    log::trace!("Token metadata update transaction sent");
+
+    Ok(id)
+}
+
+/// Relays the burn of the wrapped tokens approved with the `permit`. Returns the hash of the
+/// transaction.
+pub async fn burn_with_permit(
+    state: &RefCell<State>,
+    permit: BurnPermit,
+) -> Result<H256, BurnPermitError> {
+    let now = state.borrow().clock().now_secs();
+    permit.check_deadline(now)?;
+
+    let (evm_info, token_address) = {
+        let state = state.borrow();
+        (state.get_evm_info(), state.token_address().clone())
+    };
+    if permit.from_erc20 != token_address {
+        return Err(BurnPermitError::UnknownToken(permit.from_erc20));
+    }
+
+    let signer = state.borrow().signer().get().clone();
+    let sender = signer
+        .get_address()
+        .await
+        .map_err(|err| BurnPermitError::Sign(format!("{err:?}")))?;
+    let client = evm_info.link.get_json_rpc_client();
+    permit
+        .simulate(&client, &sender, &evm_info.bridge_contract)
+        .await?;
+
+    send_bridge_transaction(
+        state,
+        GasOperation::BurnWithPermit,
+        |sender, bridge, nonce, gas_price, gas_limit, chain_id| {
+            minter_contract_utils::bft_bridge_api::burn_with_permit_transaction(
+                sender, bridge, nonce, gas_price, gas_limit, &permit, chain_id,
+            )
+        },
+    )
+    .await
+    .map_err(|err| match err {
+        Erc20MintError::NotInitialized => BurnPermitError::NotInitialized,
+        Erc20MintError::Sign(err) => BurnPermitError::Sign(err),
+        err => BurnPermitError::Evm(format!("{err:?}")),
+    })
+}
+
+/// Signs the transaction to the BftBridge contract built by `build_tx` from the sender, bridge
+/// address, nonce, gas price, gas limit of the `operation` and chain id, and sends it to the EVM.
+async fn send_bridge_transaction(
+    state: &RefCell<State>,
+    operation: GasOperation,
+    build_tx: impl FnOnce(
+        ethers_core::types::H160,
+        ethers_core::types::H160,
+        ethers_core::types::U256,
+        ethers_core::types::U256,
+        u64,
+        u32,
+    ) -> ethers_core::types::Transaction,
+) -> Result<H256, Erc20MintError> {
     let signer = state.borrow().signer().get().clone();
     let sender = signer
         .get_address()
         .await
         .map_err(|err| Erc20MintError::Sign(format!("{err:?}")))?;
 
-    let (evm_info, evm_params) = {
+    let (evm_info, evm_params, gas_price, gas_limit) = {
         let state = state.borrow();
 
         let evm_info = state.get_evm_info();
@@ -251,16 +738,20 @@ async fn send_mint_order(
             .get_evm_params()
             .clone()
             .ok_or(Erc20MintError::NotInitialized)?;
+        let gas_price = state
+            .gas_price
+            .gas_price(evm_params.gas_price.clone(), state.clock().now());
+        let gas_limit = state.gas_limits.gas_limit(operation);
 
-        (evm_info, evm_params)
+        (evm_info, evm_params, gas_price, gas_limit)
     };
 
-    let mut tx = minter_contract_utils::bft_bridge_api::mint_transaction(
+    let mut tx = build_tx(
         sender.0,
         evm_info.bridge_contract.0,
         evm_params.nonce.into(),
-        evm_params.gas_price.into(),
-        &mint_order.to_vec(),
+        gas_price.into(),
+        gas_limit,
         evm_params.chain_id as _,
     );
 
@@ -275,10 +766,14 @@ async fn send_mint_order(
     tx.hash = tx.hash();
 
     let client = evm_info.link.get_json_rpc_client();
-    let id = client
-        .send_raw_transaction(tx)
-        .await
-        .map_err(|err| Erc20MintError::Evm(format!("{err:?}")))?;
+    let id = client.send_raw_transaction(tx).await.map_err(|err| {
+        let err = format!("{err:?}");
+        state
+            .borrow_mut()
+            .gas_limits
+            .on_transaction_error(operation, &err);
+        Erc20MintError::Evm(err)
+    })?;
 
     state.borrow_mut().update_evm_params(|p| {
         if let Some(params) = p.as_mut() {
@@ -286,11 +781,97 @@ async fn send_mint_order(
         }
     });
 
-    log::trace!("Mint transaction sent");
-
     Ok(id.into())
 }
 
+/// Deploys the BftBridge with the EVM address of the bridge as the minter. The bridge address
+/// is configured once [`check_bft_bridge_deployment`] finds the deployed contract.
+pub async fn deploy_bft_bridge(
+    state: &RefCell<State>,
+    fee_charge: H160,
+) -> minter_did::error::Result<BftBridgeDeployStatus> {
+    if let Some(status) = &state.borrow().bft_deploy_status {
+        return Err(minter_did::error::Error::Internal(format!(
+            "BftBridge deployment already started: {status:?}"
+        )));
+    }
+
+    let signer = state.borrow().signer().get().clone();
+    let sender = signer
+        .get_address()
+        .await
+        .map_err(|err| minter_did::error::Error::Internal(format!("{err:?}")))?;
+
+    let (evm_info, evm_params, gas_price) = {
+        let state = state.borrow();
+        let evm_params = state.get_evm_params().clone().ok_or_else(|| {
+            minter_did::error::Error::Internal("EVM params are not initialized".into())
+        })?;
+        let gas_price = state
+            .gas_price
+            .gas_price(evm_params.gas_price.clone(), state.clock().now());
+        (state.get_evm_info(), evm_params, gas_price)
+    };
+
+    let deployment = BftBridgeDeployment::new(
+        sender.0,
+        evm_params.nonce,
+        gas_price.into(),
+        evm_params.chain_id,
+        fee_charge.0,
+        true,
+    )
+    .map_err(minter_did::error::Error::Internal)?;
+
+    let client = evm_info.link.get_json_rpc_client();
+    let result = deployment.send(&signer, &client).await;
+
+    let sent_transactions = match &result {
+        Ok(_) => 2,
+        Err(err) => err.sent_transactions,
+    };
+    let mut state = state.borrow_mut();
+    state.update_evm_params(|p| {
+        if let Some(params) = p.as_mut() {
+            params.nonce += sent_transactions;
+        }
+    });
+
+    let status = result.map_err(|err| minter_did::error::Error::Internal(err.reason))?;
+    state.bft_deploy_status = Some(status.clone());
+    Ok(status)
+}
+
+/// Checks if the BftBridge deployed by the bridge has code, and configures its address as the
+/// bridge address once it has.
+pub async fn check_bft_bridge_deployment(
+    state: &RefCell<State>,
+) -> minter_did::error::Result<BftBridgeDeployStatus> {
+    let (status, evm_info) = {
+        let state = state.borrow();
+        let status = state.bft_deploy_status.clone().ok_or_else(|| {
+            minter_did::error::Error::Internal("BftBridge deployment is not started".into())
+        })?;
+        (status, state.get_evm_info())
+    };
+
+    let client = evm_info.link.get_json_rpc_client();
+    let status = check_deployment(&client, status)
+        .await
+        .map_err(minter_did::error::Error::Internal)?;
+
+    let mut state = state.borrow_mut();
+    if status.is_deployed() && &state.bft_config.bridge_address != status.proxy() {
+        log::info!("BftBridge deployed at {}", status.proxy());
+        let mut bft_config = state.bft_config.clone();
+        bft_config.bridge_address = status.proxy().clone();
+        state.configure_bft(bft_config);
+    }
+    state.bft_deploy_status = Some(status.clone());
+
+    Ok(status)
+}
+
 pub(crate) async fn burn_ckbtc(
     state: &RefCell<State>,
     request_id: u32,
@@ -330,6 +911,117 @@ pub(crate) async fn burn_ckbtc(
     result
 }
 
+/// Estimates the fees of a withdrawal of the burned `amount`, or of a typical withdrawal if the
+/// amount is not given. The ckBTC minter values are cached, except the fee of a given amount.
+pub(crate) async fn estimate_withdrawal_fee(
+    state: &RefCell<State>,
+    amount: Option<u64>,
+) -> Result<WithdrawalFeeEstimate, RetrieveBtcError> {
+    let now = state.borrow().clock().now();
+    let (ck_btc_minter, ledger_fee, cached_info, cached_fee) = {
+        let state = state.borrow();
+        let cache = state.withdrawal_fee_cache();
+        (
+            state.ck_btc_minter(),
+            state.ck_btc_ledger_fee(),
+            cache.minter_info(now),
+            cache.default_fee(now),
+        )
+    };
+
+    let info = match cached_info {
+        Some(info) => info,
+        None => {
+            let info = get_ckbtc_minter_info(ck_btc_minter).await?;
+            state
+                .borrow_mut()
+                .withdrawal_fee_cache_mut()
+                .set_minter_info(info, now);
+            info
+        }
+    };
+
+    let fee = match (amount, cached_fee) {
+        (None, Some(fee)) => fee,
+        (None, None) => {
+            let fee = get_ckbtc_withdrawal_fee(ck_btc_minter, None).await?;
+            state
+                .borrow_mut()
+                .withdrawal_fee_cache_mut()
+                .set_default_fee(fee, now);
+            fee
+        }
+        // The ledger fee is taken before the amount is sent to the ckBTC minter.
+        (Some(amount), _) => {
+            get_ckbtc_withdrawal_fee(ck_btc_minter, Some(amount.saturating_sub(ledger_fee))).await?
+        }
+    };
+
+    Ok(WithdrawalFeeEstimate::new(ledger_fee, fee, info, now))
+}
+
+/// Minimum amount of a burn which is withdrawn by the ckBTC minter.
+pub(crate) async fn min_burn_amount(state: &RefCell<State>) -> Result<u64, RetrieveBtcError> {
+    let now = state.borrow().clock().now();
+    let (ck_btc_minter, ledger_fee, cached_info) = {
+        let state = state.borrow();
+        (
+            state.ck_btc_minter(),
+            state.ck_btc_ledger_fee(),
+            state.withdrawal_fee_cache().minter_info(now),
+        )
+    };
+
+    let info = match cached_info {
+        Some(info) => info,
+        None => {
+            let info = get_ckbtc_minter_info(ck_btc_minter).await?;
+            state
+                .borrow_mut()
+                .withdrawal_fee_cache_mut()
+                .set_minter_info(info, now);
+            info
+        }
+    };
+
+    Ok(withdrawal_fee::min_burn_amount(ledger_fee, info))
+}
+
+async fn get_ckbtc_minter_info(ckbtc_minter: Principal) -> Result<MinterInfo, RetrieveBtcError> {
+    virtual_canister_call!(ckbtc_minter, "get_minter_info", (), MinterInfo)
+        .await
+        .map_err(|err| {
+            log::error!("Failed to get ckBTC minter info: {err:?}");
+            RetrieveBtcError::TemporarilyUnavailable("get minter info".to_string())
+        })
+}
+
+async fn get_ckbtc_withdrawal_fee(
+    ckbtc_minter: Principal,
+    amount: Option<u64>,
+) -> Result<WithdrawalFee, RetrieveBtcError> {
+    let arg = EstimateWithdrawalFeeArgs { amount };
+    virtual_canister_call!(
+        ckbtc_minter,
+        "estimate_withdrawal_fee",
+        (arg,),
+        WithdrawalFee
+    )
+    .await
+    .map_err(|err| {
+        log::error!("Failed to estimate ckBTC withdrawal fee: {err:?}");
+        RetrieveBtcError::TemporarilyUnavailable("estimate withdrawal fee".to_string())
+    })
+}
+
+/// Checks that the ckBTC minter responds to queries.
+pub(crate) async fn check_ckbtc_minter(ckbtc_minter: Principal) -> Result<(), String> {
+    virtual_canister_call!(ckbtc_minter, "get_deposit_fee", (), u64)
+        .await
+        .map(|_| ())
+        .map_err(|err| format!("failed to get deposit fee from ckBTC minter: {err:?}"))
+}
+
 async fn get_ckbtc_withdrawal_account(
     ckbtc_minter: Principal,
 ) -> Result<IcrcAccount, RetrieveBtcError> {
@@ -1,10 +1,11 @@
 use ic_stable_structures::stable_structures::DefaultMemoryImpl;
 use ic_stable_structures::VirtualMemory;
 use minter_contract_utils::mint_orders::MintOrders;
+use minter_contract_utils::pagination::{CursorPage, CursorRequest, InvalidCursor};
 use minter_did::id256::Id256;
 use minter_did::order::SignedMintOrder;
 
-use crate::memory::{MEMORY_MANAGER, MINT_ORDERS_MEMORY_ID};
+use crate::memory::{LEGACY_MINT_ORDERS_MEMORY_ID, MEMORY_MANAGER, MINT_ORDERS_MEMORY_ID};
 
 pub struct MintOrdersStore(MintOrders<VirtualMemory<DefaultMemoryImpl>>);
 
@@ -26,10 +27,39 @@ impl Default for MintOrdersStore {
 }
 
 impl MintOrdersStore {
+    /// Moves the orders stored by the earlier versions of the canister to the current store.
+    pub fn migrate_legacy_orders(&mut self) {
+        let moved = self
+            .0
+            .migrate_from_multimap(MEMORY_MANAGER.with(|mm| mm.get(LEGACY_MINT_ORDERS_MEMORY_ID)));
+        if moved > 0 {
+            log::info!("Moved {moved} mint orders to the current store");
+        }
+    }
+
     pub fn push(&mut self, sender: Id256, nonce: u32, mint_order: SignedMintOrder) {
         self.0.insert(sender, SRC_TOKEN, nonce, mint_order);
     }
 
+    pub fn get(&self, sender: Id256, nonce: u32) -> Option<SignedMintOrder> {
+        self.0.get(sender, SRC_TOKEN, nonce)
+    }
+
+    /// Returns `(nonce, mint_order)` pairs of the orders issued to the sender.
+    pub fn get_all(&self, sender: Id256) -> Vec<(u32, SignedMintOrder)> {
+        self.0.get_all(sender, SRC_TOKEN)
+    }
+
+    /// Returns the requested page of the `(nonce, mint_order)` pairs of the orders issued to the
+    /// sender.
+    pub fn page(
+        &self,
+        sender: Id256,
+        request: &CursorRequest,
+    ) -> Result<CursorPage<(u32, SignedMintOrder)>, InvalidCursor> {
+        self.0.page(sender, SRC_TOKEN, request)
+    }
+
     pub fn remove(&mut self, sender: Id256, nonce: u32) {
         self.0.remove(sender, SRC_TOKEN, nonce);
     }
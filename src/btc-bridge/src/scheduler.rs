@@ -1,6 +1,7 @@
 use std::future::Future;
 use std::pin::Pin;
 
+use candid::{Decode, Encode, Principal};
 use did::{H160, U256};
 use eth_signer::sign_strategy::TransactionSigner;
 use ethers_core::types::Log;
@@ -12,12 +13,24 @@ use ic_task_scheduler::task::{InnerScheduledTask, ScheduledTask, Task, TaskOptio
 use ic_task_scheduler::SchedulerError;
 use jsonrpc_core::Id;
 use minter_contract_utils::bft_bridge_api::{BridgeEvent, BurntEventData, MintedEventData};
+use minter_contract_utils::bridge_tx_log::BridgeTransaction;
+use minter_contract_utils::btc_address::parse_btc_address_bytes;
+use minter_contract_utils::event_subscribers::BridgeEventNotification;
 use minter_contract_utils::evm_bridge::EvmParams;
+use minter_contract_utils::gas_price;
+use minter_contract_utils::health::{self, EVM_RPC, SIGNER};
+use minter_contract_utils::mint_completion::MintTx;
 use minter_contract_utils::query::{self, Query, QueryType, GAS_PRICE_ID, NONCE_ID};
+use minter_contract_utils::soft_caps::{Admission, Lane};
+use minter_contract_utils::task_dedup::TaskKey;
+use minter_contract_utils::task_limits::TaskPriority;
 use minter_did::id256::Id256;
 use serde::{Deserialize, Serialize};
 
-use crate::canister::get_state;
+use crate::canister::{
+    get_bridge_tx_log, get_event_subscribers, get_notified_events, get_soft_cap_store, get_state,
+};
+use crate::state::CKBTC_MINTER;
 
 pub type TasksStorage =
     StableBTreeMap<u32, InnerScheduledTask<BtcTask>, VirtualMemory<DefaultMemoryImpl>>;
@@ -28,9 +41,28 @@ pub type PersistentScheduler = Scheduler<BtcTask, TasksStorage>;
 pub enum BtcTask {
     InitEvmState,
     CollectEvmEvents,
+    /// Removes the mint order of a `Minted` event collected before the mint transactions were
+    /// recorded. The new events are processed by [`BtcTask::CompleteMintOrder`].
     RemoveMintOrder(MintedEventData),
     MintBtc(BurntEventData),
     MintErc20(H160),
+    NotifySubscriber(Principal, BridgeEventNotification),
+    CheckHealth,
+    RefreshGasPrice,
+    /// Removes the mint order of a `Minted` event and marks its deposit as completed by the
+    /// transaction of the event.
+    CompleteMintOrder(MintedEventData, Option<MintTx>),
+    /// Mints the wrapped tokens of a deposit admitted from the soft cap queue.
+    ResumeDeposit {
+        eth_address: H160,
+        amount: u64,
+        nonce: u32,
+    },
+    /// Resumes the queued deposits and withdrawals which fit into the soft caps.
+    DrainSoftCapQueue,
+    /// Signs the mint orders of the ckBTC deposits whose ckBTC are transferred to the bridge
+    /// already, and returns the ckBTC of the deposits which cannot be minted.
+    RetryCkBtcDeposits,
 }
 
 impl BtcTask {
@@ -38,6 +70,66 @@ impl BtcTask {
         ScheduledTask::with_options(self, options)
     }
 
+    /// Name of the task type used to configure the task limits.
+    pub fn task_type(&self) -> &'static str {
+        match self {
+            BtcTask::InitEvmState => "InitEvmState",
+            BtcTask::CollectEvmEvents => "CollectEvmEvents",
+            BtcTask::RemoveMintOrder(_) => "RemoveMintOrder",
+            BtcTask::MintBtc(_) => "MintBtc",
+            BtcTask::MintErc20(_) => "MintErc20",
+            BtcTask::NotifySubscriber(..) => "NotifySubscriber",
+            BtcTask::CheckHealth => "CheckHealth",
+            BtcTask::RefreshGasPrice => "RefreshGasPrice",
+            BtcTask::CompleteMintOrder(..) => "CompleteMintOrder",
+            BtcTask::ResumeDeposit { .. } => "ResumeDeposit",
+            BtcTask::DrainSoftCapQueue => "DrainSoftCapQueue",
+            BtcTask::RetryCkBtcDeposits => "RetryCkBtcDeposits",
+        }
+    }
+
+    fn key(&self) -> TaskKey {
+        TaskKey::new(self.task_type(), self)
+    }
+
+    /// Appends the task to the scheduler, unless the same task is pending already. Returns
+    /// `false` if the task is not appended.
+    pub fn append_unique(
+        self,
+        scheduler: &(impl TaskScheduler<Self> + ?Sized),
+        options: TaskOptions,
+    ) -> bool {
+        if !get_state().borrow_mut().pending_tasks.insert(self.key()) {
+            log::trace!("Task {} is pending already", self.task_type());
+            return false;
+        }
+
+        scheduler.append_task(self.into_scheduled(options));
+        true
+    }
+
+    /// Allows the task to be appended with [`BtcTask::append_unique`] again once it is completed.
+    pub fn release(&self) {
+        get_state().borrow_mut().pending_tasks.remove(&self.key());
+    }
+
+    fn default_priority(&self) -> TaskPriority {
+        match self {
+            BtcTask::InitEvmState
+            | BtcTask::CollectEvmEvents
+            | BtcTask::CheckHealth
+            | BtcTask::RefreshGasPrice => TaskPriority::High,
+            BtcTask::RemoveMintOrder(_)
+            | BtcTask::CompleteMintOrder(..)
+            | BtcTask::MintBtc(_)
+            | BtcTask::DrainSoftCapQueue
+            | BtcTask::RetryCkBtcDeposits => TaskPriority::Normal,
+            BtcTask::MintErc20(_)
+            | BtcTask::ResumeDeposit { .. }
+            | BtcTask::NotifySubscriber(..) => TaskPriority::Low,
+        }
+    }
+
     pub async fn init_evm_state() -> Result<(), SchedulerError> {
         let state = get_state();
         let client = state.borrow().get_evm_info().link.get_json_rpc_client();
@@ -96,6 +188,9 @@ impl BtcTask {
             })
         });
 
+        // The events notified with `notify_burn` are scheduled already.
+        let logs = get_notified_events().filter_scanned(logs, last_block + 1);
+
         log::trace!("appending logs to tasks");
 
         scheduler.append_tasks(logs.into_iter().filter_map(Self::task_by_log).collect());
@@ -105,7 +200,7 @@ impl BtcTask {
         Ok(())
     }
 
-    fn task_by_log(log: Log) -> Option<ScheduledTask<BtcTask>> {
+    pub(crate) fn task_by_log(log: Log) -> Option<ScheduledTask<BtcTask>> {
         log::trace!("creating task from the log: {log:?}");
 
         const TASK_RETRY_DELAY_SECS: u32 = 5;
@@ -116,25 +211,106 @@ impl BtcTask {
             })
             .with_max_retries_policy(u32::MAX);
 
-        match BridgeEvent::from_log(log).into_scheduler_result() {
+        let mint_tx = MintTx::from_log(&log);
+        let evm_tx_hash = log.transaction_hash.map(Into::into);
+        let now = get_state().borrow().clock().now();
+        let task = match BridgeEvent::from_log(log).into_scheduler_result() {
             Ok(BridgeEvent::Burnt(burnt)) => {
+                get_bridge_tx_log().append(BridgeTransaction::burn(&burnt, evm_tx_hash, now));
                 log::debug!("Adding PrepareMintOrder task");
-                let mint_order_task = BtcTask::MintBtc(burnt);
-                return Some(mint_order_task.into_scheduled(options));
+                BtcTask::MintBtc(burnt)
             }
             Ok(BridgeEvent::Minted(minted)) => {
-                log::debug!("Adding RemoveMintOrder task");
-                let remove_mint_order_task = BtcTask::RemoveMintOrder(minted);
-                return Some(remove_mint_order_task.into_scheduled(options));
+                get_bridge_tx_log().append(BridgeTransaction::mint(&minted, evm_tx_hash, now));
+                log::debug!("Adding CompleteMintOrder task");
+                BtcTask::CompleteMintOrder(minted, mint_tx)
             }
             Ok(BridgeEvent::Notify(_)) => todo!(),
-            Err(e) => log::warn!("collected log is incompatible with expected events: {e}"),
-        }
+            Err(e) => {
+                log::warn!("collected log is incompatible with expected events: {e}");
+                return None;
+            }
+        };
 
-        None
+        Some(task.into_scheduled(options))
+    }
+
+    /// Notifies the event subscribers about an event once the task processing it is completed.
+    fn notify_subscribers(
+        task_scheduler: &dyn TaskScheduler<Self>,
+        notification: BridgeEventNotification,
+    ) {
+        task_scheduler.append_tasks(Self::notification_tasks(notification).collect());
+    }
+
+    /// Creates a task for each event subscriber to deliver the `notification`.
+    fn notification_tasks(
+        notification: BridgeEventNotification,
+    ) -> impl Iterator<Item = ScheduledTask<BtcTask>> {
+        const NOTIFICATION_RETRIES: u32 = 5;
+        const NOTIFICATION_RETRY_DELAY_SECS: u32 = 2;
+        const NOTIFICATION_RETRY_MULTIPLIER: u32 = 2;
+
+        let options = TaskOptions::default()
+            .with_max_retries_policy(NOTIFICATION_RETRIES)
+            .with_backoff_policy(BackoffPolicy::Exponential {
+                secs: NOTIFICATION_RETRY_DELAY_SECS,
+                multiplier: NOTIFICATION_RETRY_MULTIPLIER,
+            });
+
+        get_event_subscribers()
+            .get_all()
+            .into_iter()
+            .map(move |subscriber| {
+                BtcTask::NotifySubscriber(subscriber, notification.clone())
+                    .into_scheduled(options.clone())
+            })
     }
 
-    fn remove_mint_order(minted_event: MintedEventData) -> Result<(), SchedulerError> {
+    /// Checks the dependencies of the bridge and records the results in the health monitor.
+    async fn check_health() -> Result<(), SchedulerError> {
+        let state = get_state();
+        let (evm_link, signer, ck_btc_minter) = {
+            let state = state.borrow();
+            (
+                state.get_evm_info().link,
+                state.signer().get().clone(),
+                state.ck_btc_minter(),
+            )
+        };
+
+        let evm_result = health::check_evm_link(&evm_link).await;
+        let minter_result = crate::ops::check_ckbtc_minter(ck_btc_minter).await;
+        let signer_result = health::check_signer(&signer).await;
+
+        let now = state.borrow().clock().now();
+        let mut state = state.borrow_mut();
+        state.health.record(EVM_RPC, evm_result, now);
+        state.health.record(CKBTC_MINTER, minter_result, now);
+        state.health.record(SIGNER, signer_result, now);
+
+        Ok(())
+    }
+
+    /// Samples the gas price of the EVM and updates its estimate.
+    async fn refresh_gas_price() -> Result<(), SchedulerError> {
+        let state = get_state();
+        let client = state.borrow().get_evm_info().link.get_json_rpc_client();
+        let sample = gas_price::sample_gas_price(&client)
+            .await
+            .into_scheduler_result()?;
+
+        log::trace!("Sampled gas price: {sample:?}");
+        let now = state.borrow().clock().now();
+        state.borrow_mut().gas_price.record(sample, now);
+
+        Ok(())
+    }
+
+    fn complete_mint_order(
+        minted_event: MintedEventData,
+        mint_tx: Option<MintTx>,
+    ) -> Result<(), SchedulerError> {
         let state = get_state();
         let sender_id = Id256::from_slice(&minted_event.sender_id).ok_or_else(|| {
             SchedulerError::TaskExecutionFailed(
@@ -142,16 +318,93 @@ impl BtcTask {
             )
         })?;
 
+        let mut state = state.borrow_mut();
         state
-            .borrow_mut()
             .mint_orders_mut()
             .remove(sender_id, minted_event.nonce);
+        state
+            .deposit_statuses_mut()
+            .complete(&sender_id, minted_event.nonce, mint_tx);
 
         log::trace!("Mint order removed");
 
         Ok(())
     }
 
+    /// Options of [`BtcTask::RetryCkBtcDeposits`]: the signing is retried until it succeeds.
+    pub fn retry_ckbtc_deposits_options() -> TaskOptions {
+        const RETRY_CKBTC_DEPOSITS_DELAY_SECS: u32 = 5;
+        const RETRY_CKBTC_DEPOSITS_MULTIPLIER: u32 = 2;
+
+        TaskOptions::default()
+            .with_retry_policy(ic_task_scheduler::retry::RetryPolicy::Infinite)
+            .with_backoff_policy(BackoffPolicy::Exponential {
+                secs: RETRY_CKBTC_DEPOSITS_DELAY_SECS,
+                multiplier: RETRY_CKBTC_DEPOSITS_MULTIPLIER,
+            })
+    }
+
+    async fn retry_ckbtc_deposits() -> Result<(), SchedulerError> {
+        let pending = get_state().borrow().pending_ckbtc_deposits().get_all();
+        let mut failed = 0;
+        for (nonce, deposit) in pending {
+            let result = crate::ops::sign_ckbtc_deposit(&get_state(), nonce, deposit).await;
+            log::info!("Pending ckBTC deposit {nonce} result from scheduler: {result:?}");
+            if result.is_err() {
+                failed += 1;
+            }
+        }
+
+        let refunds = get_state().borrow().pending_ckbtc_refunds().get_all();
+        for (block_index, _) in refunds {
+            let result = crate::ops::refund_ckbtc_deposit(&get_state(), &block_index).await;
+            log::info!("Pending ckBTC refund {block_index} result from scheduler: {result:?}");
+            if result.is_err() {
+                failed += 1;
+            }
+        }
+
+        if failed > 0 {
+            return Err(SchedulerError::TaskExecutionFailed(format!(
+                "{failed} ckBTC deposits are not signed or refunded"
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn drain_soft_cap_queue(
+        task_scheduler: Box<dyn 'static + TaskScheduler<Self>>,
+    ) -> Result<(), SchedulerError> {
+        let now = get_state().borrow().clock().now();
+        for entry in get_soft_cap_store().drain(now) {
+            let task = match entry.lane {
+                Lane::Deposit => {
+                    Decode!(&entry.payload, H160, u64, u32).map(|(eth_address, amount, nonce)| {
+                        BtcTask::ResumeDeposit {
+                            eth_address,
+                            amount,
+                            nonce,
+                        }
+                    })
+                }
+                Lane::Withdrawal => Decode!(&entry.payload, BurntEventData).map(BtcTask::MintBtc),
+            };
+
+            match task {
+                Ok(task) => {
+                    task.append_unique(&*task_scheduler, TaskOptions::default());
+                }
+                Err(err) => log::error!(
+                    "Invalid payload of the queued operation {}: {err}",
+                    entry.ticket
+                ),
+            }
+        }
+
+        Ok(())
+    }
+
     pub async fn update_evm_params() -> Result<(), SchedulerError> {
         let state = get_state();
         let evm_info = state.borrow().get_evm_info();
@@ -206,13 +459,64 @@ impl Task for BtcTask {
     fn execute(
         &self,
         task_scheduler: Box<dyn 'static + TaskScheduler<Self>>,
+    ) -> Pin<Box<dyn Future<Output = Result<(), SchedulerError>>>> {
+        let permit = get_state()
+            .borrow()
+            .task_limiter
+            .try_acquire(self.task_type(), self.default_priority());
+        let permit = match permit {
+            Ok(permit) => permit,
+            Err(reason) => {
+                log::debug!("Task {} is deferred: {reason}", self.task_type());
+                return Box::pin(futures::future::err(SchedulerError::TaskExecutionFailed(
+                    reason.error_message(),
+                )));
+            }
+        };
+
+        let task = self.run(task_scheduler);
+        let this = self.clone();
+        Box::pin(async move {
+            let _permit = permit;
+            let result = task.await;
+            if result.is_ok() {
+                this.release();
+            }
+            result
+        })
+    }
+}
+
+impl BtcTask {
+    fn run(
+        &self,
+        task_scheduler: Box<dyn 'static + TaskScheduler<Self>>,
     ) -> Pin<Box<dyn Future<Output = Result<(), SchedulerError>>>> {
         match self {
             BtcTask::InitEvmState => Box::pin(Self::init_evm_state()),
             BtcTask::CollectEvmEvents => Box::pin(Self::collect_evm_events(task_scheduler)),
             BtcTask::RemoveMintOrder(data) => {
                 let data = data.clone();
-                Box::pin(async move { Self::remove_mint_order(data) })
+                Box::pin(async move {
+                    Self::complete_mint_order(data.clone(), None)?;
+                    Self::notify_subscribers(
+                        &*task_scheduler,
+                        BridgeEventNotification::Minted(data),
+                    );
+                    Ok(())
+                })
+            }
+            BtcTask::CompleteMintOrder(data, mint_tx) => {
+                let data = data.clone();
+                let mint_tx = mint_tx.clone();
+                Box::pin(async move {
+                    Self::complete_mint_order(data.clone(), mint_tx)?;
+                    Self::notify_subscribers(
+                        &*task_scheduler,
+                        BridgeEventNotification::Minted(data),
+                    );
+                    Ok(())
+                })
             }
             BtcTask::MintErc20(address) => {
                 let address = address.clone();
@@ -227,23 +531,81 @@ impl Task for BtcTask {
                     Ok(())
                 })
             }
-            BtcTask::MintBtc(BurntEventData {
-                operation_id,
-                recipient_id,
+            BtcTask::ResumeDeposit {
+                eth_address,
                 amount,
-                ..
-            }) => {
+                nonce,
+            } => {
+                let (eth_address, amount, nonce) = (eth_address.clone(), *amount, *nonce);
+                Box::pin(async move {
+                    Self::update_evm_params().await?;
+
+                    let result =
+                        crate::ops::mint_erc20(&get_state(), eth_address, amount, nonce).await;
+                    log::info!("Queued ERC20 mint result from scheduler: {result:?}");
+
+                    Ok(())
+                })
+            }
+            BtcTask::DrainSoftCapQueue => {
+                Box::pin(async move { Self::drain_soft_cap_queue(task_scheduler) })
+            }
+            BtcTask::RetryCkBtcDeposits => Box::pin(Self::retry_ckbtc_deposits()),
+            BtcTask::NotifySubscriber(subscriber, notification) => {
+                let result = notification.send(*subscriber).into_scheduler_result();
+                Box::pin(futures::future::ready(result))
+            }
+            BtcTask::CheckHealth => Box::pin(Self::check_health()),
+            BtcTask::RefreshGasPrice => Box::pin(Self::refresh_gas_price()),
+            BtcTask::MintBtc(
+                burnt @ BurntEventData {
+                    operation_id,
+                    recipient_id,
+                    amount,
+                    ..
+                },
+            ) => {
                 log::info!("ERC20 burn event received");
 
                 let amount = amount.0.as_u64();
                 let operation_id = *operation_id;
-
-                let Ok(address) = String::from_utf8(recipient_id.clone()) else {
-                    return Box::pin(futures::future::err(SchedulerError::TaskExecutionFailed(
-                        "Failed to decode recipient address".to_string(),
-                    )));
+                let burnt = burnt.clone();
+
+                // The burn with an invalid recipient can never be withdrawn, so it is recorded as
+                // failed instead of being retried.
+                let network = get_state().borrow().network().network();
+                let address = match parse_btc_address_bytes(recipient_id, network) {
+                    Ok(address) => address.to_string(),
+                    Err(err) => {
+                        log::error!(
+                            "Withdrawal {operation_id} failed: invalid recipient address: {err}"
+                        );
+                        get_state()
+                            .borrow_mut()
+                            .burn_request_store_mut()
+                            .set_failed(
+                                operation_id,
+                                String::from_utf8_lossy(recipient_id).into_owned(),
+                                amount,
+                                format!("invalid recipient address: {err}"),
+                            );
+                        return Box::pin(futures::future::ok(()));
+                    }
                 };
 
+                let payload = Encode!(&burnt).expect("serialization failed");
+                let admission = get_soft_cap_store().admit(
+                    &crate::ops::withdrawal_ticket(operation_id),
+                    Lane::Withdrawal,
+                    amount.into(),
+                    payload,
+                    get_state().borrow().clock().now(),
+                );
+                if let Admission::Queued(position) = admission {
+                    log::info!("Withdrawal {operation_id} is queued: {position:?}");
+                    return Box::pin(futures::future::ok(()));
+                }
+
                 Box::pin(async move {
                     let result =
                         crate::ops::burn_ckbtc(&get_state(), operation_id, &address, amount)
@@ -257,6 +619,10 @@ impl Task for BtcTask {
                         result.block_index
                     );
 
+                    Self::notify_subscribers(
+                        &*task_scheduler,
+                        BridgeEventNotification::Burnt(burnt),
+                    );
                     Ok(())
                 })
             }
@@ -1,20 +1,42 @@
-use candid::{CandidType, Principal};
+use std::borrow::Cow;
+use std::rc::Rc;
+
+use candid::{CandidType, Decode, Encode, Principal};
 use did::H160;
-use eth_signer::sign_strategy::{SigningStrategy, TxSigner};
-use ic_exports::ic_cdk::api::management_canister::bitcoin::BitcoinNetwork;
+use eth_signer::sign_strategy::{SigningStrategy, TransactionSigner, TxSigner};
 use ic_log::{init_log, LogSettings};
 use ic_stable_structures::stable_structures::DefaultMemoryImpl;
-use ic_stable_structures::{StableCell, VirtualMemory};
+use ic_stable_structures::{Bound, CellStructure, StableCell, Storable, VirtualMemory};
+use minter_contract_utils::bft_bridge_deploy::BftBridgeDeployStatus;
+use minter_contract_utils::btc_network::BtcNetwork;
+use minter_contract_utils::certified_data::{self, CONFIG_LABEL};
+use minter_contract_utils::clock::{Clock, IcClock};
+use minter_contract_utils::config_validation::{
+    format_config_errors, ConfigError, ConfigValidator,
+};
+use minter_contract_utils::confirmation_policy::{ConfirmationPolicy, ConfirmationTier};
+use minter_contract_utils::erc20_metadata::Erc20Metadata;
 use minter_contract_utils::evm_bridge::{EvmInfo, EvmParams};
 use minter_contract_utils::evm_link::EvmLink;
+use minter_contract_utils::gas_limits::GasLimits;
+use minter_contract_utils::gas_price::GasPriceSampler;
+use minter_contract_utils::health::{self, HealthMonitor, EVM_RPC, SIGNER};
+use minter_contract_utils::task_dedup::PendingTasks;
+use minter_contract_utils::task_limits::{TaskLimiter, TaskLimits};
 use serde::Deserialize;
 
 use crate::burn_request_store::BurnRequestStore;
-use crate::memory::{MEMORY_MANAGER, SIGNER_MEMORY_ID};
+use crate::deposit_accounts::DepositAddressStore;
+use crate::deposit_store::{DepositStatusStore, PendingCkBtcDeposits, PendingCkBtcRefunds};
+use crate::memory::{CONFIG_MEMORY_ID, MEMORY_MANAGER, SIGNER_MEMORY_ID, TASK_LIMITS_MEMORY_ID};
 use crate::orders_store::MintOrdersStore;
-use crate::{MAINNET_CHAIN_ID, REGTEST_CHAIN_ID, TESTNET_CHAIN_ID};
+use crate::withdrawal_fee::WithdrawalFeeCache;
+
+/// Name of the ckBTC minter canister in the health report.
+pub const CKBTC_MINTER: &str = "ckbtc_minter";
 
 type SignerStorage = StableCell<TxSigner, VirtualMemory<DefaultMemoryImpl>>;
+type ConfigStorage = StableCell<StoredConfig, VirtualMemory<DefaultMemoryImpl>>;
 
 pub struct State {
     pub config: BtcBridgeConfig,
@@ -22,19 +44,35 @@ pub struct State {
     pub signer: SignerStorage,
     pub orders_store: MintOrdersStore,
     pub burn_request_store: BurnRequestStore,
+    pub deposit_addresses: DepositAddressStore,
+    pub deposit_statuses: DepositStatusStore,
+    pub pending_ckbtc_deposits: PendingCkBtcDeposits,
+    pub pending_ckbtc_refunds: PendingCkBtcRefunds,
     pub evm_params: Option<EvmParams>,
+    pub task_limiter: TaskLimiter<VirtualMemory<DefaultMemoryImpl>>,
+    pub pending_tasks: PendingTasks,
+    pub health: HealthMonitor,
+    pub gas_price: GasPriceSampler,
+    pub gas_limits: GasLimits,
+    /// BftBridge deployment started by the bridge, if any.
+    pub bft_deploy_status: Option<BftBridgeDeployStatus>,
+    pub withdrawal_fee_cache: WithdrawalFeeCache,
+    pub clock: Rc<dyn Clock>,
 }
 
-#[derive(Debug, CandidType, Deserialize)]
+#[derive(Debug, Clone, CandidType, Deserialize)]
 pub struct BtcBridgeConfig {
     pub ck_btc_minter: Principal,
     pub ck_btc_ledger: Principal,
-    pub network: BitcoinNetwork,
+    pub network: BtcNetwork,
     pub evm_link: EvmLink,
     pub signing_strategy: SigningStrategy,
     pub admin: Principal,
     pub ck_btc_ledger_fee: u64,
     pub log_settings: LogSettings,
+    /// Confirmations required for larger deposits on top of the ckBTC minter requirements. The
+    /// deposit amount is the total sats value of the deposit utxos that are not yet confirmed.
+    pub confirmation_tiers: Vec<ConfirmationTier>,
 }
 
 impl Default for BtcBridgeConfig {
@@ -42,7 +80,7 @@ impl Default for BtcBridgeConfig {
         Self {
             ck_btc_minter: Principal::anonymous(),
             ck_btc_ledger: Principal::anonymous(),
-            network: BitcoinNetwork::Regtest,
+            network: BtcNetwork::Regtest,
             evm_link: EvmLink::default(),
             signing_strategy: SigningStrategy::Local {
                 private_key: [0; 32],
@@ -50,11 +88,54 @@ impl Default for BtcBridgeConfig {
             admin: Principal::management_canister(),
             ck_btc_ledger_fee: 10,
             log_settings: LogSettings::default(),
+            confirmation_tiers: vec![],
         }
     }
 }
 
-#[derive(Default, Debug, CandidType, Deserialize)]
+impl BtcBridgeConfig {
+    /// Checks the configuration, returning all the errors found.
+    pub fn validate(&self) -> Result<(), Vec<ConfigError>> {
+        let mut validator = ConfigValidator::new();
+        validator
+            .principal("admin", self.admin)
+            .canister("ck_btc_minter", self.ck_btc_minter)
+            .canister("ck_btc_ledger", self.ck_btc_ledger)
+            .evm_link("evm_link", &self.evm_link)
+            .check(
+                "confirmation_tiers",
+                ConfirmationPolicy::new(0, self.confirmation_tiers.clone()).validate(),
+            );
+
+        if self.ck_btc_minter == self.ck_btc_ledger {
+            validator.conflict(
+                &["ck_btc_minter", "ck_btc_ledger"],
+                "ckBTC minter and ledger must be different canisters",
+            );
+        }
+
+        validator.finish()
+    }
+
+    /// Checks the configuration and, if it is valid, checks the EVM of its link against the
+    /// current BftBridge configuration of the bridge, if any.
+    pub async fn validate_with_evm(
+        &self,
+        bft_config: &BftBridgeConfig,
+    ) -> Result<(), Vec<ConfigError>> {
+        self.validate()?;
+
+        if bft_config.erc20_chain_id == 0 {
+            let mut validator = ConfigValidator::new();
+            validator.check("evm_link", health::check_evm_link(&self.evm_link).await);
+            return validator.finish();
+        }
+
+        bft_config.validate_with_evm(&self.evm_link).await
+    }
+}
+
+#[derive(Default, Debug, Clone, CandidType, Deserialize)]
 pub struct BftBridgeConfig {
     pub erc20_chain_id: u32,
     pub bridge_address: H160,
@@ -64,6 +145,131 @@ pub struct BftBridgeConfig {
     pub decimals: u8,
 }
 
+impl BftBridgeConfig {
+    /// Checks the configuration against the EVM at the `evm_link`: the chain id of the EVM and
+    /// the code of the bridge and the token contracts.
+    pub async fn validate_with_evm(&self, evm_link: &EvmLink) -> Result<(), Vec<ConfigError>> {
+        let client = evm_link.get_json_rpc_client();
+        let mut validator = ConfigValidator::new();
+        validator
+            .evm_chain_id("erc20_chain_id", &client, self.erc20_chain_id.into())
+            .await;
+        for (field, address) in [
+            ("bridge_address", &self.bridge_address),
+            ("token_address", &self.token_address),
+        ] {
+            if address.0.is_zero() {
+                validator.evm_address(field, address);
+            } else {
+                validator.contract_code(field, &client, address).await;
+            }
+        }
+
+        validator.finish()
+    }
+
+    /// Checks with `eth_call` that the contracts of the configuration are wired to the canister:
+    /// the BftBridge mints with the EVM address of the `signer`, and the token is deployed by the
+    /// BftBridge or is an ERC20 with the metadata of the configuration. The contracts must be
+    /// checked with [`Self::validate_with_evm`] first.
+    pub async fn validate_wiring(
+        &self,
+        evm_link: &EvmLink,
+        signer: &TxSigner,
+    ) -> Result<(), Vec<ConfigError>> {
+        let client = evm_link.get_json_rpc_client();
+        let mut validator = ConfigValidator::new();
+        match signer.get_address().await {
+            Ok(minter_address) => {
+                validator
+                    .bridge_minter(
+                        "bridge_address",
+                        &client,
+                        &self.bridge_address,
+                        &minter_address,
+                    )
+                    .await;
+            }
+            Err(err) => {
+                validator.invalid(
+                    "bridge_address",
+                    format!("failed to get EVM address of the canister: {err}"),
+                );
+            }
+        }
+        validator
+            .bridge_token(
+                "token_address",
+                &client,
+                &self.bridge_address,
+                &self.token_address,
+                &self.token_metadata(),
+            )
+            .await;
+
+        validator.finish()
+    }
+
+    fn token_metadata(&self) -> Erc20Metadata {
+        let from_bytes = |bytes: &[u8]| {
+            String::from_utf8_lossy(bytes)
+                .trim_end_matches('\0')
+                .to_string()
+        };
+        Erc20Metadata {
+            name: from_bytes(&self.token_name),
+            symbol: from_bytes(&self.token_symbol),
+            decimals: self.decimals,
+        }
+    }
+}
+
+/// Configuration of the bridge kept in the stable memory, so it is restored after upgrades.
+#[derive(Default, Debug, Clone, CandidType, Deserialize)]
+struct StoredConfig {
+    config: BtcBridgeConfig,
+    bft_config: BftBridgeConfig,
+}
+
+impl Storable for StoredConfig {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("serialization failed"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).expect("deserialization failed")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+fn config_storage() -> ConfigStorage {
+    ConfigStorage::new(
+        MEMORY_MANAGER.with(|mm| mm.get(CONFIG_MEMORY_ID)),
+        StoredConfig::default(),
+    )
+    .expect("failed to initialize config in stable memory")
+}
+
+/// Configuration of the bridge returned by the `get_bridge_config` query. The signing strategy is
+/// not exposed.
+#[derive(Debug, Clone, CandidType, Deserialize)]
+pub struct BridgeConfigInfo {
+    pub ck_btc_minter: Principal,
+    pub ck_btc_ledger: Principal,
+    pub network: BtcNetwork,
+    pub evm_link: EvmLink,
+    pub admin: Principal,
+    pub ck_btc_ledger_fee: u64,
+    pub confirmation_tiers: Vec<ConfirmationTier>,
+    pub erc20_chain_id: u32,
+    pub bridge_address: H160,
+    pub token_address: H160,
+    pub token_name: [u8; 32],
+    pub token_symbol: [u8; 16],
+    pub decimals: u8,
+}
+
 impl Default for State {
     fn default() -> Self {
         let default_signer = SigningStrategy::Local {
@@ -84,13 +290,31 @@ impl Default for State {
             signer,
             orders_store: Default::default(),
             burn_request_store: Default::default(),
+            deposit_addresses: Default::default(),
+            deposit_statuses: Default::default(),
+            pending_ckbtc_deposits: Default::default(),
+            pending_ckbtc_refunds: Default::default(),
             evm_params: None,
+            task_limiter: TaskLimiter::new(MEMORY_MANAGER.with(|mm| mm.get(TASK_LIMITS_MEMORY_ID))),
+            pending_tasks: Default::default(),
+            health: HealthMonitor::new(&[EVM_RPC, CKBTC_MINTER, SIGNER]),
+            gas_price: GasPriceSampler::default(),
+            gas_limits: GasLimits::default(),
+            bft_deploy_status: None,
+            withdrawal_fee_cache: WithdrawalFeeCache::default(),
+            clock: Rc::new(IcClock),
         }
     }
 }
 
 impl State {
-    pub fn configure(&mut self, config: BtcBridgeConfig) {
+    /// Validates the given configuration and sets it to the state. Fails if the configuration is
+    /// invalid, e.g. if its confirmation tiers are inconsistent.
+    pub fn configure(&mut self, config: BtcBridgeConfig) -> minter_did::error::Result<()> {
+        config
+            .validate()
+            .map_err(|errors| minter_did::error::Error::Internal(format_config_errors(&errors)))?;
+
         let signer = config
             .signing_strategy
             .clone()
@@ -103,10 +327,129 @@ impl State {
         init_log(&config.log_settings).expect("failed to init logger");
 
         self.config = config;
+        self.config_updated();
+        Ok(())
+    }
+
+    /// Replaces the configuration set by [`State::configure`], e.g. by `admin_reconfigure`. The
+    /// signer and the logger are kept, they are changed with their own endpoints. The network
+    /// cannot be changed, since the deposit addresses and the ckBTC canisters depend on it.
+    pub fn reconfigure(&mut self, mut config: BtcBridgeConfig) -> minter_did::error::Result<()> {
+        config
+            .validate()
+            .map_err(|errors| minter_did::error::Error::Internal(format_config_errors(&errors)))?;
+        if config.network != self.config.network {
+            return Err(minter_did::error::Error::Internal(
+                "network of the bridge cannot be changed".to_string(),
+            ));
+        }
+
+        config.signing_strategy = self.config.signing_strategy.clone();
+        config.log_settings = self.config.log_settings.clone();
+        self.config = config;
+        self.config_updated();
+        Ok(())
     }
 
     pub fn configure_bft(&mut self, bft_config: BftBridgeConfig) {
         self.bft_config = bft_config;
+        self.config_updated();
+    }
+
+    /// Sets the metadata of the wrapped token carried by the following mint orders.
+    pub fn set_token_metadata(&mut self, name: [u8; 32], symbol: [u8; 16], decimals: u8) {
+        self.bft_config.token_name = name;
+        self.bft_config.token_symbol = symbol;
+        self.bft_config.decimals = decimals;
+        self.config_updated();
+    }
+
+    /// Replaces the key the mint orders and the EVM transactions are signed with.
+    ///
+    /// The orders signed before keep the signature of the previous key. The EVM address of the
+    /// bridge changes with the key, so the new address must be funded and set as the minter of
+    /// the BftBridge before the new orders can be minted.
+    pub fn set_signing_strategy(
+        &mut self,
+        signing_strategy: SigningStrategy,
+    ) -> minter_did::error::Result<()> {
+        let signer = signing_strategy.clone().make_signer(0).map_err(|err| {
+            minter_did::error::Error::Internal(format!("failed to create signer: {err:?}"))
+        })?;
+        self.signer.set(signer).map_err(|err| {
+            minter_did::error::Error::Internal(format!("failed to store signer: {err:?}"))
+        })?;
+
+        self.config.signing_strategy = signing_strategy;
+        self.config_updated();
+        Ok(())
+    }
+
+    /// Restores the configuration stored in the stable memory. Is called after upgrades, as
+    /// the state is created with the default configuration.
+    pub fn reload_config(&mut self) {
+        let stored = config_storage().get().clone();
+        init_log(&stored.config.log_settings).expect("failed to init logger");
+
+        self.config = stored.config;
+        self.bft_config = stored.bft_config;
+        self.certify_config();
+    }
+
+    fn config_updated(&self) {
+        let stored = StoredConfig {
+            config: self.config.clone(),
+            bft_config: self.bft_config.clone(),
+        };
+        config_storage()
+            .set(stored)
+            .expect("failed to store config in stable memory");
+
+        self.certify_config();
+    }
+
+    pub fn config_info(&self) -> BridgeConfigInfo {
+        BridgeConfigInfo {
+            ck_btc_minter: self.config.ck_btc_minter,
+            ck_btc_ledger: self.config.ck_btc_ledger,
+            network: self.config.network,
+            evm_link: self.config.evm_link.clone(),
+            admin: self.config.admin,
+            ck_btc_ledger_fee: self.config.ck_btc_ledger_fee,
+            confirmation_tiers: self.config.confirmation_tiers.clone(),
+            erc20_chain_id: self.bft_config.erc20_chain_id,
+            bridge_address: self.bft_config.bridge_address.clone(),
+            token_address: self.bft_config.token_address.clone(),
+            token_name: self.bft_config.token_name,
+            token_symbol: self.bft_config.token_symbol,
+            decimals: self.bft_config.decimals,
+        }
+    }
+
+    /// Certifies the configuration returned by `get_bridge_config`, see [`certified_data`].
+    fn certify_config(&self) {
+        certified_data::certify_value(CONFIG_LABEL, &self.config_info());
+    }
+
+    /// Sets priorities and concurrency limits of the scheduler tasks. Returns an error if the
+    /// limits are invalid.
+    pub fn configure_task_limits(&mut self, limits: TaskLimits) -> minter_did::error::Result<()> {
+        limits.validate().map_err(|err| {
+            minter_did::error::Error::Internal(format!("Invalid task limits: {err}"))
+        })?;
+
+        self.task_limiter.set_limits(limits);
+        Ok(())
+    }
+
+    /// Sets gas limits of the EVM transactions. Returns an error if the limits are invalid.
+    pub fn configure_gas_limits(&mut self, limits: GasLimits) -> minter_did::error::Result<()> {
+        limits.validate().map_err(|err| {
+            minter_did::error::Error::Internal(format!("Invalid gas limits: {err}"))
+        })?;
+
+        self.gas_limits = limits;
+        Ok(())
     }
 
     pub fn ck_btc_minter(&self) -> Principal {
@@ -122,11 +465,17 @@ impl State {
     }
 
     pub fn btc_chain_id(&self) -> u32 {
-        match self.config.network {
-            BitcoinNetwork::Mainnet => MAINNET_CHAIN_ID,
-            BitcoinNetwork::Testnet => TESTNET_CHAIN_ID,
-            BitcoinNetwork::Regtest => REGTEST_CHAIN_ID,
-        }
+        self.config.network.chain_id()
+    }
+
+    pub fn network(&self) -> BtcNetwork {
+        self.config.network
+    }
+
+    /// Confirmations requirements depending on the deposit size. Deposits below the lowest tier
+    /// only require confirmations enforced by the ckBTC minter.
+    pub fn confirmation_policy(&self) -> ConfirmationPolicy {
+        ConfirmationPolicy::new(0, self.config.confirmation_tiers.clone())
     }
 
     pub fn signer(&self) -> &SignerStorage {
@@ -149,6 +498,51 @@ impl State {
         &mut self.burn_request_store
     }
 
+    pub fn deposit_addresses(&self) -> &DepositAddressStore {
+        &self.deposit_addresses
+    }
+
+    pub fn deposit_addresses_mut(&mut self) -> &mut DepositAddressStore {
+        &mut self.deposit_addresses
+    }
+
+    pub fn deposit_statuses(&self) -> &DepositStatusStore {
+        &self.deposit_statuses
+    }
+
+    pub fn deposit_statuses_mut(&mut self) -> &mut DepositStatusStore {
+        &mut self.deposit_statuses
+    }
+
+    pub fn pending_ckbtc_deposits(&self) -> &PendingCkBtcDeposits {
+        &self.pending_ckbtc_deposits
+    }
+
+    pub fn pending_ckbtc_deposits_mut(&mut self) -> &mut PendingCkBtcDeposits {
+        &mut self.pending_ckbtc_deposits
+    }
+
+    pub fn pending_ckbtc_refunds(&self) -> &PendingCkBtcRefunds {
+        &self.pending_ckbtc_refunds
+    }
+
+    pub fn pending_ckbtc_refunds_mut(&mut self) -> &mut PendingCkBtcRefunds {
+        &mut self.pending_ckbtc_refunds
+    }
+
+    /// Source of the time for the expiry and cache checks.
+    pub fn clock(&self) -> Rc<dyn Clock> {
+        self.clock.clone()
+    }
+
+    pub fn withdrawal_fee_cache(&self) -> &WithdrawalFeeCache {
+        &self.withdrawal_fee_cache
+    }
+
+    pub fn withdrawal_fee_cache_mut(&mut self) -> &mut WithdrawalFeeCache {
+        &mut self.withdrawal_fee_cache
+    }
+
     pub fn get_evm_info(&self) -> EvmInfo {
         EvmInfo {
             link: self.config.evm_link.clone(),
@@ -185,10 +579,12 @@ impl State {
         self.config.admin
     }
 
-    pub fn check_admin(&self, caller: Principal) {
+    pub fn check_admin(&self, caller: Principal) -> minter_did::error::Result<()> {
         if caller != self.admin() {
-            panic!("access denied");
+            return Err(minter_did::error::Error::NotAuthorized);
         }
+
+        Ok(())
     }
 
     pub fn ck_btc_ledger_fee(&self) -> u64 {
@@ -0,0 +1,133 @@
+//! Estimates of the BTC withdrawal fees.
+//!
+//! A withdrawal pays the ckBTC ledger fee of the transfer to the ckBTC minter, which the bridge
+//! takes from the burned amount, and the minter and bitcoin fees of the ckBTC minter. Wallets get
+//! the total from the bridge instead of calling the ckBTC minter. The minter values change
+//! slowly, so the bridge caches them for [`CACHE_TTL`].
+
+use std::time::Duration;
+
+use candid::CandidType;
+use serde::Deserialize;
+
+use crate::ck_btc_interface::{MinterInfo, WithdrawalFee};
+
+/// Time the values received from the ckBTC minter are used for.
+pub const CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Fees of a withdrawal of BTC, in satoshi.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct WithdrawalFeeEstimate {
+    /// Fee of the ckBTC ledger transfer to the ckBTC minter.
+    pub ledger_fee: u64,
+    pub minter_fee: u64,
+    pub bitcoin_fee: u64,
+    pub total_fee: u64,
+    /// Minimum amount of a burn which the ckBTC minter withdraws.
+    pub min_burn_amount: u64,
+    /// Time (nanoseconds) the ckBTC minter fees were received.
+    pub fetched_at: u64,
+}
+
+impl WithdrawalFeeEstimate {
+    pub fn new(ledger_fee: u64, fee: WithdrawalFee, info: MinterInfo, fetched_at: u64) -> Self {
+        Self {
+            ledger_fee,
+            minter_fee: fee.minter_fee,
+            bitcoin_fee: fee.bitcoin_fee,
+            total_fee: ledger_fee
+                .saturating_add(fee.minter_fee)
+                .saturating_add(fee.bitcoin_fee),
+            min_burn_amount: min_burn_amount(ledger_fee, info),
+            fetched_at,
+        }
+    }
+}
+
+/// Minimum amount of a burn: the ledger fee is taken before the amount is sent to the ckBTC
+/// minter.
+pub fn min_burn_amount(ledger_fee: u64, info: MinterInfo) -> u64 {
+    info.retrieve_btc_min_amount.saturating_add(ledger_fee)
+}
+
+/// The ckBTC minter values with the time (nanoseconds) they were received.
+#[derive(Debug, Default, Clone)]
+pub struct WithdrawalFeeCache {
+    minter_info: Option<(MinterInfo, u64)>,
+    /// Fee of a withdrawal with no amount given.
+    default_fee: Option<(WithdrawalFee, u64)>,
+}
+
+impl WithdrawalFeeCache {
+    pub fn minter_info(&self, now: u64) -> Option<MinterInfo> {
+        fresh(self.minter_info, now)
+    }
+
+    pub fn default_fee(&self, now: u64) -> Option<WithdrawalFee> {
+        fresh(self.default_fee, now)
+    }
+
+    pub fn set_minter_info(&mut self, info: MinterInfo, now: u64) {
+        self.minter_info = Some((info, now));
+    }
+
+    pub fn set_default_fee(&mut self, fee: WithdrawalFee, now: u64) {
+        self.default_fee = Some((fee, now));
+    }
+
+    /// Estimate of a withdrawal with no amount given from the last received values, possibly
+    /// stale.
+    pub fn last_estimate(&self, ledger_fee: u64) -> Option<WithdrawalFeeEstimate> {
+        let (info, _) = self.minter_info?;
+        let (fee, fetched_at) = self.default_fee?;
+        Some(WithdrawalFeeEstimate::new(
+            ledger_fee, fee, info, fetched_at,
+        ))
+    }
+}
+
+fn fresh<T: Copy>(value: Option<(T, u64)>, now: u64) -> Option<T> {
+    let (value, fetched_at) = value?;
+    (now.saturating_sub(fetched_at) < CACHE_TTL.as_nanos() as u64).then_some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INFO: MinterInfo = MinterInfo {
+        min_confirmations: 6,
+        retrieve_btc_min_amount: 50_000,
+        kyt_fee: 1_000,
+    };
+
+    const FEE: WithdrawalFee = WithdrawalFee {
+        minter_fee: 300,
+        bitcoin_fee: 2_000,
+    };
+
+    #[test]
+    fn estimate_includes_ledger_fee() {
+        let estimate = WithdrawalFeeEstimate::new(10, FEE, INFO, 5);
+        assert_eq!(estimate.total_fee, 2_310);
+        assert_eq!(estimate.min_burn_amount, 50_010);
+        assert_eq!(estimate.fetched_at, 5);
+    }
+
+    #[test]
+    fn cached_values_expire() {
+        let mut cache = WithdrawalFeeCache::default();
+        assert_eq!(cache.last_estimate(10), None);
+
+        cache.set_minter_info(INFO, 0);
+        cache.set_default_fee(FEE, 0);
+        let ttl = CACHE_TTL.as_nanos() as u64;
+        assert_eq!(cache.minter_info(ttl - 1), Some(INFO));
+        assert_eq!(cache.default_fee(ttl - 1), Some(FEE));
+        assert_eq!(cache.minter_info(ttl), None);
+        assert_eq!(cache.default_fee(ttl), None);
+
+        // The last estimate is returned even if it is stale.
+        assert_eq!(cache.last_estimate(10).unwrap().total_fee, 2_310);
+    }
+}
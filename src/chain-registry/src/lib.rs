@@ -0,0 +1,256 @@
+//! Canonical chain ids of the bridged networks.
+//!
+//! The mint orders and the bridged token ids carry the ids of the sender and recipient chains.
+//! The EVMs are identified by their EIP-155 chain ids, while the other chains, the IC and the
+//! bitcoin networks with the BTC, rune and BRC20 pseudo-chains on top of them, get ids from the
+//! range reserved here at the top of the `u32` space, so they collide neither with each other
+//! nor with an EVM.
+//!
+//! The ids of the reserved range are laid out as `NON_EVM_CHAIN_IDS | family << 8 | network`.
+//!
+//! The bridges which were live before the registry identified the IC and the BTC and rune chains
+//! by the legacy ids of [`Chain::legacy_id`] in the mint orders. The wrapped tokens of these chains
+//! are identified by the principals and rune ids only, so the switch to the canonical ids changes
+//! just the ids of the EVM address senders. The BftBridge shares the nonces of a sender between
+//! its ids with both chain ids once the owner sets the alias of the chain with
+//! `setSenderChainIDAlias(chain.id(), legacy_id)`, so the orders signed before the switch cannot
+//! be minted twice.
+
+/// First chain id of the range reserved for the non-EVM chains.
+pub const NON_EVM_CHAIN_IDS: u32 = 0xFFFF_0000;
+
+/// Chain id of the Internet Computer.
+pub const IC_CHAIN_ID: u32 = Chain::InternetComputer.id();
+
+/// Id of the Internet Computer in the mint orders signed before the registry.
+const LEGACY_IC_CHAIN_ID: u32 = 0;
+
+const IC_FAMILY: u32 = 0x00;
+const BTC_FAMILY: u32 = 0x01;
+const RUNE_FAMILY: u32 = 0x02;
+const BRC20_FAMILY: u32 = 0x03;
+
+/// Bitcoin network in the chain ids.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BitcoinNetwork {
+    Mainnet,
+    Testnet3,
+    Testnet4,
+    Signet,
+    Regtest,
+}
+
+impl BitcoinNetwork {
+    pub const ALL: [Self; 5] = [
+        Self::Mainnet,
+        Self::Testnet3,
+        Self::Testnet4,
+        Self::Signet,
+        Self::Regtest,
+    ];
+
+    const fn index(self) -> u32 {
+        match self {
+            Self::Mainnet => 0,
+            Self::Testnet3 => 1,
+            Self::Testnet4 => 2,
+            Self::Signet => 3,
+            Self::Regtest => 4,
+        }
+    }
+
+    /// Id of the BTC and rune chains of the network in the mint orders signed before the registry.
+    pub const fn legacy_chain_id(self) -> u32 {
+        match self {
+            Self::Mainnet => 0,
+            Self::Testnet3 => 1,
+            Self::Regtest => 2,
+            Self::Testnet4 => 3,
+            Self::Signet => 4,
+        }
+    }
+
+    const fn from_index(index: u32) -> Option<Self> {
+        match index {
+            0 => Some(Self::Mainnet),
+            1 => Some(Self::Testnet3),
+            2 => Some(Self::Testnet4),
+            3 => Some(Self::Signet),
+            4 => Some(Self::Regtest),
+            _ => None,
+        }
+    }
+}
+
+/// Assets of a bitcoin network bridged as separate chains.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BitcoinProtocol {
+    Btc,
+    Rune,
+    Brc20,
+}
+
+impl BitcoinProtocol {
+    const fn family(self) -> u32 {
+        match self {
+            Self::Btc => BTC_FAMILY,
+            Self::Rune => RUNE_FAMILY,
+            Self::Brc20 => BRC20_FAMILY,
+        }
+    }
+}
+
+/// Chain a bridged token or a mint order party lives on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Chain {
+    InternetComputer,
+    Bitcoin {
+        network: BitcoinNetwork,
+        protocol: BitcoinProtocol,
+    },
+    /// EVM with the EIP-155 chain id. The ids of the reserved range are not valid EVM chain ids.
+    Evm(u32),
+}
+
+impl Chain {
+    pub const fn btc(network: BitcoinNetwork) -> Self {
+        Self::Bitcoin {
+            network,
+            protocol: BitcoinProtocol::Btc,
+        }
+    }
+
+    pub const fn rune(network: BitcoinNetwork) -> Self {
+        Self::Bitcoin {
+            network,
+            protocol: BitcoinProtocol::Rune,
+        }
+    }
+
+    pub const fn brc20(network: BitcoinNetwork) -> Self {
+        Self::Bitcoin {
+            network,
+            protocol: BitcoinProtocol::Brc20,
+        }
+    }
+
+    /// EVM with the `chain_id`, or `None` if the id is in the reserved range.
+    pub const fn evm(chain_id: u32) -> Option<Self> {
+        if is_evm_chain_id(chain_id) {
+            Some(Self::Evm(chain_id))
+        } else {
+            None
+        }
+    }
+
+    /// Canonical id of the chain in the mint orders and the bridged token ids.
+    pub const fn id(self) -> u32 {
+        match self {
+            Self::InternetComputer => NON_EVM_CHAIN_IDS,
+            Self::Bitcoin { network, protocol } => {
+                NON_EVM_CHAIN_IDS | protocol.family() << 8 | network.index()
+            }
+            Self::Evm(chain_id) => chain_id,
+        }
+    }
+
+    /// Id of the chain in the mint orders signed before the registry, or `None` if the chain was
+    /// not bridged then. The IC was `0` and the rune chains shared the ids of the BTC ones.
+    pub const fn legacy_id(self) -> Option<u32> {
+        match self {
+            Self::InternetComputer => Some(LEGACY_IC_CHAIN_ID),
+            Self::Bitcoin {
+                network,
+                protocol: BitcoinProtocol::Btc | BitcoinProtocol::Rune,
+            } => Some(network.legacy_chain_id()),
+            _ => None,
+        }
+    }
+
+    /// Chain with the canonical `id`, or `None` if the id is reserved but not assigned.
+    pub const fn from_id(id: u32) -> Option<Self> {
+        if is_evm_chain_id(id) {
+            return Some(Self::Evm(id));
+        }
+
+        let family = (id >> 8) & 0xFF;
+        let index = id & 0xFF;
+        let protocol = match family {
+            IC_FAMILY if index == 0 => return Some(Self::InternetComputer),
+            BTC_FAMILY => BitcoinProtocol::Btc,
+            RUNE_FAMILY => BitcoinProtocol::Rune,
+            BRC20_FAMILY => BitcoinProtocol::Brc20,
+            _ => return None,
+        };
+        match BitcoinNetwork::from_index(index) {
+            Some(network) => Some(Self::Bitcoin { network, protocol }),
+            None => None,
+        }
+    }
+}
+
+/// Checks that the `chain_id` can identify an EVM, i.e. it is not in the reserved range.
+pub const fn is_evm_chain_id(chain_id: u32) -> bool {
+    chain_id < NON_EVM_CHAIN_IDS
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    fn non_evm_chains() -> Vec<Chain> {
+        let mut chains = vec![Chain::InternetComputer];
+        for network in BitcoinNetwork::ALL {
+            chains.extend([
+                Chain::btc(network),
+                Chain::rune(network),
+                Chain::brc20(network),
+            ]);
+        }
+        chains
+    }
+
+    #[test]
+    fn non_evm_chain_ids_are_distinct_and_reserved() {
+        let chains = non_evm_chains();
+        let ids: HashSet<u32> = chains.iter().map(|chain| chain.id()).collect();
+        assert_eq!(ids.len(), chains.len());
+        assert!(ids.iter().all(|id| !is_evm_chain_id(*id)));
+    }
+
+    #[test]
+    fn legacy_ids_are_kept() {
+        assert_eq!(Chain::InternetComputer.legacy_id(), Some(0));
+        let legacy_ids = [
+            (BitcoinNetwork::Mainnet, 0),
+            (BitcoinNetwork::Testnet3, 1),
+            (BitcoinNetwork::Regtest, 2),
+            (BitcoinNetwork::Testnet4, 3),
+            (BitcoinNetwork::Signet, 4),
+        ];
+        for (network, legacy_id) in legacy_ids {
+            assert_eq!(Chain::btc(network).legacy_id(), Some(legacy_id));
+            assert_eq!(Chain::rune(network).legacy_id(), Some(legacy_id));
+            assert_eq!(Chain::brc20(network).legacy_id(), None);
+        }
+        assert_eq!(Chain::Evm(355113).legacy_id(), None);
+    }
+
+    #[test]
+    fn chain_ids_roundtrip() {
+        for chain in non_evm_chains() {
+            assert_eq!(Chain::from_id(chain.id()), Some(chain));
+        }
+
+        assert_eq!(Chain::from_id(355113), Some(Chain::Evm(355113)));
+        assert_eq!(Chain::evm(1).map(Chain::id), Some(1));
+        assert_eq!(Chain::evm(IC_CHAIN_ID), None);
+        assert_eq!(Chain::from_id(NON_EVM_CHAIN_IDS | 0xFF00), None);
+        assert_eq!(
+            Chain::from_id(NON_EVM_CHAIN_IDS | BTC_FAMILY << 8 | 9),
+            None
+        );
+    }
+}
@@ -0,0 +1,8 @@
+//! Writes the complete candid interface of the canister, including metrics endpoints.
+//!
+//! Usage: `cargo run -p erc20-minter --bin generate_did -- [OUTPUT_PATH]`. The interface is written
+//! to `erc20-minter.did` in the current directory if no path is given.
+
+fn main() -> std::io::Result<()> {
+    minter_contract_utils::did_output::write_idl(&erc20_minter::idl(), "erc20-minter.did")
+}
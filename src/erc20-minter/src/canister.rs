@@ -1,29 +1,59 @@
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::time::Duration;
 
 use candid::Principal;
+use did::build::BuildData;
 use did::H160;
 use eth_signer::sign_strategy::TransactionSigner;
 use ic_canister::{generate_idl, init, post_upgrade, query, update, Canister, Idl, PreUpdate};
 use ic_exports::ic_kit::ic;
 use ic_metrics::{Metrics, MetricsStorage};
 use ic_stable_structures::stable_structures::DefaultMemoryImpl;
-use ic_stable_structures::{CellStructure, StableBTreeMap, VirtualMemory};
+use ic_stable_structures::{BTreeMapStructure, CellStructure, StableBTreeMap, VirtualMemory};
 use ic_task_scheduler::retry::BackoffPolicy;
 use ic_task_scheduler::scheduler::{Scheduler, TaskScheduler};
 use ic_task_scheduler::task::{InnerScheduledTask, ScheduledTask, TaskOptions, TaskStatus};
+use ic_task_scheduler::SchedulerError;
+use minter_contract_utils::admin_approvals::{
+    ApprovalConfig, ApprovalError, ApprovalStore, Proposal,
+};
+use minter_contract_utils::bft_bridge_api::BridgeEvent;
+use minter_contract_utils::canister_status::{stable_structures_usage, CanisterStatusInfo};
+use minter_contract_utils::config_validation::{
+    validate_bft_bridge_contract, ConfigError, ConfigValidator,
+};
+use minter_contract_utils::erc20_metadata::Erc20MetadataCache;
+use minter_contract_utils::event_subscribers::EventSubscribers;
 use minter_contract_utils::evm_bridge::BridgeSide;
+use minter_contract_utils::fee_balances::{FeeBalance, FeeBalances};
+use minter_contract_utils::gas_limits::GasLimits;
+use minter_contract_utils::health::{self, HealthReport};
+use minter_contract_utils::in_flight_txs::InFlightTxsInfo;
+use minter_contract_utils::long_poll::{self, TooManyWaiters};
+use minter_contract_utils::mint_order_codec::decode_stored_mint_order;
 use minter_contract_utils::operation_store::{MinterOperationId, MinterOperationStore};
+use minter_contract_utils::operation_trace::{self, TraceEntry};
+use minter_contract_utils::pagination::{
+    CursorPage, CursorRequest, InvalidCursor, Paged, Pagination,
+};
+use minter_contract_utils::task_limits::{is_deferral_error, TaskLimits, DEFERRED_TASK_DELAY_SECS};
 use minter_did::id256::Id256;
 use minter_did::order::SignedMintOrder;
 
+use crate::interface::AdminOperation;
 use crate::memory::{
-    MEMORY_MANAGER, OPERATIONS_LOG_MEMORY_ID, OPERATIONS_MAP_MEMORY_ID, OPERATIONS_MEMORY_ID,
-    PENDING_TASKS_MEMORY_ID,
+    APPROVAL_PROPOSALS_MEMORY_ID, APPROVAL_SETTINGS_MEMORY_ID, BASE_FEE_BALANCES_MEMORY_ID,
+    EVENT_SUBSCRIBERS_MEMORY_ID, MEMORY_MANAGER, OPERATIONS_LOG_MEMORY_ID,
+    OPERATIONS_MAP_MEMORY_ID, OPERATIONS_MEMORY_ID, PENDING_TASKS_MEMORY_ID,
+    PROCESSED_EVENTS_MEMORY_ID, STABLE_STRUCTURES, TOKEN_METADATA_MEMORY_ID,
+    TOKEN_REGISTRY_MEMORY_ID, WRAPPED_FEE_BALANCES_MEMORY_ID,
 };
 use crate::operation::OperationPayload;
+use crate::processed_events::{ProcessedEvents, ReplayReport};
 use crate::state::{Settings, State};
 use crate::tasks::BridgeTask;
+use crate::token_registry::{BridgedToken, TokenRegistry};
 
 const EVM_INFO_INITIALIZATION_RETRIES: u32 = 5;
 const EVM_INFO_INITIALIZATION_RETRY_DELAY: u32 = 2;
@@ -42,19 +72,17 @@ impl EvmMinter {
         // Set the metrics updating interval
         #[cfg(target_family = "wasm")]
         {
-            use std::time::Duration;
-
             self.update_metrics_timer(std::time::Duration::from_secs(60 * 60));
 
             const GLOBAL_TIMER_INTERVAL: Duration = Duration::from_secs(1);
             ic_exports::ic_cdk_timers::set_timer_interval(GLOBAL_TIMER_INTERVAL, move || {
                 // Tasks to collect EVMs events
-                let tasks = vec![
-                    Self::collect_evm_events_task(BridgeSide::Base),
-                    Self::collect_evm_events_task(BridgeSide::Wrapped),
-                ];
-
-                get_scheduler().borrow_mut().append_tasks(tasks);
+                for side in [BridgeSide::Base, BridgeSide::Wrapped] {
+                    BridgeTask::CollectEvmEvents(side).append_unique(
+                        &*get_scheduler().borrow(),
+                        Self::collect_evm_events_options(),
+                    );
+                }
 
                 let task_execution_result = get_scheduler().borrow_mut().run();
 
@@ -62,6 +90,30 @@ impl EvmMinter {
                     log::error!("task execution failed: {err}",);
                 }
             });
+
+            ic_exports::ic_cdk_timers::set_timer_interval(
+                minter_contract_utils::health::HEALTH_CHECK_INTERVAL,
+                || {
+                    BridgeTask::CheckHealth
+                        .append_unique(&*get_scheduler().borrow(), TaskOptions::default());
+                },
+            );
+
+            ic_exports::ic_cdk_timers::set_timer_interval(
+                minter_contract_utils::gas_price::GAS_PRICE_REFRESH_INTERVAL,
+                || {
+                    for side in [BridgeSide::Base, BridgeSide::Wrapped] {
+                        BridgeTask::RefreshGasPrice(side)
+                            .append_unique(&*get_scheduler().borrow(), TaskOptions::default());
+                    }
+                },
+            );
+
+            const MINT_ORDERS_EXPIRATION_INTERVAL: Duration = Duration::from_secs(60 * 10);
+            ic_exports::ic_cdk_timers::set_timer_interval(MINT_ORDERS_EXPIRATION_INTERVAL, || {
+                BridgeTask::ExpireMintOrders
+                    .append_unique(&*get_scheduler().borrow(), TaskOptions::default());
+            });
         }
     }
 
@@ -76,17 +128,15 @@ impl EvmMinter {
 
         log::info!("starting erc20-minter canister");
 
-        let tasks = vec![
-            // Tasks to init EVMs state
-            Self::init_evm_info_task(BridgeSide::Base),
-            Self::init_evm_info_task(BridgeSide::Wrapped),
-        ];
-
         {
             let scheduler = get_scheduler();
             let mut borrowed_scheduler = scheduler.borrow_mut();
-            borrowed_scheduler.on_completion_callback(log_task_execution_error);
-            borrowed_scheduler.append_tasks(tasks);
+            borrowed_scheduler.on_completion_callback(on_task_completed);
+            // Tasks to init EVMs state
+            for side in [BridgeSide::Base, BridgeSide::Wrapped] {
+                BridgeTask::InitEvmState(side)
+                    .append_unique(&*borrowed_scheduler, Self::init_evm_info_options());
+            }
         }
 
         self.set_timers();
@@ -94,31 +144,31 @@ impl EvmMinter {
         log::info!("erc20-minter canister initialized");
     }
 
-    fn init_evm_info_task(bridge_side: BridgeSide) -> ScheduledTask<BridgeTask> {
-        let init_options = TaskOptions::default()
+    fn init_evm_info_options() -> TaskOptions {
+        TaskOptions::default()
             .with_max_retries_policy(EVM_INFO_INITIALIZATION_RETRIES)
             .with_backoff_policy(BackoffPolicy::Exponential {
                 secs: EVM_INFO_INITIALIZATION_RETRY_DELAY,
                 multiplier: EVM_INFO_INITIALIZATION_RETRY_MULTIPLIER,
-            });
-        BridgeTask::InitEvmState(bridge_side).into_scheduled(init_options)
+            })
     }
 
     #[cfg(target_family = "wasm")]
-    fn collect_evm_events_task(bridge_side: BridgeSide) -> ScheduledTask<BridgeTask> {
+    fn collect_evm_events_options() -> TaskOptions {
         const EVM_EVENTS_COLLECTING_DELAY: u32 = 1;
 
-        let options = TaskOptions::default()
+        TaskOptions::default()
             .with_retry_policy(ic_task_scheduler::retry::RetryPolicy::Infinite)
             .with_backoff_policy(BackoffPolicy::Fixed {
                 secs: EVM_EVENTS_COLLECTING_DELAY,
-            });
-
-        BridgeTask::CollectEvmEvents(bridge_side).into_scheduled(options)
+            })
     }
 
     #[post_upgrade]
     pub fn post_upgrade(&mut self) {
+        get_scheduler()
+            .borrow_mut()
+            .on_completion_callback(on_task_completed);
         self.set_timers();
     }
 
@@ -140,6 +190,39 @@ impl EvmMinter {
             .collect()
     }
 
+    /// Same as `list_mint_orders`, but returns only the requested page of the list.
+    #[query]
+    pub fn list_mint_orders_page(
+        &self,
+        wallet_address: H160,
+        src_token: Id256,
+        pagination: Option<Pagination>,
+    ) -> Paged<(u32, SignedMintOrder)> {
+        pagination
+            .unwrap_or_default()
+            .paginate(self.list_mint_orders(wallet_address, src_token))
+    }
+
+    /// Same as `list_mint_orders_page`, but pages the list with a cursor instead of an offset,
+    /// so the query reads only the operations of the requested page.
+    #[query]
+    pub fn list_mint_orders_by_cursor(
+        &self,
+        wallet_address: H160,
+        src_token: Id256,
+        request: Option<CursorRequest>,
+    ) -> Result<CursorPage<(u32, SignedMintOrder)>, InvalidCursor> {
+        get_operations_store().page_for_address(
+            &wallet_address,
+            &request.unwrap_or_default(),
+            |operation_id, status| {
+                status
+                    .get_signed_mint_order(Some(src_token))
+                    .map(|mint_order| (operation_id.nonce(), *mint_order))
+            },
+        )
+    }
+
     /// Returns `(nonce, mint_order)` pairs for the given sender id and operation_id.
     #[query]
     pub fn get_mint_order(
@@ -154,6 +237,22 @@ impl EvmMinter {
             .map(|(_, mint_order)| mint_order)
     }
 
+    /// Returns the base tokens bridged by the minter with their wrapped tokens, metadata and the
+    /// amount of the wrapped tokens in circulation, as collected from the BftBridge events.
+    #[query]
+    pub fn list_bridged_tokens(&self) -> Vec<BridgedToken> {
+        get_token_registry().get_all()
+    }
+
+    /// Returns the deposit of the `user` in the FeeCharge contract of the BftBridge on the given
+    /// side, as last seen by the minter. The mint orders of the users with no deposit covering the
+    /// fees of their pending orders have no fee payer, and are not sent to the EVM by the minter.
+    #[query]
+    pub fn get_fee_balance(&self, side: BridgeSide, user: H160) -> Option<FeeBalance> {
+        let fee_charge = get_state().borrow().fee_charge(side).flatten()?;
+        get_fee_balances(side).get(&fee_charge, &user)
+    }
+
     #[query]
     pub fn get_operations_list(
         &self,
@@ -162,6 +261,86 @@ impl EvmMinter {
         get_operations_store().get_for_address(&wallet_address)
     }
 
+    /// Same as `get_operations_list`, but returns only the requested page of the list.
+    #[query]
+    pub fn get_operations_page(
+        &self,
+        wallet_address: H160,
+        pagination: Option<Pagination>,
+    ) -> Paged<(MinterOperationId, OperationPayload)> {
+        pagination
+            .unwrap_or_default()
+            .paginate(get_operations_store().get_for_address(&wallet_address))
+    }
+
+    /// Same as `get_operations_page`, but pages the list with a cursor instead of an offset, so
+    /// the query reads only the operations of the requested page.
+    #[query]
+    pub fn get_operations_by_cursor(
+        &self,
+        wallet_address: H160,
+        request: Option<CursorRequest>,
+    ) -> Result<CursorPage<(MinterOperationId, OperationPayload)>, InvalidCursor> {
+        get_operations_store().page_for_address(
+            &wallet_address,
+            &request.unwrap_or_default(),
+            |id, payload| Some((id, payload)),
+        )
+    }
+
+    /// Waits until the state of the operation changes, or until `timeout_secs` pass, and returns
+    /// the state, so the clients don't poll `get_operations_list`. The timeout is capped at 30
+    /// seconds, and the call is rejected if too many calls are waiting already.
+    #[update]
+    pub async fn await_operation(
+        &self,
+        operation_id: MinterOperationId,
+        timeout_secs: u64,
+    ) -> Result<Option<OperationPayload>, TooManyWaiters> {
+        long_poll::await_change(timeout_secs, || get_operations_store().get(operation_id)).await
+    }
+
+    /// Returns the entries recorded for the operation, e.g. its status changes and the task
+    /// failures, oldest first. The traces are kept for the latest operations since the last
+    /// upgrade.
+    #[query]
+    pub fn get_operation_trace(&self, operation_id: MinterOperationId) -> Vec<TraceEntry> {
+        operation_trace::get_operation_trace(operation_id)
+    }
+
+    /// Returns the results of the latest checks of the base and wrapped EVM RPCs and the signer.
+    /// The dependencies are reported as `Unknown` until the first periodic check after the
+    /// canister installation or upgrade.
+    #[query]
+    pub fn health(&self) -> HealthReport {
+        get_state().borrow().health.report()
+    }
+
+    /// Returns the cycles balance, the heap and stable memory usage and the number of the
+    /// scheduled tasks of the canister.
+    #[query]
+    pub fn canister_status_info(&self) -> CanisterStatusInfo {
+        MEMORY_MANAGER.with(|mm| {
+            let stable_structures = stable_structures_usage(STABLE_STRUCTURES, |id| mm.get(id));
+            let scheduled_tasks = TasksStorage::new(mm.get(PENDING_TASKS_MEMORY_ID)).len();
+            CanisterStatusInfo::collect(stable_structures, scheduled_tasks)
+        })
+    }
+
+    /// Returns the version, the git commit, the build timestamp and the enabled features of the
+    /// canister build.
+    #[query]
+    pub fn get_build_data(&self) -> BuildData {
+        minter_contract_utils::canister_build_data!()
+    }
+
+    /// Checks the settings with the same rules as the canister init, returning all the errors
+    /// found. The list is empty if the settings are valid.
+    #[query]
+    pub fn validate_config(&self, settings: Settings) -> Vec<ConfigError> {
+        settings.validate().err().unwrap_or_default()
+    }
+
     /// Returns EVM address of the canister.
     #[update]
     pub async fn get_evm_address(&self) -> Option<H160> {
@@ -175,13 +354,88 @@ impl EvmMinter {
         }
     }
 
+    /// Dry-runs the settings: checks them with the init rules and, if they are valid, checks that
+    /// the EVMs of the links respond and have the chain ids the bridge works with. Returns all
+    /// the errors found, or an empty list.
+    ///
+    /// The EVM checks need outcalls, so this is an update, but it doesn't change the state.
+    #[update]
+    pub async fn admin_validate_bridge_config(
+        &self,
+        settings: Settings,
+    ) -> minter_did::error::Result<Vec<ConfigError>> {
+        Self::check_admin(ic::caller())?;
+        if let Err(errors) = settings.validate() {
+            return Ok(errors);
+        }
+
+        let mut validator = ConfigValidator::new();
+        for (field, side, link) in [
+            ("base_evm_link", BridgeSide::Base, &settings.base_evm_link),
+            (
+                "wrapped_evm_link",
+                BridgeSide::Wrapped,
+                &settings.wrapped_evm_link,
+            ),
+        ] {
+            let chain_id = get_state()
+                .borrow()
+                .config
+                .get_evm_params(side)
+                .ok()
+                .map(|params| params.chain_id);
+            match chain_id {
+                Some(chain_id) => {
+                    validator
+                        .evm_chain_id(field, &link.get_json_rpc_client(), chain_id)
+                        .await
+                }
+                None => validator.check(field, health::check_evm_link(link).await),
+            };
+        }
+
+        Ok(validator.finish().err().unwrap_or_default())
+    }
+
+    /// Dry-runs `set_bft_bridge_contract`: checks that the contract is deployed in the EVM of the
+    /// side without setting it. Returns all the errors found, or an empty list.
+    #[update]
+    pub async fn admin_validate_bft_config(
+        &self,
+        address: H160,
+        side: BridgeSide,
+    ) -> minter_did::error::Result<Vec<ConfigError>> {
+        Self::check_admin(ic::caller())?;
+        let (link, chain_id) = {
+            let state = get_state();
+            let state = state.borrow();
+            (
+                state.config.get_evm_info(side).link,
+                state
+                    .config
+                    .get_evm_params(side)
+                    .ok()
+                    .map(|params| params.chain_id),
+            )
+        };
+
+        Ok(
+            validate_bft_bridge_contract(&link.get_json_rpc_client(), &address, chain_id)
+                .await
+                .err()
+                .unwrap_or_default(),
+        )
+    }
+
     /// Sets the BFT bridge contract address.
     #[update]
-    pub async fn set_bft_bridge_contract(&mut self, address: H160, side: BridgeSide) {
-        get_state()
-            .borrow_mut()
-            .config
-            .set_bft_bridge_contract(side, address);
+    pub async fn set_bft_bridge_contract(
+        &mut self,
+        address: H160,
+        side: BridgeSide,
+    ) -> minter_did::error::Result<()> {
+        Self::check_direct_admin(ic::caller())?;
+        Self::apply_admin_operation(AdminOperation::SetBftBridgeContract { address, side }).await
     }
 
     /// Returns bridge contract address for EVM.
@@ -191,6 +445,437 @@ impl EvmMinter {
         get_state().borrow().config.get_bft_bridge_contract(side)
     }
 
+    /// Sets the time in seconds after which unclaimed mint orders expire. `None` disables the
+    /// expiration of new mint orders.
+    #[update]
+    pub async fn admin_set_mint_order_ttl(
+        &mut self,
+        ttl_secs: Option<u64>,
+    ) -> minter_did::error::Result<()> {
+        Self::check_direct_admin(ic::caller())?;
+        Self::apply_admin_operation(AdminOperation::SetMintOrderTtl(ttl_secs)).await
+    }
+
+    #[query]
+    pub fn get_mint_order_ttl(&self) -> Option<u64> {
+        get_state()
+            .borrow()
+            .config
+            .mint_order_ttl()
+            .map(|ttl| ttl.as_secs())
+    }
+
+    /// Makes the expired mint order claimable again with a new expiration time and schedules
+    /// the mint transaction for it.
+    #[update]
+    pub fn admin_reissue_mint_order(
+        &mut self,
+        operation_id: MinterOperationId,
+    ) -> minter_did::error::Result<()> {
+        Self::check_direct_admin(ic::caller())?;
+        Self::reissue_mint_order(operation_id)
+    }
+
+    fn reissue_mint_order(operation_id: MinterOperationId) -> minter_did::error::Result<()> {
+        let now = get_state().borrow().clock().now();
+        let expires_at = get_state()
+            .borrow()
+            .config
+            .mint_order_ttl()
+            .map(|ttl| now.saturating_add(ttl.as_nanos() as u64));
+        Self::update_expired_operation(operation_id, |operation| operation.reissue(expires_at))?;
+
+        get_scheduler().borrow_mut().append_task(
+            BridgeTask::SendMintTransaction(operation_id).into_scheduled(TaskOptions::default()),
+        );
+
+        Ok(())
+    }
+
+    /// Collects the BftBridge events of the `side` in the blocks `from_block..=to_block` again and
+    /// processes the ones missed by the regular scan, e.g. after an RPC node returned incomplete
+    /// logs. The events processed already are skipped. At most `MAX_REPLAYED_BLOCKS` blocks are
+    /// replayed per call.
+    #[update]
+    pub async fn admin_replay_events(
+        &mut self,
+        side: BridgeSide,
+        from_block: u64,
+        to_block: u64,
+    ) -> minter_did::error::Result<ReplayReport> {
+        Self::check_direct_admin(ic::caller())?;
+        Self::replay_events(side, from_block, to_block).await
+    }
+
+    async fn replay_events(
+        side: BridgeSide,
+        from_block: u64,
+        to_block: u64,
+    ) -> minter_did::error::Result<ReplayReport> {
+        const MAX_REPLAYED_BLOCKS: u64 = 10_000;
+
+        if from_block > to_block {
+            return Err(minter_did::error::Error::Internal(format!(
+                "invalid block range: {from_block} > {to_block}"
+            )));
+        }
+        if to_block - from_block >= MAX_REPLAYED_BLOCKS {
+            return Err(minter_did::error::Error::Internal(format!(
+                "at most {MAX_REPLAYED_BLOCKS} blocks can be replayed per call"
+            )));
+        }
+
+        let (link, bft_bridge) = {
+            let state = get_state();
+            let state = state.borrow();
+            let bft_bridge = state.config.get_bft_bridge_contract(side).ok_or_else(|| {
+                minter_did::error::Error::Internal(format!("no bft bridge contract set for {side}"))
+            })?;
+            (state.config.get_evm_info(side).link, bft_bridge)
+        };
+
+        let client = link.get_json_rpc_client();
+        let logs = BridgeEvent::collect_logs(&client, from_block, to_block, bft_bridge.0)
+            .await
+            .map_err(|e| {
+                minter_did::error::Error::Internal(format!("failed to collect logs: {e}"))
+            })?;
+
+        let (tasks, report) = BridgeTask::tasks_by_replayed_logs(logs, side);
+        get_scheduler().borrow_mut().append_tasks(tasks);
+
+        log::info!(
+            "replayed blocks {from_block}..={to_block} of {side}: {} burns and {} mints recovered, {} events skipped",
+            report.burns,
+            report.mints,
+            report.skipped
+        );
+
+        Ok(report)
+    }
+
+    /// Re-signs the unsent mint orders of the operations with the ids in `start..end` with the
+    /// current signer, e.g. after the signer was changed, and returns the ids of the re-signed
+    /// operations. At most `MAX_RESIGNED_ORDERS` orders are re-signed per call, so a large range
+    /// is processed by several calls.
+    #[update]
+    pub async fn admin_resign_mint_orders(
+        &mut self,
+        start: MinterOperationId,
+        end: MinterOperationId,
+    ) -> minter_did::error::Result<Vec<MinterOperationId>> {
+        Self::check_direct_admin(ic::caller())?;
+        Self::resign_mint_orders(start, end).await
+    }
+
+    async fn resign_mint_orders(
+        start: MinterOperationId,
+        end: MinterOperationId,
+    ) -> minter_did::error::Result<Vec<MinterOperationId>> {
+        const MAX_RESIGNED_ORDERS: usize = 100;
+
+        let orders: Vec<(MinterOperationId, SignedMintOrder)> = get_operations_store()
+            .get_incomplete()
+            .into_iter()
+            .filter(|(operation_id, _)| (start..end).contains(operation_id))
+            .filter_map(|(operation_id, operation)| {
+                operation
+                    .unsent_mint_order()
+                    .map(|order| (operation_id, *order))
+            })
+            .take(MAX_RESIGNED_ORDERS)
+            .collect();
+
+        let signer = get_state().borrow().signer.get().clone();
+        let mut resigned = Vec::with_capacity(orders.len());
+        for (operation_id, order) in orders {
+            let mint_order = decode_stored_mint_order(&order).map_err(|err| {
+                minter_did::error::Error::Internal(format!(
+                    "mint order of operation {operation_id} cannot be decoded: {err}"
+                ))
+            })?;
+            let resigned_order = mint_order.encode_and_sign(&signer).await.map_err(|err| {
+                minter_did::error::Error::Internal(format!(
+                    "failed to sign mint order of operation {operation_id}: {err:?}"
+                ))
+            })?;
+
+            // The operation could be updated while the order was being signed.
+            let mut operation_store = get_operations_store();
+            let Some(mut operation) = operation_store.get(operation_id) else {
+                continue;
+            };
+            match operation.replace_unsent_mint_order(&order, resigned_order) {
+                Ok(()) => {
+                    operation_store.update(operation_id, operation);
+                    resigned.push(operation_id);
+                }
+                Err(err) => {
+                    log::warn!("Mint order of operation {operation_id} is not re-signed: {err}")
+                }
+            }
+        }
+
+        log::info!("{} mint orders are re-signed", resigned.len());
+        Ok(resigned)
+    }
+
+    fn update_expired_operation(
+        operation_id: MinterOperationId,
+        f: impl FnOnce(&mut OperationPayload) -> Result<(), String>,
+    ) -> minter_did::error::Result<()> {
+        let mut operation_store = get_operations_store();
+        let mut operation = operation_store.get(operation_id).ok_or_else(|| {
+            minter_did::error::Error::Internal(format!("operation {operation_id} not found"))
+        })?;
+
+        f(&mut operation).map_err(minter_did::error::Error::Internal)?;
+        operation_store.update(operation_id, operation);
+
+        Ok(())
+    }
+
+    /// Sets priorities and concurrency limits of the canister tasks. Task types are named after
+    /// the `BridgeTask` variants, e.g. `PrepareMintOrder`.
+    #[update]
+    pub async fn admin_set_task_limits(
+        &mut self,
+        limits: TaskLimits,
+    ) -> minter_did::error::Result<()> {
+        Self::check_direct_admin(ic::caller())?;
+        Self::apply_admin_operation(AdminOperation::SetTaskLimits(limits)).await
+    }
+
+    #[query]
+    pub fn get_task_limits(&self) -> TaskLimits {
+        get_state().borrow().task_limiter.limits().clone()
+    }
+
+    /// Sets gas limits of the EVM transactions by the operation. The limit of an operation is
+    /// bumped automatically when its transaction runs out of gas.
+    #[update]
+    pub async fn admin_set_gas_limits(
+        &mut self,
+        limits: GasLimits,
+    ) -> minter_did::error::Result<()> {
+        Self::check_direct_admin(ic::caller())?;
+        Self::apply_admin_operation(AdminOperation::SetGasLimits(limits)).await
+    }
+
+    #[query]
+    pub fn get_gas_limits(&self) -> GasLimits {
+        get_state().borrow().gas_limits.clone()
+    }
+
+    /// Sets the maximum number of the mint transactions sent to the EVM on the given side and not
+    /// yet confirmed. The mint transactions above the limit wait for the earlier ones to be
+    /// confirmed.
+    #[update]
+    pub async fn admin_set_max_in_flight_txs(
+        &mut self,
+        side: BridgeSide,
+        max_in_flight: u32,
+    ) -> minter_did::error::Result<()> {
+        Self::check_direct_admin(ic::caller())?;
+        Self::apply_admin_operation(AdminOperation::SetMaxInFlightTxs {
+            side,
+            max_in_flight,
+        })
+        .await
+    }
+
+    #[query]
+    pub fn get_in_flight_txs(&self, side: BridgeSide) -> InFlightTxsInfo {
+        get_state().borrow().in_flight_txs(side).info()
+    }
+
+    /// Subscribes the canister to bridge events. The subscriber is notified about every
+    /// processed `Minted` and `Burnt` event with a one-way call of its `on_bridge_event` method.
+    #[update]
+    pub async fn admin_add_event_subscriber(
+        &mut self,
+        subscriber: Principal,
+    ) -> minter_did::error::Result<()> {
+        Self::check_direct_admin(ic::caller())?;
+        Self::apply_admin_operation(AdminOperation::AddEventSubscriber(subscriber)).await
+    }
+
+    /// Removes the canister from the bridge events subscribers.
+    #[update]
+    pub async fn admin_remove_event_subscriber(
+        &mut self,
+        subscriber: Principal,
+    ) -> minter_did::error::Result<()> {
+        Self::check_direct_admin(ic::caller())?;
+        Self::apply_admin_operation(AdminOperation::RemoveEventSubscriber(subscriber)).await
+    }
+
+    /// Returns the list of canisters subscribed to bridge events.
+    #[query]
+    pub fn get_event_subscribers(&self) -> Vec<Principal> {
+        get_event_subscribers().get_all()
+    }
+
+    /// Sets the approvers of the dangerous operations, or disables the approvals if `config` is
+    /// `None`. Once the approvals are enabled, the operations of [`AdminOperation`], e.g. the
+    /// BftBridge contract and the limits changes, are applied only with the approved proposals.
+    #[update]
+    pub async fn admin_set_approval_config(
+        &mut self,
+        config: Option<ApprovalConfig>,
+    ) -> minter_did::error::Result<()> {
+        Self::check_direct_admin(ic::caller())?;
+        Self::apply_admin_operation(AdminOperation::SetApprovalConfig(config)).await
+    }
+
+    #[query]
+    pub fn get_approval_config(&self) -> Option<ApprovalConfig> {
+        get_admin_approvals().config()
+    }
+
+    /// Proposes the dangerous `operation`, approved by the caller. Returns the id of the
+    /// proposal.
+    #[update]
+    pub fn admin_propose_operation(
+        &mut self,
+        operation: AdminOperation,
+    ) -> minter_did::error::Result<u64> {
+        get_admin_approvals()
+            .propose(ic::caller(), operation, get_state().borrow().clock().now())
+            .map_err(approval_error)
+    }
+
+    /// Approves the proposal. Returns the number of its approvals.
+    #[update]
+    pub fn admin_approve_operation(&mut self, proposal_id: u64) -> minter_did::error::Result<u32> {
+        get_admin_approvals()
+            .approve(
+                ic::caller(),
+                proposal_id,
+                get_state().borrow().clock().now(),
+            )
+            .map_err(approval_error)
+    }
+
+    /// Applies the operation of the proposal if it has the approvals of the threshold number of
+    /// the approvers.
+    #[update]
+    pub async fn admin_execute_operation(
+        &mut self,
+        proposal_id: u64,
+    ) -> minter_did::error::Result<()> {
+        let operation = get_admin_approvals()
+            .approved_operation(
+                ic::caller(),
+                proposal_id,
+                get_state().borrow().clock().now(),
+            )
+            .map_err(approval_error)?;
+        get_admin_approvals().mark_executed(proposal_id, get_state().borrow().clock().now());
+        let result = Self::apply_admin_operation(operation).await;
+        if result.is_err() {
+            get_admin_approvals().reopen(proposal_id);
+        }
+        result
+    }
+
+    /// Returns the proposals which are not executed and not expired, to the approvers only.
+    #[query]
+    pub fn get_operation_proposals(
+        &self,
+    ) -> minter_did::error::Result<Vec<Proposal<AdminOperation>>> {
+        let approvals = get_admin_approvals();
+        if !approvals
+            .config()
+            .is_some_and(|config| config.is_approver(&ic::caller()))
+        {
+            return Err(minter_did::error::Error::NotAuthorized);
+        }
+
+        Ok(approvals.open_proposals(get_state().borrow().clock().now()))
+    }
+
+    fn check_admin(caller: Principal) -> minter_did::error::Result<()> {
+        get_state()
+            .borrow()
+            .config
+            .check_admin(caller)
+            .ok_or(minter_did::error::Error::NotAuthorized)
+    }
+
+    /// Checks that the `caller` may apply a dangerous operation without an approved proposal.
+    fn check_direct_admin(caller: Principal) -> minter_did::error::Result<()> {
+        Self::check_admin(caller)?;
+        get_admin_approvals().check_direct().map_err(approval_error)
+    }
+
+    async fn apply_admin_operation(operation: AdminOperation) -> minter_did::error::Result<()> {
+        match operation {
+            AdminOperation::SetBftBridgeContract { address, side } => {
+                let state = get_state();
+                let mut state = state.borrow_mut();
+                state.config.set_bft_bridge_contract(side, address);
+                // The new bridge may charge the fees with another contract.
+                state.set_fee_charge(side, None);
+                Ok(())
+            }
+            AdminOperation::SetMintOrderTtl(ttl_secs) => {
+                get_state().borrow_mut().config.set_mint_order_ttl(ttl_secs);
+                Ok(())
+            }
+            AdminOperation::SetTaskLimits(limits) => {
+                limits
+                    .validate()
+                    .map_err(minter_did::error::Error::Internal)?;
+                get_state().borrow_mut().task_limiter.set_limits(limits);
+                Ok(())
+            }
+            AdminOperation::SetGasLimits(limits) => {
+                limits
+                    .validate()
+                    .map_err(minter_did::error::Error::Internal)?;
+                get_state().borrow_mut().gas_limits = limits;
+                Ok(())
+            }
+            AdminOperation::SetMaxInFlightTxs {
+                side,
+                max_in_flight,
+            } => get_state()
+                .borrow_mut()
+                .in_flight_txs_mut(side)
+                .set_max_in_flight(max_in_flight)
+                .map_err(minter_did::error::Error::Internal),
+            AdminOperation::AddEventSubscriber(subscriber) => {
+                get_event_subscribers().add(subscriber)
+            }
+            AdminOperation::RemoveEventSubscriber(subscriber) => {
+                get_event_subscribers().remove(&subscriber);
+                Ok(())
+            }
+            AdminOperation::SetApprovalConfig(config) => get_admin_approvals()
+                .configure(config)
+                .map_err(approval_error),
+            AdminOperation::ReissueMintOrder(operation_id) => {
+                Self::reissue_mint_order(operation_id)
+            }
+            AdminOperation::CancelMintOrder(operation_id) => {
+                Err(minter_did::error::Error::Internal(format!(
+                    "mint order of operation {operation_id} can't be cancelled: the signed order stays valid in the BftBridge, so it can only be re-issued"
+                )))
+            }
+            AdminOperation::ReplayEvents {
+                side,
+                from_block,
+                to_block,
+            } => Self::replay_events(side, from_block, to_block)
+                .await
+                .map(|_| ()),
+            AdminOperation::ResignMintOrders { start, end } => {
+                Self::resign_mint_orders(start, end).await.map(|_| ())
+            }
+        }
+    }
+
     fn check_anonymous_principal(principal: Principal) -> minter_did::error::Result<()> {
         if principal == Principal::anonymous() {
             return Err(minter_did::error::Error::AnonymousPrincipal);
@@ -215,7 +900,43 @@ type TasksStorage =
     StableBTreeMap<u32, InnerScheduledTask<BridgeTask>, VirtualMemory<DefaultMemoryImpl>>;
 type PersistentScheduler = Scheduler<BridgeTask, TasksStorage>;
 
-fn log_task_execution_error(task: InnerScheduledTask<BridgeTask>) {
+fn on_task_completed(task: InnerScheduledTask<BridgeTask>) {
+    if reschedule_deferred_task(&task) {
+        return;
+    }
+
+    task.task().release();
+    log_task_execution_error(&task);
+}
+
+/// Appends a task whose retries were used up by the deferrals of the task limits again, with the
+/// options it was appended with. Returns `false` if the task is completed or has failed for
+/// another reason. The scheduler is borrowed while it reports the completed tasks, so the task is
+/// appended by a timer after [`DEFERRED_TASK_DELAY_SECS`].
+fn reschedule_deferred_task(task: &InnerScheduledTask<BridgeTask>) -> bool {
+    let TaskStatus::Failed {
+        error: SchedulerError::TaskExecutionFailed(error),
+        ..
+    } = task.status()
+    else {
+        return false;
+    };
+    if !is_deferral_error(error.as_str()) {
+        return false;
+    }
+
+    log::debug!("task #{} is rescheduled after its deferrals", task.id());
+    let scheduled = ScheduledTask::with_options(task.task().clone(), task.options().clone());
+    ic_exports::ic_cdk_timers::set_timer(
+        Duration::from_secs(DEFERRED_TASK_DELAY_SECS),
+        move || {
+            get_scheduler().borrow().append_task(scheduled);
+        },
+    );
+    true
+}
+
+fn log_task_execution_error(task: &InnerScheduledTask<BridgeTask>) {
     match task.status() {
         TaskStatus::Failed {
             timestamp_secs,
@@ -224,10 +945,27 @@ fn log_task_execution_error(task: InnerScheduledTask<BridgeTask>) {
             log::error!(
                 "task #{} execution failed: {error} at {timestamp_secs}",
                 task.id()
-            )
+            );
+            if let Some(operation_id) = task.task().operation_id() {
+                operation_trace::record(
+                    operation_id,
+                    log::Level::Error,
+                    format!(
+                        "task={} status=failed error={error}",
+                        task.task().task_type()
+                    ),
+                );
+            }
         }
         TaskStatus::TimeoutOrPanic { timestamp_secs } => {
-            log::error!("task #{} panicked at {timestamp_secs}", task.id())
+            log::error!("task #{} panicked at {timestamp_secs}", task.id());
+            if let Some(operation_id) = task.task().operation_id() {
+                operation_trace::record(
+                    operation_id,
+                    log::Level::Error,
+                    format!("task={} status=panicked", task.task().task_type()),
+                );
+            }
         }
         _ => (),
     };
@@ -263,6 +1001,48 @@ pub fn get_operations_store(
     })
 }
 
+fn approval_error(err: ApprovalError) -> minter_did::error::Error {
+    match err {
+        ApprovalError::NotApprover => minter_did::error::Error::NotAuthorized,
+        err => minter_did::error::Error::Internal(err.to_string()),
+    }
+}
+
+pub fn get_admin_approvals() -> ApprovalStore<AdminOperation, VirtualMemory<DefaultMemoryImpl>> {
+    MEMORY_MANAGER.with(|mm| {
+        ApprovalStore::new(
+            mm.get(APPROVAL_SETTINGS_MEMORY_ID),
+            mm.get(APPROVAL_PROPOSALS_MEMORY_ID),
+        )
+    })
+}
+
+pub fn get_event_subscribers() -> EventSubscribers<VirtualMemory<DefaultMemoryImpl>> {
+    MEMORY_MANAGER.with(|mm| EventSubscribers::new(mm.get(EVENT_SUBSCRIBERS_MEMORY_ID)))
+}
+
+pub fn get_token_registry() -> TokenRegistry<VirtualMemory<DefaultMemoryImpl>> {
+    MEMORY_MANAGER.with(|mm| TokenRegistry::new(mm.get(TOKEN_REGISTRY_MEMORY_ID)))
+}
+
+pub fn get_processed_events() -> ProcessedEvents<VirtualMemory<DefaultMemoryImpl>> {
+    MEMORY_MANAGER.with(|mm| ProcessedEvents::new(mm.get(PROCESSED_EVENTS_MEMORY_ID)))
+}
+
+/// Deposits of the users in the FeeCharge contracts of the EVM on the given side.
+pub fn get_fee_balances(side: BridgeSide) -> FeeBalances<VirtualMemory<DefaultMemoryImpl>> {
+    let memory_id = match side {
+        BridgeSide::Base => BASE_FEE_BALANCES_MEMORY_ID,
+        BridgeSide::Wrapped => WRAPPED_FEE_BALANCES_MEMORY_ID,
+    };
+    MEMORY_MANAGER.with(|mm| FeeBalances::new(mm.get(memory_id)))
+}
+
+/// Metadata of the base EVM tokens, read from the token contracts.
+pub fn get_token_metadata() -> Erc20MetadataCache<VirtualMemory<DefaultMemoryImpl>> {
+    MEMORY_MANAGER.with(|mm| Erc20MetadataCache::new(mm.get(TOKEN_METADATA_MEMORY_ID)))
+}
+
 #[cfg(test)]
 mod test {
     use candid::Principal;
@@ -293,8 +1073,33 @@ mod test {
                 private_key: [0; 32],
             },
             log_settings: None,
+            mint_order_ttl_secs: None,
         };
 
         canister_call!(canister.init(init_data), ()).await.unwrap();
     }
+
+    #[tokio::test]
+    async fn validate_config_lists_all_errors() {
+        MockContext::new().inject();
+        let canister = EvmMinter::from_principal(Principal::from_slice(&[1; 10]));
+
+        let settings = Settings {
+            base_evm_link: EvmLink::Http("http://evm".to_string()),
+            wrapped_evm_link: EvmLink::Http("http://evm".to_string()),
+            signing_strategy: SigningStrategy::Local {
+                private_key: [0; 32],
+            },
+            log_settings: None,
+            mint_order_ttl_secs: Some(0),
+        };
+
+        let errors = canister_call!(canister.validate_config(settings), Vec<ConfigError>)
+            .await
+            .unwrap();
+        assert_eq!(errors.len(), 4, "{errors:?}");
+        assert!(errors
+            .iter()
+            .any(|err| matches!(err, ConfigError::Conflict { .. })));
+    }
 }
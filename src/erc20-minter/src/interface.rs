@@ -0,0 +1,40 @@
+use candid::{CandidType, Principal};
+use did::H160;
+use minter_contract_utils::admin_approvals::ApprovalConfig;
+use minter_contract_utils::evm_bridge::BridgeSide;
+use minter_contract_utils::gas_limits::GasLimits;
+use minter_contract_utils::operation_store::MinterOperationId;
+use minter_contract_utils::task_limits::TaskLimits;
+use serde::Deserialize;
+
+/// Dangerous operation applied through an approved proposal when the approvals are enabled.
+#[derive(Debug, Clone, CandidType, Deserialize)]
+pub enum AdminOperation {
+    SetBftBridgeContract {
+        address: H160,
+        side: BridgeSide,
+    },
+    SetMintOrderTtl(Option<u64>),
+    SetTaskLimits(TaskLimits),
+    SetGasLimits(GasLimits),
+    SetMaxInFlightTxs {
+        side: BridgeSide,
+        max_in_flight: u32,
+    },
+    AddEventSubscriber(Principal),
+    RemoveEventSubscriber(Principal),
+    SetApprovalConfig(Option<ApprovalConfig>),
+    ReissueMintOrder(MinterOperationId),
+    /// Kept to decode the stored proposals. The signed mint orders stay valid in the BftBridge,
+    /// so applying it fails.
+    CancelMintOrder(MinterOperationId),
+    ReplayEvents {
+        side: BridgeSide,
+        from_block: u64,
+        to_block: u64,
+    },
+    ResignMintOrders {
+        start: MinterOperationId,
+        end: MinterOperationId,
+    },
+}
@@ -1,8 +1,11 @@
 pub mod canister;
+pub mod interface;
 pub mod memory;
 pub mod operation;
+pub mod processed_events;
 pub mod state;
 pub mod tasks;
+pub mod token_registry;
 
 use ic_metrics::Metrics;
 
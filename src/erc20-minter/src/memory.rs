@@ -5,10 +5,45 @@ pub const CONFIG_MEMORY_ID: MemoryId = MemoryId::new(0);
 pub const PENDING_TASKS_MEMORY_ID: MemoryId = MemoryId::new(1);
 pub const SIGNER_MEMORY_ID: MemoryId = MemoryId::new(2);
 pub const LOGGER_SETTINGS_MEMORY_ID: MemoryId = MemoryId::new(4);
+pub const EVENT_SUBSCRIBERS_MEMORY_ID: MemoryId = MemoryId::new(5);
+pub const TOKEN_REGISTRY_MEMORY_ID: MemoryId = MemoryId::new(6);
+pub const PROCESSED_EVENTS_MEMORY_ID: MemoryId = MemoryId::new(7);
+pub const TOKEN_METADATA_MEMORY_ID: MemoryId = MemoryId::new(8);
+pub const APPROVAL_SETTINGS_MEMORY_ID: MemoryId = MemoryId::new(11);
+pub const APPROVAL_PROPOSALS_MEMORY_ID: MemoryId = MemoryId::new(12);
+pub const TASK_LIMITS_MEMORY_ID: MemoryId = MemoryId::new(13);
+pub const BASE_IN_FLIGHT_TXS_MEMORY_ID: MemoryId = MemoryId::new(14);
+pub const WRAPPED_IN_FLIGHT_TXS_MEMORY_ID: MemoryId = MemoryId::new(15);
+// The memories 9 and 10 held the fee deposits of the users before they were kept for each
+// FeeCharge contract, and are not used anymore.
+pub const BASE_FEE_BALANCES_MEMORY_ID: MemoryId = MemoryId::new(16);
+pub const WRAPPED_FEE_BALANCES_MEMORY_ID: MemoryId = MemoryId::new(17);
 pub const OPERATIONS_MEMORY_ID: MemoryId = MemoryId::new(88);
 pub const OPERATIONS_LOG_MEMORY_ID: MemoryId = MemoryId::new(89);
 pub const OPERATIONS_MAP_MEMORY_ID: MemoryId = MemoryId::new(90);
 
+/// Stable structures reported by the `canister_status_info` query.
+pub const STABLE_STRUCTURES: &[(&str, MemoryId)] = &[
+    ("config", CONFIG_MEMORY_ID),
+    ("pending_tasks", PENDING_TASKS_MEMORY_ID),
+    ("signer", SIGNER_MEMORY_ID),
+    ("logger_settings", LOGGER_SETTINGS_MEMORY_ID),
+    ("event_subscribers", EVENT_SUBSCRIBERS_MEMORY_ID),
+    ("token_registry", TOKEN_REGISTRY_MEMORY_ID),
+    ("processed_events", PROCESSED_EVENTS_MEMORY_ID),
+    ("token_metadata", TOKEN_METADATA_MEMORY_ID),
+    ("approval_settings", APPROVAL_SETTINGS_MEMORY_ID),
+    ("approval_proposals", APPROVAL_PROPOSALS_MEMORY_ID),
+    ("task_limits", TASK_LIMITS_MEMORY_ID),
+    ("base_in_flight_txs", BASE_IN_FLIGHT_TXS_MEMORY_ID),
+    ("wrapped_in_flight_txs", WRAPPED_IN_FLIGHT_TXS_MEMORY_ID),
+    ("base_fee_balances", BASE_FEE_BALANCES_MEMORY_ID),
+    ("wrapped_fee_balances", WRAPPED_FEE_BALANCES_MEMORY_ID),
+    ("operations", OPERATIONS_MEMORY_ID),
+    ("operations_log", OPERATIONS_LOG_MEMORY_ID),
+    ("operations_map", OPERATIONS_MAP_MEMORY_ID),
+];
+
 thread_local! {
     pub static MEMORY_MANAGER: IcMemoryManager<DefaultMemoryImpl> = IcMemoryManager::init(DefaultMemoryImpl::default());
 }
@@ -1,8 +1,9 @@
 use candid::{CandidType, Deserialize};
-use did::{H256, U256};
+use did::{H160, H256, U256};
 use minter_contract_utils::bft_bridge_api::BurntEventData;
 use minter_contract_utils::evm_bridge::BridgeSide;
-use minter_contract_utils::operation_store::MinterOperation;
+use minter_contract_utils::mint_order_codec::decode_stored_mint_order;
+use minter_contract_utils::operation_store::{MinterOperation, MinterOperationId};
 use minter_did::id256::Id256;
 use minter_did::order::SignedMintOrder;
 
@@ -37,6 +38,147 @@ impl OperationPayload {
             _ => None,
         }
     }
+
+    /// Signed mint order which is not sent to the EVM, if any.
+    pub fn unsent_mint_order(&self) -> Option<&SignedMintOrder> {
+        match &self.status {
+            OperationStatus::MintOrderSigned {
+                signed_mint_order, ..
+            }
+            | OperationStatus::Expired {
+                signed_mint_order,
+                tx_id: None,
+                ..
+            } => Some(signed_mint_order),
+            _ => None,
+        }
+    }
+
+    /// Replaces the unsent mint order `old` with the `resigned` one. Fails if the operation has
+    /// no unsent order or its order is not `old` anymore, e.g. if it was sent meanwhile.
+    pub fn replace_unsent_mint_order(
+        &mut self,
+        old: &SignedMintOrder,
+        resigned: SignedMintOrder,
+    ) -> Result<(), String> {
+        match &mut self.status {
+            OperationStatus::MintOrderSigned {
+                signed_mint_order, ..
+            }
+            | OperationStatus::Expired {
+                signed_mint_order,
+                tx_id: None,
+                ..
+            } if **signed_mint_order == *old => {
+                **signed_mint_order = resigned;
+                Ok(())
+            }
+            status => Err(format!(
+                "mint order of the operation has changed, the operation status is {status:?}"
+            )),
+        }
+    }
+
+    /// Expiration timestamp of the signed mint order, if any.
+    pub fn expires_at(&self) -> Option<u64> {
+        match &self.status {
+            OperationStatus::MintOrderSigned { expires_at, .. }
+            | OperationStatus::MintOrderSent { expires_at, .. } => *expires_at,
+            _ => None,
+        }
+    }
+
+    /// Moves the operation into the `Expired` state if its mint order expired at the moment
+    /// `now`. Returns `true` if the status was changed.
+    ///
+    /// The orders whose mint transaction is sent never expire, as the transaction can still be
+    /// included.
+    pub fn expire(&mut self, now: u64) -> bool {
+        let expired = match &self.status {
+            OperationStatus::MintOrderSigned {
+                token_id,
+                amount,
+                signed_mint_order,
+                expires_at: Some(expires_at),
+            } if *expires_at <= now => OperationStatus::Expired {
+                token_id: *token_id,
+                amount: amount.clone(),
+                signed_mint_order: signed_mint_order.clone(),
+                tx_id: None,
+                expired_at: now,
+            },
+            _ => return false,
+        };
+
+        self.status = expired;
+        true
+    }
+
+    /// Returns the expired mint order back into the `MintOrderSigned` state with the new
+    /// expiration timestamp, so that it is sent again.
+    pub fn reissue(&mut self, expires_at: Option<u64>) -> Result<(), String> {
+        let OperationStatus::Expired {
+            token_id,
+            amount,
+            signed_mint_order,
+            ..
+        } = &self.status
+        else {
+            return Err(format!(
+                "only expired mint orders can be re-issued, but the operation status is {:?}",
+                self.status
+            ));
+        };
+
+        self.status = OperationStatus::MintOrderSigned {
+            token_id: *token_id,
+            amount: amount.clone(),
+            signed_mint_order: signed_mint_order.clone(),
+            expires_at,
+        };
+
+        Ok(())
+    }
+
+    /// Returns the nonce of the mint order of the operation, which is the id of the burn
+    /// operation in the BftBridge.
+    pub fn mint_order_nonce(&self) -> Option<u32> {
+        match &self.status {
+            OperationStatus::Scheduled(burnt) => Some(burnt.operation_id),
+            OperationStatus::MintOrderSigned {
+                signed_mint_order, ..
+            }
+            | OperationStatus::MintOrderSent {
+                signed_mint_order, ..
+            }
+            | OperationStatus::Expired {
+                signed_mint_order, ..
+            } => decode_stored_mint_order(signed_mint_order)
+                .ok()
+                .map(|order| order.nonce),
+            OperationStatus::Minted { nonce, .. } | OperationStatus::Cancelled { nonce, .. } => {
+                *nonce
+            }
+        }
+    }
+
+    /// Returns the fee payer of the signed mint order of the operation, if any.
+    pub fn mint_order_fee_payer(&self) -> Option<H160> {
+        match &self.status {
+            OperationStatus::MintOrderSigned {
+                signed_mint_order, ..
+            }
+            | OperationStatus::MintOrderSent {
+                signed_mint_order, ..
+            }
+            | OperationStatus::Expired {
+                signed_mint_order, ..
+            } => decode_stored_mint_order(signed_mint_order)
+                .ok()
+                .map(|order| order.fee_payer),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, CandidType, Deserialize)]
@@ -46,22 +188,250 @@ pub enum OperationStatus {
         token_id: Id256,
         amount: U256,
         signed_mint_order: Box<SignedMintOrder>,
+        /// Timestamp (nanoseconds) after which the order is considered unclaimed.
+        expires_at: Option<u64>,
     },
     MintOrderSent {
         token_id: Id256,
         amount: U256,
         signed_mint_order: Box<SignedMintOrder>,
         tx_id: H256,
+        /// Timestamp (nanoseconds) after which the order is considered unclaimed.
+        expires_at: Option<u64>,
     },
     Minted {
         token_id: Id256,
         amount: U256,
         tx_id: H256,
+        /// Nonce of the minted order. `None` for the operations minted before it was recorded.
+        nonce: Option<u32>,
+    },
+    /// The mint order was not claimed before its expiration. The admin can re-issue it.
+    ///
+    /// The signed order stays valid in the BftBridge, so the order can't be cancelled.
+    Expired {
+        token_id: Id256,
+        amount: U256,
+        signed_mint_order: Box<SignedMintOrder>,
+        /// Mint transaction of the orders expired after it was sent. Only the orders expired
+        /// before the sent orders stopped expiring have it.
+        tx_id: Option<H256>,
+        expired_at: u64,
+    },
+    /// The expired mint order was cancelled by the admin, before the cancellation was refused
+    /// for the signed orders. Kept to decode the stored operations.
+    ///
+    /// Cancellation didn't invalidate the signed order in the BFT bridge contract, it only
+    /// stopped the minter from tracking and re-sending it.
+    Cancelled {
+        token_id: Id256,
+        amount: U256,
+        /// Nonce of the cancelled order. `None` for the operations cancelled before it was
+        /// recorded.
+        nonce: Option<u32>,
     },
 }
 
+/// Returns `true` if one of the `operations` of the sender of the `burnt` event is created for
+/// the burn already. The operations created before the processed events were recorded are found
+/// this way when their burns are replayed.
+pub fn is_burn_recorded(
+    operations: &[(MinterOperationId, OperationPayload)],
+    burnt: &BurntEventData,
+) -> bool {
+    operations
+        .iter()
+        .any(|(_, operation)| operation.mint_order_nonce() == Some(burnt.operation_id))
+}
+
 impl MinterOperation for OperationPayload {
     fn is_complete(&self) -> bool {
-        matches!(self.status, OperationStatus::Minted { .. })
+        matches!(
+            self.status,
+            OperationStatus::Minted { .. } | OperationStatus::Cancelled { .. }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use candid::Principal;
+    use did::H160;
+    use minter_did::order::MintOrder;
+
+    use super::*;
+
+    fn signed_payload(expires_at: Option<u64>) -> OperationPayload {
+        OperationPayload {
+            side: BridgeSide::Base,
+            status: OperationStatus::MintOrderSigned {
+                token_id: Id256::from(&Principal::management_canister()),
+                amount: U256::from(1000u64),
+                signed_mint_order: Box::new(SignedMintOrder(
+                    [0; MintOrder::SIGNED_ENCODED_DATA_SIZE],
+                )),
+                expires_at,
+            },
+        }
+    }
+
+    #[test]
+    fn mint_order_expires_after_deadline() {
+        let mut payload = signed_payload(Some(100));
+
+        assert!(!payload.expire(99));
+        assert!(payload.get_signed_mint_order(None).is_some());
+
+        assert!(payload.expire(100));
+        assert!(matches!(
+            payload.status,
+            OperationStatus::Expired {
+                tx_id: None,
+                expired_at: 100,
+                ..
+            }
+        ));
+        assert!(payload.get_signed_mint_order(None).is_none());
+        assert!(!payload.is_complete());
+    }
+
+    #[test]
+    fn mint_order_without_deadline_never_expires() {
+        let mut payload = signed_payload(None);
+        assert!(!payload.expire(u64::MAX));
+    }
+
+    #[test]
+    fn sent_mint_order_never_expires() {
+        let mut payload = signed_payload(Some(100));
+        let OperationStatus::MintOrderSigned {
+            token_id,
+            amount,
+            signed_mint_order,
+            expires_at,
+        } = payload.status.clone()
+        else {
+            unreachable!()
+        };
+        payload.status = OperationStatus::MintOrderSent {
+            token_id,
+            amount,
+            signed_mint_order,
+            tx_id: H256::default(),
+            expires_at,
+        };
+
+        assert!(!payload.expire(u64::MAX));
+        assert!(matches!(
+            payload.status,
+            OperationStatus::MintOrderSent { .. }
+        ));
+    }
+
+    #[test]
+    fn expired_order_can_be_reissued() {
+        let mut payload = signed_payload(Some(100));
+        assert!(payload.reissue(Some(300)).is_err());
+
+        payload.expire(200);
+        payload.reissue(Some(300)).unwrap();
+        assert_eq!(payload.expires_at(), Some(300));
+        assert!(payload.get_signed_mint_order(None).is_some());
+    }
+
+    #[test]
+    fn unsent_order_can_be_replaced() {
+        let old = SignedMintOrder([0; MintOrder::SIGNED_ENCODED_DATA_SIZE]);
+        let resigned = SignedMintOrder([1; MintOrder::SIGNED_ENCODED_DATA_SIZE]);
+
+        let mut payload = signed_payload(Some(100));
+        assert_eq!(payload.unsent_mint_order(), Some(&old));
+        assert!(payload
+            .replace_unsent_mint_order(&resigned, resigned)
+            .is_err());
+        payload.replace_unsent_mint_order(&old, resigned).unwrap();
+        assert_eq!(payload.get_signed_mint_order(None), Some(&resigned));
+
+        payload.expire(200);
+        assert_eq!(payload.unsent_mint_order(), Some(&resigned));
+    }
+
+    fn burnt(operation_id: u32) -> BurntEventData {
+        BurntEventData {
+            sender: H160::from_slice(&[1; 20]),
+            amount: U256::from(1000u64),
+            from_erc20: H160::from_slice(&[2; 20]),
+            recipient_id: vec![3; 20],
+            to_token: vec![4; 32],
+            operation_id,
+            name: vec![],
+            symbol: vec![],
+            decimals: 18,
+        }
+    }
+
+    fn signed_order_with_nonce(nonce: u32) -> Box<SignedMintOrder> {
+        // The nonce follows the amount, the sender, the source token, the recipient and the
+        // destination token in the encoded order.
+        let mut data = [0; MintOrder::SIGNED_ENCODED_DATA_SIZE];
+        data[136..140].copy_from_slice(&nonce.to_be_bytes());
+        Box::new(SignedMintOrder(data))
+    }
+
+    #[test]
+    fn mint_order_nonce_is_the_burn_operation_id() {
+        let payload = OperationPayload::new(BridgeSide::Base, burnt(5));
+        assert_eq!(payload.mint_order_nonce(), Some(5));
+
+        let mut payload = signed_payload(Some(100));
+        if let OperationStatus::MintOrderSigned {
+            signed_mint_order, ..
+        } = &mut payload.status
+        {
+            *signed_mint_order = signed_order_with_nonce(6);
+        }
+        assert_eq!(payload.mint_order_nonce(), Some(6));
+
+        payload.expire(200);
+        assert_eq!(payload.mint_order_nonce(), Some(6));
+    }
+
+    #[test]
+    fn recorded_burns_are_found() {
+        let operations = vec![
+            (
+                MinterOperationId::from(1),
+                OperationPayload::new(BridgeSide::Base, burnt(5)),
+            ),
+            (
+                MinterOperationId::from(2),
+                OperationPayload {
+                    side: BridgeSide::Base,
+                    status: OperationStatus::Minted {
+                        token_id: Id256::from(&Principal::management_canister()),
+                        amount: U256::from(1000u64),
+                        tx_id: H256::default(),
+                        nonce: Some(7),
+                    },
+                },
+            ),
+            (
+                MinterOperationId::from(3),
+                OperationPayload {
+                    side: BridgeSide::Base,
+                    status: OperationStatus::Minted {
+                        token_id: Id256::from(&Principal::management_canister()),
+                        amount: U256::from(1000u64),
+                        tx_id: H256::default(),
+                        nonce: None,
+                    },
+                },
+            ),
+        ];
+
+        assert!(is_burn_recorded(&operations, &burnt(5)));
+        assert!(is_burn_recorded(&operations, &burnt(7)));
+        assert!(!is_burn_recorded(&operations, &burnt(8)));
+        assert!(!is_burn_recorded(&[], &burnt(5)));
     }
 }
@@ -0,0 +1,165 @@
+//! Record of the BftBridge events processed by the minter.
+//!
+//! The collected `Burnt` and `Minted` events are recorded with their position in the chain, so
+//! an event collected twice, by the regular scan and by the replay of a block range with
+//! `admin_replay_events`, is processed only once.
+
+use std::borrow::Cow;
+
+use candid::CandidType;
+use ethers_core::types::Log;
+use ic_stable_structures::stable_structures::Memory;
+use ic_stable_structures::{BTreeMapStructure, Bound, StableBTreeMap, Storable};
+use minter_contract_utils::burn_notification::EventKey;
+use minter_contract_utils::evm_bridge::BridgeSide;
+use serde::Deserialize;
+
+/// Events recovered by the replay of a block range.
+#[derive(Debug, Default, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct ReplayReport {
+    /// Number of the `Burnt` events scheduled for minting.
+    pub burns: u32,
+    /// Number of the `Minted` events scheduled for the mint order removal.
+    pub mints: u32,
+    /// Number of the events in the range which were processed already.
+    pub skipped: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct ProcessedEventKey {
+    side: u8,
+    event: EventKey,
+}
+
+impl ProcessedEventKey {
+    const SIZE: usize = 1 + EventKey::SIZE;
+
+    fn new(side: BridgeSide, event: EventKey) -> Self {
+        Self {
+            side: side as u8,
+            event,
+        }
+    }
+}
+
+impl Storable for ProcessedEventKey {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        let mut bytes = Vec::with_capacity(Self::SIZE);
+        bytes.push(self.side);
+        bytes.extend_from_slice(&self.event.to_bytes());
+        Cow::Owned(bytes)
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Self {
+            side: bytes[0],
+            event: EventKey::from_bytes(Cow::Borrowed(&bytes[1..])),
+        }
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: Self::SIZE as u32,
+        is_fixed_size: true,
+    };
+}
+
+pub struct ProcessedEvents<M: Memory> {
+    events: StableBTreeMap<ProcessedEventKey, (), M>,
+}
+
+impl<M: Memory> ProcessedEvents<M> {
+    pub fn new(memory: M) -> Self {
+        Self {
+            events: StableBTreeMap::new(memory),
+        }
+    }
+
+    /// Records the `logs` collected from the `side` and returns the ones not processed before.
+    /// The pending logs have no position in the chain, so they are dropped.
+    pub fn filter_new(&mut self, logs: Vec<Log>, side: BridgeSide) -> Vec<Log> {
+        logs.into_iter()
+            .filter(|log| {
+                let Some(event) = EventKey::from_log(log) else {
+                    return false;
+                };
+                self.events
+                    .insert(ProcessedEventKey::new(side, event), ())
+                    .is_none()
+            })
+            .collect()
+    }
+
+    pub fn is_processed(&self, log: &Log, side: BridgeSide) -> bool {
+        EventKey::from_log(log).is_some_and(|event| {
+            self.events
+                .contains_key(&ProcessedEventKey::new(side, event))
+        })
+    }
+
+    pub fn len(&self) -> u64 {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers_core::types::{H256, U256, U64};
+    use ic_stable_structures::VectorMemory;
+
+    use super::*;
+
+    fn log(block_number: u64, log_index: u64) -> Log {
+        Log {
+            block_number: Some(U64::from(block_number)),
+            transaction_hash: Some(H256::repeat_byte(block_number as u8)),
+            log_index: Some(U256::from(log_index)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn event_is_processed_once_per_side() {
+        let mut events = ProcessedEvents::new(VectorMemory::default());
+
+        let new = events.filter_new(vec![log(10, 0), log(10, 1)], BridgeSide::Base);
+        assert_eq!(new.len(), 2);
+
+        let new = events.filter_new(vec![log(10, 1), log(11, 0)], BridgeSide::Base);
+        assert_eq!(new, vec![log(11, 0)]);
+        assert!(events.is_processed(&log(10, 0), BridgeSide::Base));
+
+        // The same position on the other chain is another event.
+        assert!(!events.is_processed(&log(10, 0), BridgeSide::Wrapped));
+        assert_eq!(
+            events
+                .filter_new(vec![log(10, 0)], BridgeSide::Wrapped)
+                .len(),
+            1
+        );
+        assert_eq!(events.len(), 4);
+    }
+
+    #[test]
+    fn pending_logs_are_dropped() {
+        let mut events = ProcessedEvents::new(VectorMemory::default());
+        let pending = Log::default();
+
+        assert!(events
+            .filter_new(vec![pending], BridgeSide::Base)
+            .is_empty());
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn key_roundtrip() {
+        let key = ProcessedEventKey::new(
+            BridgeSide::Wrapped,
+            EventKey::from_log(&log(300, 2)).unwrap(),
+        );
+        assert_eq!(ProcessedEventKey::from_bytes(key.to_bytes()), key);
+    }
+}
@@ -1,28 +1,62 @@
 use std::fmt;
+use std::rc::Rc;
 
 use candid::{CandidType, Principal};
 pub use config::Config;
+use did::H160;
 use eth_signer::sign_strategy::{
     ManagementCanisterSigner, SigningKeyId, SigningStrategy, TxSigner,
 };
 use ic_log::LogSettings;
 use ic_stable_structures::stable_structures::DefaultMemoryImpl;
 use ic_stable_structures::{CellStructure, StableCell, VirtualMemory};
+use minter_contract_utils::clock::{Clock, IcClock};
+use minter_contract_utils::config_validation::{
+    format_config_errors, ConfigError, ConfigValidator,
+};
+use minter_contract_utils::evm_bridge::BridgeSide;
 use minter_contract_utils::evm_link::EvmLink;
+use minter_contract_utils::gas_limits::GasLimits;
+use minter_contract_utils::gas_price::GasPriceSampler;
+use minter_contract_utils::health::{HealthMonitor, SIGNER};
+use minter_contract_utils::in_flight_txs::InFlightTxs;
+use minter_contract_utils::operation_store::MinterOperationId;
+use minter_contract_utils::task_dedup::PendingTasks;
+use minter_contract_utils::task_limits::TaskLimiter;
 use serde::Deserialize;
 
 use self::log::LoggerConfigService;
-use crate::memory::{MEMORY_MANAGER, SIGNER_MEMORY_ID};
+use crate::memory::{
+    BASE_IN_FLIGHT_TXS_MEMORY_ID, MEMORY_MANAGER, SIGNER_MEMORY_ID, TASK_LIMITS_MEMORY_ID,
+    WRAPPED_IN_FLIGHT_TXS_MEMORY_ID,
+};
 
 mod config;
 mod log;
 
+/// Names of the EVM JSON-RPC endpoints of the bridge sides in the health report.
+pub const BASE_EVM_RPC: &str = "base_evm_rpc";
+pub const WRAPPED_EVM_RPC: &str = "wrapped_evm_rpc";
+
 type SignerStorage = StableCell<TxSigner, VirtualMemory<DefaultMemoryImpl>>;
+type InFlightTxsWindow = InFlightTxs<MinterOperationId, VirtualMemory<DefaultMemoryImpl>>;
 
 pub struct State {
     pub config: Config,
     pub signer: SignerStorage,
     pub logger: LoggerConfigService,
+    pub task_limiter: TaskLimiter<VirtualMemory<DefaultMemoryImpl>>,
+    pub pending_tasks: PendingTasks,
+    pub health: HealthMonitor,
+    pub base_gas_price: GasPriceSampler,
+    pub wrapped_gas_price: GasPriceSampler,
+    pub gas_limits: GasLimits,
+    pub base_in_flight_txs: InFlightTxsWindow,
+    pub wrapped_in_flight_txs: InFlightTxsWindow,
+    /// FeeCharge contracts of the BftBridges, `None` until read from the bridge of the side.
+    pub base_fee_charge: Option<Option<H160>>,
+    pub wrapped_fee_charge: Option<Option<H160>>,
+    pub clock: Rc<dyn Clock>,
 }
 
 impl Default for State {
@@ -41,6 +75,21 @@ impl Default for State {
             config: Default::default(),
             signer,
             logger,
+            task_limiter: TaskLimiter::new(MEMORY_MANAGER.with(|mm| mm.get(TASK_LIMITS_MEMORY_ID))),
+            pending_tasks: PendingTasks::default(),
+            health: HealthMonitor::new(&[BASE_EVM_RPC, WRAPPED_EVM_RPC, SIGNER]),
+            base_gas_price: GasPriceSampler::default(),
+            wrapped_gas_price: GasPriceSampler::default(),
+            gas_limits: GasLimits::default(),
+            base_in_flight_txs: InFlightTxs::new(
+                MEMORY_MANAGER.with(|mm| mm.get(BASE_IN_FLIGHT_TXS_MEMORY_ID)),
+            ),
+            wrapped_in_flight_txs: InFlightTxs::new(
+                MEMORY_MANAGER.with(|mm| mm.get(WRAPPED_IN_FLIGHT_TXS_MEMORY_ID)),
+            ),
+            base_fee_charge: None,
+            wrapped_fee_charge: None,
+            clock: Rc::new(IcClock),
         }
     }
 }
@@ -55,7 +104,16 @@ impl fmt::Debug for State {
 }
 
 impl State {
+    /// Source of the time for the expiry, lease and gas price checks.
+    pub fn clock(&self) -> Rc<dyn Clock> {
+        self.clock.clone()
+    }
+
     pub fn init(&mut self, admin: Principal, settings: Settings) {
+        if let Err(errors) = settings.validate() {
+            panic!("{}", format_config_errors(&errors));
+        }
+
         let signer = settings
             .signing_strategy
             .clone()
@@ -70,6 +128,52 @@ impl State {
 
         self.signer.set(signer).expect("failed to set signer");
     }
+
+    /// Gas price estimate of the EVM on the given bridge side.
+    pub fn gas_price(&self, side: BridgeSide) -> &GasPriceSampler {
+        match side {
+            BridgeSide::Base => &self.base_gas_price,
+            BridgeSide::Wrapped => &self.wrapped_gas_price,
+        }
+    }
+
+    pub fn gas_price_mut(&mut self, side: BridgeSide) -> &mut GasPriceSampler {
+        match side {
+            BridgeSide::Base => &mut self.base_gas_price,
+            BridgeSide::Wrapped => &mut self.wrapped_gas_price,
+        }
+    }
+
+    /// FeeCharge contract of the BftBridge on the given side, or `Some(None)` if the bridge charges
+    /// no fees. `None` if the contract is not read from the bridge yet.
+    pub fn fee_charge(&self, side: BridgeSide) -> Option<Option<H160>> {
+        match side {
+            BridgeSide::Base => self.base_fee_charge.clone(),
+            BridgeSide::Wrapped => self.wrapped_fee_charge.clone(),
+        }
+    }
+
+    pub fn set_fee_charge(&mut self, side: BridgeSide, fee_charge: Option<Option<H160>>) {
+        match side {
+            BridgeSide::Base => self.base_fee_charge = fee_charge,
+            BridgeSide::Wrapped => self.wrapped_fee_charge = fee_charge,
+        }
+    }
+
+    /// Mint transactions sent to the EVM on the given bridge side and not yet confirmed.
+    pub fn in_flight_txs(&self, side: BridgeSide) -> &InFlightTxsWindow {
+        match side {
+            BridgeSide::Base => &self.base_in_flight_txs,
+            BridgeSide::Wrapped => &self.wrapped_in_flight_txs,
+        }
+    }
+
+    pub fn in_flight_txs_mut(&mut self, side: BridgeSide) -> &mut InFlightTxsWindow {
+        match side {
+            BridgeSide::Base => &mut self.base_in_flight_txs,
+            BridgeSide::Wrapped => &mut self.wrapped_in_flight_txs,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, CandidType)]
@@ -81,4 +185,32 @@ pub struct Settings {
     /// Log settings
     #[serde(default)]
     pub log_settings: Option<LogSettings>,
+
+    /// Time in seconds after which unclaimed mint orders expire. Mint orders never expire if
+    /// not set.
+    #[serde(default)]
+    pub mint_order_ttl_secs: Option<u64>,
+}
+
+impl Settings {
+    /// Checks the settings, returning all the errors found.
+    pub fn validate(&self) -> Result<(), Vec<ConfigError>> {
+        let mut validator = ConfigValidator::new();
+        validator
+            .evm_link("base_evm_link", &self.base_evm_link)
+            .evm_link("wrapped_evm_link", &self.wrapped_evm_link);
+
+        if self.base_evm_link == self.wrapped_evm_link {
+            validator.conflict(
+                &["base_evm_link", "wrapped_evm_link"],
+                "base and wrapped sides must be different EVMs",
+            );
+        }
+
+        if self.mint_order_ttl_secs == Some(0) {
+            validator.invalid("mint_order_ttl_secs", "must be greater than zero");
+        }
+
+        validator.finish()
+    }
 }
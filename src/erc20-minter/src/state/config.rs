@@ -1,5 +1,6 @@
 use std::borrow::Cow;
 use std::fmt;
+use std::time::Duration;
 
 use candid::{CandidType, Principal};
 use did::{codec, H160};
@@ -47,6 +48,8 @@ impl Config {
 
             let wrapped_evm = &mut data.evm_info_by_side_mut(BridgeSide::Wrapped);
             wrapped_evm.link = settings.wrapped_evm_link;
+
+            data.mint_order_ttl_secs = settings.mint_order_ttl_secs;
         })
     }
 
@@ -95,6 +98,18 @@ impl Config {
         })
     }
 
+    /// Returns the time after which a signed mint order that was not minted is considered
+    /// unclaimed. `None` means that mint orders never expire.
+    pub fn mint_order_ttl(&self) -> Option<Duration> {
+        self.data.get().mint_order_ttl_secs.map(Duration::from_secs)
+    }
+
+    /// Sets the mint order expiration time. The new value applies only to orders signed after
+    /// the change.
+    pub fn set_mint_order_ttl(&mut self, ttl_secs: Option<u64>) {
+        self.update_data(|data| data.mint_order_ttl_secs = ttl_secs);
+    }
+
     /// Checks if the caller is the admin.
     pub fn check_admin(&self, caller: Principal) -> Option<()> {
         (self.data.get().admin == caller).then_some(())
@@ -120,6 +135,8 @@ pub struct ConfigData {
     pub wrapped_evm: EvmInfo,
     pub base_bft_bridge: Option<H160>,
     pub wrapped_bft_bridge: Option<H160>,
+    #[serde(default)]
+    pub mint_order_ttl_secs: Option<u64>,
 }
 
 impl ConfigData {
@@ -164,6 +181,7 @@ impl Default for ConfigData {
             wrapped_evm: EvmInfo::default(),
             base_bft_bridge: Default::default(),
             wrapped_bft_bridge: Default::default(),
+            mint_order_ttl_secs: None,
         }
     }
 }
@@ -217,4 +235,13 @@ mod tests {
         let params = config.get_evm_params(BridgeSide::Wrapped).unwrap();
         assert_eq!(params.next_block, 200);
     }
+
+    #[test]
+    fn test_mint_order_ttl() {
+        let mut config = Config::default();
+        assert_eq!(config.mint_order_ttl(), None);
+
+        config.set_mint_order_ttl(Some(3600));
+        assert_eq!(config.mint_order_ttl(), Some(Duration::from_secs(3600)));
+    }
 }
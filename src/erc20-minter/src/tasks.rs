@@ -3,6 +3,7 @@ use std::future::Future;
 use std::pin::Pin;
 use std::rc::Rc;
 
+use candid::Principal;
 use did::{H160, U256};
 use eth_signer::sign_strategy::TransactionSigner;
 use ethers_core::types::{BlockNumber, Log};
@@ -13,16 +14,27 @@ use ic_task_scheduler::task::{ScheduledTask, Task, TaskOptions};
 use ic_task_scheduler::SchedulerError;
 use jsonrpc_core::Id;
 use minter_contract_utils::bft_bridge_api::{self, BridgeEvent, MintedEventData};
+use minter_contract_utils::event_subscribers::BridgeEventNotification;
 use minter_contract_utils::evm_bridge::{BridgeSide, EvmParams};
+use minter_contract_utils::fee_balances::{query_fee_balance, FeeBalance, FeeBalanceUpdate};
+use minter_contract_utils::gas_limits::GasOperation;
+use minter_contract_utils::gas_price;
+use minter_contract_utils::health::{self, SIGNER};
 use minter_contract_utils::operation_store::MinterOperationId;
 use minter_contract_utils::query::{self, Query, QueryType, GAS_PRICE_ID, NONCE_ID};
+use minter_contract_utils::task_dedup::TaskKey;
+use minter_contract_utils::task_limits::{TaskDeferred, TaskPriority};
 use minter_did::id256::Id256;
 use minter_did::order::MintOrder;
 use serde::{Deserialize, Serialize};
 
-use crate::canister::{get_operations_store, get_state};
-use crate::operation::{OperationPayload, OperationStatus};
-use crate::state::State;
+use crate::canister::{
+    get_event_subscribers, get_fee_balances, get_operations_store, get_processed_events, get_state,
+    get_token_metadata, get_token_registry,
+};
+use crate::operation::{is_burn_recorded, OperationPayload, OperationStatus};
+use crate::processed_events::ReplayReport;
+use crate::state::{State, BASE_EVM_RPC, WRAPPED_EVM_RPC};
 
 /// Task for the ERC-20 bridge
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -32,12 +44,55 @@ pub enum BridgeTask {
     PrepareMintOrder(MinterOperationId),
     RemoveMintOrder(MintedEventData, BridgeSide),
     SendMintTransaction(MinterOperationId),
+    NotifySubscriber(Principal, BridgeEventNotification),
+    ExpireMintOrders,
+    CheckHealth,
+    RefreshGasPrice(BridgeSide),
 }
 
 impl Task for BridgeTask {
     fn execute(
         &self,
         scheduler: Box<dyn 'static + TaskScheduler<Self>>,
+    ) -> Pin<Box<dyn Future<Output = Result<(), SchedulerError>>>> {
+        if let BridgeTask::SendMintTransaction(operation_id) = self {
+            if let Err(reason) = Self::acquire_tx_slot(*operation_id) {
+                log::debug!("Mint transaction of operation {operation_id} is queued: {reason}");
+                let error = SchedulerError::TaskExecutionFailed(reason.error_message());
+                return Box::pin(async move { Err(error) });
+            }
+        }
+
+        let permit = get_state()
+            .borrow()
+            .task_limiter
+            .try_acquire(self.task_type(), self.default_priority());
+        let permit = match permit {
+            Ok(permit) => permit,
+            Err(reason) => {
+                log::debug!("Task {} is deferred: {reason}", self.task_type());
+                let error = SchedulerError::TaskExecutionFailed(reason.error_message());
+                return Box::pin(async move { Err(error) });
+            }
+        };
+
+        let task = self.run(scheduler);
+        let this = self.clone();
+        Box::pin(async move {
+            let _permit = permit;
+            let result = task.await;
+            if result.is_ok() {
+                this.release();
+            }
+            result
+        })
+    }
+}
+
+impl BridgeTask {
+    fn run(
+        &self,
+        scheduler: Box<dyn 'static + TaskScheduler<Self>>,
     ) -> Pin<Box<dyn Future<Output = Result<(), SchedulerError>>>> {
         log::trace!("Running ERC-20 task: {:?}", self);
 
@@ -55,12 +110,35 @@ impl Task for BridgeTask {
             BridgeTask::RemoveMintOrder(event_data, sender_side) => {
                 let event_data = event_data.clone();
                 let sender_side = *sender_side;
-                Box::pin(async move { Self::remove_mint_order(event_data, sender_side) })
+                Box::pin(async move {
+                    let fee_charge = Self::fee_charge_contract(&state, sender_side).await?;
+                    Self::remove_mint_order(event_data.clone(), sender_side, fee_charge)?;
+                    Self::notify_subscribers(
+                        &*scheduler,
+                        BridgeEventNotification::Minted(event_data),
+                    );
+                    Ok(())
+                })
             }
             BridgeTask::SendMintTransaction(operation_id) => {
                 let operation_id = *operation_id;
-                Box::pin(Self::send_mint_transaction(state, operation_id))
+                Box::pin(async move {
+                    let result = Self::send_mint_transaction(state, operation_id).await;
+                    // A transaction which is not sent never frees its slot with the `Minted` event.
+                    if result.is_err() {
+                        Self::release_tx_slot(operation_id);
+                    }
+                    result
+                })
+            }
+            BridgeTask::NotifySubscriber(subscriber, notification) => {
+                let subscriber = *subscriber;
+                let notification = notification.clone();
+                Box::pin(async move { notification.send(subscriber).into_scheduler_result() })
             }
+            BridgeTask::ExpireMintOrders => Box::pin(async { Self::expire_mint_orders() }),
+            BridgeTask::CheckHealth => Box::pin(Self::check_health(state)),
+            BridgeTask::RefreshGasPrice(side) => Box::pin(Self::refresh_gas_price(state, *side)),
         }
     }
 }
@@ -70,6 +148,99 @@ impl BridgeTask {
         ScheduledTask::with_options(self, options)
     }
 
+    /// Name of the task type used to configure the task limits.
+    pub fn task_type(&self) -> &'static str {
+        match self {
+            BridgeTask::InitEvmState(_) => "InitEvmState",
+            BridgeTask::CollectEvmEvents(_) => "CollectEvmEvents",
+            BridgeTask::PrepareMintOrder(_) => "PrepareMintOrder",
+            BridgeTask::RemoveMintOrder(..) => "RemoveMintOrder",
+            BridgeTask::SendMintTransaction(_) => "SendMintTransaction",
+            BridgeTask::NotifySubscriber(..) => "NotifySubscriber",
+            BridgeTask::ExpireMintOrders => "ExpireMintOrders",
+            BridgeTask::CheckHealth => "CheckHealth",
+            BridgeTask::RefreshGasPrice(_) => "RefreshGasPrice",
+        }
+    }
+
+    /// Operation processed by the task, if any. It is the correlation id of the operation trace.
+    pub fn operation_id(&self) -> Option<MinterOperationId> {
+        match self {
+            BridgeTask::PrepareMintOrder(operation_id)
+            | BridgeTask::SendMintTransaction(operation_id) => Some(*operation_id),
+            _ => None,
+        }
+    }
+
+    fn key(&self) -> TaskKey {
+        TaskKey::new(self.task_type(), self)
+    }
+
+    /// Appends the task to the scheduler, unless the same task is pending already. Returns
+    /// `false` if the task is not appended.
+    pub fn append_unique(
+        self,
+        scheduler: &(impl TaskScheduler<Self> + ?Sized),
+        options: TaskOptions,
+    ) -> bool {
+        if !get_state().borrow_mut().pending_tasks.insert(self.key()) {
+            log::trace!("Task {} is pending already", self.task_type());
+            return false;
+        }
+
+        scheduler.append_task(self.into_scheduled(options));
+        true
+    }
+
+    /// Allows the task to be appended with [`BridgeTask::append_unique`] again once it is
+    /// completed.
+    pub fn release(&self) {
+        get_state().borrow_mut().pending_tasks.remove(&self.key());
+    }
+
+    fn default_priority(&self) -> TaskPriority {
+        match self {
+            BridgeTask::InitEvmState(_)
+            | BridgeTask::CollectEvmEvents(_)
+            | BridgeTask::CheckHealth
+            | BridgeTask::RefreshGasPrice(_) => TaskPriority::High,
+            BridgeTask::PrepareMintOrder(_)
+            | BridgeTask::RemoveMintOrder(..)
+            | BridgeTask::SendMintTransaction(_) => TaskPriority::Normal,
+            BridgeTask::NotifySubscriber(..) | BridgeTask::ExpireMintOrders => TaskPriority::Low,
+        }
+    }
+
+    /// Takes a slot for the mint transaction of the operation in the window of the EVM it is sent
+    /// to. Unknown operations are not limited, as the task fails on them anyway.
+    fn acquire_tx_slot(operation_id: MinterOperationId) -> Result<(), TaskDeferred> {
+        let Some(operation) = get_operations_store().get(operation_id) else {
+            return Ok(());
+        };
+
+        let now = get_state().borrow().clock().now();
+        let state = get_state();
+        let mut state = state.borrow_mut();
+        let in_flight_txs = state.in_flight_txs_mut(operation.side);
+        if in_flight_txs.try_acquire(operation_id, now) {
+            return Ok(());
+        }
+
+        Err(TaskDeferred::InFlightTxLimit {
+            limit: in_flight_txs.max_in_flight(),
+        })
+    }
+
+    /// Frees the slot of the mint transaction of the operation.
+    fn release_tx_slot(operation_id: MinterOperationId) {
+        if let Some(operation) = get_operations_store().get(operation_id) {
+            get_state()
+                .borrow_mut()
+                .in_flight_txs_mut(operation.side)
+                .release(&operation_id);
+        }
+    }
+
     pub async fn init_evm_state(
         state: Rc<RefCell<State>>,
         side: BridgeSide,
@@ -130,6 +301,21 @@ impl BridgeTask {
 
         log::debug!("got logs from side {side}: {logs:?}");
 
+        // The deposits are synced before the bridge events are recorded as processed, so a failure
+        // makes the task collect the same range again.
+        if let Some(fee_charge) = Self::fee_charge_contract(&state, side).await? {
+            let updates =
+                FeeBalanceUpdate::collect(&client, params.next_block, last_block, &fee_charge)
+                    .await
+                    .into_scheduler_result()?;
+            let mut balances = get_fee_balances(side);
+            for update in updates {
+                balances.apply(&fee_charge, update);
+            }
+        }
+
+        let logs = get_processed_events().filter_new(logs, side);
+
         state
             .borrow_mut()
             .config
@@ -167,6 +353,7 @@ impl BridgeTask {
         };
 
         log::trace!("preparing mint order: {burn_event:?}");
+        let notification = BridgeEventNotification::Burnt(burn_event.clone());
 
         let burn_evm_params = state
             .borrow()
@@ -203,8 +390,43 @@ impl BridgeTask {
             data.try_into().into_scheduler_result()
         }
 
+        // The base tokens are described by their contracts, the metadata of the wrapped tokens
+        // burnt to release the base ones is not used by the bridge.
+        let (name, symbol, decimals) = if burn_side == BridgeSide::Base {
+            let client = state
+                .borrow()
+                .config
+                .get_evm_info(burn_side)
+                .link
+                .get_json_rpc_client();
+            let metadata = get_token_metadata()
+                .get_or_query(&client, &burn_event.from_erc20)
+                .await
+                .into_scheduler_result()?;
+            (
+                metadata.name_bytes(),
+                metadata.symbol_bytes(),
+                metadata.decimals,
+            )
+        } else {
+            (
+                to_array(&burn_event.name)?,
+                to_array(&burn_event.symbol)?,
+                burn_event.decimals,
+            )
+        };
+
         let nonce = burn_event.operation_id;
         let amount = burn_event.amount;
+        // The mint transaction is sent to the EVM of the operation side.
+        let fee_payer = Self::select_fee_payer(
+            &state,
+            burn_side,
+            &burn_event.sender,
+            nonce,
+            &burn_evm_params,
+        )
+        .await?;
 
         let mint_order = MintOrder {
             amount: amount.clone(),
@@ -215,12 +437,12 @@ impl BridgeTask {
             nonce,
             sender_chain_id,
             recipient_chain_id,
-            name: to_array(&burn_event.name)?,
-            symbol: to_array(&burn_event.symbol)?,
-            decimals: burn_event.decimals,
+            name,
+            symbol,
+            decimals,
             approve_spender: H160::zero(),
             approve_amount: U256::zero(),
-            fee_payer: burn_event.sender,
+            fee_payer: fee_payer.clone(),
         };
 
         let signer = state.borrow().signer.get().clone();
@@ -229,6 +451,18 @@ impl BridgeTask {
             .await
             .into_scheduler_result()?;
 
+        // Update the EVM params. The fallible steps are done before the operation leaves the
+        // `Scheduled` state, so the task is retried if any of them fails, and the subscribers are
+        // notified once.
+        Self::update_evm_params(state.clone(), burn_side).await?;
+
+        let now = state.borrow().clock().now();
+        let expires_at = state
+            .borrow()
+            .config
+            .mint_order_ttl()
+            .map(|ttl| now.saturating_add(ttl.as_nanos() as u64));
+
         operation_store.update(
             operation_id,
             OperationPayload {
@@ -237,14 +471,30 @@ impl BridgeTask {
                     token_id: src_token,
                     amount,
                     signed_mint_order: Box::new(signed_mint_order),
+                    expires_at,
                 },
             },
         );
+        Self::notify_subscribers(&*scheduler, notification);
 
-        // Update the EVM params
-        Self::update_evm_params(state.clone(), burn_side).await?;
+        // The BftBridge would not charge the gas of the transaction from anyone.
+        if fee_payer == H160::zero() {
+            log::info!("Mint order of operation {operation_id} has no fee payer and is left to be sent by the user");
+            return Ok(());
+        }
+
+        // The signed mint order is kept in the operation store, so the transaction can be resent
+        // if the EVM is temporarily unavailable.
+        const SEND_MINT_TX_RETRIES: u32 = 10;
+        const SEND_MINT_TX_RETRY_DELAY_SECS: u32 = 2;
+        const SEND_MINT_TX_RETRY_MULTIPLIER: u32 = 2;
 
-        let options = TaskOptions::default();
+        let options = TaskOptions::default()
+            .with_max_retries_policy(SEND_MINT_TX_RETRIES)
+            .with_backoff_policy(BackoffPolicy::Exponential {
+                secs: SEND_MINT_TX_RETRY_DELAY_SECS,
+                multiplier: SEND_MINT_TX_RETRY_MULTIPLIER,
+            });
         scheduler
             .append_task(BridgeTask::SendMintTransaction(operation_id).into_scheduled(options));
 
@@ -253,6 +503,128 @@ impl BridgeTask {
         Ok(())
     }
 
+    /// Returns the FeeCharge contract of the BftBridge on the `side`, reading it from the bridge
+    /// on the first call.
+    async fn fee_charge_contract(
+        state: &Rc<RefCell<State>>,
+        side: BridgeSide,
+    ) -> Result<Option<H160>, SchedulerError> {
+        if let Some(fee_charge) = state.borrow().fee_charge(side) {
+            return Ok(fee_charge);
+        }
+
+        let bft_bridge = state
+            .borrow()
+            .config
+            .get_bft_bridge_contract(side)
+            .ok_or_else(|| {
+                SchedulerError::TaskExecutionFailed("no bft bridge contract set".into())
+            })?;
+        let client = state
+            .borrow()
+            .config
+            .get_evm_info(side)
+            .link
+            .get_json_rpc_client();
+        let fee_charge = bft_bridge_api::fee_charge_contract(&client, bft_bridge.0)
+            .await
+            .into_scheduler_result()?
+            .map(H160::from);
+
+        state
+            .borrow_mut()
+            .set_fee_charge(side, Some(fee_charge.clone()));
+        Ok(fee_charge)
+    }
+
+    /// Selects the fee payer of the mint order with the `nonce` sent to the EVM on the `side`:
+    /// the `user` if the BftBridge charges no fees or if the FeeCharge deposit of the user covers
+    /// the fee of the mint transaction besides the fees reserved for the other pending orders,
+    /// and the zero address otherwise. The fee of the order is reserved until it is minted.
+    async fn select_fee_payer(
+        state: &Rc<RefCell<State>>,
+        side: BridgeSide,
+        user: &H160,
+        nonce: u32,
+        evm_params: &EvmParams,
+    ) -> Result<H160, SchedulerError> {
+        let Some(fee_charge) = Self::fee_charge_contract(state, side).await? else {
+            return Ok(user.clone());
+        };
+
+        // No events are collected for the deposits made before the minter started to follow the
+        // contract, so the unknown deposits are read from it. The later events override the read
+        // balance.
+        if get_fee_balances(side).get(&fee_charge, user).is_none() {
+            let client = state
+                .borrow()
+                .config
+                .get_evm_info(side)
+                .link
+                .get_json_rpc_client();
+            let balance = query_fee_balance(&client, &fee_charge, user)
+                .await
+                .into_scheduler_result()?;
+            get_fee_balances(side).apply(
+                &fee_charge,
+                FeeBalanceUpdate {
+                    user: user.clone(),
+                    balance: FeeBalance {
+                        balance,
+                        block_number: evm_params.next_block.saturating_sub(1),
+                        log_index: u64::MAX,
+                    },
+                },
+            );
+        }
+
+        let fee = {
+            let state = state.borrow();
+            let gas_price = state
+                .gas_price(side)
+                .gas_price(evm_params.gas_price.clone(), state.clock().now());
+            let gas_limit = state.gas_limits.gas_limit(GasOperation::Mint);
+            U256::from(gas_price.0 * ethers_core::types::U256::from(gas_limit))
+        };
+
+        Ok(get_fee_balances(side).reserve_fee(&fee_charge, user, nonce, &fee))
+    }
+
+    /// Creates the tasks for the `logs` of a replayed block range which were not processed yet.
+    pub(crate) fn tasks_by_replayed_logs(
+        logs: Vec<Log>,
+        sender_side: BridgeSide,
+    ) -> (Vec<ScheduledTask<BridgeTask>>, ReplayReport) {
+        let collected = logs.len();
+        let logs = get_processed_events().filter_new(logs, sender_side);
+
+        let mut report = ReplayReport {
+            skipped: (collected - logs.len()) as u32,
+            ..Default::default()
+        };
+        let mut tasks = Vec::new();
+        for log in logs {
+            match BridgeEvent::from_log(log.clone()) {
+                // The burns processed before the processed events were recorded have operations.
+                Ok(BridgeEvent::Burnt(burnt))
+                    if is_burn_recorded(
+                        &get_operations_store().get_for_address(&burnt.sender),
+                        &burnt,
+                    ) =>
+                {
+                    report.skipped += 1;
+                    continue;
+                }
+                Ok(BridgeEvent::Burnt(_)) => report.burns += 1,
+                Ok(BridgeEvent::Minted(_)) => report.mints += 1,
+                _ => {}
+            }
+            tasks.extend(Self::task_by_log(log, sender_side));
+        }
+
+        (tasks, report)
+    }
+
     fn task_by_log(log: Log, sender_side: BridgeSide) -> Option<ScheduledTask<BridgeTask>> {
         log::trace!("creating task from the log: {log:?}");
 
@@ -264,31 +636,74 @@ impl BridgeTask {
             })
             .with_max_retries_policy(u32::MAX);
 
-        match BridgeEvent::from_log(log).into_scheduler_result() {
+        let task = match BridgeEvent::from_log(log).into_scheduler_result() {
             Ok(BridgeEvent::Burnt(burnt)) => {
+                match get_state().borrow().config.get_evm_params(BridgeSide::Base) {
+                    Ok(params) => {
+                        get_token_registry().on_burnt(&burnt, sender_side, params.chain_id as u32)
+                    }
+                    Err(err) => log::warn!("Burnt event is not added to the token registry: {err}"),
+                }
+
                 log::debug!("Adding PrepareMintOrder task");
                 let operation_id = get_operations_store().new_operation(
                     burnt.sender.clone(),
-                    OperationPayload::new(sender_side.other(), burnt),
+                    OperationPayload::new(sender_side.other(), burnt.clone()),
                 );
-                let mint_order_task = BridgeTask::PrepareMintOrder(operation_id);
-                return Some(mint_order_task.into_scheduled(options));
+                BridgeTask::PrepareMintOrder(operation_id)
             }
             Ok(BridgeEvent::Minted(minted)) => {
+                get_token_registry().on_minted(&minted, sender_side);
+
                 log::debug!("Adding RemoveMintOrder task");
-                let remove_mint_order_task = BridgeTask::RemoveMintOrder(minted, sender_side);
-                return Some(remove_mint_order_task.into_scheduled(options));
+                BridgeTask::RemoveMintOrder(minted, sender_side)
             }
             Ok(BridgeEvent::Notify(_)) => todo!(),
-            Err(e) => log::warn!("collected log is incompatible with expected events: {e}"),
-        }
+            Err(e) => {
+                log::warn!("collected log is incompatible with expected events: {e}");
+                return None;
+            }
+        };
 
-        None
+        Some(task.into_scheduled(options))
+    }
+
+    /// Notifies the event subscribers about an event once the task processing it is completed.
+    fn notify_subscribers(
+        scheduler: &dyn TaskScheduler<Self>,
+        notification: BridgeEventNotification,
+    ) {
+        scheduler.append_tasks(Self::notification_tasks(notification).collect());
+    }
+
+    /// Creates a task for each event subscriber to deliver the `notification`.
+    fn notification_tasks(
+        notification: BridgeEventNotification,
+    ) -> impl Iterator<Item = ScheduledTask<BridgeTask>> {
+        const NOTIFICATION_RETRIES: u32 = 5;
+        const NOTIFICATION_RETRY_DELAY_SECS: u32 = 2;
+        const NOTIFICATION_RETRY_MULTIPLIER: u32 = 2;
+
+        let options = TaskOptions::default()
+            .with_max_retries_policy(NOTIFICATION_RETRIES)
+            .with_backoff_policy(BackoffPolicy::Exponential {
+                secs: NOTIFICATION_RETRY_DELAY_SECS,
+                multiplier: NOTIFICATION_RETRY_MULTIPLIER,
+            });
+
+        get_event_subscribers()
+            .get_all()
+            .into_iter()
+            .map(move |subscriber| {
+                BridgeTask::NotifySubscriber(subscriber, notification.clone())
+                    .into_scheduled(options.clone())
+            })
     }
 
     fn remove_mint_order(
         minted_event: MintedEventData,
         sender_side: BridgeSide,
+        fee_charge: Option<H160>,
     ) -> Result<(), SchedulerError> {
         let wallet_id = match sender_side {
             BridgeSide::Base => minted_event.recipient,
@@ -322,20 +737,49 @@ impl BridgeTask {
             )));
         };
 
+        get_state()
+            .borrow_mut()
+            .in_flight_txs_mut(operation_state.side)
+            .release(&operation_id);
+
         let src_token = Id256::from_slice(&minted_event.from_token).ok_or_else(|| {
             SchedulerError::TaskExecutionFailed(
                 "failed to decode token id256 from minted event".into(),
             )
         })?;
 
-        if let OperationStatus::MintOrderSent {
-            token_id,
-            amount,
-            tx_id,
-            ..
-        } = operation_state.status
-        {
+        let minted = match &operation_state.status {
+            OperationStatus::MintOrderSent {
+                token_id,
+                amount,
+                tx_id,
+                ..
+            } => Some((*token_id, amount.clone(), tx_id.clone())),
+            // The expired order can still be minted by anyone who has it. If it wasn't sent by
+            // the minter, the transaction id is unknown.
+            OperationStatus::Expired {
+                token_id,
+                amount,
+                tx_id,
+                ..
+            } => Some((*token_id, amount.clone(), tx_id.clone().unwrap_or_default())),
+            // The order is minted by a transaction whose result was lost, or by the user.
+            OperationStatus::MintOrderSigned {
+                token_id, amount, ..
+            } => Some((*token_id, amount.clone(), Default::default())),
+            _ => None,
+        };
+
+        if let Some((token_id, amount, tx_id)) = minted {
             if token_id == src_token {
+                // The mint transaction is charged, so the fee reserved for the order is released.
+                if let (Some(fee_charge), Some(fee_payer)) =
+                    (&fee_charge, operation_state.mint_order_fee_payer())
+                {
+                    get_fee_balances(operation_state.side)
+                        .release_fee(fee_charge, &fee_payer, nonce);
+                }
+
                 operation_store.update(
                     operation_id,
                     OperationPayload {
@@ -344,6 +788,7 @@ impl BridgeTask {
                             amount,
                             token_id,
                             tx_id,
+                            nonce: Some(nonce),
                         },
                     },
                 );
@@ -353,7 +798,68 @@ impl BridgeTask {
                 log::warn!("Operation {operation_id} was created for token id {token_id:?} but the mint event is emitted by {src_token:?}.");
             }
         } else {
-            log::error!("Operation {operation_id} was expected to be in `MintOrderSent` state, but was found: {operation_state:?}");
+            log::error!("Operation {operation_id} was expected to be in `MintOrderSigned`, `MintOrderSent` or `Expired` state, but was found: {operation_state:?}");
+        }
+
+        Ok(())
+    }
+
+    /// Checks the dependencies of the bridge and records the results in the health monitor.
+    async fn check_health(state: Rc<RefCell<State>>) -> Result<(), SchedulerError> {
+        let (base_link, wrapped_link, signer) = {
+            let state = state.borrow();
+            (
+                state.config.get_evm_info(BridgeSide::Base).link,
+                state.config.get_evm_info(BridgeSide::Wrapped).link,
+                state.signer.get().clone(),
+            )
+        };
+
+        let base_result = health::check_evm_link(&base_link).await;
+        let wrapped_result = health::check_evm_link(&wrapped_link).await;
+        let signer_result = health::check_signer(&signer).await;
+
+        let now = state.borrow().clock().now();
+        let mut state = state.borrow_mut();
+        state.health.record(BASE_EVM_RPC, base_result, now);
+        state.health.record(WRAPPED_EVM_RPC, wrapped_result, now);
+        state.health.record(SIGNER, signer_result, now);
+
+        Ok(())
+    }
+
+    /// Samples the gas price of the EVM on the `side` and updates its estimate.
+    async fn refresh_gas_price(
+        state: Rc<RefCell<State>>,
+        side: BridgeSide,
+    ) -> Result<(), SchedulerError> {
+        let client = state
+            .borrow()
+            .config
+            .get_evm_info(side)
+            .link
+            .get_json_rpc_client();
+        let sample = gas_price::sample_gas_price(&client)
+            .await
+            .into_scheduler_result()?;
+
+        log::trace!("Sampled {side} gas price: {sample:?}");
+        let now = state.borrow().clock().now();
+        state.borrow_mut().gas_price_mut(side).record(sample, now);
+
+        Ok(())
+    }
+
+    /// Moves all the mint orders which were not claimed before their expiration into the
+    /// `Expired` state.
+    fn expire_mint_orders() -> Result<(), SchedulerError> {
+        let now = get_state().borrow().clock().now();
+        let mut operation_store = get_operations_store();
+        for (operation_id, mut operation) in operation_store.get_incomplete() {
+            if operation.expire(now) {
+                log::info!("Mint order of operation {operation_id} expired");
+                operation_store.update(operation_id, operation);
+            }
         }
 
         Ok(())
@@ -375,6 +881,7 @@ impl BridgeTask {
             token_id,
             amount,
             signed_mint_order,
+            expires_at,
         } = operation.status
         else {
             return Err(SchedulerError::TaskExecutionFailed(format!("Operation {operation_id} was expected to be in `MintOrderSigned` state, but found: {operation:?}")));
@@ -403,6 +910,36 @@ impl BridgeTask {
             })?;
 
         let client = evm_info.link.get_json_rpc_client();
+
+        // A retry of the task must not resend the order if an earlier transaction, whose result
+        // was lost, or the user has minted it already. The `Minted` event completes the operation.
+        if let Some((order, _)) = MintOrder::decode_signed(&signed_mint_order.0) {
+            // Older BftBridge proxies have no `isNonceUsed` and revert the check. The bridge
+            // rejects a used order itself, so an unknown order is sent.
+            let used = match bft_bridge_api::is_mint_order_used(
+                &client,
+                bft_bridge.0,
+                &order.sender,
+                order.nonce,
+            )
+            .await
+            {
+                Ok(used) => used,
+                Err(err) => {
+                    log::warn!(
+                        "Failed to check mint order of operation {operation_id}, sending it: {err:?}"
+                    );
+                    false
+                }
+            };
+            if used {
+                log::info!(
+                    "Mint order of operation {operation_id} is minted already, skipping the transaction"
+                );
+                return Ok(());
+            }
+        }
+
         let nonce = client
             .get_transaction_count(sender.0, BlockNumber::Latest)
             .await
@@ -412,7 +949,12 @@ impl BridgeTask {
             sender.0,
             bft_bridge.0,
             nonce.into(),
-            evm_params.gas_price.into(),
+            state
+                .borrow()
+                .gas_price(side)
+                .gas_price(evm_params.gas_price, state.borrow().clock().now())
+                .into(),
+            state.borrow().gas_limits.gas_limit(GasOperation::Mint),
             &signed_mint_order.0,
             evm_params.chain_id as _,
         );
@@ -430,6 +972,14 @@ impl BridgeTask {
         let tx_id = client
             .send_raw_transaction(tx)
             .await
+            .map_err(|err| {
+                let err = format!("{err:?}");
+                state
+                    .borrow_mut()
+                    .gas_limits
+                    .on_transaction_error(GasOperation::Mint, &err);
+                err
+            })
             .into_scheduler_result()?;
 
         operation_store.update(
@@ -441,6 +991,7 @@ impl BridgeTask {
                     amount,
                     signed_mint_order,
                     tx_id: tx_id.into(),
+                    expires_at,
                 },
             },
         );
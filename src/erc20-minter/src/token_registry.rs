@@ -0,0 +1,238 @@
+//! Registry of the base tokens bridged by the minter and of their wrapped tokens.
+//!
+//! The registry is filled from the collected BftBridge events: the `Burnt` events of the base
+//! side give the token metadata, the `Minted` events of the wrapped side give the wrapped token
+//! address, and the mints and burns of the wrapped side give the bridged amount. So explorers
+//! can list the tokens served by the minter without scanning the EVMs.
+
+use std::borrow::Cow;
+
+use candid::{CandidType, Decode, Encode};
+use did::{H160, U256};
+use ic_stable_structures::stable_structures::Memory;
+use ic_stable_structures::{BTreeMapStructure, Bound, StableBTreeMap, Storable};
+use minter_contract_utils::bft_bridge_api::{BurntEventData, MintedEventData};
+use minter_contract_utils::evm_bridge::BridgeSide;
+use minter_did::id256::Id256;
+use serde::Deserialize;
+
+/// Base token served by the minter.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct BridgedToken {
+    pub base_token: Id256,
+    /// Wrapped token on the wrapped side. `None` until the first mint of the token is collected.
+    pub wrapped_token: Option<H160>,
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    /// Amount of the wrapped tokens minted and not burnt yet.
+    pub total_bridged: U256,
+}
+
+impl BridgedToken {
+    fn new(base_token: Id256) -> Self {
+        Self {
+            base_token,
+            wrapped_token: None,
+            name: String::new(),
+            symbol: String::new(),
+            decimals: 0,
+            total_bridged: U256::zero(),
+        }
+    }
+}
+
+impl Storable for BridgedToken {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("serialization failed"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).expect("deserialization failed")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct TokenKey(Id256);
+
+impl Storable for TokenKey {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Borrowed(&self.0 .0)
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Self(Id256(
+            bytes[..]
+                .try_into()
+                .expect("expected 32 bytes for token id"),
+        ))
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: Id256::BYTE_SIZE as _,
+        is_fixed_size: true,
+    };
+}
+
+pub struct TokenRegistry<M: Memory> {
+    tokens: StableBTreeMap<TokenKey, BridgedToken, M>,
+}
+
+impl<M: Memory> TokenRegistry<M> {
+    pub fn new(memory: M) -> Self {
+        Self {
+            tokens: StableBTreeMap::new(memory),
+        }
+    }
+
+    /// All the registered tokens, ordered by the base token id.
+    pub fn get_all(&self) -> Vec<BridgedToken> {
+        self.tokens.iter().map(|(_, token)| token).collect()
+    }
+
+    /// Records the `Burnt` event collected from the `side`. `base_chain_id` is the chain id of
+    /// the base side EVM.
+    pub fn on_burnt(&mut self, event: &BurntEventData, side: BridgeSide, base_chain_id: u32) {
+        match side {
+            BridgeSide::Base => {
+                let base_token = Id256::from_evm_address(&event.from_erc20, base_chain_id);
+                self.update(base_token, |token| {
+                    token.name = decode_metadata(&event.name);
+                    token.symbol = decode_metadata(&event.symbol);
+                    token.decimals = event.decimals;
+                });
+            }
+            BridgeSide::Wrapped => {
+                let Some(base_token) = Id256::from_slice(&event.to_token) else {
+                    log::warn!("Burnt event has invalid base token id: {event:?}");
+                    return;
+                };
+
+                self.update(base_token, |token| {
+                    token.total_bridged =
+                        U256::from(token.total_bridged.0.saturating_sub(event.amount.0));
+                });
+            }
+        }
+    }
+
+    /// Records the `Minted` event collected from the `side`. Only the mints of the wrapped side
+    /// change the registry.
+    pub fn on_minted(&mut self, event: &MintedEventData, side: BridgeSide) {
+        if side != BridgeSide::Wrapped {
+            return;
+        }
+
+        let Some(base_token) = Id256::from_slice(&event.from_token) else {
+            log::warn!("Minted event has invalid base token id: {event:?}");
+            return;
+        };
+
+        self.update(base_token, |token| {
+            token.wrapped_token = Some(event.to_erc20.clone());
+            token.total_bridged = U256::from(token.total_bridged.0.saturating_add(event.amount.0));
+        });
+    }
+
+    fn update(&mut self, base_token: Id256, f: impl FnOnce(&mut BridgedToken)) {
+        let key = TokenKey(base_token);
+        let mut token = self
+            .tokens
+            .get(&key)
+            .unwrap_or_else(|| BridgedToken::new(base_token));
+        f(&mut token);
+        self.tokens.insert(key, token);
+    }
+}
+
+/// Token name or symbol from the zero padded bytes of an event.
+fn decode_metadata(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes)
+        .trim_end_matches('\0')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use ic_stable_structures::VectorMemory;
+    use minter_did::order::fit_str_to_array;
+
+    use super::*;
+
+    const BASE_CHAIN_ID: u32 = 1;
+
+    fn base_burn(base_token: &H160, amount: u64) -> BurntEventData {
+        BurntEventData {
+            from_erc20: base_token.clone(),
+            amount: U256::from(amount),
+            name: fit_str_to_array::<32>("Token").to_vec(),
+            symbol: fit_str_to_array::<16>("TKN").to_vec(),
+            decimals: 18,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn tokens_are_aggregated_from_events() {
+        let mut registry = TokenRegistry::new(VectorMemory::default());
+        let base_address = H160::from_slice(&[1; 20]);
+        let wrapped_address = H160::from_slice(&[2; 20]);
+        let base_token = Id256::from_evm_address(&base_address, BASE_CHAIN_ID);
+
+        registry.on_burnt(
+            &base_burn(&base_address, 100),
+            BridgeSide::Base,
+            BASE_CHAIN_ID,
+        );
+        let minted = MintedEventData {
+            amount: U256::from(100u64),
+            from_token: base_token.0.to_vec(),
+            sender_id: vec![],
+            to_erc20: wrapped_address.clone(),
+            recipient: H160::from_slice(&[3; 20]),
+            nonce: 0,
+        };
+        registry.on_minted(&minted, BridgeSide::Wrapped);
+        registry.on_minted(&minted, BridgeSide::Base);
+
+        let wrapped_burn = BurntEventData {
+            from_erc20: wrapped_address.clone(),
+            to_token: base_token.0.to_vec(),
+            amount: U256::from(30u64),
+            ..Default::default()
+        };
+        registry.on_burnt(&wrapped_burn, BridgeSide::Wrapped, BASE_CHAIN_ID);
+
+        assert_eq!(
+            registry.get_all(),
+            vec![BridgedToken {
+                base_token,
+                wrapped_token: Some(wrapped_address),
+                name: "Token".to_string(),
+                symbol: "TKN".to_string(),
+                decimals: 18,
+                total_bridged: U256::from(70u64),
+            }]
+        );
+    }
+
+    #[test]
+    fn burns_do_not_underflow_bridged_amount() {
+        let mut registry = TokenRegistry::new(VectorMemory::default());
+        let base_token = Id256::from_evm_address(&H160::from_slice(&[1; 20]), BASE_CHAIN_ID);
+
+        let wrapped_burn = BurntEventData {
+            to_token: base_token.0.to_vec(),
+            amount: U256::from(30u64),
+            ..Default::default()
+        };
+        registry.on_burnt(&wrapped_burn, BridgeSide::Wrapped, BASE_CHAIN_ID);
+
+        let tokens = registry.get_all();
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].total_bridged, U256::zero());
+        assert_eq!(tokens[0].wrapped_token, None);
+    }
+}
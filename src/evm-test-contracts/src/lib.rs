@@ -0,0 +1,7 @@
+//! Typed wrappers of the test contracts of the `solidity` directory, for the integration tests.
+//!
+//! The wrappers produce the inputs of the deploy and call transactions and decode the outputs of
+//! the `eth_call` requests, so they work with any EVM client of the tests: the transactions are
+//! signed and sent by the test context.
+
+pub mod watermelon_token;
@@ -0,0 +1,124 @@
+//! `WatermelonToken` (WTM): an ERC-20 token with zero decimals whose initial supply is minted to
+//! the deployer. The tests use it as the base token of the bridges and to fund the accounts
+//! which provide the liquidity of the bridged tokens.
+
+use did::{H160, U256};
+use ethers_core::abi::{Constructor, Function, Param, ParamType, Token};
+use minter_contract_utils::build_data::test_contracts::TEST_WTM_HEX_CODE;
+use minter_contract_utils::wrapped_token_api::{
+    ERC_20_ALLOWANCE, ERC_20_APPROVE, ERC_20_BALANCE, TRANSFER,
+};
+use once_cell::sync::Lazy;
+
+pub const NAME: &str = "Watermelon";
+pub const SYMBOL: &str = "WTM";
+pub const DECIMALS: u8 = 0;
+
+static CONSTRUCTOR: Lazy<Constructor> = Lazy::new(|| Constructor {
+    inputs: vec![Param {
+        name: "initialSupply".into(),
+        kind: ParamType::Uint(256),
+        internal_type: None,
+    }],
+});
+
+/// Deployed `WatermelonToken` contract.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatermelonToken {
+    pub address: H160,
+}
+
+impl WatermelonToken {
+    pub fn new(address: H160) -> Self {
+        Self { address }
+    }
+
+    /// Input of the transaction deploying the contract with the `initial_supply` minted to the
+    /// sender.
+    pub fn deploy_data(initial_supply: U256) -> Vec<u8> {
+        CONSTRUCTOR
+            .encode_input(TEST_WTM_HEX_CODE.clone(), &[Token::Uint(initial_supply.0)])
+            .expect("WatermelonToken constructor encoding should pass")
+    }
+
+    pub fn transfer_data(to: &H160, amount: U256) -> Vec<u8> {
+        encode(&TRANSFER, &[Token::Address(to.0), Token::Uint(amount.0)])
+    }
+
+    pub fn approve_data(spender: &H160, amount: U256) -> Vec<u8> {
+        encode(
+            &ERC_20_APPROVE,
+            &[Token::Address(spender.0), Token::Uint(amount.0)],
+        )
+    }
+
+    /// Input of the `balanceOf` call. The output is decoded with [`Self::decode_balance`].
+    pub fn balance_of_data(owner: &H160) -> Vec<u8> {
+        encode(&ERC_20_BALANCE, &[Token::Address(owner.0)])
+    }
+
+    /// Input of the `allowance` call. The output is decoded with [`Self::decode_allowance`].
+    pub fn allowance_data(owner: &H160, spender: &H160) -> Vec<u8> {
+        encode(
+            &ERC_20_ALLOWANCE,
+            &[Token::Address(owner.0), Token::Address(spender.0)],
+        )
+    }
+
+    /// Decodes the `balanceOf` output, as the hex string returned by `eth_call`.
+    pub fn decode_balance(output: &str) -> anyhow::Result<U256> {
+        decode_uint(&ERC_20_BALANCE, output)
+    }
+
+    /// Decodes the `allowance` output, as the hex string returned by `eth_call`.
+    pub fn decode_allowance(output: &str) -> anyhow::Result<U256> {
+        decode_uint(&ERC_20_ALLOWANCE, output)
+    }
+}
+
+fn encode(function: &Function, args: &[Token]) -> Vec<u8> {
+    function
+        .encode_input(args)
+        .unwrap_or_else(|err| panic!("{} input encoding should pass: {err}", function.name))
+}
+
+fn decode_uint(function: &Function, output: &str) -> anyhow::Result<U256> {
+    let output = hex::decode(output.trim_start_matches("0x"))?;
+    match function.decode_output(&output)?.as_slice() {
+        [Token::Uint(value)] => Ok((*value).into()),
+        tokens => Err(anyhow::anyhow!(
+            "unexpected output of {}: {tokens:?}",
+            function.name
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deploy_data_appends_initial_supply_to_bytecode() {
+        let data = WatermelonToken::deploy_data(U256::from(1_000u64));
+
+        assert!(data.starts_with(&TEST_WTM_HEX_CODE));
+        assert_eq!(data.len(), TEST_WTM_HEX_CODE.len() + 32);
+        assert_eq!(data[data.len() - 2..], [0x03, 0xe8]);
+    }
+
+    #[test]
+    fn call_outputs_are_decoded() {
+        let owner = H160::from_slice(&[1; 20]);
+        assert_eq!(
+            WatermelonToken::balance_of_data(&owner)[..4],
+            ERC_20_BALANCE.short_signature()
+        );
+
+        let output = format!("0x{:064x}", 42);
+        assert_eq!(
+            WatermelonToken::decode_balance(&output).unwrap(),
+            U256::from(42u64)
+        );
+        assert!(WatermelonToken::decode_allowance("0x").is_err());
+    }
+}
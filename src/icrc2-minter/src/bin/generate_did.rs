@@ -0,0 +1,8 @@
+//! Writes the complete candid interface of the canister, including metrics endpoints.
+//!
+//! Usage: `cargo run -p icrc2-minter --bin generate_did -- [OUTPUT_PATH]`. The interface is written
+//! to `icrc2-minter.did` in the current directory if no path is given.
+
+fn main() -> std::io::Result<()> {
+    minter_contract_utils::did_output::write_idl(&icrc2_minter::idl(), "icrc2-minter.did")
+}
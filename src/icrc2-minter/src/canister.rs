@@ -1,5 +1,6 @@
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::time::Duration;
 
 use candid::Principal;
 use did::build::BuildData;
@@ -13,12 +14,30 @@ use ic_exports::ic_kit::ic;
 use ic_log::writer::Logs;
 use ic_metrics::{Metrics, MetricsStorage};
 use ic_stable_structures::stable_structures::DefaultMemoryImpl;
-use ic_stable_structures::{StableBTreeMap, VirtualMemory};
+use ic_stable_structures::{BTreeMapStructure, StableBTreeMap, VirtualMemory};
 use ic_task_scheduler::retry::BackoffPolicy;
 use ic_task_scheduler::scheduler::{Scheduler, TaskScheduler};
 use ic_task_scheduler::task::{InnerScheduledTask, ScheduledTask, TaskOptions, TaskStatus};
+use ic_task_scheduler::SchedulerError;
 use log::*;
+use minter_contract_utils::admin_approvals::{
+    ApprovalConfig, ApprovalError, ApprovalStore, Proposal,
+};
+use minter_contract_utils::canister_status::{stable_structures_usage, CanisterStatusInfo};
+use minter_contract_utils::config_validation::{
+    format_config_errors, validate_bft_bridge_contract, ConfigError, ConfigValidator,
+};
+use minter_contract_utils::evm_link::EvmLink;
+use minter_contract_utils::gas_limits::GasLimits;
+use minter_contract_utils::health::{self, HealthReport};
+use minter_contract_utils::in_flight_txs::InFlightTxsInfo;
+use minter_contract_utils::long_poll::{self, TooManyWaiters};
 use minter_contract_utils::operation_store::{MinterOperationId, MinterOperationStore};
+use minter_contract_utils::operation_trace::{self, TraceEntry};
+use minter_contract_utils::pagination::{
+    CursorPage, CursorRequest, InvalidCursor, Paged, Pagination,
+};
+use minter_contract_utils::task_limits::{is_deferral_error, DEFERRED_TASK_DELAY_SECS};
 use minter_did::error::{Error, Result};
 use minter_did::id256::Id256;
 use minter_did::init::InitData;
@@ -26,9 +45,10 @@ use minter_did::order::SignedMintOrder;
 
 use crate::build_data::canister_build_data;
 use crate::constant::{
-    OPERATIONS_LOG_MEMORY_ID, OPERATIONS_MAP_MEMORY_ID, OPERATIONS_MEMORY_ID,
-    PENDING_TASKS_MEMORY_ID,
+    APPROVAL_PROPOSALS_MEMORY_ID, APPROVAL_SETTINGS_MEMORY_ID, OPERATIONS_LOG_MEMORY_ID,
+    OPERATIONS_MAP_MEMORY_ID, OPERATIONS_MEMORY_ID, PENDING_TASKS_MEMORY_ID, STABLE_STRUCTURES,
 };
+use crate::interface::AdminOperation;
 use crate::memory::MEMORY_MANAGER;
 use crate::operation::OperationState;
 use crate::state::{Settings, State};
@@ -53,8 +73,6 @@ impl MinterCanister {
         // This block of code only need to be run in the wasm environment
         #[cfg(target_family = "wasm")]
         {
-            use std::time::Duration;
-
             self.update_metrics_timer(Duration::from_secs(60 * 60));
 
             const GLOBAL_TIMER_INTERVAL: Duration = Duration::from_secs(2);
@@ -70,6 +88,24 @@ impl MinterCanister {
                     log::error!("task execution failed: {err}",);
                 }
             });
+
+            ic_exports::ic_cdk_timers::set_timer_interval(
+                minter_contract_utils::health::HEALTH_CHECK_INTERVAL,
+                || {
+                    get_scheduler().borrow_mut().append_task(
+                        BridgeTask::CheckHealth.into_scheduled(TaskOptions::default()),
+                    );
+                },
+            );
+
+            ic_exports::ic_cdk_timers::set_timer_interval(
+                minter_contract_utils::gas_price::GAS_PRICE_REFRESH_INTERVAL,
+                || {
+                    get_scheduler().borrow_mut().append_task(
+                        BridgeTask::RefreshGasPrice.into_scheduled(TaskOptions::default()),
+                    );
+                },
+            );
         }
     }
 
@@ -117,12 +153,16 @@ impl MinterCanister {
             signing_strategy: init_data.signing_strategy,
         };
 
+        if let Err(errors) = settings.validate() {
+            panic!("{}", format_config_errors(&errors));
+        }
+
         state.reset(settings);
 
         {
             let scheduler = get_scheduler();
             let mut borrowed_scheduler = scheduler.borrow_mut();
-            borrowed_scheduler.on_completion_callback(log_task_execution_error);
+            borrowed_scheduler.on_completion_callback(on_task_completed);
             borrowed_scheduler.append_task(Self::init_evm_info_task());
         }
 
@@ -138,6 +178,9 @@ impl MinterCanister {
             ic_exports::ic_cdk::println!("error configuring the logger. Err: {err:?}")
         }
 
+        get_scheduler()
+            .borrow_mut()
+            .on_completion_callback(on_task_completed);
         self.set_timers();
         debug!("upgrade completed");
     }
@@ -203,16 +246,16 @@ impl MinterCanister {
     ///
     /// This method should be called only by current owner,
     /// else `Error::NotAuthorised` will be returned.
+    /// With the approvals enabled, the owner is changed with an approved proposal.
     #[update]
     pub fn set_owner(&mut self, owner: Principal) -> Result<()> {
-        let state = get_state();
-        let mut state = state.borrow_mut();
-
-        MinterCanister::set_owner_inspect_message_check(ic::caller(), owner, &state)?;
-        state.config.set_owner(owner);
-
-        info!("minter canister owner changed to {owner}");
-        Ok(())
+        MinterCanister::set_owner_inspect_message_check(
+            ic::caller(),
+            owner,
+            &get_state().borrow(),
+        )?;
+        check_direct_operation()?;
+        apply_admin_operation(AdminOperation::SetOwner(owner))
     }
 
     /// Returns principal of EVM canister with which the minter canister works.
@@ -235,25 +278,39 @@ impl MinterCanister {
     ///
     /// This method should be called only by current owner,
     /// else `Error::NotAuthorised` will be returned.
+    /// With the approvals enabled, the principal is changed with an approved proposal.
     #[update]
     pub fn set_evm_principal(&mut self, evm: Principal) -> Result<()> {
-        let state = get_state();
-        let mut state = state.borrow_mut();
-
-        MinterCanister::set_evm_principal_inspect_message_check(ic::caller(), evm, &state)?;
-        state.config.set_evm_principal(evm);
+        MinterCanister::set_evm_principal_inspect_message_check(
+            ic::caller(),
+            evm,
+            &get_state().borrow(),
+        )?;
+        check_direct_operation()?;
+        apply_admin_operation(AdminOperation::SetEvmPrincipal(evm))
+    }
 
-        info!("EVM principal changed to {evm}");
-        Ok(())
+    /// set_bft_bridge_contract inspect_message check
+    pub fn set_bft_bridge_contract_inspect_message_check(
+        principal: Principal,
+        state: &State,
+    ) -> Result<()> {
+        inspect_check_is_owner(principal, state)
     }
 
     /// Set BFT bridge contract address.
+    ///
+    /// This method should be called only by current owner,
+    /// else `Error::NotAuthorised` will be returned.
+    /// With the approvals enabled, the contract is changed with an approved proposal.
     #[update]
-    pub async fn set_bft_bridge_contract(&mut self, address: H160) {
-        get_state()
-            .borrow_mut()
-            .config
-            .set_bft_bridge_contract(address);
+    pub async fn set_bft_bridge_contract(&mut self, address: H160) -> Result<()> {
+        MinterCanister::set_bft_bridge_contract_inspect_message_check(
+            ic::caller(),
+            &get_state().borrow(),
+        )?;
+        check_direct_operation()?;
+        apply_admin_operation(AdminOperation::SetBftBridgeContract(address))
     }
 
     /// Returns bridge contract address for EVM.
@@ -263,6 +320,138 @@ impl MinterCanister {
         get_state().borrow().config.get_bft_bridge_contract()
     }
 
+    /// set_gas_limits inspect_message check
+    pub fn set_gas_limits_inspect_message_check(principal: Principal, state: &State) -> Result<()> {
+        inspect_check_is_owner(principal, state)
+    }
+
+    /// Sets gas limits of the EVM transactions by the operation. The limit of an operation is
+    /// bumped automatically when its transaction runs out of gas.
+    ///
+    /// This method should be called only by current owner,
+    /// else `Error::NotAuthorised` will be returned.
+    #[update]
+    pub fn set_gas_limits(&mut self, limits: GasLimits) -> Result<()> {
+        MinterCanister::set_gas_limits_inspect_message_check(ic::caller(), &get_state().borrow())?;
+        check_direct_operation()?;
+        apply_admin_operation(AdminOperation::SetGasLimits(limits))
+    }
+
+    /// Returns gas limits of the EVM transactions.
+    #[query]
+    pub fn get_gas_limits(&self) -> GasLimits {
+        get_state().borrow().gas_limits.clone()
+    }
+
+    /// set_max_in_flight_txs inspect_message check
+    pub fn set_max_in_flight_txs_inspect_message_check(
+        principal: Principal,
+        state: &State,
+    ) -> Result<()> {
+        inspect_check_is_owner(principal, state)
+    }
+
+    /// Sets the maximum number of the mint transactions sent to the EVM and not yet confirmed.
+    /// The mint transactions above the limit wait for the earlier ones to be confirmed.
+    ///
+    /// This method should be called only by current owner,
+    /// else `Error::NotAuthorised` will be returned.
+    #[update]
+    pub fn set_max_in_flight_txs(&mut self, max_in_flight: u32) -> Result<()> {
+        MinterCanister::set_max_in_flight_txs_inspect_message_check(
+            ic::caller(),
+            &get_state().borrow(),
+        )?;
+        check_direct_operation()?;
+        apply_admin_operation(AdminOperation::SetMaxInFlightTxs(max_in_flight))
+    }
+
+    /// Returns the number of the mint transactions in flight and waiting to be sent.
+    #[query]
+    pub fn get_in_flight_txs(&self) -> InFlightTxsInfo {
+        get_state().borrow().in_flight_txs.info()
+    }
+
+    /// admin_set_approval_config inspect_message check
+    pub fn admin_set_approval_config_inspect_message_check(
+        principal: Principal,
+        state: &State,
+    ) -> Result<()> {
+        inspect_check_is_owner(principal, state)
+    }
+
+    /// Sets the approvers of the dangerous operations, or disables the approvals if `config` is
+    /// `None`. Once the approvals are enabled, the operations of [`AdminOperation`], e.g. the
+    /// owner and the BftBridge contract changes, are applied only with the approved proposals.
+    ///
+    /// This method should be called only by current owner,
+    /// else `Error::NotAuthorised` will be returned.
+    #[update]
+    pub fn admin_set_approval_config(&mut self, config: Option<ApprovalConfig>) -> Result<()> {
+        MinterCanister::admin_set_approval_config_inspect_message_check(
+            ic::caller(),
+            &get_state().borrow(),
+        )?;
+        check_direct_operation()?;
+        apply_admin_operation(AdminOperation::SetApprovalConfig(config))
+    }
+
+    #[query]
+    pub fn get_approval_config(&self) -> Option<ApprovalConfig> {
+        get_admin_approvals().config()
+    }
+
+    /// Proposes the dangerous `operation`, approved by the caller. Returns the id of the
+    /// proposal.
+    #[update]
+    pub fn admin_propose_operation(&mut self, operation: AdminOperation) -> Result<u64> {
+        get_admin_approvals()
+            .propose(ic::caller(), operation, get_state().borrow().clock().now())
+            .map_err(approval_error)
+    }
+
+    /// Approves the proposal. Returns the number of its approvals.
+    #[update]
+    pub fn admin_approve_operation(&mut self, proposal_id: u64) -> Result<u32> {
+        get_admin_approvals()
+            .approve(
+                ic::caller(),
+                proposal_id,
+                get_state().borrow().clock().now(),
+            )
+            .map_err(approval_error)
+    }
+
+    /// Applies the operation of the proposal if it has the approvals of the threshold number of
+    /// the approvers.
+    #[update]
+    pub fn admin_execute_operation(&mut self, proposal_id: u64) -> Result<()> {
+        let now = get_state().borrow().clock().now();
+        let operation = get_admin_approvals()
+            .approved_operation(ic::caller(), proposal_id, now)
+            .map_err(approval_error)?;
+        get_admin_approvals().mark_executed(proposal_id, now);
+        let result = apply_admin_operation(operation);
+        if result.is_err() {
+            get_admin_approvals().reopen(proposal_id);
+        }
+        result
+    }
+
+    /// Returns the proposals which are not executed and not expired, to the approvers only.
+    #[query]
+    pub fn get_operation_proposals(&self) -> Result<Vec<Proposal<AdminOperation>>> {
+        let approvals = get_admin_approvals();
+        if !approvals
+            .config()
+            .is_some_and(|config| config.is_approver(&ic::caller()))
+        {
+            return Err(Error::NotAuthorized);
+        }
+
+        Ok(approvals.open_proposals(get_state().borrow().clock().now()))
+    }
+
     /// Returns `(nonce, mint_order)` pairs for the given sender id.
     #[query]
     pub fn list_mint_orders(
@@ -281,6 +470,39 @@ impl MinterCanister {
             .collect()
     }
 
+    /// Same as `list_mint_orders`, but returns only the requested page of the list.
+    #[query]
+    pub fn list_mint_orders_page(
+        &self,
+        wallet_address: H160,
+        src_token: Id256,
+        pagination: Option<Pagination>,
+    ) -> Paged<(u32, SignedMintOrder)> {
+        pagination
+            .unwrap_or_default()
+            .paginate(self.list_mint_orders(wallet_address, src_token))
+    }
+
+    /// Same as `list_mint_orders_page`, but pages the list with a cursor instead of an offset,
+    /// so the query reads only the operations of the requested page.
+    #[query]
+    pub fn list_mint_orders_by_cursor(
+        &self,
+        wallet_address: H160,
+        src_token: Id256,
+        request: Option<CursorRequest>,
+    ) -> Result<CursorPage<(u32, SignedMintOrder)>, InvalidCursor> {
+        get_operations_store().page_for_address(
+            &wallet_address,
+            &request.unwrap_or_default(),
+            |operation_id, status| {
+                status
+                    .get_signed_mint_order(Some(src_token))
+                    .map(|mint_order| (operation_id.nonce(), *mint_order))
+            },
+        )
+    }
+
     /// Returns `(nonce, mint_order)` pairs for the given sender id and operation_id.
     #[query]
     pub fn get_mint_order(
@@ -303,6 +525,53 @@ impl MinterCanister {
         get_operations_store().get_for_address(&wallet_address)
     }
 
+    /// Same as `get_operations_list`, but returns only the requested page of the list.
+    #[query]
+    pub fn get_operations_page(
+        &self,
+        wallet_address: H160,
+        pagination: Option<Pagination>,
+    ) -> Paged<(MinterOperationId, OperationState)> {
+        pagination
+            .unwrap_or_default()
+            .paginate(get_operations_store().get_for_address(&wallet_address))
+    }
+
+    /// Same as `get_operations_page`, but pages the list with a cursor instead of an offset, so
+    /// the query reads only the operations of the requested page.
+    #[query]
+    pub fn get_operations_by_cursor(
+        &self,
+        wallet_address: H160,
+        request: Option<CursorRequest>,
+    ) -> Result<CursorPage<(MinterOperationId, OperationState)>, InvalidCursor> {
+        get_operations_store().page_for_address(
+            &wallet_address,
+            &request.unwrap_or_default(),
+            |id, payload| Some((id, payload)),
+        )
+    }
+
+    /// Waits until the state of the operation changes, or until `timeout_secs` pass, and returns
+    /// the state, so the clients don't poll `get_operations_list`. The timeout is capped at 30
+    /// seconds, and the call is rejected if too many calls are waiting already.
+    #[update]
+    pub async fn await_operation(
+        &self,
+        operation_id: MinterOperationId,
+        timeout_secs: u64,
+    ) -> Result<Option<OperationState>, TooManyWaiters> {
+        long_poll::await_change(timeout_secs, || get_operations_store().get(operation_id)).await
+    }
+
+    /// Returns the entries recorded for the operation, e.g. its status changes and the task
+    /// failures, oldest first. The traces are kept for the latest operations since the last
+    /// upgrade.
+    #[query]
+    pub fn get_operation_trace(&self, operation_id: MinterOperationId) -> Vec<TraceEntry> {
+        operation_trace::get_operation_trace(operation_id)
+    }
+
     /// Returns evm_address of the minter canister.
     #[update]
     pub async fn get_minter_canister_evm_address(&mut self) -> Result<H160> {
@@ -313,7 +582,122 @@ impl MinterCanister {
             .map_err(|e| Error::Internal(format!("failed to get minter canister address: {e}")))
     }
 
-    /// Returns the build data of the canister
+    /// Returns the results of the latest checks of the EVM canister and the signer. The
+    /// dependencies are reported as `Unknown` until the first periodic check after the canister
+    /// installation or upgrade.
+    #[query]
+    pub fn health(&self) -> HealthReport {
+        get_state().borrow().health.report()
+    }
+
+    /// Returns the cycles balance, the heap and stable memory usage and the number of the
+    /// scheduled tasks of the canister.
+    #[query]
+    pub fn canister_status_info(&self) -> CanisterStatusInfo {
+        MEMORY_MANAGER.with(|mm| {
+            let stable_structures = stable_structures_usage(STABLE_STRUCTURES, |id| mm.get(id));
+            let scheduled_tasks = TasksStorage::new(mm.get(PENDING_TASKS_MEMORY_ID)).len();
+            CanisterStatusInfo::collect(stable_structures, scheduled_tasks)
+        })
+    }
+
+    /// Checks the init data with the same rules as the canister init, returning all the errors
+    /// found. The list is empty if the init data is valid.
+    #[query]
+    pub fn validate_config(&self, init_data: InitData) -> Vec<ConfigError> {
+        let settings = Settings {
+            owner: init_data.owner,
+            evm_principal: init_data.evm_principal,
+            signing_strategy: init_data.signing_strategy,
+        };
+
+        settings.validate().err().unwrap_or_default()
+    }
+
+    /// admin_validate_bridge_config and admin_validate_bft_config inspect_message check
+    pub fn admin_validate_config_inspect_message_check(
+        principal: Principal,
+        state: &State,
+    ) -> Result<()> {
+        inspect_check_is_owner(principal, state)
+    }
+
+    /// Dry-runs the init data: checks it with the init rules and, if it is valid, checks that
+    /// the EVM canister responds and has the chain id the minter works with. Returns all the
+    /// errors found, or an empty list.
+    ///
+    /// The EVM checks need calls to the EVM canister, so this is an update, but it doesn't
+    /// change the state.
+    ///
+    /// This method should be called only by current owner,
+    /// else `Error::NotAuthorised` will be returned.
+    #[update]
+    pub async fn admin_validate_bridge_config(
+        &self,
+        init_data: InitData,
+    ) -> Result<Vec<ConfigError>> {
+        MinterCanister::admin_validate_config_inspect_message_check(
+            ic::caller(),
+            &get_state().borrow(),
+        )?;
+
+        let evm_principal = init_data.evm_principal;
+        let errors = self.validate_config(init_data);
+        if !errors.is_empty() || evm_principal == Principal::anonymous() {
+            return Ok(errors);
+        }
+
+        let client = EvmLink::Ic(evm_principal).get_json_rpc_client();
+        let chain_id = get_state()
+            .borrow()
+            .config
+            .get_evm_params()
+            .map(|params| params.chain_id);
+        let mut validator = ConfigValidator::new();
+        match chain_id {
+            Some(chain_id) => {
+                validator
+                    .evm_chain_id("evm_principal", &client, chain_id)
+                    .await
+            }
+            None => validator.check("evm_principal", health::check_evm_client(&client).await),
+        };
+
+        Ok(validator.finish().err().unwrap_or_default())
+    }
+
+    /// Dry-runs `set_bft_bridge_contract`: checks that the contract is deployed in the EVM
+    /// without setting it. Returns all the errors found, or an empty list.
+    ///
+    /// This method should be called only by current owner,
+    /// else `Error::NotAuthorised` will be returned.
+    #[update]
+    pub async fn admin_validate_bft_config(&self, address: H160) -> Result<Vec<ConfigError>> {
+        let (client, chain_id) = {
+            let state = get_state();
+            let state = state.borrow();
+            MinterCanister::admin_validate_config_inspect_message_check(ic::caller(), &state)?;
+            (
+                state.config.get_evm_client(),
+                state.config.get_evm_params().map(|params| params.chain_id),
+            )
+        };
+
+        Ok(validate_bft_bridge_contract(&client, &address, chain_id)
+            .await
+            .err()
+            .unwrap_or_default())
+    }
+
+    /// Returns the version, the git commit, the build timestamp and the enabled features of the
+    /// canister build.
+    #[query]
+    pub fn get_build_data(&self) -> BuildData {
+        canister_build_data()
+    }
+
+    /// Returns the build data of the canister. Same as `get_build_data`, kept for the existing
+    /// clients.
     #[query]
     pub fn get_canister_build_data(&self) -> BuildData {
         canister_build_data()
@@ -410,10 +794,94 @@ fn check_anonymous_principal(principal: Principal) -> Result<()> {
     Ok(())
 }
 
+/// Checks that a dangerous operation may be applied by the owner call directly, without an
+/// approved proposal.
+fn check_direct_operation() -> Result<()> {
+    get_admin_approvals().check_direct().map_err(approval_error)
+}
+
+fn approval_error(err: ApprovalError) -> Error {
+    match err {
+        ApprovalError::NotApprover => Error::NotAuthorized,
+        err => Error::Internal(err.to_string()),
+    }
+}
+
+fn apply_admin_operation(operation: AdminOperation) -> Result<()> {
+    let state = get_state();
+    match operation {
+        AdminOperation::SetOwner(owner) => {
+            check_anonymous_principal(owner)?;
+            state.borrow_mut().config.set_owner(owner);
+            info!("minter canister owner changed to {owner}");
+        }
+        AdminOperation::SetEvmPrincipal(evm) => {
+            check_anonymous_principal(evm)?;
+            state.borrow_mut().config.set_evm_principal(evm);
+            info!("EVM principal changed to {evm}");
+        }
+        AdminOperation::SetBftBridgeContract(address) => {
+            info!("BFT bridge contract changed to {:#x}", address.0);
+            state.borrow_mut().config.set_bft_bridge_contract(address);
+        }
+        AdminOperation::SetGasLimits(limits) => {
+            limits
+                .validate()
+                .map_err(|e| Error::Internal(format!("invalid gas limits: {e}")))?;
+            state.borrow_mut().gas_limits = limits;
+        }
+        AdminOperation::SetMaxInFlightTxs(max_in_flight) => state
+            .borrow_mut()
+            .in_flight_txs
+            .set_max_in_flight(max_in_flight)
+            .map_err(Error::Internal)?,
+        AdminOperation::SetApprovalConfig(config) => get_admin_approvals()
+            .configure(config)
+            .map_err(approval_error)?,
+    }
+
+    Ok(())
+}
+
 type TasksStorage =
     StableBTreeMap<u32, InnerScheduledTask<BridgeTask>, VirtualMemory<DefaultMemoryImpl>>;
 type PersistentScheduler = Scheduler<BridgeTask, TasksStorage>;
 
+fn on_task_completed(task: InnerScheduledTask<BridgeTask>) {
+    if reschedule_deferred_task(&task) {
+        return;
+    }
+
+    log_task_execution_error(task);
+}
+
+/// Appends a mint transaction task whose retries were used up by the deferrals of the in-flight
+/// window again, with the options it was appended with. Returns `false` if the task is completed
+/// or has failed for another reason. The scheduler is borrowed while it reports the completed
+/// tasks, so the task is appended by a timer after [`DEFERRED_TASK_DELAY_SECS`].
+fn reschedule_deferred_task(task: &InnerScheduledTask<BridgeTask>) -> bool {
+    let TaskStatus::Failed {
+        error: SchedulerError::TaskExecutionFailed(error),
+        ..
+    } = task.status()
+    else {
+        return false;
+    };
+    if !is_deferral_error(error.as_str()) {
+        return false;
+    }
+
+    debug!("task #{} is rescheduled after its deferrals", task.id());
+    let scheduled = ScheduledTask::with_options(task.task().clone(), task.options().clone());
+    ic_exports::ic_cdk_timers::set_timer(
+        Duration::from_secs(DEFERRED_TASK_DELAY_SECS),
+        move || {
+            get_scheduler().borrow().append_task(scheduled);
+        },
+    );
+    true
+}
+
 fn log_task_execution_error(task: InnerScheduledTask<BridgeTask>) {
     match task.status() {
         TaskStatus::Failed {
@@ -423,10 +891,24 @@ fn log_task_execution_error(task: InnerScheduledTask<BridgeTask>) {
             log::error!(
                 "task #{} execution failed: {error} at {timestamp_secs}",
                 task.id()
-            )
+            );
+            if let Some(operation_id) = task.task().operation_id() {
+                operation_trace::record(
+                    operation_id,
+                    log::Level::Error,
+                    format!("task={:?} status=failed error={error}", task.task()),
+                );
+            }
         }
         TaskStatus::TimeoutOrPanic { timestamp_secs } => {
-            log::error!("task #{} panicked at {timestamp_secs}", task.id())
+            log::error!("task #{} panicked at {timestamp_secs}", task.id());
+            if let Some(operation_id) = task.task().operation_id() {
+                operation_trace::record(
+                    operation_id,
+                    log::Level::Error,
+                    format!("task={:?} status=panicked", task.task()),
+                );
+            }
         }
         status_change => {
             log::trace!("task #{} status changed: {status_change:?}", task.id())
@@ -452,6 +934,15 @@ pub fn get_state() -> Rc<RefCell<State>> {
     STATE.with(|state| state.clone())
 }
 
+pub fn get_admin_approvals() -> ApprovalStore<AdminOperation, VirtualMemory<DefaultMemoryImpl>> {
+    MEMORY_MANAGER.with(|mm| {
+        ApprovalStore::new(
+            mm.get(APPROVAL_SETTINGS_MEMORY_ID),
+            mm.get(APPROVAL_PROPOSALS_MEMORY_ID),
+        )
+    })
+}
+
 pub fn get_operations_store(
 ) -> MinterOperationStore<VirtualMemory<DefaultMemoryImpl>, OperationState> {
     MEMORY_MANAGER.with(|mm| {
@@ -586,6 +1077,57 @@ mod test {
         assert_eq!(stored_owner, bob());
     }
 
+    #[tokio::test]
+    async fn owner_change_needs_approval_when_enabled() {
+        let mut canister = init_canister().await;
+
+        inject::get_context().update_id(owner());
+        let config = ApprovalConfig {
+            approvers: vec![owner(), bob()],
+            threshold: 2,
+        };
+        canister_call!(canister.admin_set_approval_config(Some(config)), Result<()>)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let err = canister_call!(canister.set_owner(bob()), Result<()>)
+            .await
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(
+            err,
+            Error::Internal(ApprovalError::ApprovalRequired.to_string())
+        );
+
+        let proposal_id = canister_call!(
+            canister.admin_propose_operation(AdminOperation::SetOwner(bob())),
+            Result<u64>
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        canister_call!(canister.admin_execute_operation(proposal_id), Result<()>)
+            .await
+            .unwrap()
+            .unwrap_err();
+
+        inject::get_context().update_id(bob());
+        canister_call!(canister.admin_approve_operation(proposal_id), Result<u32>)
+            .await
+            .unwrap()
+            .unwrap();
+        canister_call!(canister.admin_execute_operation(proposal_id), Result<()>)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let stored_owner = canister_call!(canister.get_owner(), Principal)
+            .await
+            .unwrap();
+        assert_eq!(stored_owner, bob());
+    }
+
     #[tokio::test]
     async fn set_anonymous_principal_as_owner() {
         let mut canister = init_canister().await;
@@ -1,12 +1,28 @@
-use candid::Principal;
+use candid::{CandidType, Principal};
+use did::H160;
 use ic_exports::ic_cdk::{self, api};
 use ic_exports::ic_cdk_macros::inspect_message;
 use ic_exports::ic_kit::ic;
-use minter_did::error::Result;
+use minter_contract_utils::admin_approvals::ApprovalConfig;
+use minter_contract_utils::gas_limits::GasLimits;
+use minter_contract_utils::operation_store::MinterOperationId;
+use minter_did::error::{Error, Result};
+use minter_did::init::InitData;
+use serde::Deserialize;
 
+use crate::interface::AdminOperation;
 use crate::state::State;
 use crate::MinterCanister;
 
+/// Methods which anonymous callers are allowed to call with ingress messages. The replicated
+/// execution of any other method, including a query called as an update, is paid by the canister,
+/// so the messages of anonymous callers are rejected before the execution.
+const ANONYMOUS_METHODS: &[&str] = &["get_minter_canister_evm_address"];
+
+/// Maximum size of the arguments of an ingress message. The arguments of all the methods are well
+/// below it, so a larger message is junk.
+const MAX_ARG_SIZE: usize = 16 * 1024;
+
 #[inspect_message]
 async fn inspect_message() {
     let check_result = inspect_method(&api::call::method_name()).await;
@@ -18,26 +34,148 @@ async fn inspect_message() {
     }
 }
 
+/// Checks of the message which don't depend on the method arguments.
+fn inspect_ingress(method: &str, caller: Principal, arg_size: usize) -> Result<()> {
+    if arg_size > MAX_ARG_SIZE {
+        return Err(Error::Internal(format!(
+            "arguments of {arg_size} bytes exceed the limit of {MAX_ARG_SIZE} bytes"
+        )));
+    }
+
+    if caller == Principal::anonymous() && !ANONYMOUS_METHODS.contains(&method) {
+        return Err(Error::AnonymousPrincipal);
+    }
+
+    Ok(())
+}
+
+/// Decodes the arguments of the method, trapping if they are not valid, so the message is
+/// rejected.
+fn args<T: CandidType + for<'de> Deserialize<'de>>() -> T {
+    api::call::arg_data::<T>(Default::default())
+}
+
+/// Rejects the principals no canister method accepts as an argument.
+fn check_principal_arg(principal: Principal) -> Result<()> {
+    if principal == Principal::anonymous() {
+        return Err(Error::AnonymousPrincipal);
+    }
+    if principal == Principal::management_canister() {
+        return Err(Error::Internal(
+            "management canister principal is not allowed".into(),
+        ));
+    }
+
+    Ok(())
+}
+
 async fn inspect_method(method: &str) -> Result<()> {
+    inspect_ingress(method, ic::caller(), api::call::arg_data_raw_size())?;
+
     let state = State::default();
 
     match method {
         "set_logger_filter" => {
+            let (_filter,) = args::<(String,)>();
             MinterCanister::set_logger_filter_inspect_message_check(ic::caller(), &state)
         }
-        "ic_logs" => MinterCanister::ic_logs_inspect_message_check(ic::caller(), &state),
+        "ic_logs" => {
+            let (_count, _offset) = args::<(usize, usize)>();
+            MinterCanister::ic_logs_inspect_message_check(ic::caller(), &state)
+        }
         "set_evm_principal" => {
-            let (evm,) = api::call::arg_data::<(Principal,)>(Default::default());
+            let (evm,) = args::<(Principal,)>();
+            check_principal_arg(evm)?;
             MinterCanister::set_evm_principal_inspect_message_check(ic::caller(), evm, &state)
         }
         "set_owner" => {
-            let (owner,) = api::call::arg_data::<(Principal,)>(Default::default());
+            let (owner,) = args::<(Principal,)>();
+            check_principal_arg(owner)?;
             MinterCanister::set_owner_inspect_message_check(ic::caller(), owner, &state)
         }
+        "set_bft_bridge_contract" => {
+            let (address,) = args::<(H160,)>();
+            if address.0.is_zero() {
+                return Err(Error::Internal("zero address is not allowed".into()));
+            }
+            MinterCanister::set_bft_bridge_contract_inspect_message_check(ic::caller(), &state)
+        }
+        "set_gas_limits" => {
+            let (_limits,) = args::<(GasLimits,)>();
+            MinterCanister::set_gas_limits_inspect_message_check(ic::caller(), &state)
+        }
+        "set_max_in_flight_txs" => {
+            let (max_in_flight,) = args::<(u32,)>();
+            if max_in_flight == 0 {
+                return Err(Error::Internal(
+                    "maximum number of transactions in flight must be positive".into(),
+                ));
+            }
+            MinterCanister::set_max_in_flight_txs_inspect_message_check(ic::caller(), &state)
+        }
+        "admin_set_approval_config" => {
+            let (_config,) = args::<(Option<ApprovalConfig>,)>();
+            MinterCanister::admin_set_approval_config_inspect_message_check(ic::caller(), &state)
+        }
+        "admin_propose_operation" => {
+            let (_operation,) = args::<(AdminOperation,)>();
+            Ok(())
+        }
+        "admin_approve_operation" | "admin_execute_operation" => {
+            let (_proposal_id,) = args::<(u64,)>();
+            Ok(())
+        }
+        "admin_validate_bridge_config" => {
+            let (_init_data,) = args::<(InitData,)>();
+            MinterCanister::admin_validate_config_inspect_message_check(ic::caller(), &state)
+        }
+        "admin_validate_bft_config" => {
+            let (_address,) = args::<(H160,)>();
+            MinterCanister::admin_validate_config_inspect_message_check(ic::caller(), &state)
+        }
         "add_to_whitelist" | "remove_from_whitelist" => {
-            let (principal,) = api::call::arg_data::<(Principal,)>(Default::default());
+            let (principal,) = args::<(Principal,)>();
+            check_principal_arg(principal)?;
             MinterCanister::access_control_inspect_message_check(ic::caller(), principal, &state)
         }
+        "await_operation" => {
+            let (_operation_id, _timeout_secs) = args::<(MinterOperationId, u64)>();
+            Ok(())
+        }
+        "get_minter_canister_evm_address" => {
+            let () = args::<()>();
+            Ok(())
+        }
         _ => Ok(()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn oversized_and_anonymous_messages_are_rejected() {
+        let user = Principal::from_slice(&[1; 20]);
+
+        assert!(inspect_ingress("set_owner", user, MAX_ARG_SIZE).is_ok());
+        assert!(inspect_ingress("set_owner", user, MAX_ARG_SIZE + 1).is_err());
+        assert_eq!(
+            inspect_ingress("set_owner", Principal::anonymous(), 0),
+            Err(Error::AnonymousPrincipal)
+        );
+        assert!(
+            inspect_ingress("get_minter_canister_evm_address", Principal::anonymous(), 0).is_ok()
+        );
+    }
+
+    #[test]
+    fn invalid_principal_args_are_rejected() {
+        assert!(check_principal_arg(Principal::from_slice(&[1; 20])).is_ok());
+        assert_eq!(
+            check_principal_arg(Principal::anonymous()),
+            Err(Error::AnonymousPrincipal)
+        );
+        assert!(check_principal_arg(Principal::management_canister()).is_err());
+    }
+}
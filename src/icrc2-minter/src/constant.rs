@@ -8,7 +8,23 @@ pub const ACCESS_LIST_MEMORY_ID: MemoryId = MemoryId::new(87);
 pub const OPERATIONS_MEMORY_ID: MemoryId = MemoryId::new(88);
 pub const OPERATIONS_LOG_MEMORY_ID: MemoryId = MemoryId::new(89);
 pub const OPERATIONS_MAP_MEMORY_ID: MemoryId = MemoryId::new(90);
+pub const APPROVAL_SETTINGS_MEMORY_ID: MemoryId = MemoryId::new(91);
+pub const APPROVAL_PROPOSALS_MEMORY_ID: MemoryId = MemoryId::new(92);
+pub const IN_FLIGHT_TXS_MEMORY_ID: MemoryId = MemoryId::new(93);
 
-pub const DEFAULT_TX_GAS_LIMIT: u64 = 3_000_000;
+/// Stable structures reported by the `canister_status_info` query.
+pub const STABLE_STRUCTURES: &[(&str, MemoryId)] = &[
+    ("config", CONFIG_MEMORY_ID),
+    ("tx_signer", TX_SIGNER_MEMORY_ID),
+    ("log_settings", LOG_SETTINGS_MEMORY_ID),
+    ("pending_tasks", PENDING_TASKS_MEMORY_ID),
+    ("access_list", ACCESS_LIST_MEMORY_ID),
+    ("operations", OPERATIONS_MEMORY_ID),
+    ("operations_log", OPERATIONS_LOG_MEMORY_ID),
+    ("operations_map", OPERATIONS_MAP_MEMORY_ID),
+    ("approval_settings", APPROVAL_SETTINGS_MEMORY_ID),
+    ("approval_proposals", APPROVAL_PROPOSALS_MEMORY_ID),
+    ("in_flight_txs", IN_FLIGHT_TXS_MEMORY_ID),
+];
 
-pub const IC_CHAIN_ID: u32 = 0;
+pub const IC_CHAIN_ID: u32 = chain_registry::IC_CHAIN_ID;
@@ -0,0 +1,16 @@
+use candid::{CandidType, Principal};
+use did::H160;
+use minter_contract_utils::admin_approvals::ApprovalConfig;
+use minter_contract_utils::gas_limits::GasLimits;
+use serde::Deserialize;
+
+/// Dangerous operation applied through an approved proposal when the approvals are enabled.
+#[derive(Debug, Clone, CandidType, Deserialize)]
+pub enum AdminOperation {
+    SetOwner(Principal),
+    SetEvmPrincipal(Principal),
+    SetBftBridgeContract(H160),
+    SetGasLimits(GasLimits),
+    SetMaxInFlightTxs(u32),
+    SetApprovalConfig(Option<ApprovalConfig>),
+}
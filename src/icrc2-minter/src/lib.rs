@@ -9,6 +9,7 @@ pub use crate::canister::MinterCanister;
 mod build_data;
 pub mod canister;
 mod constant;
+pub mod interface;
 mod memory;
 pub mod operation;
 pub mod state;
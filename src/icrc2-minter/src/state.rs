@@ -1,13 +1,22 @@
+use std::rc::Rc;
+
 use access_list::AccessList;
 use candid::Principal;
 pub use config::Config;
 pub use eth_signer::sign_strategy::{SigningStrategy, TransactionSigner};
 use ic_stable_structures::stable_structures::DefaultMemoryImpl;
 use ic_stable_structures::{default_ic_memory_manager, VirtualMemory};
+use minter_contract_utils::clock::{Clock, IcClock};
+use minter_contract_utils::config_validation::{ConfigError, ConfigValidator};
+use minter_contract_utils::gas_limits::GasLimits;
+use minter_contract_utils::gas_price::GasPriceSampler;
+use minter_contract_utils::health::{HealthMonitor, EVM_RPC, SIGNER};
+use minter_contract_utils::in_flight_txs::InFlightTxs;
+use minter_contract_utils::operation_store::MinterOperationId;
 
 use self::log::LoggerConfigService;
 use self::signer::SignerInfo;
-use crate::constant::ACCESS_LIST_MEMORY_ID;
+use crate::constant::{ACCESS_LIST_MEMORY_ID, IN_FLIGHT_TXS_MEMORY_ID};
 
 mod access_list;
 mod config;
@@ -25,6 +34,21 @@ pub struct State {
     pub logger_config_service: LoggerConfigService,
 
     pub access_list: AccessList<VirtualMemory<DefaultMemoryImpl>>,
+
+    /// Results of the latest dependency checks.
+    pub health: HealthMonitor,
+
+    /// Gas price estimate of the EVM.
+    pub gas_price: GasPriceSampler,
+
+    /// Gas limits of the EVM transactions.
+    pub gas_limits: GasLimits,
+
+    /// Mint transactions sent to the EVM and not yet confirmed.
+    pub in_flight_txs: InFlightTxs<MinterOperationId, VirtualMemory<DefaultMemoryImpl>>,
+
+    /// Source of the time for the timeout, lease and gas price checks.
+    pub clock: Rc<dyn Clock>,
 }
 
 impl Default for State {
@@ -35,11 +59,20 @@ impl Default for State {
             signer: SignerInfo::default(),
             logger_config_service: LoggerConfigService::default(),
             access_list: AccessList::new(memory_manager.get(ACCESS_LIST_MEMORY_ID)),
+            health: HealthMonitor::new(&[EVM_RPC, SIGNER]),
+            gas_price: GasPriceSampler::default(),
+            gas_limits: GasLimits::default(),
+            in_flight_txs: InFlightTxs::new(memory_manager.get(IN_FLIGHT_TXS_MEMORY_ID)),
+            clock: Rc::new(IcClock),
         }
     }
 }
 
 impl State {
+    pub fn clock(&self) -> Rc<dyn Clock> {
+        self.clock.clone()
+    }
+
     /// Clear the state and set initial data from settings.
     pub fn reset(&mut self, settings: Settings) {
         self.signer
@@ -57,6 +90,25 @@ pub struct Settings {
     pub signing_strategy: SigningStrategy,
 }
 
+impl Settings {
+    /// Checks the settings, returning all the errors found.
+    pub fn validate(&self) -> Result<(), Vec<ConfigError>> {
+        let mut validator = ConfigValidator::new();
+        validator.principal("owner", self.owner);
+
+        // The EVM principal can be set after the init with `set_evm_principal`, so the anonymous
+        // principal is allowed here as a placeholder.
+        if self.evm_principal == Principal::management_canister() {
+            validator.invalid(
+                "evm_principal",
+                "management canister principal is not allowed",
+            );
+        }
+
+        validator.finish()
+    }
+}
+
 impl Default for Settings {
     fn default() -> Self {
         Self {
@@ -8,7 +8,7 @@ use candid::{CandidType, Decode, Nat, Principal};
 use did::{H160, U256};
 use eth_signer::sign_strategy::TransactionSigner;
 use ethers_core::types::Log;
-use ic_exports::ic_kit::{ic, RejectionCode};
+use ic_exports::ic_kit::RejectionCode;
 use ic_task_scheduler::retry::BackoffPolicy;
 use ic_task_scheduler::scheduler::TaskScheduler;
 use ic_task_scheduler::task::{ScheduledTask, Task, TaskOptions};
@@ -16,11 +16,15 @@ use ic_task_scheduler::SchedulerError;
 use icrc_client::account::Account;
 use icrc_client::transfer::TransferError;
 use jsonrpc_core::Id;
+use minter_contract_utils::account_mapping::eth_address_to_subaccount;
 use minter_contract_utils::bft_bridge_api::{self, BridgeEvent, MintedEventData};
 use minter_contract_utils::evm_bridge::EvmParams;
-use minter_contract_utils::evm_link::address_to_icrc_subaccount;
+use minter_contract_utils::gas_limits::GasOperation;
+use minter_contract_utils::gas_price;
+use minter_contract_utils::health::{self, EVM_RPC, SIGNER};
 use minter_contract_utils::operation_store::MinterOperationId;
 use minter_contract_utils::query::{self, Query, QueryType, GAS_PRICE_ID, NONCE_ID};
+use minter_contract_utils::task_limits::TaskDeferred;
 use minter_did::error::Error;
 use minter_did::id256::Id256;
 use minter_did::order::{self, MintOrder};
@@ -52,10 +56,11 @@ struct CollectLogsLock {
 
 impl CollectLogsLock {
     fn take() -> Option<Self> {
+        let now = crate::canister::get_state().borrow().clock().now();
         match COLLECT_EVM_LOGS_TS.with(|v| *v.borrow()) {
-            Some(ts) if (ts + COLLECT_EVM_LOGS_TIMEOUT.as_nanos() as u64) >= ic::time() => None,
+            Some(ts) if (ts + COLLECT_EVM_LOGS_TIMEOUT.as_nanos() as u64) >= now => None,
             _ => {
-                let ts = ic::time();
+                let ts = now;
                 COLLECT_EVM_LOGS_TS.with(|v| *v.borrow_mut() = Some(ts));
                 Some(Self { ts })
             }
@@ -85,6 +90,8 @@ pub enum BridgeTask {
     RemoveMintOrder(MintedEventData),
     SendMintTransaction(MinterOperationId),
     MintIcrc2Tokens(MinterOperationId),
+    CheckHealth,
+    RefreshGasPrice,
 }
 
 impl Task for BridgeTask {
@@ -93,6 +100,19 @@ impl Task for BridgeTask {
         scheduler: Box<dyn 'static + TaskScheduler<Self>>,
     ) -> Pin<Box<dyn Future<Output = Result<(), SchedulerError>>>> {
         let state = crate::canister::get_state();
+        if let BridgeTask::SendMintTransaction(operation_id) = self {
+            let now = state.borrow().clock().now();
+            let mut state = state.borrow_mut();
+            if !state.in_flight_txs.try_acquire(*operation_id, now) {
+                let reason = TaskDeferred::InFlightTxLimit {
+                    limit: state.in_flight_txs.max_in_flight(),
+                };
+                log::debug!("Mint transaction of operation {operation_id} is queued: {reason}");
+                let error = SchedulerError::TaskExecutionFailed(reason.error_message());
+                return Box::pin(async move { Err(error) });
+            }
+        }
+
         match self {
             BridgeTask::InitEvmInfo => Box::pin(Self::init_evm_info(state)),
             BridgeTask::CollectEvmEvents => Box::pin(Self::collect_evm_events(state, scheduler)),
@@ -107,11 +127,21 @@ impl Task for BridgeTask {
                 Box::pin(async move { Self::remove_mint_order(data) })
             }
             BridgeTask::SendMintTransaction(operation_id) => {
-                Box::pin(Self::send_mint_transaction(state, *operation_id))
+                let operation_id = *operation_id;
+                Box::pin(async move {
+                    let result = Self::send_mint_transaction(state.clone(), operation_id).await;
+                    // A transaction which is not sent never frees its slot with the `Minted` event.
+                    if result.is_err() {
+                        state.borrow_mut().in_flight_txs.release(&operation_id);
+                    }
+                    result
+                })
             }
             BridgeTask::MintIcrc2Tokens(operation_id) => {
                 Box::pin(Self::mint_icrc2(*operation_id, scheduler))
             }
+            BridgeTask::CheckHealth => Box::pin(Self::check_health(state)),
+            BridgeTask::RefreshGasPrice => Box::pin(Self::refresh_gas_price(state)),
         }
     }
 }
@@ -121,6 +151,17 @@ impl BridgeTask {
         ScheduledTask::with_options(self, options)
     }
 
+    /// Operation processed by the task, if any. It is the correlation id of the operation trace.
+    pub fn operation_id(&self) -> Option<MinterOperationId> {
+        match self {
+            BridgeTask::BurnIcrc2Tokens(operation_id)
+            | BridgeTask::PrepareMintOrder(operation_id)
+            | BridgeTask::SendMintTransaction(operation_id)
+            | BridgeTask::MintIcrc2Tokens(operation_id) => Some(*operation_id),
+            _ => None,
+        }
+    }
+
     pub async fn init_evm_info(state: Rc<RefCell<State>>) -> Result<(), SchedulerError> {
         log::trace!("evm info initialization started");
 
@@ -144,6 +185,36 @@ impl BridgeTask {
         Ok(())
     }
 
+    /// Checks the dependencies of the minter and records the results in the health monitor.
+    async fn check_health(state: Rc<RefCell<State>>) -> Result<(), SchedulerError> {
+        let client = state.borrow().config.get_evm_client();
+        let signer = state.borrow().signer.get_transaction_signer();
+
+        let evm_result = health::check_evm_client(&client).await;
+        let signer_result = health::check_signer(&signer).await;
+
+        let now = state.borrow().clock().now();
+        let mut state = state.borrow_mut();
+        state.health.record(EVM_RPC, evm_result, now);
+        state.health.record(SIGNER, signer_result, now);
+
+        Ok(())
+    }
+
+    /// Samples the gas price of the EVM and updates its estimate.
+    async fn refresh_gas_price(state: Rc<RefCell<State>>) -> Result<(), SchedulerError> {
+        let client = state.borrow().config.get_evm_client();
+        let sample = gas_price::sample_gas_price(&client)
+            .await
+            .into_scheduler_result()?;
+
+        log::trace!("Sampled gas price: {sample:?}");
+        let now = state.borrow().clock().now();
+        state.borrow_mut().gas_price.record(sample, now);
+
+        Ok(())
+    }
+
     async fn collect_evm_events(
         state: Rc<RefCell<State>>,
         scheduler: Box<dyn 'static + TaskScheduler<Self>>,
@@ -242,7 +313,7 @@ impl BridgeTask {
         let name = order::fit_str_to_array(&token_info.name);
         let symbol = order::fit_str_to_array(&token_info.symbol);
 
-        let spender_subaccount = address_to_icrc_subaccount(&reason.recipient_address.0);
+        let spender_subaccount = eth_address_to_subaccount(&reason.recipient_address);
         icrc2::burn(
             reason.icrc2_token_principal,
             caller_account,
@@ -475,6 +546,11 @@ impl BridgeTask {
             )));
         };
 
+        crate::canister::get_state()
+            .borrow_mut()
+            .in_flight_txs
+            .release(&operation_id);
+
         match operation_state {
             OperationState::Deposit(DepositOperationState::MintOrderSent {
                 token_id,
@@ -504,6 +580,33 @@ impl BridgeTask {
                     }),
                 );
             }
+            // The order is minted by a transaction whose result was lost, or by the user, so the
+            // transaction id is unknown.
+            OperationState::Deposit(DepositOperationState::MintOrderSigned {
+                token_id, ..
+            }) if token_id == src_token => {
+                operation_store.update(
+                    operation_id,
+                    OperationState::Deposit(DepositOperationState::Minted {
+                        token_id: src_token,
+                        amount: minted_event.amount,
+                        tx_id: Default::default(),
+                    }),
+                );
+            }
+            OperationState::Withdrawal(WithdrawalOperationState::RefundMintOrderSigned {
+                token_id,
+                ..
+            }) if token_id == src_token => {
+                operation_store.update(
+                    operation_id,
+                    OperationState::Withdrawal(WithdrawalOperationState::RefundMinted {
+                        token_id: src_token,
+                        amount: minted_event.amount,
+                        tx_id: Default::default(),
+                    }),
+                );
+            }
             OperationState::Deposit(DepositOperationState::MintOrderSent { token_id, .. })
             | OperationState::Withdrawal(WithdrawalOperationState::RefundMintOrderSent {
                 token_id,
@@ -570,11 +673,46 @@ impl BridgeTask {
             ));
         };
 
+        // A retry of the task must not resend the order if an earlier transaction, whose result
+        // was lost, or the user has minted it already. The `Minted` event completes the operation.
+        if let Some((order, _)) = MintOrder::decode_signed(&signed_mint_order.0) {
+            let client = state.borrow().config.get_evm_client();
+            // Older BftBridge proxies have no `isNonceUsed` and revert the check. The bridge
+            // rejects a used order itself, so an unknown order is sent.
+            let used = match bft_bridge_api::is_mint_order_used(
+                &client,
+                bridge_contract.0,
+                &order.sender,
+                order.nonce,
+            )
+            .await
+            {
+                Ok(used) => used,
+                Err(err) => {
+                    log::warn!(
+                        "Failed to check mint order of operation {operation_id}, sending it: {err:?}"
+                    );
+                    false
+                }
+            };
+            if used {
+                log::info!(
+                    "Mint order of operation {operation_id} is minted already, skipping the transaction"
+                );
+                return Ok(());
+            }
+        }
+
         let mut tx = bft_bridge_api::mint_transaction(
             sender.0,
             bridge_contract.0,
             evm_params.nonce.into(),
-            evm_params.gas_price.clone().into(),
+            state
+                .borrow()
+                .gas_price
+                .gas_price(evm_params.gas_price.clone(), state.borrow().clock().now())
+                .into(),
+            state.borrow().gas_limits.gas_limit(GasOperation::Mint),
             &signed_mint_order.0,
             evm_params.chain_id as _,
         );
@@ -592,6 +730,14 @@ impl BridgeTask {
         let tx_id = client
             .send_raw_transaction(tx)
             .await
+            .map_err(|err| {
+                let err = format!("{err:?}");
+                state
+                    .borrow_mut()
+                    .gas_limits
+                    .on_transaction_error(GasOperation::Mint, &err);
+                err
+            })
             .into_scheduler_result()?;
 
         if is_despoit {
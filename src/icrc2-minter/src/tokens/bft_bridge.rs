@@ -8,9 +8,9 @@ use ethers_core::abi::Token;
 use ethers_core::types::TransactionRequest;
 use minter_contract_utils::bft_bridge_api::MINTER_CANISTER_ADDRESS;
 use minter_contract_utils::build_data::BFT_BRIDGE_SMART_CONTRACT_DEPLOYED_CODE;
+use minter_contract_utils::gas_limits::DEFAULT_TX_GAS_LIMIT;
 use minter_did::error::{Error, Result};
 
-use crate::constant::DEFAULT_TX_GAS_LIMIT;
 use crate::state::State;
 
 /// This structure contains data of a valid burn operation.
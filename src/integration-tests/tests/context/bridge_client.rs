@@ -86,6 +86,11 @@ pub trait BridgeCanisterClient<C: CanisterClient> {
             .await
     }
 
+    /// Returns the build data of the canister.
+    async fn get_build_data(&self) -> CanisterClientResult<BuildData> {
+        self.client().query("get_build_data", ()).await
+    }
+
     /// Returns the build data of the canister.
     async fn get_canister_build_data(&self) -> CanisterClientResult<BuildData> {
         self.client().query("get_canister_build_data", ()).await
@@ -25,10 +25,11 @@ use ic_exports::icrc_types::icrc2::approve::ApproveArgs;
 use ic_log::LogSettings;
 use icrc2_minter::SigningStrategy;
 use icrc_client::IcrcCanisterClient;
+use minter_contract_utils::account_mapping::eth_address_to_subaccount;
 use minter_contract_utils::build_data::{
     BFT_BRIDGE_SMART_CONTRACT_CODE, FEE_CHARGE_SMART_CONTRACT_CODE, UUPS_PROXY_SMART_CONTRACT_CODE,
 };
-use minter_contract_utils::evm_link::{address_to_icrc_subaccount, EvmLink};
+use minter_contract_utils::evm_link::EvmLink;
 use minter_contract_utils::fee_charge_api::{NATIVE_TOKEN_BALANCE, NATIVE_TOKEN_DEPOSIT};
 use minter_contract_utils::{bft_bridge_api, fee_charge_api, wrapped_token_api};
 use minter_did::error::Result as McResult;
@@ -259,8 +260,8 @@ pub trait TestContext {
 
         let raw_client = self.client(self.canisters().icrc2_minter(), self.admin_name());
         raw_client
-            .update("set_bft_bridge_contract", (bridge_address.clone(),))
-            .await?;
+            .update::<_, McResult<()>>("set_bft_bridge_contract", (bridge_address.clone(),))
+            .await??;
 
         Ok(bridge_address)
     }
@@ -619,7 +620,7 @@ pub trait TestContext {
     async fn approve_icrc2_burn(&self, caller: &str, recipient: &H160, amount: u128) -> Result<()> {
         let client = self.icrc_token_1_client(caller);
 
-        let subaccount = Some(address_to_icrc_subaccount(&recipient.0));
+        let subaccount = Some(eth_address_to_subaccount(recipient));
         let minter_canister = Account {
             owner: self.canisters().icrc2_minter(),
             subaccount,
@@ -890,6 +891,7 @@ pub trait TestContext {
                         in_memory_records: None,
                         log_filter: Some("trace".to_string()),
                     }),
+                    mint_order_ttl_secs: None,
                 };
                 self.install_canister(self.canisters().ck_erc20_minter(), wasm, (init_data,))
                     .await
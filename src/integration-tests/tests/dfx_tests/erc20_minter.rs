@@ -2,11 +2,10 @@ use std::time::Duration;
 
 use did::{H160, H256, U64};
 use eth_signer::{Signer, Wallet};
-use ethers_core::abi::{Constructor, Param, ParamType, Token};
 use ethers_core::k256::ecdsa::SigningKey;
 use evm_canister_client::EvmCanisterClient;
+use evm_test_contracts::watermelon_token::WatermelonToken;
 use ic_canister_client::CanisterClient as _;
-use minter_contract_utils::build_data::test_contracts::TEST_WTM_HEX_CODE;
 use minter_contract_utils::evm_bridge::BridgeSide;
 use minter_did::id256::Id256;
 
@@ -120,17 +119,7 @@ impl ContextWithBridges {
         assert_eq!(expected_fee_charge_address, fee_charge_address.0);
 
         // Deploy ERC-20 token on external EVM.
-        let data: Constructor = Constructor {
-            inputs: vec![Param {
-                name: "initialSupply".into(),
-                kind: ParamType::Uint(256),
-                internal_type: None,
-            }],
-        };
-
-        let data = data
-            .encode_input(TEST_WTM_HEX_CODE.clone(), &[Token::Uint(u64::MAX.into())])
-            .unwrap();
+        let data = WatermelonToken::deploy_data(u64::MAX.into());
 
         let nonce = base_evm_client
             .account_basic(bob_address.clone())
@@ -12,9 +12,9 @@ use eth_signer::{Signer, Wallet};
 use ethers_core::abi::Token;
 use ethers_core::k256::ecdsa::SigningKey;
 use ic_canister_client::CanisterClient;
-use ic_exports::ic_cdk::api::management_canister::bitcoin::BitcoinNetwork;
 use ic_log::LogSettings;
 use minter_contract_utils::bft_bridge_api;
+use minter_contract_utils::btc_network::BtcNetwork;
 use minter_contract_utils::evm_link::EvmLink;
 use minter_contract_utils::operation_store::MinterOperationId;
 use rune_bridge::core::deposit::DepositRequestStatus;
@@ -80,7 +80,7 @@ impl RunesContext {
 
         let bridge = context.canisters().rune_bridge();
         let init_args = RuneBridgeConfig {
-            network: BitcoinNetwork::Regtest,
+            network: BtcNetwork::Regtest,
             evm_link: EvmLink::Ic(context.canisters().evm()),
             signing_strategy: SigningStrategy::ManagementCanister {
                 key_id: SigningKeyId::Dfx,
@@ -92,9 +92,18 @@ impl RunesContext {
                 log_filter: Some("trace".to_string()),
             },
             min_confirmations: 1,
+            confirmation_tiers: vec![],
             indexer_url: "https://localhost:8001".to_string(),
             deposit_fee: 500_000,
             mempool_timeout: Duration::from_secs(60),
+            withdrawal_postage: 10_000,
+            wrapped_token_decimals: None,
+            screening: Default::default(),
+            refund_threshold: 10_000,
+            dst_chain_ids: vec![],
+            coin_selection: Default::default(),
+            deposit_expiry: Duration::from_secs(7 * 24 * 60 * 60),
+            indexer_max_lag: 1,
         };
         context
             .install_canister(
@@ -104,11 +113,12 @@ impl RunesContext {
             )
             .await
             .unwrap();
-        let _: () = context
+        let result: minter_did::error::Result<()> = context
             .client(bridge, ADMIN)
             .update("admin_configure_ecdsa", ())
             .await
             .unwrap();
+        result.expect("failed to configure ecdsa");
 
         let wallet = context.new_wallet(u128::MAX).await.unwrap();
 
@@ -152,11 +162,12 @@ impl RunesContext {
             decimals: 0,
         };
 
-        let _: () = context
+        let result: minter_did::error::Result<()> = context
             .client(bridge, ADMIN)
             .update("admin_configure_bft_bridge", (bft_config,))
             .await
             .unwrap();
+        result.expect("failed to configure bft bridge");
 
         context.advance_time(Duration::from_secs(2)).await;
 
@@ -298,6 +309,9 @@ impl RunesContext {
         let data = RuneDepositRequestData {
             dst_address: eth_address.clone(),
             amounts: None,
+            refund_address: None,
+            dst_chain_id: None,
+            funding_txids: None,
         };
         let input = bft_bridge_api::NOTIFY_MINTER
             .encode_input(&[
@@ -345,7 +359,7 @@ impl RunesContext {
 
             if !response.is_empty() {
                 if let OperationState::Deposit(payload) = &response[0].1 {
-                    if let DepositRequestStatus::Minted { amounts } = &payload.status {
+                    if let DepositRequestStatus::Minted { amounts, .. } = &payload.status {
                         eprintln!("Deposit successful with amounts: {amounts:?}");
 
                         return Ok(amounts.clone());
@@ -3,11 +3,11 @@ use std::time::Duration;
 use did::{H160, U256, U64};
 use erc20_minter::operation::OperationStatus;
 use eth_signer::{Signer, Wallet};
-use ethers_core::abi::{Constructor, Param, ParamType, Token};
+use ethers_core::abi::Token;
 use ethers_core::k256::ecdsa::SigningKey;
 use evm_canister_client::EvmCanisterClient;
+use evm_test_contracts::watermelon_token::WatermelonToken;
 use minter_contract_utils::bft_bridge_api;
-use minter_contract_utils::build_data::test_contracts::TEST_WTM_HEX_CODE;
 use minter_contract_utils::build_data::{
     BFT_BRIDGE_SMART_CONTRACT_CODE, UUPS_PROXY_SMART_CONTRACT_CODE,
 };
@@ -130,17 +130,7 @@ impl ContextWithBridges {
         assert_eq!(expected_fee_charge_address, fee_charge_address.0);
 
         // Deploy ERC-20 token on external EVM.
-        let data: Constructor = Constructor {
-            inputs: vec![Param {
-                name: "initialSupply".into(),
-                kind: ParamType::Uint(256),
-                internal_type: None,
-            }],
-        };
-
-        let data = data
-            .encode_input(TEST_WTM_HEX_CODE.clone(), &[Token::Uint(u64::MAX.into())])
-            .unwrap();
+        let data = WatermelonToken::deploy_data(u64::MAX.into());
 
         let nonce = base_evm_client
             .account_basic(bob_address.clone())
@@ -280,6 +270,76 @@ async fn test_external_bridging() {
     assert!(signed_order.is_none());
 }
 
+// Stop the wrapped EVM before the erc20-minter sends the mint transaction, so the transaction
+// fails to be sent, and check that the transaction is resent once the EVM is available again.
+#[tokio::test]
+async fn mint_transaction_is_resent_after_evm_outage() {
+    let ctx = ContextWithBridges::new().await;
+    let alice_wallet = ctx.context.new_wallet(u128::MAX).await.unwrap();
+    let alice_address: H160 = alice_wallet.address().into();
+    let alice_id = Id256::from_evm_address(&alice_address, CHAIN_ID as _);
+    let amount = 1000_u128;
+
+    let wrapped_evm_client = ctx.context.evm_client(ADMIN);
+    let bob_id = Id256::from_evm_address(&ctx.bob_address, CHAIN_ID as _);
+    ctx.context
+        .native_token_deposit(
+            &wrapped_evm_client,
+            ctx.fee_charge_address.clone(),
+            &ctx.bob_wallet,
+            &[bob_id],
+            10_u64.pow(15).into(),
+        )
+        .await
+        .unwrap();
+
+    let wrapped_evm = ctx.context.canisters().evm();
+    ctx.context
+        .client
+        .stop_canister(wrapped_evm, Some(PocketIcTestContext::admin()))
+        .await
+        .unwrap();
+
+    let base_evm_client = EvmCanisterClient::new(
+        ctx.context
+            .client(ctx.context.canisters().external_evm(), ADMIN),
+    );
+    ctx.context
+        .burn_erc_20_tokens(
+            &base_evm_client,
+            &ctx.bob_wallet,
+            &ctx.base_token_address,
+            alice_id,
+            &ctx.base_bft_bridge,
+            amount,
+        )
+        .await
+        .unwrap();
+
+    // The mint order is signed, but the mint transaction can't be sent.
+    ctx.context
+        .advance_by_times(Duration::from_secs(2), 8)
+        .await;
+
+    ctx.context
+        .client
+        .start_canister(wrapped_evm, Some(PocketIcTestContext::admin()))
+        .await
+        .unwrap();
+
+    // Wait for the retries of the mint transaction sending.
+    ctx.context
+        .advance_by_times(Duration::from_secs(2), 40)
+        .await;
+
+    let balance = ctx
+        .context
+        .check_erc20_balance(&ctx.wrapped_token_address, &alice_wallet, None)
+        .await
+        .unwrap();
+    assert_eq!(amount, balance);
+}
+
 #[tokio::test]
 async fn native_token_deposit_increase_and_decrease() {
     let ctx = ContextWithBridges::new().await;
@@ -608,8 +668,12 @@ async fn create_bft_bridge(
         .unwrap();
 
     minter_client
-        .update::<_, ()>("set_bft_bridge_contract", (proxy_address.clone(), side))
+        .update::<_, minter_did::error::Result<()>>(
+            "set_bft_bridge_contract",
+            (proxy_address.clone(), side),
+        )
         .await
+        .unwrap()
         .unwrap();
 
     proxy_address
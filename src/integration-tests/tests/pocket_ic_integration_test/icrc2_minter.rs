@@ -290,6 +290,9 @@ async fn test_canister_build_data() {
     let minter_client = ctx.icrc_minter_client(ALICE);
     let build_data = minter_client.get_canister_build_data().await.unwrap();
     assert!(build_data.pkg_name.contains("icrc2-minter"));
+
+    let build_data = minter_client.get_build_data().await.unwrap();
+    assert!(build_data.pkg_name.contains("icrc2-minter"));
 }
 
 #[tokio::test]
@@ -9,12 +9,11 @@ use std::time::Duration;
 use bitcoin::hashes::Hash;
 use bitcoin::secp256k1::Secp256k1;
 use bitcoin::{Address as BtcAddress, Network as BtcNetwork, PublicKey};
-use btc_bridge::canister::eth_address_to_subaccount;
 use btc_bridge::ck_btc_interface::PendingUtxo;
 use btc_bridge::interface::{Erc20MintError, Erc20MintStatus};
 use btc_bridge::state::{BftBridgeConfig, BtcBridgeConfig};
 use candid::{Decode, Encode, Nat, Principal};
-use did::H160;
+use did::{H160, U64};
 use eth_signer::sign_strategy::SigningStrategy;
 use eth_signer::{Signer, Wallet};
 use ethers_core::k256::ecdsa::SigningKey;
@@ -37,18 +36,21 @@ use ic_ckbtc_minter::updates::retrieve_btc::{
 };
 use ic_ckbtc_minter::updates::update_balance::{UpdateBalanceArgs, UpdateBalanceError, UtxoStatus};
 use ic_ckbtc_minter::{Log, MinterInfo, CKBTC_LEDGER_MEMO_SIZE, MIN_RELAY_FEE_PER_VBYTE};
-use ic_exports::ic_cdk::api::management_canister::bitcoin::BitcoinNetwork;
 use ic_exports::icrc_types::icrc1::account::Account;
 use ic_exports::icrc_types::icrc1::transfer::{TransferArg, TransferError};
 use ic_exports::icrc_types::icrc2::approve::{ApproveArgs, ApproveError};
+use ic_exports::icrc_types::icrc2::transfer_from::TransferFromError;
 use ic_exports::icrc_types::icrc3::transactions::{
     GetTransactionsRequest, GetTransactionsResponse,
 };
 use ic_icrc1_ledger::{InitArgsBuilder as LedgerInitArgsBuilder, LedgerArgument};
 use ic_log::LogSettings;
 use ic_state_machine_tests::{Cycles, StateMachine, StateMachineBuilder, WasmResult};
+use minter_contract_utils::account_mapping::eth_address_to_subaccount;
+use minter_contract_utils::btc_network::BtcNetwork;
 use minter_contract_utils::evm_link::EvmLink;
 use minter_did::id256::Id256;
+use minter_did::order::SignedMintOrder;
 
 use crate::context::{CanisterType, TestContext};
 use crate::state_machine_tests::StateMachineContext;
@@ -208,6 +210,16 @@ impl CkBtcSetup {}
 
 impl CkBtcSetup {
     pub async fn new() -> Self {
+        Self::init(true).await
+    }
+
+    /// Setup where the EVM address of the bridge has no native tokens, so the bridge cannot
+    /// send the mint orders it signs and they wait for the users to claim them.
+    pub async fn with_unfunded_bridge() -> Self {
+        Self::init(false).await
+    }
+
+    async fn init(fund_bridge: bool) -> Self {
         let bitcoin_id = mainnet_bitcoin_canister_id();
         let caller = PrincipalId::new_user_test_id(1);
 
@@ -320,7 +332,7 @@ impl CkBtcSetup {
         let config = BtcBridgeConfig {
             ck_btc_minter: minter_id.into(),
             ck_btc_ledger: ledger_id.into(),
-            network: BitcoinNetwork::Mainnet,
+            network: BtcNetwork::Mainnet,
             evm_link: EvmLink::Ic((&context).canisters().evm()),
             signing_strategy: SigningStrategy::Local {
                 private_key: [2; 32],
@@ -332,6 +344,7 @@ impl CkBtcSetup {
                 in_memory_records: None,
                 log_filter: Some("trace".to_string()),
             },
+            confirmation_tiers: vec![],
         };
 
         let btc_bridge = (&context).create_canister().await.unwrap();
@@ -351,12 +364,14 @@ impl CkBtcSetup {
             .await
             .unwrap();
 
-        let client = (&context).evm_client("admin");
-        client
-            .mint_native_tokens(btc_bridge_eth_address.clone().unwrap(), u64::MAX.into())
-            .await
-            .unwrap()
-            .unwrap();
+        if fund_bridge {
+            let client = (&context).evm_client("admin");
+            client
+                .mint_native_tokens(btc_bridge_eth_address.clone().unwrap(), u64::MAX.into())
+                .await
+                .unwrap()
+                .unwrap();
+        }
 
         let bft_bridge = (&context)
             .initialize_bft_bridge_with_minter(&wallet, btc_bridge_eth_address.unwrap(), None, true)
@@ -384,11 +399,12 @@ impl CkBtcSetup {
             decimals: 0,
         };
 
-        let _: () = (&context)
+        let result: minter_did::error::Result<()> = (&context)
             .client(btc_bridge, "admin")
             .update("admin_configure_bft_bridge", (bft_config,))
             .await
             .unwrap();
+        result.expect("failed to configure bft bridge");
 
         (&context).advance_time(Duration::from_secs(2)).await;
 
@@ -467,7 +483,7 @@ impl CkBtcSetup {
                         self.caller,
                         CanisterId::try_from(PrincipalId(self.context.canisters.btc_bridge()))
                             .unwrap(),
-                        "get_btc_address",
+                        "get_btc_address_checked",
                         Encode!(&GetBtcAddressArgs {
                             owner: Some(account.owner),
                             subaccount: account.subaccount,
@@ -476,9 +492,10 @@ impl CkBtcSetup {
                     )
                     .expect("failed to get btc address")
             ),
-            String
+            Result<String, Erc20MintError>
         )
         .unwrap()
+        .expect("failed to get btc address from the bridge")
     }
 
     pub fn get_btc_address(&self, account: impl Into<Account>) -> String {
@@ -1001,6 +1018,87 @@ impl CkBtcSetup {
         .expect("failed to decode btc_to_erc20 result")
     }
 
+    pub fn approve_bridge(&self, from: PrincipalId, amount: u64) -> Nat {
+        Decode!(&assert_reply(self.env().execute_ingress_as(
+            from,
+            self.ledger_id,
+            "icrc2_approve",
+            Encode!(&ApproveArgs {
+                from_subaccount: None,
+                spender: Account {
+                    owner: self.context.canisters.btc_bridge(),
+                    subaccount: None
+                },
+                amount: Nat::from(amount),
+                expected_allowance: None,
+                expires_at: None,
+                fee: None,
+                memo: None,
+                created_at_time: None,
+            }).unwrap()
+            ).expect("failed to execute approve")),
+            Result<Nat, ApproveError>
+        )
+        .unwrap()
+        .expect("approve failed")
+    }
+
+    pub fn deposit_ckbtc(
+        &self,
+        from: PrincipalId,
+        amount: u64,
+        eth_address: &H160,
+    ) -> Result<Erc20MintStatus, Erc20MintError> {
+        let result = self
+            .env()
+            .execute_ingress_as(
+                from,
+                CanisterId::try_from(PrincipalId(self.context.canisters.btc_bridge())).unwrap(),
+                "deposit_ckbtc",
+                Encode!(&amount, eth_address).unwrap(),
+            )
+            .expect("deposit_ckbtc call failed");
+
+        Decode!(&result.bytes(), Result<Erc20MintStatus, Erc20MintError>)
+            .expect("failed to decode deposit_ckbtc result")
+    }
+
+    pub async fn list_mint_orders(&self, sender: Id256) -> Vec<(u32, SignedMintOrder)> {
+        (&self.context)
+            .client(self.context.canisters.btc_bridge(), "admin")
+            .query("list_mint_orders", (sender,))
+            .await
+            .expect("list_mint_orders call failed")
+    }
+
+    pub async fn get_mint_order(&self, sender: Id256, nonce: u32) -> Option<SignedMintOrder> {
+        (&self.context)
+            .client(self.context.canisters.btc_bridge(), "admin")
+            .query("get_mint_order", (sender, nonce))
+            .await
+            .expect("get_mint_order call failed")
+    }
+
+    pub async fn bridge_evm_address(&self) -> H160 {
+        let address: Option<H160> = (&self.context)
+            .client(self.context.canisters.btc_bridge(), "admin")
+            .update("get_evm_address", ())
+            .await
+            .expect("get_evm_address call failed");
+        address.expect("bridge has no EVM address")
+    }
+
+    pub async fn upgrade_bridge(&self) {
+        (&self.context)
+            .upgrade_canister(
+                self.context.canisters.btc_bridge(),
+                get_btc_bridge_canister_bytecode().await,
+                (),
+            )
+            .await
+            .expect("failed to upgrade the bridge");
+    }
+
     pub fn advance_blocks(&self, blocks_count: usize) {
         for _ in 0..blocks_count {
             self.advance_tip_height(1);
@@ -1035,7 +1133,7 @@ impl CkBtcSetup {
 
         let deposit_account = Account {
             owner: self.context.canisters.btc_bridge(),
-            subaccount: Some(eth_address_to_subaccount(&caller_eth_address).0),
+            subaccount: Some(eth_address_to_subaccount(&caller_eth_address)),
         };
         let deposit_address = self.get_btc_address(deposit_account);
         self.push_utxo(deposit_address, utxo.clone());
@@ -1187,7 +1285,7 @@ async fn btc_to_erc20_test() {
 
     let deposit_account = Account {
         owner: ckbtc.context.canisters.btc_bridge(),
-        subaccount: Some(eth_address_to_subaccount(&caller_eth_address).0),
+        subaccount: Some(eth_address_to_subaccount(&caller_eth_address)),
     };
 
     let deposit_address = ckbtc.get_btc_address(deposit_account);
@@ -1325,7 +1423,7 @@ async fn test_get_btc_address_from_bridge() {
 
     let deposit_account = Account {
         owner: ckbtc.context.canisters.btc_bridge(),
-        subaccount: Some(eth_address_to_subaccount(&caller_eth_address).0),
+        subaccount: Some(eth_address_to_subaccount(&caller_eth_address)),
     };
     let deposit_address = ckbtc.get_btc_address(deposit_account);
 
@@ -1335,3 +1433,162 @@ async fn test_get_btc_address_from_bridge() {
 
     ckbtc.async_drop().await;
 }
+
+#[tokio::test]
+async fn deposit_ckbtc_test() {
+    let ckbtc = CkBtcSetup::new().await;
+    ckbtc.set_tip_height(24);
+
+    let deposit_value = 100_000_000;
+    let utxo = Utxo {
+        height: 12,
+        outpoint: OutPoint {
+            txid: range_to_txid(1..=32).into(),
+            vout: 1,
+        },
+        value: deposit_value,
+    };
+    ckbtc.deposit_utxo(Principal::from(ckbtc.caller), utxo);
+    let ckbtc_balance = deposit_value - ckbtc.kyt_fee();
+
+    let wallet = (&ckbtc.context)
+        .new_wallet(u128::MAX)
+        .await
+        .expect("Failed to create a wallet");
+    let eth_address: H160 = wallet.address().0.into();
+
+    let amount = 1_000_000;
+    assert_eq!(
+        ckbtc.deposit_ckbtc(ckbtc.caller, amount, &eth_address),
+        Err(Erc20MintError::CkBtcLedgerTransferFrom(
+            TransferFromError::InsufficientAllowance {
+                allowance: 0u64.into()
+            }
+        ))
+    );
+
+    ckbtc.approve_bridge(ckbtc.caller, amount);
+    let result = ckbtc.deposit_ckbtc(ckbtc.caller, amount, &eth_address);
+    let Ok(Erc20MintStatus::Minted {
+        amount: minted,
+        tx_id,
+    }) = result
+    else {
+        panic!("failed to deposit ckBTC: {result:?}");
+    };
+    assert_eq!(minted, amount - CKBTC_LEDGER_FEE);
+
+    (&ckbtc.context).advance_time(Duration::from_secs(2)).await;
+    (&ckbtc.context)
+        .wait_transaction_receipt(&tx_id)
+        .await
+        .unwrap();
+
+    let balance = (&ckbtc.context)
+        .check_erc20_balance(&ckbtc.wrapped_token, &wallet, None)
+        .await
+        .unwrap();
+    assert_eq!(balance, minted as u128);
+
+    let user_balance = ckbtc.balance_of(Principal::from(ckbtc.caller)).await;
+    assert_eq!(
+        user_balance,
+        Nat::from(ckbtc_balance - amount - TRANSFER_FEE)
+    );
+
+    let bridge_balance = ckbtc
+        .balance_of(Account {
+            owner: ckbtc.context.canisters.btc_bridge(),
+            subaccount: None,
+        })
+        .await;
+    assert_eq!(bridge_balance, Nat::from(minted));
+
+    ckbtc.async_drop().await;
+}
+
+#[tokio::test]
+async fn mint_orders_survive_upgrade_and_signer_rotation() {
+    let ckbtc = CkBtcSetup::with_unfunded_bridge().await;
+    ckbtc.set_tip_height(24);
+
+    let utxo = Utxo {
+        height: 12,
+        outpoint: OutPoint {
+            txid: range_to_txid(1..=32).into(),
+            vout: 1,
+        },
+        value: 100_000_000,
+    };
+    ckbtc.deposit_utxo(Principal::from(ckbtc.caller), utxo);
+
+    let wallet = (&ckbtc.context)
+        .new_wallet(u128::MAX)
+        .await
+        .expect("Failed to create a wallet");
+    let eth_address: H160 = wallet.address().0.into();
+
+    let amount = 1_000_000;
+    ckbtc.approve_bridge(ckbtc.caller, 2 * amount);
+    for _ in 0..2 {
+        let result = ckbtc.deposit_ckbtc(ckbtc.caller, amount, &eth_address);
+        assert!(
+            matches!(result, Ok(Erc20MintStatus::Signed(_))),
+            "mint order is not left to the user: {result:?}"
+        );
+    }
+
+    let sender = Id256::from(&Principal::from(ckbtc.caller));
+    let orders = ckbtc.list_mint_orders(sender).await;
+    assert_eq!(orders.len(), 2);
+
+    ckbtc.upgrade_bridge().await;
+    let after_upgrade = ckbtc.list_mint_orders(sender).await;
+    assert_eq!(nonces_and_bytes(&after_upgrade), nonces_and_bytes(&orders));
+
+    let old_address = ckbtc.bridge_evm_address().await;
+    let result: minter_did::error::Result<()> = (&ckbtc.context)
+        .client(ckbtc.context.canisters.btc_bridge(), "admin")
+        .update(
+            "admin_set_signing_strategy",
+            (SigningStrategy::Local {
+                private_key: [3; 32],
+            },),
+        )
+        .await
+        .unwrap();
+    result.expect("failed to rotate the signing strategy");
+    assert_ne!(ckbtc.bridge_evm_address().await, old_address);
+
+    let after_rotation = ckbtc.list_mint_orders(sender).await;
+    assert_eq!(nonces_and_bytes(&after_rotation), nonces_and_bytes(&orders));
+
+    for (nonce, order) in orders {
+        let stored = ckbtc
+            .get_mint_order(sender, nonce)
+            .await
+            .expect("mint order is lost");
+        assert_eq!(stored.0, order.0);
+
+        let receipt = (&ckbtc.context)
+            .mint_erc_20_with_order(&wallet, &ckbtc.bft_bridge, stored)
+            .await
+            .expect("failed to claim the mint order");
+        assert_eq!(receipt.status, Some(U64::one()));
+    }
+
+    let balance = (&ckbtc.context)
+        .check_erc20_balance(&ckbtc.wrapped_token, &wallet, None)
+        .await
+        .unwrap();
+    assert_eq!(balance, 2 * (amount - CKBTC_LEDGER_FEE) as u128);
+
+    ckbtc.async_drop().await;
+}
+
+fn nonces_and_bytes(orders: &[(u32, SignedMintOrder)]) -> Vec<(u32, Vec<u8>)> {
+    orders
+        .iter()
+        .map(|(nonce, order)| (*nonce, order.0.to_vec()))
+        .collect()
+}
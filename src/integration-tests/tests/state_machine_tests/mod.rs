@@ -126,11 +126,23 @@ impl<'a> TestContext for &'a StateMachineContext {
 
     async fn upgrade_canister(
         &self,
-        _canister: Principal,
-        _wasm: Vec<u8>,
-        _args: impl ArgumentEncoder + Send,
+        canister: Principal,
+        wasm: Vec<u8>,
+        args: impl ArgumentEncoder + Send,
     ) -> crate::utils::error::Result<()> {
-        todo!()
+        let env = self.env.clone();
+        let data = encode_args(args).unwrap();
+
+        tokio::task::spawn_blocking(move || {
+            env.upgrade_canister(
+                CanisterId::try_from(PrincipalId(canister)).unwrap(),
+                wasm,
+                data,
+            )
+            .map_err(|err| TestError::Generic(format!("{err:?}")))
+        })
+        .await
+        .unwrap()
     }
 
     fn icrc_token_initial_balances(&self) -> Vec<(Account, Nat)> {
@@ -4,17 +4,24 @@ use candid::Principal;
 use did::H160;
 use eth_signer::sign_strategy::{SigningKeyId, SigningStrategy};
 use ic_canister_client::CanisterClient;
-use ic_exports::ic_cdk::api::management_canister::bitcoin::BitcoinNetwork;
 use ic_management_canister_types::{EcdsaCurve, EcdsaKeyId};
 use ic_state_machine_tests::StateMachineBuilder;
+use minter_contract_utils::btc_network::BtcNetwork;
+use minter_contract_utils::canister_status::CanisterStatusInfo;
+use minter_contract_utils::evm_link::EvmLink;
+use minter_contract_utils::health::{DependencyStatus, HealthReport, HEALTH_CHECK_INTERVAL};
 use rune_bridge::interface::GetAddressError;
 use rune_bridge::state::RuneBridgeConfig;
 
 use crate::context::TestContext;
 use crate::state_machine_tests::StateMachineContext;
-use crate::utils::wasm::get_rune_bridge_canister_bytecode;
+use crate::utils::mock_indexer::{MockIndexer, MockResponse};
+use crate::utils::wasm::{
+    get_previous_rune_bridge_canister_bytecode, get_rune_bridge_canister_bytecode,
+};
 
 const KEY_ID: &str = "test_key";
+const INDEXER_URL: &str = "https://indexer";
 
 struct RunesSetup {
     ctx: StateMachineContext,
@@ -30,6 +37,10 @@ fn key_id() -> EcdsaKeyId {
 
 impl RunesSetup {
     async fn init() -> RunesSetup {
+        Self::init_with_wasm(get_rune_bridge_canister_bytecode().await).await
+    }
+
+    async fn init_with_wasm(wasm: Vec<u8>) -> RunesSetup {
         let context = tokio::task::spawn_blocking(move || {
             StateMachineContext::new(StateMachineBuilder::new().with_ecdsa_key(key_id()).build())
         })
@@ -38,31 +49,38 @@ impl RunesSetup {
 
         let bridge = (&context).create_canister().await.unwrap();
         let init_args = RuneBridgeConfig {
-            network: BitcoinNetwork::Mainnet,
-            evm_link: Default::default(),
+            network: BtcNetwork::Mainnet,
+            // The tests don't use the EVM, but the link must point to a canister.
+            evm_link: EvmLink::Ic(bridge),
             signing_strategy: SigningStrategy::ManagementCanister {
                 key_id: SigningKeyId::Custom(KEY_ID.to_string()),
             },
             admin: (&context).admin(),
             log_settings: Default::default(),
             min_confirmations: 1,
-            indexer_url: "https://indexer".to_string(),
+            confirmation_tiers: vec![],
+            indexer_url: INDEXER_URL.to_string(),
             deposit_fee: 0,
             mempool_timeout: Duration::from_secs(60),
+            withdrawal_postage: 10_000,
+            wrapped_token_decimals: None,
+            screening: Default::default(),
+            refund_threshold: 10_000,
+            dst_chain_ids: vec![],
+            coin_selection: Default::default(),
+            deposit_expiry: Duration::from_secs(7 * 24 * 60 * 60),
+            indexer_max_lag: 1,
         };
         (&context)
-            .install_canister(
-                bridge,
-                get_rune_bridge_canister_bytecode().await,
-                (init_args,),
-            )
+            .install_canister(bridge, wasm, (init_args,))
             .await
             .unwrap();
-        let _: () = (&context)
+        let result: minter_did::error::Result<()> = (&context)
             .client(bridge, "admin")
             .update("admin_configure_ecdsa", ())
             .await
             .unwrap();
+        result.expect("failed to configure ecdsa");
 
         RunesSetup {
             ctx: context,
@@ -82,6 +100,70 @@ impl RunesSetup {
             .expect("failed to get deposit address")
     }
 
+    fn admin_client(&self) -> impl CanisterClient {
+        (&self.ctx).client(self.rune_bridge, "admin")
+    }
+
+    async fn address_index(&self, eth_address: &H160) -> Option<u32> {
+        self.rune_client()
+            .query("get_address_index", (eth_address,))
+            .await
+            .expect("failed to get address index")
+    }
+
+    async fn set_screening_override(&self, subject: &str, allowed: bool) {
+        let result: minter_did::error::Result<()> = self
+            .admin_client()
+            .update("admin_set_screening_override", (subject, allowed))
+            .await
+            .expect("failed to send screening override request");
+        result.expect("failed to set screening override");
+    }
+
+    async fn screening_overrides(&self) -> Vec<(String, bool)> {
+        self.rune_client()
+            .query("get_screening_overrides", ())
+            .await
+            .expect("failed to get screening overrides")
+    }
+
+    async fn status_info(&self) -> CanisterStatusInfo {
+        self.rune_client()
+            .query("canister_status_info", ())
+            .await
+            .expect("failed to get canister status info")
+    }
+
+    async fn health(&self) -> HealthReport {
+        self.rune_client()
+            .query("health", ())
+            .await
+            .expect("failed to get health report")
+    }
+
+    /// Runs the next periodic health check of the bridge against the `indexer`.
+    async fn check_health(&self, indexer: MockIndexer) -> MockIndexer {
+        let env = self.ctx.env.clone();
+        tokio::task::spawn_blocking(move || {
+            env.advance_time(HEALTH_CHECK_INTERVAL);
+            indexer.serve_until_idle(&env);
+            indexer
+        })
+        .await
+        .unwrap()
+    }
+
+    async fn upgrade_bridge(&self) {
+        (&self.ctx)
+            .upgrade_canister(
+                self.rune_bridge,
+                get_rune_bridge_canister_bytecode().await,
+                (),
+            )
+            .await
+            .expect("failed to upgrade the bridge");
+    }
+
     pub async fn async_drop(self) {
         let env = self.ctx.env;
         tokio::task::spawn_blocking(move || {
@@ -117,3 +199,102 @@ async fn generates_correct_deposit_address() {
 
     setup.async_drop().await;
 }
+
+/// Fills the stable structures with the wasm of the previous release, upgrades the bridge to the
+/// working tree wasm and checks the stored data is decoded as before. A change of the `Storable`
+/// layout of a stored type without a migration fails the test.
+#[tokio::test]
+async fn stable_stores_survive_upgrade_from_previous_release() {
+    let setup =
+        RunesSetup::init_with_wasm(get_previous_rune_bridge_canister_bytecode().await).await;
+
+    let eth_addresses: Vec<H160> = (1..=5u8).map(|i| H160::from_slice(&[i; 20])).collect();
+    let mut deposit_addresses = vec![];
+    let mut address_indices = vec![];
+    for eth_address in &eth_addresses {
+        deposit_addresses.push(setup.deposit_address(eth_address).await);
+        address_indices.push(setup.address_index(eth_address).await);
+    }
+    setup
+        .set_screening_override("0x0101010101010101010101010101010101010101", false)
+        .await;
+    setup.set_screening_override("deadbeef", true).await;
+
+    let overrides = setup.screening_overrides().await;
+    let status = setup.status_info().await;
+
+    setup.upgrade_bridge().await;
+
+    for (i, eth_address) in eth_addresses.iter().enumerate() {
+        assert_eq!(setup.address_index(eth_address).await, address_indices[i]);
+        assert_eq!(
+            setup.deposit_address(eth_address).await,
+            deposit_addresses[i]
+        );
+    }
+    assert_eq!(setup.screening_overrides().await, overrides);
+
+    let upgraded_status = setup.status_info().await;
+    for structure in &status.stable_structures {
+        let upgraded = upgraded_status
+            .stable_structures
+            .iter()
+            .find(|upgraded| upgraded.name == structure.name)
+            .unwrap_or_else(|| panic!("stable structure {} is lost", structure.name));
+        assert!(
+            upgraded.size_bytes >= structure.size_bytes,
+            "stable structure {} shrank on upgrade",
+            structure.name
+        );
+    }
+
+    // The upgraded bridge keeps assigning the addresses after the stored ones.
+    let new_address = H160::from_slice(&[6; 20]);
+    let new_deposit_address = setup.deposit_address(&new_address).await;
+    assert!(!deposit_addresses.contains(&new_deposit_address));
+    let last_index = address_indices.iter().flatten().max().copied();
+    let new_index = setup.address_index(&new_address).await;
+    assert!(
+        new_index > last_index,
+        "address index {new_index:?} is reused"
+    );
+
+    setup.set_screening_override("deadbeef", false).await;
+    assert!(setup
+        .screening_overrides()
+        .await
+        .contains(&("deadbeef".to_string(), false)));
+
+    setup.async_drop().await;
+}
+
+#[tokio::test]
+async fn indexer_health_is_checked_with_mock_indexer() {
+    let setup = RunesSetup::init().await;
+    let indexer_status = |report: HealthReport| {
+        report
+            .dependencies
+            .into_iter()
+            .find(|dependency| dependency.name == "indexer")
+            .expect("indexer is not reported")
+            .status
+    };
+
+    let indexer = MockIndexer::new(INDEXER_URL);
+    indexer.set_block_height(840_000);
+    let indexer = setup.check_health(indexer).await;
+    assert!(indexer.requests().contains(&"/blockheight".to_string()));
+    assert_eq!(
+        indexer_status(setup.health().await),
+        DependencyStatus::Healthy
+    );
+
+    indexer.set_route("/blockheight", MockResponse::error(503));
+    setup.check_health(indexer).await;
+    assert!(matches!(
+        indexer_status(setup.health().await),
+        DependencyStatus::Unhealthy(_)
+    ));
+
+    setup.async_drop().await;
+}
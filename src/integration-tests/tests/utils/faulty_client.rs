@@ -0,0 +1,302 @@
+//! Fault injection for the EVM JSON-RPC requests.
+//!
+//! [`FaultyClient`] wraps any JSON-RPC [`Client`] and can be configured to fail the next requests,
+//! delay the responses or return stale nonces, so the tests can check how the code talking to an
+//! EVM behaves when the EVM is unreliable.
+
+use std::collections::HashSet;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::anyhow;
+use ethereum_json_rpc_client::Client;
+use ethers_core::types::U256;
+use jsonrpc_core::{Call, Id, Output, Request, Response};
+
+const GET_TRANSACTION_COUNT_METHOD: &str = "eth_getTransactionCount";
+
+#[derive(Debug, Default)]
+struct Faults {
+    fail_requests: u32,
+    delay: Option<Duration>,
+    nonce_lag: u64,
+    received_requests: u32,
+    failed_requests: u32,
+}
+
+/// JSON-RPC client injecting faults into the requests sent through it.
+///
+/// The clones of the client share the faults configuration, so the faults can be changed while a
+/// clone is used by the code under test.
+#[derive(Clone)]
+pub struct FaultyClient<C> {
+    inner: C,
+    faults: Arc<Mutex<Faults>>,
+}
+
+impl<C: Client> FaultyClient<C> {
+    pub fn new(inner: C) -> Self {
+        Self {
+            inner,
+            faults: Arc::default(),
+        }
+    }
+
+    /// Fails the next `count` requests without sending them to the EVM.
+    pub fn fail_next_requests(&self, count: u32) {
+        self.faults.lock().unwrap().fail_requests = count;
+    }
+
+    /// Delays every request by `delay` before it is sent.
+    pub fn delay_requests(&self, delay: Duration) {
+        self.faults.lock().unwrap().delay = Some(delay);
+    }
+
+    /// Returns the nonces from `eth_getTransactionCount` decreased by `lag`.
+    pub fn return_stale_nonces(&self, lag: u64) {
+        self.faults.lock().unwrap().nonce_lag = lag;
+    }
+
+    /// Removes all the configured faults.
+    pub fn heal(&self) {
+        let mut faults = self.faults.lock().unwrap();
+        faults.fail_requests = 0;
+        faults.delay = None;
+        faults.nonce_lag = 0;
+    }
+
+    /// Number of requests received by the client, including the failed ones.
+    pub fn received_requests(&self) -> u32 {
+        self.faults.lock().unwrap().received_requests
+    }
+
+    /// Number of requests failed by the client.
+    pub fn failed_requests(&self) -> u32 {
+        self.faults.lock().unwrap().failed_requests
+    }
+}
+
+impl<C: Client + Send + Sync + 'static> Client for FaultyClient<C> {
+    fn send_rpc_request(
+        &self,
+        request: Request,
+    ) -> Pin<Box<dyn Future<Output = anyhow::Result<Response>> + Send>> {
+        let (fail, delay, nonce_lag) = {
+            let mut faults = self.faults.lock().unwrap();
+            faults.received_requests += 1;
+
+            let fail = faults.fail_requests > 0;
+            if fail {
+                faults.fail_requests -= 1;
+                faults.failed_requests += 1;
+            }
+
+            (fail, faults.delay, faults.nonce_lag)
+        };
+
+        let inner = self.inner.clone();
+        Box::pin(async move {
+            if let Some(delay) = delay {
+                tokio::time::sleep(delay).await;
+            }
+
+            if fail {
+                return Err(anyhow!("injected EVM RPC failure"));
+            }
+
+            let nonce_ids = nonce_request_ids(&request);
+            let response = inner.send_rpc_request(request).await?;
+            if nonce_lag == 0 || nonce_ids.is_empty() {
+                return Ok(response);
+            }
+
+            Ok(with_stale_nonces(response, &nonce_ids, nonce_lag))
+        })
+    }
+}
+
+/// Ids of the `eth_getTransactionCount` calls of the request.
+fn nonce_request_ids(request: &Request) -> HashSet<Id> {
+    let calls = match request {
+        Request::Single(call) => std::slice::from_ref(call),
+        Request::Batch(calls) => calls.as_slice(),
+    };
+
+    calls
+        .iter()
+        .filter_map(|call| match call {
+            Call::MethodCall(call) if call.method == GET_TRANSACTION_COUNT_METHOD => {
+                Some(call.id.clone())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn with_stale_nonces(response: Response, nonce_ids: &HashSet<Id>, lag: u64) -> Response {
+    let make_stale = |output: Output| match output {
+        Output::Success(mut success) if nonce_ids.contains(&success.id) => {
+            if let Ok(nonce) = serde_json::from_value::<U256>(success.result.clone()) {
+                let stale = nonce.saturating_sub(lag.into());
+                success.result = serde_json::to_value(stale).expect("should be able to convert");
+            }
+            Output::Success(success)
+        }
+        output => output,
+    };
+
+    match response {
+        Response::Single(output) => Response::Single(make_stale(output)),
+        Response::Batch(outputs) => Response::Batch(outputs.into_iter().map(make_stale).collect()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use did::U256 as DidU256;
+    use ethereum_json_rpc_client::EthJsonRpcClient;
+    use jsonrpc_core::{Success, Value, Version};
+    use minter_contract_utils::query::{self, Query, QueryType, GAS_PRICE_ID, NONCE_ID};
+
+    use super::*;
+
+    const NONCE: u64 = 42;
+    const GAS_PRICE: u64 = 10;
+
+    /// EVM stub answering the nonce and gas price requests.
+    #[derive(Clone)]
+    struct StubEvm;
+
+    impl StubEvm {
+        fn output(call: &Call) -> Output {
+            let Call::MethodCall(call) = call else {
+                panic!("unexpected call: {call:?}");
+            };
+
+            let result = match call.method.as_str() {
+                GET_TRANSACTION_COUNT_METHOD => U256::from(NONCE),
+                "eth_gasPrice" => U256::from(GAS_PRICE),
+                method => panic!("unexpected method: {method}"),
+            };
+
+            Output::Success(Success {
+                jsonrpc: Some(Version::V2),
+                result: serde_json::to_value(result).unwrap(),
+                id: call.id.clone(),
+            })
+        }
+    }
+
+    impl Client for StubEvm {
+        fn send_rpc_request(
+            &self,
+            request: Request,
+        ) -> Pin<Box<dyn Future<Output = anyhow::Result<Response>> + Send>> {
+            let response = match &request {
+                Request::Single(call) => Response::Single(Self::output(call)),
+                Request::Batch(calls) => Response::Batch(calls.iter().map(Self::output).collect()),
+            };
+
+            Box::pin(async move { Ok(response) })
+        }
+    }
+
+    async fn query_nonce(client: &EthJsonRpcClient<FaultyClient<StubEvm>>) -> anyhow::Result<u64> {
+        let responses = query::batch_query(
+            client,
+            &[
+                QueryType::Nonce {
+                    address: Default::default(),
+                },
+                QueryType::GasPrice,
+            ],
+        )
+        .await?;
+
+        let gas_price: DidU256 = responses.get_value_by_id(Id::Str(GAS_PRICE_ID.into()))?;
+        assert_eq!(gas_price, DidU256::from(GAS_PRICE));
+
+        let nonce: DidU256 = responses.get_value_by_id(Id::Str(NONCE_ID.into()))?;
+        Ok(nonce.0.as_u64())
+    }
+
+    #[tokio::test]
+    async fn failed_requests_are_recovered_after_heal() {
+        let faulty = FaultyClient::new(StubEvm);
+        let client = EthJsonRpcClient::new(faulty.clone());
+
+        faulty.fail_next_requests(2);
+        assert!(query_nonce(&client).await.is_err());
+        assert!(query_nonce(&client).await.is_err());
+        assert_eq!(query_nonce(&client).await.unwrap(), NONCE);
+
+        assert_eq!(faulty.received_requests(), 3);
+        assert_eq!(faulty.failed_requests(), 2);
+
+        faulty.fail_next_requests(5);
+        faulty.heal();
+        assert_eq!(query_nonce(&client).await.unwrap(), NONCE);
+    }
+
+    #[tokio::test]
+    async fn stale_nonces_are_reconciled_after_heal() {
+        let faulty = FaultyClient::new(StubEvm);
+        let client = EthJsonRpcClient::new(faulty.clone());
+
+        faulty.return_stale_nonces(3);
+        assert_eq!(query_nonce(&client).await.unwrap(), NONCE - 3);
+
+        faulty.return_stale_nonces(NONCE + 1);
+        assert_eq!(query_nonce(&client).await.unwrap(), 0);
+
+        faulty.heal();
+        assert_eq!(query_nonce(&client).await.unwrap(), NONCE);
+    }
+
+    #[tokio::test]
+    async fn requests_are_delayed() {
+        let faulty = FaultyClient::new(StubEvm);
+        let client = EthJsonRpcClient::new(faulty.clone());
+        let delay = Duration::from_millis(50);
+
+        faulty.delay_requests(delay);
+        let started = std::time::Instant::now();
+        assert_eq!(query_nonce(&client).await.unwrap(), NONCE);
+        assert!(started.elapsed() >= delay);
+    }
+
+    #[test]
+    fn only_nonce_outputs_are_changed() {
+        let nonce_ids = HashSet::from([Id::Num(1)]);
+        let output = |id| {
+            Output::Success(Success {
+                jsonrpc: Some(Version::V2),
+                result: Value::String("0xa".into()),
+                id,
+            })
+        };
+
+        let response = with_stale_nonces(
+            Response::Batch(vec![output(Id::Num(1)), output(Id::Num(2))]),
+            &nonce_ids,
+            4,
+        );
+        let Response::Batch(outputs) = response else {
+            panic!("expected batch response");
+        };
+
+        let results: Vec<Value> = outputs
+            .into_iter()
+            .map(|output| match output {
+                Output::Success(success) => success.result,
+                output => panic!("unexpected output: {output:?}"),
+            })
+            .collect();
+        assert_eq!(
+            results,
+            vec![Value::String("0x6".into()), Value::String("0xa".into())]
+        );
+    }
+}
@@ -0,0 +1,183 @@
+//! Programmable `ord` indexer for the state machine tests.
+//!
+//! The state machine doesn't make real HTTP outcalls: the requests of the canisters stay pending
+//! until the test answers them. [`MockIndexer`] answers the pending requests to its url with the
+//! responses programmed by the test, so the rune flows can be tested without an indexer and with
+//! the exact indexer state the test needs. The routes not programmed are answered with `404`.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use candid::Principal;
+use ic_base_types::{CanisterId, PrincipalId};
+use ic_management_canister_types::CanisterHttpResponsePayload;
+use ic_state_machine_tests::{PayloadBuilder, StateMachine, WasmResult};
+use serde_json::Value;
+
+/// Number of the rounds in a row without indexer requests after which a call is considered to
+/// make no more requests.
+const IDLE_ROUNDS: u32 = 10;
+
+const MAX_ROUNDS: u32 = 100;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockResponse {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+impl MockResponse {
+    pub fn json(value: &Value) -> Self {
+        Self {
+            status: 200,
+            body: serde_json::to_vec(value).expect("failed to serialize mock response"),
+        }
+    }
+
+    pub fn error(status: u16) -> Self {
+        Self {
+            status,
+            body: vec![],
+        }
+    }
+}
+
+/// Indexer answering the HTTP outcalls of the canisters under test.
+pub struct MockIndexer {
+    url: String,
+    /// Responses by the request path, e.g. `/runes`.
+    routes: Mutex<HashMap<String, MockResponse>>,
+    /// Paths of the answered requests, in the order they were answered.
+    requests: Mutex<Vec<String>>,
+}
+
+impl MockIndexer {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into().trim_end_matches('/').to_string(),
+            routes: Default::default(),
+            requests: Default::default(),
+        }
+    }
+
+    /// Url to configure in the canister under test.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Sets the response to the requests of the `path`.
+    pub fn set_route(&self, path: &str, response: MockResponse) {
+        self.routes
+            .lock()
+            .unwrap()
+            .insert(path.to_string(), response);
+    }
+
+    pub fn remove_route(&self, path: &str) {
+        self.routes.lock().unwrap().remove(path);
+    }
+
+    pub fn set_runes(&self, runes: Value) {
+        self.set_route("/runes", MockResponse::json(&runes));
+    }
+
+    pub fn set_rune(&self, rune_id: &str, rune: Value) {
+        self.set_route(&format!("/rune/{rune_id}"), MockResponse::json(&rune));
+    }
+
+    /// Sets the response for the output `txid:vout`.
+    pub fn set_output(&self, outpoint: &str, output: Value) {
+        self.set_route(&format!("/output/{outpoint}"), MockResponse::json(&output));
+    }
+
+    pub fn set_inscription(&self, inscription_id: &str, inscription: Value) {
+        self.set_route(
+            &format!("/inscription/{inscription_id}"),
+            MockResponse::json(&inscription),
+        );
+    }
+
+    pub fn set_block_height(&self, height: u64) {
+        self.set_route("/blockheight", MockResponse::json(&height.into()));
+    }
+
+    /// Paths of the requests answered so far.
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Answers the pending outcalls to the indexer and returns their number. The outcalls to
+    /// other urls are left pending.
+    pub fn serve(&self, env: &StateMachine) -> usize {
+        let mut payload = PayloadBuilder::new();
+        let mut served = 0;
+        for (id, context) in env.canister_http_request_contexts() {
+            let Some(path) = context.url.strip_prefix(&self.url) else {
+                continue;
+            };
+
+            let response = self
+                .routes
+                .lock()
+                .unwrap()
+                .get(path)
+                .cloned()
+                .unwrap_or_else(|| MockResponse::error(404));
+            payload = payload.http_response(
+                id,
+                &CanisterHttpResponsePayload {
+                    status: response.status.into(),
+                    headers: vec![],
+                    body: response.body,
+                },
+            );
+            self.requests.lock().unwrap().push(path.to_string());
+            served += 1;
+        }
+
+        if served > 0 {
+            env.execute_payload(payload);
+        }
+
+        served
+    }
+
+    /// Runs the rounds of the state machine, answering the indexer outcalls, until no outcalls
+    /// are made for a few rounds.
+    pub fn serve_until_idle(&self, env: &StateMachine) {
+        let mut idle_rounds = 0;
+        for _ in 0..MAX_ROUNDS {
+            env.tick();
+            if self.serve(env) == 0 {
+                idle_rounds += 1;
+                if idle_rounds == IDLE_ROUNDS {
+                    return;
+                }
+            } else {
+                idle_rounds = 0;
+            }
+        }
+    }
+
+    /// Calls the update `method` of the `canister`, answering the indexer outcalls it makes.
+    pub fn update_call(
+        &self,
+        env: &StateMachine,
+        sender: Principal,
+        canister: Principal,
+        method: &str,
+        payload: Vec<u8>,
+    ) -> WasmResult {
+        let message_id = env
+            .submit_ingress_as(
+                PrincipalId(sender),
+                CanisterId::try_from(PrincipalId(canister)).unwrap(),
+                method,
+                payload,
+            )
+            .expect("failed to submit ingress message");
+        self.serve_until_idle(env);
+        env.await_ingress(message_id, MAX_ROUNDS as usize)
+            .expect("update call failed")
+    }
+}
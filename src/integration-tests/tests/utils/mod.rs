@@ -12,6 +12,9 @@ use solidity_helper::{SolidityBuilder, SolidityContract};
 
 pub mod btc;
 pub mod error;
+pub mod faulty_client;
+#[cfg(feature = "state_machine_tests")]
+pub mod mock_indexer;
 
 pub mod wasm;
 
@@ -76,6 +76,13 @@ pub async fn get_rune_bridge_canister_bytecode() -> Vec<u8> {
     get_or_load_wasm(&CANISTER_BYTECODE, "rune-bridge.wasm.gz").await
 }
 
+/// Returns the bytecode of the rune-bridge built from the previous release, to test upgrades
+/// of the bridge to the working tree wasm.
+pub async fn get_previous_rune_bridge_canister_bytecode() -> Vec<u8> {
+    static CANISTER_BYTECODE: OnceCell<Vec<u8>> = OnceCell::new();
+    get_or_load_wasm(&CANISTER_BYTECODE, "rune-bridge-previous.wasm.gz").await
+}
+
 async fn load_wasm_bytecode_or_panic(wasm_name: &str) -> Vec<u8> {
     let path = get_path_to_file(wasm_name).await;
 
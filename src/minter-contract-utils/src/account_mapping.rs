@@ -0,0 +1,84 @@
+//! Mapping of the EVM addresses to the ICRC subaccounts of the bridge canisters.
+//!
+//! The canisters keep the funds of an EVM user, e.g. the ckBTC deposit or the ICRC-2 burn
+//! allowance, in the subaccount holding the 20 bytes of the address followed by zeros. The
+//! mapping is part of the deposit addresses given to the users, so it must not be changed.
+
+use did::H160;
+
+/// Length of the ICRC subaccount.
+pub const SUBACCOUNT_LENGTH: usize = 32;
+
+/// Subaccount of the `eth_address`.
+///
+/// The zero address maps to the default subaccount of the canister, so it must not be accepted
+/// as a deposit recipient.
+pub fn eth_address_to_subaccount(eth_address: &H160) -> [u8; SUBACCOUNT_LENGTH] {
+    let mut subaccount = [0; SUBACCOUNT_LENGTH];
+    subaccount[..H160::BYTE_SIZE].copy_from_slice(eth_address.0.as_bytes());
+    subaccount
+}
+
+/// EVM address which the `subaccount` belongs to, or `None` if the subaccount is not derived
+/// from an address.
+pub fn subaccount_to_eth_address(subaccount: &[u8; SUBACCOUNT_LENGTH]) -> Option<H160> {
+    let (address, padding) = subaccount.split_at(H160::BYTE_SIZE);
+    if padding.iter().any(|byte| *byte != 0) {
+        return None;
+    }
+
+    Some(H160::from_slice(address))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn subaccount_keeps_address_bytes() {
+        let address = H160::from_hex_str("0x1f9840a85d5af5bf1d1762f925bdaddc4201f984").unwrap();
+        let subaccount = eth_address_to_subaccount(&address);
+
+        assert_eq!(&subaccount[..20], address.0.as_bytes());
+        assert_eq!(subaccount[20..], [0; 12]);
+        assert_eq!(subaccount_to_eth_address(&subaccount), Some(address));
+    }
+
+    #[test]
+    fn distinct_addresses_have_distinct_subaccounts() {
+        let addresses: Vec<H160> = (1..=u8::MAX)
+            .flat_map(|byte| {
+                [
+                    H160::from_slice(&[byte; 20]),
+                    H160::from_slice(&[[byte].as_slice(), &[0; 19]].concat()),
+                    H160::from_slice(&[[0; 19].as_slice(), &[byte]].concat()),
+                ]
+            })
+            .chain([H160::default()])
+            .collect();
+
+        let subaccounts: HashSet<_> = addresses.iter().map(eth_address_to_subaccount).collect();
+        assert_eq!(subaccounts.len(), addresses.len());
+
+        for address in addresses {
+            assert_eq!(
+                subaccount_to_eth_address(&eth_address_to_subaccount(&address)),
+                Some(address)
+            );
+        }
+    }
+
+    #[test]
+    fn foreign_subaccount_has_no_address() {
+        let mut subaccount = [0; SUBACCOUNT_LENGTH];
+        subaccount[SUBACCOUNT_LENGTH - 1] = 1;
+        assert_eq!(subaccount_to_eth_address(&subaccount), None);
+
+        assert_eq!(
+            eth_address_to_subaccount(&H160::default()),
+            [0; SUBACCOUNT_LENGTH]
+        );
+    }
+}
@@ -0,0 +1,454 @@
+//! M-of-N approval of the dangerous admin operations.
+//!
+//! A bridge with a single admin is compromised together with the admin key. With an
+//! [`ApprovalConfig`] set, the dangerous operations of a bridge, e.g. a signer rotation or a
+//! configuration change, are not applied by the admin calls directly. One of the approvers
+//! proposes the operation, the others approve it, and the operation is executed once it has the
+//! approvals of the threshold number of the current approvers.
+//!
+//! The proposals expire after [`PROPOSAL_TTL`], so an operation approved long ago cannot be
+//! executed in a changed context.
+
+use std::borrow::Cow;
+use std::time::Duration;
+
+use candid::{CandidType, Decode, Deserialize, Encode, Principal};
+use ic_stable_structures::stable_structures::Memory;
+use ic_stable_structures::{
+    BTreeMapStructure, Bound, CellStructure, StableBTreeMap, StableCell, Storable,
+};
+use serde::de::DeserializeOwned;
+use thiserror::Error;
+
+/// Time after which a proposal cannot be approved or executed anymore.
+pub const PROPOSAL_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Maximum number of the approvers.
+pub const MAX_APPROVERS: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct ApprovalConfig {
+    /// Principals which propose, approve and execute the dangerous operations.
+    pub approvers: Vec<Principal>,
+    /// Number of the approvals an operation needs to be executed.
+    pub threshold: u32,
+}
+
+impl ApprovalConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.approvers.is_empty() || self.approvers.len() > MAX_APPROVERS {
+            return Err(format!(
+                "between 1 and {MAX_APPROVERS} approvers are required, got {}",
+                self.approvers.len()
+            ));
+        }
+        if self.approvers.contains(&Principal::anonymous()) {
+            return Err("anonymous principal cannot be an approver".into());
+        }
+        if (1..self.approvers.len()).any(|i| self.approvers[..i].contains(&self.approvers[i])) {
+            return Err("approvers must be unique".into());
+        }
+        if self.threshold == 0 || self.threshold as usize > self.approvers.len() {
+            return Err(format!(
+                "threshold must be between 1 and {}, got {}",
+                self.approvers.len(),
+                self.threshold
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn is_approver(&self, principal: &Principal) -> bool {
+        self.approvers.contains(principal)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error, CandidType, Deserialize)]
+pub enum ApprovalError {
+    #[error("approvals are not configured")]
+    NotEnabled,
+    #[error("the operation requires approval, propose it with `admin_propose_operation`")]
+    ApprovalRequired,
+    #[error("caller is not an approver")]
+    NotApprover,
+    #[error("proposal {0} is not found")]
+    ProposalNotFound(u64),
+    #[error("proposal is approved by the caller already")]
+    AlreadyApproved,
+    #[error("proposal is executed already")]
+    AlreadyExecuted,
+    #[error("proposal has expired")]
+    Expired,
+    #[error("proposal has {approvals} of {threshold} required approvals")]
+    NotEnoughApprovals { approvals: u32, threshold: u32 },
+    #[error("invalid approval config: {0}")]
+    InvalidConfig(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct Proposal<Op> {
+    pub id: u64,
+    pub operation: Op,
+    pub proposer: Principal,
+    pub approvals: Vec<Principal>,
+    pub created_at: u64,
+    pub executed_at: Option<u64>,
+}
+
+impl<Op> Proposal<Op> {
+    pub fn is_expired(&self, now: u64) -> bool {
+        now > self.created_at + PROPOSAL_TTL.as_nanos() as u64
+    }
+
+    /// Number of the approvals made by the current approvers.
+    fn valid_approvals(&self, config: &ApprovalConfig) -> u32 {
+        self.approvals
+            .iter()
+            .filter(|approver| config.is_approver(approver))
+            .count() as u32
+    }
+}
+
+impl<Op: CandidType + DeserializeOwned> Storable for Proposal<Op> {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("serialization failed"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).expect("deserialization failed")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[derive(Debug, Default, Clone, CandidType, Deserialize)]
+struct ApprovalSettings {
+    config: Option<ApprovalConfig>,
+    next_id: u64,
+}
+
+impl Storable for ApprovalSettings {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("serialization failed"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).expect("deserialization failed")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// Approval settings and proposals of the operations `Op` of a bridge.
+pub struct ApprovalStore<Op, M: Memory>
+where
+    Op: CandidType + DeserializeOwned + Clone,
+{
+    settings: StableCell<ApprovalSettings, M>,
+    proposals: StableBTreeMap<u64, Proposal<Op>, M>,
+}
+
+impl<Op, M> ApprovalStore<Op, M>
+where
+    Op: CandidType + DeserializeOwned + Clone,
+    M: Memory,
+{
+    pub fn new(settings_memory: M, proposals_memory: M) -> Self {
+        Self {
+            settings: StableCell::new(settings_memory, ApprovalSettings::default())
+                .expect("stable memory approval settings initialization failed"),
+            proposals: StableBTreeMap::new(proposals_memory),
+        }
+    }
+
+    pub fn config(&self) -> Option<ApprovalConfig> {
+        self.settings.get().config.clone()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.settings.get().config.is_some()
+    }
+
+    /// Checks that a dangerous operation may be applied by the admin call directly.
+    pub fn check_direct(&self) -> Result<(), ApprovalError> {
+        if self.is_enabled() {
+            return Err(ApprovalError::ApprovalRequired);
+        }
+
+        Ok(())
+    }
+
+    /// Sets the approvers, or disables the approvals if `config` is `None`. The open proposals
+    /// count only the approvals of the approvers of the new config.
+    pub fn configure(&mut self, config: Option<ApprovalConfig>) -> Result<(), ApprovalError> {
+        if let Some(config) = &config {
+            config.validate().map_err(ApprovalError::InvalidConfig)?;
+        }
+
+        let mut settings = self.settings.get().clone();
+        settings.config = config;
+        self.settings
+            .set(settings)
+            .expect("failed to update approval settings");
+        Ok(())
+    }
+
+    /// Proposes the `operation`, approved by the `caller`. Returns the id of the proposal.
+    pub fn propose(
+        &mut self,
+        caller: Principal,
+        operation: Op,
+        now: u64,
+    ) -> Result<u64, ApprovalError> {
+        self.approver_config(&caller)?;
+        self.remove_stale(now);
+
+        let mut settings = self.settings.get().clone();
+        let id = settings.next_id;
+        settings.next_id += 1;
+        self.settings
+            .set(settings)
+            .expect("failed to update approval settings");
+
+        self.proposals.insert(
+            id,
+            Proposal {
+                id,
+                operation,
+                proposer: caller,
+                approvals: vec![caller],
+                created_at: now,
+                executed_at: None,
+            },
+        );
+        Ok(id)
+    }
+
+    /// Approves the proposal `id` by the `caller`. Returns the number of its valid approvals.
+    pub fn approve(&mut self, caller: Principal, id: u64, now: u64) -> Result<u32, ApprovalError> {
+        let config = self.approver_config(&caller)?;
+        let mut proposal = self.open_proposal(id, now)?;
+        if proposal.approvals.contains(&caller) {
+            return Err(ApprovalError::AlreadyApproved);
+        }
+
+        proposal.approvals.push(caller);
+        let approvals = proposal.valid_approvals(&config);
+        self.proposals.insert(id, proposal);
+        Ok(approvals)
+    }
+
+    /// Returns the operation of the proposal `id` if it has enough approvals to be executed by
+    /// the `caller`. The proposal is marked executed by [`Self::mark_executed`] before the
+    /// operation is applied, so an operation applied asynchronously cannot be executed twice, and
+    /// is reopened by [`Self::reopen`] if the operation fails.
+    pub fn approved_operation(
+        &self,
+        caller: Principal,
+        id: u64,
+        now: u64,
+    ) -> Result<Op, ApprovalError> {
+        let config = self.approver_config(&caller)?;
+        let proposal = self.open_proposal(id, now)?;
+        let approvals = proposal.valid_approvals(&config);
+        if approvals < config.threshold {
+            return Err(ApprovalError::NotEnoughApprovals {
+                approvals,
+                threshold: config.threshold,
+            });
+        }
+
+        Ok(proposal.operation)
+    }
+
+    pub fn mark_executed(&mut self, id: u64, now: u64) {
+        if let Some(mut proposal) = self.proposals.get(&id) {
+            proposal.executed_at = Some(now);
+            self.proposals.insert(id, proposal);
+        }
+    }
+
+    /// Allows the proposal `id` to be executed again after its operation has failed.
+    pub fn reopen(&mut self, id: u64) {
+        if let Some(mut proposal) = self.proposals.get(&id) {
+            proposal.executed_at = None;
+            self.proposals.insert(id, proposal);
+        }
+    }
+
+    pub fn get(&self, id: u64) -> Option<Proposal<Op>> {
+        self.proposals.get(&id)
+    }
+
+    /// Proposals which are not executed and not expired, oldest first.
+    pub fn open_proposals(&self, now: u64) -> Vec<Proposal<Op>> {
+        self.proposals
+            .iter()
+            .map(|(_, proposal)| proposal)
+            .filter(|proposal| proposal.executed_at.is_none() && !proposal.is_expired(now))
+            .collect()
+    }
+
+    fn approver_config(&self, caller: &Principal) -> Result<ApprovalConfig, ApprovalError> {
+        let config = self.config().ok_or(ApprovalError::NotEnabled)?;
+        if !config.is_approver(caller) {
+            return Err(ApprovalError::NotApprover);
+        }
+
+        Ok(config)
+    }
+
+    fn open_proposal(&self, id: u64, now: u64) -> Result<Proposal<Op>, ApprovalError> {
+        let proposal = self
+            .proposals
+            .get(&id)
+            .ok_or(ApprovalError::ProposalNotFound(id))?;
+        if proposal.executed_at.is_some() {
+            return Err(ApprovalError::AlreadyExecuted);
+        }
+        if proposal.is_expired(now) {
+            return Err(ApprovalError::Expired);
+        }
+
+        Ok(proposal)
+    }
+
+    /// Removes the proposals created more than twice the TTL ago, executed or not.
+    fn remove_stale(&mut self, now: u64) {
+        let stale: Vec<u64> = self
+            .proposals
+            .iter()
+            .take_while(|(_, proposal)| {
+                now > proposal.created_at + 2 * PROPOSAL_TTL.as_nanos() as u64
+            })
+            .map(|(id, _)| id)
+            .collect();
+        for id in stale {
+            self.proposals.remove(&id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ic_stable_structures::VectorMemory;
+
+    use super::*;
+
+    fn principal(id: u8) -> Principal {
+        Principal::from_slice(&[id; 29])
+    }
+
+    fn store() -> ApprovalStore<String, VectorMemory> {
+        let mut store = ApprovalStore::new(VectorMemory::default(), VectorMemory::default());
+        store
+            .configure(Some(ApprovalConfig {
+                approvers: vec![principal(1), principal(2), principal(3)],
+                threshold: 2,
+            }))
+            .unwrap();
+        store
+    }
+
+    #[test]
+    fn operation_is_executed_with_threshold_approvals() {
+        let mut store = store();
+        assert_eq!(store.check_direct(), Err(ApprovalError::ApprovalRequired));
+
+        let id = store.propose(principal(1), "rotate".into(), 0).unwrap();
+        assert_eq!(
+            store.approved_operation(principal(1), id, 0),
+            Err(ApprovalError::NotEnoughApprovals {
+                approvals: 1,
+                threshold: 2
+            })
+        );
+        assert_eq!(
+            store.approve(principal(1), id, 0),
+            Err(ApprovalError::AlreadyApproved)
+        );
+        assert_eq!(
+            store.approve(principal(4), id, 0),
+            Err(ApprovalError::NotApprover)
+        );
+
+        assert_eq!(store.approve(principal(2), id, 0), Ok(2));
+        assert_eq!(
+            store.approved_operation(principal(3), id, 0),
+            Ok("rotate".to_string())
+        );
+        store.mark_executed(id, 1);
+        assert_eq!(
+            store.approved_operation(principal(3), id, 1),
+            Err(ApprovalError::AlreadyExecuted)
+        );
+        assert!(store.open_proposals(1).is_empty());
+
+        store.reopen(id);
+        assert_eq!(
+            store.approved_operation(principal(3), id, 1),
+            Ok("rotate".to_string())
+        );
+    }
+
+    #[test]
+    fn expired_and_revoked_approvals_do_not_count() {
+        let mut store = store();
+        let id = store.propose(principal(1), "pause".into(), 0).unwrap();
+        store.approve(principal(2), id, 0).unwrap();
+
+        // The second approver is removed, so its approval is not counted anymore.
+        store
+            .configure(Some(ApprovalConfig {
+                approvers: vec![principal(1), principal(3)],
+                threshold: 2,
+            }))
+            .unwrap();
+        assert!(matches!(
+            store.approved_operation(principal(1), id, 0),
+            Err(ApprovalError::NotEnoughApprovals { approvals: 1, .. })
+        ));
+
+        let expired = PROPOSAL_TTL.as_nanos() as u64 + 1;
+        assert_eq!(
+            store.approve(principal(3), id, expired),
+            Err(ApprovalError::Expired)
+        );
+        assert_eq!(store.open_proposals(expired), vec![]);
+    }
+
+    #[test]
+    fn invalid_config_is_rejected() {
+        let mut store: ApprovalStore<String, _> =
+            ApprovalStore::new(VectorMemory::default(), VectorMemory::default());
+        for config in [
+            ApprovalConfig {
+                approvers: vec![],
+                threshold: 1,
+            },
+            ApprovalConfig {
+                approvers: vec![principal(1), principal(1)],
+                threshold: 1,
+            },
+            ApprovalConfig {
+                approvers: vec![principal(1)],
+                threshold: 2,
+            },
+            ApprovalConfig {
+                approvers: vec![Principal::anonymous()],
+                threshold: 1,
+            },
+        ] {
+            assert!(matches!(
+                store.configure(Some(config)),
+                Err(ApprovalError::InvalidConfig(_))
+            ));
+        }
+        assert_eq!(store.check_direct(), Ok(()));
+        assert_eq!(
+            store.propose(principal(1), "op".into(), 0),
+            Err(ApprovalError::NotEnabled)
+        );
+    }
+}
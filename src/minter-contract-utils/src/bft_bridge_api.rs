@@ -3,10 +3,16 @@ use ethereum_json_rpc_client::{Client, EthGetLogsParams, EthJsonRpcClient};
 use ethers_core::abi::{
     Constructor, Event, EventParam, Function, Param, ParamType, RawLog, StateMutability, Token,
 };
-use ethers_core::types::{BlockNumber as EthBlockNumber, Log, Transaction, H160, U256};
+use ethers_core::types::{
+    BlockNumber as EthBlockNumber, Log, Transaction, TransactionRequest, H160, H256, U256,
+};
+use minter_did::id256::Id256;
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 
+use crate::burn_permit::BurnPermit;
+use crate::gas_limits::DEFAULT_TX_GAS_LIMIT;
+
 pub static CONSTRUCTOR: Lazy<Constructor> = Lazy::new(|| Constructor { inputs: vec![] });
 
 #[allow(deprecated)] // need to initialize `constant` field
@@ -22,6 +28,19 @@ pub static MINTER_CANISTER_ADDRESS: Lazy<Function> = Lazy::new(|| Function {
     state_mutability: StateMutability::View,
 });
 
+#[allow(deprecated)] // need to initialize `constant` field
+pub static FEE_CHARGE_CONTRACT: Lazy<Function> = Lazy::new(|| Function {
+    name: "feeChargeContract".into(),
+    inputs: vec![],
+    outputs: vec![Param {
+        name: "".into(),
+        kind: ParamType::Address,
+        internal_type: None,
+    }],
+    constant: None,
+    state_mutability: StateMutability::View,
+});
+
 #[allow(deprecated)] // need to initialize `constant` field
 pub static NOTIFY_MINTER: Lazy<Function> = Lazy::new(|| Function {
     name: "notifyMinter".into(),
@@ -71,6 +90,65 @@ pub static BURN: Lazy<Function> = Lazy::new(|| Function {
     state_mutability: StateMutability::NonPayable,
 });
 
+#[allow(deprecated)] // need to initialize `constant` field
+pub static BURN_WITH_PERMIT: Lazy<Function> = Lazy::new(|| Function {
+    name: "burnWithPermit".into(),
+    inputs: vec![
+        Param {
+            name: "from".into(),
+            kind: ParamType::Address,
+            internal_type: None,
+        },
+        Param {
+            name: "amount".into(),
+            kind: ParamType::Uint(256),
+            internal_type: None,
+        },
+        Param {
+            name: "fromERC20".into(),
+            kind: ParamType::Address,
+            internal_type: None,
+        },
+        Param {
+            name: "recipientID".into(),
+            kind: ParamType::Bytes,
+            internal_type: None,
+        },
+        Param {
+            name: "deadline".into(),
+            kind: ParamType::Uint(256),
+            internal_type: None,
+        },
+        Param {
+            name: "v".into(),
+            kind: ParamType::Uint(8),
+            internal_type: None,
+        },
+        Param {
+            name: "r".into(),
+            kind: ParamType::FixedBytes(32),
+            internal_type: None,
+        },
+        Param {
+            name: "s".into(),
+            kind: ParamType::FixedBytes(32),
+            internal_type: None,
+        },
+        Param {
+            name: "burnSignature".into(),
+            kind: ParamType::Bytes,
+            internal_type: None,
+        },
+    ],
+    outputs: vec![Param {
+        name: "".into(),
+        kind: ParamType::Uint(32),
+        internal_type: None,
+    }],
+    constant: None,
+    state_mutability: StateMutability::NonPayable,
+});
+
 pub fn decode_burn_operation_id(raw_data: &[u8]) -> anyhow::Result<u32> {
     let id = BURN
         .decode_output(raw_data)?
@@ -186,6 +264,36 @@ pub static DEPLOY_WRAPPED_TOKEN: Lazy<Function> = Lazy::new(|| Function {
     state_mutability: StateMutability::NonPayable,
 });
 
+#[allow(deprecated)] // need to initialize `constant` field
+pub static UPDATE_WRAPPED_TOKEN_METADATA: Lazy<Function> = Lazy::new(|| Function {
+    name: "updateWrappedTokenMetadata".into(),
+    inputs: vec![
+        Param {
+            name: "wrappedToken".into(),
+            kind: ParamType::Address,
+            internal_type: None,
+        },
+        Param {
+            name: "name".into(),
+            kind: ParamType::FixedBytes(32),
+            internal_type: None,
+        },
+        Param {
+            name: "symbol".into(),
+            kind: ParamType::FixedBytes(16),
+            internal_type: None,
+        },
+        Param {
+            name: "decimals".into(),
+            kind: ParamType::Uint(8),
+            internal_type: None,
+        },
+    ],
+    outputs: vec![],
+    constant: None,
+    state_mutability: StateMutability::NonPayable,
+});
+
 pub static BURNT_EVENT: Lazy<Event> = Lazy::new(|| Event {
     name: "BurnTokenEvent".into(),
     inputs: vec![
@@ -238,6 +346,57 @@ pub static BURNT_EVENT: Lazy<Event> = Lazy::new(|| Event {
     anonymous: false,
 });
 
+/// Collects the logs of the `contract` with any of the `topics` in the range of blocks. The range
+/// is requested in pages, which are halved if the EVM fails to return them.
+pub async fn collect_contract_logs(
+    evm_client: &EthJsonRpcClient<impl Client>,
+    mut from_block: u64,
+    to_block: u64,
+    contract: H160,
+    topics: Vec<H256>,
+) -> Result<Vec<Log>, anyhow::Error> {
+    const DEFAULT_BLOCKS_TO_COLLECT_PER_PAGE: u64 = 128;
+    log::debug!("collecting logs from {from_block} to {to_block}",);
+
+    let mut offset = DEFAULT_BLOCKS_TO_COLLECT_PER_PAGE;
+    let mut logs = Vec::new();
+
+    while from_block <= to_block {
+        let to_block_for_page = (from_block + offset).min(to_block);
+        log::debug!("collecting logs from {from_block} to {to_block_for_page}");
+        let params = EthGetLogsParams {
+            address: Some(vec![contract]),
+            from_block: EthBlockNumber::Number(from_block.into()),
+            to_block: EthBlockNumber::Number(to_block_for_page.into()),
+            topics: Some(vec![topics.clone()]),
+        };
+        match evm_client.get_logs(params).await {
+            Ok(new_logs) => {
+                logs.extend(new_logs);
+                // offset is inclusive, so we need to add 1
+                from_block = to_block_for_page + 1;
+                // reset offset to default value
+                offset = DEFAULT_BLOCKS_TO_COLLECT_PER_PAGE;
+            }
+            Err(err) => {
+                log::error!(
+                    "failed to collect logs from {from_block} to {to_block_for_page}: {}",
+                    err
+                );
+                // reduce offset to retry fetching logs; if offset is 0, skip the block
+                if offset > 0 {
+                    offset /= 2;
+                } else {
+                    log::error!("unable to collect logs for block {from_block}. Skipping it.");
+                    from_block += 1;
+                }
+            }
+        }
+    }
+
+    Ok(logs)
+}
+
 /// Emitted when token is burnt or minted by BFTBridge.
 #[derive(Debug, Clone, CandidType, Serialize, Deserialize)]
 pub enum BridgeEvent {
@@ -249,71 +408,22 @@ pub enum BridgeEvent {
 impl BridgeEvent {
     pub async fn collect_logs(
         evm_client: &EthJsonRpcClient<impl Client>,
-        mut from_block: u64,
+        from_block: u64,
         to_block: u64,
         bridge_contract: H160,
     ) -> Result<Vec<Log>, anyhow::Error> {
-        const DEFAULT_BLOCKS_TO_COLLECT_PER_PAGE: u64 = 128;
-        log::debug!("collecting logs from {from_block} to {to_block}",);
-
-        let mut offset = DEFAULT_BLOCKS_TO_COLLECT_PER_PAGE;
-        let mut logs = Vec::new();
-
-        while from_block <= to_block {
-            let to_block_for_page = (from_block + offset).min(to_block);
-            log::debug!("collecting logs from {from_block} to {to_block_for_page}");
-            match Self::collect_logs_from_to(
-                evm_client,
-                bridge_contract,
-                EthBlockNumber::Number(from_block.into()),
-                EthBlockNumber::Number(to_block_for_page.into()),
-            )
-            .await
-            {
-                Ok(new_logs) => {
-                    logs.extend(new_logs);
-                    // offset is inclusive, so we need to add 1
-                    from_block = to_block_for_page + 1;
-                    // reset offset to default value
-                    offset = DEFAULT_BLOCKS_TO_COLLECT_PER_PAGE;
-                }
-                Err(err) => {
-                    log::error!(
-                        "failed to collect logs from {from_block} to {to_block_for_page}: {}",
-                        err
-                    );
-                    // reduce offset to retry fetching logs; if offset is 0, skip the block
-                    if offset > 0 {
-                        offset /= 2;
-                    } else {
-                        log::error!("unable to collect logs for block {from_block}. Skipping it.");
-                        from_block += 1;
-                    }
-                }
-            }
-        }
-
-        Ok(logs)
-    }
-
-    /// Collects logs from the given range of blocks.
-    async fn collect_logs_from_to(
-        evm_client: &EthJsonRpcClient<impl Client>,
-        bridge_contract: H160,
-        from_block: EthBlockNumber,
-        to_block: EthBlockNumber,
-    ) -> Result<Vec<Log>, anyhow::Error> {
-        let params = EthGetLogsParams {
-            address: Some(vec![bridge_contract]),
+        collect_contract_logs(
+            evm_client,
             from_block,
             to_block,
-            topics: Some(vec![vec![
+            bridge_contract,
+            vec![
                 BURNT_EVENT.signature(),
                 MINTED_EVENT.signature(),
                 NOTIFY_EVENT.signature(),
-            ]]),
-        };
-        evm_client.get_logs(params).await
+            ],
+        )
+        .await
     }
 
     pub fn from_log(log: Log) -> Result<Self, ethers_core::abi::Error> {
@@ -340,13 +450,20 @@ impl TryFrom<RawLog> for BridgeEvent {
 /// Emitted when token is burnt by BFTBridge.
 #[derive(Debug, Default, Clone, CandidType, Serialize, Deserialize)]
 pub struct BurntEventData {
+    #[serde(with = "crate::hex_serde::h160")]
     pub sender: did::H160,
+    #[serde(with = "crate::hex_serde::u256")]
     pub amount: did::U256,
+    #[serde(with = "crate::hex_serde::h160")]
     pub from_erc20: did::H160,
+    #[serde(with = "crate::hex_serde::bytes")]
     pub recipient_id: Vec<u8>,
+    #[serde(with = "crate::hex_serde::bytes")]
     pub to_token: Vec<u8>,
     pub operation_id: u32,
+    #[serde(with = "crate::hex_serde::bytes")]
     pub name: Vec<u8>,
+    #[serde(with = "crate::hex_serde::bytes")]
     pub symbol: Vec<u8>,
     pub decimals: u8,
 }
@@ -482,10 +599,15 @@ pub static NOTIFY_EVENT: Lazy<Event> = Lazy::new(|| Event {
 /// Event emitted when token is minted by BFTBridge.
 #[derive(Debug, Default, Clone, CandidType, Serialize, Deserialize)]
 pub struct MintedEventData {
+    #[serde(with = "crate::hex_serde::u256")]
     pub amount: did::U256,
+    #[serde(with = "crate::hex_serde::bytes")]
     pub from_token: Vec<u8>,
+    #[serde(with = "crate::hex_serde::bytes")]
     pub sender_id: Vec<u8>,
+    #[serde(with = "crate::hex_serde::h160")]
     pub to_erc20: did::H160,
+    #[serde(with = "crate::hex_serde::h160")]
     pub recipient: did::H160,
     pub nonce: u32,
 }
@@ -548,7 +670,9 @@ impl TryFrom<RawLog> for MintedEventData {
 #[derive(Debug, PartialEq, Eq, Clone, CandidType, Serialize, Deserialize)]
 pub struct NotifyMinterEventData {
     pub notification_type: u32,
+    #[serde(with = "crate::hex_serde::h160")]
     pub tx_sender: did::H160,
+    #[serde(with = "crate::hex_serde::bytes")]
     pub user_data: Vec<u8>,
 }
 
@@ -634,11 +758,146 @@ pub static LIST_TOKEN_PAIRS: Lazy<Function> = Lazy::new(|| Function {
     state_mutability: StateMutability::View,
 });
 
+#[allow(deprecated)] // need to initialize `constant` field
+pub static IS_NONCE_USED: Lazy<Function> = Lazy::new(|| Function {
+    name: "isNonceUsed".into(),
+    inputs: vec![
+        Param {
+            name: "senderID".into(),
+            kind: ParamType::FixedBytes(32),
+            internal_type: None,
+        },
+        Param {
+            name: "nonce".into(),
+            kind: ParamType::Uint(32),
+            internal_type: None,
+        },
+    ],
+    outputs: vec![Param {
+        name: "".into(),
+        kind: ParamType::Bool,
+        internal_type: None,
+    }],
+    constant: None,
+    state_mutability: StateMutability::View,
+});
+
+/// Checks with `eth_call` whether the BftBridge has minted the order of the `sender` with the
+/// `nonce` already. A bridge retrying to send a signed order checks it first, as the order can be
+/// minted by an earlier transaction whose result was lost, or by the user.
+pub async fn is_mint_order_used(
+    evm_client: &EthJsonRpcClient<impl Client>,
+    bridge_contract: H160,
+    sender: &Id256,
+    nonce: u32,
+) -> anyhow::Result<bool> {
+    let data = IS_NONCE_USED.encode_input(&[
+        Token::FixedBytes(sender.0.to_vec()),
+        Token::Uint(nonce.into()),
+    ])?;
+
+    let call_result = evm_client
+        .eth_call(
+            TransactionRequest {
+                to: Some(bridge_contract.into()),
+                gas: Some(DEFAULT_TX_GAS_LIMIT.into()),
+                data: Some(data.into()),
+                ..Default::default()
+            },
+            EthBlockNumber::Latest,
+        )
+        .await?;
+
+    let call_result = hex::decode(call_result.trim_start_matches("0x"))?;
+    match IS_NONCE_USED.decode_output(&call_result)?.as_slice() {
+        &[Token::Bool(used)] => Ok(used),
+        tokens => Err(anyhow::anyhow!("unexpected isNonceUsed output: {tokens:?}")),
+    }
+}
+
+/// Calls the view `function` of the `contract` with `eth_call` and decodes its output.
+pub async fn call_view_function(
+    evm_client: &EthJsonRpcClient<impl Client>,
+    contract: H160,
+    function: &Function,
+    args: &[Token],
+) -> anyhow::Result<Vec<Token>> {
+    let data = function.encode_input(args)?;
+    let call_result = evm_client
+        .eth_call(
+            TransactionRequest {
+                to: Some(contract.into()),
+                gas: Some(DEFAULT_TX_GAS_LIMIT.into()),
+                data: Some(data.into()),
+                ..Default::default()
+            },
+            EthBlockNumber::Latest,
+        )
+        .await?;
+
+    let call_result = hex::decode(call_result.trim_start_matches("0x"))?;
+    Ok(function.decode_output(&call_result)?)
+}
+
+/// Returns the minter canister address the BftBridge accepts the mint orders from.
+pub async fn minter_canister_address(
+    evm_client: &EthJsonRpcClient<impl Client>,
+    bridge_contract: H160,
+) -> anyhow::Result<H160> {
+    match call_view_function(evm_client, bridge_contract, &MINTER_CANISTER_ADDRESS, &[])
+        .await?
+        .as_slice()
+    {
+        &[Token::Address(address)] => Ok(address),
+        tokens => Err(anyhow::anyhow!(
+            "unexpected minterCanisterAddress output: {tokens:?}"
+        )),
+    }
+}
+
+/// Returns the FeeCharge contract the BftBridge charges the mint fees with, or `None` if the
+/// bridge charges no fees.
+pub async fn fee_charge_contract(
+    evm_client: &EthJsonRpcClient<impl Client>,
+    bridge_contract: H160,
+) -> anyhow::Result<Option<H160>> {
+    match call_view_function(evm_client, bridge_contract, &FEE_CHARGE_CONTRACT, &[])
+        .await?
+        .as_slice()
+    {
+        &[Token::Address(address)] => Ok((!address.is_zero()).then_some(address)),
+        tokens => Err(anyhow::anyhow!(
+            "unexpected feeChargeContract output: {tokens:?}"
+        )),
+    }
+}
+
+/// Returns the wrapped tokens deployed by the BftBridge.
+pub async fn wrapped_tokens(
+    evm_client: &EthJsonRpcClient<impl Client>,
+    bridge_contract: H160,
+) -> anyhow::Result<Vec<H160>> {
+    let tokens = call_view_function(evm_client, bridge_contract, &LIST_TOKEN_PAIRS, &[]).await?;
+    let Some(Token::Array(wrapped)) = tokens.into_iter().next() else {
+        return Err(anyhow::anyhow!("unexpected listTokenPairs output"));
+    };
+
+    wrapped
+        .into_iter()
+        .map(|token| {
+            token
+                .into_address()
+                .ok_or_else(|| anyhow::anyhow!("unexpected listTokenPairs output"))
+        })
+        .collect()
+}
+
 pub fn mint_transaction(
     sender: H160,
     bridge: H160,
     nonce: U256,
     gas_price: U256,
+    gas_limit: u64,
     mint_order_data: &[u8],
     chain_id: u32,
 ) -> Transaction {
@@ -646,13 +905,73 @@ pub fn mint_transaction(
         .encode_input(&[Token::Bytes(mint_order_data.to_vec())])
         .expect("mint order encoding should pass");
 
-    pub const DEFAULT_TX_GAS_LIMIT: u64 = 3_000_000;
     ethers_core::types::Transaction {
         from: sender,
         to: bridge.into(),
         nonce,
         value: U256::zero(),
-        gas: DEFAULT_TX_GAS_LIMIT.into(),
+        gas: gas_limit.into(),
+        gas_price: Some(gas_price),
+        input: data.into(),
+        chain_id: Some(chain_id.into()),
+        ..Default::default()
+    }
+}
+
+/// Transaction relaying the burn of the `permit` holder tokens to the BftBridge. The `sender`
+/// must be the minter canister address of the bridge.
+pub fn burn_with_permit_transaction(
+    sender: H160,
+    bridge: H160,
+    nonce: U256,
+    gas_price: U256,
+    gas_limit: u64,
+    permit: &BurnPermit,
+    chain_id: u32,
+) -> Transaction {
+    ethers_core::types::Transaction {
+        from: sender,
+        to: bridge.into(),
+        nonce,
+        value: U256::zero(),
+        gas: gas_limit.into(),
+        gas_price: Some(gas_price),
+        input: permit.call_data().into(),
+        chain_id: Some(chain_id.into()),
+        ..Default::default()
+    }
+}
+
+/// Transaction updating the name, symbol and decimals of a wrapped token deployed by the bridge.
+/// The `sender` must be the minter canister address of the bridge.
+#[allow(clippy::too_many_arguments)]
+pub fn update_wrapped_token_metadata_transaction(
+    sender: H160,
+    bridge: H160,
+    nonce: U256,
+    gas_price: U256,
+    gas_limit: u64,
+    wrapped_token: H160,
+    name: [u8; 32],
+    symbol: [u8; 16],
+    decimals: u8,
+    chain_id: u32,
+) -> Transaction {
+    let data = UPDATE_WRAPPED_TOKEN_METADATA
+        .encode_input(&[
+            Token::Address(wrapped_token),
+            Token::FixedBytes(name.to_vec()),
+            Token::FixedBytes(symbol.to_vec()),
+            Token::Uint(decimals.into()),
+        ])
+        .expect("metadata encoding should pass");
+
+    ethers_core::types::Transaction {
+        from: sender,
+        to: bridge.into(),
+        nonce,
+        value: U256::zero(),
+        gas: gas_limit.into(),
         gas_price: Some(gas_price),
         input: data.into(),
         chain_id: Some(chain_id.into()),
@@ -798,6 +1117,95 @@ mod tests {
         let _event = BurntEventData::try_from(raw).unwrap();
     }
 
+    #[test]
+    fn update_wrapped_token_metadata_transaction_encoding() {
+        let bridge = ethers_core::types::H160::from_slice(&[1; 20]);
+        let wrapped_token = ethers_core::types::H160::from_slice(&[2; 20]);
+        let mut name = [0; 32];
+        name[..5].copy_from_slice(b"Token");
+        let mut symbol = [0; 16];
+        symbol[..3].copy_from_slice(b"TKN");
+
+        let tx = update_wrapped_token_metadata_transaction(
+            Default::default(),
+            bridge,
+            3.into(),
+            10.into(),
+            100_000,
+            wrapped_token,
+            name,
+            symbol,
+            8,
+            355113,
+        );
+
+        assert_eq!(tx.to, Some(bridge));
+        assert_eq!(tx.nonce, 3.into());
+        assert_eq!(tx.gas, 100_000.into());
+        assert_eq!(
+            &tx.input[..4],
+            &UPDATE_WRAPPED_TOKEN_METADATA.short_signature()[..]
+        );
+
+        let decoded = UPDATE_WRAPPED_TOKEN_METADATA
+            .decode_input(&tx.input[4..])
+            .unwrap();
+        assert_eq!(
+            decoded,
+            vec![
+                Token::Address(wrapped_token),
+                Token::FixedBytes(name.to_vec()),
+                Token::FixedBytes(symbol.to_vec()),
+                Token::Uint(8.into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn burn_with_permit_transaction_encoding() {
+        let bridge = ethers_core::types::H160::from_slice(&[1; 20]);
+        let permit = BurnPermit {
+            from: H160::from_slice(&[2; 20]),
+            amount: 1_000u64.into(),
+            from_erc20: H160::from_slice(&[3; 20]),
+            recipient_id: b"bc1qrecipient".to_vec(),
+            deadline: 1_700_000_000u64.into(),
+            v: 27,
+            r: H256::from_slice(&[4; 32]),
+            s: H256::from_slice(&[5; 32]),
+            burn_signature: vec![6; 65],
+        };
+
+        let tx = burn_with_permit_transaction(
+            Default::default(),
+            bridge,
+            3.into(),
+            10.into(),
+            100_000,
+            &permit,
+            355113,
+        );
+
+        assert_eq!(tx.to, Some(bridge));
+        assert_eq!(&tx.input[..4], &BURN_WITH_PERMIT.short_signature()[..]);
+
+        let decoded = BURN_WITH_PERMIT.decode_input(&tx.input[4..]).unwrap();
+        assert_eq!(
+            decoded,
+            vec![
+                Token::Address(permit.from.0),
+                Token::Uint(1_000.into()),
+                Token::Address(permit.from_erc20.0),
+                Token::Bytes(permit.recipient_id.clone()),
+                Token::Uint(1_700_000_000u64.into()),
+                Token::Uint(27.into()),
+                Token::FixedBytes(vec![4; 32]),
+                Token::FixedBytes(vec![5; 32]),
+                Token::Bytes(vec![6; 65]),
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn test_should_get_paginated_logs() {
         env_logger::init();
@@ -865,6 +1273,79 @@ mod tests {
         assert_eq!(logs.len(), 800);
     }
 
+    #[tokio::test]
+    async fn mint_order_nonce_is_checked_with_eth_call() {
+        let sender = Id256([1; 32]);
+        let evm_client = EthJsonRpcClient::new(UsedNoncesClient {
+            used: vec![(Id256([1; 32]), 3)],
+        });
+        let bridge = ethers_core::types::H160::from_low_u64_be(42);
+
+        assert!(is_mint_order_used(&evm_client, bridge, &sender, 3)
+            .await
+            .unwrap());
+        assert!(!is_mint_order_used(&evm_client, bridge, &sender, 4)
+            .await
+            .unwrap());
+        assert!(!is_mint_order_used(&evm_client, bridge, &Id256([2; 32]), 3)
+            .await
+            .unwrap());
+    }
+
+    /// Answers the `isNonceUsed` calls.
+    #[derive(Clone)]
+    struct UsedNoncesClient {
+        used: Vec<(Id256, u32)>,
+    }
+
+    impl Client for UsedNoncesClient {
+        fn send_rpc_request(
+            &self,
+            request: jsonrpc_core::Request,
+        ) -> std::pin::Pin<
+            Box<dyn std::future::Future<Output = anyhow::Result<jsonrpc_core::Response>> + Send>,
+        > {
+            let jsonrpc_core::Request::Single(jsonrpc_core::Call::MethodCall(method_call)) =
+                request
+            else {
+                unimplemented!("expected single method call request");
+            };
+            assert_eq!(method_call.method, "eth_call");
+            let jsonrpc_core::Params::Array(params) = method_call.params else {
+                unimplemented!("expected array params");
+            };
+
+            let input = params[0]
+                .get("data")
+                .or_else(|| params[0].get("input"))
+                .and_then(|data| data.as_str())
+                .unwrap();
+            let input = hex::decode(input.trim_start_matches("0x")).unwrap();
+            assert_eq!(&input[..4], IS_NONCE_USED.short_signature().as_slice());
+
+            let tokens = IS_NONCE_USED.decode_input(&input[4..]).unwrap();
+            let used = match tokens.as_slice() {
+                [Token::FixedBytes(sender), Token::Uint(nonce)] => {
+                    self.used.iter().any(|(used, used_nonce)| {
+                        used.0 == sender[..] && nonce.as_u32() == *used_nonce
+                    })
+                }
+                tokens => unimplemented!("unexpected input: {tokens:?}"),
+            };
+            let output = ethers_core::abi::encode(&[Token::Bool(used)]);
+
+            let response = jsonrpc_core::Response::Single(jsonrpc_core::Output::Success(
+                jsonrpc_core::Success {
+                    jsonrpc: None,
+                    result: serde_json::json!(format!("0x{}", hex::encode(output))),
+                    id: method_call.id,
+                },
+            ));
+
+            Box::pin(async { Ok(response) })
+        }
+    }
+
     #[derive(Clone)]
     struct FakeEthJsonRpcClient {
         /// block number -> logs
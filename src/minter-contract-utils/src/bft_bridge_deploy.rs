@@ -0,0 +1,216 @@
+//! Deployment of the BftBridge contract by the bridge canisters.
+//!
+//! A bridge deploys the BftBridge implementation and the UUPS proxy initialized with the EVM
+//! address of the canister as the minter, so the operators don't have to deploy the contract with
+//! the bridge-tool. The contract addresses follow from the sender address and the nonces of the
+//! deployment transactions, so they are known as soon as the transactions are sent, and the
+//! deployment is complete once the proxy address has code.
+
+use candid::CandidType;
+use eth_signer::sign_strategy::TransactionSigner;
+use ethereum_json_rpc_client::{Client, EthJsonRpcClient};
+use ethers_core::abi::Token;
+use ethers_core::types::{BlockNumber, Transaction, H160, U256};
+use serde::Deserialize;
+
+use crate::bft_bridge_api;
+use crate::build_data::{BFT_BRIDGE_SMART_CONTRACT_CODE, UUPS_PROXY_SMART_CONTRACT_CODE};
+
+/// Gas limit of each of the deployment transactions.
+pub const BFT_BRIDGE_DEPLOY_GAS_LIMIT: u64 = 5_000_000;
+
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub enum BftBridgeDeployStatus {
+    /// Deployment transactions are sent, but the proxy has no code yet.
+    Pending {
+        implementation: did::H160,
+        proxy: did::H160,
+    },
+    /// The proxy is deployed. Its address is the BftBridge address of the bridge.
+    Deployed {
+        implementation: did::H160,
+        proxy: did::H160,
+    },
+}
+
+impl BftBridgeDeployStatus {
+    /// Address of the BftBridge proxy.
+    pub fn proxy(&self) -> &did::H160 {
+        match self {
+            Self::Pending { proxy, .. } | Self::Deployed { proxy, .. } => proxy,
+        }
+    }
+
+    pub fn is_deployed(&self) -> bool {
+        matches!(self, Self::Deployed { .. })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BftBridgeDeployError {
+    /// Number of the deployment transactions sent before the error. Each of them used a nonce
+    /// of the sender.
+    pub sent_transactions: u64,
+    pub reason: String,
+}
+
+/// Unsigned transactions deploying the BftBridge behind a proxy.
+#[derive(Debug, Clone)]
+pub struct BftBridgeDeployment {
+    pub implementation: H160,
+    pub proxy: H160,
+    /// Implementation deployment with the `nonce`, and proxy deployment with the `nonce + 1`.
+    pub transactions: [Transaction; 2],
+}
+
+impl BftBridgeDeployment {
+    /// Deployment of the BftBridge with the `sender` as the minter.
+    pub fn new(
+        sender: H160,
+        nonce: u64,
+        gas_price: U256,
+        chain_id: u64,
+        fee_charge: H160,
+        is_wrapped_side: bool,
+    ) -> Result<Self, String> {
+        let implementation = ethers_core::utils::get_contract_address(sender, nonce);
+        let proxy = ethers_core::utils::get_contract_address(sender, nonce + 1);
+
+        let implementation_input = bft_bridge_api::CONSTRUCTOR
+            .encode_input(BFT_BRIDGE_SMART_CONTRACT_CODE.clone(), &[])
+            .map_err(|err| format!("failed to encode BftBridge constructor: {err}"))?;
+        let initialize_data = bft_bridge_api::proxy::INITIALISER
+            .encode_input(&[
+                Token::Address(sender),
+                Token::Address(fee_charge),
+                Token::Bool(is_wrapped_side),
+            ])
+            .map_err(|err| format!("failed to encode BftBridge initializer: {err}"))?;
+        let proxy_input = bft_bridge_api::proxy::CONSTRUCTOR
+            .encode_input(
+                UUPS_PROXY_SMART_CONTRACT_CODE.clone(),
+                &[
+                    Token::Address(implementation),
+                    Token::Bytes(initialize_data),
+                ],
+            )
+            .map_err(|err| format!("failed to encode proxy constructor: {err}"))?;
+
+        let deploy_tx = |nonce: u64, input: Vec<u8>| Transaction {
+            from: sender,
+            to: None,
+            nonce: nonce.into(),
+            value: U256::zero(),
+            gas: BFT_BRIDGE_DEPLOY_GAS_LIMIT.into(),
+            gas_price: Some(gas_price),
+            input: input.into(),
+            chain_id: Some(chain_id.into()),
+            ..Default::default()
+        };
+
+        Ok(Self {
+            implementation,
+            proxy,
+            transactions: [
+                deploy_tx(nonce, implementation_input),
+                deploy_tx(nonce + 1, proxy_input),
+            ],
+        })
+    }
+
+    /// Signs and sends the deployment transactions in order.
+    pub async fn send(
+        self,
+        signer: &impl TransactionSigner,
+        client: &EthJsonRpcClient<impl Client>,
+    ) -> Result<BftBridgeDeployStatus, BftBridgeDeployError> {
+        let mut sent_transactions = 0;
+        for mut tx in self.transactions {
+            let error = |reason: String| BftBridgeDeployError {
+                sent_transactions,
+                reason,
+            };
+
+            let signature = signer
+                .sign_transaction(&(&tx).into())
+                .await
+                .map_err(|err| error(format!("failed to sign deployment transaction: {err:?}")))?;
+            tx.r = signature.r.0;
+            tx.s = signature.s.0;
+            tx.v = signature.v.0;
+            tx.hash = tx.hash();
+
+            client
+                .send_raw_transaction(tx)
+                .await
+                .map_err(|err| error(format!("failed to send deployment transaction: {err:?}")))?;
+            sent_transactions += 1;
+        }
+
+        Ok(BftBridgeDeployStatus::Pending {
+            implementation: self.implementation.into(),
+            proxy: self.proxy.into(),
+        })
+    }
+}
+
+/// Checks if the proxy of the pending deployment has code, and returns the updated status.
+pub async fn check_deployment(
+    client: &EthJsonRpcClient<impl Client>,
+    status: BftBridgeDeployStatus,
+) -> Result<BftBridgeDeployStatus, String> {
+    let BftBridgeDeployStatus::Pending {
+        implementation,
+        proxy,
+    } = status
+    else {
+        return Ok(status);
+    };
+
+    let code = client
+        .get_code(proxy.0, BlockNumber::Latest)
+        .await
+        .map_err(|err| format!("failed to get BftBridge proxy code: {err}"))?;
+
+    if code.trim_start_matches("0x").is_empty() {
+        Ok(BftBridgeDeployStatus::Pending {
+            implementation,
+            proxy,
+        })
+    } else {
+        Ok(BftBridgeDeployStatus::Deployed {
+            implementation,
+            proxy,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn contract_addresses_follow_sender_nonces() {
+        let sender = H160::from_str("0x6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0").unwrap();
+        let deployment =
+            BftBridgeDeployment::new(sender, 0, 10.into(), 355113, H160::zero(), true).unwrap();
+
+        assert_eq!(
+            deployment.implementation,
+            H160::from_str("0xcd234a471b72ba2f1ccf0a70fcaba648a5eecd8d").unwrap()
+        );
+        assert_eq!(
+            deployment.proxy,
+            H160::from_str("0x343c43a37d37dff08ae8c4a11544c718abb4fcf8").unwrap()
+        );
+
+        let [implementation_tx, proxy_tx] = &deployment.transactions;
+        assert_eq!(implementation_tx.nonce, 0.into());
+        assert_eq!(proxy_tx.nonce, 1.into());
+        assert!(implementation_tx.to.is_none() && proxy_tx.to.is_none());
+        assert_eq!(proxy_tx.chain_id, Some(355113.into()));
+        assert!(proxy_tx.input.len() > UUPS_PROXY_SMART_CONTRACT_CODE.len());
+    }
+}
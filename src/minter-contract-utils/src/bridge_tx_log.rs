@@ -0,0 +1,273 @@
+//! Append-only log of the wrapped token mints and burns, in a shape inspired by ICRC-3.
+//!
+//! Every entry of the log keeps the hash of the previous entry, so the whole log is committed to
+//! by the hash of its last entry. The hash is certified with the [`TX_LOG_TIP_LABEL`], so the
+//! indexers can check with the certificate returned by [`BridgeTxLog::get_transactions`] that the
+//! log they read was not altered by a replica or a boundary node.
+
+use std::borrow::Cow;
+
+use bitcoin::hashes::{sha256, Hash};
+use candid::{CandidType, Decode, Encode};
+use did::{H160, H256, U256};
+use ic_stable_structures::stable_structures::Memory;
+use ic_stable_structures::{BTreeMapStructure, Bound, StableBTreeMap, Storable};
+use serde::Deserialize;
+
+use crate::bft_bridge_api::{BurntEventData, MintedEventData};
+use crate::certified_data::{self, TX_LOG_TIP_LABEL};
+
+/// Maximum number of transactions returned by a single [`BridgeTxLog::get_transactions`] call.
+pub const MAX_TRANSACTIONS_PER_REQUEST: u64 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, CandidType, Deserialize)]
+pub enum BridgeTxKind {
+    /// Wrapped tokens are minted on the EVM for an asset deposited to the bridge.
+    Mint,
+    /// Wrapped tokens are burnt on the EVM to withdraw the asset from the bridge.
+    Burn,
+}
+
+/// Mint or burn of the wrapped tokens, as reported by the BftBridge events.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct BridgeTransaction {
+    pub kind: BridgeTxKind,
+    /// Time the event was collected by the bridge, in nanoseconds.
+    pub timestamp: u64,
+    /// Wrapped token contract on the EVM.
+    pub wrapped_token: H160,
+    pub amount: U256,
+    /// Recipient of the minted or sender of the burnt tokens on the EVM.
+    pub evm_address: H160,
+    /// Sender id of the mint order for the mints, or the recipient id of the burns, on the side
+    /// of the base asset, e.g. the encoded BTC address of a withdrawal.
+    pub base_address: Vec<u8>,
+    /// EVM transaction which emitted the event.
+    pub evm_tx_hash: Option<H256>,
+    /// Nonce of the mint order, or the operation id of the burn.
+    pub nonce: u32,
+}
+
+impl BridgeTransaction {
+    pub fn mint(event: &MintedEventData, evm_tx_hash: Option<H256>, timestamp: u64) -> Self {
+        Self {
+            kind: BridgeTxKind::Mint,
+            timestamp,
+            wrapped_token: event.to_erc20.clone(),
+            amount: event.amount.clone(),
+            evm_address: event.recipient.clone(),
+            base_address: event.sender_id.clone(),
+            evm_tx_hash,
+            nonce: event.nonce,
+        }
+    }
+
+    pub fn burn(event: &BurntEventData, evm_tx_hash: Option<H256>, timestamp: u64) -> Self {
+        Self {
+            kind: BridgeTxKind::Burn,
+            timestamp,
+            wrapped_token: event.from_erc20.clone(),
+            amount: event.amount.clone(),
+            evm_address: event.sender.clone(),
+            base_address: event.recipient_id.clone(),
+            evm_tx_hash,
+            nonce: event.operation_id,
+        }
+    }
+}
+
+/// Entry of the log.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct BridgeTxBlock {
+    pub id: u64,
+    pub transaction: BridgeTransaction,
+    /// Hash of the previous entry. `None` for the first entry.
+    pub parent_hash: Option<H256>,
+}
+
+impl BridgeTxBlock {
+    pub fn hash(&self) -> H256 {
+        let hash = sha256::Hash::hash(&self.to_bytes());
+        H256::from_slice(hash.as_byte_array())
+    }
+}
+
+impl Storable for BridgeTxBlock {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("serialization failed"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).expect("deserialization failed")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct GetBridgeTransactionsResponse {
+    /// Number of entries in the log.
+    pub log_length: u64,
+    pub transactions: Vec<BridgeTxBlock>,
+    /// Hash of the last entry of the log, certified with the [`TX_LOG_TIP_LABEL`].
+    pub tip_hash: Option<H256>,
+    /// Certified leaves of the canister, see [`certified_data`].
+    pub leaves: Vec<(String, H256)>,
+    /// Certificate of the root of the certified leaves. Only returned by the query calls.
+    pub certificate: Option<Vec<u8>>,
+}
+
+/// Stable storage of the bridge transactions log.
+pub struct BridgeTxLog<M: Memory> {
+    blocks: StableBTreeMap<u64, BridgeTxBlock, M>,
+}
+
+impl<M: Memory> BridgeTxLog<M> {
+    pub fn new(memory: M) -> Self {
+        Self {
+            blocks: StableBTreeMap::new(memory),
+        }
+    }
+
+    /// Appends the transaction to the log and certifies the new tip. Returns the id of the entry.
+    pub fn append(&mut self, transaction: BridgeTransaction) -> u64 {
+        let id = self.len();
+        let block = BridgeTxBlock {
+            id,
+            transaction,
+            parent_hash: self.tip_hash(),
+        };
+
+        let hash = block.hash();
+        self.blocks.insert(id, block);
+        Self::certify(&hash);
+
+        id
+    }
+
+    pub fn len(&self) -> u64 {
+        self.blocks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Hash of the last entry of the log.
+    pub fn tip_hash(&self) -> Option<H256> {
+        let last_id = self.len().checked_sub(1)?;
+        self.blocks.get(&last_id).map(|block| block.hash())
+    }
+
+    /// Returns up to `length` entries starting from the `start` id, limited by
+    /// [`MAX_TRANSACTIONS_PER_REQUEST`].
+    pub fn get_transactions(&self, start: u64, length: u64) -> GetBridgeTransactionsResponse {
+        let end = start
+            .saturating_add(length.min(MAX_TRANSACTIONS_PER_REQUEST))
+            .min(self.len());
+        let transactions = (start..end).filter_map(|id| self.blocks.get(&id)).collect();
+        let certified = certified_data::Certified::new(TX_LOG_TIP_LABEL, self.tip_hash());
+
+        GetBridgeTransactionsResponse {
+            log_length: self.len(),
+            transactions,
+            tip_hash: certified.value,
+            leaves: certified.leaves,
+            certificate: certified.certificate,
+        }
+    }
+
+    /// Certifies the tip of the log again after an upgrade, as the certified leaves are not
+    /// preserved by the upgrades.
+    pub fn certify_tip(&self) {
+        if let Some(hash) = self.tip_hash() {
+            Self::certify(&hash);
+        }
+    }
+
+    fn certify(hash: &H256) {
+        certified_data::certify_hash(TX_LOG_TIP_LABEL, hash.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ic_stable_structures::VectorMemory;
+
+    use super::*;
+
+    fn transaction(nonce: u32) -> BridgeTransaction {
+        BridgeTransaction::mint(
+            &MintedEventData {
+                amount: U256::from(100u64),
+                from_token: vec![1; 32],
+                sender_id: vec![2; 32],
+                to_erc20: H160::from_slice(&[3; 20]),
+                recipient: H160::from_slice(&[4; 20]),
+                nonce,
+            },
+            None,
+            0,
+        )
+    }
+
+    #[test]
+    fn blocks_are_chained() {
+        let mut log = BridgeTxLog::new(VectorMemory::default());
+        assert!(log.is_empty());
+        assert_eq!(log.tip_hash(), None);
+
+        assert_eq!(log.append(transaction(1)), 0);
+        assert_eq!(log.append(transaction(2)), 1);
+
+        let response = log.get_transactions(0, 10);
+        assert_eq!(response.log_length, 2);
+        assert_eq!(response.transactions[0].parent_hash, None);
+        assert_eq!(
+            response.transactions[1].parent_hash,
+            Some(response.transactions[0].hash())
+        );
+        assert_eq!(response.tip_hash, Some(response.transactions[1].hash()));
+        assert!(response.leaves.contains(&(
+            TX_LOG_TIP_LABEL.to_string(),
+            response.transactions[1].hash()
+        )));
+    }
+
+    #[test]
+    fn transactions_are_paged() {
+        let mut log = BridgeTxLog::new(VectorMemory::default());
+        for nonce in 0..(MAX_TRANSACTIONS_PER_REQUEST as u32 + 10) {
+            log.append(transaction(nonce));
+        }
+
+        let page = log.get_transactions(5, 3);
+        let ids: Vec<u64> = page.transactions.iter().map(|block| block.id).collect();
+        assert_eq!(ids, vec![5, 6, 7]);
+
+        assert_eq!(
+            log.get_transactions(0, u64::MAX).transactions.len() as u64,
+            MAX_TRANSACTIONS_PER_REQUEST
+        );
+        assert!(log.get_transactions(log.len(), 10).transactions.is_empty());
+        assert!(log.get_transactions(u64::MAX, 10).transactions.is_empty());
+    }
+
+    #[test]
+    fn burn_is_recorded_from_event() {
+        let event = BurntEventData {
+            sender: H160::from_slice(&[5; 20]),
+            amount: U256::from(7u64),
+            from_erc20: H160::from_slice(&[6; 20]),
+            recipient_id: b"bc1qrecipient".to_vec(),
+            operation_id: 3,
+            ..Default::default()
+        };
+
+        let transaction = BridgeTransaction::burn(&event, None, 42);
+        assert_eq!(transaction.kind, BridgeTxKind::Burn);
+        assert_eq!(transaction.evm_address, event.sender);
+        assert_eq!(transaction.base_address, event.recipient_id);
+        assert_eq!(transaction.nonce, 3);
+    }
+}
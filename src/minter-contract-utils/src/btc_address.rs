@@ -0,0 +1,114 @@
+//! Validation of BTC addresses received from the users.
+//!
+//! Withdrawal addresses come from the burn events as raw bytes, so they must be decoded, parsed
+//! and checked against the network the canister works with before any funds are sent to them.
+
+use std::str::FromStr;
+
+use bitcoin::{Address, Network};
+use candid::CandidType;
+use serde::Deserialize;
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error, CandidType, Deserialize)]
+pub enum BtcAddressError {
+    #[error("address bytes are not a valid utf-8 string: {0:?}")]
+    InvalidUtf8(Vec<u8>),
+    #[error("failed to parse address {address}: {reason}")]
+    Malformed { address: String, reason: String },
+    #[error("address {address} is not valid for the {expected} network")]
+    WrongNetwork { address: String, expected: String },
+}
+
+/// Parses the `address` and checks that it belongs to the `network`.
+pub fn parse_btc_address(address: &str, network: Network) -> Result<Address, BtcAddressError> {
+    let address = address.trim();
+    let unchecked = Address::from_str(address).map_err(|err| BtcAddressError::Malformed {
+        address: address.to_string(),
+        reason: err.to_string(),
+    })?;
+
+    unchecked
+        .require_network(network)
+        .map_err(|_| BtcAddressError::WrongNetwork {
+            address: address.to_string(),
+            expected: network.to_string(),
+        })
+}
+
+/// Same as [`parse_btc_address`], but for the utf-8 encoded address as it is stored in the
+/// `recipient_id` field of the burn events.
+pub fn parse_btc_address_bytes(
+    address: &[u8],
+    network: Network,
+) -> Result<Address, BtcAddressError> {
+    let address =
+        std::str::from_utf8(address).map_err(|_| BtcAddressError::InvalidUtf8(address.to_vec()))?;
+    parse_btc_address(address, network)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAINNET_ADDRESS: &str = "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq";
+    const TESTNET_ADDRESS: &str = "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx";
+
+    #[test]
+    fn valid_addresses_are_parsed() {
+        let address = parse_btc_address(MAINNET_ADDRESS, Network::Bitcoin).unwrap();
+        assert_eq!(address.to_string(), MAINNET_ADDRESS);
+
+        let address =
+            parse_btc_address_bytes(TESTNET_ADDRESS.as_bytes(), Network::Testnet).unwrap();
+        assert_eq!(address.to_string(), TESTNET_ADDRESS);
+    }
+
+    #[test]
+    fn surrounding_whitespace_is_ignored() {
+        let address = parse_btc_address(&format!(" {MAINNET_ADDRESS}\n"), Network::Bitcoin);
+        assert!(address.is_ok());
+    }
+
+    #[test]
+    fn wrong_network_is_rejected() {
+        assert_eq!(
+            parse_btc_address(TESTNET_ADDRESS, Network::Bitcoin),
+            Err(BtcAddressError::WrongNetwork {
+                address: TESTNET_ADDRESS.to_string(),
+                expected: Network::Bitcoin.to_string(),
+            })
+        );
+        assert!(matches!(
+            parse_btc_address(MAINNET_ADDRESS, Network::Regtest),
+            Err(BtcAddressError::WrongNetwork { .. })
+        ));
+    }
+
+    #[test]
+    fn malformed_addresses_are_rejected() {
+        assert!(matches!(
+            parse_btc_address("bc1qnotanaddress", Network::Bitcoin),
+            Err(BtcAddressError::Malformed { .. })
+        ));
+        assert!(matches!(
+            parse_btc_address("", Network::Bitcoin),
+            Err(BtcAddressError::Malformed { .. })
+        ));
+        assert_eq!(
+            parse_btc_address_bytes(&[0xff, 0xfe], Network::Bitcoin),
+            Err(BtcAddressError::InvalidUtf8(vec![0xff, 0xfe]))
+        );
+    }
+
+    #[test]
+    fn test_network_addresses_are_valid_for_signet() {
+        let address = parse_btc_address(TESTNET_ADDRESS, Network::Signet).unwrap();
+        assert_eq!(address.to_string(), TESTNET_ADDRESS);
+
+        assert!(matches!(
+            parse_btc_address(MAINNET_ADDRESS, Network::Signet),
+            Err(BtcAddressError::WrongNetwork { .. })
+        ));
+    }
+}
@@ -0,0 +1,219 @@
+//! Watcher of the confirmations of the BTC transactions of the bridge operations.
+//!
+//! An operation registers its interest in an output of a transaction together with the number
+//! of confirmations it waits for. A periodic scheduler task of the bridge polls the
+//! [`ConfirmationWatcher`] with a [`ConfirmationSource`] and schedules the next steps of the
+//! operations whose outputs are confirmed. The registrations are kept in a stable structure, so
+//! they survive upgrades.
+//!
+//! [`IcUtxoConfirmations`] reads the confirmations from the utxos of the output address returned
+//! by the IC Bitcoin API. A bridge can plug another source, e.g. backed by its indexer.
+
+use std::borrow::Cow;
+use std::time::Duration;
+
+use candid::{CandidType, Decode, Encode};
+use ic_exports::ic_cdk::api::management_canister::bitcoin::{
+    bitcoin_get_utxos, BitcoinNetwork, GetUtxosRequest,
+};
+use ic_stable_structures::stable_structures::Memory;
+use ic_stable_structures::{BTreeMapStructure, Bound, StableBTreeMap, Storable};
+use serde::Deserialize;
+
+use crate::operation_store::MinterOperationId;
+
+/// Interval of polling the watched outputs.
+pub const CONFIRMATIONS_POLL_INTERVAL: Duration = Duration::from_secs(5 * 60);
+/// Time after which an output which is still not confirmed, e.g. whose transaction is dropped
+/// from the mempool, is not watched anymore.
+pub const WATCH_TIMEOUT: Duration = Duration::from_secs(3 * 24 * 60 * 60);
+
+/// Number of confirmations of a utxo mined in the block `utxo_height`.
+pub fn utxo_confirmations(tip_height: u32, utxo_height: u32) -> u32 {
+    tip_height.saturating_sub(utxo_height) + 1
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct WatchedOutput {
+    /// Transaction id in the byte order of the IC Bitcoin API.
+    pub txid: Vec<u8>,
+    pub vout: u32,
+    /// Address the output is sent to.
+    pub address: String,
+    pub required_confirmations: u32,
+    /// Timestamp (nanoseconds) of the registration.
+    pub registered_at: u64,
+}
+
+impl Storable for WatchedOutput {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("serialization failed"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).expect("deserialization failed")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// Outcome of a poll for a watched output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchEvent {
+    /// The output has the required number of confirmations.
+    Confirmed { confirmations: u32 },
+    /// The output was not confirmed within [`WATCH_TIMEOUT`].
+    TimedOut,
+}
+
+pub trait ConfirmationSource {
+    /// Number of confirmations of the output, or `None` if the output is not found, e.g. its
+    /// transaction is not mined yet.
+    async fn confirmations(&self, output: &WatchedOutput) -> Result<Option<u32>, String>;
+}
+
+/// Reads the confirmations from the utxos of the output address returned by the IC Bitcoin API.
+pub struct IcUtxoConfirmations {
+    network: BitcoinNetwork,
+}
+
+impl IcUtxoConfirmations {
+    pub fn new(network: BitcoinNetwork) -> Self {
+        Self { network }
+    }
+}
+
+impl ConfirmationSource for IcUtxoConfirmations {
+    async fn confirmations(&self, output: &WatchedOutput) -> Result<Option<u32>, String> {
+        let (response,) = bitcoin_get_utxos(GetUtxosRequest {
+            address: output.address.clone(),
+            network: self.network,
+            filter: None,
+        })
+        .await
+        .map_err(|err| format!("failed to get utxos of {}: {err:?}", output.address))?;
+
+        Ok(response
+            .utxos
+            .iter()
+            .find(|utxo| utxo.outpoint.txid == output.txid && utxo.outpoint.vout == output.vout)
+            .map(|utxo| utxo_confirmations(response.tip_height, utxo.height)))
+    }
+}
+
+/// Outputs watched by the operations. An operation watches at most one output.
+pub struct ConfirmationWatcher<M: Memory> {
+    watched: StableBTreeMap<MinterOperationId, WatchedOutput, M>,
+}
+
+impl<M: Memory> ConfirmationWatcher<M> {
+    pub fn new(memory: M) -> Self {
+        Self {
+            watched: StableBTreeMap::new(memory),
+        }
+    }
+
+    /// Registers the interest of the operation in the output, replacing its previous one.
+    pub fn watch(&mut self, operation_id: MinterOperationId, output: WatchedOutput) {
+        self.watched.insert(operation_id, output);
+    }
+
+    pub fn unwatch(&mut self, operation_id: MinterOperationId) {
+        self.watched.remove(&operation_id);
+    }
+
+    pub fn get(&self, operation_id: MinterOperationId) -> Option<WatchedOutput> {
+        self.watched.get(&operation_id)
+    }
+
+    pub fn watched(&self) -> Vec<(MinterOperationId, WatchedOutput)> {
+        self.watched.iter().collect()
+    }
+}
+
+/// Checks the `watched` outputs with the `source` and returns the events of the operations
+/// which are not watching anymore. The outputs which fail to be checked are kept watched.
+pub async fn poll_watched(
+    source: &impl ConfirmationSource,
+    watched: Vec<(MinterOperationId, WatchedOutput)>,
+    now: u64,
+) -> Vec<(MinterOperationId, WatchEvent)> {
+    let mut events = vec![];
+    for (operation_id, output) in watched {
+        match source.confirmations(&output).await {
+            Ok(Some(confirmations)) if confirmations >= output.required_confirmations => {
+                events.push((operation_id, WatchEvent::Confirmed { confirmations }));
+            }
+            Ok(_) if now.saturating_sub(output.registered_at) > WATCH_TIMEOUT.as_nanos() as u64 => {
+                events.push((operation_id, WatchEvent::TimedOut));
+            }
+            Ok(_) => {}
+            Err(err) => {
+                log::warn!("Failed to check confirmations of operation {operation_id}: {err}")
+            }
+        }
+    }
+
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use ic_stable_structures::VectorMemory;
+
+    use super::*;
+
+    struct MockSource(HashMap<Vec<u8>, Result<Option<u32>, String>>);
+
+    impl ConfirmationSource for MockSource {
+        async fn confirmations(&self, output: &WatchedOutput) -> Result<Option<u32>, String> {
+            self.0.get(&output.txid).cloned().unwrap_or(Ok(None))
+        }
+    }
+
+    fn output(txid_byte: u8, registered_at: u64) -> WatchedOutput {
+        WatchedOutput {
+            txid: vec![txid_byte; 32],
+            vout: 1,
+            address: "bcrt1q".to_string(),
+            required_confirmations: 6,
+            registered_at,
+        }
+    }
+
+    #[tokio::test]
+    async fn confirmed_and_timed_out_outputs_are_reported() {
+        let mut watcher = ConfirmationWatcher::new(VectorMemory::default());
+        let timeout = WATCH_TIMEOUT.as_nanos() as u64;
+        for (id, txid_byte, registered_at) in
+            [(1, 1, timeout), (2, 2, timeout), (3, 3, 0), (4, 4, 0)]
+        {
+            watcher.watch(
+                MinterOperationId::from(id),
+                output(txid_byte, registered_at),
+            );
+        }
+
+        let source = MockSource(HashMap::from([
+            (vec![1; 32], Ok(Some(6))),
+            (vec![2; 32], Ok(Some(5))),
+            (vec![4; 32], Err("unavailable".to_string())),
+        ]));
+        let events = poll_watched(&source, watcher.watched(), timeout + 1).await;
+
+        assert_eq!(
+            events,
+            vec![
+                (
+                    MinterOperationId::from(1),
+                    WatchEvent::Confirmed { confirmations: 6 }
+                ),
+                (MinterOperationId::from(3), WatchEvent::TimedOut),
+            ]
+        );
+        assert_eq!(utxo_confirmations(12, 12), 1);
+        assert_eq!(utxo_confirmations(12, 13), 1);
+    }
+}
@@ -0,0 +1,139 @@
+//! Bitcoin networks the bridges can be deployed to.
+//!
+//! The IC Bitcoin API serves only the mainnet, testnet and regtest networks, so the other
+//! networks are mapped onto them: the IC testnet follows testnet4, and the signet deployments run
+//! a local bitcoin canister set up as regtest and connected to a signet node.
+
+use bitcoin::Network;
+use candid::CandidType;
+use chain_registry::Chain;
+use ic_exports::ic_cdk::api::management_canister::bitcoin::BitcoinNetwork;
+use serde::Deserialize;
+
+/// Bitcoin network of a bridge. The candid names of the IC networks are the same as in
+/// [`BitcoinNetwork`], so the existing init arguments stay valid.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, CandidType, Deserialize)]
+pub enum BtcNetwork {
+    #[serde(rename = "mainnet")]
+    Mainnet,
+    #[serde(rename = "testnet")]
+    Testnet,
+    #[serde(rename = "testnet4")]
+    Testnet4,
+    #[serde(rename = "signet")]
+    Signet,
+    #[default]
+    #[serde(rename = "regtest")]
+    Regtest,
+}
+
+impl BtcNetwork {
+    /// Network of the IC Bitcoin API serving this network.
+    pub fn ic_network(self) -> BitcoinNetwork {
+        match self {
+            Self::Mainnet => BitcoinNetwork::Mainnet,
+            Self::Testnet | Self::Testnet4 => BitcoinNetwork::Testnet,
+            Self::Signet | Self::Regtest => BitcoinNetwork::Regtest,
+        }
+    }
+
+    /// Network in the `bitcoin` crate terms. Testnet4 uses the testnet address encoding.
+    pub fn network(self) -> Network {
+        match self {
+            Self::Mainnet => Network::Bitcoin,
+            Self::Testnet | Self::Testnet4 => Network::Testnet,
+            Self::Signet => Network::Signet,
+            Self::Regtest => Network::Regtest,
+        }
+    }
+
+    /// Network in the chain registry terms.
+    pub fn registry_network(self) -> chain_registry::BitcoinNetwork {
+        match self {
+            Self::Mainnet => chain_registry::BitcoinNetwork::Mainnet,
+            Self::Testnet => chain_registry::BitcoinNetwork::Testnet3,
+            Self::Testnet4 => chain_registry::BitcoinNetwork::Testnet4,
+            Self::Signet => chain_registry::BitcoinNetwork::Signet,
+            Self::Regtest => chain_registry::BitcoinNetwork::Regtest,
+        }
+    }
+
+    /// Chain id of the BTC of the network in the mint orders.
+    pub fn chain_id(self) -> u32 {
+        Chain::btc(self.registry_network()).id()
+    }
+
+    /// Chain id of the runes of the network in the mint orders.
+    pub fn rune_chain_id(self) -> u32 {
+        Chain::rune(self.registry_network()).id()
+    }
+
+    /// Id of the BTC and rune chains of the network in the mint orders signed before the chain
+    /// registry, see [`Chain::legacy_id`].
+    pub fn legacy_chain_id(self) -> u32 {
+        self.registry_network().legacy_chain_id()
+    }
+}
+
+impl From<BitcoinNetwork> for BtcNetwork {
+    fn from(network: BitcoinNetwork) -> Self {
+        match network {
+            BitcoinNetwork::Mainnet => Self::Mainnet,
+            BitcoinNetwork::Testnet => Self::Testnet,
+            BitcoinNetwork::Regtest => Self::Regtest,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use candid::{Decode, Encode};
+
+    use super::*;
+
+    #[test]
+    fn ic_networks_are_converted() {
+        for network in [
+            BitcoinNetwork::Mainnet,
+            BitcoinNetwork::Testnet,
+            BitcoinNetwork::Regtest,
+        ] {
+            assert_eq!(BtcNetwork::from(network).ic_network(), network);
+        }
+
+        assert_eq!(BtcNetwork::Mainnet.network(), Network::Bitcoin);
+        assert_eq!(BtcNetwork::Testnet4.network(), Network::Testnet);
+        assert_eq!(BtcNetwork::Signet.network(), Network::Signet);
+        assert_eq!(BtcNetwork::Signet.ic_network(), BitcoinNetwork::Regtest);
+    }
+
+    #[test]
+    fn ic_network_args_are_decoded() {
+        let encoded = Encode!(&BitcoinNetwork::Testnet).unwrap();
+        assert_eq!(Decode!(&encoded, BtcNetwork).unwrap(), BtcNetwork::Testnet);
+    }
+
+    #[test]
+    fn chain_ids_are_distinct() {
+        let networks = [
+            BtcNetwork::Mainnet,
+            BtcNetwork::Testnet,
+            BtcNetwork::Testnet4,
+            BtcNetwork::Signet,
+            BtcNetwork::Regtest,
+        ];
+        for (i, network) in networks.iter().enumerate() {
+            for other in &networks[i + 1..] {
+                assert_ne!(network.chain_id(), other.chain_id());
+                assert_ne!(network.rune_chain_id(), other.rune_chain_id());
+            }
+            assert_ne!(network.chain_id(), network.rune_chain_id());
+        }
+
+        assert_eq!(BtcNetwork::Mainnet.legacy_chain_id(), 0);
+        assert_eq!(BtcNetwork::Testnet.legacy_chain_id(), 1);
+        assert_eq!(BtcNetwork::Regtest.legacy_chain_id(), 2);
+        assert_eq!(BtcNetwork::Testnet4.legacy_chain_id(), 3);
+        assert_eq!(BtcNetwork::Signet.legacy_chain_id(), 4);
+    }
+}
@@ -38,6 +38,38 @@ pub static FEE_CHARGE_SMART_CONTRACT_CODE: Lazy<Vec<u8>> =
 pub static UUPS_PROXY_SMART_CONTRACT_CODE: Lazy<Vec<u8>> =
     Lazy::new(|| get_contract_code(BUILD_SMART_CONTRACT_UUPS_PROXY_HEX_CODE));
 
+/// Returns the [`BuildData`](did::build::BuildData) of the calling canister crate.
+///
+/// The macro is expanded in the caller, so the package data and the `VERGEN_*` variables are the
+/// ones of the caller. The caller's build script must emit the variables with `vergen`.
+#[macro_export]
+macro_rules! canister_build_data {
+    () => {
+        ::did::build::BuildData {
+            // E.g.: x86_64-unknown-linux-gnu
+            cargo_target_triple: env!("VERGEN_CARGO_TARGET_TRIPLE").to_string(),
+            // E.g.: default
+            cargo_features: env!("VERGEN_CARGO_FEATURES").to_string(),
+            // E.g.: evm
+            pkg_name: env!("CARGO_PKG_NAME").to_string(),
+            // E.g.: 0.1.0
+            pkg_version: env!("CARGO_PKG_VERSION").to_string(),
+            // E.g.: 1.64.0
+            rustc_semver: env!("VERGEN_RUSTC_SEMVER").to_string(),
+            // E.g.: 2022-12-23T15:29:20.000000000Z
+            build_timestamp: env!("VERGEN_BUILD_TIMESTAMP").to_string(),
+            // E.g.: true/false
+            cargo_debug: env!("VERGEN_CARGO_DEBUG").to_string(),
+            // E.g.: main
+            git_branch: env!("VERGEN_GIT_BRANCH").to_string(),
+            // E.g.: acf6c5744b1f4f29c5960a25f4fb4056e2ceedc3
+            git_sha: env!("VERGEN_GIT_SHA").to_string(),
+            // E.g.: 2022-12-23T15:29:20.000000000Z
+            git_commit_timestamp: env!("VERGEN_GIT_COMMIT_TIMESTAMP").to_string(),
+        }
+    };
+}
+
 #[cfg(feature = "test-contracts")]
 pub mod test_contracts {
     use once_cell::sync::Lazy;
@@ -55,7 +87,7 @@ pub mod test_contracts {
     pub static WRAPPED_TOKEN_SMART_CONTRACT_CODE: Lazy<Vec<u8>> =
         Lazy::new(|| get_contract_code(BUILD_SMART_CONTRACT_WRAPPED_TOKEN_HEX_CODE));
 
-    /// Uniswap token contract bytecode
+    /// WatermelonToken test ERC-20 contract bytecode
     pub static TEST_WTM_HEX_CODE: Lazy<Vec<u8>> =
         Lazy::new(|| get_contract_code(BUILD_SMART_CONTRACT_TEST_WTM_HEX_CODE));
 }
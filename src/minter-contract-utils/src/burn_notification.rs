@@ -0,0 +1,247 @@
+//! Withdrawals initiated by a notification of the burn transaction.
+//!
+//! The bridges find the `Burnt` events by scanning the BftBridge logs from the next unscanned
+//! block, so a withdrawal starts only on the next scan. A wallet knows the hash of its burn
+//! transaction already and can pass it to the `notify_burn` endpoint of the bridge, which fetches
+//! the receipt with [`burnt_logs`] and schedules the withdrawals of its `Burnt` events at once.
+//!
+//! An event starts one withdrawal either way. A notification is accepted only for the events in
+//! the blocks the scan has not reached yet, and [`NotifiedEvents`] keeps the notified events
+//! until the scan passes their blocks, so the scan skips them.
+
+use std::borrow::Cow;
+
+use candid::CandidType;
+use did::{H160, H256};
+use ethereum_json_rpc_client::{Client, EthJsonRpcClient};
+use ethers_core::abi::RawLog;
+use ethers_core::types::Log;
+use ic_stable_structures::stable_structures::Memory;
+use ic_stable_structures::{BTreeMapStructure, Bound, StableBTreeMap, Storable};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::bft_bridge_api::BurntEventData;
+
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize, Error)]
+pub enum NotifyBurnError {
+    #[error("bridge is not initialized")]
+    NotInitialized,
+    #[error("transaction receipt is unavailable: {0}")]
+    ReceiptUnavailable(String),
+    #[error("transaction has failed")]
+    TxFailed,
+    #[error("transaction has no Burnt events of the bridge")]
+    NoBurntEvents,
+    /// The events are notified already or are collected by the scan of the bridge logs.
+    #[error("events of the transaction are processed already")]
+    AlreadyProcessed,
+}
+
+/// Position of an event in the chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct EventKey {
+    pub block_number: u64,
+    pub tx_hash: [u8; 32],
+    pub log_index: u64,
+}
+
+impl EventKey {
+    pub const SIZE: usize = 8 + 32 + 8;
+
+    /// Key of a mined log, or `None` if the log is pending.
+    pub fn from_log(log: &Log) -> Option<Self> {
+        Some(Self {
+            block_number: log.block_number?.as_u64(),
+            tx_hash: log.transaction_hash?.0,
+            log_index: log.log_index?.as_u64(),
+        })
+    }
+}
+
+impl Storable for EventKey {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        let mut bytes = Vec::with_capacity(Self::SIZE);
+        bytes.extend_from_slice(&self.block_number.to_be_bytes());
+        bytes.extend_from_slice(&self.tx_hash);
+        bytes.extend_from_slice(&self.log_index.to_be_bytes());
+        Cow::Owned(bytes)
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        let block_number = u64::from_be_bytes(bytes[..8].try_into().expect("invalid key"));
+        let tx_hash = bytes[8..40].try_into().expect("invalid key");
+        let log_index = u64::from_be_bytes(bytes[40..].try_into().expect("invalid key"));
+        Self {
+            block_number,
+            tx_hash,
+            log_index,
+        }
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: Self::SIZE as u32,
+        is_fixed_size: true,
+    };
+}
+
+/// Events notified to the bridge, with the time (nanoseconds) of the notification, in the
+/// blocks not scanned yet.
+pub struct NotifiedEvents<M: Memory> {
+    events: StableBTreeMap<EventKey, u64, M>,
+}
+
+impl<M: Memory> NotifiedEvents<M> {
+    pub fn new(memory: M) -> Self {
+        Self {
+            events: StableBTreeMap::new(memory),
+        }
+    }
+
+    /// Accepts the notified `logs` which are in the blocks from `next_block`, the next block to
+    /// be scanned, and are not notified yet. The accepted logs are recorded, so they must be
+    /// processed by the caller.
+    pub fn accept(
+        &mut self,
+        logs: Vec<Log>,
+        next_block: u64,
+        now: u64,
+    ) -> Result<Vec<Log>, NotifyBurnError> {
+        let accepted: Vec<Log> = logs
+            .into_iter()
+            .filter(|log| {
+                EventKey::from_log(log).is_some_and(|key| {
+                    key.block_number >= next_block && !self.events.contains_key(&key)
+                })
+            })
+            .collect();
+
+        if accepted.is_empty() {
+            return Err(NotifyBurnError::AlreadyProcessed);
+        }
+
+        for key in accepted.iter().filter_map(EventKey::from_log) {
+            self.events.insert(key, now);
+        }
+
+        Ok(accepted)
+    }
+
+    /// Drops the notified logs from the `logs` collected by a scan, and forgets the events in the
+    /// blocks before `next_block`, which are not scanned again.
+    pub fn filter_scanned(&mut self, logs: Vec<Log>, next_block: u64) -> Vec<Log> {
+        let logs = logs
+            .into_iter()
+            .filter(|log| {
+                EventKey::from_log(log).map_or(true, |key| !self.events.contains_key(&key))
+            })
+            .collect();
+
+        let scanned: Vec<EventKey> = self
+            .events
+            .iter()
+            .map(|(key, _)| key)
+            .take_while(|key| key.block_number < next_block)
+            .collect();
+        for key in scanned {
+            self.events.remove(&key);
+        }
+
+        logs
+    }
+
+    pub fn len(&self) -> u64 {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.len() == 0
+    }
+}
+
+/// Fetches the receipt of the transaction and returns its `Burnt` logs emitted by the
+/// `bridge_contract`.
+pub async fn burnt_logs(
+    client: &EthJsonRpcClient<impl Client>,
+    tx_hash: &H256,
+    bridge_contract: &H160,
+) -> Result<Vec<Log>, NotifyBurnError> {
+    let receipt = client
+        .get_receipt_by_hash(tx_hash.0)
+        .await
+        .map_err(|err| NotifyBurnError::ReceiptUnavailable(err.to_string()))?;
+
+    if receipt.status != Some(1u64.into()) {
+        return Err(NotifyBurnError::TxFailed);
+    }
+
+    let logs: Vec<Log> = receipt
+        .logs
+        .into_iter()
+        .filter(|log| log.address == bridge_contract.0 && is_burnt_log(log))
+        .collect();
+
+    if logs.is_empty() {
+        return Err(NotifyBurnError::NoBurntEvents);
+    }
+
+    Ok(logs)
+}
+
+fn is_burnt_log(log: &Log) -> bool {
+    BurntEventData::try_from(RawLog {
+        topics: log.topics.clone(),
+        data: log.data.to_vec(),
+    })
+    .is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use ic_stable_structures::VectorMemory;
+
+    use super::*;
+
+    fn log(block_number: u64, log_index: u64) -> Log {
+        Log {
+            block_number: Some(block_number.into()),
+            transaction_hash: Some([block_number as u8; 32].into()),
+            log_index: Some(log_index.into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn event_keys_are_ordered_by_block() {
+        let key = EventKey::from_log(&log(300, 2)).unwrap();
+        assert_eq!(EventKey::from_bytes(key.to_bytes()), key);
+        assert!(key < EventKey::from_log(&log(301, 0)).unwrap());
+        assert_eq!(EventKey::from_log(&Log::default()), None);
+    }
+
+    #[test]
+    fn events_are_processed_once() {
+        let mut notified = NotifiedEvents::new(VectorMemory::default());
+
+        assert_eq!(
+            notified.accept(vec![log(9, 0)], 10, 0),
+            Err(NotifyBurnError::AlreadyProcessed)
+        );
+        assert_eq!(
+            notified.accept(vec![log(9, 0), log(10, 0), log(10, 1)], 10, 0),
+            Ok(vec![log(10, 0), log(10, 1)])
+        );
+        assert_eq!(
+            notified.accept(vec![log(10, 1)], 10, 0),
+            Err(NotifyBurnError::AlreadyProcessed)
+        );
+        notified.accept(vec![log(12, 0)], 10, 0).unwrap();
+
+        let scanned = notified.filter_scanned(vec![log(10, 0), log(10, 2), log(11, 0)], 12);
+        assert_eq!(scanned, vec![log(10, 2), log(11, 0)]);
+        assert_eq!(notified.len(), 1);
+
+        assert_eq!(notified.filter_scanned(vec![log(12, 0)], 13), vec![]);
+        assert!(notified.is_empty());
+    }
+}
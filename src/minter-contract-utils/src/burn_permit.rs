@@ -0,0 +1,125 @@
+//! Burns of the wrapped tokens approved with ERC-2612 permits.
+//!
+//! Instead of sending an approve and a burn transaction, a holder of a wrapped token signs a
+//! permit for the BftBridge and passes it to the bridge canister, which relays the burn with
+//! `burnWithPermit`. The ERC-2612 permit doesn't cover the burn recipient, so the holder also
+//! signs the EIP-712 `BurnPermit` of the BftBridge over the amount, the token, the recipient, the
+//! deadline and the holder's `burnPermitNonces`, and the BftBridge checks both signatures.
+
+use candid::CandidType;
+use did::{H160, H256, U256};
+use ethereum_json_rpc_client::{Client, EthJsonRpcClient};
+use ethers_core::abi::Token;
+use ethers_core::types::{BlockNumber, TransactionRequest};
+use serde::{Deserialize, Serialize};
+
+use crate::bft_bridge_api::BURN_WITH_PERMIT;
+use crate::gas_limits::DEFAULT_TX_GAS_LIMIT;
+
+/// Permit of the BftBridge to burn `amount` of the `from_erc20` tokens of the `from` address,
+/// signed by the token holder.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Serialize, Deserialize)]
+pub struct BurnPermit {
+    pub from: H160,
+    pub amount: U256,
+    pub from_erc20: H160,
+    /// Recipient of the burn on the other side of the bridge, as in `burn`.
+    pub recipient_id: Vec<u8>,
+    /// Timestamp (seconds) after which the permit is not valid.
+    pub deadline: U256,
+    pub v: u8,
+    pub r: H256,
+    pub s: H256,
+    /// Signature of the `from` address over the `burnPermitDigest` of the BftBridge.
+    pub burn_signature: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub enum BurnPermitError {
+    /// The deadline of the permit is in the past.
+    Expired,
+    /// The permit is for a token which is not wrapped by the bridge.
+    UnknownToken(H160),
+    /// The burn is rejected by the BftBridge, e.g. for an invalid permit signature.
+    Rejected(String),
+    /// The bridge is not initialized yet.
+    NotInitialized,
+    Sign(String),
+    Evm(String),
+}
+
+impl BurnPermit {
+    /// Input of the `burnWithPermit` call.
+    pub fn call_data(&self) -> Vec<u8> {
+        BURN_WITH_PERMIT
+            .encode_input(&[
+                Token::Address(self.from.0),
+                Token::Uint(self.amount.0),
+                Token::Address(self.from_erc20.0),
+                Token::Bytes(self.recipient_id.clone()),
+                Token::Uint(self.deadline.0),
+                Token::Uint(self.v.into()),
+                Token::FixedBytes(self.r.0.as_bytes().to_vec()),
+                Token::FixedBytes(self.s.0.as_bytes().to_vec()),
+                Token::Bytes(self.burn_signature.clone()),
+            ])
+            .expect("burn with permit encoding should pass")
+    }
+
+    pub fn check_deadline(&self, now_secs: u64) -> Result<(), BurnPermitError> {
+        if self.deadline.0 < now_secs.into() {
+            return Err(BurnPermitError::Expired);
+        }
+
+        Ok(())
+    }
+
+    /// Executes the burn with `eth_call`, so the bridge doesn't pay for the transactions with
+    /// invalid permits.
+    pub async fn simulate(
+        &self,
+        client: &EthJsonRpcClient<impl Client>,
+        sender: &H160,
+        bridge: &H160,
+    ) -> Result<(), BurnPermitError> {
+        client
+            .eth_call(
+                TransactionRequest {
+                    from: Some(sender.0),
+                    to: Some(bridge.0.into()),
+                    gas: Some(DEFAULT_TX_GAS_LIMIT.into()),
+                    data: Some(self.call_data().into()),
+                    ..Default::default()
+                },
+                BlockNumber::Latest,
+            )
+            .await
+            .map_err(|err| BurnPermitError::Rejected(format!("{err}")))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expired_permits_are_rejected() {
+        let permit = BurnPermit {
+            from: H160::from_slice(&[1; 20]),
+            amount: 100u64.into(),
+            from_erc20: H160::from_slice(&[2; 20]),
+            recipient_id: vec![3; 20],
+            deadline: 1_000u64.into(),
+            v: 28,
+            r: H256::from_slice(&[4; 32]),
+            s: H256::from_slice(&[5; 32]),
+            burn_signature: vec![6; 65],
+        };
+
+        assert_eq!(permit.check_deadline(999), Ok(()));
+        assert_eq!(permit.check_deadline(1_000), Ok(()));
+        assert_eq!(permit.check_deadline(1_001), Err(BurnPermitError::Expired));
+    }
+}
@@ -0,0 +1,132 @@
+//! Resource usage of the bridge canisters.
+//!
+//! Every bridge returns a [`CanisterStatusInfo`] from the `canister_status_info` query, so the
+//! monitoring dashboards can watch the cycles and the memory of all the bridges in the same way,
+//! without being controllers of the canisters.
+
+use candid::CandidType;
+use ic_stable_structures::stable_structures::Memory;
+use ic_stable_structures::MemoryId;
+use serde::Deserialize;
+
+/// Size of the wasm and of the stable memory pages in bytes.
+pub const WASM_PAGE_SIZE: u64 = 64 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct StableMemoryUsage {
+    /// Name of the stable structure.
+    pub name: String,
+    /// Size of the virtual memory of the structure in bytes.
+    pub size_bytes: u64,
+}
+
+impl StableMemoryUsage {
+    pub fn new(name: &str, memory: &impl Memory) -> Self {
+        Self {
+            name: name.to_string(),
+            size_bytes: memory.size() * WASM_PAGE_SIZE,
+        }
+    }
+}
+
+/// Usage of the named `structures`, taking their memories from `get_memory`.
+pub fn stable_structures_usage<M: Memory>(
+    structures: &[(&str, MemoryId)],
+    get_memory: impl Fn(MemoryId) -> M,
+) -> Vec<StableMemoryUsage> {
+    structures
+        .iter()
+        .map(|(name, memory_id)| StableMemoryUsage::new(name, &get_memory(*memory_id)))
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct CanisterStatusInfo {
+    pub cycles_balance: u128,
+    pub heap_memory_bytes: u64,
+    /// Size of the whole stable memory, including the memory manager bookkeeping.
+    pub stable_memory_bytes: u64,
+    pub stable_structures: Vec<StableMemoryUsage>,
+    /// Number of the tasks in the scheduler, including the ones waiting for a retry.
+    pub scheduler_queue_depth: u64,
+}
+
+impl CanisterStatusInfo {
+    /// Status of the running canister with the given stable structures usage.
+    pub fn collect(stable_structures: Vec<StableMemoryUsage>, scheduler_queue_depth: u64) -> Self {
+        Self {
+            cycles_balance: cycles_balance(),
+            heap_memory_bytes: heap_memory_bytes(),
+            stable_memory_bytes: stable_memory_bytes(),
+            stable_structures,
+            scheduler_queue_depth,
+        }
+    }
+}
+
+fn cycles_balance() -> u128 {
+    #[cfg(target_family = "wasm")]
+    return ic_exports::ic_cdk::api::canister_balance128();
+
+    #[cfg(not(target_family = "wasm"))]
+    0
+}
+
+fn heap_memory_bytes() -> u64 {
+    #[cfg(target_arch = "wasm32")]
+    return core::arch::wasm32::memory_size(0) as u64 * WASM_PAGE_SIZE;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    0
+}
+
+fn stable_memory_bytes() -> u64 {
+    #[cfg(target_family = "wasm")]
+    return ic_exports::ic_cdk::api::stable::stable64_size() * WASM_PAGE_SIZE;
+
+    #[cfg(not(target_family = "wasm"))]
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use ic_stable_structures::VectorMemory;
+
+    use super::*;
+
+    #[test]
+    fn structure_usage_is_counted_in_bytes() {
+        let memory = VectorMemory::default();
+        assert_eq!(StableMemoryUsage::new("empty", &memory).size_bytes, 0);
+
+        memory.grow(3);
+        assert_eq!(
+            StableMemoryUsage::new("grown", &memory),
+            StableMemoryUsage {
+                name: "grown".to_string(),
+                size_bytes: 3 * WASM_PAGE_SIZE,
+            }
+        );
+    }
+
+    #[test]
+    fn structures_usage_keeps_order() {
+        let first = VectorMemory::default();
+        let second = VectorMemory::default();
+        second.grow(1);
+
+        let usage = stable_structures_usage(
+            &[("first", MemoryId::new(0)), ("second", MemoryId::new(1))],
+            |memory_id| match memory_id {
+                id if id == MemoryId::new(0) => first.clone(),
+                _ => second.clone(),
+            },
+        );
+
+        assert_eq!(usage.len(), 2);
+        assert_eq!(usage[0].name, "first");
+        assert_eq!(usage[0].size_bytes, 0);
+        assert_eq!(usage[1].name, "second");
+        assert_eq!(usage[1].size_bytes, WASM_PAGE_SIZE);
+    }
+}
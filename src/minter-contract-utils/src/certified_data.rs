@@ -0,0 +1,153 @@
+//! Certified variables of the bridge canisters.
+//!
+//! A canister can certify only 32 bytes of data, so the critical values, e.g. the configuration
+//! or the reserves, are certified as labeled leaves of a hash tree whose root is the certified
+//! data. The leaf of a value is the SHA-256 hash of its candid encoding, and the root is the
+//! SHA-256 hash of the concatenated `sha256(label) || leaf` pairs ordered by the label.
+//!
+//! A [`Certified`] response carries all the leaves together with the certificate, so the client
+//! checks the root in the certificate, recomputes the leaf of the returned value and compares it
+//! with the certified leaf.
+//!
+//! The leaves are kept in the heap, so the canisters certify their values again after upgrade.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+use bitcoin::hashes::{sha256, Hash, HashEngine};
+use candid::{CandidType, Encode};
+use did::H256;
+use serde::Deserialize;
+
+/// Label of the bridge configuration.
+pub const CONFIG_LABEL: &str = "config";
+/// Label of the reserves of the base asset held by the bridge.
+pub const RESERVES_LABEL: &str = "reserves";
+/// Label of the hash of the last entry of the bridge transactions log.
+pub const TX_LOG_TIP_LABEL: &str = "tx_log_tip";
+
+thread_local! {
+    static LEAVES: RefCell<BTreeMap<String, H256>> = RefCell::default();
+}
+
+/// Response of a certified query.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct Certified<T> {
+    pub value: T,
+    /// Label of the value in the hash tree.
+    pub label: String,
+    /// All the certified leaves, ordered by the label.
+    pub leaves: Vec<(String, H256)>,
+    /// Certificate of the root of the tree. Only returned by the query calls.
+    pub certificate: Option<Vec<u8>>,
+}
+
+impl<T: CandidType> Certified<T> {
+    /// Wraps the value certified with the `label` into a response.
+    pub fn new(label: &str, value: T) -> Self {
+        Self {
+            value,
+            label: label.to_string(),
+            leaves: leaves(),
+            certificate: certificate(),
+        }
+    }
+}
+
+/// Hash of the candid encoding of the value.
+pub fn value_hash(value: &impl CandidType) -> H256 {
+    let bytes = Encode!(value).expect("serialization failed");
+    H256::from_slice(sha256::Hash::hash(&bytes).as_byte_array())
+}
+
+/// Certifies the value with the label.
+pub fn certify_value(label: &str, value: &impl CandidType) {
+    certify_hash(label, value_hash(value));
+}
+
+/// Certifies the hash with the label, e.g. the tip of a hash chain.
+pub fn certify_hash(label: &str, hash: H256) {
+    LEAVES.with(|leaves| leaves.borrow_mut().insert(label.to_string(), hash));
+    set_certified_data(&root_hash());
+}
+
+pub fn leaves() -> Vec<(String, H256)> {
+    LEAVES.with(|leaves| {
+        leaves
+            .borrow()
+            .iter()
+            .map(|(label, hash)| (label.clone(), hash.clone()))
+            .collect()
+    })
+}
+
+/// Root of the tree, which is set as the certified data of the canister.
+pub fn root_hash() -> H256 {
+    compute_root(&leaves())
+}
+
+/// Computes the root of the tree from its leaves, as the clients do to check a response.
+pub fn compute_root(leaves: &[(String, H256)]) -> H256 {
+    let mut sorted: Vec<_> = leaves.iter().collect();
+    sorted.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut engine = sha256::Hash::engine();
+    for (label, hash) in sorted {
+        engine.input(sha256::Hash::hash(label.as_bytes()).as_byte_array());
+        engine.input(hash.0.as_bytes());
+    }
+
+    H256::from_slice(sha256::Hash::from_engine(engine).as_byte_array())
+}
+
+fn set_certified_data(_root: &H256) {
+    #[cfg(target_family = "wasm")]
+    ic_exports::ic_cdk::api::set_certified_data(_root.0.as_bytes());
+}
+
+fn certificate() -> Option<Vec<u8>> {
+    #[cfg(target_family = "wasm")]
+    return ic_exports::ic_cdk::api::data_certificate();
+
+    #[cfg(not(target_family = "wasm"))]
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn root_commits_to_all_leaves() {
+        certify_value(CONFIG_LABEL, &42u64);
+        let config_root = root_hash();
+
+        certify_value(RESERVES_LABEL, &1000u64);
+        let root = root_hash();
+        assert_ne!(root, config_root);
+
+        let response = Certified::new(RESERVES_LABEL, 1000u64);
+        assert_eq!(compute_root(&response.leaves), root);
+        assert!(response
+            .leaves
+            .contains(&(RESERVES_LABEL.to_string(), value_hash(&response.value))));
+
+        certify_value(RESERVES_LABEL, &1001u64);
+        assert_ne!(root_hash(), root);
+    }
+
+    #[test]
+    fn root_does_not_depend_on_leaves_order() {
+        let a = ("a".to_string(), value_hash(&1u8));
+        let b = ("b".to_string(), value_hash(&2u8));
+
+        assert_eq!(
+            compute_root(&[a.clone(), b.clone()]),
+            compute_root(&[b.clone(), a.clone()])
+        );
+        assert_ne!(
+            compute_root(&[a.clone(), b.clone()]),
+            compute_root(&[(a.0.clone(), b.1.clone()), (b.0, a.1)])
+        );
+    }
+}
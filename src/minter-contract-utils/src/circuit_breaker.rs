@@ -0,0 +1,268 @@
+//! Automatic pause of the deposits on dependency failures.
+//!
+//! The bridges record the outcomes of the calls to their dependencies, e.g. the indexer and the
+//! EVM RPC, in a [`CircuitBreaker`]. When the share of the failed calls to a dependency over the
+//! sliding window reaches the threshold, the circuit opens and the deposits are paused, so the
+//! bridge doesn't keep issuing calls which fail and doesn't process the deposits with partial
+//! data. The circuit closes after the cool-down, and the calls are counted from scratch.
+//!
+//! The breaker is kept in the heap, so it is closed after an upgrade.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use candid::CandidType;
+use serde::Deserialize;
+
+/// Maximum number of outcomes kept per dependency in the window.
+const MAX_WINDOW_CALLS: usize = 1024;
+
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct CircuitBreakerConfig {
+    /// Length of the sliding window in seconds.
+    pub window_secs: u64,
+    /// Number of calls to a dependency in the window required to evaluate its error rate.
+    pub min_calls: u32,
+    /// Percentage of the failed calls at which the circuit opens.
+    pub max_error_rate_percent: u8,
+    /// Time in seconds the circuit stays open.
+    pub cool_down_secs: u64,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            window_secs: 10 * 60,
+            min_calls: 10,
+            max_error_rate_percent: 50,
+            cool_down_secs: 15 * 60,
+        }
+    }
+}
+
+impl CircuitBreakerConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.window_secs == 0 {
+            return Err("window must be positive".into());
+        }
+        if self.min_calls == 0 {
+            return Err("minimum number of calls must be positive".into());
+        }
+        if !(1..=100).contains(&self.max_error_rate_percent) {
+            return Err(format!(
+                "error rate must be between 1 and 100 percent, got {}",
+                self.max_error_rate_percent
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub enum CircuitState {
+    /// The deposits are processed.
+    #[default]
+    Closed,
+    /// The deposits are paused until `resumes_at` (nanoseconds).
+    Open {
+        dependency: String,
+        error_rate_percent: u8,
+        opened_at: u64,
+        resumes_at: u64,
+    },
+}
+
+/// Calls to a dependency in the current window.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct DependencyCalls {
+    pub name: String,
+    pub calls: u32,
+    pub errors: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct CircuitBreakerStatus {
+    pub state: CircuitState,
+    pub config: CircuitBreakerConfig,
+    /// Number of times the circuit opened since the canister was installed or upgraded.
+    pub trips: u64,
+    pub dependencies: Vec<DependencyCalls>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    /// Timestamps (nanoseconds) and outcomes of the calls, oldest first.
+    calls: BTreeMap<String, VecDeque<(u64, bool)>>,
+    state: CircuitState,
+    trips: u64,
+}
+
+impl CircuitBreaker {
+    pub fn config(&self) -> &CircuitBreakerConfig {
+        &self.config
+    }
+
+    pub fn configure(&mut self, config: CircuitBreakerConfig) -> Result<(), String> {
+        config.validate()?;
+        self.config = config;
+        Ok(())
+    }
+
+    /// Records the outcome of a call to the `dependency` made at `now`, and opens the circuit if
+    /// the error rate of the dependency reaches the threshold.
+    pub fn record(&mut self, dependency: &str, success: bool, now: u64) {
+        let window_start = now.saturating_sub(self.config.window_secs * NANOS_PER_SEC);
+        let calls = self.calls.entry(dependency.to_string()).or_default();
+        calls.push_back((now, success));
+        while calls.len() > MAX_WINDOW_CALLS
+            || calls.front().is_some_and(|(ts, _)| *ts < window_start)
+        {
+            calls.pop_front();
+        }
+
+        if self.state != CircuitState::Closed {
+            return;
+        }
+
+        let total = calls.len() as u64;
+        let errors = calls.iter().filter(|(_, success)| !success).count() as u64;
+        if total < u64::from(self.config.min_calls) {
+            return;
+        }
+
+        let error_rate_percent = (errors * 100 / total) as u8;
+        if error_rate_percent >= self.config.max_error_rate_percent {
+            let resumes_at = now + self.config.cool_down_secs * NANOS_PER_SEC;
+            log::warn!(
+                "Circuit breaker opened: {errors} of {total} calls to {dependency} failed, deposits are paused until {resumes_at}"
+            );
+            self.state = CircuitState::Open {
+                dependency: dependency.to_string(),
+                error_rate_percent,
+                opened_at: now,
+                resumes_at,
+            };
+            self.trips += 1;
+        }
+    }
+
+    /// Whether the deposits are processed at `now`. Closes the circuit if its cool-down is over.
+    pub fn allows(&mut self, now: u64) -> bool {
+        if let CircuitState::Open { resumes_at, .. } = self.state {
+            if now < resumes_at {
+                return false;
+            }
+
+            log::info!("Circuit breaker closed, deposits are resumed");
+            self.state = CircuitState::Closed;
+            self.calls.clear();
+        }
+
+        true
+    }
+
+    pub fn status(&self, now: u64) -> CircuitBreakerStatus {
+        let state = match &self.state {
+            CircuitState::Open { resumes_at, .. } if now >= *resumes_at => CircuitState::Closed,
+            state => state.clone(),
+        };
+
+        CircuitBreakerStatus {
+            state,
+            config: self.config.clone(),
+            trips: self.trips,
+            dependencies: self
+                .calls
+                .iter()
+                .map(|(name, calls)| DependencyCalls {
+                    name: name.clone(),
+                    calls: calls.len() as u32,
+                    errors: calls.iter().filter(|(_, success)| !success).count() as u32,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEC: u64 = NANOS_PER_SEC;
+
+    fn breaker() -> CircuitBreaker {
+        let mut breaker = CircuitBreaker::default();
+        breaker
+            .configure(CircuitBreakerConfig {
+                window_secs: 60,
+                min_calls: 4,
+                max_error_rate_percent: 60,
+                cool_down_secs: 120,
+            })
+            .unwrap();
+        breaker
+    }
+
+    #[test]
+    fn opens_on_error_rate_and_resumes_after_cool_down() {
+        let mut breaker = breaker();
+        breaker.record("indexer", false, 0);
+        breaker.record("indexer", false, SEC);
+        breaker.record("evm_rpc", false, SEC);
+        breaker.record("indexer", true, 2 * SEC);
+        breaker.record("indexer", true, 3 * SEC);
+        assert!(breaker.allows(3 * SEC));
+
+        // The window is 60 s, so the first failures are dropped from it.
+        breaker.record("indexer", false, 61 * SEC);
+        breaker.record("indexer", false, 62 * SEC);
+        assert!(breaker.allows(62 * SEC));
+        breaker.record("indexer", false, 63 * SEC);
+        assert!(!breaker.allows(63 * SEC));
+
+        let status = breaker.status(63 * SEC);
+        assert_eq!(
+            status.state,
+            CircuitState::Open {
+                dependency: "indexer".into(),
+                error_rate_percent: 75,
+                opened_at: 63 * SEC,
+                resumes_at: 183 * SEC,
+            }
+        );
+        assert_eq!(status.trips, 1);
+
+        assert!(!breaker.allows(182 * SEC));
+        assert_eq!(breaker.status(183 * SEC).state, CircuitState::Closed);
+        assert!(breaker.allows(183 * SEC));
+        assert!(breaker.status(183 * SEC).dependencies.is_empty());
+    }
+
+    #[test]
+    fn invalid_config_is_rejected() {
+        let mut breaker = CircuitBreaker::default();
+        let config = CircuitBreakerConfig::default();
+        assert!(breaker
+            .configure(CircuitBreakerConfig {
+                max_error_rate_percent: 0,
+                ..config.clone()
+            })
+            .is_err());
+        assert!(breaker
+            .configure(CircuitBreakerConfig {
+                max_error_rate_percent: 101,
+                ..config.clone()
+            })
+            .is_err());
+        assert!(breaker
+            .configure(CircuitBreakerConfig {
+                window_secs: 0,
+                ..config.clone()
+            })
+            .is_err());
+        assert_eq!(breaker.config(), &config);
+    }
+}
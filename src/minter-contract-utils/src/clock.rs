@@ -0,0 +1,78 @@
+//! Source of the current time for the time-dependent logic of the bridges.
+//!
+//! The expiry, retry and timeout checks of the bridges read the time from a [`Clock`] kept in
+//! their state instead of calling `ic::time` directly. The canisters use the [`IcClock`], and the
+//! tests replace it with a [`MockClock`] to move the time forward without waiting.
+
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use ic_exports::ic_kit::ic;
+
+pub trait Clock {
+    /// Current time in nanoseconds since the Unix epoch.
+    fn now(&self) -> u64;
+
+    /// Current time in seconds since the Unix epoch.
+    fn now_secs(&self) -> u64 {
+        self.now() / 1_000_000_000
+    }
+}
+
+/// Time of the IC.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct IcClock;
+
+impl Clock for IcClock {
+    fn now(&self) -> u64 {
+        ic::time()
+    }
+}
+
+/// Clock whose time is set by the test. The clones share the time, so a test can keep a clone
+/// to move the time of the clock it injected.
+#[derive(Debug, Default, Clone)]
+pub struct MockClock {
+    now: Rc<Cell<u64>>,
+}
+
+impl MockClock {
+    pub fn new(now: u64) -> Self {
+        Self {
+            now: Rc::new(Cell::new(now)),
+        }
+    }
+
+    pub fn set(&self, now: u64) {
+        self.now.set(now);
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration.as_nanos() as u64);
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> u64 {
+        self.now.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mock_clock_is_shared_by_clones() {
+        let clock = MockClock::new(1_000_000_000);
+        let injected: Rc<dyn Clock> = Rc::new(clock.clone());
+        assert_eq!(injected.now_secs(), 1);
+
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(injected.now_secs(), 61);
+
+        clock.set(5);
+        assert_eq!(injected.now(), 5);
+    }
+}
@@ -0,0 +1,149 @@
+//! Protection of the bridge configuration against accidental overwrites.
+//!
+//! The configuration of a bridge is set by `init`, and a deployment script run once more against
+//! a configured bridge must not silently replace it together with the signer. The guard counts
+//! the applied configurations in the stable memory: `init` refuses to run over a configured
+//! bridge unless forced, and the later changes go through `admin_reconfigure` with the nonce of
+//! the configuration they replace.
+//!
+//! A reconfiguration retried with the same nonce and the same configuration after it was applied
+//! succeeds without changes, so a script can safely retry a call whose response was lost.
+
+use std::borrow::Cow;
+
+use candid::{CandidType, Decode, Deserialize, Encode};
+use ethers_core::utils::keccak256;
+use ic_stable_structures::stable_structures::Memory;
+use ic_stable_structures::{Bound, CellStructure, StableCell, Storable};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reconfiguration {
+    /// The configuration is to be applied.
+    Apply,
+    /// The same configuration was applied with this nonce already.
+    AlreadyApplied,
+}
+
+#[derive(Debug, Default, Clone, CandidType, Deserialize)]
+struct ConfigRevision {
+    /// Number of the configurations applied. `0` if the bridge is not configured.
+    nonce: u64,
+    /// Hash of the last applied configuration.
+    last_hash: Option<[u8; 32]>,
+}
+
+impl Storable for ConfigRevision {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("serialization failed"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).expect("deserialization failed")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+pub struct ConfigGuard<M: Memory> {
+    revision: StableCell<ConfigRevision, M>,
+}
+
+impl<M: Memory> ConfigGuard<M> {
+    pub fn new(memory: M) -> Self {
+        Self {
+            revision: StableCell::new(memory, ConfigRevision::default())
+                .expect("stable memory config revision initialization failed"),
+        }
+    }
+
+    /// Nonce the next reconfiguration must be made with.
+    pub fn nonce(&self) -> u64 {
+        self.revision.get().nonce
+    }
+
+    pub fn is_configured(&self) -> bool {
+        self.nonce() > 0
+    }
+
+    /// Checks that `init` may configure the bridge: it is not configured yet, or `force` is set.
+    pub fn check_init(&self, force: bool) -> Result<(), String> {
+        if self.is_configured() && !force {
+            return Err(format!(
+                "the bridge is configured already (nonce {}), use `admin_reconfigure` or pass `force` to overwrite the configuration",
+                self.nonce()
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Checks the reconfiguration made with the `nonce` of the configuration it replaces.
+    pub fn check(&self, config: &impl CandidType, nonce: u64) -> Result<Reconfiguration, String> {
+        let revision = self.revision.get();
+        if nonce == revision.nonce {
+            return Ok(Reconfiguration::Apply);
+        }
+
+        if nonce + 1 == revision.nonce && revision.last_hash == Some(config_hash(config)) {
+            return Ok(Reconfiguration::AlreadyApplied);
+        }
+
+        Err(format!(
+            "configuration nonce mismatch: expected {}, got {nonce}",
+            revision.nonce
+        ))
+    }
+
+    /// Records the applied `config` and returns the nonce of the next reconfiguration.
+    pub fn record(&mut self, config: &impl CandidType) -> u64 {
+        let nonce = self.nonce() + 1;
+        self.revision
+            .set(ConfigRevision {
+                nonce,
+                last_hash: Some(config_hash(config)),
+            })
+            .expect("failed to update config revision");
+        nonce
+    }
+}
+
+fn config_hash(config: &impl CandidType) -> [u8; 32] {
+    keccak256(Encode!(config).expect("serialization failed"))
+}
+
+#[cfg(test)]
+mod tests {
+    use ic_stable_structures::VectorMemory;
+
+    use super::*;
+
+    #[test]
+    fn init_does_not_overwrite_configuration() {
+        let mut guard = ConfigGuard::new(VectorMemory::default());
+        assert!(guard.check_init(false).is_ok());
+
+        assert_eq!(guard.record(&"config"), 1);
+        assert!(guard.is_configured());
+        assert!(guard.check_init(false).is_err());
+        assert!(guard.check_init(true).is_ok());
+    }
+
+    #[test]
+    fn reconfiguration_is_idempotent() {
+        let mut guard = ConfigGuard::new(VectorMemory::default());
+        guard.record(&"initial");
+
+        assert_eq!(guard.check(&"updated", 1), Ok(Reconfiguration::Apply));
+        assert_eq!(guard.record(&"updated"), 2);
+
+        // The same call retried after it was applied.
+        assert_eq!(
+            guard.check(&"updated", 1),
+            Ok(Reconfiguration::AlreadyApplied)
+        );
+        // Another configuration with the stale nonce.
+        assert!(guard.check(&"other", 1).is_err());
+        assert!(guard.check(&"updated", 3).is_err());
+        assert_eq!(guard.check(&"other", 2), Ok(Reconfiguration::Apply));
+    }
+}
@@ -0,0 +1,617 @@
+//! Validation of the bridge canister configurations.
+//!
+//! Instead of stopping at the first problem, the configurations are checked with a
+//! [`ConfigValidator`], which collects every error found. The canisters reject an invalid
+//! configuration at init with all the errors listed in the trap message, and expose the same
+//! checks with a `validate_config` query, so a configuration can be checked before the canister is
+//! installed.
+//!
+//! The checks against the live EVM, e.g. of the chain id or of the deployed contracts, need
+//! outcalls, so they are performed by the `admin_validate_*` dry-run updates of the canisters with
+//! [`ConfigValidator::evm_chain_id`] and [`ConfigValidator::contract_code`], and never by the init.
+//! `admin_configure_bft_bridge` runs them too, together with the `eth_call` checks of the wiring
+//! of the contracts: [`ConfigValidator::bridge_minter`] and [`ConfigValidator::bridge_token`].
+
+use candid::{CandidType, Principal};
+use did::H160;
+use ethereum_json_rpc_client::{Client, EthJsonRpcClient};
+use ethers_core::types::BlockNumber;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::bft_bridge_api;
+use crate::erc20_metadata::Erc20Metadata;
+use crate::evm_link::EvmLink;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error, CandidType, Deserialize)]
+pub enum ConfigError {
+    #[error("{field}: anonymous principal is not allowed")]
+    AnonymousPrincipal { field: String },
+    #[error("{field}: management canister principal is not allowed")]
+    ManagementCanisterPrincipal { field: String },
+    #[error("{field}: zero address is not allowed")]
+    ZeroAddress { field: String },
+    #[error("{field}: invalid url {url:?}: {reason}")]
+    InvalidUrl {
+        field: String,
+        url: String,
+        reason: String,
+    },
+    #[error("{fields:?} conflict: {reason}")]
+    Conflict { fields: Vec<String>, reason: String },
+    #[error("{field}: {reason}")]
+    InvalidValue { field: String, reason: String },
+}
+
+/// Collects the errors of a configuration.
+#[derive(Debug, Default)]
+pub struct ConfigValidator {
+    errors: Vec<ConfigError>,
+}
+
+impl ConfigValidator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Checks that the principal is not anonymous.
+    pub fn principal(&mut self, field: &str, principal: Principal) -> &mut Self {
+        if principal == Principal::anonymous() {
+            self.errors.push(ConfigError::AnonymousPrincipal {
+                field: field.to_string(),
+            });
+        }
+        self
+    }
+
+    /// Checks that the principal can be an id of a canister the bridge calls.
+    pub fn canister(&mut self, field: &str, principal: Principal) -> &mut Self {
+        if principal == Principal::management_canister() {
+            self.errors.push(ConfigError::ManagementCanisterPrincipal {
+                field: field.to_string(),
+            });
+        }
+        self.principal(field, principal)
+    }
+
+    pub fn evm_address(&mut self, field: &str, address: &H160) -> &mut Self {
+        if address.0.is_zero() {
+            self.errors.push(ConfigError::ZeroAddress {
+                field: field.to_string(),
+            });
+        }
+        self
+    }
+
+    /// Checks that the url is a non-empty https url, as required by the HTTP outcalls.
+    pub fn https_url(&mut self, field: &str, url: &str) -> &mut Self {
+        let reason = if url.trim().is_empty() {
+            Some("url is empty")
+        } else if !url.starts_with("https://") {
+            Some("url must use https")
+        } else if url.len() == "https://".len() {
+            Some("url has no host")
+        } else {
+            None
+        };
+
+        if let Some(reason) = reason {
+            self.errors.push(ConfigError::InvalidUrl {
+                field: field.to_string(),
+                url: url.to_string(),
+                reason: reason.to_string(),
+            });
+        }
+        self
+    }
+
+    pub fn evm_link(&mut self, field: &str, link: &EvmLink) -> &mut Self {
+        match link {
+            EvmLink::Http(url) => self.https_url(field, url),
+            EvmLink::Ic(principal) => self.canister(field, *principal),
+            EvmLink::EvmRpcCanister {
+                canister_id,
+                rpc_service,
+            } => {
+                if rpc_service.is_empty() {
+                    self.invalid(field, "no RPC services are specified");
+                }
+                self.canister(field, *canister_id)
+            }
+        }
+    }
+
+    /// Checks that the EVM of the `client` has the `expected` chain id. A failed request is
+    /// reported as an error of the field too.
+    pub async fn evm_chain_id(
+        &mut self,
+        field: &str,
+        client: &EthJsonRpcClient<impl Client>,
+        expected: u64,
+    ) -> &mut Self {
+        match client.eth_chain_id().await {
+            Ok(chain_id) if chain_id == expected => self,
+            Ok(chain_id) => self.invalid(
+                field,
+                format!("expected chain id {expected}, EVM has {chain_id}"),
+            ),
+            Err(err) => self.invalid(field, format!("failed to get chain id: {err}")),
+        }
+    }
+
+    /// Checks that a contract is deployed at the `address` in the EVM of the `client`.
+    pub async fn contract_code(
+        &mut self,
+        field: &str,
+        client: &EthJsonRpcClient<impl Client>,
+        address: &H160,
+    ) -> &mut Self {
+        let hex_address = format!("0x{}", hex::encode(address.0));
+        match client.get_code(address.0, BlockNumber::Latest).await {
+            Ok(code) if code.trim_start_matches("0x").is_empty() => {
+                self.invalid(field, format!("no contract is deployed at {hex_address}"))
+            }
+            Ok(_) => self,
+            Err(err) => self.invalid(field, format!("failed to get code of {hex_address}: {err}")),
+        }
+    }
+
+    /// Checks that the BftBridge at the `bridge` address accepts the mint orders of the `minter`,
+    /// the EVM address of the canister.
+    pub async fn bridge_minter(
+        &mut self,
+        field: &str,
+        client: &EthJsonRpcClient<impl Client>,
+        bridge: &H160,
+        minter: &H160,
+    ) -> &mut Self {
+        match bft_bridge_api::minter_canister_address(client, bridge.0).await {
+            Ok(address) if address == minter.0 => self,
+            Ok(address) => self.invalid(
+                field,
+                format!(
+                    "bridge minter is {address:#x}, the canister address is {:#x}",
+                    minter.0
+                ),
+            ),
+            Err(err) => self.invalid(field, format!("failed to get bridge minter: {err}")),
+        }
+    }
+
+    /// Checks that the `token` is a wrapped token deployed by the BftBridge at the `bridge`
+    /// address. A token deployed otherwise is accepted if it is an ERC20 with the `expected`
+    /// metadata.
+    pub async fn bridge_token(
+        &mut self,
+        field: &str,
+        client: &EthJsonRpcClient<impl Client>,
+        bridge: &H160,
+        token: &H160,
+        expected: &Erc20Metadata,
+    ) -> &mut Self {
+        match bft_bridge_api::wrapped_tokens(client, bridge.0).await {
+            Ok(wrapped) if wrapped.contains(&token.0) => return self,
+            Ok(_) => {}
+            Err(err) => {
+                return self.invalid(field, format!("failed to list bridge tokens: {err}"));
+            }
+        }
+
+        match Erc20Metadata::query(client, token).await {
+            Ok(metadata) if metadata == *expected => self,
+            Ok(metadata) => self.invalid(
+                field,
+                format!(
+                    "token is not deployed by the bridge and its metadata {metadata:?} differs from the expected {expected:?}"
+                ),
+            ),
+            Err(err) => self.invalid(
+                field,
+                format!("token is not deployed by the bridge and is not a valid ERC20: {err}"),
+            ),
+        }
+    }
+
+    /// Records the error of a check performed by the configuration itself.
+    pub fn check(&mut self, field: &str, result: Result<(), String>) -> &mut Self {
+        if let Err(reason) = result {
+            self.invalid(field, reason);
+        }
+        self
+    }
+
+    pub fn invalid(&mut self, field: &str, reason: impl Into<String>) -> &mut Self {
+        self.errors.push(ConfigError::InvalidValue {
+            field: field.to_string(),
+            reason: reason.into(),
+        });
+        self
+    }
+
+    pub fn conflict(&mut self, fields: &[&str], reason: impl Into<String>) -> &mut Self {
+        self.errors.push(ConfigError::Conflict {
+            fields: fields.iter().map(|field| field.to_string()).collect(),
+            reason: reason.into(),
+        });
+        self
+    }
+
+    pub fn errors(&self) -> &[ConfigError] {
+        &self.errors
+    }
+
+    pub fn finish(self) -> Result<(), Vec<ConfigError>> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self.errors)
+        }
+    }
+}
+
+/// Checks a BftBridge contract address before it is set: the contract must be deployed in the EVM
+/// of the `client`, and the EVM must have the `chain_id` the bridge works with, if it is known.
+pub async fn validate_bft_bridge_contract(
+    client: &EthJsonRpcClient<impl Client>,
+    address: &H160,
+    chain_id: Option<u64>,
+) -> Result<(), Vec<ConfigError>> {
+    let mut validator = ConfigValidator::new();
+    if let Some(chain_id) = chain_id {
+        validator.evm_chain_id("evm", client, chain_id).await;
+    }
+    if address.0.is_zero() {
+        validator.evm_address("bft_bridge_contract", address);
+    } else {
+        validator
+            .contract_code("bft_bridge_contract", client, address)
+            .await;
+    }
+
+    validator.finish()
+}
+
+/// Formats the errors into a message listing all of them, e.g. for a trap.
+pub fn format_config_errors(errors: &[ConfigError]) -> String {
+    let mut message = format!("Invalid configuration ({} errors):", errors.len());
+    for error in errors {
+        message.push_str("\n - ");
+        message.push_str(&error.to_string());
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers_core::abi::Token;
+
+    use super::*;
+    use crate::evm_link::RpcService;
+    use crate::wrapped_token_api::{ERC_20_DECIMALS, ERC_20_NAME, ERC_20_SYMBOL};
+
+    #[test]
+    fn valid_values_pass() {
+        let mut validator = ConfigValidator::new();
+        validator
+            .principal("admin", Principal::from_slice(&[1; 29]))
+            .canister("evm", Principal::from_slice(&[2; 10]))
+            .evm_address("bridge", &H160::from_slice(&[3; 20]))
+            .https_url("indexer_url", "https://indexer.com")
+            .evm_link("evm_link", &EvmLink::Http("https://evm.com".into()))
+            .check("tiers", Ok(()));
+
+        assert_eq!(validator.finish(), Ok(()));
+    }
+
+    #[test]
+    fn all_errors_are_collected() {
+        let mut validator = ConfigValidator::new();
+        validator
+            .principal("admin", Principal::anonymous())
+            .canister("ck_btc_minter", Principal::management_canister())
+            .evm_address("bridge", &H160::default())
+            .https_url("indexer_url", "http://indexer.com")
+            .evm_link(
+                "evm_link",
+                &EvmLink::EvmRpcCanister {
+                    canister_id: Principal::from_slice(&[2; 10]),
+                    rpc_service: Vec::<RpcService>::new(),
+                },
+            )
+            .check("tiers", Err("tiers are not sorted".into()))
+            .conflict(&["base_evm_link", "wrapped_evm_link"], "links are equal");
+
+        let errors = validator.finish().unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                ConfigError::AnonymousPrincipal {
+                    field: "admin".into()
+                },
+                ConfigError::ManagementCanisterPrincipal {
+                    field: "ck_btc_minter".into()
+                },
+                ConfigError::ZeroAddress {
+                    field: "bridge".into()
+                },
+                ConfigError::InvalidUrl {
+                    field: "indexer_url".into(),
+                    url: "http://indexer.com".into(),
+                    reason: "url must use https".into(),
+                },
+                ConfigError::InvalidValue {
+                    field: "evm_link".into(),
+                    reason: "no RPC services are specified".into(),
+                },
+                ConfigError::InvalidValue {
+                    field: "tiers".into(),
+                    reason: "tiers are not sorted".into(),
+                },
+                ConfigError::Conflict {
+                    fields: vec!["base_evm_link".into(), "wrapped_evm_link".into()],
+                    reason: "links are equal".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_and_hostless_urls_are_rejected() {
+        let mut validator = ConfigValidator::new();
+        validator.https_url("a", "").https_url("b", "https://");
+        assert_eq!(validator.errors().len(), 2);
+    }
+
+    /// EVM with the chain id 355113 and a contract at the address `0x11..11`.
+    #[derive(Clone)]
+    struct StubEvm;
+
+    impl Client for StubEvm {
+        fn send_rpc_request(
+            &self,
+            request: jsonrpc_core::Request,
+        ) -> std::pin::Pin<
+            Box<dyn std::future::Future<Output = anyhow::Result<jsonrpc_core::Response>> + Send>,
+        > {
+            let jsonrpc_core::Request::Single(jsonrpc_core::Call::MethodCall(call)) = request
+            else {
+                unimplemented!("expected single method call request");
+            };
+            let result = match call.method.as_str() {
+                "eth_chainId" => serde_json::json!("0x56b29"),
+                "eth_getCode" => {
+                    let contract = format!("0x{}", "11".repeat(20));
+                    match call.params {
+                        jsonrpc_core::Params::Array(params) if params[0] == contract => {
+                            serde_json::json!("0x6080")
+                        }
+                        _ => serde_json::json!("0x"),
+                    }
+                }
+                method => unimplemented!("unexpected method {method}"),
+            };
+
+            Box::pin(async move {
+                Ok(jsonrpc_core::Response::Single(
+                    jsonrpc_core::Output::Success(jsonrpc_core::Success {
+                        jsonrpc: None,
+                        result,
+                        id: call.id,
+                    }),
+                ))
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn live_evm_checks() {
+        let client = EthJsonRpcClient::new(StubEvm);
+        let mut validator = ConfigValidator::new();
+        validator
+            .evm_chain_id("erc20_chain_id", &client, 355113)
+            .await;
+        validator
+            .contract_code("bridge_address", &client, &H160::from_slice(&[0x11; 20]))
+            .await;
+        assert!(validator.errors().is_empty());
+
+        validator.evm_chain_id("erc20_chain_id", &client, 1).await;
+        validator
+            .contract_code("token_address", &client, &H160::from_slice(&[0x22; 20]))
+            .await;
+        assert_eq!(
+            validator.finish().unwrap_err(),
+            vec![
+                ConfigError::InvalidValue {
+                    field: "erc20_chain_id".into(),
+                    reason: "expected chain id 1, EVM has 355113".into(),
+                },
+                ConfigError::InvalidValue {
+                    field: "token_address".into(),
+                    reason: format!("no contract is deployed at 0x{}", "22".repeat(20)),
+                },
+            ]
+        );
+    }
+
+    /// EVM with a BftBridge at `0x11..11` minted by `0xaa..aa`, which deployed the wrapped token
+    /// `0x22..22`, and an ERC20 token `0x33..33` deployed separately.
+    #[derive(Clone)]
+    struct WiredEvm;
+
+    impl WiredEvm {
+        fn call(to: &str, data: &str) -> Option<Vec<Token>> {
+            let selector = |function: &ethers_core::abi::Function| {
+                format!("0x{}", hex::encode(function.short_signature()))
+            };
+            let bridge = format!("0x{}", "11".repeat(20));
+            let token = format!("0x{}", "33".repeat(20));
+            let output = if to == bridge
+                && data.starts_with(&selector(&bft_bridge_api::MINTER_CANISTER_ADDRESS))
+            {
+                vec![Token::Address([0xaa; 20].into())]
+            } else if to == bridge && data.starts_with(&selector(&bft_bridge_api::LIST_TOKEN_PAIRS))
+            {
+                vec![
+                    Token::Array(vec![Token::Address([0x22; 20].into())]),
+                    Token::Array(vec![Token::FixedBytes(vec![1; 32])]),
+                ]
+            } else if to == token && data.starts_with(&selector(&ERC_20_NAME)) {
+                vec![Token::String("Token".into())]
+            } else if to == token && data.starts_with(&selector(&ERC_20_SYMBOL)) {
+                vec![Token::String("TKN".into())]
+            } else if to == token && data.starts_with(&selector(&ERC_20_DECIMALS)) {
+                vec![Token::Uint(8.into())]
+            } else {
+                return None;
+            };
+            Some(output)
+        }
+    }
+
+    impl Client for WiredEvm {
+        fn send_rpc_request(
+            &self,
+            request: jsonrpc_core::Request,
+        ) -> std::pin::Pin<
+            Box<dyn std::future::Future<Output = anyhow::Result<jsonrpc_core::Response>> + Send>,
+        > {
+            let jsonrpc_core::Request::Single(jsonrpc_core::Call::MethodCall(call)) = request
+            else {
+                unimplemented!("expected single method call request");
+            };
+            assert_eq!(call.method, "eth_call");
+            let jsonrpc_core::Params::Array(params) = call.params else {
+                unimplemented!("expected array params");
+            };
+            let to = params[0]["to"].as_str().unwrap_or_default();
+            let data = params[0]["data"]
+                .as_str()
+                .or(params[0]["input"].as_str())
+                .unwrap_or_default();
+            let result = match Self::call(to, data) {
+                Some(output) => {
+                    serde_json::json!(format!(
+                        "0x{}",
+                        hex::encode(ethers_core::abi::encode(&output))
+                    ))
+                }
+                None => serde_json::json!("0x"),
+            };
+
+            Box::pin(async move {
+                Ok(jsonrpc_core::Response::Single(
+                    jsonrpc_core::Output::Success(jsonrpc_core::Success {
+                        jsonrpc: None,
+                        result,
+                        id: call.id,
+                    }),
+                ))
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn bridge_wiring_checks() {
+        let client = EthJsonRpcClient::new(WiredEvm);
+        let bridge = H160::from_slice(&[0x11; 20]);
+        let metadata = Erc20Metadata {
+            name: "Token".into(),
+            symbol: "TKN".into(),
+            decimals: 8,
+        };
+
+        let mut validator = ConfigValidator::new();
+        validator
+            .bridge_minter(
+                "bridge_address",
+                &client,
+                &bridge,
+                &H160::from_slice(&[0xaa; 20]),
+            )
+            .await;
+        validator
+            .bridge_token(
+                "token_address",
+                &client,
+                &bridge,
+                &H160::from_slice(&[0x22; 20]),
+                &metadata,
+            )
+            .await;
+        validator
+            .bridge_token(
+                "token_address",
+                &client,
+                &bridge,
+                &H160::from_slice(&[0x33; 20]),
+                &metadata,
+            )
+            .await;
+        assert!(validator.errors().is_empty());
+
+        validator
+            .bridge_minter(
+                "bridge_address",
+                &client,
+                &bridge,
+                &H160::from_slice(&[0xbb; 20]),
+            )
+            .await;
+        validator
+            .bridge_token(
+                "token_address",
+                &client,
+                &bridge,
+                &H160::from_slice(&[0x33; 20]),
+                &Erc20Metadata {
+                    decimals: 18,
+                    ..metadata.clone()
+                },
+            )
+            .await;
+        validator
+            .bridge_token(
+                "token_address",
+                &client,
+                &bridge,
+                &H160::from_slice(&[0x44; 20]),
+                &metadata,
+            )
+            .await;
+
+        let errors = validator.finish().unwrap_err();
+        assert_eq!(errors.len(), 3);
+        assert_eq!(
+            errors[0],
+            ConfigError::InvalidValue {
+                field: "bridge_address".into(),
+                reason: format!(
+                    "bridge minter is 0x{}, the canister address is 0x{}",
+                    "aa".repeat(20),
+                    "bb".repeat(20)
+                ),
+            }
+        );
+        assert!(errors[1..].iter().all(|error| error
+            .to_string()
+            .starts_with("token_address: token is not deployed")));
+    }
+
+    #[test]
+    fn errors_are_listed_in_message() {
+        let errors = vec![
+            ConfigError::AnonymousPrincipal {
+                field: "admin".into(),
+            },
+            ConfigError::ZeroAddress {
+                field: "bridge".into(),
+            },
+        ];
+
+        assert_eq!(
+            format_config_errors(&errors),
+            "Invalid configuration (2 errors):\n - admin: anonymous principal is not allowed\n - bridge: zero address is not allowed"
+        );
+    }
+}
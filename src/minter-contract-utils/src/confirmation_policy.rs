@@ -0,0 +1,128 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+
+/// Additional confirmations requirement for deposits of at least `min_amount`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, CandidType, Serialize, Deserialize)]
+pub struct ConfirmationTier {
+    /// Minimum deposit amount (inclusive) the tier is applied to.
+    pub min_amount: u64,
+    /// Number of confirmations required for deposits in this tier.
+    pub confirmations: u32,
+}
+
+/// Number of block confirmations required for a deposit depending on its size.
+///
+/// Deposits smaller than the lowest tier threshold require `min_confirmations`. Larger
+/// deposits require the number of confirmations of the highest tier they reach.
+#[derive(Debug, Default, Clone, PartialEq, Eq, CandidType, Serialize, Deserialize)]
+pub struct ConfirmationPolicy {
+    /// Confirmations required for any deposit.
+    pub min_confirmations: u32,
+    /// Tiers sorted by `min_amount` in ascending order.
+    pub tiers: Vec<ConfirmationTier>,
+}
+
+impl ConfirmationPolicy {
+    /// Creates a new policy. Tiers are sorted by their amount threshold.
+    pub fn new(min_confirmations: u32, mut tiers: Vec<ConfirmationTier>) -> Self {
+        tiers.sort_by_key(|tier| tier.min_amount);
+        Self {
+            min_confirmations,
+            tiers,
+        }
+    }
+
+    /// Returns the number of confirmations required for a deposit of the given `amount`.
+    pub fn required_confirmations(&self, amount: u64) -> u32 {
+        self.tiers
+            .iter()
+            .filter(|tier| tier.min_amount <= amount)
+            .map(|tier| tier.confirmations)
+            .fold(self.min_confirmations, u32::max)
+    }
+
+    /// Highest number of confirmations this policy can require.
+    pub fn max_confirmations(&self) -> u32 {
+        self.tiers
+            .iter()
+            .map(|tier| tier.confirmations)
+            .fold(self.min_confirmations, u32::max)
+    }
+
+    /// Checks that tier thresholds are unique and tiers for larger amounts do not require
+    /// fewer confirmations than tiers for smaller ones.
+    pub fn validate(&self) -> Result<(), String> {
+        let mut tiers = self.tiers.clone();
+        tiers.sort_by_key(|tier| tier.min_amount);
+
+        let mut prev_confirmations = self.min_confirmations;
+        for (index, tier) in tiers.iter().enumerate() {
+            if index > 0 && tiers[index - 1].min_amount == tier.min_amount {
+                return Err(format!(
+                    "Duplicate confirmation tier for amount {}",
+                    tier.min_amount
+                ));
+            }
+
+            if tier.confirmations < prev_confirmations {
+                return Err(format!(
+                    "Confirmation tier for amount {} requires {} confirmations, which is less than {} required for smaller amounts",
+                    tier.min_amount, tier.confirmations, prev_confirmations
+                ));
+            }
+
+            prev_confirmations = tier.confirmations;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tier(min_amount: u64, confirmations: u32) -> ConfirmationTier {
+        ConfirmationTier {
+            min_amount,
+            confirmations,
+        }
+    }
+
+    #[test]
+    fn flat_policy_requires_min_confirmations() {
+        let policy = ConfirmationPolicy::new(6, vec![]);
+        assert_eq!(policy.required_confirmations(0), 6);
+        assert_eq!(policy.required_confirmations(u64::MAX), 6);
+        assert_eq!(policy.max_confirmations(), 6);
+    }
+
+    #[test]
+    fn tiered_policy_selects_highest_reached_tier() {
+        let policy = ConfirmationPolicy::new(1, vec![tier(100_000_000, 6), tier(1_000_000, 3)]);
+
+        assert_eq!(policy.required_confirmations(10_000), 1);
+        assert_eq!(policy.required_confirmations(999_999), 1);
+        assert_eq!(policy.required_confirmations(1_000_000), 3);
+        assert_eq!(policy.required_confirmations(99_999_999), 3);
+        assert_eq!(policy.required_confirmations(100_000_000), 6);
+        assert_eq!(policy.required_confirmations(u64::MAX), 6);
+        assert_eq!(policy.max_confirmations(), 6);
+    }
+
+    #[test]
+    fn validation() {
+        assert!(ConfirmationPolicy::new(1, vec![tier(10, 2), tier(20, 3)])
+            .validate()
+            .is_ok());
+        assert!(ConfirmationPolicy::new(1, vec![tier(10, 2), tier(10, 3)])
+            .validate()
+            .is_err());
+        assert!(ConfirmationPolicy::new(1, vec![tier(10, 3), tier(20, 2)])
+            .validate()
+            .is_err());
+        assert!(ConfirmationPolicy::new(4, vec![tier(10, 3)])
+            .validate()
+            .is_err());
+    }
+}
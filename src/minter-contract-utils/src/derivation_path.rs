@@ -0,0 +1,203 @@
+//! Derivation paths of the keys the bridges derive from the canister master key.
+//!
+//! The management canister takes a derivation path as a list of byte strings, while BIP-32
+//! derivation works with child numbers. [`DerivationPath`] is built only from the values a key is
+//! derived for, and always consists of 4-byte big-endian normal child indices, so the same path
+//! is used whether the key is derived by the management canister or locally from the master
+//! public key.
+
+use bitcoin::bip32::{self, ChildNumber};
+use candid::Principal;
+use did::H160;
+use thiserror::Error;
+
+/// First byte of the derivation paths of keys derived for an EVM address.
+pub const ETH_ADDRESS_PREFIX: u8 = 7;
+/// First byte of the derivation paths of keys derived for a principal.
+pub const PRINCIPAL_PREFIX: u8 = 8;
+
+/// Number of payload bytes in a derivation path part. The first byte of each part is zero,
+/// which keeps the part a normal (non-hardened) child index.
+const PART_PAYLOAD_SIZE: usize = 3;
+const PART_SIZE: usize = PART_PAYLOAD_SIZE + 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum DerivationPathError {
+    #[error("derivation path part {index} has {len} bytes instead of {PART_SIZE}")]
+    InvalidPartSize { index: usize, len: usize },
+    #[error("derivation path part {index} is a hardened child index")]
+    HardenedPart { index: usize },
+}
+
+/// Derivation path of a key. The default path is the empty path of the master key.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct DerivationPath(Vec<Vec<u8>>);
+
+impl DerivationPath {
+    /// Path of the key derived for the EVM address, e.g. the deposit address of its owner.
+    pub fn from_eth_address(address: &H160) -> Self {
+        let mut bytes = vec![ETH_ADDRESS_PREFIX];
+        bytes.extend_from_slice(address.0.as_bytes());
+        Self::from_payload(&bytes)
+    }
+
+    /// Path of the key derived for the principal. The length of the principal is a part of the
+    /// path, so principals which are prefixes of each other get different keys.
+    pub fn from_principal(principal: &Principal) -> Self {
+        let principal = principal.as_slice();
+        let mut bytes = vec![PRINCIPAL_PREFIX, principal.len() as u8];
+        bytes.extend_from_slice(principal);
+        Self::from_payload(&bytes)
+    }
+
+    /// Restores the path from its management canister form, e.g. when it is read from the stable
+    /// memory.
+    pub fn from_ic(parts: Vec<Vec<u8>>) -> Result<Self, DerivationPathError> {
+        for (index, part) in parts.iter().enumerate() {
+            if part.len() != PART_SIZE {
+                return Err(DerivationPathError::InvalidPartSize {
+                    index,
+                    len: part.len(),
+                });
+            }
+
+            if part[0] & 0x80 != 0 {
+                return Err(DerivationPathError::HardenedPart { index });
+            }
+        }
+
+        Ok(Self(parts))
+    }
+
+    /// Splits the payload into the parts with a zero first byte, padding the last part with
+    /// zeros.
+    fn from_payload(payload: &[u8]) -> Self {
+        let parts = payload
+            .chunks(PART_PAYLOAD_SIZE)
+            .map(|chunk| {
+                let mut part = vec![0; PART_SIZE];
+                part[1..1 + chunk.len()].copy_from_slice(chunk);
+                part
+            })
+            .collect();
+
+        Self(parts)
+    }
+
+    /// The path in the form of the management canister ECDSA API.
+    pub fn as_ic(&self) -> &[Vec<u8>] {
+        &self.0
+    }
+
+    pub fn into_ic(self) -> Vec<Vec<u8>> {
+        self.0
+    }
+
+    /// Canonical byte encoding of the path: its parts concatenated.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.concat()
+    }
+
+    /// Decodes the path from its canonical byte encoding.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DerivationPathError> {
+        if bytes.len() % PART_SIZE != 0 {
+            return Err(DerivationPathError::InvalidPartSize {
+                index: bytes.len() / PART_SIZE,
+                len: bytes.len() % PART_SIZE,
+            });
+        }
+
+        Self::from_ic(bytes.chunks(PART_SIZE).map(<[u8]>::to_vec).collect())
+    }
+
+    /// Child indices of the path for the BIP-32 derivation.
+    pub fn child_indices(&self) -> Vec<u32> {
+        self.0
+            .iter()
+            .map(|part| {
+                u32::from_be_bytes(
+                    part[..]
+                        .try_into()
+                        .expect("derivation path parts are 4 bytes long"),
+                )
+            })
+            .collect()
+    }
+}
+
+impl From<&DerivationPath> for bip32::DerivationPath {
+    fn from(path: &DerivationPath) -> Self {
+        path.child_indices()
+            .into_iter()
+            .map(|index| {
+                ChildNumber::from_normal_idx(index)
+                    .expect("derivation path parts are normal child indices")
+            })
+            .collect::<Vec<_>>()
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eth_address_path_layout() {
+        let address = H160::from_slice(&[0xab; 20]);
+        let path = DerivationPath::from_eth_address(&address);
+
+        assert_eq!(path.as_ic().len(), 7);
+        assert_eq!(path.as_ic()[0], vec![0, ETH_ADDRESS_PREFIX, 0xab, 0xab]);
+        assert!(path.as_ic().iter().all(|part| part.len() == PART_SIZE));
+        assert!(path.as_ic().iter().all(|part| part[0] == 0));
+    }
+
+    #[test]
+    fn principal_paths_are_distinct() {
+        let short = Principal::from_slice(&[1, 2, 3]);
+        let long = Principal::from_slice(&[1, 2, 3, 0]);
+
+        assert_ne!(
+            DerivationPath::from_principal(&short),
+            DerivationPath::from_principal(&long)
+        );
+        assert_ne!(
+            DerivationPath::from_principal(&Principal::from_slice(&[0; 20])).to_bytes(),
+            DerivationPath::from_eth_address(&H160::from_slice(&[0; 20])).to_bytes()
+        );
+    }
+
+    #[test]
+    fn byte_encoding_roundtrip() {
+        let path = DerivationPath::from_principal(&Principal::management_canister());
+        assert_eq!(
+            DerivationPath::from_bytes(&path.to_bytes()),
+            Ok(path.clone())
+        );
+        assert_eq!(DerivationPath::from_ic(path.clone().into_ic()), Ok(path));
+    }
+
+    #[test]
+    fn invalid_parts_are_rejected() {
+        assert_eq!(
+            DerivationPath::from_ic(vec![vec![0, 1, 2, 3], vec![0, 1]]),
+            Err(DerivationPathError::InvalidPartSize { index: 1, len: 2 })
+        );
+        assert_eq!(
+            DerivationPath::from_ic(vec![vec![0x80, 0, 0, 0]]),
+            Err(DerivationPathError::HardenedPart { index: 0 })
+        );
+        assert!(DerivationPath::from_bytes(&[0, 1, 2]).is_err());
+    }
+
+    #[test]
+    fn bip32_path_matches_parts() {
+        let path = DerivationPath::from_eth_address(&H160::from_slice(&[1; 20]));
+        let bip32_path = bip32::DerivationPath::from(&path);
+
+        let children: Vec<ChildNumber> = bip32_path.into();
+        let indices: Vec<u32> = children.into_iter().map(u32::from).collect();
+        assert_eq!(indices, path.child_indices());
+    }
+}
@@ -0,0 +1,14 @@
+//! Output of the candid interfaces by the `generate_did` binaries of the canister crates.
+
+/// Writes the `idl` to the file named by the first command line argument, or to `default_path`
+/// if there is no argument.
+pub fn write_idl(idl: &str, default_path: &str) -> std::io::Result<()> {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| default_path.to_string());
+
+    std::fs::write(&path, idl)?;
+    println!("candid interface written to {path}");
+
+    Ok(())
+}
@@ -0,0 +1,233 @@
+//! Metadata of the ERC20 tokens read from their contracts.
+//!
+//! The mint orders of the tokens bridged from an EVM carry the name, symbol and decimals of the
+//! token, which the BftBridge uses to deploy the wrapped token. The metadata is read from the
+//! token contract with `eth_call` instead of being taken from the burn, and is kept in an
+//! [`Erc20MetadataCache`], so each token is queried once.
+
+use std::borrow::Cow;
+
+use candid::{CandidType, Decode, Encode};
+use did::H160;
+use ethereum_json_rpc_client::{Client, EthJsonRpcClient};
+use ethers_core::abi::Token;
+use ic_stable_structures::stable_structures::Memory;
+use ic_stable_structures::{BTreeMapStructure, Bound, StableBTreeMap, Storable};
+use minter_did::order::fit_str_to_array;
+use serde::Deserialize;
+
+use crate::bft_bridge_api::call_view_function;
+use crate::wrapped_token_api::{ERC_20_DECIMALS, ERC_20_NAME, ERC_20_SYMBOL};
+
+/// Metadata of an ERC20 token.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct Erc20Metadata {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+impl Erc20Metadata {
+    /// Reads the metadata of the `token` contract with `eth_call`.
+    pub async fn query(
+        client: &EthJsonRpcClient<impl Client>,
+        token: &H160,
+    ) -> anyhow::Result<Self> {
+        let name = call_view_function(client, token.0, &ERC_20_NAME, &[]).await?;
+        let symbol = call_view_function(client, token.0, &ERC_20_SYMBOL, &[]).await?;
+        let decimals = call_view_function(client, token.0, &ERC_20_DECIMALS, &[]).await?;
+        match (name.as_slice(), symbol.as_slice(), decimals.as_slice()) {
+            ([Token::String(name)], [Token::String(symbol)], [Token::Uint(decimals)])
+                if *decimals <= u8::MAX.into() =>
+            {
+                Ok(Self {
+                    name: name.clone(),
+                    symbol: symbol.clone(),
+                    decimals: decimals.as_u32() as u8,
+                })
+            }
+            _ => Err(anyhow::anyhow!("unexpected ERC20 metadata output")),
+        }
+    }
+
+    /// Name of the token in the form of the mint order, truncated to 32 bytes.
+    pub fn name_bytes(&self) -> [u8; 32] {
+        fit_str_to_array(&self.name)
+    }
+
+    /// Symbol of the token in the form of the mint order, truncated to 16 bytes.
+    pub fn symbol_bytes(&self) -> [u8; 16] {
+        fit_str_to_array(&self.symbol)
+    }
+}
+
+impl Storable for Erc20Metadata {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("serialization failed"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).expect("deserialization failed")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct TokenAddress([u8; 20]);
+
+impl Storable for TokenAddress {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Borrowed(&self.0)
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Self(bytes[..].try_into().expect("expected 20 bytes for address"))
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: 20,
+        is_fixed_size: true,
+    };
+}
+
+/// Metadata of the tokens of one EVM by the token address.
+pub struct Erc20MetadataCache<M: Memory> {
+    tokens: StableBTreeMap<TokenAddress, Erc20Metadata, M>,
+}
+
+impl<M: Memory> Erc20MetadataCache<M> {
+    pub fn new(memory: M) -> Self {
+        Self {
+            tokens: StableBTreeMap::new(memory),
+        }
+    }
+
+    pub fn get(&self, token: &H160) -> Option<Erc20Metadata> {
+        self.tokens.get(&TokenAddress(token.0 .0))
+    }
+
+    pub fn insert(&mut self, token: &H160, metadata: Erc20Metadata) {
+        self.tokens.insert(TokenAddress(token.0 .0), metadata);
+    }
+
+    /// Returns the cached metadata of the `token`, or reads it from the contract and caches it.
+    pub async fn get_or_query(
+        &mut self,
+        client: &EthJsonRpcClient<impl Client>,
+        token: &H160,
+    ) -> anyhow::Result<Erc20Metadata> {
+        if let Some(metadata) = self.get(token) {
+            return Ok(metadata);
+        }
+
+        let metadata = Erc20Metadata::query(client, token).await?;
+        self.insert(token, metadata.clone());
+        Ok(metadata)
+    }
+
+    /// Forgets the metadata of the `token`, so it is read again, e.g. after an upgradable token
+    /// changed it.
+    pub fn remove(&mut self, token: &H160) -> Option<Erc20Metadata> {
+        self.tokens.remove(&TokenAddress(token.0 .0))
+    }
+
+    pub fn len(&self) -> u64 {
+        self.tokens.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tokens.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    use ic_stable_structures::VectorMemory;
+
+    use super::*;
+
+    /// Token contract answering the metadata calls and counting them.
+    #[derive(Clone, Default)]
+    struct TokenEvm {
+        calls: Arc<AtomicU32>,
+    }
+
+    impl TokenEvm {
+        fn calls(&self) -> u32 {
+            self.calls.load(Ordering::Relaxed)
+        }
+    }
+
+    impl Client for TokenEvm {
+        fn send_rpc_request(
+            &self,
+            request: jsonrpc_core::Request,
+        ) -> std::pin::Pin<
+            Box<dyn std::future::Future<Output = anyhow::Result<jsonrpc_core::Response>> + Send>,
+        > {
+            let jsonrpc_core::Request::Single(jsonrpc_core::Call::MethodCall(call)) = request
+            else {
+                unimplemented!("expected single method call request");
+            };
+            let jsonrpc_core::Params::Array(params) = call.params else {
+                unimplemented!("expected array params");
+            };
+            self.calls.fetch_add(1, Ordering::Relaxed);
+
+            let data = params[0]["data"]
+                .as_str()
+                .or(params[0]["input"].as_str())
+                .unwrap_or_default();
+            let selector = |function: &ethers_core::abi::Function| {
+                format!("0x{}", hex::encode(function.short_signature()))
+            };
+            let output = if data.starts_with(&selector(&ERC_20_NAME)) {
+                Token::String("A very long token name which does not fit".into())
+            } else if data.starts_with(&selector(&ERC_20_SYMBOL)) {
+                Token::String("TKN".into())
+            } else {
+                Token::Uint(18.into())
+            };
+            let result = serde_json::json!(format!(
+                "0x{}",
+                hex::encode(ethers_core::abi::encode(&[output]))
+            ));
+
+            Box::pin(async move {
+                Ok(jsonrpc_core::Response::Single(
+                    jsonrpc_core::Output::Success(jsonrpc_core::Success {
+                        jsonrpc: None,
+                        result,
+                        id: call.id,
+                    }),
+                ))
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn metadata_is_queried_once() {
+        let evm = TokenEvm::default();
+        let client = EthJsonRpcClient::new(evm.clone());
+        let token = H160::from_slice(&[0x33; 20]);
+        let mut cache = Erc20MetadataCache::new(VectorMemory::default());
+
+        let metadata = cache.get_or_query(&client, &token).await.unwrap();
+        assert_eq!(metadata.symbol, "TKN");
+        assert_eq!(metadata.decimals, 18);
+        assert_eq!(metadata.name_bytes()[..], metadata.name.as_bytes()[..32]);
+        assert_eq!(&metadata.symbol_bytes()[..3], b"TKN");
+        assert_eq!(evm.calls(), 3);
+
+        assert_eq!(cache.get_or_query(&client, &token).await.unwrap(), metadata);
+        assert_eq!(evm.calls(), 3);
+
+        cache.remove(&token);
+        cache.get_or_query(&client, &token).await.unwrap();
+        assert_eq!(evm.calls(), 6);
+    }
+}
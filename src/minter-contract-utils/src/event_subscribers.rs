@@ -0,0 +1,111 @@
+//! Registry of canisters which want to be notified about bridge events instead of polling
+//! the bridge canister for them.
+use candid::{CandidType, Principal};
+use ic_exports::ic_cdk::api::call;
+use ic_stable_structures::stable_structures::Memory;
+use ic_stable_structures::{BTreeMapStructure, StableBTreeMap};
+use serde::{Deserialize, Serialize};
+
+use crate::bft_bridge_api::{BurntEventData, MintedEventData};
+
+/// Method of a subscriber canister which receives [`BridgeEventNotification`]s.
+///
+/// Subscribers should expose it as `on_bridge_event : (BridgeEventNotification) -> ()`.
+pub const NOTIFICATION_METHOD: &str = "on_bridge_event";
+
+/// Notification sent to subscribers after the bridge processed an event.
+#[derive(Debug, Clone, CandidType, Serialize, Deserialize)]
+pub enum BridgeEventNotification {
+    Minted(MintedEventData),
+    Burnt(BurntEventData),
+}
+
+impl BridgeEventNotification {
+    /// Sends the notification to the `subscriber` as a one-way call.
+    ///
+    /// Only errors that happened before the message was enqueued are reported. The subscriber
+    /// response, if any, is ignored.
+    pub fn send(&self, subscriber: Principal) -> Result<(), String> {
+        call::notify(subscriber, NOTIFICATION_METHOD, (self,))
+            .map_err(|code| format!("failed to notify subscriber {subscriber}: {code:?}"))
+    }
+}
+
+/// Stable storage of the event subscribers.
+pub struct EventSubscribers<M: Memory> {
+    subscribers: StableBTreeMap<Principal, (), M>,
+}
+
+impl<M: Memory> EventSubscribers<M> {
+    pub fn new(memory: M) -> Self {
+        Self {
+            subscribers: StableBTreeMap::new(memory),
+        }
+    }
+
+    /// Registers a new subscriber. Anonymous principal can't be subscribed.
+    pub fn add(&mut self, subscriber: Principal) -> minter_did::error::Result<()> {
+        if subscriber == Principal::anonymous() {
+            return Err(minter_did::error::Error::AnonymousPrincipal);
+        }
+
+        self.subscribers.insert(subscriber, ());
+
+        Ok(())
+    }
+
+    pub fn remove(&mut self, subscriber: &Principal) {
+        self.subscribers.remove(subscriber);
+    }
+
+    pub fn contains(&self, subscriber: &Principal) -> bool {
+        self.subscribers.contains_key(subscriber)
+    }
+
+    pub fn get_all(&self) -> Vec<Principal> {
+        self.subscribers
+            .iter()
+            .map(|(subscriber, _)| subscriber)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ic_stable_structures::stable_structures::DefaultMemoryImpl;
+    use ic_stable_structures::{default_ic_memory_manager, MemoryId, VirtualMemory};
+
+    use super::*;
+
+    fn subscribers() -> EventSubscribers<VirtualMemory<DefaultMemoryImpl>> {
+        let memory_manager = default_ic_memory_manager();
+        EventSubscribers::new(memory_manager.get(MemoryId::new(0)))
+    }
+
+    #[test]
+    fn add_and_remove_subscribers() {
+        let mut subscribers = subscribers();
+        let first = Principal::management_canister();
+        let second = Principal::from_text("2chl6-4hpzw-vqaaa-aaaaa-c").unwrap();
+
+        subscribers.add(first).unwrap();
+        subscribers.add(second).unwrap();
+        subscribers.add(second).unwrap();
+
+        let all = subscribers.get_all();
+        assert_eq!(all.len(), 2);
+        assert!(all.contains(&first));
+        assert!(all.contains(&second));
+
+        subscribers.remove(&first);
+        assert!(!subscribers.contains(&first));
+        assert!(subscribers.contains(&second));
+    }
+
+    #[test]
+    fn anonymous_subscriber_is_rejected() {
+        let mut subscribers = subscribers();
+        assert!(subscribers.add(Principal::anonymous()).is_err());
+        assert!(subscribers.get_all().is_empty());
+    }
+}
@@ -0,0 +1,433 @@
+//! Native token deposits of the users in the FeeCharge contract.
+//!
+//! The BftBridge charges the gas of a mint transaction sent by the minter from the deposit of
+//! the mint order fee payer, and reverts the transaction if the deposit is too small. The bridges
+//! follow the deposits by the `BalanceUpdated` events of the FeeCharge contract, and name the user
+//! as the fee payer only if the deposit covers the fee, leaving the other orders to be sent by
+//! the users themselves. The fees of the orders which are not minted yet are reserved, so a
+//! deposit covers the pending orders of the user only as long as it covers all of their fees.
+//!
+//! The deposits are kept for each FeeCharge contract, so the balances seen in the contract of a
+//! replaced BftBridge never cover the fees charged by the new one.
+
+use std::borrow::Cow;
+
+use candid::{CandidType, Decode, Encode};
+use did::{H160, U256};
+use ethereum_json_rpc_client::{Client, EthJsonRpcClient};
+use ethers_core::abi::{RawLog, Token};
+use ethers_core::types::Log;
+use ic_stable_structures::stable_structures::Memory;
+use ic_stable_structures::{BTreeMapStructure, Bound, StableBTreeMap, Storable};
+use serde::Deserialize;
+
+use crate::bft_bridge_api::{call_view_function, collect_contract_logs};
+use crate::fee_charge_api::{BALANCE_UPDATED_EVENT, NATIVE_TOKEN_BALANCE};
+
+/// Deposit of a user with the position of the change in the chain.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct FeeBalance {
+    pub balance: U256,
+    pub block_number: u64,
+    pub log_index: u64,
+}
+
+impl FeeBalance {
+    fn position(&self) -> (u64, u64) {
+        (self.block_number, self.log_index)
+    }
+}
+
+impl Storable for FeeBalance {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("serialization failed"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).expect("deserialization failed")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// Change of a deposit, as reported by a `BalanceUpdated` event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeeBalanceUpdate {
+    pub user: H160,
+    pub balance: FeeBalance,
+}
+
+impl FeeBalanceUpdate {
+    /// Decodes the `BalanceUpdated` event. The pending logs have no position in the chain and
+    /// are not decoded.
+    pub fn from_log(log: Log) -> Option<Self> {
+        let block_number = log.block_number?.as_u64();
+        let log_index = log.log_index?.as_u64();
+        let parsed = BALANCE_UPDATED_EVENT
+            .parse_log(RawLog {
+                topics: log.topics,
+                data: log.data.to_vec(),
+            })
+            .ok()?;
+
+        let (mut user, mut balance) = (None, None);
+        for param in parsed.params {
+            match (param.name.as_str(), param.value) {
+                ("user", Token::Address(address)) => user = Some(address),
+                ("balance", Token::Uint(value)) => balance = Some(value),
+                _ => {}
+            }
+        }
+
+        Some(Self {
+            user: user?.into(),
+            balance: FeeBalance {
+                balance: balance?.into(),
+                block_number,
+                log_index,
+            },
+        })
+    }
+
+    /// Collects the deposit changes of the `fee_charge` contract in the range of blocks.
+    pub async fn collect(
+        evm_client: &EthJsonRpcClient<impl Client>,
+        from_block: u64,
+        to_block: u64,
+        fee_charge: &H160,
+    ) -> anyhow::Result<Vec<Self>> {
+        let logs = collect_contract_logs(
+            evm_client,
+            from_block,
+            to_block,
+            fee_charge.0,
+            vec![BALANCE_UPDATED_EVENT.signature()],
+        )
+        .await?;
+
+        Ok(logs.into_iter().filter_map(Self::from_log).collect())
+    }
+}
+
+/// Reads the current deposit of the `user` from the `fee_charge` contract.
+pub async fn query_fee_balance(
+    evm_client: &EthJsonRpcClient<impl Client>,
+    fee_charge: &H160,
+    user: &H160,
+) -> anyhow::Result<U256> {
+    match call_view_function(
+        evm_client,
+        fee_charge.0,
+        &NATIVE_TOKEN_BALANCE,
+        &[Token::Address(user.0)],
+    )
+    .await?
+    .as_slice()
+    {
+        [Token::Uint(balance)] => Ok((*balance).into()),
+        tokens => Err(anyhow::anyhow!(
+            "unexpected nativeTokenBalance output: {tokens:?}"
+        )),
+    }
+}
+
+/// Deposit of a user in a FeeCharge contract, with the fees reserved for the pending orders.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+struct FeeDeposit {
+    balance: FeeBalance,
+    reservations: Vec<FeeReservation>,
+}
+
+impl FeeDeposit {
+    fn reserved(&self) -> U256 {
+        self.reservations
+            .iter()
+            .fold(U256::zero(), |total, reservation| {
+                total.0.saturating_add(reservation.fee.0).into()
+            })
+    }
+
+    fn available(&self) -> U256 {
+        self.balance
+            .balance
+            .0
+            .saturating_sub(self.reserved().0)
+            .into()
+    }
+}
+
+impl Storable for FeeDeposit {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("serialization failed"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).expect("deserialization failed")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// Fee of the mint order with the `nonce`, reserved until the order is minted or cancelled.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+struct FeeReservation {
+    nonce: u32,
+    fee: U256,
+}
+
+/// Address of the FeeCharge contract followed by the address of the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct DepositKey([u8; 40]);
+
+impl DepositKey {
+    fn new(fee_charge: &H160, user: &H160) -> Self {
+        let mut key = [0; 40];
+        key[..20].copy_from_slice(fee_charge.0.as_bytes());
+        key[20..].copy_from_slice(user.0.as_bytes());
+        Self(key)
+    }
+}
+
+impl Storable for DepositKey {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Borrowed(&self.0)
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Self(
+            bytes[..]
+                .try_into()
+                .expect("expected 40 bytes for deposit key"),
+        )
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: 40,
+        is_fixed_size: true,
+    };
+}
+
+/// Deposits of the users in the FeeCharge contracts of one EVM.
+pub struct FeeBalances<M: Memory> {
+    deposits: StableBTreeMap<DepositKey, FeeDeposit, M>,
+}
+
+impl<M: Memory> FeeBalances<M> {
+    pub fn new(memory: M) -> Self {
+        Self {
+            deposits: StableBTreeMap::new(memory),
+        }
+    }
+
+    pub fn get(&self, fee_charge: &H160, user: &H160) -> Option<FeeBalance> {
+        self.deposits
+            .get(&DepositKey::new(fee_charge, user))
+            .map(|deposit| deposit.balance)
+    }
+
+    /// Returns the part of the deposit of the `user` reserved for the fees of the pending orders.
+    pub fn reserved(&self, fee_charge: &H160, user: &H160) -> U256 {
+        self.deposits
+            .get(&DepositKey::new(fee_charge, user))
+            .map(|deposit| deposit.reserved())
+            .unwrap_or_else(U256::zero)
+    }
+
+    /// Applies the deposit change in the `fee_charge` contract, unless a later change of the
+    /// deposit is recorded already. Returns `true` if the recorded balance is updated.
+    pub fn apply(&mut self, fee_charge: &H160, update: FeeBalanceUpdate) -> bool {
+        let key = DepositKey::new(fee_charge, &update.user);
+        let reservations = match self.deposits.get(&key) {
+            Some(recorded) if recorded.balance.position() > update.balance.position() => {
+                return false
+            }
+            Some(recorded) => recorded.reservations,
+            None => Vec::new(),
+        };
+
+        self.deposits.insert(
+            key,
+            FeeDeposit {
+                balance: update.balance,
+                reservations,
+            },
+        );
+        true
+    }
+
+    /// Reserves the `fee` of the mint order with the `nonce` if the part of the recorded deposit
+    /// which is not reserved for the other orders covers it. Returns the user as the fee payer of
+    /// the order if the fee is reserved, or the zero address otherwise.
+    ///
+    /// A fee reserved for the same nonce before, e.g. by a failed attempt to sign the order, is
+    /// replaced.
+    pub fn reserve_fee(&mut self, fee_charge: &H160, user: &H160, nonce: u32, fee: &U256) -> H160 {
+        let key = DepositKey::new(fee_charge, user);
+        let Some(mut deposit) = self.deposits.get(&key) else {
+            return H160::zero();
+        };
+
+        deposit
+            .reservations
+            .retain(|reservation| reservation.nonce != nonce);
+        let fee_payer = if deposit.available().0 >= fee.0 {
+            deposit.reservations.push(FeeReservation {
+                nonce,
+                fee: fee.clone(),
+            });
+            user.clone()
+        } else {
+            H160::zero()
+        };
+
+        self.deposits.insert(key, deposit);
+        fee_payer
+    }
+
+    /// Releases the fee reserved for the mint order with the `nonce` once it is minted or
+    /// cancelled.
+    pub fn release_fee(&mut self, fee_charge: &H160, user: &H160, nonce: u32) {
+        let key = DepositKey::new(fee_charge, user);
+        let Some(mut deposit) = self.deposits.get(&key) else {
+            return;
+        };
+
+        let reserved = deposit.reservations.len();
+        deposit
+            .reservations
+            .retain(|reservation| reservation.nonce != nonce);
+        if deposit.reservations.len() != reserved {
+            self.deposits.insert(key, deposit);
+        }
+    }
+
+    pub fn len(&self) -> u64 {
+        self.deposits.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.deposits.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers_core::types::{U256 as EthU256, U64};
+    use ic_stable_structures::VectorMemory;
+
+    use super::*;
+
+    fn balance_log(user: &H160, balance: u64, block_number: u64, log_index: u64) -> Log {
+        let mut user_topic = [0; 32];
+        user_topic[12..].copy_from_slice(user.0.as_bytes());
+        Log {
+            topics: vec![BALANCE_UPDATED_EVENT.signature(), user_topic.into()],
+            data: ethers_core::abi::encode(&[Token::Uint(balance.into())]).into(),
+            block_number: Some(U64::from(block_number)),
+            log_index: Some(EthU256::from(log_index)),
+            ..Default::default()
+        }
+    }
+
+    fn update(user: &H160, balance: u64, block_number: u64, log_index: u64) -> FeeBalanceUpdate {
+        FeeBalanceUpdate::from_log(balance_log(user, balance, block_number, log_index))
+            .expect("valid BalanceUpdated log")
+    }
+
+    #[test]
+    fn balance_updated_log_is_decoded() {
+        let user = H160::from_slice(&[0x42; 20]);
+        let update = update(&user, 1_000, 12, 3);
+        assert_eq!(update.user, user);
+        assert_eq!(update.balance.balance, U256::from(1_000u64));
+        assert_eq!(update.balance.position(), (12, 3));
+
+        let mut pending = balance_log(&user, 1_000, 12, 3);
+        pending.block_number = None;
+        assert!(FeeBalanceUpdate::from_log(pending).is_none());
+    }
+
+    fn fee_charge() -> H160 {
+        H160::from_slice(&[0x11; 20])
+    }
+
+    #[test]
+    fn later_updates_win() {
+        let user = H160::from_slice(&[0x42; 20]);
+        let mut balances = FeeBalances::new(VectorMemory::default());
+
+        assert!(balances.apply(&fee_charge(), update(&user, 500, 10, 1)));
+        assert!(!balances.apply(&fee_charge(), update(&user, 900, 10, 0)));
+        assert_eq!(
+            balances.get(&fee_charge(), &user).unwrap().balance,
+            U256::from(500u64)
+        );
+
+        assert!(balances.apply(&fee_charge(), update(&user, 100, 11, 0)));
+        assert_eq!(
+            balances.get(&fee_charge(), &user).unwrap().balance,
+            U256::from(100u64)
+        );
+        assert_eq!(balances.len(), 1);
+    }
+
+    #[test]
+    fn deposits_are_kept_per_fee_charge_contract() {
+        let user = H160::from_slice(&[0x42; 20]);
+        let new_fee_charge = H160::from_slice(&[0x12; 20]);
+        let mut balances = FeeBalances::new(VectorMemory::default());
+        balances.apply(&fee_charge(), update(&user, 500, 10, 0));
+
+        assert!(balances.get(&new_fee_charge, &user).is_none());
+        assert_eq!(
+            balances.reserve_fee(&new_fee_charge, &user, 1, &U256::from(1u64)),
+            H160::zero()
+        );
+    }
+
+    #[test]
+    fn fee_payer_needs_covering_deposit() {
+        let user = H160::from_slice(&[0x42; 20]);
+        let stranger = H160::from_slice(&[0x43; 20]);
+        let mut balances = FeeBalances::new(VectorMemory::default());
+        balances.apply(&fee_charge(), update(&user, 500, 10, 0));
+
+        assert_eq!(
+            balances.reserve_fee(&fee_charge(), &user, 1, &U256::from(501u64)),
+            H160::zero()
+        );
+        assert_eq!(
+            balances.reserve_fee(&fee_charge(), &user, 1, &U256::from(500u64)),
+            user
+        );
+        assert_eq!(
+            balances.reserve_fee(&fee_charge(), &stranger, 2, &U256::from(1u64)),
+            H160::zero()
+        );
+    }
+
+    #[test]
+    fn pending_orders_share_deposit() {
+        let user = H160::from_slice(&[0x42; 20]);
+        let fee = U256::from(300u64);
+        let mut balances = FeeBalances::new(VectorMemory::default());
+        balances.apply(&fee_charge(), update(&user, 500, 10, 0));
+
+        assert_eq!(balances.reserve_fee(&fee_charge(), &user, 1, &fee), user);
+        assert_eq!(
+            balances.reserve_fee(&fee_charge(), &user, 2, &fee),
+            H160::zero()
+        );
+        // Reserving the fee of the same order again doesn't count it twice.
+        assert_eq!(balances.reserve_fee(&fee_charge(), &user, 1, &fee), user);
+        assert_eq!(balances.reserved(&fee_charge(), &user), fee);
+
+        // The reservations are kept when the deposit changes.
+        balances.apply(&fee_charge(), update(&user, 600, 11, 0));
+        assert_eq!(balances.reserved(&fee_charge(), &user), fee);
+
+        balances.release_fee(&fee_charge(), &user, 1);
+        assert_eq!(balances.reserved(&fee_charge(), &user), U256::zero());
+        assert_eq!(balances.reserve_fee(&fee_charge(), &user, 2, &fee), user);
+    }
+}
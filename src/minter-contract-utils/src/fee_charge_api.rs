@@ -1,4 +1,6 @@
-use ethers_core::abi::{Constructor, Function, Param, ParamType, StateMutability};
+use ethers_core::abi::{
+    Constructor, Event, EventParam, Function, Param, ParamType, StateMutability,
+};
 use once_cell::sync::Lazy;
 
 pub static CONSTRUCTOR: Lazy<Constructor> = Lazy::new(|| Constructor {
@@ -55,3 +57,21 @@ pub static REMOVE_APPROVED_SPENDER_IDS: Lazy<Function> = Lazy::new(|| Function {
     constant: None,
     state_mutability: StateMutability::NonPayable,
 });
+
+/// Emitted when the native token deposit of a user changes.
+pub static BALANCE_UPDATED_EVENT: Lazy<Event> = Lazy::new(|| Event {
+    name: "BalanceUpdated".into(),
+    inputs: vec![
+        EventParam {
+            name: "user".into(),
+            kind: ParamType::Address,
+            indexed: true,
+        },
+        EventParam {
+            name: "balance".into(),
+            kind: ParamType::Uint(256),
+            indexed: false,
+        },
+    ],
+    anonymous: false,
+});
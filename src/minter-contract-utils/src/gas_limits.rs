@@ -0,0 +1,185 @@
+//! Gas limits of the EVM transactions sent by the bridges.
+//!
+//! A transaction is sent with the limit configured for its operation, or with
+//! [`DEFAULT_TX_GAS_LIMIT`]. If the EVM rejects the transaction as running out of gas, the bridge
+//! reports the error with [`GasLimits::on_transaction_error`], which bumps the limit of the
+//! operation, so the retry of the task is sent with a larger limit.
+
+use std::collections::HashMap;
+
+use candid::CandidType;
+use serde::Deserialize;
+
+/// Gas limit of the operations which have no configured limit.
+pub const DEFAULT_TX_GAS_LIMIT: u64 = 3_000_000;
+/// Default upper bound of the bumped gas limits.
+pub const DEFAULT_MAX_TX_GAS_LIMIT: u64 = 10_000_000;
+/// Gas used by a plain value transfer, below which no transaction can be executed.
+pub const MIN_TX_GAS_LIMIT: u64 = 21_000;
+
+/// Percentage of the current limit added to it after an out of gas failure.
+const GAS_BUMP_PERCENT: u64 = 50;
+
+/// Parts of the EVM error messages reporting that the transaction gas limit is too low.
+const OUT_OF_GAS_ERRORS: [&str; 4] = [
+    "out of gas",
+    "outofgas",
+    "intrinsic gas too low",
+    "gas required exceeds allowance",
+];
+
+/// Bridge operations sending EVM transactions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, CandidType, Deserialize)]
+pub enum GasOperation {
+    /// Sending of a signed mint order to the BftBridge.
+    Mint,
+    /// Update of the metadata of a wrapped token.
+    UpdateTokenMetadata,
+    /// Burn of the wrapped tokens with a permit of the holder.
+    BurnWithPermit,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct GasLimits {
+    /// Gas limits by the operation.
+    pub operations: HashMap<GasOperation, u64>,
+    /// Maximum gas limit the operations are bumped to.
+    pub max_gas_limit: u64,
+}
+
+impl Default for GasLimits {
+    fn default() -> Self {
+        Self {
+            operations: HashMap::new(),
+            max_gas_limit: DEFAULT_MAX_TX_GAS_LIMIT,
+        }
+    }
+}
+
+impl GasLimits {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_gas_limit < MIN_TX_GAS_LIMIT {
+            return Err(format!(
+                "Maximum gas limit must be at least {MIN_TX_GAS_LIMIT}"
+            ));
+        }
+
+        if let Some((operation, limit)) = self
+            .operations
+            .iter()
+            .find(|(_, limit)| !(MIN_TX_GAS_LIMIT..=self.max_gas_limit).contains(*limit))
+        {
+            return Err(format!(
+                "Gas limit {limit} of {operation:?} must be between {MIN_TX_GAS_LIMIT} and the maximum gas limit"
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Gas limit for the transactions of the operation.
+    pub fn gas_limit(&self, operation: GasOperation) -> u64 {
+        self.operations
+            .get(&operation)
+            .copied()
+            .unwrap_or(DEFAULT_TX_GAS_LIMIT)
+    }
+
+    /// Increases the gas limit of the operation, up to the maximum. Returns the new limit, or
+    /// `None` if the limit is already at the maximum.
+    pub fn bump(&mut self, operation: GasOperation) -> Option<u64> {
+        let current = self.gas_limit(operation);
+        if current >= self.max_gas_limit {
+            return None;
+        }
+
+        let bumped = current
+            .saturating_add(current * GAS_BUMP_PERCENT / 100)
+            .min(self.max_gas_limit);
+        self.operations.insert(operation, bumped);
+
+        Some(bumped)
+    }
+
+    /// Bumps the gas limit of the operation if its transaction failed with the out of gas
+    /// `error`.
+    pub fn on_transaction_error(&mut self, operation: GasOperation, error: &str) {
+        if !is_out_of_gas_error(error) {
+            return;
+        }
+
+        match self.bump(operation) {
+            Some(limit) => {
+                log::info!(
+                    "{operation:?} transaction ran out of gas, gas limit is bumped to {limit}"
+                )
+            }
+            None => log::warn!(
+                "{operation:?} transaction ran out of gas with the maximum gas limit {}",
+                self.max_gas_limit
+            ),
+        }
+    }
+}
+
+/// Checks whether the EVM error reports that the transaction ran out of gas.
+pub fn is_out_of_gas_error(error: &str) -> bool {
+    let error = error.to_lowercase();
+    OUT_OF_GAS_ERRORS
+        .iter()
+        .any(|pattern| error.contains(pattern))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gas_limit_is_bumped_up_to_maximum() {
+        let mut limits = GasLimits {
+            max_gas_limit: 5_000_000,
+            ..Default::default()
+        };
+        assert_eq!(limits.gas_limit(GasOperation::Mint), DEFAULT_TX_GAS_LIMIT);
+
+        assert_eq!(limits.bump(GasOperation::Mint), Some(4_500_000));
+        assert_eq!(limits.bump(GasOperation::Mint), Some(5_000_000));
+        assert_eq!(limits.bump(GasOperation::Mint), None);
+        assert_eq!(limits.gas_limit(GasOperation::Mint), 5_000_000);
+        assert_eq!(
+            limits.gas_limit(GasOperation::UpdateTokenMetadata),
+            DEFAULT_TX_GAS_LIMIT
+        );
+    }
+
+    #[test]
+    fn only_out_of_gas_errors_bump_limit() {
+        let mut limits = GasLimits::default();
+
+        limits.on_transaction_error(GasOperation::Mint, "nonce too low");
+        assert_eq!(limits.gas_limit(GasOperation::Mint), DEFAULT_TX_GAS_LIMIT);
+
+        limits.on_transaction_error(
+            GasOperation::Mint,
+            "JsonRpcError: Transaction error: OutOfGas",
+        );
+        assert!(limits.gas_limit(GasOperation::Mint) > DEFAULT_TX_GAS_LIMIT);
+    }
+
+    #[test]
+    fn limits_are_validated() {
+        assert!(GasLimits::default().validate().is_ok());
+
+        let mut limits = GasLimits {
+            max_gas_limit: 1_000,
+            ..Default::default()
+        };
+        assert!(limits.validate().is_err());
+
+        limits.max_gas_limit = DEFAULT_MAX_TX_GAS_LIMIT;
+        limits
+            .operations
+            .insert(GasOperation::Mint, DEFAULT_MAX_TX_GAS_LIMIT + 1);
+        assert!(limits.validate().is_err());
+    }
+}
@@ -0,0 +1,209 @@
+//! Gas price estimation for the transactions sent by the bridges.
+//!
+//! The gas price stored in [`EvmParams`](crate::evm_bridge::EvmParams) is only refreshed after a
+//! mint transaction is sent, so it quickly becomes stale when the fees change. Bridges sample the
+//! fees periodically with [`sample_gas_price`] and keep a smoothed estimate in a
+//! [`GasPriceSampler`], which is consulted every time a mint transaction is sent.
+
+use std::time::Duration;
+
+use anyhow::anyhow;
+use did::U256;
+use ethereum_json_rpc_client::{Client, EthJsonRpcClient};
+use ethers_core::types::U256 as EthU256;
+use jsonrpc_core::Id;
+use serde::Deserialize;
+
+use crate::query::{batch_query, Query, QueryType, FEE_HISTORY_ID, GAS_PRICE_ID};
+
+/// Interval between gas price samples.
+pub const GAS_PRICE_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Estimates older than this are not used.
+pub const MAX_ESTIMATE_AGE: Duration = Duration::from_secs(10 * 60);
+
+/// Number of latest blocks requested in `eth_feeHistory`.
+const FEE_HISTORY_BLOCKS: u64 = 10;
+
+/// Percentile of the priority fees paid in a block used as its priority fee.
+const PRIORITY_FEE_PERCENTILE: f64 = 50.0;
+
+/// Weight of the previous estimate when a lower sample is recorded.
+const DECAY_WEIGHT: u64 = 3;
+
+/// Smoothed gas price estimate.
+///
+/// The estimate follows the rising fees immediately, so transactions are not underpriced during
+/// fee spikes, and goes down gradually when the fees drop.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GasPriceSampler {
+    estimate: Option<U256>,
+    updated_at: u64,
+}
+
+impl GasPriceSampler {
+    /// Records the gas price sampled at `now` (nanoseconds).
+    pub fn record(&mut self, sample: U256, now: u64) {
+        let estimate = match &self.estimate {
+            Some(estimate) if estimate.0 > sample.0 => {
+                let weighted = estimate.0 * EthU256::from(DECAY_WEIGHT) + sample.0;
+                U256::from(weighted / EthU256::from(DECAY_WEIGHT + 1))
+            }
+            _ => sample,
+        };
+
+        self.estimate = Some(estimate);
+        self.updated_at = now;
+    }
+
+    /// Returns the estimate if it is not older than [`MAX_ESTIMATE_AGE`].
+    pub fn estimate(&self, now: u64) -> Option<U256> {
+        let age = now.saturating_sub(self.updated_at);
+        if age > MAX_ESTIMATE_AGE.as_nanos() as u64 {
+            return None;
+        }
+
+        self.estimate.clone()
+    }
+
+    /// Gas price to send a transaction with: the current estimate, or the `fallback` price if
+    /// there is no fresh estimate.
+    pub fn gas_price(&self, fallback: U256, now: u64) -> U256 {
+        self.estimate(now).unwrap_or(fallback)
+    }
+}
+
+/// Response of `eth_feeHistory`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeHistory {
+    /// Base fees of the requested blocks and of the next block.
+    pub base_fee_per_gas: Vec<U256>,
+    /// Priority fees of the requested blocks at the requested percentiles.
+    #[serde(default)]
+    pub reward: Vec<Vec<U256>>,
+}
+
+impl FeeHistory {
+    /// Gas price of a legacy transaction to be included in the next block: the base fee of the
+    /// next block plus the median of the priority fees paid in the latest blocks.
+    pub fn gas_price(&self) -> Option<U256> {
+        let next_base_fee = self.base_fee_per_gas.last()?;
+
+        let mut priority_fees: Vec<EthU256> = self
+            .reward
+            .iter()
+            .filter_map(|rewards| rewards.first())
+            .map(|reward| reward.0)
+            .collect();
+        priority_fees.sort();
+        let priority_fee = priority_fees
+            .get(priority_fees.len() / 2)
+            .copied()
+            .unwrap_or_default();
+
+        Some(U256::from(next_base_fee.0 + priority_fee))
+    }
+}
+
+/// Samples the gas price of the EVM. The price estimated from the fee history is used if the
+/// EVM supports EIP-1559, unless `eth_gasPrice` returns a higher one.
+pub async fn sample_gas_price(client: &EthJsonRpcClient<impl Client>) -> anyhow::Result<U256> {
+    let fee_history_query = QueryType::FeeHistory {
+        block_count: FEE_HISTORY_BLOCKS,
+        reward_percentiles: vec![PRIORITY_FEE_PERCENTILE],
+    };
+
+    let responses = match batch_query(client, &[QueryType::GasPrice, fee_history_query]).await {
+        Ok(responses) => responses,
+        Err(err) => {
+            log::debug!("failed to get fee history, falling back to eth_gasPrice: {err:?}");
+            batch_query(client, &[QueryType::GasPrice]).await?
+        }
+    };
+
+    let gas_price: U256 = responses.get_value_by_id(Id::Str(GAS_PRICE_ID.into()))?;
+    let fee_history_price = responses
+        .get_value_by_id::<FeeHistory>(Id::Str(FEE_HISTORY_ID.into()))
+        .ok()
+        .and_then(|history| history.gas_price());
+
+    let price = match fee_history_price {
+        Some(price) if price.0 > gas_price.0 => price,
+        _ => gas_price,
+    };
+
+    if price.0.is_zero() {
+        return Err(anyhow!("EVM returned zero gas price"));
+    }
+
+    Ok(price)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECOND: u64 = 1_000_000_000;
+
+    fn gwei(value: u64) -> U256 {
+        U256::from(value * 1_000_000_000)
+    }
+
+    #[test]
+    fn estimate_follows_rising_prices_immediately() {
+        let mut sampler = GasPriceSampler::default();
+        assert_eq!(sampler.estimate(0), None);
+
+        sampler.record(gwei(10), SECOND);
+        assert_eq!(sampler.estimate(SECOND), Some(gwei(10)));
+
+        sampler.record(gwei(50), 2 * SECOND);
+        assert_eq!(sampler.estimate(2 * SECOND), Some(gwei(50)));
+    }
+
+    #[test]
+    fn estimate_decays_gradually() {
+        let mut sampler = GasPriceSampler::default();
+        sampler.record(gwei(50), SECOND);
+        sampler.record(gwei(10), 2 * SECOND);
+
+        assert_eq!(sampler.estimate(2 * SECOND), Some(gwei(40)));
+    }
+
+    #[test]
+    fn stale_estimate_is_not_used() {
+        let mut sampler = GasPriceSampler::default();
+        sampler.record(gwei(10), SECOND);
+
+        let stale_at = SECOND + MAX_ESTIMATE_AGE.as_nanos() as u64 + 1;
+        assert_eq!(sampler.estimate(stale_at), None);
+        assert_eq!(sampler.gas_price(gwei(7), stale_at), gwei(7));
+        assert_eq!(sampler.gas_price(gwei(7), SECOND), gwei(10));
+    }
+
+    #[test]
+    fn fee_history_price() {
+        let history: FeeHistory = serde_json::from_value(serde_json::json!({
+            "oldestBlock": "0x10",
+            "baseFeePerGas": ["0x64", "0x6e", "0x78"],
+            "gasUsedRatio": [0.5, 0.7],
+            "reward": [["0x5"], ["0x1"], ["0x3"]],
+        }))
+        .unwrap();
+
+        assert_eq!(history.gas_price(), Some(U256::from(0x78u64 + 3)));
+    }
+
+    #[test]
+    fn fee_history_without_base_fee() {
+        let history: FeeHistory = serde_json::from_value(serde_json::json!({
+            "oldestBlock": "0x10",
+            "baseFeePerGas": [],
+            "gasUsedRatio": [],
+        }))
+        .unwrap();
+
+        assert_eq!(history.gas_price(), None);
+    }
+}
@@ -0,0 +1,186 @@
+//! Health monitoring of the bridge canister dependencies.
+//!
+//! Bridge canisters check their dependencies periodically from a scheduler task and record the
+//! results in a [`HealthMonitor`]. The monitor is then exposed with a `health` query, so external
+//! monitoring can see which dependency is failing without issuing update calls by itself.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use candid::CandidType;
+use eth_signer::sign_strategy::TransactionSigner;
+use ethereum_json_rpc_client::{Client, EthJsonRpcClient};
+use serde::Deserialize;
+
+use crate::evm_link::EvmLink;
+
+/// Interval between dependency checks.
+pub const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// EVM JSON-RPC endpoint the bridge works with.
+pub const EVM_RPC: &str = "evm_rpc";
+/// Transaction and mint order signer of the bridge.
+pub const SIGNER: &str = "signer";
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub enum DependencyStatus {
+    /// The dependency was not checked yet.
+    #[default]
+    Unknown,
+    Healthy,
+    /// The last check failed with the given error.
+    Unhealthy(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct DependencyHealth {
+    pub name: String,
+    pub status: DependencyStatus,
+    /// Timestamp (nanoseconds) of the last check.
+    pub last_check: Option<u64>,
+    /// Timestamp (nanoseconds) of the last successful check.
+    pub last_success: Option<u64>,
+}
+
+impl DependencyHealth {
+    fn unchecked(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            status: DependencyStatus::Unknown,
+            last_check: None,
+            last_success: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct HealthReport {
+    /// `true` if the last check of every dependency succeeded.
+    pub healthy: bool,
+    pub dependencies: Vec<DependencyHealth>,
+}
+
+/// Results of the latest dependency checks.
+#[derive(Debug, Default, Clone)]
+pub struct HealthMonitor {
+    dependencies: BTreeMap<String, DependencyHealth>,
+}
+
+impl HealthMonitor {
+    /// Creates a monitor for the given dependencies, all in the `Unknown` state.
+    pub fn new(dependencies: &[&str]) -> Self {
+        Self {
+            dependencies: dependencies
+                .iter()
+                .map(|name| (name.to_string(), DependencyHealth::unchecked(name)))
+                .collect(),
+        }
+    }
+
+    /// Records the result of a dependency check performed at `now`.
+    pub fn record(&mut self, dependency: &str, result: Result<(), String>, now: u64) {
+        let health = self
+            .dependencies
+            .entry(dependency.to_string())
+            .or_insert_with(|| DependencyHealth::unchecked(dependency));
+
+        health.last_check = Some(now);
+        match result {
+            Ok(()) => {
+                health.status = DependencyStatus::Healthy;
+                health.last_success = Some(now);
+            }
+            Err(err) => {
+                log::warn!("Health check of {dependency} failed: {err}");
+                health.status = DependencyStatus::Unhealthy(err);
+            }
+        }
+    }
+
+    pub fn report(&self) -> HealthReport {
+        HealthReport {
+            healthy: self
+                .dependencies
+                .values()
+                .all(|health| health.status == DependencyStatus::Healthy),
+            dependencies: self.dependencies.values().cloned().collect(),
+        }
+    }
+}
+
+/// Checks that the EVM JSON-RPC endpoint responds by requesting the latest block number.
+pub async fn check_evm_link(link: &EvmLink) -> Result<(), String> {
+    check_evm_client(&link.get_json_rpc_client()).await
+}
+
+/// Same as [`check_evm_link`], but for an already created client.
+pub async fn check_evm_client<C: Client>(client: &EthJsonRpcClient<C>) -> Result<(), String> {
+    client
+        .get_block_number()
+        .await
+        .map(|_| ())
+        .map_err(|err| format!("failed to get block number: {err}"))
+}
+
+/// Checks that the signer is able to produce its address, which requires the signing key to be
+/// available.
+pub async fn check_signer(signer: &impl TransactionSigner) -> Result<(), String> {
+    signer
+        .get_address()
+        .await
+        .map(|_| ())
+        .map_err(|err| format!("failed to get signer address: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unchecked_dependencies_are_not_healthy() {
+        let monitor = HealthMonitor::new(&[EVM_RPC, SIGNER]);
+        let report = monitor.report();
+
+        assert!(!report.healthy);
+        assert_eq!(report.dependencies.len(), 2);
+        assert!(report
+            .dependencies
+            .iter()
+            .all(|health| health.status == DependencyStatus::Unknown));
+    }
+
+    #[test]
+    fn check_results_are_recorded() {
+        let mut monitor = HealthMonitor::new(&[EVM_RPC, SIGNER]);
+        monitor.record(EVM_RPC, Ok(()), 10);
+        monitor.record(SIGNER, Ok(()), 10);
+        assert!(monitor.report().healthy);
+
+        monitor.record(EVM_RPC, Err("timeout".to_string()), 20);
+        let report = monitor.report();
+        assert!(!report.healthy);
+
+        let evm = report
+            .dependencies
+            .iter()
+            .find(|health| health.name == EVM_RPC)
+            .unwrap();
+        assert_eq!(
+            evm.status,
+            DependencyStatus::Unhealthy("timeout".to_string())
+        );
+        assert_eq!(evm.last_check, Some(20));
+        assert_eq!(evm.last_success, Some(10));
+    }
+
+    #[test]
+    fn unknown_dependency_is_added_on_record() {
+        let mut monitor = HealthMonitor::default();
+        monitor.record("indexer", Ok(()), 5);
+
+        let report = monitor.report();
+        assert!(report.healthy);
+        assert_eq!(report.dependencies[0].name, "indexer");
+        assert_eq!(report.dependencies[0].last_success, Some(5));
+    }
+}
@@ -0,0 +1,218 @@
+//! Serde formats of the hashes, addresses and byte strings shared by the JSON interfaces.
+//!
+//! The modules are used with `#[serde(with = "...")]`. In the human readable formats, e.g. JSON
+//! of the HTTP requests and of the bridge-tool output, the values are `0x` prefixed hex strings.
+//! In the binary formats, e.g. the stored scheduler tasks, the default serde representation of
+//! the type is kept, so the stored values decode as before. The candid encoding doesn't use serde
+//! and is not affected.
+
+use std::fmt;
+
+use serde::de::{self, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+fn to_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+fn from_hex<E: de::Error>(value: &str) -> Result<Vec<u8>, E> {
+    let digits = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value);
+    hex::decode(digits).map_err(|err| E::custom(format!("invalid hex string {value:?}: {err}")))
+}
+
+/// Accepts a hex string, or the bytes as a sequence of numbers written by the previous format.
+struct HexBytesVisitor;
+
+impl<'de> Visitor<'de> for HexBytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a 0x prefixed hex string")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        from_hex(value)
+    }
+
+    fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<Self::Value, E> {
+        Ok(value.to_vec())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+        Ok(bytes)
+    }
+}
+
+fn deserialize_fixed<'de, D: Deserializer<'de>>(
+    deserializer: D,
+    len: usize,
+) -> Result<Vec<u8>, D::Error> {
+    let bytes = deserializer.deserialize_any(HexBytesVisitor)?;
+    if bytes.len() != len {
+        return Err(de::Error::invalid_length(
+            bytes.len(),
+            &format!("{len} bytes").as_str(),
+        ));
+    }
+    Ok(bytes)
+}
+
+/// `Vec<u8>` byte strings.
+pub mod bytes {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&to_hex(value))
+        } else {
+            value.serialize(serializer)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(HexBytesVisitor)
+        } else {
+            Vec::deserialize(deserializer)
+        }
+    }
+}
+
+/// `did::H160` addresses.
+pub mod h160 {
+    use did::H160;
+
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &H160, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&to_hex(value.0.as_bytes()))
+        } else {
+            value.serialize(serializer)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<H160, D::Error> {
+        if deserializer.is_human_readable() {
+            deserialize_fixed(deserializer, 20).map(|bytes| H160::from_slice(&bytes))
+        } else {
+            H160::deserialize(deserializer)
+        }
+    }
+}
+
+/// `did::H256` hashes.
+pub mod h256 {
+    use did::H256;
+
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &H256, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&to_hex(value.0.as_bytes()))
+        } else {
+            value.serialize(serializer)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<H256, D::Error> {
+        if deserializer.is_human_readable() {
+            deserialize_fixed(deserializer, 32).map(|bytes| H256::from_slice(&bytes))
+        } else {
+            H256::deserialize(deserializer)
+        }
+    }
+}
+
+/// `did::U256` numbers, as hex quantities without leading zeros like in the Ethereum JSON-RPC.
+pub mod u256 {
+    use did::U256;
+
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &U256, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&format!("{:#x}", value.0))
+        } else {
+            value.serialize(serializer)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<U256, D::Error> {
+        if !deserializer.is_human_readable() {
+            return U256::deserialize(deserializer);
+        }
+
+        let value = String::deserialize(deserializer)?;
+        let digits = value
+            .strip_prefix("0x")
+            .or_else(|| value.strip_prefix("0X"))
+            .ok_or_else(|| de::Error::custom(format!("{value:?} is not a 0x prefixed number")))?;
+        ethers_core::types::U256::from_str_radix(digits, 16)
+            .map(U256)
+            .map_err(|err| de::Error::custom(format!("invalid number {value:?}: {err}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use did::{H160, H256, U256};
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Formatted {
+        #[serde(with = "bytes")]
+        data: Vec<u8>,
+        #[serde(with = "h160")]
+        address: H160,
+        #[serde(with = "h256")]
+        hash: H256,
+        #[serde(with = "u256")]
+        amount: U256,
+    }
+
+    fn formatted() -> Formatted {
+        Formatted {
+            data: vec![0xab, 0x01],
+            address: H160::from_slice(&[0x11; 20]),
+            hash: H256::from_slice(&[0x22; 32]),
+            amount: U256::from(255u64),
+        }
+    }
+
+    #[test]
+    fn json_uses_prefixed_hex_strings() {
+        let json = serde_json::to_value(formatted()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "data": "0xab01",
+                "address": format!("0x{}", "11".repeat(20)),
+                "hash": format!("0x{}", "22".repeat(32)),
+                "amount": "0xff",
+            })
+        );
+
+        let decoded: Formatted = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded, formatted());
+    }
+
+    #[test]
+    fn json_byte_arrays_and_wrong_lengths() {
+        let mut json = serde_json::to_value(formatted()).unwrap();
+        json["data"] = serde_json::json!([171, 1]);
+        let decoded: Formatted = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(decoded.data, vec![0xab, 0x01]);
+
+        json["address"] = serde_json::json!("0x1111");
+        assert!(serde_json::from_value::<Formatted>(json).is_err());
+    }
+}
@@ -0,0 +1,212 @@
+//! Window of the mint transactions sent by a bridge and not yet confirmed.
+//!
+//! An EVM limits the number of the pending transactions of an account, so a bridge sending a
+//! mint transaction for every signed order may have its transactions rejected under load. A
+//! transaction takes a slot of [`InFlightTxs`] before it is sent and frees it once the `Minted`
+//! event of the order is collected. When all the slots are taken, the transactions wait in a FIFO
+//! queue and take the freed slots in the order they asked for them.
+//!
+//! A slot of a transaction which is never confirmed, e.g. dropped by the EVM, is freed after
+//! [`IN_FLIGHT_TX_TIMEOUT`], and a queued transaction which stops asking for a slot leaves the
+//! queue after [`QUEUED_TX_TIMEOUT`]. The size of the window is kept in the stable memory, while
+//! the transactions of the window are kept in the heap, so the window is empty after an upgrade.
+
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::time::Duration;
+
+use candid::CandidType;
+use ic_stable_structures::stable_structures::Memory;
+use ic_stable_structures::{CellStructure, StableCell};
+use serde::Deserialize;
+
+/// Default number of the mint transactions in flight.
+pub const DEFAULT_MAX_IN_FLIGHT_TXS: u32 = 16;
+/// Time after which an unconfirmed transaction frees its slot.
+pub const IN_FLIGHT_TX_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+/// Time after which a queued transaction which did not ask for a slot again leaves the queue.
+pub const QUEUED_TX_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, CandidType, Deserialize)]
+pub struct InFlightTxsInfo {
+    pub max_in_flight: u32,
+    pub in_flight: u32,
+    pub queued: u32,
+}
+
+pub struct InFlightTxs<K, M: Memory> {
+    max_in_flight: StableCell<u32, M>,
+    /// Keys of the sent transactions with the time (nanoseconds) they took their slots.
+    in_flight: HashMap<K, u64>,
+    /// Keys of the queued transactions with the time (nanoseconds) they last asked for a slot.
+    queue: VecDeque<(K, u64)>,
+}
+
+impl<K: Clone + Eq + Hash, M: Memory> InFlightTxs<K, M> {
+    pub fn new(memory: M) -> Self {
+        Self {
+            max_in_flight: StableCell::new(memory, DEFAULT_MAX_IN_FLIGHT_TXS)
+                .expect("stable memory in-flight window initialization failed"),
+            in_flight: HashMap::new(),
+            queue: VecDeque::new(),
+        }
+    }
+
+    pub fn max_in_flight(&self) -> u32 {
+        *self.max_in_flight.get()
+    }
+
+    /// Sets the size of the window. The transactions in flight keep their slots.
+    pub fn set_max_in_flight(&mut self, max_in_flight: u32) -> Result<(), String> {
+        if max_in_flight == 0 {
+            return Err("Maximum number of transactions in flight must be positive".into());
+        }
+
+        self.max_in_flight
+            .set(max_in_flight)
+            .expect("failed to update in-flight window size");
+        Ok(())
+    }
+
+    /// Takes a slot for the transaction of the `key`. Returns `false` if the transaction must
+    /// wait, in which case it is queued and should ask again later. A transaction which has a
+    /// slot already, e.g. when it is retried, keeps it.
+    pub fn try_acquire(&mut self, key: K, now: u64) -> bool {
+        let timeout = IN_FLIGHT_TX_TIMEOUT.as_nanos() as u64;
+        self.in_flight
+            .retain(|_, sent_at| now.saturating_sub(*sent_at) < timeout);
+
+        if self.in_flight.contains_key(&key) {
+            return true;
+        }
+
+        // A transaction which stopped asking, e.g. because its task ran out of retries, would
+        // block the transactions queued after it.
+        let queue_timeout = QUEUED_TX_TIMEOUT.as_nanos() as u64;
+        self.queue
+            .retain(|(_, asked_at)| now.saturating_sub(*asked_at) < queue_timeout);
+
+        let queued = self.queue.iter_mut().find(|(queued, _)| *queued == key);
+        let is_queued = match queued {
+            Some((_, asked_at)) => {
+                *asked_at = now;
+                true
+            }
+            None => false,
+        };
+
+        let is_next = self.queue.front().map_or(true, |(next, _)| *next == key);
+        if is_next && self.in_flight.len() < self.max_in_flight() as usize {
+            if is_queued {
+                self.queue.pop_front();
+            }
+            self.in_flight.insert(key, now);
+            return true;
+        }
+
+        if !is_queued {
+            self.queue.push_back((key, now));
+        }
+        false
+    }
+
+    /// Frees the slot of the confirmed transaction or of the one which failed to be sent, or
+    /// drops the transaction from the queue.
+    pub fn release(&mut self, key: &K) {
+        self.in_flight.remove(key);
+        self.queue.retain(|(queued, _)| queued != key);
+    }
+
+    pub fn info(&self) -> InFlightTxsInfo {
+        InFlightTxsInfo {
+            max_in_flight: self.max_in_flight(),
+            in_flight: self.in_flight.len() as u32,
+            queued: self.queue.len() as u32,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ic_stable_structures::VectorMemory;
+
+    use super::*;
+
+    fn window() -> InFlightTxs<u32, VectorMemory> {
+        InFlightTxs::new(VectorMemory::default())
+    }
+
+    #[test]
+    fn queued_transactions_take_freed_slots_in_order() {
+        let mut window = window();
+        window.set_max_in_flight(2).unwrap();
+
+        assert!(window.try_acquire(1, 0));
+        assert!(window.try_acquire(2, 0));
+        assert!(!window.try_acquire(3, 0));
+        assert!(!window.try_acquire(4, 0));
+        assert!(window.try_acquire(2, 0));
+
+        window.release(&1);
+        assert!(!window.try_acquire(4, 0));
+        assert!(window.try_acquire(3, 0));
+        assert!(!window.try_acquire(4, 0));
+
+        assert_eq!(
+            window.info(),
+            InFlightTxsInfo {
+                max_in_flight: 2,
+                in_flight: 2,
+                queued: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn window_size_is_kept_in_memory() {
+        let memory = VectorMemory::default();
+        InFlightTxs::<u32, _>::new(memory.clone())
+            .set_max_in_flight(3)
+            .unwrap();
+
+        assert_eq!(InFlightTxs::<u32, _>::new(memory).max_in_flight(), 3);
+        assert_eq!(window().max_in_flight(), DEFAULT_MAX_IN_FLIGHT_TXS);
+    }
+
+    #[test]
+    fn unconfirmed_transactions_free_slots_after_timeout() {
+        let mut window = window();
+        window.set_max_in_flight(1).unwrap();
+
+        assert!(window.try_acquire(1, 0));
+        assert!(!window.try_acquire(2, 1));
+
+        let timeout = IN_FLIGHT_TX_TIMEOUT.as_nanos() as u64;
+        assert!(window.try_acquire(2, timeout));
+        assert!(window.set_max_in_flight(0).is_err());
+    }
+
+    #[test]
+    fn queue_head_which_stops_asking_is_evicted() {
+        let mut window = window();
+        window.set_max_in_flight(1).unwrap();
+
+        assert!(window.try_acquire(1, 0));
+        assert!(!window.try_acquire(2, 0));
+        assert!(!window.try_acquire(3, 0));
+        window.release(&1);
+
+        // The head keeps its turn while it asks again within the timeout.
+        let queue_timeout = QUEUED_TX_TIMEOUT.as_nanos() as u64;
+        assert!(!window.try_acquire(3, queue_timeout - 1));
+        assert!(window.try_acquire(3, queue_timeout));
+        assert_eq!(
+            window.info(),
+            InFlightTxsInfo {
+                max_in_flight: 1,
+                in_flight: 1,
+                queued: 0,
+            }
+        );
+    }
+}
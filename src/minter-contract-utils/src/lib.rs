@@ -1,9 +1,46 @@
+pub mod account_mapping;
+pub mod admin_approvals;
 pub mod bft_bridge_api;
+pub mod bft_bridge_deploy;
+pub mod bridge_tx_log;
+pub mod btc_address;
+pub mod btc_confirmations;
+pub mod btc_network;
 pub mod build_data;
+pub mod burn_notification;
+pub mod burn_permit;
+pub mod canister_status;
+pub mod certified_data;
+pub mod circuit_breaker;
+pub mod clock;
+pub mod config_guard;
+pub mod config_validation;
+pub mod confirmation_policy;
+pub mod derivation_path;
+pub mod did_output;
+pub mod erc20_metadata;
+pub mod event_subscribers;
 pub mod evm_bridge;
 pub mod evm_link;
+pub mod fee_balances;
 pub mod fee_charge_api;
+pub mod gas_limits;
+pub mod gas_price;
+pub mod health;
+pub mod hex_serde;
+pub mod in_flight_txs;
+pub mod long_poll;
+pub mod mint_completion;
+pub mod mint_order_codec;
+pub mod mint_order_vectors;
 pub mod mint_orders;
+pub mod operation_archive;
 pub mod operation_store;
+pub mod operation_trace;
+pub mod pagination;
 pub mod query;
+pub mod soft_caps;
+pub mod task_dedup;
+pub mod task_limits;
+pub mod withdrawal_allowlist;
 pub mod wrapped_token_api;
@@ -0,0 +1,172 @@
+//! Long polling of the operation states.
+//!
+//! A client following an operation would call its status query in a loop until the operation
+//! completes. The `await_operation` endpoints of the bridges hold the call instead: the state is
+//! checked until it changes or the timeout expires, and the last state is returned, so the client
+//! makes one call per state change.
+//!
+//! The IC rejects a call that returns with no calls of its own outstanding, and a timer callback
+//! cannot reply to it, so the waiting call keeps its call context open by awaiting `raw_rand`
+//! calls to the management canister between the checks.
+//!
+//! A canister cannot be stopped, and so upgraded, while it has calls in progress, so the timeout
+//! is capped by [`MAX_AWAIT_TIMEOUT`]. Every waiting call makes management canister calls
+//! charged to the canister, so at most [`MAX_WAITERS`] calls wait at once and the others are rejected.
+
+use std::cell::Cell;
+use std::future::Future;
+use std::time::Duration;
+
+use candid::CandidType;
+use ic_exports::ic_cdk;
+use ic_exports::ic_cdk::api::management_canister::main::raw_rand;
+use serde::Deserialize;
+
+/// Maximum time a call waits for the state to change.
+pub const MAX_AWAIT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Interval between the checks of the state.
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Maximum number of calls waiting at once.
+pub const MAX_WAITERS: usize = 100;
+
+/// The call is rejected because [`MAX_WAITERS`] calls are waiting already. The client should
+/// query the state instead, or retry later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, CandidType, Deserialize)]
+pub struct TooManyWaiters;
+
+thread_local! {
+    static WAITERS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Slot of a waiting call, freed on drop.
+struct Waiter(());
+
+impl Waiter {
+    fn try_new() -> Result<Self, TooManyWaiters> {
+        WAITERS.with(|waiters| {
+            if waiters.get() >= MAX_WAITERS {
+                return Err(TooManyWaiters);
+            }
+
+            waiters.set(waiters.get() + 1);
+            Ok(Self(()))
+        })
+    }
+}
+
+impl Drop for Waiter {
+    fn drop(&mut self) {
+        WAITERS.with(|waiters| waiters.set(waiters.get().saturating_sub(1)));
+    }
+}
+
+/// Waits until the value returned by `state` changes, or until `timeout_secs` pass, and returns
+/// the last value. The values are compared by their candid encoding.
+pub async fn await_change<T: CandidType>(
+    timeout_secs: u64,
+    state: impl Fn() -> T,
+) -> Result<T, TooManyWaiters> {
+    let _waiter = Waiter::try_new()?;
+    Ok(await_change_with(Duration::from_secs(timeout_secs), state, sleep).await)
+}
+
+async fn await_change_with<T, F>(
+    timeout: Duration,
+    state: impl Fn() -> T,
+    sleep: impl Fn(Duration) -> F,
+) -> T
+where
+    T: CandidType,
+    F: Future<Output = ()>,
+{
+    let timeout = timeout.min(MAX_AWAIT_TIMEOUT);
+    let mut current = state();
+    let initial = encode(&current);
+
+    let mut waited = Duration::ZERO;
+    while waited < timeout {
+        let interval = CHECK_INTERVAL.min(timeout - waited);
+        sleep(interval).await;
+        waited += interval;
+
+        current = state();
+        if encode(&current) != initial {
+            break;
+        }
+    }
+
+    current
+}
+
+fn encode<T: CandidType>(value: &T) -> Vec<u8> {
+    candid::encode_one(value).expect("failed to encode state")
+}
+
+/// Waits for `duration` to pass, awaiting a `raw_rand` call per execution round.
+async fn sleep(duration: Duration) {
+    let deadline = ic_cdk::api::time().saturating_add(duration.as_nanos() as u64);
+    while ic_cdk::api::time() < deadline {
+        if raw_rand().await.is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn returns_on_change() {
+        let checks = Cell::new(0u32);
+        let slept = Cell::new(Duration::ZERO);
+        let state = || {
+            checks.set(checks.get() + 1);
+            checks.get() > 3
+        };
+        let sleep = |interval| {
+            slept.set(slept.get() + interval);
+            std::future::ready(())
+        };
+
+        assert!(await_change_with(Duration::from_secs(10), state, sleep).await);
+        assert_eq!(checks.get(), 4);
+        assert_eq!(slept.get(), CHECK_INTERVAL * 3);
+    }
+
+    #[tokio::test]
+    async fn returns_unchanged_state_on_timeout() {
+        let slept = Cell::new(Duration::ZERO);
+        let sleep = |interval| {
+            slept.set(slept.get() + interval);
+            std::future::ready(())
+        };
+
+        let timeout = CHECK_INTERVAL * 2 + Duration::from_millis(500);
+        assert_eq!(await_change_with(timeout, || 42u64, sleep).await, 42);
+        assert_eq!(slept.get(), timeout);
+
+        slept.set(Duration::ZERO);
+        await_change_with(Duration::MAX, || 42u64, sleep).await;
+        assert_eq!(slept.get(), MAX_AWAIT_TIMEOUT);
+
+        slept.set(Duration::ZERO);
+        await_change_with(Duration::ZERO, || 42u64, sleep).await;
+        assert_eq!(slept.get(), Duration::ZERO);
+    }
+
+    #[test]
+    fn waiters_are_capped() {
+        let waiters: Vec<_> = (0..MAX_WAITERS)
+            .map(|_| Waiter::try_new().unwrap())
+            .collect();
+        assert!(matches!(Waiter::try_new(), Err(TooManyWaiters)));
+
+        drop(waiters);
+        assert!(Waiter::try_new().is_ok());
+    }
+}
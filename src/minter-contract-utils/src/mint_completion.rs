@@ -0,0 +1,102 @@
+//! Completion of the deposits detected from the `Minted` events of the BftBridge.
+//!
+//! A mint order may be sent to the BftBridge by the bridge canister or by the user, so the only
+//! reliable signal that the wrapped tokens are minted is the `Minted` event collected from the
+//! EVM. The bridges record the transaction of the event with the deposit, and report the deposit
+//! progress as a [`DepositStatus`].
+
+use std::borrow::Cow;
+
+use candid::{CandidType, Decode, Encode};
+use did::H256;
+use ethers_core::types::Log;
+use ic_stable_structures::{Bound, Storable};
+use serde::{Deserialize, Serialize};
+
+/// EVM transaction which executed a mint order.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Serialize, Deserialize)]
+pub struct MintTx {
+    pub tx_hash: H256,
+    /// Number of the block with the transaction. `None` if it is not known, e.g. for the mints
+    /// completed before the block numbers were recorded.
+    pub block_number: Option<u64>,
+}
+
+impl MintTx {
+    /// Transaction which emitted the event log. `None` if the log has no transaction hash, which
+    /// is only the case for the logs of pending transactions.
+    pub fn from_log(log: &Log) -> Option<Self> {
+        Some(Self {
+            tx_hash: log.transaction_hash?.into(),
+            block_number: log.block_number.map(|number| number.as_u64()),
+        })
+    }
+}
+
+/// Progress of a deposit, in the form common for the bridges.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Serialize, Deserialize)]
+pub enum DepositStatus {
+    /// The deposit is being processed, or its mint orders wait to be executed on the EVM.
+    Pending,
+    /// The wrapped tokens are minted by the given transactions.
+    Completed { mints: Vec<MintTx> },
+    /// The deposit cannot be completed.
+    Failed { reason: String },
+}
+
+impl DepositStatus {
+    pub fn is_completed(&self) -> bool {
+        matches!(self, Self::Completed { .. })
+    }
+}
+
+impl Storable for DepositStatus {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("serialization failed"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).expect("deserialization failed")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers_core::types::{H256 as EthH256, U64};
+
+    use super::*;
+
+    #[test]
+    fn mint_tx_is_taken_from_log() {
+        let log = Log {
+            transaction_hash: Some(EthH256::repeat_byte(1)),
+            block_number: Some(U64::from(42)),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            MintTx::from_log(&log),
+            Some(MintTx {
+                tx_hash: EthH256::repeat_byte(1).into(),
+                block_number: Some(42),
+            })
+        );
+        assert_eq!(MintTx::from_log(&Log::default()), None);
+    }
+
+    #[test]
+    fn status_storable_roundtrip() {
+        let status = DepositStatus::Completed {
+            mints: vec![MintTx {
+                tx_hash: EthH256::repeat_byte(2).into(),
+                block_number: None,
+            }],
+        };
+
+        assert_eq!(DepositStatus::from_bytes(status.to_bytes()), status);
+        assert!(status.is_completed());
+        assert!(!DepositStatus::Pending.is_completed());
+    }
+}
@@ -0,0 +1,223 @@
+//! Checked decoding of the signed mint orders.
+//!
+//! Signed mint orders reach the tools and canisters as raw bytes, e.g. from a candid blob or a
+//! command line argument. [`decode_signed_mint_order`] checks the length of the bytes before they
+//! are decoded, so malformed orders are reported as errors instead of being mis-sliced.
+
+use minter_did::order::{MintOrder, SignedMintOrder};
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum MintOrderDecodeError {
+    #[error("invalid signed mint order length: expected {expected} bytes, got {actual}")]
+    InvalidLength { expected: usize, actual: usize },
+    #[error("signed mint order data cannot be decoded")]
+    InvalidData,
+}
+
+/// Decodes the bytes of a signed mint order.
+pub fn decode_signed_mint_order(bytes: &[u8]) -> Result<MintOrder, MintOrderDecodeError> {
+    if bytes.len() != MintOrder::SIGNED_ENCODED_DATA_SIZE {
+        return Err(MintOrderDecodeError::InvalidLength {
+            expected: MintOrder::SIGNED_ENCODED_DATA_SIZE,
+            actual: bytes.len(),
+        });
+    }
+
+    MintOrder::decode_signed(bytes)
+        .map(|(order, _)| order)
+        .ok_or(MintOrderDecodeError::InvalidData)
+}
+
+/// Decodes the signed mint order stored by a bridge.
+pub fn decode_stored_mint_order(
+    order: &SignedMintOrder,
+) -> Result<MintOrder, MintOrderDecodeError> {
+    decode_signed_mint_order(&order.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use did::{H160, U256};
+    use eth_signer::sign_strategy::{SigningStrategy, TransactionSigner};
+    use ethers_core::types::U256 as EthU256;
+    use ic_exports::ic_kit::MockContext;
+    use minter_did::id256::Id256;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+
+    const CASES: usize = 32;
+
+    /// Order fields decoded with the offsets used by `BftBridge._decodeAndValidateOrder`, which
+    /// is the reference the Rust encoding must agree with.
+    #[derive(Debug, PartialEq, Eq)]
+    struct ContractOrder {
+        amount: [u8; 32],
+        sender_id: [u8; 32],
+        from_token_id: [u8; 32],
+        recipient: [u8; 20],
+        to_erc20: [u8; 20],
+        nonce: u32,
+        sender_chain_id: u32,
+        recipient_chain_id: u32,
+        name: [u8; 32],
+        symbol: [u8; 16],
+        decimals: u8,
+        approve_spender: [u8; 20],
+        approve_amount: [u8; 32],
+        fee_payer: [u8; 20],
+    }
+
+    impl ContractOrder {
+        fn decode(data: &[u8]) -> Self {
+            let bytes = |from: usize, to: usize| &data[from..to];
+            let u32_at =
+                |from: usize| u32::from_be_bytes(bytes(from, from + 4).try_into().unwrap());
+
+            Self {
+                amount: bytes(0, 32).try_into().unwrap(),
+                sender_id: bytes(32, 64).try_into().unwrap(),
+                from_token_id: bytes(64, 96).try_into().unwrap(),
+                recipient: bytes(96, 116).try_into().unwrap(),
+                to_erc20: bytes(116, 136).try_into().unwrap(),
+                nonce: u32_at(136),
+                sender_chain_id: u32_at(140),
+                recipient_chain_id: u32_at(144),
+                name: bytes(148, 180).try_into().unwrap(),
+                symbol: bytes(180, 196).try_into().unwrap(),
+                decimals: data[196],
+                approve_spender: bytes(197, 217).try_into().unwrap(),
+                approve_amount: bytes(217, 249).try_into().unwrap(),
+                fee_payer: bytes(249, 269).try_into().unwrap(),
+            }
+        }
+
+        fn from_order(order: &MintOrder) -> Self {
+            let mut amount = [0; 32];
+            order.amount.0.to_big_endian(&mut amount);
+            let mut approve_amount = [0; 32];
+            order.approve_amount.0.to_big_endian(&mut approve_amount);
+
+            Self {
+                amount,
+                sender_id: order.sender.0,
+                from_token_id: order.src_token.0,
+                recipient: order.recipient.0 .0,
+                to_erc20: order.dst_token.0 .0,
+                nonce: order.nonce,
+                sender_chain_id: order.sender_chain_id,
+                recipient_chain_id: order.recipient_chain_id,
+                name: order.name,
+                symbol: order.symbol,
+                decimals: order.decimals,
+                approve_spender: order.approve_spender.0 .0,
+                approve_amount,
+                fee_payer: order.fee_payer.0 .0,
+            }
+        }
+    }
+
+    fn random_order(rng: &mut StdRng) -> MintOrder {
+        let address = |rng: &mut StdRng| H160::from_slice(&rng.gen::<[u8; 20]>());
+        let amount =
+            |rng: &mut StdRng| U256::from(EthU256::from_big_endian(&rng.gen::<[u8; 32]>()));
+
+        MintOrder {
+            amount: amount(rng),
+            sender: Id256(rng.gen()),
+            src_token: Id256(rng.gen()),
+            recipient: address(rng),
+            dst_token: address(rng),
+            nonce: rng.gen(),
+            sender_chain_id: rng.gen(),
+            recipient_chain_id: rng.gen(),
+            name: rng.gen(),
+            symbol: rng.gen(),
+            decimals: rng.gen(),
+            approve_spender: address(rng),
+            approve_amount: amount(rng),
+            fee_payer: address(rng),
+        }
+    }
+
+    async fn sign(order: &MintOrder) -> Vec<u8> {
+        MockContext::new().inject();
+        let signer = SigningStrategy::Local {
+            private_key: [42; 32],
+        }
+        .make_signer(0)
+        .unwrap();
+
+        order.encode_and_sign(&signer).await.unwrap().0.to_vec()
+    }
+
+    #[tokio::test]
+    async fn roundtrip_agrees_with_contract_layout() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..CASES {
+            let order = random_order(&mut rng);
+            let encoded = sign(&order).await;
+
+            assert_eq!(decode_signed_mint_order(&encoded), Ok(order.clone()));
+            assert_eq!(
+                ContractOrder::decode(&encoded[..MintOrder::ENCODED_DATA_SIZE]),
+                ContractOrder::from_order(&order)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn mutated_order_agrees_with_contract_layout() {
+        let mut rng = StdRng::seed_from_u64(2);
+        for _ in 0..CASES {
+            let order = random_order(&mut rng);
+            let mut encoded = sign(&order).await;
+
+            let offset = rng.gen_range(0..MintOrder::ENCODED_DATA_SIZE);
+            encoded[offset] ^= rng.gen_range(1..=u8::MAX);
+
+            let mutated = decode_signed_mint_order(&encoded).unwrap();
+            assert_ne!(mutated, order, "mutation at {offset} is lost");
+            assert_eq!(
+                ContractOrder::from_order(&mutated),
+                ContractOrder::decode(&encoded[..MintOrder::ENCODED_DATA_SIZE]),
+                "mutation at {offset} is decoded differently"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn wrong_length_is_rejected() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let encoded = sign(&random_order(&mut rng)).await;
+
+        for len in [
+            0,
+            MintOrder::ENCODED_DATA_SIZE,
+            MintOrder::SIGNED_ENCODED_DATA_SIZE - 1,
+            MintOrder::SIGNED_ENCODED_DATA_SIZE + 1,
+        ] {
+            let mut bytes = encoded.clone();
+            bytes.resize(len, 0);
+            assert_eq!(
+                decode_signed_mint_order(&bytes),
+                Err(MintOrderDecodeError::InvalidLength {
+                    expected: MintOrder::SIGNED_ENCODED_DATA_SIZE,
+                    actual: len,
+                })
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn stored_order_is_decoded() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let order = random_order(&mut rng);
+        let encoded = sign(&order).await;
+
+        let stored = SignedMintOrder(encoded.try_into().unwrap());
+        assert_eq!(decode_stored_mint_order(&stored), Ok(order));
+    }
+}
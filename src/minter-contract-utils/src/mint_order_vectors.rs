@@ -0,0 +1,282 @@
+//! Test vectors of the signed mint orders.
+//!
+//! The bridges sign the mint orders in Rust and `BftBridge.mint` checks them in Solidity, so the
+//! two sides must agree on every byte of the encoding and on the signature scheme: the signer signs
+//! the `keccak256` of the encoded order, with no message prefix, and the signature is appended as
+//! `r`, `s`, `v`. [`generate_vectors`] signs the [`fixture_orders`] with a known local key and
+//! checks that the signatures recover to its address.
+//!
+//! The vectors are exported as JSON to `solidity/test/vectors/mint_orders.json`, which the
+//! forge tests read to re-encode the orders and recover the signer on the contract side. The
+//! tests of this module fail if the file is stale; run them with `UPDATE_MINT_ORDER_VECTORS=1`
+//! to regenerate it.
+
+use anyhow::{anyhow, ensure, Context};
+use did::{H160, H256, U256};
+use eth_signer::sign_strategy::{SigningStrategy, TransactionSigner};
+use ethers_core::utils::keccak256;
+use minter_did::id256::Id256;
+use minter_did::order::{fit_str_to_array, MintOrder};
+use serde::{Deserialize, Serialize};
+
+use crate::hex_serde;
+use crate::mint_order_codec::decode_signed_mint_order;
+
+/// Private key of the signer of the exported vectors.
+pub const VECTORS_SIGNER_KEY: [u8; 32] = [42; 32];
+
+/// Signed mint orders together with the signer which must be recovered from them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MintOrderVectors {
+    #[serde(with = "hex_serde::bytes")]
+    pub signer_key: Vec<u8>,
+    #[serde(with = "hex_serde::h160")]
+    pub signer: H160,
+    pub vectors: Vec<MintOrderVector>,
+}
+
+/// Fields of a mint order, the hash which is signed and the signed order as passed to
+/// `BftBridge.mint`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MintOrderVector {
+    #[serde(with = "hex_serde::u256")]
+    pub amount: U256,
+    #[serde(with = "hex_serde::bytes")]
+    pub sender: Vec<u8>,
+    #[serde(with = "hex_serde::bytes")]
+    pub src_token: Vec<u8>,
+    #[serde(with = "hex_serde::h160")]
+    pub recipient: H160,
+    #[serde(with = "hex_serde::h160")]
+    pub dst_token: H160,
+    pub nonce: u32,
+    pub sender_chain_id: u32,
+    pub recipient_chain_id: u32,
+    #[serde(with = "hex_serde::bytes")]
+    pub name: Vec<u8>,
+    #[serde(with = "hex_serde::bytes")]
+    pub symbol: Vec<u8>,
+    pub decimals: u8,
+    #[serde(with = "hex_serde::h160")]
+    pub approve_spender: H160,
+    #[serde(with = "hex_serde::u256")]
+    pub approve_amount: U256,
+    #[serde(with = "hex_serde::h160")]
+    pub fee_payer: H160,
+    #[serde(with = "hex_serde::h256")]
+    pub hash: H256,
+    #[serde(with = "hex_serde::bytes")]
+    pub signed_order: Vec<u8>,
+}
+
+impl MintOrderVector {
+    /// Builds the vector of the signed order, checking that it decodes to `order`.
+    pub fn new(order: &MintOrder, signed_order: Vec<u8>) -> anyhow::Result<Self> {
+        let decoded = decode_signed_mint_order(&signed_order)?;
+        ensure!(
+            decoded == *order,
+            "signed order decodes to {decoded:?} instead of {order:?}"
+        );
+
+        Ok(Self {
+            amount: order.amount.clone(),
+            sender: order.sender.0.to_vec(),
+            src_token: order.src_token.0.to_vec(),
+            recipient: order.recipient.clone(),
+            dst_token: order.dst_token.clone(),
+            nonce: order.nonce,
+            sender_chain_id: order.sender_chain_id,
+            recipient_chain_id: order.recipient_chain_id,
+            name: order.name.to_vec(),
+            symbol: order.symbol.to_vec(),
+            decimals: order.decimals,
+            approve_spender: order.approve_spender.clone(),
+            approve_amount: order.approve_amount.clone(),
+            fee_payer: order.fee_payer.clone(),
+            hash: H256::from_slice(&keccak256(&signed_order[..MintOrder::ENCODED_DATA_SIZE])),
+            signed_order,
+        })
+    }
+
+    /// Recovers the signer the way `BftBridge` does.
+    pub fn recover_signer(&self) -> anyhow::Result<H160> {
+        let (_, signature) = MintOrder::decode_signed(&self.signed_order)
+            .ok_or_else(|| anyhow!("signed order cannot be decoded"))?;
+        let digest = keccak256(&self.signed_order[..MintOrder::ENCODED_DATA_SIZE]);
+        let signer = ethers_core::types::Signature::from(signature).recover(digest)?;
+
+        Ok(signer.into())
+    }
+}
+
+impl MintOrderVectors {
+    /// Checks that every vector is consistent and is signed by the `signer`.
+    pub fn verify(&self) -> anyhow::Result<()> {
+        for (index, vector) in self.vectors.iter().enumerate() {
+            let order = decode_signed_mint_order(&vector.signed_order)
+                .with_context(|| format!("vector {index}"))?;
+            ensure!(
+                MintOrderVector::new(&order, vector.signed_order.clone())? == *vector,
+                "fields of vector {index} don't match the signed order"
+            );
+
+            let signer = vector.recover_signer()?;
+            ensure!(
+                signer == self.signer,
+                "vector {index} is signed by {signer:?} instead of {:?}",
+                self.signer
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Orders covering the common cases and the bounds of every field.
+pub fn fixture_orders() -> Vec<MintOrder> {
+    let order = MintOrder {
+        amount: 1000u64.into(),
+        sender: Id256([1; 32]),
+        src_token: Id256([2; 32]),
+        recipient: H160::from_slice(&[0x33; 20]),
+        dst_token: H160::from_slice(&[0x44; 20]),
+        nonce: 1,
+        sender_chain_id: 0,
+        recipient_chain_id: 355113,
+        name: fit_str_to_array("Wrapped Token"),
+        symbol: fit_str_to_array("WTKN"),
+        decimals: 18,
+        approve_spender: H160::default(),
+        approve_amount: U256::zero(),
+        fee_payer: H160::default(),
+    };
+
+    let with_approve_and_fee_payer = MintOrder {
+        amount: 1_000_000_000_000_000_000u64.into(),
+        nonce: 2,
+        sender_chain_id: 355113,
+        approve_spender: H160::from_slice(&[0x55; 20]),
+        approve_amount: 500u64.into(),
+        fee_payer: H160::from_slice(&[0x66; 20]),
+        ..order.clone()
+    };
+
+    let max = MintOrder {
+        amount: U256(ethers_core::types::U256::MAX),
+        sender: Id256([0xff; 32]),
+        src_token: Id256([0xff; 32]),
+        recipient: H160::from_slice(&[0xff; 20]),
+        dst_token: H160::from_slice(&[0xff; 20]),
+        nonce: u32::MAX,
+        sender_chain_id: u32::MAX,
+        recipient_chain_id: u32::MAX,
+        name: [b'a'; 32],
+        symbol: [b'b'; 16],
+        decimals: u8::MAX,
+        approve_spender: H160::from_slice(&[0xff; 20]),
+        approve_amount: U256(ethers_core::types::U256::MAX),
+        fee_payer: H160::from_slice(&[0xff; 20]),
+    };
+
+    let zero = MintOrder {
+        amount: U256::zero(),
+        sender: Id256([0; 32]),
+        src_token: Id256([0; 32]),
+        recipient: H160::default(),
+        dst_token: H160::default(),
+        nonce: 0,
+        sender_chain_id: 0,
+        recipient_chain_id: 0,
+        name: [0; 32],
+        symbol: [0; 16],
+        decimals: 0,
+        approve_spender: H160::default(),
+        approve_amount: U256::zero(),
+        fee_payer: H160::default(),
+    };
+
+    vec![order, with_approve_and_fee_payer, max, zero]
+}
+
+/// Signs the [`fixture_orders`] with the local signer of the `private_key`, as the bridges sign
+/// their orders, and verifies the result.
+pub async fn generate_vectors(private_key: [u8; 32]) -> anyhow::Result<MintOrderVectors> {
+    let signer = SigningStrategy::Local { private_key }
+        .make_signer(0)
+        .map_err(|err| anyhow!("failed to create signer: {err:?}"))?;
+
+    let mut vectors = vec![];
+    for order in fixture_orders() {
+        let signed_order = order
+            .encode_and_sign(&signer)
+            .await
+            .map_err(|err| anyhow!("failed to sign mint order: {err:?}"))?;
+        vectors.push(MintOrderVector::new(&order, signed_order.0.to_vec())?);
+    }
+
+    let vectors = MintOrderVectors {
+        signer_key: private_key.to_vec(),
+        signer: signer
+            .get_address()
+            .await
+            .map_err(|err| anyhow!("failed to get signer address: {err:?}"))?,
+        vectors,
+    };
+    vectors.verify()?;
+
+    Ok(vectors)
+}
+
+#[cfg(test)]
+mod tests {
+    use ic_exports::ic_kit::MockContext;
+
+    use super::*;
+
+    const VECTORS_PATH: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../../solidity/test/vectors/mint_orders.json"
+    );
+
+    #[tokio::test]
+    async fn exported_vectors_are_up_to_date() {
+        MockContext::new().inject();
+        let vectors = generate_vectors(VECTORS_SIGNER_KEY).await.unwrap();
+
+        if std::env::var_os("UPDATE_MINT_ORDER_VECTORS").is_some() {
+            let json = serde_json::to_string_pretty(&vectors).unwrap();
+            std::fs::write(VECTORS_PATH, json + "\n").unwrap();
+        }
+
+        let exported: MintOrderVectors =
+            serde_json::from_str(&std::fs::read_to_string(VECTORS_PATH).unwrap()).unwrap();
+        exported.verify().unwrap();
+        assert_eq!(
+            exported, vectors,
+            "{VECTORS_PATH} is stale, run the test with UPDATE_MINT_ORDER_VECTORS=1"
+        );
+    }
+
+    #[tokio::test]
+    async fn tampered_vectors_are_rejected() {
+        MockContext::new().inject();
+        let vectors = generate_vectors(VECTORS_SIGNER_KEY).await.unwrap();
+
+        let mut wrong_field = vectors.clone();
+        wrong_field.vectors[0].nonce += 1;
+        assert!(wrong_field.verify().is_err());
+
+        let mut wrong_signature = vectors.clone();
+        wrong_signature.vectors[0].signed_order[MintOrder::ENCODED_DATA_SIZE] ^= 1;
+        let order = decode_signed_mint_order(&wrong_signature.vectors[0].signed_order).unwrap();
+        wrong_signature.vectors[0] =
+            MintOrderVector::new(&order, wrong_signature.vectors[0].signed_order.clone()).unwrap();
+        assert!(wrong_signature.verify().is_err());
+
+        let mut wrong_signer = vectors;
+        wrong_signer.signer = H160::from_slice(&[1; 20]);
+        assert!(wrong_signer.verify().is_err());
+    }
+}
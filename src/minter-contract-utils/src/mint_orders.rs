@@ -1,21 +1,44 @@
 use std::borrow::Cow;
+use std::mem::size_of;
+use std::ops::Bound as RangeBound;
 
 use ic_stable_structures::stable_structures::Memory;
-use ic_stable_structures::{Bound, MultimapStructure as _, StableMultimap, Storable};
+use ic_stable_structures::{
+    BTreeMapStructure, Bound, MultimapStructure as _, StableBTreeMap, StableMultimap, Storable,
+};
 use minter_did::id256::Id256;
 use minter_did::order::SignedMintOrder;
 
+use crate::pagination::{CursorPage, CursorRequest, InvalidCursor};
+
 pub struct MintOrders<M: Memory> {
-    mint_orders_map: StableMultimap<MintOrderKey, u32, SignedMintOrder, M>,
+    mint_orders_map: StableBTreeMap<MintOrderEntryKey, SignedMintOrder, M>,
 }
 
 impl<M: Memory> MintOrders<M> {
     pub fn new(memory: M) -> Self {
         Self {
-            mint_orders_map: StableMultimap::new(memory),
+            mint_orders_map: StableBTreeMap::new(memory),
         }
     }
 
+    /// Moves the orders from the `legacy_memory`, where the earlier versions of the store kept
+    /// them in a multimap, which cannot be read from the middle of the orders of a sender.
+    /// Returns the number of the moved orders.
+    pub fn migrate_from_multimap(&mut self, legacy_memory: M) -> u64 {
+        let mut legacy: StableMultimap<MintOrderKey, u32, SignedMintOrder, M> =
+            StableMultimap::new(legacy_memory);
+        let mut moved = 0;
+        for (key, operation_id, order) in legacy.iter() {
+            self.mint_orders_map
+                .insert(MintOrderEntryKey::new(key, operation_id), order);
+            moved += 1;
+        }
+        legacy.clear();
+
+        moved
+    }
+
     /// Inserts a new signed mint order.
     /// Returns replaced signed mint order if it already exists.
     pub fn insert(
@@ -26,7 +49,8 @@ impl<M: Memory> MintOrders<M> {
         order: SignedMintOrder,
     ) -> Option<SignedMintOrder> {
         let key = MintOrderKey { sender, src_token };
-        self.mint_orders_map.insert(&key, &operation_id, order)
+        self.mint_orders_map
+            .insert(MintOrderEntryKey::new(key, operation_id), order)
     }
 
     /// Returns the signed mint order for the given sender and token, if it exists.
@@ -37,13 +61,42 @@ impl<M: Memory> MintOrders<M> {
         operation_id: u32,
     ) -> Option<SignedMintOrder> {
         let key = MintOrderKey { sender, src_token };
-        self.mint_orders_map.get(&key, &operation_id)
+        self.mint_orders_map
+            .get(&MintOrderEntryKey::new(key, operation_id))
     }
 
     /// Returns all the signed mint orders for the given sender and token.
     pub fn get_all(&self, sender: Id256, src_token: Id256) -> Vec<(u32, SignedMintOrder)> {
         let key = MintOrderKey { sender, src_token };
-        self.mint_orders_map.range(&key).collect()
+        self.orders_after(key, None).collect()
+    }
+
+    /// Returns the requested page of the signed mint orders for the given sender and token.
+    pub fn page(
+        &self,
+        sender: Id256,
+        src_token: Id256,
+        request: &CursorRequest,
+    ) -> Result<CursorPage<(u32, SignedMintOrder)>, InvalidCursor> {
+        let key = MintOrderKey { sender, src_token };
+        let after = request.after::<u32>()?;
+        Ok(request.page_entries(self.orders_after(key, after)))
+    }
+
+    /// Iterates over the orders of the `key` with operation ids after the `after` one.
+    fn orders_after(
+        &self,
+        key: MintOrderKey,
+        after: Option<u32>,
+    ) -> impl Iterator<Item = (u32, SignedMintOrder)> + '_ {
+        let start = match after {
+            Some(operation_id) => RangeBound::Excluded(MintOrderEntryKey::new(key, operation_id)),
+            None => RangeBound::Included(MintOrderEntryKey::new(key, u32::MIN)),
+        };
+        let end = RangeBound::Included(MintOrderEntryKey::new(key, u32::MAX));
+        self.mint_orders_map
+            .range((start, end))
+            .map(|(entry_key, order)| (entry_key.operation_id, order))
     }
 
     /// Removes all signed mint orders.
@@ -58,7 +111,8 @@ impl<M: Memory> MintOrders<M> {
         operation_id: u32,
     ) -> Option<SignedMintOrder> {
         let key = MintOrderKey { sender, src_token };
-        self.mint_orders_map.remove(&key, &operation_id)
+        self.mint_orders_map
+            .remove(&MintOrderEntryKey::new(key, operation_id))
     }
 }
 
@@ -99,16 +153,63 @@ impl Storable for MintOrderKey {
     };
 }
 
+/// Key of a single order. The operation id is encoded in big endian after the sender and the
+/// token, so the orders of a sender and a token are stored together in the ascending order of
+/// the operation ids.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct MintOrderEntryKey {
+    key: MintOrderKey,
+    operation_id: u32,
+}
+
+impl MintOrderEntryKey {
+    const STORABLE_BYTE_SIZE: usize = MintOrderKey::STORABLE_BYTE_SIZE + size_of::<u32>();
+
+    fn new(key: MintOrderKey, operation_id: u32) -> Self {
+        Self { key, operation_id }
+    }
+}
+
+impl Storable for MintOrderEntryKey {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        let mut buf = Vec::with_capacity(Self::STORABLE_BYTE_SIZE);
+        buf.extend_from_slice(&self.key.to_bytes());
+        buf.extend_from_slice(&self.operation_id.to_be_bytes());
+        buf.into()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        let (key, operation_id) = bytes.split_at(MintOrderKey::STORABLE_BYTE_SIZE);
+        Self {
+            key: MintOrderKey::from_bytes(Cow::Borrowed(key)),
+            operation_id: u32::from_be_bytes(
+                operation_id
+                    .try_into()
+                    .expect("expected 4 bytes for operation id"),
+            ),
+        }
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: Self::STORABLE_BYTE_SIZE as _,
+        is_fixed_size: true,
+    };
+}
+
 #[cfg(test)]
 mod tests {
     use candid::Principal;
     use ic_exports::ic_kit::MockContext;
     use ic_stable_structures::stable_structures::DefaultMemoryImpl;
-    use ic_stable_structures::{default_ic_memory_manager, MemoryId, Storable, VirtualMemory};
+    use ic_stable_structures::{
+        default_ic_memory_manager, MemoryId, MultimapStructure as _, StableMultimap, Storable,
+        VirtualMemory,
+    };
     use minter_did::id256::Id256;
     use minter_did::order::{MintOrder, SignedMintOrder};
 
-    use super::{MintOrderKey, MintOrders};
+    use super::{MintOrderEntryKey, MintOrderKey, MintOrders};
+    use crate::pagination::CursorRequest;
 
     #[test]
     fn mint_order_key_encoding() {
@@ -119,6 +220,15 @@ mod tests {
 
         let decoded = MintOrderKey::from_bytes(mint_order_key.to_bytes());
         assert_eq!(mint_order_key, decoded);
+
+        let entry_key = MintOrderEntryKey::new(mint_order_key, 0x0102_0304);
+        let decoded = MintOrderEntryKey::from_bytes(entry_key.to_bytes());
+        assert_eq!(entry_key, decoded);
+        // The byte order of the keys follows the order of the operation ids.
+        assert!(
+            MintOrderEntryKey::new(mint_order_key, 255).to_bytes()
+                < MintOrderEntryKey::new(mint_order_key, 256).to_bytes()
+        );
     }
 
     fn init_context() -> MintOrders<VirtualMemory<DefaultMemoryImpl>> {
@@ -195,4 +305,60 @@ mod tests {
             vec![(4, order), (5, order)]
         );
     }
+
+    #[test]
+    fn mint_orders_page() {
+        let mut orders = init_context();
+
+        let sender = Id256::from(&Principal::management_canister());
+        let src_token = Id256::from(&Principal::anonymous());
+        let order = SignedMintOrder([0; MintOrder::SIGNED_ENCODED_DATA_SIZE]);
+        for operation_id in 0..5 {
+            orders.insert(sender, src_token, operation_id, order);
+        }
+
+        orders.insert(sender, Id256([1; 32]), 7, order);
+
+        let first = orders
+            .page(sender, src_token, &CursorRequest::new(None, 3))
+            .unwrap();
+        assert_eq!(first.items, vec![(0, order), (1, order), (2, order)]);
+
+        let last = orders
+            .page(sender, src_token, &CursorRequest::new(first.next, 3))
+            .unwrap();
+        assert_eq!(last.items, vec![(3, order), (4, order)]);
+        assert!(last.next.is_none());
+    }
+
+    #[test]
+    fn mint_orders_are_migrated_from_multimap() {
+        let memory_manager = default_ic_memory_manager();
+        MockContext::new().inject();
+
+        let sender = Id256::from(&Principal::management_canister());
+        let src_token = Id256::from(&Principal::anonymous());
+        let order = SignedMintOrder([0; MintOrder::SIGNED_ENCODED_DATA_SIZE]);
+        let mut legacy: StableMultimap<MintOrderKey, u32, SignedMintOrder, _> =
+            StableMultimap::new(memory_manager.get(MemoryId::new(1)));
+        let key = MintOrderKey { sender, src_token };
+        legacy.insert(&key, &1, order);
+        legacy.insert(&key, &2, order);
+
+        let mut orders = MintOrders::new(memory_manager.get(MemoryId::new(0)));
+        assert_eq!(
+            orders.migrate_from_multimap(memory_manager.get(MemoryId::new(1))),
+            2
+        );
+        assert_eq!(
+            orders.get_all(sender, src_token),
+            vec![(1, order), (2, order)]
+        );
+
+        // The legacy memory is cleared, so migrating again moves nothing.
+        assert_eq!(
+            orders.migrate_from_multimap(memory_manager.get(MemoryId::new(1))),
+            0
+        );
+    }
 }
@@ -0,0 +1,211 @@
+//! Offloading of the completed operations to an archive canister.
+//!
+//! The operation log of a bridge is bounded, and the oldest completed operations are dropped
+//! when it is full. With an archive configured, the bridge pushes the completed operations
+//! beyond the latest [`ArchiveConfig::keep_latest`] to the archive canister in batches, and
+//! removes them from the log once the archive accepts them. The address map of the
+//! [`crate::operation_store::MinterOperationStore`] keeps the ids of the archived operations, so
+//! the bridge can tell a client which operations of a wallet to query from the archive.
+//!
+//! The archive canister implements the following interface:
+//!
+//! ```candid
+//! type ArchivedOperation = record { id : nat64; dst_address : text; payload : blob };
+//!
+//! service : {
+//!   // Stores the operations. Operations with the ids stored already are skipped, so a batch
+//!   // which was stored but not acknowledged can be pushed again.
+//!   append_operations : (vec ArchivedOperation) -> (variant { Ok; Err : text });
+//!   get_operation : (nat64) -> (opt ArchivedOperation) query;
+//!   get_operations : (vec nat64) -> (vec ArchivedOperation) query;
+//! }
+//! ```
+//!
+//! The `payload` is the candid encoding of the operation state of the bridge.
+
+use std::borrow::Cow;
+
+use candid::{CandidType, Decode, Deserialize, Encode, Principal};
+use did::H160;
+use ic_exports::ic_cdk;
+use ic_stable_structures::stable_structures::Memory;
+use ic_stable_structures::{Bound, CellStructure, StableCell, Storable};
+
+use crate::operation_store::MinterOperationId;
+
+/// Interval between the pushes of the operation batches to the archive.
+pub const ARCHIVE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10 * 60);
+
+/// Maximum number of operations pushed in one call. It keeps the call below the message size
+/// limit of the IC.
+pub const MAX_ARCHIVE_BATCH_SIZE: u32 = 500;
+
+const APPEND_OPERATIONS_METHOD: &str = "append_operations";
+
+/// Completed operation stored in the archive canister.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct ArchivedOperation {
+    pub id: MinterOperationId,
+    pub dst_address: H160,
+    /// Candid encoded operation state.
+    pub payload: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct ArchiveConfig {
+    /// Archive canister. It must not be changed once operations are archived to it, since the
+    /// bridge keeps only their ids.
+    pub canister: Principal,
+    /// Number of the latest completed operations kept in the bridge.
+    pub keep_latest: u64,
+    /// Number of the operations pushed to the archive in one call.
+    pub batch_size: u32,
+}
+
+impl ArchiveConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.canister == Principal::anonymous() {
+            return Err("archive canister must not be anonymous".into());
+        }
+        if !(1..=MAX_ARCHIVE_BATCH_SIZE).contains(&self.batch_size) {
+            return Err(format!(
+                "batch size must be between 1 and {MAX_ARCHIVE_BATCH_SIZE}, got {}",
+                self.batch_size
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Archived operations of a wallet returned by the bridges.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct ArchivedOperations {
+    pub archive: Principal,
+    pub ids: Vec<MinterOperationId>,
+}
+
+#[derive(Debug, Default, Clone, CandidType, Deserialize)]
+struct StoredArchiveConfig(Option<ArchiveConfig>);
+
+impl Storable for StoredArchiveConfig {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("serialization failed"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).expect("deserialization failed")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// Archive configuration of a bridge, kept in the stable memory.
+pub struct OperationArchive<M: Memory> {
+    config: StableCell<StoredArchiveConfig, M>,
+}
+
+impl<M: Memory> OperationArchive<M> {
+    pub fn new(memory: M) -> Self {
+        Self {
+            config: StableCell::new(memory, StoredArchiveConfig::default())
+                .expect("stable memory archive config initialization failed"),
+        }
+    }
+
+    pub fn config(&self) -> Option<ArchiveConfig> {
+        self.config.get().0.clone()
+    }
+
+    /// Sets the archive, or stops the archiving if `config` is `None`. The operations archived
+    /// already stay in the archive.
+    pub fn configure(&mut self, config: Option<ArchiveConfig>) -> Result<(), String> {
+        if let Some(config) = &config {
+            config.validate()?;
+        }
+
+        self.config
+            .set(StoredArchiveConfig(config))
+            .expect("failed to update archive config");
+        Ok(())
+    }
+
+    /// Archived operations with the `ids`, or `None` if there are none or no archive is set.
+    pub fn archived(&self, ids: Vec<MinterOperationId>) -> Option<ArchivedOperations> {
+        if ids.is_empty() {
+            return None;
+        }
+
+        self.config().map(|config| ArchivedOperations {
+            archive: config.canister,
+            ids,
+        })
+    }
+}
+
+/// Pushes the `batch` to the archive `canister`.
+pub async fn push_batch(canister: Principal, batch: &[ArchivedOperation]) -> Result<(), String> {
+    ic_cdk::call::<_, (Result<(), String>,)>(canister, APPEND_OPERATIONS_METHOD, (batch,))
+        .await
+        .map_err(|(code, msg)| format!("archive canister call failed: {code:?} {msg}"))?
+        .0
+        .map_err(|err| format!("archive canister rejected the batch: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use ic_stable_structures::VectorMemory;
+
+    use super::*;
+
+    fn config() -> ArchiveConfig {
+        ArchiveConfig {
+            canister: Principal::management_canister(),
+            keep_latest: 1_000,
+            batch_size: 100,
+        }
+    }
+
+    #[test]
+    fn archive_is_configured() {
+        let mut archive = OperationArchive::new(VectorMemory::default());
+        assert_eq!(archive.config(), None);
+        assert_eq!(archive.archived(vec![1.into()]), None);
+
+        archive.configure(Some(config())).unwrap();
+        assert_eq!(archive.config(), Some(config()));
+        assert_eq!(archive.archived(vec![]), None);
+        assert_eq!(
+            archive.archived(vec![1.into(), 2.into()]),
+            Some(ArchivedOperations {
+                archive: config().canister,
+                ids: vec![1.into(), 2.into()],
+            })
+        );
+
+        archive.configure(None).unwrap();
+        assert_eq!(archive.config(), None);
+    }
+
+    #[test]
+    fn invalid_config_is_rejected() {
+        let mut archive = OperationArchive::new(VectorMemory::default());
+        for config in [
+            ArchiveConfig {
+                batch_size: 0,
+                ..config()
+            },
+            ArchiveConfig {
+                batch_size: MAX_ARCHIVE_BATCH_SIZE + 1,
+                ..config()
+            },
+            ArchiveConfig {
+                canister: Principal::anonymous(),
+                ..config()
+            },
+        ] {
+            assert!(archive.configure(Some(config)).is_err());
+        }
+        assert_eq!(archive.config(), None);
+    }
+}
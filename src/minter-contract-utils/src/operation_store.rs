@@ -13,6 +13,10 @@ use ic_stable_structures::{
 };
 use serde::Serialize;
 
+use crate::operation_archive::ArchivedOperation;
+use crate::operation_trace;
+use crate::pagination::{CursorPage, CursorRequest, InvalidCursor};
+
 const DEFAULT_CACHE_SIZE: u32 = 1000;
 const DEFAULT_MAX_REQUEST_COUNT: u64 = 100_000;
 
@@ -47,6 +51,12 @@ impl MinterOperationId {
     }
 }
 
+impl From<u64> for MinterOperationId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
 impl Storable for MinterOperationId {
     fn to_bytes(&self) -> Cow<[u8]> {
         self.0.to_bytes()
@@ -175,7 +185,7 @@ where
             payload,
         };
 
-        log::trace!("Operation {id} is created.");
+        operation_trace::record(id, log::Level::Debug, "status=created");
 
         if entry.payload.is_complete() {
             self.move_to_log(id, entry);
@@ -217,6 +227,37 @@ where
             .collect()
     }
 
+    /// Retrieves the requested page of the operations for the given ETH wallet address, selected
+    /// and mapped by the `select` function. Only the operations of the page are read from the
+    /// stable memory, so the cost of the call does not grow with the number of operations.
+    pub fn page_for_address<T>(
+        &self,
+        dst_address: &H160,
+        request: &CursorRequest,
+        mut select: impl FnMut(MinterOperationId, P) -> Option<T>,
+    ) -> Result<CursorPage<T>, InvalidCursor> {
+        let after = request.after::<MinterOperationId>()?;
+        let ids = self
+            .address_operation_map
+            .get(dst_address)
+            .unwrap_or_default()
+            .0;
+        // The ids of an address are stored in the order of creation, which is ascending.
+        let start = ids.partition_point(|id| after.is_some_and(|after| *id <= after));
+        Ok(request.page(ids.into_iter().skip(start), |id| {
+            self.get_with_id(*id)
+                .and_then(|(id, payload)| select(id, payload))
+        }))
+    }
+
+    /// Retrieves all operations that are not complete yet.
+    pub fn get_incomplete(&self) -> Vec<(MinterOperationId, P)> {
+        self.incomplete_operations
+            .iter()
+            .map(|(id, entry)| (id, entry.payload))
+            .collect()
+    }
+
     /// Update the payload of the operation with the given id. If no operation with the given ID
     /// is found, nothing is done (except an error message in the log).
     pub fn update(&mut self, operation_id: MinterOperationId, payload: P) {
@@ -226,6 +267,11 @@ where
         };
 
         entry.payload = payload;
+        operation_trace::record(
+            operation_id,
+            log::Level::Debug,
+            format!("status=updated complete={}", entry.payload.is_complete()),
+        );
 
         if entry.payload.is_complete() {
             self.move_to_log(operation_id, entry);
@@ -234,6 +280,48 @@ where
         }
     }
 
+    /// Returns the oldest completed operations beyond the `keep_latest` latest ones, at most
+    /// `max_count` of them, to be pushed to the archive.
+    pub fn archive_candidates(&self, keep_latest: u64, max_count: u32) -> Vec<ArchivedOperation> {
+        let count = self
+            .operations_log
+            .len()
+            .saturating_sub(keep_latest)
+            .min(u64::from(max_count));
+
+        self.operations_log
+            .iter()
+            .take(count as usize)
+            .map(|(id, entry)| ArchivedOperation {
+                id,
+                dst_address: entry.dst_address,
+                payload: candid::encode_one(&entry.payload)
+                    .expect("failed to encode archived operation"),
+            })
+            .collect()
+    }
+
+    /// Removes the operations accepted by the archive from the log. Their ids are kept in the
+    /// address map and are returned by [`Self::archived_ids_for_address`].
+    pub fn remove_archived(&mut self, operation_ids: &[MinterOperationId]) {
+        for id in operation_ids {
+            if self.operations_log.remove(id).is_some() {
+                log::trace!("Operation {id} is moved to the archive");
+            }
+        }
+    }
+
+    /// Ids of the archived operations of the given ETH wallet address.
+    pub fn archived_ids_for_address(&self, dst_address: &H160) -> Vec<MinterOperationId> {
+        self.address_operation_map
+            .get(dst_address)
+            .unwrap_or_default()
+            .0
+            .into_iter()
+            .filter(|id| self.get_with_id(*id).is_none())
+            .collect()
+    }
+
     fn move_to_log(&mut self, operation_id: MinterOperationId, entry: OperationStoreEntry<P>) {
         self.incomplete_operations.remove(&operation_id);
         self.operations_log.insert(operation_id, entry);
@@ -388,6 +476,21 @@ mod tests {
         assert_eq!(store.get_for_address(&eth_address(1)).len(), COUNT as usize);
     }
 
+    #[test]
+    fn get_incomplete_operations() {
+        let mut store = test_store(10);
+
+        let first = store.new_operation(eth_address(1), 1);
+        let second = store.new_operation(eth_address(2), 2);
+        let completed = store.new_operation(eth_address(1), 3);
+        store.update(completed, COMPLETE);
+        store.new_operation(eth_address(3), COMPLETE);
+
+        let mut incomplete = store.get_incomplete();
+        incomplete.sort();
+        assert_eq!(incomplete, vec![(first, 1), (second, 2)]);
+    }
+
     #[test]
     fn operations_are_moved_to_log_on_completion() {
         const LIMIT: u64 = 10;
@@ -411,4 +514,64 @@ mod tests {
         assert_eq!(store.incomplete_operations.len(), 0);
         assert_eq!(store.address_operation_map.len(), LIMIT);
     }
+
+    #[test]
+    fn archived_operations_are_indexed() {
+        let mut store = test_store(10);
+
+        let archived: Vec<_> = (0..3)
+            .map(|_| store.new_operation(eth_address(1), COMPLETE))
+            .collect();
+        let kept = store.new_operation(eth_address(1), COMPLETE);
+        let incomplete = store.new_operation(eth_address(1), 1);
+        store.new_operation(eth_address(2), COMPLETE);
+
+        assert!(store.archive_candidates(5, 100).is_empty());
+        assert_eq!(store.archive_candidates(2, 2).len(), 2);
+
+        let candidates = store.archive_candidates(2, 100);
+        let ids: Vec<_> = candidates.iter().map(|op| op.id).collect();
+        assert_eq!(ids, archived);
+        assert_eq!(candidates[0].dst_address, eth_address(1));
+        assert_eq!(
+            candid::decode_one::<u32>(&candidates[0].payload).unwrap(),
+            COMPLETE
+        );
+
+        store.remove_archived(&ids);
+        assert_eq!(store.operations_log.len(), 2);
+        assert_eq!(store.archived_ids_for_address(&eth_address(1)), archived);
+        assert!(store.archived_ids_for_address(&eth_address(2)).is_empty());
+        assert_eq!(
+            store.get_for_address(&eth_address(1)),
+            vec![(kept, COMPLETE), (incomplete, 1)]
+        );
+    }
+
+    #[test]
+    fn operations_page_for_address() {
+        let mut store = test_store(10);
+        let ids: Vec<_> = (0..5)
+            .map(|i| store.new_operation(eth_address(1), i))
+            .collect();
+        store.new_operation(eth_address(2), 10);
+
+        let select = |id, payload: u32| (payload % 2 == 0).then_some((id, payload));
+        let first = store
+            .page_for_address(&eth_address(1), &CursorRequest::new(None, 2), select)
+            .unwrap();
+        assert_eq!(first.items, vec![(ids[0], 0), (ids[2], 2)]);
+
+        let last = store
+            .page_for_address(&eth_address(1), &CursorRequest::new(first.next, 2), select)
+            .unwrap();
+        assert_eq!(last.items, vec![(ids[4], 4)]);
+        assert!(last.next.is_none());
+
+        let empty = store
+            .page_for_address(&eth_address(3), &CursorRequest::default(), select)
+            .unwrap();
+        assert!(empty.items.is_empty());
+        assert!(empty.next.is_none());
+    }
 }
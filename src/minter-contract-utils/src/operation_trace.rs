@@ -0,0 +1,134 @@
+//! Traces of the user operations of the bridges.
+//!
+//! The id of an operation in the [`MinterOperationStore`] is generated when the operation starts
+//! and is carried by the scheduler tasks processing it, so it is the correlation id of the log
+//! lines of the operation. The lines recorded with [`record`] are written to the canister log as
+//! `operation_id=<id> key=value ...` and collected in the heap, so the trace of an operation can be
+//! requested from the bridge instead of searching the log.
+//!
+//! The traces are kept for the latest [`MAX_TRACED_OPERATIONS`] operations and are lost on
+//! upgrade.
+//!
+//! [`MinterOperationStore`]: crate::operation_store::MinterOperationStore
+
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+
+use candid::CandidType;
+use serde::Deserialize;
+
+use crate::operation_store::MinterOperationId;
+
+/// Number of the operations the traces are kept for.
+pub const MAX_TRACED_OPERATIONS: usize = 1_000;
+/// Number of the latest entries kept in the trace of an operation.
+pub const MAX_TRACE_ENTRIES: usize = 100;
+
+thread_local! {
+    static OPERATION_TRACES: RefCell<OperationTraces> = RefCell::default();
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct TraceEntry {
+    /// Timestamp (nanoseconds) of the entry.
+    pub timestamp: u64,
+    pub level: String,
+    pub message: String,
+}
+
+#[derive(Debug, Default)]
+pub struct OperationTraces {
+    traces: HashMap<MinterOperationId, VecDeque<TraceEntry>>,
+    /// Traced operations in the order of their first entry, to drop the oldest traces first.
+    order: VecDeque<MinterOperationId>,
+}
+
+impl OperationTraces {
+    pub fn push(&mut self, operation_id: MinterOperationId, entry: TraceEntry) {
+        let trace = self.traces.entry(operation_id).or_insert_with(|| {
+            self.order.push_back(operation_id);
+            VecDeque::new()
+        });
+        trace.push_back(entry);
+        if trace.len() > MAX_TRACE_ENTRIES {
+            trace.pop_front();
+        }
+
+        while self.order.len() > MAX_TRACED_OPERATIONS {
+            if let Some(oldest) = self.order.pop_front() {
+                self.traces.remove(&oldest);
+            }
+        }
+    }
+
+    pub fn get(&self, operation_id: MinterOperationId) -> Vec<TraceEntry> {
+        self.traces
+            .get(&operation_id)
+            .map(|trace| trace.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Writes the `message` of the operation to the canister log and to the operation trace.
+/// The message should consist of `key=value` pairs.
+pub fn record(operation_id: MinterOperationId, level: log::Level, message: impl Into<String>) {
+    let message = message.into();
+    log::log!(level, "operation_id={operation_id} {message}");
+
+    let entry = TraceEntry {
+        timestamp: now(),
+        level: level.to_string(),
+        message,
+    };
+    OPERATION_TRACES.with(|traces| traces.borrow_mut().push(operation_id, entry));
+}
+
+/// Entries recorded for the operation, oldest first.
+pub fn get_operation_trace(operation_id: MinterOperationId) -> Vec<TraceEntry> {
+    OPERATION_TRACES.with(|traces| traces.borrow().get(operation_id))
+}
+
+#[cfg(target_family = "wasm")]
+fn now() -> u64 {
+    ic_exports::ic_kit::ic::time()
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(message: &str) -> TraceEntry {
+        TraceEntry {
+            timestamp: 0,
+            level: "INFO".into(),
+            message: message.into(),
+        }
+    }
+
+    #[test]
+    fn traces_keep_latest_entries_of_latest_operations() {
+        let mut traces = OperationTraces::default();
+        let first = MinterOperationId::from(0);
+
+        for i in 0..MAX_TRACE_ENTRIES + 1 {
+            traces.push(first, entry(&format!("step={i}")));
+        }
+        let trace = traces.get(first);
+        assert_eq!(trace.len(), MAX_TRACE_ENTRIES);
+        assert_eq!(trace[0].message, "step=1");
+
+        for id in 1..=MAX_TRACED_OPERATIONS as u64 {
+            traces.push(MinterOperationId::from(id), entry("status=created"));
+        }
+        assert!(traces.get(first).is_empty());
+        assert_eq!(traces.get(MinterOperationId::from(1)).len(), 1);
+    }
+}
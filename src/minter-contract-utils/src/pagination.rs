@@ -0,0 +1,325 @@
+//! Pagination of the list queries of the bridge canisters.
+//!
+//! The lists returned by the canisters grow with the number of bridge operations, so the queries
+//! accept a [`Pagination`] and return a [`Paged`] slice of the list together with the total
+//! number of items, which lets the clients iterate over the whole list page by page.
+//!
+//! Counting the total requires reading the whole list from the stable memory, which does not fit
+//! into the instruction limit of a query once the stores grow large. The `*_by_cursor` queries
+//! accept a [`CursorRequest`] instead and return a [`CursorPage`] with a continuation [`Cursor`].
+//! The stores start reading the list right after the cursor, and read only the items of the page
+//! and at most [`MAX_SCANNED_ITEMS`] of them per call.
+
+use std::borrow::Cow;
+
+use candid::CandidType;
+use ic_stable_structures::{Bound, Storable};
+use serde::Deserialize;
+
+/// Number of items returned if the page is not specified.
+pub const DEFAULT_PAGE_SIZE: u64 = 100;
+/// Maximum number of items returned in a single page.
+pub const MAX_PAGE_SIZE: u64 = 1_000;
+/// Maximum number of keys read by a single cursor page, including the keys filtered out.
+pub const MAX_SCANNED_ITEMS: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, CandidType, Deserialize)]
+pub struct Pagination {
+    /// Number of items to skip.
+    pub offset: u64,
+    /// Maximum number of items to return. Values above [`MAX_PAGE_SIZE`] are reduced to it.
+    pub limit: u64,
+}
+
+impl Default for Pagination {
+    fn default() -> Self {
+        Self {
+            offset: 0,
+            limit: DEFAULT_PAGE_SIZE,
+        }
+    }
+}
+
+/// Page of a list.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct Paged<T> {
+    pub items: Vec<T>,
+    /// Number of items in the whole list.
+    pub total: u64,
+}
+
+impl Pagination {
+    pub fn new(offset: u64, limit: u64) -> Self {
+        Self { offset, limit }
+    }
+
+    /// Returns the page of the `items`, consuming the whole iterator to count the items.
+    pub fn paginate<T>(&self, items: impl IntoIterator<Item = T>) -> Paged<T> {
+        let limit = self.limit.min(MAX_PAGE_SIZE) as usize;
+        let offset = usize::try_from(self.offset).unwrap_or(usize::MAX);
+
+        let mut total = 0u64;
+        let mut page = vec![];
+        for (index, item) in items.into_iter().enumerate() {
+            if index >= offset && page.len() < limit {
+                page.push(item);
+            }
+            total += 1;
+        }
+
+        Paged { items: page, total }
+    }
+}
+
+impl<T> Paged<T> {
+    /// Returns `true` if there are items after this page, given the pagination used to get it.
+    pub fn has_more(&self, pagination: &Pagination) -> bool {
+        pagination.offset.saturating_add(self.items.len() as u64) < self.total
+    }
+}
+
+/// Opaque position in a list, after the last item of the previous page.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct Cursor(Vec<u8>);
+
+/// The cursor of the request is not one returned by the list query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, CandidType, Deserialize)]
+pub struct InvalidCursor;
+
+impl Cursor {
+    fn new<K: Storable>(key: &K) -> Self {
+        Self(key.to_bytes().into_owned())
+    }
+
+    /// Decodes the key of the cursor. The cursors come from the callers, so only the keys of a
+    /// fixed size are supported, which decode from any bytes of the right length.
+    pub fn key<K: Storable>(&self) -> Result<K, InvalidCursor> {
+        match K::BOUND {
+            Bound::Bounded {
+                max_size,
+                is_fixed_size: true,
+            } if self.0.len() == max_size as usize => Ok(K::from_bytes(Cow::Borrowed(&self.0))),
+            _ => Err(InvalidCursor),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct CursorRequest {
+    /// Cursor returned with the previous page, or `None` for the first page.
+    pub cursor: Option<Cursor>,
+    /// Maximum number of items to return, from 1 to [`MAX_PAGE_SIZE`].
+    pub limit: u64,
+}
+
+impl Default for CursorRequest {
+    fn default() -> Self {
+        Self {
+            cursor: None,
+            limit: DEFAULT_PAGE_SIZE,
+        }
+    }
+}
+
+/// Page of a list read with a [`CursorRequest`].
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct CursorPage<T> {
+    pub items: Vec<T>,
+    /// Cursor to request the next page with, or `None` if the list is over. A page may be shorter
+    /// than the limit, or even empty, and still have a cursor if the scan budget ran out.
+    pub next: Option<Cursor>,
+}
+
+impl CursorRequest {
+    pub fn new(cursor: Option<Cursor>, limit: u64) -> Self {
+        Self { cursor, limit }
+    }
+
+    /// Returns the key of the last item of the previous page, or `None` for the first page. The
+    /// lists start the iteration of the page right after this key.
+    pub fn after<K: Storable>(&self) -> Result<Option<K>, InvalidCursor> {
+        self.cursor.as_ref().map(Cursor::key).transpose()
+    }
+
+    /// Returns the page of the items selected by the `read` function from the `keys`, which must
+    /// be in ascending order and start after the [`CursorRequest::after`] key. The iteration
+    /// stops when the page is full or [`MAX_SCANNED_ITEMS`] keys are read.
+    pub fn page<K, T>(
+        &self,
+        keys: impl IntoIterator<Item = K>,
+        mut read: impl FnMut(&K) -> Option<T>,
+    ) -> CursorPage<T>
+    where
+        K: Storable,
+    {
+        self.page_by(keys, |key| key, |key| read(&key))
+    }
+
+    /// Returns the page of the `(key, value)` entries in ascending order of the keys, such as the
+    /// ones of a stable map range starting after the [`CursorRequest::after`] key.
+    pub fn page_entries<K, V>(
+        &self,
+        entries: impl IntoIterator<Item = (K, V)>,
+    ) -> CursorPage<(K, V)>
+    where
+        K: Storable,
+    {
+        self.page_by(entries, |(key, _)| key, |(key, value)| Some((key, value)))
+    }
+
+    fn page_by<I, K, T>(
+        &self,
+        items: impl IntoIterator<Item = I>,
+        key: impl Fn(&I) -> &K,
+        mut read: impl FnMut(I) -> Option<T>,
+    ) -> CursorPage<T>
+    where
+        K: Storable,
+    {
+        // An empty page would end the list without reading it.
+        let limit = self.limit.clamp(1, MAX_PAGE_SIZE) as usize;
+        let mut items = items.into_iter().peekable();
+
+        let mut page = vec![];
+        let mut last = None;
+        let mut scanned = 0;
+        while page.len() < limit && scanned < MAX_SCANNED_ITEMS {
+            let Some(item) = items.next() else {
+                break;
+            };
+            scanned += 1;
+            last = Some(Cursor::new(key(&item)));
+            if let Some(item) = read(item) {
+                page.push(item);
+            }
+        }
+
+        let next = items.peek().and(last);
+        CursorPage { items: page, next }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pages_of_a_list() {
+        let first = Pagination::new(0, 4).paginate(0..10);
+        assert_eq!(first.items, vec![0, 1, 2, 3]);
+        assert_eq!(first.total, 10);
+        assert!(first.has_more(&Pagination::new(0, 4)));
+
+        let last = Pagination::new(8, 4).paginate(0..10);
+        assert_eq!(last.items, vec![8, 9]);
+        assert_eq!(last.total, 10);
+        assert!(!last.has_more(&Pagination::new(8, 4)));
+    }
+
+    #[test]
+    fn offset_past_the_end() {
+        let page = Pagination::new(20, 5).paginate(0..10);
+        assert!(page.items.is_empty());
+        assert_eq!(page.total, 10);
+
+        let page = Pagination::new(u64::MAX, 5).paginate(0..10);
+        assert!(page.items.is_empty());
+        assert!(!page.has_more(&Pagination::new(u64::MAX, 5)));
+    }
+
+    #[test]
+    fn limit_is_capped() {
+        let page = Pagination::new(0, u64::MAX).paginate(0..(MAX_PAGE_SIZE + 10));
+        assert_eq!(page.items.len() as u64, MAX_PAGE_SIZE);
+        assert_eq!(page.total, MAX_PAGE_SIZE + 10);
+    }
+
+    #[test]
+    fn default_page() {
+        let page = Pagination::default().paginate(0..(DEFAULT_PAGE_SIZE * 2));
+        assert_eq!(page.items.len() as u64, DEFAULT_PAGE_SIZE);
+        assert_eq!(page.items[0], 0);
+    }
+
+    fn after_cursor(request: &CursorRequest, keys: &[u64]) -> impl Iterator<Item = u64> {
+        let after = request.after::<u64>().unwrap();
+        let start = keys.partition_point(|key| after.is_some_and(|after| *key <= after));
+        keys[start..].to_vec().into_iter()
+    }
+
+    fn read_all(request: CursorRequest, keys: &[u64]) -> (Vec<u64>, usize) {
+        let mut request = request;
+        let mut items = vec![];
+        let mut pages = 0;
+        loop {
+            let page = request.page(after_cursor(&request, keys), |key| Some(*key));
+            items.extend(page.items);
+            pages += 1;
+            match page.next {
+                Some(cursor) => request.cursor = Some(cursor),
+                None => return (items, pages),
+            }
+        }
+    }
+
+    #[test]
+    fn cursor_pages_of_a_list() {
+        let keys: Vec<u64> = (0..10).collect();
+
+        let first = CursorRequest::new(None, 4).page(keys.iter().copied(), |key| Some(*key));
+        assert_eq!(first.items, vec![0, 1, 2, 3]);
+        assert_eq!(first.next.unwrap().key::<u64>(), Ok(3));
+
+        assert_eq!(
+            read_all(CursorRequest::new(None, 4), &keys),
+            (keys.clone(), 3)
+        );
+        // The page which ends the list has no cursor, so no empty page is requested.
+        assert_eq!(read_all(CursorRequest::new(None, 5), &keys), (keys, 2));
+    }
+
+    #[test]
+    fn cursor_page_reads_only_selected_items() {
+        let keys: Vec<u64> = (0..20).collect();
+        let mut reads = 0;
+        let page = CursorRequest::new(None, 3).page(keys.iter().copied(), |key| {
+            reads += 1;
+            (key % 2 == 0).then_some(*key)
+        });
+        assert_eq!(page.items, vec![0, 2, 4]);
+        assert_eq!(reads, 5);
+
+        let request = CursorRequest::new(page.next, 3);
+        let mut reads = 0;
+        let next = request.page(after_cursor(&request, &keys), |key| {
+            reads += 1;
+            (key % 2 == 0).then_some(*key)
+        });
+        assert_eq!(next.items, vec![6, 8, 10]);
+        assert_eq!(reads, 6);
+    }
+
+    #[test]
+    fn cursor_scan_is_bounded() {
+        let keys: Vec<u64> = (0..(MAX_SCANNED_ITEMS as u64 * 2)).collect();
+        let page = CursorRequest::default().page(keys.iter().copied(), |_| None::<u64>);
+        assert!(page.items.is_empty());
+
+        let cursor = page.next.expect("scan budget is exhausted");
+        assert_eq!(cursor.key::<u64>(), Ok(MAX_SCANNED_ITEMS as u64 - 1));
+        let request = CursorRequest::new(Some(cursor), 10);
+        let page = request.page(after_cursor(&request, &keys), |key| Some(*key));
+        assert_eq!(page.items[0], MAX_SCANNED_ITEMS as u64);
+    }
+
+    #[test]
+    fn malformed_cursor_is_rejected() {
+        let request = CursorRequest::new(Some(Cursor(vec![1, 2, 3])), 10);
+        assert_eq!(request.after::<u64>(), Err(InvalidCursor));
+        assert_eq!(request.after::<String>(), Err(InvalidCursor));
+
+        let request = CursorRequest::new(Some(Cursor::new(&42u64)), 10);
+        assert_eq!(request.after::<u64>(), Ok(Some(42)));
+        assert_eq!(CursorRequest::default().after::<u64>(), Ok(None));
+    }
+}
@@ -10,6 +10,7 @@ use jsonrpc_core::{
 use serde::de::DeserializeOwned;
 
 pub const CHAINID_ID: &str = "chainID";
+pub const FEE_HISTORY_ID: &str = "feeHistory";
 pub const GAS_PRICE_ID: &str = "gasPrice";
 pub const LATEST_BLOCK_ID: &str = "latestBlock";
 pub const NONCE_ID: &str = "nonce";
@@ -17,9 +18,17 @@ pub const NONCE_ID: &str = "nonce";
 /// Represents different types of queries that can be made to an EVM node
 pub enum QueryType {
     GasPrice,
-    Nonce { address: H160 },
+    Nonce {
+        address: H160,
+    },
     LatestBlock,
     ChainID,
+    /// Fee history of the `block_count` latest blocks with the priority fees at the given
+    /// percentiles of each block.
+    FeeHistory {
+        block_count: u64,
+        reward_percentiles: Vec<f64>,
+    },
 }
 
 impl QueryType {
@@ -36,6 +45,18 @@ impl QueryType {
             ),
             QueryType::LatestBlock => ("eth_blockNumber", vec![], LATEST_BLOCK_ID),
             QueryType::ChainID => ("eth_chainId", vec![], CHAINID_ID),
+            QueryType::FeeHistory {
+                block_count,
+                reward_percentiles,
+            } => (
+                "eth_feeHistory",
+                vec![
+                    Value::String(format!("{block_count:#x}")),
+                    serde_json::to_value(BlockNumber::Latest).expect("should be able to convert"),
+                    serde_json::to_value(reward_percentiles).expect("should be able to convert"),
+                ],
+                FEE_HISTORY_ID,
+            ),
         };
 
         Call::MethodCall(MethodCall {
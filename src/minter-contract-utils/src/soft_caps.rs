@@ -0,0 +1,430 @@
+//! Hourly soft caps of the bridged volume.
+//!
+//! An operation above the hard limits of a bridge is rejected, but an operation which only
+//! exceeds the volume allowed per hour is not wrong, it is early. With a soft cap set for a
+//! [`Lane`], the operations beyond the cap of the current hour are accepted into a queue and are
+//! processed in the order they came once the next hours free the volume. The users can query the
+//! position of their operation in the queue and the estimated time it is processed at.
+//!
+//! The volume is counted in fixed hourly windows. An operation larger than the cap is admitted
+//! alone into an empty window, so it waits, but is not stuck forever.
+//!
+//! The queue keeps a payload of every queued operation, so the bridge can resume it when it is
+//! admitted by [`SoftCapStore::drain`].
+
+use std::borrow::Cow;
+use std::time::Duration;
+
+use candid::{CandidType, Decode, Deserialize, Encode};
+use ic_stable_structures::stable_structures::Memory;
+use ic_stable_structures::{BTreeMapStructure, Bound, StableBTreeMap, Storable};
+
+/// Length of the window the volume is counted over.
+pub const SOFT_CAP_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+/// Interval between the checks of the queue for the operations fitting into the caps.
+pub const SOFT_CAP_DRAIN_INTERVAL: Duration = Duration::from_secs(60);
+
+const WINDOW_NANOS: u64 = SOFT_CAP_WINDOW.as_nanos() as u64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, CandidType, Deserialize)]
+pub enum Lane {
+    Deposit,
+    Withdrawal,
+}
+
+impl Lane {
+    fn key(self) -> u8 {
+        match self {
+            Self::Deposit => 0,
+            Self::Withdrawal => 1,
+        }
+    }
+}
+
+/// Position of a queued operation.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct QueuePosition {
+    pub lane: Lane,
+    /// Number of the operations ahead of this one in the lane.
+    pub position: u64,
+    /// Volume of the operations ahead of this one in the lane.
+    pub volume_ahead: u128,
+    pub queued_at: u64,
+    /// Estimated time the operation is processed at, in nanoseconds. `None` if the cap of the
+    /// lane is removed and the operation is processed with the next drain of the queue.
+    pub eta: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Admission {
+    /// The operation fits into the cap and is processed now.
+    Accepted,
+    Queued(QueuePosition),
+}
+
+/// Soft cap of a lane returned by the bridges.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct SoftCap {
+    pub lane: Lane,
+    /// Volume processed per hour. `None` if the lane is not capped.
+    pub cap_per_hour: Option<u128>,
+    /// Volume processed in the current window.
+    pub window_volume: u128,
+    pub queued_operations: u64,
+    pub queued_volume: u128,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, CandidType, Deserialize)]
+struct LaneState {
+    cap_per_hour: Option<u128>,
+    window_start: u64,
+    window_volume: u128,
+}
+
+impl LaneState {
+    /// Starts a new window if the current one is over at `now`.
+    fn roll(&mut self, now: u64) {
+        if now >= self.window_start.saturating_add(WINDOW_NANOS) {
+            self.window_start = now - now % WINDOW_NANOS;
+            self.window_volume = 0;
+        }
+    }
+
+    fn fits(&self, amount: u128) -> bool {
+        match self.cap_per_hour {
+            None => true,
+            Some(cap) => {
+                self.window_volume == 0 || self.window_volume.saturating_add(amount) <= cap
+            }
+        }
+    }
+
+    /// Estimated time the operation with the `amount` is admitted at, if the operations with
+    /// the amounts `ahead` are admitted before it.
+    fn eta(&self, ahead: &[u128], amount: u128, now: u64) -> Option<u64> {
+        self.cap_per_hour?;
+        let mut window = self.clone();
+        for amount in ahead.iter().chain(std::iter::once(&amount)) {
+            if !window.fits(*amount) {
+                window.window_start = window.window_start.saturating_add(WINDOW_NANOS);
+                window.window_volume = 0;
+            }
+            window.window_volume = window.window_volume.saturating_add(*amount);
+        }
+
+        if window.window_start == self.window_start {
+            Some(now)
+        } else {
+            Some(window.window_start)
+        }
+    }
+}
+
+impl Storable for LaneState {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("serialization failed"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).expect("deserialization failed")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// Operation in the queue of a lane.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct QueueEntry {
+    /// Identifier of the operation given by the bridge.
+    pub ticket: String,
+    pub lane: Lane,
+    pub amount: u128,
+    pub queued_at: u64,
+    /// Data the bridge needs to resume the operation.
+    pub payload: Vec<u8>,
+    /// Whether the operation was admitted by a drain and waits to be resumed.
+    pub admitted: bool,
+}
+
+impl Storable for QueueEntry {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("serialization failed"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).expect("deserialization failed")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+pub struct SoftCapStore<M: Memory> {
+    lanes: StableBTreeMap<u8, LaneState, M>,
+    /// Queued operations by the sequence number they were queued with.
+    queue: StableBTreeMap<u64, QueueEntry, M>,
+}
+
+impl<M: Memory> SoftCapStore<M> {
+    pub fn new(lanes_memory: M, queue_memory: M) -> Self {
+        Self {
+            lanes: StableBTreeMap::new(lanes_memory),
+            queue: StableBTreeMap::new(queue_memory),
+        }
+    }
+
+    /// Sets the cap of the `lane`, or removes it if `cap_per_hour` is `None`. The operations
+    /// queued already are admitted by the next drains under the new cap.
+    pub fn set_cap(&mut self, lane: Lane, cap_per_hour: Option<u128>) -> Result<(), String> {
+        if cap_per_hour == Some(0) {
+            return Err("soft cap must be positive".into());
+        }
+
+        let mut state = self.lane(lane);
+        state.cap_per_hour = cap_per_hour;
+        self.lanes.insert(lane.key(), state);
+        Ok(())
+    }
+
+    pub fn caps(&self, now: u64) -> Vec<SoftCap> {
+        [Lane::Deposit, Lane::Withdrawal]
+            .into_iter()
+            .map(|lane| {
+                let mut state = self.lane(lane);
+                state.roll(now);
+                let queued = self.waiting(lane);
+                SoftCap {
+                    lane,
+                    cap_per_hour: state.cap_per_hour,
+                    window_volume: state.window_volume,
+                    queued_operations: queued.len() as u64,
+                    queued_volume: queued.iter().map(|(_, entry)| entry.amount).sum(),
+                }
+            })
+            .collect()
+    }
+
+    /// Admits the operation with the `ticket` into the `lane`, or queues it if the lane has
+    /// queued operations or the `amount` doesn't fit into the cap of the current window.
+    ///
+    /// An operation admitted by a drain is accepted, and an operation still in the queue gets
+    /// its position, so the bridges call this every time they resume the operation.
+    pub fn admit(
+        &mut self,
+        ticket: &str,
+        lane: Lane,
+        amount: u128,
+        payload: Vec<u8>,
+        now: u64,
+    ) -> Admission {
+        if let Some((seq, entry)) = self.find(ticket) {
+            if entry.admitted {
+                self.queue.remove(&seq);
+                return Admission::Accepted;
+            }
+
+            let position = self
+                .position(ticket, now)
+                .expect("queued operation has a position");
+            return Admission::Queued(position);
+        }
+
+        let mut state = self.lane(lane);
+        state.roll(now);
+        if self.waiting(lane).is_empty() && state.fits(amount) {
+            if state.cap_per_hour.is_some() {
+                state.window_volume = state.window_volume.saturating_add(amount);
+                self.lanes.insert(lane.key(), state);
+            }
+            return Admission::Accepted;
+        }
+
+        let seq = self.queue.iter().map(|(seq, _)| seq + 1).max().unwrap_or(0);
+        self.queue.insert(
+            seq,
+            QueueEntry {
+                ticket: ticket.to_string(),
+                lane,
+                amount,
+                queued_at: now,
+                payload,
+                admitted: false,
+            },
+        );
+        log::info!("Operation {ticket} is queued by the {lane:?} soft cap");
+
+        Admission::Queued(
+            self.position(ticket, now)
+                .expect("queued operation has a position"),
+        )
+    }
+
+    /// Admits the queued operations fitting into the caps at `now`, oldest first, and returns
+    /// them. The admitted operations stay in the queue until the bridge resumes them with
+    /// [`Self::admit`], so an operation admitted again after an upgrade is not lost.
+    pub fn drain(&mut self, now: u64) -> Vec<QueueEntry> {
+        let mut admitted: Vec<_> = self
+            .queue
+            .iter()
+            .filter(|(_, entry)| entry.admitted)
+            .map(|(_, entry)| entry)
+            .collect();
+
+        for lane in [Lane::Deposit, Lane::Withdrawal] {
+            let mut state = self.lane(lane);
+            state.roll(now);
+            for (seq, mut entry) in self.waiting(lane) {
+                if !state.fits(entry.amount) {
+                    break;
+                }
+
+                state.window_volume = state.window_volume.saturating_add(entry.amount);
+                entry.admitted = true;
+                self.queue.insert(seq, entry.clone());
+                admitted.push(entry);
+            }
+            self.lanes.insert(lane.key(), state);
+        }
+
+        admitted
+    }
+
+    /// Removes the operation from the queue, e.g. when it is cancelled.
+    pub fn remove(&mut self, ticket: &str) -> Option<QueueEntry> {
+        let (seq, _) = self.find(ticket)?;
+        self.queue.remove(&seq)
+    }
+
+    /// Position of the queued operation, or `None` if it is not queued or is admitted already.
+    pub fn position(&self, ticket: &str, now: u64) -> Option<QueuePosition> {
+        let (_, entry) = self.find(ticket).filter(|(_, entry)| !entry.admitted)?;
+        let ahead: Vec<_> = self
+            .waiting(entry.lane)
+            .into_iter()
+            .take_while(|(_, queued)| queued.ticket != ticket)
+            .map(|(_, queued)| queued.amount)
+            .collect();
+
+        let mut state = self.lane(entry.lane);
+        state.roll(now);
+        Some(QueuePosition {
+            lane: entry.lane,
+            position: ahead.len() as u64,
+            volume_ahead: ahead.iter().sum(),
+            queued_at: entry.queued_at,
+            eta: state.eta(&ahead, entry.amount, now),
+        })
+    }
+
+    fn lane(&self, lane: Lane) -> LaneState {
+        self.lanes.get(&lane.key()).unwrap_or_default()
+    }
+
+    fn find(&self, ticket: &str) -> Option<(u64, QueueEntry)> {
+        self.queue.iter().find(|(_, entry)| entry.ticket == ticket)
+    }
+
+    /// Operations of the `lane` waiting for admission, oldest first.
+    fn waiting(&self, lane: Lane) -> Vec<(u64, QueueEntry)> {
+        self.queue
+            .iter()
+            .filter(|(_, entry)| entry.lane == lane && !entry.admitted)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ic_stable_structures::VectorMemory;
+
+    use super::*;
+
+    const HOUR: u64 = WINDOW_NANOS;
+
+    fn store() -> SoftCapStore<VectorMemory> {
+        let mut store = SoftCapStore::new(VectorMemory::default(), VectorMemory::default());
+        store.set_cap(Lane::Deposit, Some(100)).unwrap();
+        store
+    }
+
+    #[test]
+    fn operations_beyond_cap_are_queued_in_order() {
+        let mut store = store();
+        assert_eq!(
+            store.admit("a", Lane::Deposit, 60, vec![], 0),
+            Admission::Accepted
+        );
+        assert_eq!(
+            store.admit("b", Lane::Deposit, 50, vec![1], 1),
+            Admission::Queued(QueuePosition {
+                lane: Lane::Deposit,
+                position: 0,
+                volume_ahead: 0,
+                queued_at: 1,
+                eta: Some(HOUR),
+            })
+        );
+        // The lane has a queue, so a smaller operation fitting into the cap waits too.
+        let Admission::Queued(position) = store.admit("c", Lane::Deposit, 30, vec![2], 2) else {
+            panic!("operation is not queued");
+        };
+        assert_eq!(position.position, 1);
+        assert_eq!(position.volume_ahead, 50);
+        assert_eq!(position.eta, Some(HOUR));
+
+        // Other lanes are not capped.
+        assert_eq!(
+            store.admit("d", Lane::Withdrawal, 1_000, vec![], 2),
+            Admission::Accepted
+        );
+
+        assert!(store.drain(HOUR - 1).is_empty());
+        let admitted = store.drain(HOUR);
+        assert_eq!(
+            admitted
+                .iter()
+                .map(|entry| entry.ticket.as_str())
+                .collect::<Vec<_>>(),
+            vec!["b", "c"]
+        );
+        assert_eq!(store.position("b", HOUR), None);
+
+        assert_eq!(
+            store.admit("b", Lane::Deposit, 50, vec![], HOUR),
+            Admission::Accepted
+        );
+        assert_eq!(store.drain(HOUR).len(), 1);
+        assert_eq!(store.caps(HOUR)[0].window_volume, 80);
+    }
+
+    #[test]
+    fn operation_larger_than_cap_is_admitted_alone() {
+        let mut store = store();
+        store.admit("a", Lane::Deposit, 10, vec![], 0);
+        let Admission::Queued(position) = store.admit("b", Lane::Deposit, 250, vec![], 0) else {
+            panic!("operation is not queued");
+        };
+        assert_eq!(position.eta, Some(HOUR));
+
+        let admitted = store.drain(HOUR);
+        assert_eq!(admitted.len(), 1);
+        assert_eq!(admitted[0].amount, 250);
+    }
+
+    #[test]
+    fn queued_operation_is_removed() {
+        let mut store = store();
+        store.admit("a", Lane::Deposit, 100, vec![], 0);
+        store.admit("b", Lane::Deposit, 100, vec![], 0);
+        store.admit("c", Lane::Deposit, 100, vec![], 0);
+        assert_eq!(store.caps(0)[0].queued_operations, 2);
+
+        assert!(store.remove("b").is_some());
+        assert_eq!(store.position("c", 0).unwrap().position, 0);
+
+        store.set_cap(Lane::Deposit, None).unwrap();
+        assert_eq!(store.position("c", 0).unwrap().eta, None);
+        assert_eq!(store.drain(0).len(), 1);
+        assert!(store.set_cap(Lane::Deposit, Some(0)).is_err());
+    }
+}
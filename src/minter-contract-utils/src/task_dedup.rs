@@ -0,0 +1,111 @@
+//! Deduplication of the tasks appended to the bridge schedulers.
+//!
+//! The canister timers append the same tasks, e.g. collecting the EVM events, on every tick,
+//! whether the previous task has been executed or not. When the tasks are completed slower than
+//! they are appended, e.g. while an EVM is unreachable and the tasks are retried, the scheduler
+//! storage grows without bound. [`PendingTasks`] keeps the identities of the tasks pending in the
+//! scheduler, so that a task is only appended if the same task is not pending already.
+//!
+//! The identities are kept in the heap and are lost on upgrade, so a task pending at the upgrade
+//! can be appended once more after it.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+
+use serde::Serialize;
+
+/// Identity of a task: its type and the hash of its parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TaskKey {
+    pub task_type: &'static str,
+    pub params_hash: u64,
+}
+
+impl TaskKey {
+    pub fn new(task_type: &'static str, task: &impl Serialize) -> Self {
+        let mut hasher = DefaultHasher::new();
+        match serde_json::to_vec(task) {
+            Ok(params) => params.hash(&mut hasher),
+            Err(err) => log::warn!("failed to serialize {task_type} task params: {err}"),
+        }
+
+        Self {
+            task_type,
+            params_hash: hasher.finish(),
+        }
+    }
+}
+
+/// Tasks appended to the scheduler and not completed yet.
+#[derive(Debug, Default, Clone)]
+pub struct PendingTasks {
+    keys: HashSet<TaskKey>,
+}
+
+impl PendingTasks {
+    /// Registers the task as pending. Returns `false` if the same task is pending already, in
+    /// which case the task should not be appended.
+    pub fn insert(&mut self, key: TaskKey) -> bool {
+        self.keys.insert(key)
+    }
+
+    /// Unregisters the task once it is completed or has failed for good.
+    pub fn remove(&mut self, key: &TaskKey) {
+        self.keys.remove(key);
+    }
+
+    pub fn contains(&self, key: &TaskKey) -> bool {
+        self.keys.contains(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    enum TestTask {
+        Collect(u32),
+        Refresh,
+    }
+
+    #[test]
+    fn task_key_depends_on_params() {
+        assert_eq!(
+            TaskKey::new("Collect", &TestTask::Collect(1)),
+            TaskKey::new("Collect", &TestTask::Collect(1))
+        );
+        assert_ne!(
+            TaskKey::new("Collect", &TestTask::Collect(1)),
+            TaskKey::new("Collect", &TestTask::Collect(2))
+        );
+        assert_ne!(
+            TaskKey::new("Collect", &TestTask::Refresh),
+            TaskKey::new("Refresh", &TestTask::Refresh)
+        );
+    }
+
+    #[test]
+    fn same_task_is_pending_once() {
+        let mut pending = PendingTasks::default();
+        let key = TaskKey::new("Refresh", &TestTask::Refresh);
+
+        assert!(pending.insert(key));
+        assert!(!pending.insert(key));
+        assert!(pending.insert(TaskKey::new("Collect", &TestTask::Collect(1))));
+        assert_eq!(pending.len(), 2);
+
+        pending.remove(&key);
+        assert!(!pending.contains(&key));
+        assert!(pending.insert(key));
+    }
+}
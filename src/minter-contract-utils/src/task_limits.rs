@@ -0,0 +1,395 @@
+//! Priorities and concurrency limits for the bridge scheduler tasks.
+//!
+//! The scheduler executes all the pending tasks with the same priority, so a burst of tasks of
+//! one type can occupy all the concurrent inter-canister calls of the canister. [`TaskLimiter`]
+//! keeps track of the tasks in flight and tells the caller to defer tasks which exceed their
+//! limits, so that more important tasks can proceed.
+//!
+//! A deferred task fails with the [`TaskDeferred::error_message`] error, so the scheduler retries
+//! it with the backoff of the task. A task whose retries are used up by the deferrals is
+//! recognized by [`is_deferral_error`] and is appended again with its options and
+//! [`DEFERRED_TASK_DELAY_SECS`] of delay.
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+use candid::{CandidType, Decode, Encode};
+use ic_stable_structures::stable_structures::Memory;
+use ic_stable_structures::{Bound, CellStructure, StableCell, Storable};
+use serde::Deserialize;
+
+/// Default maximum number of tasks executed concurrently.
+pub const DEFAULT_MAX_CONCURRENT_TASKS: u32 = 32;
+
+/// Delay before a task whose retries are used up by the deferrals is executed again.
+pub const DEFERRED_TASK_DELAY_SECS: u64 = 2;
+
+const DEFERRAL_ERROR_PREFIX: &str = "task is deferred: ";
+
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, CandidType, Deserialize,
+)]
+pub enum TaskPriority {
+    /// May only use half of the global concurrency limit.
+    Low,
+    /// May use the whole global concurrency limit.
+    #[default]
+    Normal,
+    /// Not restricted by the global concurrency limit.
+    High,
+}
+
+/// Limits of a single task type.
+#[derive(Debug, Default, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct TaskTypeLimits {
+    /// Overrides the default priority of the task type.
+    pub priority: Option<TaskPriority>,
+    /// Maximum number of tasks of this type executed concurrently.
+    pub max_concurrent: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct TaskLimits {
+    /// Maximum number of tasks executed concurrently. `None` means no limit.
+    pub max_concurrent_tasks: Option<u32>,
+    /// Limits by the task type name.
+    pub task_types: HashMap<String, TaskTypeLimits>,
+}
+
+impl Default for TaskLimits {
+    fn default() -> Self {
+        Self {
+            max_concurrent_tasks: Some(DEFAULT_MAX_CONCURRENT_TASKS),
+            task_types: HashMap::new(),
+        }
+    }
+}
+
+impl TaskLimits {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_concurrent_tasks == Some(0) {
+            return Err("Maximum number of concurrent tasks must be greater than zero".into());
+        }
+
+        if let Some((task_type, _)) = self
+            .task_types
+            .iter()
+            .find(|(_, limits)| limits.max_concurrent == Some(0))
+        {
+            return Err(format!(
+                "Maximum number of concurrent {task_type} tasks must be greater than zero"
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn priority(&self, task_type: &str, default_priority: TaskPriority) -> TaskPriority {
+        self.task_types
+            .get(task_type)
+            .and_then(|limits| limits.priority)
+            .unwrap_or(default_priority)
+    }
+
+    fn max_concurrent(&self, task_type: &str) -> Option<u32> {
+        self.task_types
+            .get(task_type)
+            .and_then(|limits| limits.max_concurrent)
+    }
+
+    fn capacity(&self, priority: TaskPriority) -> Option<u32> {
+        let max = self.max_concurrent_tasks?;
+        match priority {
+            TaskPriority::Low => Some((max / 2).max(1)),
+            TaskPriority::Normal => Some(max),
+            TaskPriority::High => None,
+        }
+    }
+}
+
+impl Storable for TaskLimits {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("serialization failed"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).expect("deserialization failed")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// Reason for a task to be deferred.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaskDeferred {
+    /// Too many tasks of the same type are in flight.
+    TaskTypeLimit { task_type: String, limit: u32 },
+    /// Too many tasks are in flight for the task priority.
+    PriorityLimit { priority: TaskPriority, limit: u32 },
+    /// Too many EVM transactions are in flight.
+    InFlightTxLimit { limit: u32 },
+}
+
+impl TaskDeferred {
+    /// Message of the error the deferred task fails with.
+    pub fn error_message(&self) -> String {
+        format!("{DEFERRAL_ERROR_PREFIX}{self}")
+    }
+}
+
+impl fmt::Display for TaskDeferred {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TaskTypeLimit { task_type, limit } => {
+                write!(f, "{limit} {task_type} tasks are in flight")
+            }
+            Self::PriorityLimit { priority, limit } => {
+                write!(
+                    f,
+                    "{limit} tasks are in flight for the {priority:?} priority"
+                )
+            }
+            Self::InFlightTxLimit { limit } => write!(f, "{limit} transactions are in flight"),
+        }
+    }
+}
+
+/// Checks whether the task failed with the error of [`TaskDeferred::error_message`].
+pub fn is_deferral_error(error: &str) -> bool {
+    error.starts_with(DEFERRAL_ERROR_PREFIX)
+}
+
+#[derive(Debug, Default)]
+struct InFlight {
+    total: u32,
+    by_type: HashMap<String, u32>,
+}
+
+/// Tracks tasks in flight against the configured [`TaskLimits`]. The limits are kept in the
+/// stable memory, the tasks in flight are not, as no task is in flight after an upgrade.
+pub struct TaskLimiter<M: Memory> {
+    limits: StableCell<TaskLimits, M>,
+    in_flight: Rc<RefCell<InFlight>>,
+}
+
+impl<M: Memory> TaskLimiter<M> {
+    pub fn new(memory: M) -> Self {
+        Self {
+            limits: StableCell::new(memory, TaskLimits::default())
+                .expect("stable memory task limits initialization failed"),
+            in_flight: Rc::default(),
+        }
+    }
+
+    pub fn limits(&self) -> &TaskLimits {
+        self.limits.get()
+    }
+
+    pub fn set_limits(&mut self, limits: TaskLimits) {
+        self.limits
+            .set(limits)
+            .expect("failed to update task limits");
+    }
+
+    /// Registers a task of the given type as being in flight, if the limits allow it. The task is
+    /// considered to be in flight until the returned permit is dropped.
+    pub fn try_acquire(
+        &self,
+        task_type: &str,
+        default_priority: TaskPriority,
+    ) -> Result<TaskPermit, TaskDeferred> {
+        let limits = self.limits.get();
+        let mut in_flight = self.in_flight.borrow_mut();
+        let type_in_flight = in_flight
+            .by_type
+            .get(task_type)
+            .copied()
+            .unwrap_or_default();
+
+        if let Some(limit) = limits.max_concurrent(task_type) {
+            if type_in_flight >= limit {
+                return Err(TaskDeferred::TaskTypeLimit {
+                    task_type: task_type.to_string(),
+                    limit,
+                });
+            }
+        }
+
+        let priority = limits.priority(task_type, default_priority);
+        if let Some(limit) = limits.capacity(priority) {
+            if in_flight.total >= limit {
+                return Err(TaskDeferred::PriorityLimit { priority, limit });
+            }
+        }
+
+        in_flight.total += 1;
+        in_flight
+            .by_type
+            .insert(task_type.to_string(), type_in_flight + 1);
+
+        Ok(TaskPermit {
+            task_type: task_type.to_string(),
+            in_flight: self.in_flight.clone(),
+        })
+    }
+
+    /// Number of tasks in flight.
+    pub fn in_flight(&self) -> u32 {
+        self.in_flight.borrow().total
+    }
+}
+
+/// Marks a task as being in flight while alive.
+#[derive(Debug)]
+pub struct TaskPermit {
+    task_type: String,
+    in_flight: Rc<RefCell<InFlight>>,
+}
+
+impl Drop for TaskPermit {
+    fn drop(&mut self) {
+        let mut in_flight = self.in_flight.borrow_mut();
+        in_flight.total = in_flight.total.saturating_sub(1);
+        if let Some(count) = in_flight.by_type.get_mut(&self.task_type) {
+            *count = count.saturating_sub(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ic_stable_structures::VectorMemory;
+
+    use super::*;
+
+    fn limiter(
+        max_concurrent_tasks: Option<u32>,
+        task_types: &[(&str, TaskTypeLimits)],
+    ) -> TaskLimiter<VectorMemory> {
+        let mut limiter = TaskLimiter::new(VectorMemory::default());
+        limiter.set_limits(TaskLimits {
+            max_concurrent_tasks,
+            task_types: task_types
+                .iter()
+                .map(|(name, limits)| (name.to_string(), limits.clone()))
+                .collect(),
+        });
+        limiter
+    }
+
+    #[test]
+    fn permits_are_released_on_drop() {
+        let limiter = limiter(Some(1), &[]);
+
+        let permit = limiter.try_acquire("mint", TaskPriority::Normal).unwrap();
+        assert_eq!(limiter.in_flight(), 1);
+        assert!(limiter.try_acquire("mint", TaskPriority::Normal).is_err());
+
+        drop(permit);
+        assert_eq!(limiter.in_flight(), 0);
+        assert!(limiter.try_acquire("mint", TaskPriority::Normal).is_ok());
+    }
+
+    #[test]
+    fn high_priority_tasks_ignore_global_limit() {
+        let limiter = limiter(Some(2), &[]);
+
+        let _first = limiter.try_acquire("mint", TaskPriority::Normal).unwrap();
+        let _second = limiter.try_acquire("mint", TaskPriority::Normal).unwrap();
+        assert_eq!(
+            limiter
+                .try_acquire("mint", TaskPriority::Normal)
+                .unwrap_err(),
+            TaskDeferred::PriorityLimit {
+                priority: TaskPriority::Normal,
+                limit: 2
+            }
+        );
+        assert!(limiter.try_acquire("collect", TaskPriority::High).is_ok());
+    }
+
+    #[test]
+    fn low_priority_tasks_use_half_of_global_limit() {
+        let limiter = limiter(Some(4), &[]);
+
+        let _first = limiter.try_acquire("mint", TaskPriority::Low).unwrap();
+        let _second = limiter.try_acquire("mint", TaskPriority::Low).unwrap();
+        assert!(limiter.try_acquire("mint", TaskPriority::Low).is_err());
+        assert!(limiter.try_acquire("burn", TaskPriority::Normal).is_ok());
+    }
+
+    #[test]
+    fn task_type_limits() {
+        let limiter = limiter(
+            None,
+            &[(
+                "mint",
+                TaskTypeLimits {
+                    priority: Some(TaskPriority::High),
+                    max_concurrent: Some(1),
+                },
+            )],
+        );
+
+        let _permit = limiter.try_acquire("mint", TaskPriority::Low).unwrap();
+        assert_eq!(
+            limiter.try_acquire("mint", TaskPriority::Low).unwrap_err(),
+            TaskDeferred::TaskTypeLimit {
+                task_type: "mint".to_string(),
+                limit: 1
+            }
+        );
+        assert!(limiter.try_acquire("burn", TaskPriority::Low).is_ok());
+    }
+
+    #[test]
+    fn limits_are_kept_in_memory() {
+        let memory = VectorMemory::default();
+        let limits = TaskLimits {
+            max_concurrent_tasks: Some(4),
+            task_types: HashMap::from([(
+                "mint".to_string(),
+                TaskTypeLimits {
+                    priority: Some(TaskPriority::Low),
+                    max_concurrent: Some(1),
+                },
+            )]),
+        };
+        TaskLimiter::new(memory.clone()).set_limits(limits.clone());
+
+        assert_eq!(TaskLimiter::new(memory).limits(), &limits);
+    }
+
+    #[test]
+    fn deferral_errors_are_recognized() {
+        let reason = TaskDeferred::InFlightTxLimit { limit: 8 };
+        assert!(is_deferral_error(&reason.error_message()));
+        assert!(!is_deferral_error("rpc call failed"));
+    }
+
+    #[test]
+    fn limits_validation() {
+        assert!(TaskLimits::default().validate().is_ok());
+        assert!(TaskLimits {
+            max_concurrent_tasks: Some(0),
+            task_types: HashMap::new(),
+        }
+        .validate()
+        .is_err());
+        assert!(TaskLimits {
+            max_concurrent_tasks: None,
+            task_types: HashMap::from([(
+                "mint".to_string(),
+                TaskTypeLimits {
+                    priority: None,
+                    max_concurrent: Some(0),
+                }
+            )]),
+        }
+        .validate()
+        .is_err());
+    }
+}
@@ -0,0 +1,304 @@
+//! Allowlist of the withdrawal addresses of the EVM accounts.
+//!
+//! Some deployments restrict the withdrawals of an EVM account to the BTC addresses registered
+//! by its holder in advance. In the allowlist mode a burn to another address is not withdrawn.
+//!
+//! The holder registers and unregisters the addresses with `personal_sign` signatures of the
+//! [`WithdrawalAllowlist::change_message`], so the change is approved by the EVM account and not
+//! by the caller of the canister. The message includes the bridge and a per account nonce, so a
+//! signature cannot be replayed to another bridge or to undo a later change.
+
+use std::borrow::Cow;
+
+use candid::{CandidType, Decode, Deserialize, Encode};
+use did::H160;
+use ethers_core::types::Signature;
+use ic_stable_structures::stable_structures::Memory;
+use ic_stable_structures::{
+    BTreeMapStructure, Bound, CellStructure, StableBTreeMap, StableCell, Storable,
+};
+
+/// Maximum number of the withdrawal addresses of an account.
+pub const MAX_ADDRESSES_PER_ACCOUNT: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, CandidType, Deserialize)]
+pub enum AllowlistAction {
+    Register,
+    Unregister,
+}
+
+impl AllowlistAction {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Register => "register",
+            Self::Unregister => "unregister",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub enum AllowlistError {
+    /// The signature cannot be parsed or recovered.
+    InvalidSignature(String),
+    /// The message is signed by another account.
+    SignerMismatch {
+        signer: H160,
+    },
+    InvalidAddress(String),
+    AlreadyRegistered,
+    NotRegistered,
+    TooManyAddresses {
+        max: u32,
+    },
+}
+
+/// Withdrawal addresses of an account.
+#[derive(Debug, Default, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct AccountAllowlist {
+    pub addresses: Vec<String>,
+    /// Nonce of the next change message of the account.
+    pub nonce: u64,
+}
+
+impl Storable for AccountAllowlist {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("serialization failed"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).expect("deserialization failed")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+pub struct WithdrawalAllowlist<M: Memory> {
+    accounts: StableBTreeMap<H160, AccountAllowlist, M>,
+    /// `1` if the allowlist mode is enabled.
+    enabled: StableCell<u8, M>,
+}
+
+impl<M: Memory> WithdrawalAllowlist<M> {
+    pub fn new(accounts_memory: M, mode_memory: M) -> Self {
+        Self {
+            accounts: StableBTreeMap::new(accounts_memory),
+            enabled: StableCell::new(mode_memory, 0)
+                .expect("stable memory allowlist mode initialization failed"),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        *self.enabled.get() == 1
+    }
+
+    /// Enables or disables the allowlist mode. The registered addresses are kept either way.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled
+            .set(u8::from(enabled))
+            .expect("failed to update allowlist mode");
+    }
+
+    pub fn get(&self, account: &H160) -> AccountAllowlist {
+        self.accounts.get(account).unwrap_or_default()
+    }
+
+    /// Whether the `account` may withdraw to the `address`. Any address is allowed if the mode
+    /// is disabled.
+    pub fn allows(&self, account: &H160, address: &str) -> bool {
+        !self.is_enabled()
+            || self
+                .get(account)
+                .addresses
+                .iter()
+                .any(|registered| registered == address)
+    }
+
+    /// Message the `account` signs to apply the `action` to the `address` on the `bridge`.
+    pub fn change_message(
+        &self,
+        bridge: &str,
+        account: &H160,
+        address: &str,
+        action: AllowlistAction,
+    ) -> String {
+        let nonce = self.get(account).nonce;
+        format!(
+            "Withdrawal allowlist\nBridge: {bridge}\nAccount: {:#x}\nAction: {}\nAddress: {address}\nNonce: {nonce}",
+            account.0,
+            action.as_str(),
+        )
+    }
+
+    /// Applies the `action` to the `address` of the `account` if the `signature` of its change
+    /// message is made by the account. The `address` must be validated by the caller.
+    pub fn apply(
+        &mut self,
+        bridge: &str,
+        account: &H160,
+        address: &str,
+        action: AllowlistAction,
+        signature: &[u8],
+    ) -> Result<(), AllowlistError> {
+        let message = self.change_message(bridge, account, address, action);
+        let signer = recover_signer(&message, signature)?;
+        if signer != *account {
+            return Err(AllowlistError::SignerMismatch { signer });
+        }
+
+        let mut allowlist = self.get(account);
+        let position = allowlist
+            .addresses
+            .iter()
+            .position(|registered| registered == address);
+        match (action, position) {
+            (AllowlistAction::Register, Some(_)) => return Err(AllowlistError::AlreadyRegistered),
+            (AllowlistAction::Register, None) => {
+                if allowlist.addresses.len() >= MAX_ADDRESSES_PER_ACCOUNT {
+                    return Err(AllowlistError::TooManyAddresses {
+                        max: MAX_ADDRESSES_PER_ACCOUNT as u32,
+                    });
+                }
+                allowlist.addresses.push(address.to_string());
+            }
+            (AllowlistAction::Unregister, Some(position)) => {
+                allowlist.addresses.remove(position);
+            }
+            (AllowlistAction::Unregister, None) => return Err(AllowlistError::NotRegistered),
+        }
+
+        allowlist.nonce += 1;
+        self.accounts.insert(account.clone(), allowlist);
+        Ok(())
+    }
+}
+
+/// Recovers the signer of a `personal_sign` signature of the `message`.
+fn recover_signer(message: &str, signature: &[u8]) -> Result<H160, AllowlistError> {
+    let signature = Signature::try_from(signature)
+        .map_err(|err| AllowlistError::InvalidSignature(err.to_string()))?;
+    signature
+        .recover(message)
+        .map(H160::from)
+        .map_err(|err| AllowlistError::InvalidSignature(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers_core::k256::ecdsa::SigningKey;
+    use ethers_core::utils::{hash_message, secret_key_to_address};
+    use ic_stable_structures::VectorMemory;
+
+    use super::*;
+
+    const BRIDGE: &str = "bridge";
+    const ADDRESS: &str = "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq";
+
+    fn allowlist() -> WithdrawalAllowlist<VectorMemory> {
+        WithdrawalAllowlist::new(VectorMemory::default(), VectorMemory::default())
+    }
+
+    fn account_key(seed: u8) -> (SigningKey, H160) {
+        let key = SigningKey::from_slice(&[seed; 32]).unwrap();
+        let address = secret_key_to_address(&key).into();
+        (key, address)
+    }
+
+    fn sign(key: &SigningKey, message: &str) -> Vec<u8> {
+        let (signature, recovery_id) = key
+            .sign_prehash_recoverable(hash_message(message).as_bytes())
+            .unwrap();
+        let mut bytes = signature.to_bytes().to_vec();
+        bytes.push(27 + recovery_id.to_byte());
+        bytes
+    }
+
+    fn signed_change(
+        allowlist: &mut WithdrawalAllowlist<VectorMemory>,
+        key: &SigningKey,
+        account: &H160,
+        action: AllowlistAction,
+    ) -> Result<(), AllowlistError> {
+        let message = allowlist.change_message(BRIDGE, account, ADDRESS, action);
+        let signature = sign(key, &message);
+        allowlist.apply(BRIDGE, account, ADDRESS, action, &signature)
+    }
+
+    #[test]
+    fn addresses_are_changed_with_account_signatures() {
+        let mut allowlist = allowlist();
+        let (key, account) = account_key(1);
+        assert!(allowlist.allows(&account, ADDRESS));
+
+        allowlist.set_enabled(true);
+        assert!(!allowlist.allows(&account, ADDRESS));
+
+        signed_change(&mut allowlist, &key, &account, AllowlistAction::Register).unwrap();
+        assert!(allowlist.allows(&account, ADDRESS));
+        assert!(!allowlist.allows(&account, "bc1qother"));
+        assert_eq!(
+            signed_change(&mut allowlist, &key, &account, AllowlistAction::Register),
+            Err(AllowlistError::AlreadyRegistered)
+        );
+
+        signed_change(&mut allowlist, &key, &account, AllowlistAction::Unregister).unwrap();
+        assert!(!allowlist.allows(&account, ADDRESS));
+        assert_eq!(allowlist.get(&account).nonce, 2);
+    }
+
+    #[test]
+    fn foreign_and_replayed_signatures_are_rejected() {
+        let mut allowlist = allowlist();
+        let (key, account) = account_key(1);
+        let (other_key, other_account) = account_key(2);
+
+        let message =
+            allowlist.change_message(BRIDGE, &account, ADDRESS, AllowlistAction::Register);
+        assert_eq!(
+            allowlist.apply(
+                BRIDGE,
+                &account,
+                ADDRESS,
+                AllowlistAction::Register,
+                &sign(&other_key, &message)
+            ),
+            Err(AllowlistError::SignerMismatch {
+                signer: other_account
+            })
+        );
+        assert!(matches!(
+            allowlist.apply(
+                BRIDGE,
+                &account,
+                ADDRESS,
+                AllowlistAction::Register,
+                &[1; 10]
+            ),
+            Err(AllowlistError::InvalidSignature(_))
+        ));
+
+        let register = sign(&key, &message);
+        allowlist
+            .apply(
+                BRIDGE,
+                &account,
+                ADDRESS,
+                AllowlistAction::Register,
+                &register,
+            )
+            .unwrap();
+        signed_change(&mut allowlist, &key, &account, AllowlistAction::Unregister).unwrap();
+
+        // The nonce of the account has changed, so the first signature is not valid anymore.
+        assert!(matches!(
+            allowlist.apply(
+                BRIDGE,
+                &account,
+                ADDRESS,
+                AllowlistAction::Register,
+                &register
+            ),
+            Err(AllowlistError::SignerMismatch { .. })
+        ));
+        assert!(allowlist.get(&account).addresses.is_empty());
+    }
+}
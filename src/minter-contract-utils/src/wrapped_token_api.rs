@@ -109,3 +109,42 @@ pub static ERC_20_ALLOWANCE: Lazy<Function> = Lazy::new(|| Function {
     constant: None,
     state_mutability: StateMutability::View,
 });
+
+#[allow(deprecated)] // need to initialize `constant` field
+pub static ERC_20_NAME: Lazy<Function> = Lazy::new(|| Function {
+    name: "name".into(),
+    inputs: vec![],
+    outputs: vec![Param {
+        name: "".into(),
+        kind: ParamType::String,
+        internal_type: None,
+    }],
+    constant: None,
+    state_mutability: StateMutability::View,
+});
+
+#[allow(deprecated)] // need to initialize `constant` field
+pub static ERC_20_SYMBOL: Lazy<Function> = Lazy::new(|| Function {
+    name: "symbol".into(),
+    inputs: vec![],
+    outputs: vec![Param {
+        name: "".into(),
+        kind: ParamType::String,
+        internal_type: None,
+    }],
+    constant: None,
+    state_mutability: StateMutability::View,
+});
+
+#[allow(deprecated)] // need to initialize `constant` field
+pub static ERC_20_DECIMALS: Lazy<Function> = Lazy::new(|| Function {
+    name: "decimals".into(),
+    inputs: vec![],
+    outputs: vec![Param {
+        name: "".into(),
+        kind: ParamType::Uint(8),
+        internal_type: None,
+    }],
+    constant: None,
+    state_mutability: StateMutability::View,
+});
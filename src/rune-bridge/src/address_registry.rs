@@ -0,0 +1,111 @@
+//! Enumerable registry of the EVM addresses whose deposit addresses the bridge controls.
+//!
+//! Deposit addresses are derived from the EVM addresses of the users, so the set of the bridge
+//! addresses cannot be listed from the master key alone. Every address the bridge may receive
+//! BTC to is given the next sequential index in the registry, which lets the admin rescan the
+//! addresses by index ranges, BIP-32 wallet style, when the ledger has to be rebuilt. Index 0
+//! belongs to the own address of the bridge, which holds the change of its transactions.
+
+use did::H160;
+use ic_stable_structures::stable_structures::Memory;
+use ic_stable_structures::{BTreeMapStructure, StableBTreeMap};
+
+/// Index of the own address of the bridge.
+pub const BRIDGE_ADDRESS_INDEX: u32 = 0;
+
+pub struct AddressRegistry<M: Memory> {
+    addresses: StableBTreeMap<u32, H160, M>,
+    indices: StableBTreeMap<H160, u32, M>,
+}
+
+impl<M: Memory> AddressRegistry<M> {
+    pub fn new(addresses_memory: M, indices_memory: M) -> Self {
+        let mut registry = Self {
+            addresses: StableBTreeMap::new(addresses_memory),
+            indices: StableBTreeMap::new(indices_memory),
+        };
+        registry.register(&H160::default());
+
+        registry
+    }
+
+    /// Returns the index of the `address`, giving it the next free index if it is not
+    /// registered yet.
+    pub fn register(&mut self, address: &H160) -> u32 {
+        if let Some(index) = self.indices.get(address) {
+            return index;
+        }
+
+        let index = self.len();
+        self.addresses.insert(index, address.clone());
+        self.indices.insert(address.clone(), index);
+
+        index
+    }
+
+    pub fn get(&self, index: u32) -> Option<H160> {
+        self.addresses.get(&index)
+    }
+
+    pub fn index_of(&self, address: &H160) -> Option<u32> {
+        self.indices.get(address)
+    }
+
+    /// Number of the registered addresses. The indices are `0..len`.
+    pub fn len(&self) -> u32 {
+        self.addresses.len() as u32
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.addresses.is_empty()
+    }
+
+    /// Registered addresses ordered by the index.
+    pub fn iter(&self) -> impl Iterator<Item = (u32, H160)> + '_ {
+        self.addresses.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ic_stable_structures::VectorMemory;
+
+    use super::*;
+
+    fn registry() -> AddressRegistry<VectorMemory> {
+        AddressRegistry::new(VectorMemory::default(), VectorMemory::default())
+    }
+
+    #[test]
+    fn addresses_get_sequential_indices() {
+        let mut registry = registry();
+        assert_eq!(registry.get(BRIDGE_ADDRESS_INDEX), Some(H160::default()));
+
+        let first = H160::from_slice(&[1; 20]);
+        let second = H160::from_slice(&[2; 20]);
+        assert_eq!(registry.register(&first), 1);
+        assert_eq!(registry.register(&second), 2);
+        assert_eq!(registry.register(&first), 1);
+
+        assert_eq!(registry.len(), 3);
+        assert_eq!(registry.index_of(&second), Some(2));
+        assert_eq!(registry.get(1), Some(first));
+        assert_eq!(registry.get(3), None);
+    }
+
+    #[test]
+    fn registry_is_restored_from_memory() {
+        let addresses_memory = VectorMemory::default();
+        let indices_memory = VectorMemory::default();
+        let address = H160::from_slice(&[1; 20]);
+
+        AddressRegistry::new(addresses_memory.clone(), indices_memory.clone()).register(&address);
+
+        let registry = AddressRegistry::new(addresses_memory, indices_memory);
+        assert_eq!(registry.index_of(&address), Some(1));
+        assert_eq!(
+            registry.iter().collect::<Vec<_>>(),
+            vec![(0, H160::default()), (1, address)]
+        );
+    }
+}
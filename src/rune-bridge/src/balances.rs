@@ -0,0 +1,173 @@
+//! Per-address balances of the runes bridged through the canister.
+//!
+//! The balances are updated when the deposits are completed and when the withdrawals are sent,
+//! so they don't need to be rebuilt from the operations history. The deposits and withdrawals
+//! completed before the balances were introduced are not counted.
+
+use std::borrow::Cow;
+
+use candid::{CandidType, Decode, Encode};
+use did::H160;
+use ic_stable_structures::stable_structures::Memory;
+use ic_stable_structures::{BTreeMapStructure, Bound, StableBTreeMap, Storable};
+use serde::Deserialize;
+
+use crate::rune_info::RuneName;
+
+/// Amount of a rune bridged by an address.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct BridgedBalance {
+    pub rune_name: RuneName,
+    /// Rune units deposited by the address and not withdrawn yet.
+    pub rune_amount: u128,
+    /// Wrapped token units minted for the deposits and not burnt yet.
+    pub wrapped_amount: u128,
+}
+
+#[derive(Debug, Default, Clone, CandidType, Deserialize)]
+struct AddressBalances(Vec<BridgedBalance>);
+
+impl Storable for AddressBalances {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("serialization failed"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).expect("deserialization failed")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+pub struct BridgedBalances<M: Memory> {
+    balances: StableBTreeMap<H160, AddressBalances, M>,
+}
+
+impl<M: Memory> BridgedBalances<M> {
+    pub fn new(memory: M) -> Self {
+        Self {
+            balances: StableBTreeMap::new(memory),
+        }
+    }
+
+    /// Balances of all the runes bridged by the address.
+    pub fn get(&self, address: &H160) -> Vec<BridgedBalance> {
+        self.balances.get(address).unwrap_or_default().0
+    }
+
+    /// Adds the deposited rune amount and the wrapped amount minted for it.
+    pub fn add_deposit(
+        &mut self,
+        address: &H160,
+        rune_name: RuneName,
+        rune_amount: u128,
+        wrapped_amount: u128,
+    ) {
+        self.update(address, rune_name, |balance| {
+            balance.rune_amount = balance.rune_amount.saturating_add(rune_amount);
+            balance.wrapped_amount = balance.wrapped_amount.saturating_add(wrapped_amount);
+        });
+    }
+
+    /// Subtracts the withdrawn rune amount and the burnt wrapped amount. The wrapped tokens may
+    /// be withdrawn by another address than the one they were minted to, so the balances are
+    /// never taken below zero.
+    pub fn sub_withdrawal(
+        &mut self,
+        address: &H160,
+        rune_name: RuneName,
+        rune_amount: u128,
+        wrapped_amount: u128,
+    ) {
+        self.update(address, rune_name, |balance| {
+            balance.rune_amount = balance.rune_amount.saturating_sub(rune_amount);
+            balance.wrapped_amount = balance.wrapped_amount.saturating_sub(wrapped_amount);
+        });
+    }
+
+    fn update(&mut self, address: &H160, rune_name: RuneName, f: impl FnOnce(&mut BridgedBalance)) {
+        let mut balances = self.balances.get(address).unwrap_or_default();
+        let index = match balances
+            .0
+            .iter()
+            .position(|balance| balance.rune_name == rune_name)
+        {
+            Some(index) => index,
+            None => {
+                balances.0.push(BridgedBalance {
+                    rune_name,
+                    rune_amount: 0,
+                    wrapped_amount: 0,
+                });
+                balances.0.len() - 1
+            }
+        };
+
+        f(&mut balances.0[index]);
+
+        let balance = &balances.0[index];
+        if balance.rune_amount == 0 && balance.wrapped_amount == 0 {
+            balances.0.remove(index);
+        }
+
+        if balances.0.is_empty() {
+            self.balances.remove(address);
+        } else {
+            self.balances.insert(address.clone(), balances);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use ic_stable_structures::VectorMemory;
+
+    use super::*;
+
+    fn rune(name: &str) -> RuneName {
+        RuneName::from_str(name).unwrap()
+    }
+
+    #[test]
+    fn deposits_and_withdrawals_are_accumulated() {
+        let mut balances = BridgedBalances::new(VectorMemory::default());
+        let address = H160::from_slice(&[1; 20]);
+
+        balances.add_deposit(&address, rune("FIRSTRUNE"), 100, 10_000);
+        balances.add_deposit(&address, rune("FIRSTRUNE"), 50, 5_000);
+        balances.add_deposit(&address, rune("SECONDRUNE"), 7, 7);
+        balances.sub_withdrawal(&address, rune("FIRSTRUNE"), 30, 3_000);
+
+        assert_eq!(
+            balances.get(&address),
+            vec![
+                BridgedBalance {
+                    rune_name: rune("FIRSTRUNE"),
+                    rune_amount: 120,
+                    wrapped_amount: 12_000,
+                },
+                BridgedBalance {
+                    rune_name: rune("SECONDRUNE"),
+                    rune_amount: 7,
+                    wrapped_amount: 7,
+                },
+            ]
+        );
+        assert!(balances.get(&H160::from_slice(&[2; 20])).is_empty());
+    }
+
+    #[test]
+    fn withdrawn_balances_are_removed() {
+        let mut balances = BridgedBalances::new(VectorMemory::default());
+        let address = H160::from_slice(&[1; 20]);
+
+        balances.add_deposit(&address, rune("FIRSTRUNE"), 100, 100);
+        balances.sub_withdrawal(&address, rune("FIRSTRUNE"), 150, 150);
+        assert!(balances.get(&address).is_empty());
+
+        balances.sub_withdrawal(&address, rune("SECONDRUNE"), 1, 1);
+        assert!(balances.get(&address).is_empty());
+    }
+}
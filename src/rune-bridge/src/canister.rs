@@ -1,41 +1,97 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 use std::str::FromStr;
+use std::time::Duration;
 
 use bitcoin::bip32::DerivationPath;
 use bitcoin::consensus::Encodable;
-use bitcoin::hashes::sha256d::Hash;
-use bitcoin::{Address, Amount, OutPoint, TxOut, Txid};
+use bitcoin::hashes::Hash;
+use bitcoin::{Amount, OutPoint, TxOut, Txid};
 use candid::Principal;
-use did::H160;
+use did::build::BuildData;
+use did::{H160, H256};
 use eth_signer::sign_strategy::TransactionSigner;
 use ic_canister::{generate_idl, init, post_upgrade, query, update, Canister, Idl, PreUpdate};
 use ic_exports::ic_cdk::api::management_canister::ecdsa::{
     ecdsa_public_key, EcdsaPublicKeyArgument,
 };
 use ic_exports::ic_kit::ic;
-use ic_exports::ledger::Subaccount;
 use ic_metrics::{Metrics, MetricsStorage};
-use ic_stable_structures::CellStructure;
+use ic_stable_structures::stable_structures::DefaultMemoryImpl;
+use ic_stable_structures::{BTreeMapStructure, CellStructure, VirtualMemory};
 use ic_task_scheduler::retry::BackoffPolicy;
 use ic_task_scheduler::scheduler::TaskScheduler;
 use ic_task_scheduler::task::{InnerScheduledTask, ScheduledTask, TaskOptions, TaskStatus};
+use ic_task_scheduler::SchedulerError;
+use minter_contract_utils::admin_approvals::{
+    ApprovalConfig, ApprovalError, ApprovalStore, Proposal,
+};
+use minter_contract_utils::bft_bridge_deploy::BftBridgeDeployStatus;
+use minter_contract_utils::bridge_tx_log::{BridgeTxLog, GetBridgeTransactionsResponse};
+use minter_contract_utils::btc_address::parse_btc_address;
+use minter_contract_utils::btc_confirmations::ConfirmationWatcher;
+use minter_contract_utils::burn_notification::{self, NotifiedEvents, NotifyBurnError};
+use minter_contract_utils::burn_permit::{BurnPermit, BurnPermitError};
+use minter_contract_utils::canister_status::{stable_structures_usage, CanisterStatusInfo};
+use minter_contract_utils::certified_data::{Certified, CONFIG_LABEL, RESERVES_LABEL};
+use minter_contract_utils::circuit_breaker::{CircuitBreakerConfig, CircuitBreakerStatus};
+use minter_contract_utils::config_guard::{ConfigGuard, Reconfiguration};
+use minter_contract_utils::config_validation::{format_config_errors, ConfigError};
+use minter_contract_utils::confirmation_policy::ConfirmationPolicy;
+use minter_contract_utils::gas_limits::GasLimits;
+use minter_contract_utils::health::HealthReport;
+use minter_contract_utils::long_poll::{self, TooManyWaiters};
+use minter_contract_utils::mint_completion::DepositStatus;
+use minter_contract_utils::operation_archive::{
+    ArchiveConfig, ArchivedOperations, OperationArchive,
+};
 use minter_contract_utils::operation_store::{MinterOperationId, MinterOperationStore};
+use minter_contract_utils::operation_trace::{self, TraceEntry};
+use minter_contract_utils::pagination::{
+    CursorPage, CursorRequest, InvalidCursor, Paged, Pagination,
+};
+use minter_contract_utils::soft_caps::{Lane, QueuePosition, SoftCap, SoftCapStore};
+use minter_contract_utils::task_limits::{is_deferral_error, TaskLimits, DEFERRED_TASK_DELAY_SECS};
+use minter_contract_utils::withdrawal_allowlist::{
+    AccountAllowlist, AllowlistAction, AllowlistError, WithdrawalAllowlist,
+};
 use ord_rs::wallet::{ScriptType, TxInputInfo};
 use ord_rs::OrdTransactionBuilder;
+use ordinals::RuneId;
 
-use crate::core::deposit::RuneDeposit;
+use crate::address_registry::AddressRegistry;
+use crate::balances::{BridgedBalance, BridgedBalances};
+use crate::core::deposit::{DepositGcStats, RuneDeposit};
 use crate::core::index_provider::{OrdIndexProvider, RuneIndexProvider};
+use crate::core::open_mint::OpenMint;
+use crate::core::rescan::{RescanReport, MAX_RESCAN_RANGE};
 use crate::core::utxo_provider::{IcUtxoProvider, UtxoProvider};
-use crate::interface::{CreateEdictTxArgs, GetAddressError, WithdrawError};
+use crate::core::withdrawal::Withdrawal;
+use crate::fee_priority::{FeePriorities, FeePriority};
+use crate::interface::{
+    AdminOperation, CancelDepositError, CreateEdictTxArgs, DepositError, DepositRequirements,
+    GetAddressError, OpenMintError, RuneIdDid, WithdrawError, WithdrawalPreview,
+};
+use crate::key::PublicKeyCache;
+use crate::ledger::Reserves;
 use crate::memory::{
-    MEMORY_MANAGER, OPERATIONS_LOG_MEMORY_ID, OPERATIONS_MAP_MEMORY_ID, OPERATIONS_MEMORY_ID,
-    PENDING_TASKS_MEMORY_ID,
+    ADDRESS_INDICES_LOOKUP_MEMORY_ID, ADDRESS_INDICES_MEMORY_ID, APPROVAL_PROPOSALS_MEMORY_ID,
+    APPROVAL_SETTINGS_MEMORY_ID, BRIDGED_BALANCES_MEMORY_ID, BRIDGE_TX_LOG_MEMORY_ID,
+    CONFIG_REVISION_MEMORY_ID, CONFIRMATION_WATCHER_MEMORY_ID, FEE_PRIORITIES_MEMORY_ID,
+    MEMORY_MANAGER, NOTIFIED_EVENTS_MEMORY_ID, OPERATIONS_LOG_MEMORY_ID, OPERATIONS_MAP_MEMORY_ID,
+    OPERATIONS_MEMORY_ID, OPERATION_ARCHIVE_MEMORY_ID, PENDING_TASKS_MEMORY_ID,
+    PUBLIC_KEY_CACHE_MEMORY_ID, RUNE_LIMITS_MEMORY_ID, SOFT_CAP_LANES_MEMORY_ID,
+    SOFT_CAP_QUEUE_MEMORY_ID, STABLE_STRUCTURES, TX_JOURNAL_MEMORY_ID,
+    WITHDRAWAL_ALLOWLIST_MEMORY_ID, WITHDRAWAL_ALLOWLIST_MODE_MEMORY_ID,
+    WITHDRAWAL_CREDITS_MEMORY_ID,
 };
 use crate::operation::{OperationState, RuneOperationStore};
-use crate::rune_info::RuneInfo;
-use crate::scheduler::{PersistentScheduler, RuneBridgeTask, TasksStorage};
-use crate::state::{BftBridgeConfig, RuneBridgeConfig, State};
+use crate::rune_info::{RuneInfo, RuneName};
+use crate::rune_limits::{RuneLimits, RuneLimitsStore};
+use crate::scheduler::{PersistentScheduler, RuneBridgeTask, RuneDepositRequestData, TasksStorage};
+use crate::state::{BftBridgeConfig, BridgeConfigInfo, RuneBridgeConfig, State};
+use crate::tx_journal::TxJournal;
+use crate::withdrawal_credits::{WithdrawalCredit, WithdrawalCredits};
 use crate::{
     EVM_INFO_INITIALIZATION_RETRIES, EVM_INFO_INITIALIZATION_RETRY_DELAY_SEC,
     EVM_INFO_INITIALIZATION_RETRY_MULTIPLIER,
@@ -53,7 +109,6 @@ impl RuneBridge {
     fn set_timers(&mut self) {
         #[cfg(target_family = "wasm")]
         {
-            use std::time::Duration;
             const METRICS_UPDATE_INTERVAL_SEC: u64 = 60 * 60;
 
             self.update_metrics_timer(std::time::Duration::from_secs(METRICS_UPDATE_INTERVAL_SEC));
@@ -62,9 +117,10 @@ impl RuneBridge {
             const USED_UTXOS_REMOVE_INTERVAL: Duration = Duration::from_secs(60 * 60 * 24); // once a day
 
             ic_exports::ic_cdk_timers::set_timer_interval(GLOBAL_TIMER_INTERVAL, move || {
-                get_scheduler()
-                    .borrow_mut()
-                    .append_task(Self::collect_evm_events_task());
+                RuneBridgeTask::CollectEvmEvents.append_unique(
+                    &*get_scheduler().borrow(),
+                    Self::collect_evm_events_options(),
+                );
 
                 let task_execution_result = get_scheduler().borrow_mut().run();
 
@@ -78,18 +134,77 @@ impl RuneBridge {
                     crate::task::RemoveUsedUtxosTask::from(get_state()).run(),
                 );
             });
+
+            ic_exports::ic_cdk_timers::set_timer_interval(
+                minter_contract_utils::health::HEALTH_CHECK_INTERVAL,
+                || {
+                    RuneBridgeTask::CheckHealth
+                        .append_unique(&*get_scheduler().borrow(), TaskOptions::default());
+                },
+            );
+
+            ic_exports::ic_cdk_timers::set_timer_interval(
+                minter_contract_utils::gas_price::GAS_PRICE_REFRESH_INTERVAL,
+                || {
+                    RuneBridgeTask::RefreshGasPrice
+                        .append_unique(&*get_scheduler().borrow(), TaskOptions::default());
+                },
+            );
+
+            ic_exports::ic_cdk_timers::set_timer_interval(
+                minter_contract_utils::btc_confirmations::CONFIRMATIONS_POLL_INTERVAL,
+                || {
+                    RuneBridgeTask::WatchConfirmations
+                        .append_unique(&*get_scheduler().borrow(), TaskOptions::default());
+                },
+            );
+
+            ic_exports::ic_cdk_timers::set_timer_interval(
+                minter_contract_utils::operation_archive::ARCHIVE_INTERVAL,
+                || {
+                    RuneBridgeTask::ArchiveOperations
+                        .append_unique(&*get_scheduler().borrow(), TaskOptions::default());
+                },
+            );
+
+            ic_exports::ic_cdk_timers::set_timer_interval(
+                minter_contract_utils::soft_caps::SOFT_CAP_DRAIN_INTERVAL,
+                || {
+                    RuneBridgeTask::DrainSoftCapQueue
+                        .append_unique(&*get_scheduler().borrow(), TaskOptions::default());
+                },
+            );
+
+            ic_exports::ic_cdk_timers::set_timer_interval(
+                crate::core::deposit::DEPOSIT_GC_INTERVAL,
+                || {
+                    RuneBridgeTask::AbandonExpiredDeposits
+                        .append_unique(&*get_scheduler().borrow(), TaskOptions::default());
+                },
+            );
         }
     }
 
+    /// Configures the bridge. Panics if the bridge is configured already, e.g. when it is
+    /// installed over the stable memory of a configured bridge, unless `force` is set.
     #[init]
-    pub fn init(&mut self, config: RuneBridgeConfig) {
-        get_state().borrow_mut().configure(config);
+    pub fn init(&mut self, config: RuneBridgeConfig, force: Option<bool>) {
+        let mut config_guard = get_config_guard();
+        if let Err(err) = config_guard.check_init(force.unwrap_or_default()) {
+            panic!("{err}");
+        }
+
+        if let Err(err) = get_state().borrow_mut().configure(config.clone()) {
+            panic!("{err}");
+        }
+        config_guard.record(&config);
 
         {
             let scheduler = get_scheduler();
             let mut borrowed_scheduler = scheduler.borrow_mut();
-            borrowed_scheduler.on_completion_callback(log_task_execution_error);
-            borrowed_scheduler.append_task(Self::init_evm_info_task());
+            borrowed_scheduler.on_completion_callback(on_task_completed);
+            RuneBridgeTask::InitEvmState
+                .append_unique(&*borrowed_scheduler, Self::init_evm_info_options());
         }
 
         self.set_timers();
@@ -97,6 +212,11 @@ impl RuneBridge {
 
     #[post_upgrade]
     pub fn post_upgrade(&mut self) {
+        get_scheduler()
+            .borrow_mut()
+            .on_completion_callback(on_task_completed);
+        get_state().borrow().certify();
+        get_bridge_tx_log().certify_tip();
         self.set_timers();
     }
 
@@ -113,14 +233,362 @@ impl RuneBridge {
         get_operations_store().get_for_address(&wallet_address)
     }
 
-    fn init_evm_info_task() -> ScheduledTask<RuneBridgeTask> {
-        let init_options = TaskOptions::default()
+    /// Same as `get_operations_list`, but returns only the requested page of the list.
+    #[query]
+    pub fn get_operations_page(
+        &self,
+        wallet_address: H160,
+        pagination: Option<Pagination>,
+    ) -> Paged<(MinterOperationId, OperationState)> {
+        pagination
+            .unwrap_or_default()
+            .paginate(get_operations_store().get_for_address(&wallet_address))
+    }
+
+    /// Same as `get_operations_page`, but pages the list with a cursor instead of an offset, so
+    /// the query reads only the operations of the requested page.
+    #[query]
+    pub fn get_operations_by_cursor(
+        &self,
+        wallet_address: H160,
+        request: Option<CursorRequest>,
+    ) -> Result<CursorPage<(MinterOperationId, OperationState)>, InvalidCursor> {
+        get_operations_store().page_for_address(
+            &wallet_address,
+            &request.unwrap_or_default(),
+            |id, payload| Some((id, payload)),
+        )
+    }
+
+    /// Returns the archive canister and the ids of the operations of the `wallet_address` moved
+    /// to it, or `None` if no operations of the wallet are archived. The operations are queried
+    /// from the archive with its `get_operations` method.
+    #[query]
+    pub fn get_archived_operations(&self, wallet_address: H160) -> Option<ArchivedOperations> {
+        get_operation_archive()
+            .archived(get_operations_store().archived_ids_for_address(&wallet_address))
+    }
+
+    /// Sets the archive canister the completed operations beyond the `keep_latest` latest ones
+    /// are pushed to, or stops the archiving if `config` is `None`.
+    ///
+    /// With the approvals enabled, the archive is changed with an approved proposal.
+    #[update]
+    pub fn admin_set_operation_archive(
+        &self,
+        config: Option<ArchiveConfig>,
+    ) -> minter_did::error::Result<()> {
+        check_direct_admin(ic::caller())?;
+        set_operation_archive(config)
+    }
+
+    #[query]
+    pub fn get_operation_archive_config(&self) -> Option<ArchiveConfig> {
+        get_operation_archive().config()
+    }
+
+    /// Replaces the configuration of the bridge. The `nonce` is the one returned by
+    /// `get_config_nonce`, so a change based on an outdated configuration is rejected. Retrying
+    /// an applied change with the same nonce and configuration succeeds without changes.
+    ///
+    /// Returns the nonce of the next reconfiguration. The signing strategy and the log settings
+    /// of the `config` are ignored.
+    ///
+    /// With the approvals enabled, the configuration is changed with an approved proposal.
+    #[update]
+    pub fn admin_reconfigure(
+        &self,
+        config: RuneBridgeConfig,
+        nonce: u64,
+    ) -> minter_did::error::Result<u64> {
+        check_direct_admin(ic::caller())?;
+        reconfigure(config, nonce)
+    }
+
+    /// Sets the approvers of the dangerous operations, or disables the approvals if `config` is
+    /// `None`. Once the approvals are enabled, the operations of [`AdminOperation`], e.g. the
+    /// reconfiguration, the circuit breaker changes and the change of the approvers, are applied
+    /// only with the approved proposals.
+    #[update]
+    pub fn admin_set_approval_config(
+        &self,
+        config: Option<ApprovalConfig>,
+    ) -> minter_did::error::Result<()> {
+        check_direct_admin(ic::caller())?;
+        get_admin_approvals()
+            .configure(config)
+            .map_err(approval_error)
+    }
+
+    #[query]
+    pub fn get_approval_config(&self) -> Option<ApprovalConfig> {
+        get_admin_approvals().config()
+    }
+
+    /// Proposes the dangerous `operation`, approved by the caller. Returns the id of the
+    /// proposal.
+    #[update]
+    pub fn admin_propose_operation(
+        &self,
+        operation: AdminOperation,
+    ) -> minter_did::error::Result<u64> {
+        get_admin_approvals()
+            .propose(ic::caller(), operation, get_state().borrow().clock().now())
+            .map_err(approval_error)
+    }
+
+    /// Approves the proposal. Returns the number of its approvals.
+    #[update]
+    pub fn admin_approve_operation(&self, proposal_id: u64) -> minter_did::error::Result<u32> {
+        get_admin_approvals()
+            .approve(
+                ic::caller(),
+                proposal_id,
+                get_state().borrow().clock().now(),
+            )
+            .map_err(approval_error)
+    }
+
+    /// Applies the operation of the proposal if it has the approvals of the threshold number of
+    /// the approvers.
+    #[update]
+    pub async fn admin_execute_operation(&self, proposal_id: u64) -> minter_did::error::Result<()> {
+        let operation = get_admin_approvals()
+            .approved_operation(
+                ic::caller(),
+                proposal_id,
+                get_state().borrow().clock().now(),
+            )
+            .map_err(approval_error)?;
+        get_admin_approvals().mark_executed(proposal_id, get_state().borrow().clock().now());
+        let result = apply_admin_operation(operation).await;
+        if result.is_err() {
+            get_admin_approvals().reopen(proposal_id);
+        }
+        result
+    }
+
+    /// Returns the proposals which are not executed and not expired. The proposed configurations
+    /// carry the signing strategy, so the proposals are returned to the approvers only.
+    #[query]
+    pub fn get_operation_proposals(
+        &self,
+    ) -> minter_did::error::Result<Vec<Proposal<AdminOperation>>> {
+        let approvals = get_admin_approvals();
+        if !approvals
+            .config()
+            .is_some_and(|config| config.is_approver(&ic::caller()))
+        {
+            return Err(minter_did::error::Error::NotAuthorized);
+        }
+
+        Ok(approvals.open_proposals(get_state().borrow().clock().now()))
+    }
+
+    /// Nonce the next `admin_reconfigure` call must be made with.
+    #[query]
+    pub fn get_config_nonce(&self) -> u64 {
+        get_config_guard().nonce()
+    }
+
+    /// Sets the number of the deposits or withdrawals processed per hour. The operations beyond
+    /// it are queued instead of rejected. `None` removes the cap, and the queued operations are
+    /// processed with the next drain of the queue.
+    #[update]
+    pub fn admin_set_soft_cap(
+        &self,
+        lane: Lane,
+        cap_per_hour: Option<u128>,
+    ) -> minter_did::error::Result<()> {
+        check_direct_admin(ic::caller())?;
+        set_soft_cap(lane, cap_per_hour)
+    }
+
+    #[query]
+    pub fn get_soft_caps(&self) -> Vec<SoftCap> {
+        get_soft_cap_store().caps(get_state().borrow().clock().now())
+    }
+
+    /// Returns the position of the queued deposit or withdrawal in the soft cap queue and its
+    /// estimated processing time, or `None` if the operation is not queued.
+    #[query]
+    pub fn get_queue_position(&self, operation_id: MinterOperationId) -> Option<QueuePosition> {
+        get_soft_cap_store().position(
+            &operation_id.to_string(),
+            get_state().borrow().clock().now(),
+        )
+    }
+
+    /// Starts a deposit of the runes sent to the deposit address of `request.dst_address`, like
+    /// the `DEPOSIT_TYPE` notification sent through the BftBridge, and returns the operation id
+    /// of the deposit. The deposits of an address announcing their funding transactions are
+    /// processed independently of each other.
+    #[update]
+    pub fn request_deposit(
+        &mut self,
+        request: RuneDepositRequestData,
+    ) -> Result<MinterOperationId, DepositError> {
+        get_state()
+            .borrow()
+            .recipient_chain_id(request.dst_chain_id)?;
+
+        let operation_id = RuneDeposit::get().create_deposit_request(
+            request.dst_address,
+            request.amounts,
+            request.refund_address,
+            request.dst_chain_id,
+            request.funding_txids,
+        );
+        get_scheduler()
+            .borrow_mut()
+            .append_task(RuneBridgeTask::Deposit(operation_id).into_scheduled(TaskOptions::new()));
+
+        Ok(operation_id)
+    }
+
+    /// Returns the progress of the deposit operation, or `None` if there is no deposit with the
+    /// given id. The deposit is `Completed` once the `Minted` events of all its mint orders are
+    /// collected from the EVM.
+    #[query]
+    pub fn get_deposit_status(&self, operation_id: MinterOperationId) -> Option<DepositStatus> {
+        match get_operations_store().get(operation_id)? {
+            OperationState::Deposit(payload) => Some(payload.deposit_status()),
+            OperationState::Withdrawal(_) => None,
+        }
+    }
+
+    /// Same as `get_deposit_status`, but waits until the status changes, or until `timeout_secs`
+    /// pass. The timeout is capped at 30 seconds, and the call is rejected if too many calls are
+    /// waiting already.
+    #[update]
+    pub async fn await_operation(
+        &self,
+        operation_id: MinterOperationId,
+        timeout_secs: u64,
+    ) -> Result<Option<DepositStatus>, TooManyWaiters> {
+        long_poll::await_change(timeout_secs, || self.get_deposit_status(operation_id)).await
+    }
+
+    /// Returns the entries recorded for the operation, e.g. its status changes and the task
+    /// failures, oldest first. The traces are kept for the latest operations since the last
+    /// upgrade.
+    #[query]
+    pub fn get_operation_trace(&self, operation_id: MinterOperationId) -> Vec<TraceEntry> {
+        operation_trace::get_operation_trace(operation_id)
+    }
+
+    /// Returns up to `length` entries of the log of the wrapped rune mints and burns, starting
+    /// from the `start` entry. The hash of the last entry is certified, so the query response
+    /// can be verified with the returned certificate.
+    #[query]
+    pub fn get_bridge_transactions(
+        &self,
+        start: u64,
+        length: u64,
+    ) -> GetBridgeTransactionsResponse {
+        get_bridge_tx_log().get_transactions(start, length)
+    }
+
+    /// Returns the amounts of the runes deposited by the `eth_address` and not withdrawn yet,
+    /// with the amounts of the wrapped tokens minted for them.
+    #[query]
+    pub fn get_bridged_balance(&self, eth_address: H160) -> Vec<BridgedBalance> {
+        get_bridged_balances().get(&eth_address)
+    }
+
+    /// Returns the wrapped token units burnt by the `eth_address` which could not be withdrawn as
+    /// whole rune units. They are withdrawn with the next burn of the rune by the address.
+    #[query]
+    pub fn get_withdrawal_credits(&self, eth_address: H160) -> Vec<WithdrawalCredit> {
+        get_withdrawal_credits().get_all(&eth_address)
+    }
+
+    /// Returns the BTC held by the bridge. The response is certified, see `certified_data`.
+    #[query]
+    pub fn get_reserves(&self) -> Certified<Reserves> {
+        Certified::new(RESERVES_LABEL, get_state().borrow().ledger().reserves())
+    }
+
+    /// Returns the configuration of the bridge. The response is certified, see `certified_data`.
+    #[query]
+    pub fn get_bridge_config(&self) -> Certified<BridgeConfigInfo> {
+        Certified::new(CONFIG_LABEL, get_state().borrow().config_info())
+    }
+
+    /// Returns the page of the runes known to the bridge, ordered by the rune name.
+    #[query]
+    pub fn get_runes(&self, pagination: Option<Pagination>) -> Paged<RuneInfo> {
+        let state = get_state();
+        let state = state.borrow();
+        let mut runes: Vec<RuneInfo> = state.runes().values().copied().collect();
+        runes.sort_by_key(|rune_info| rune_info.name());
+
+        pagination.unwrap_or_default().paginate(runes)
+    }
+
+    /// Returns the number of BTC confirmations required for deposits depending on their size.
+    #[query]
+    pub fn get_confirmation_policy(&self) -> ConfirmationPolicy {
+        get_state().borrow().confirmation_policy()
+    }
+
+    /// Returns the minimum BTC amount, confirmations and the smallest rune amount required for a
+    /// deposit of the `rune_name`, and the estimated time until the deposit is indexed.
+    #[query]
+    pub fn get_deposit_requirements(&self, rune_name: String) -> DepositRequirements {
+        let rune_name = RuneName::from_str(&rune_name).ok();
+        let mut requirements = get_state().borrow().deposit_requirements(rune_name);
+        if let Some(min_deposit_amount) =
+            rune_name.and_then(|name| get_rune_limits_store().get(name).min_deposit_amount)
+        {
+            requirements.min_rune_amount = requirements
+                .min_rune_amount
+                .map(|amount| amount.max(min_deposit_amount));
+        }
+
+        requirements
+    }
+
+    /// Returns the results of the latest checks of the EVM RPC, `ord` indexer and signer. The
+    /// dependencies are reported as `Unknown` until the first periodic check after the canister
+    /// installation or upgrade.
+    #[query]
+    pub fn health(&self) -> HealthReport {
+        get_state().borrow().health().report()
+    }
+
+    /// Returns the cycles balance, the heap and stable memory usage and the number of the
+    /// scheduled tasks of the canister.
+    #[query]
+    pub fn canister_status_info(&self) -> CanisterStatusInfo {
+        MEMORY_MANAGER.with(|mm| {
+            let stable_structures = stable_structures_usage(STABLE_STRUCTURES, |id| mm.get(id));
+            let scheduled_tasks = TasksStorage::new(mm.get(PENDING_TASKS_MEMORY_ID)).len();
+            CanisterStatusInfo::collect(stable_structures, scheduled_tasks)
+        })
+    }
+
+    /// Returns the version, the git commit, the build timestamp and the enabled features of the
+    /// canister build.
+    #[query]
+    pub fn get_build_data(&self) -> BuildData {
+        minter_contract_utils::canister_build_data!()
+    }
+
+    /// Checks the configuration with the same rules as the canister init, returning all the
+    /// errors found. The list is empty if the configuration is valid.
+    #[query]
+    pub fn validate_config(&self, config: RuneBridgeConfig) -> Vec<ConfigError> {
+        config.validate().err().unwrap_or_default()
+    }
+
+    fn init_evm_info_options() -> TaskOptions {
+        TaskOptions::default()
             .with_max_retries_policy(EVM_INFO_INITIALIZATION_RETRIES)
             .with_backoff_policy(BackoffPolicy::Exponential {
                 secs: EVM_INFO_INITIALIZATION_RETRY_DELAY_SEC,
                 multiplier: EVM_INFO_INITIALIZATION_RETRY_MULTIPLIER,
-            });
-        RuneBridgeTask::InitEvmState.into_scheduled(init_options)
+            })
     }
 
     /// Returns EVM address of the canister.
@@ -136,116 +604,572 @@ impl RuneBridge {
         }
     }
 
+    /// Burns the wrapped rune tokens approved with an ERC-2612 `permit` of their holder, so the
+    /// holder doesn't need to send the approve and burn transactions. The `recipient_id` of the
+    /// permit is the BTC address the runes are withdrawn to, bound to the burn by the holder's
+    /// signature over the `burnPermitDigest` of the BftBridge.
+    #[update]
+    pub async fn burn_with_permit(&mut self, permit: BurnPermit) -> Result<H256, BurnPermitError> {
+        crate::core::burn_permit::burn_with_permit(&get_state(), permit).await
+    }
+
+    /// Schedules the withdrawals of the `Burnt` events of the transaction without waiting for the
+    /// next scan of the BftBridge logs. The events collected by the scan already are rejected
+    /// as `AlreadyProcessed`.
     #[update]
-    pub async fn admin_configure_ecdsa(&self) {
-        get_state().borrow().check_admin(ic::caller());
-        let key_id = get_state().borrow().ecdsa_key_id();
+    pub async fn notify_burn(&mut self, tx_hash: H256) -> Result<(), NotifyBurnError> {
+        let evm_info = get_state().borrow().get_evm_info();
+        if evm_info.params.is_none() {
+            return Err(NotifyBurnError::NotInitialized);
+        }
 
-        let master_key = ecdsa_public_key(EcdsaPublicKeyArgument {
-            canister_id: None,
-            derivation_path: vec![],
-            key_id,
-        })
-        .await
-        .expect("failed to get master key");
+        let client = evm_info.link.get_json_rpc_client();
+        let logs =
+            burn_notification::burnt_logs(&client, &tx_hash, &evm_info.bridge_contract).await?;
+
+        // The scan may have passed the blocks of the logs while the receipt was fetched.
+        let next_block = get_state()
+            .borrow()
+            .get_evm_params()
+            .as_ref()
+            .map(|params| params.next_block)
+            .ok_or(NotifyBurnError::NotInitialized)?;
+        let logs =
+            get_notified_events().accept(logs, next_block, get_state().borrow().clock().now())?;
+
+        let state = get_state();
+        let tasks = logs
+            .into_iter()
+            .filter_map(|log| RuneBridgeTask::task_by_log(log, &state))
+            .collect();
+        get_scheduler().borrow_mut().append_tasks(tasks);
+
+        Ok(())
+    }
+
+    /// Sets the master key of the configured ECDSA key id. The key is fetched from the management
+    /// canister once per key id and then taken from the stable memory cache.
+    #[update]
+    pub async fn admin_configure_ecdsa(&self) -> minter_did::error::Result<()> {
+        check_direct_admin(ic::caller())?;
+        configure_ecdsa().await
+    }
+
+    /// Dry-runs the configuration of the bridge: checks it with the init rules and against the
+    /// EVM of its link without applying it. Returns all the errors found, or an empty list.
+    ///
+    /// The EVM checks need outcalls, so this is an update, but it doesn't change the state.
+    #[update]
+    pub async fn admin_validate_bridge_config(
+        &self,
+        config: RuneBridgeConfig,
+    ) -> minter_did::error::Result<Vec<ConfigError>> {
+        get_state().borrow().check_admin(ic::caller())?;
+        let bft_config = get_state().borrow().bft_config().clone();
+        Ok(config
+            .validate_with_evm(&bft_config)
+            .await
+            .err()
+            .unwrap_or_default())
+    }
+
+    /// Dry-runs `admin_configure_bft_bridge`: checks the chain id and the contracts of the
+    /// configuration against the EVM of the bridge without applying it. Returns all the errors
+    /// found, or an empty list.
+    #[update]
+    pub async fn admin_validate_bft_config(
+        &self,
+        config: BftBridgeConfig,
+    ) -> minter_did::error::Result<Vec<ConfigError>> {
+        get_state().borrow().check_admin(ic::caller())?;
+        Ok(validate_bft_config(&config).await.err().unwrap_or_default())
+    }
+
+    /// Sets the BftBridge configuration after checking it against the EVM: the contract must be
+    /// deployed and mint with the EVM address of the canister.
+    #[update]
+    pub async fn admin_configure_bft_bridge(
+        &self,
+        config: BftBridgeConfig,
+    ) -> minter_did::error::Result<()> {
+        check_direct_admin(ic::caller())?;
+        configure_bft_bridge(config).await
+    }
+
+    /// Deploys the BftBridge contract with the EVM address of the canister as the minter. The
+    /// contract is configured as the bridge contract once `admin_check_bft_bridge_deployment`
+    /// finds it deployed.
+    #[update]
+    pub async fn admin_deploy_bft_bridge(
+        &mut self,
+        fee_charge: H160,
+    ) -> minter_did::error::Result<BftBridgeDeployStatus> {
+        check_direct_admin(ic::caller())?;
+        crate::core::bft_deploy::deploy_bft_bridge(&get_state(), fee_charge).await
+    }
+
+    #[update]
+    pub async fn admin_check_bft_bridge_deployment(
+        &mut self,
+    ) -> minter_did::error::Result<BftBridgeDeployStatus> {
+        get_state().borrow().check_admin(ic::caller())?;
+        crate::core::bft_deploy::check_bft_bridge_deployment(&get_state()).await
+    }
+
+    #[query]
+    pub fn get_bft_bridge_deploy_status(&self) -> Option<BftBridgeDeployStatus> {
+        get_state().borrow().bft_deploy_status().cloned()
+    }
+
+    /// Sets priorities and concurrency limits of the canister tasks. Task types are named after
+    /// the `RuneBridgeTask` variants, e.g. `Deposit`.
+    #[update]
+    pub fn admin_set_task_limits(&self, limits: TaskLimits) -> minter_did::error::Result<()> {
+        check_direct_admin(ic::caller())?;
+        get_state().borrow_mut().configure_task_limits(limits)
+    }
+
+    #[query]
+    pub fn get_task_limits(&self) -> TaskLimits {
+        get_state().borrow().task_limiter().limits().clone()
+    }
+
+    /// Sets gas limits of the EVM transactions by the operation. The limit of an operation is
+    /// bumped automatically when its transaction runs out of gas.
+    #[update]
+    pub fn admin_set_gas_limits(&self, limits: GasLimits) -> minter_did::error::Result<()> {
+        check_direct_admin(ic::caller())?;
+        get_state().borrow_mut().configure_gas_limits(limits)
+    }
+
+    #[query]
+    pub fn get_gas_limits(&self) -> GasLimits {
+        get_state().borrow().gas_limits().clone()
+    }
+
+    /// Sets the error rate thresholds of the indexer and EVM RPC calls at which the deposits are
+    /// paused, and the cool-down after which they are resumed.
+    ///
+    /// With the approvals enabled, the circuit breaker is changed with an approved proposal.
+    #[update]
+    pub fn admin_set_circuit_breaker_config(
+        &self,
+        config: CircuitBreakerConfig,
+    ) -> minter_did::error::Result<()> {
+        check_direct_admin(ic::caller())?;
+        get_state().borrow_mut().configure_circuit_breaker(config)
+    }
+
+    /// Returns whether the deposits are paused by the circuit breaker, and the outcomes of the
+    /// dependency calls in its current window.
+    #[query]
+    pub fn get_circuit_breaker_status(&self) -> CircuitBreakerStatus {
+        let state = get_state();
+        let state = state.borrow();
+        state.circuit_breaker().status(state.clock().now())
+    }
 
-        get_state().borrow_mut().configure_ecdsa(master_key.0);
+    /// Caps the rune amount held by the bridge at the `supply_cap_bps` basis points of the
+    /// circulating supply of the rune, or removes the cap if it is `None`. The deposits which
+    /// would exceed the cap wait until the bridged amount or the supply changes.
+    #[update]
+    pub fn admin_set_rune_supply_cap(
+        &self,
+        rune_name: String,
+        supply_cap_bps: Option<u32>,
+    ) -> minter_did::error::Result<()> {
+        check_direct_admin(ic::caller())?;
+        set_rune_supply_cap(&rune_name, supply_cap_bps)
+    }
+
+    /// Sets the minimum amounts in rune units of the deposits and withdrawals of the rune. `None`
+    /// removes the minimum. The deposits below the minimum wait for more runes at the deposit
+    /// address, and the burns below the minimum are not withdrawn.
+    #[update]
+    pub fn admin_set_rune_min_amounts(
+        &self,
+        rune_name: String,
+        min_deposit_amount: Option<u128>,
+        min_withdrawal_amount: Option<u128>,
+    ) -> minter_did::error::Result<()> {
+        check_direct_admin(ic::caller())?;
+        set_rune_min_amounts(&rune_name, min_deposit_amount, min_withdrawal_amount)
+    }
+
+    /// Returns the supply caps, minimum amounts and bridged amounts of the runes which are limited
+    /// or bridged.
+    #[query]
+    pub fn get_rune_limits(&self) -> Vec<RuneLimits> {
+        get_rune_limits_store().list()
+    }
+
+    /// Sets the admin screening decision for a funding transaction id or a hex encoded EVM
+    /// recipient address. The decision takes priority over the screening provider verdict.
+    #[update]
+    pub fn admin_set_screening_override(
+        &self,
+        subject: String,
+        allowed: bool,
+    ) -> minter_did::error::Result<()> {
+        check_direct_admin(ic::caller())?;
+        get_state()
+            .borrow_mut()
+            .screening_overrides_mut()
+            .set(&subject, allowed);
+        Ok(())
+    }
+
+    #[update]
+    pub fn admin_remove_screening_override(
+        &self,
+        subject: String,
+    ) -> minter_did::error::Result<()> {
+        check_direct_admin(ic::caller())?;
+        get_state()
+            .borrow_mut()
+            .screening_overrides_mut()
+            .remove(&subject);
+        Ok(())
+    }
+
+    /// Cancels the deposit operation which has no signed mint orders yet, e.g. a deposit stuck
+    /// waiting for confirmations. The recipients of the deposits cancel them with the
+    /// `CANCEL_DEPOSIT_TYPE` notification sent through the BftBridge.
+    ///
+    /// With the approvals enabled, the deposit is cancelled with an approved proposal, and the
+    /// call returns `NotAuthorized`.
+    #[update]
+    pub fn admin_cancel_deposit(
+        &self,
+        operation_id: MinterOperationId,
+    ) -> Result<(), CancelDepositError> {
+        check_direct_admin(ic::caller()).map_err(|_| CancelDepositError::NotAuthorized)?;
+        RuneDeposit::get().cancel_deposit(operation_id, None)
+    }
+
+    /// Returns the admin screening decisions.
+    #[query]
+    pub fn get_screening_overrides(&self) -> Vec<(String, bool)> {
+        get_state().borrow().screening_overrides().get_all()
+    }
+
+    /// Schedules a rescan of the registered deposit addresses with the indices in
+    /// `from_index..to_index`. The bridge utxos missing from the ledger are added to it, and the
+    /// utxos at the addresses of the users are listed in the report returned by
+    /// `get_last_rescan_report`.
+    #[update]
+    pub fn admin_rescan_addresses(
+        &self,
+        from_index: u32,
+        to_index: u32,
+    ) -> minter_did::error::Result<()> {
+        get_state().borrow().check_admin(ic::caller())?;
+
+        if from_index >= to_index || to_index - from_index > MAX_RESCAN_RANGE {
+            return Err(minter_did::error::Error::Internal(format!(
+                "invalid rescan range {from_index}..{to_index}, the range must be non-empty and contain at most {MAX_RESCAN_RANGE} addresses"
+            )));
+        }
+
+        get_scheduler().borrow_mut().append_task(
+            RuneBridgeTask::RescanAddresses {
+                from_index,
+                to_index,
+            }
+            .into_scheduled(TaskOptions::default()),
+        );
+        Ok(())
+    }
+
+    /// Fee priority of the withdrawals of the `eth_address`, set with the `FEE_PRIORITY_TYPE`
+    /// notification.
+    #[query]
+    pub fn get_fee_priority(&self, eth_address: H160) -> FeePriority {
+        get_fee_priorities().get(&eth_address).unwrap_or_default()
+    }
+
+    /// Enables or disables the withdrawal allowlist mode. In the mode the runes are withdrawn
+    /// only to the addresses registered by the burner with `register_withdrawal_address`.
+    #[update]
+    pub fn admin_set_withdrawal_allowlist_mode(
+        &mut self,
+        enabled: bool,
+    ) -> minter_did::error::Result<()> {
+        check_direct_admin(ic::caller())?;
+        set_withdrawal_allowlist_mode(enabled);
+        Ok(())
+    }
+
+    #[query]
+    pub fn is_withdrawal_allowlist_enabled(&self) -> bool {
+        get_withdrawal_allowlist().is_enabled()
+    }
+
+    /// Withdrawal addresses of the `eth_address` and the nonce of its next change message.
+    #[query]
+    pub fn get_withdrawal_allowlist(&self, eth_address: H160) -> AccountAllowlist {
+        get_withdrawal_allowlist().get(&eth_address)
+    }
+
+    /// Message the `eth_address` signs with `personal_sign` to apply the `action` to the
+    /// `btc_address`.
+    #[query]
+    pub fn get_withdrawal_allowlist_message(
+        &self,
+        eth_address: H160,
+        btc_address: String,
+        action: AllowlistAction,
+    ) -> String {
+        let btc_address = Self::normalize_withdrawal_address(btc_address);
+        get_withdrawal_allowlist().change_message(
+            &ic::id().to_text(),
+            &eth_address,
+            &btc_address,
+            action,
+        )
+    }
+
+    /// Registers the `btc_address` as a withdrawal address of the `eth_address`. The `signature`
+    /// is the `personal_sign` signature of the `get_withdrawal_allowlist_message` by the
+    /// `eth_address`.
+    #[update]
+    pub fn register_withdrawal_address(
+        &mut self,
+        eth_address: H160,
+        btc_address: String,
+        signature: Vec<u8>,
+    ) -> Result<(), AllowlistError> {
+        Self::change_withdrawal_allowlist(
+            eth_address,
+            btc_address,
+            AllowlistAction::Register,
+            signature,
+        )
     }
 
+    /// Removes the `btc_address` from the withdrawal addresses of the `eth_address`, with the
+    /// signature as for `register_withdrawal_address`.
     #[update]
-    pub fn admin_configure_bft_bridge(&self, config: BftBridgeConfig) {
-        get_state().borrow().check_admin(ic::caller());
-        get_state().borrow_mut().configure_bft(config);
+    pub fn unregister_withdrawal_address(
+        &mut self,
+        eth_address: H160,
+        btc_address: String,
+        signature: Vec<u8>,
+    ) -> Result<(), AllowlistError> {
+        Self::change_withdrawal_allowlist(
+            eth_address,
+            btc_address,
+            AllowlistAction::Unregister,
+            signature,
+        )
+    }
+
+    fn change_withdrawal_allowlist(
+        eth_address: H160,
+        btc_address: String,
+        action: AllowlistAction,
+        signature: Vec<u8>,
+    ) -> Result<(), AllowlistError> {
+        let network = get_state().borrow().network();
+        if action == AllowlistAction::Register {
+            parse_btc_address(&btc_address, network)
+                .map_err(|err| AllowlistError::InvalidAddress(format!("{err:?}")))?;
+        }
+
+        let btc_address = Self::normalize_withdrawal_address(btc_address);
+        get_withdrawal_allowlist().apply(
+            &ic::id().to_text(),
+            &eth_address,
+            &btc_address,
+            action,
+            &signature,
+        )
+    }
+
+    /// Withdrawal addresses are compared in their canonical form, e.g. bech32 in lowercase. An
+    /// address which cannot be parsed is taken as is.
+    fn normalize_withdrawal_address(btc_address: String) -> String {
+        let network = get_state().borrow().network();
+        parse_btc_address(&btc_address, network)
+            .map(|address| address.to_string())
+            .unwrap_or(btc_address)
+    }
+
+    #[query]
+    pub fn get_last_rescan_report(&self) -> Option<RescanReport> {
+        get_state().borrow().last_rescan().cloned()
+    }
+
+    /// Returns the numbers of the abandoned and pending deposits counted by the periodic checks
+    /// of the deposit expiry. The counters are reset by upgrades.
+    #[query]
+    pub fn get_deposit_gc_stats(&self) -> DepositGcStats {
+        get_state().borrow().deposit_gc_stats().clone()
+    }
+
+    /// Index of the deposit address of the `eth_address` in the address registry.
+    #[query]
+    pub fn get_address_index(&self, eth_address: H160) -> Option<u32> {
+        get_address_registry().index_of(&eth_address)
     }
 
     #[cfg(target_family = "wasm")]
-    fn collect_evm_events_task() -> ScheduledTask<RuneBridgeTask> {
+    fn collect_evm_events_options() -> TaskOptions {
         const EVM_EVENTS_COLLECTING_DELAY: u32 = 1;
 
-        let options = TaskOptions::default()
+        TaskOptions::default()
             .with_retry_policy(ic_task_scheduler::retry::RetryPolicy::Infinite)
             .with_backoff_policy(BackoffPolicy::Fixed {
                 secs: EVM_EVENTS_COLLECTING_DELAY,
-            });
-
-        RuneBridgeTask::CollectEvmEvents.into_scheduled(options)
+            })
     }
 
+    /// Same as `get_rune_balances_checked`, but traps on errors. Kept for the clients of the
+    /// original interface.
     #[update]
     pub async fn get_rune_balances(&self, btc_address: String) -> Vec<(RuneInfo, u128)> {
-        let address = Address::from_str(&btc_address)
-            .expect("invalid address")
-            .assume_checked();
+        self.get_rune_balances_checked(btc_address)
+            .await
+            .expect("failed to get rune balances")
+    }
+
+    #[update]
+    pub async fn get_rune_balances_checked(
+        &self,
+        btc_address: String,
+    ) -> Result<Vec<(RuneInfo, u128)>, DepositError> {
+        let network = get_state().borrow().network();
+        let address =
+            parse_btc_address(&btc_address, network).map_err(DepositError::InvalidAddress)?;
 
         let deposit = RuneDeposit::get();
-        let utxos = deposit
-            .get_deposit_utxos(&address)
-            .await
-            .expect("failed to get utxos");
-        let (rune_info_amounts, _) = deposit
-            .get_mint_amounts(&utxos.utxos, &None)
-            .await
-            .expect("failed to get rune amounts");
+        let utxos = deposit.get_deposit_utxos(&address).await?;
+        deposit.check_indexer_height(utxos.tip_height).await?;
+        let (rune_info_amounts, _) = deposit.get_mint_amounts(&utxos.utxos, &None).await?;
 
-        rune_info_amounts
+        Ok(rune_info_amounts)
     }
 
+    /// Mints the rune with open mint terms using the BTC sent to the deposit address of the
+    /// `eth_address`, and wraps the minted runes to the `eth_address` after the mint transaction is
+    /// confirmed. The runes are wrapped on the EVM with the `dst_chain_id` if it is given and
+    /// allowed by the configuration. Returns the id of the deposit operation created for the minted
+    /// runes.
+    ///
+    /// The mint spends the BTC of the user, so only the admin may call it. The users request the
+    /// mint with the `OPEN_MINT_TYPE` notification sent through the BftBridge from the
+    /// `eth_address`. With the approvals enabled, the mint is made with an approved proposal, and
+    /// the call returns `NotAuthorized`.
+    #[update]
+    pub async fn mint_and_bridge_runes(
+        &self,
+        eth_address: H160,
+        rune_id: RuneIdDid,
+        dst_chain_id: Option<u32>,
+    ) -> Result<MinterOperationId, OpenMintError> {
+        check_direct_admin(ic::caller()).map_err(|_| OpenMintError::NotAuthorized)?;
+        mint_and_bridge_runes(eth_address, rune_id, dst_chain_id).await
+    }
+
+    /// Builds a withdrawal transaction for the given parameters without signing or sending it.
+    /// Returns the transaction inputs, outputs, fee and estimated confirmation time. The fee rate
+    /// is the one of the `fee_priority`, or of the standard priority if it is not given.
+    #[update]
+    pub async fn preview_withdraw(
+        &self,
+        amount: u128,
+        rune_id: RuneIdDid,
+        address: String,
+        fee_priority: Option<FeePriority>,
+    ) -> Result<WithdrawalPreview, WithdrawError> {
+        let state = get_state();
+        let network = state.borrow().network();
+        let dst_address =
+            parse_btc_address(&address, network).map_err(WithdrawError::InvalidAddress)?;
+        let rune_id = RuneId {
+            block: rune_id.block_id,
+            tx: rune_id.txid,
+        };
+
+        Withdrawal::new(state)
+            .preview_withdraw(
+                amount,
+                dst_address,
+                rune_id,
+                fee_priority.unwrap_or_default(),
+            )
+            .await
+    }
+
+    /// Same as `create_edict_tx_checked`, but traps on errors. Kept for the clients of the
+    /// original interface.
     #[update]
     pub async fn create_edict_tx(&self, args: CreateEdictTxArgs) -> Vec<u8> {
-        let from_addr = Address::from_str(&args.from_address)
-            .expect("failed to parse from address")
-            .assume_checked();
-        let to_addr = Address::from_str(&args.destination)
-            .expect("failed to parse destination address")
-            .assume_checked();
-        let change_addr = args
-            .change_address
-            .map(|addr| {
-                Address::from_str(&addr)
-                    .expect("failed to parse change address")
-                    .assume_checked()
-            })
-            .unwrap_or_else(|| from_addr.clone());
+        self.create_edict_tx_checked(args)
+            .await
+            .expect("failed to create edict transaction")
+    }
 
+    #[update]
+    pub async fn create_edict_tx_checked(
+        &self,
+        args: CreateEdictTxArgs,
+    ) -> Result<Vec<u8>, WithdrawError> {
         let state = get_state();
+        let network = state.borrow().network();
+        let parse_address = |address: &str| {
+            parse_btc_address(address, network).map_err(WithdrawError::InvalidAddress)
+        };
+
+        let from_addr = parse_address(&args.from_address)?;
+        let to_addr = parse_address(&args.destination)?;
+        let change_addr = match &args.change_address {
+            Some(address) => parse_address(address)?,
+            None => from_addr.clone(),
+        };
+
         let index_provider = OrdIndexProvider::new(state.borrow().indexer_url());
-        let runes_list = index_provider
-            .get_rune_list()
-            .await
-            .expect("failed to get rune list");
+        let runes_list = index_provider.get_rune_list().await.map_err(|err| {
+            WithdrawError::InternalError(format!("failed to get rune list: {err:?}"))
+        })?;
         let rune_id = runes_list
             .into_iter()
             .find(|(_, spaced_rune, _)| args.rune_name == spaced_rune.to_string())
-            .unwrap_or_else(|| panic!("rune {} is not in the list of runes", args.rune_name))
+            .ok_or_else(|| {
+                WithdrawError::InternalError(format!(
+                    "rune {} is not in the list of runes",
+                    args.rune_name
+                ))
+            })?
             .0;
 
         let utxo_provider = IcUtxoProvider::new(state.borrow().ic_btc_network());
-        let input_utxos = utxo_provider
-            .get_utxos(&from_addr)
-            .await
-            .expect("failed to get input utxos");
+        let input_utxos = utxo_provider.get_utxos(&from_addr).await.map_err(|err| {
+            WithdrawError::InternalError(format!("failed to get input utxos: {err:?}"))
+        })?;
         let inputs = input_utxos
             .utxos
             .iter()
-            .map(|utxo| TxInputInfo {
-                outpoint: OutPoint {
-                    txid: Txid::from_raw_hash(*Hash::from_bytes_ref(
-                        &utxo.outpoint.txid.clone().try_into().expect("invalid txid"),
-                    )),
-                    vout: utxo.outpoint.vout,
-                },
-                tx_out: TxOut {
-                    value: Amount::from_sat(utxo.value),
-                    script_pubkey: from_addr.script_pubkey(),
-                },
-                derivation_path: DerivationPath::default(),
+            .map(|utxo| {
+                let txid = Txid::from_slice(&utxo.outpoint.txid).map_err(|_| {
+                    WithdrawError::InternalError("invalid txid of input utxo".to_string())
+                })?;
+
+                Ok(TxInputInfo {
+                    outpoint: OutPoint {
+                        txid,
+                        vout: utxo.outpoint.vout,
+                    },
+                    tx_out: TxOut {
+                        value: Amount::from_sat(utxo.value),
+                        script_pubkey: from_addr.script_pubkey(),
+                    },
+                    derivation_path: DerivationPath::default(),
+                })
             })
-            .collect();
+            .collect::<Result<Vec<_>, WithdrawError>>()?;
 
-        let fee_rate = utxo_provider
-            .get_fee_rate()
-            .await
-            .expect("failed to get fee rate");
+        let fee_rate = utxo_provider.get_fee_rate(FeePriority::Standard).await?;
 
         let args = ord_rs::wallet::CreateEdictTxArgs {
             rune: rune_id,
@@ -262,20 +1186,17 @@ impl RuneBridge {
             ScriptType::P2WSH,
             state.borrow().wallet(),
         );
-        let unsigned_tx = builder
-            .create_edict_transaction(&args)
-            .map_err(|err| {
-                log::warn!("Failed to create withdraw transaction: {err:?}");
-                WithdrawError::TransactionCreation
-            })
-            .expect("failed to create transaction");
+        let unsigned_tx = builder.create_edict_transaction(&args).map_err(|err| {
+            log::warn!("Failed to create withdraw transaction: {err:?}");
+            WithdrawError::TransactionCreation
+        })?;
 
         let mut bytes = vec![];
         unsigned_tx
             .consensus_encode(&mut bytes)
-            .expect("failed to encode transaction");
+            .map_err(|_| WithdrawError::TransactionSerialization)?;
 
-        bytes
+        Ok(bytes)
     }
 
     pub fn idl() -> Idl {
@@ -283,14 +1204,257 @@ impl RuneBridge {
     }
 }
 
-pub fn eth_address_to_subaccount(eth_address: &H160) -> Subaccount {
-    let mut subaccount = [0; 32];
-    subaccount[0..eth_address.0 .0.len()].copy_from_slice(eth_address.0.as_bytes());
+/// Checks that the `caller` may apply a dangerous operation without an approved proposal.
+fn check_direct_admin(caller: Principal) -> minter_did::error::Result<()> {
+    get_state().borrow().check_admin(caller)?;
+    get_admin_approvals().check_direct().map_err(approval_error)
+}
+
+fn approval_error(err: ApprovalError) -> minter_did::error::Error {
+    match err {
+        ApprovalError::NotApprover => minter_did::error::Error::NotAuthorized,
+        err => minter_did::error::Error::Internal(err.to_string()),
+    }
+}
+
+async fn apply_admin_operation(operation: AdminOperation) -> minter_did::error::Result<()> {
+    match operation {
+        AdminOperation::Reconfigure { config, nonce } => reconfigure(config, nonce).map(|_| ()),
+        AdminOperation::SetCircuitBreakerConfig(config) => {
+            get_state().borrow_mut().configure_circuit_breaker(config)
+        }
+        AdminOperation::SetApprovalConfig(config) => get_admin_approvals()
+            .configure(config)
+            .map_err(approval_error),
+        AdminOperation::ConfigureEcdsa => configure_ecdsa().await,
+        AdminOperation::ConfigureBftBridge(config) => configure_bft_bridge(config).await,
+        AdminOperation::DeployBftBridge { fee_charge } => {
+            crate::core::bft_deploy::deploy_bft_bridge(&get_state(), fee_charge)
+                .await
+                .map(|_| ())
+        }
+        AdminOperation::SetTaskLimits(limits) => {
+            get_state().borrow_mut().configure_task_limits(limits)
+        }
+        AdminOperation::SetGasLimits(limits) => {
+            get_state().borrow_mut().configure_gas_limits(limits)
+        }
+        AdminOperation::SetScreeningOverride { subject, allowed } => {
+            get_state()
+                .borrow_mut()
+                .screening_overrides_mut()
+                .set(&subject, allowed);
+            Ok(())
+        }
+        AdminOperation::RemoveScreeningOverride(subject) => {
+            get_state()
+                .borrow_mut()
+                .screening_overrides_mut()
+                .remove(&subject);
+            Ok(())
+        }
+        AdminOperation::SetOperationArchive(config) => set_operation_archive(config),
+        AdminOperation::MintAndBridgeRunes {
+            eth_address,
+            rune_id,
+            dst_chain_id,
+        } => {
+            let operation_id = mint_and_bridge_runes(eth_address, rune_id, dst_chain_id)
+                .await
+                .map_err(|err| {
+                    minter_did::error::Error::Internal(format!("failed to mint runes: {err:?}"))
+                })?;
+            log::info!("Open mint is requested with the deposit operation {operation_id}");
+            Ok(())
+        }
+        AdminOperation::SetWithdrawalAllowlistMode(enabled) => {
+            set_withdrawal_allowlist_mode(enabled);
+            Ok(())
+        }
+        AdminOperation::SetRuneSupplyCap {
+            rune_name,
+            supply_cap_bps,
+        } => set_rune_supply_cap(&rune_name, supply_cap_bps),
+        AdminOperation::SetRuneMinAmounts {
+            rune_name,
+            min_deposit_amount,
+            min_withdrawal_amount,
+        } => set_rune_min_amounts(&rune_name, min_deposit_amount, min_withdrawal_amount),
+        AdminOperation::SetSoftCap { lane, cap_per_hour } => set_soft_cap(lane, cap_per_hour),
+        AdminOperation::CancelDeposit(operation_id) => RuneDeposit::get()
+            .cancel_deposit(operation_id, None)
+            .map_err(|err| {
+                minter_did::error::Error::Internal(format!("failed to cancel deposit: {err:?}"))
+            }),
+    }
+}
+
+fn set_operation_archive(config: Option<ArchiveConfig>) -> minter_did::error::Result<()> {
+    get_operation_archive()
+        .configure(config)
+        .map_err(|err| minter_did::error::Error::Internal(format!("Invalid archive config: {err}")))
+}
+
+async fn mint_and_bridge_runes(
+    eth_address: H160,
+    rune_id: RuneIdDid,
+    dst_chain_id: Option<u32>,
+) -> Result<MinterOperationId, OpenMintError> {
+    let rune_id = RuneId {
+        block: rune_id.block_id,
+        tx: rune_id.txid,
+    };
+
+    let operation_id = OpenMint::new(get_state())
+        .mint_and_request_deposit(eth_address, rune_id, dst_chain_id)
+        .await?;
+    get_scheduler()
+        .borrow_mut()
+        .append_task(RuneBridgeTask::Deposit(operation_id).into_scheduled(TaskOptions::new()));
+
+    Ok(operation_id)
+}
+
+fn set_withdrawal_allowlist_mode(enabled: bool) {
+    get_withdrawal_allowlist().set_enabled(enabled);
+    log::info!("Withdrawal allowlist mode is set to {enabled}");
+}
+
+fn parse_rune_name(rune_name: &str) -> minter_did::error::Result<RuneName> {
+    RuneName::from_str(rune_name).map_err(|err| {
+        minter_did::error::Error::Internal(format!("Invalid rune name {rune_name}: {err}"))
+    })
+}
+
+fn set_rune_supply_cap(
+    rune_name: &str,
+    supply_cap_bps: Option<u32>,
+) -> minter_did::error::Result<()> {
+    get_rune_limits_store()
+        .set_supply_cap(parse_rune_name(rune_name)?, supply_cap_bps)
+        .map_err(minter_did::error::Error::Internal)
+}
+
+fn set_rune_min_amounts(
+    rune_name: &str,
+    min_deposit_amount: Option<u128>,
+    min_withdrawal_amount: Option<u128>,
+) -> minter_did::error::Result<()> {
+    get_rune_limits_store().set_min_amounts(
+        parse_rune_name(rune_name)?,
+        min_deposit_amount,
+        min_withdrawal_amount,
+    );
+    Ok(())
+}
+
+fn set_soft_cap(lane: Lane, cap_per_hour: Option<u128>) -> minter_did::error::Result<()> {
+    get_soft_cap_store()
+        .set_cap(lane, cap_per_hour)
+        .map_err(|err| minter_did::error::Error::Internal(format!("Invalid soft cap: {err}")))
+}
+
+/// Sets the master key of the configured ECDSA key id, fetching it from the management canister
+/// if it is not cached.
+async fn configure_ecdsa() -> minter_did::error::Result<()> {
+    let key_id = get_state().borrow().ecdsa_key_id();
+
+    let master_key = match get_public_key_cache().master_key(&key_id) {
+        Some(master_key) => master_key,
+        None => {
+            let master_key = ecdsa_public_key(EcdsaPublicKeyArgument {
+                canister_id: None,
+                derivation_path: vec![],
+                key_id: key_id.clone(),
+            })
+            .await
+            .map_err(|err| {
+                minter_did::error::Error::Internal(format!("failed to get master key: {err:?}"))
+            })?
+            .0;
+            get_public_key_cache().set_master_key(&key_id, &master_key);
+            master_key
+        }
+    };
+
+    get_state().borrow_mut().configure_ecdsa(master_key);
+    Ok(())
+}
+
+/// Sets the BftBridge configuration once it is checked against the EVM.
+async fn configure_bft_bridge(config: BftBridgeConfig) -> minter_did::error::Result<()> {
+    validate_bft_config(&config)
+        .await
+        .map_err(|errors| minter_did::error::Error::Internal(format_config_errors(&errors)))?;
+    get_state().borrow_mut().configure_bft(config);
+    Ok(())
+}
+
+/// Replaces the configuration made with the `nonce`. Returns the nonce of the next
+/// reconfiguration.
+fn reconfigure(config: RuneBridgeConfig, nonce: u64) -> minter_did::error::Result<u64> {
+    let mut config_guard = get_config_guard();
+    match config_guard
+        .check(&config, nonce)
+        .map_err(minter_did::error::Error::Internal)?
+    {
+        Reconfiguration::AlreadyApplied => return Ok(config_guard.nonce()),
+        Reconfiguration::Apply => {}
+    }
+
+    get_state().borrow_mut().reconfigure(config.clone())?;
+    Ok(config_guard.record(&config))
+}
+
+/// Checks the BftBridge configuration against the EVM of the bridge, the wiring of the contract
+/// to the canister included.
+async fn validate_bft_config(config: &BftBridgeConfig) -> Result<(), Vec<ConfigError>> {
+    let (evm_link, signer) = {
+        let state = get_state();
+        let state = state.borrow();
+        (state.get_evm_info().link, state.signer().get().clone())
+    };
+    config.validate_with_evm(&evm_link).await?;
+    config.validate_wiring(&evm_link, &signer).await
+}
+
+fn on_task_completed(task: InnerScheduledTask<RuneBridgeTask>) {
+    if reschedule_deferred_task(&task) {
+        return;
+    }
+
+    task.task().release();
+    log_task_execution_error(&task);
+}
+
+/// Appends a task whose retries were used up by the deferrals of the task limits again, with the
+/// options it was appended with. Returns `false` if the task is completed or has failed for
+/// another reason. The scheduler is borrowed while it reports the completed tasks, so the task is
+/// appended by a timer after [`DEFERRED_TASK_DELAY_SECS`].
+fn reschedule_deferred_task(task: &InnerScheduledTask<RuneBridgeTask>) -> bool {
+    let TaskStatus::Failed {
+        error: SchedulerError::TaskExecutionFailed(error),
+        ..
+    } = task.status()
+    else {
+        return false;
+    };
+    if !is_deferral_error(error.as_str()) {
+        return false;
+    }
 
-    Subaccount(subaccount)
+    log::debug!("task #{} is rescheduled after its deferrals", task.id());
+    let scheduled = ScheduledTask::with_options(task.task().clone(), task.options().clone());
+    ic_exports::ic_cdk_timers::set_timer(
+        Duration::from_secs(DEFERRED_TASK_DELAY_SECS),
+        move || {
+            get_scheduler().borrow().append_task(scheduled);
+        },
+    );
+    true
 }
 
-fn log_task_execution_error(task: InnerScheduledTask<RuneBridgeTask>) {
+fn log_task_execution_error(task: &InnerScheduledTask<RuneBridgeTask>) {
     match task.status() {
         TaskStatus::Failed {
             timestamp_secs,
@@ -299,10 +1463,27 @@ fn log_task_execution_error(task: InnerScheduledTask<RuneBridgeTask>) {
             log::error!(
                 "task #{} execution failed: {error} at {timestamp_secs}",
                 task.id()
-            )
+            );
+            if let Some(operation_id) = task.task().operation_id() {
+                operation_trace::record(
+                    operation_id,
+                    log::Level::Error,
+                    format!(
+                        "task={} status=failed error={error}",
+                        task.task().task_type()
+                    ),
+                );
+            }
         }
         TaskStatus::TimeoutOrPanic { timestamp_secs } => {
-            log::error!("task #{} panicked at {timestamp_secs}", task.id())
+            log::error!("task #{} panicked at {timestamp_secs}", task.id());
+            if let Some(operation_id) = task.task().operation_id() {
+                operation_trace::record(
+                    operation_id,
+                    log::Level::Error,
+                    format!("task={} status=panicked", task.task().task_type()),
+                );
+            }
         }
         _ => (),
     };
@@ -344,3 +1525,84 @@ pub(crate) fn get_operations_store() -> RuneOperationStore {
         None,
     )
 }
+
+pub(crate) fn get_public_key_cache() -> PublicKeyCache<VirtualMemory<DefaultMemoryImpl>> {
+    MEMORY_MANAGER.with(|mm| PublicKeyCache::new(mm.get(PUBLIC_KEY_CACHE_MEMORY_ID)))
+}
+
+pub(crate) fn get_bridge_tx_log() -> BridgeTxLog<VirtualMemory<DefaultMemoryImpl>> {
+    MEMORY_MANAGER.with(|mm| BridgeTxLog::new(mm.get(BRIDGE_TX_LOG_MEMORY_ID)))
+}
+
+pub(crate) fn get_bridged_balances() -> BridgedBalances<VirtualMemory<DefaultMemoryImpl>> {
+    MEMORY_MANAGER.with(|mm| BridgedBalances::new(mm.get(BRIDGED_BALANCES_MEMORY_ID)))
+}
+
+pub(crate) fn get_withdrawal_credits() -> WithdrawalCredits<VirtualMemory<DefaultMemoryImpl>> {
+    MEMORY_MANAGER.with(|mm| WithdrawalCredits::new(mm.get(WITHDRAWAL_CREDITS_MEMORY_ID)))
+}
+
+pub(crate) fn get_tx_journal() -> TxJournal<VirtualMemory<DefaultMemoryImpl>> {
+    MEMORY_MANAGER.with(|mm| TxJournal::new(mm.get(TX_JOURNAL_MEMORY_ID)))
+}
+
+pub(crate) fn get_confirmation_watcher() -> ConfirmationWatcher<VirtualMemory<DefaultMemoryImpl>> {
+    MEMORY_MANAGER.with(|mm| ConfirmationWatcher::new(mm.get(CONFIRMATION_WATCHER_MEMORY_ID)))
+}
+
+pub(crate) fn get_withdrawal_allowlist() -> WithdrawalAllowlist<VirtualMemory<DefaultMemoryImpl>> {
+    MEMORY_MANAGER.with(|mm| {
+        WithdrawalAllowlist::new(
+            mm.get(WITHDRAWAL_ALLOWLIST_MEMORY_ID),
+            mm.get(WITHDRAWAL_ALLOWLIST_MODE_MEMORY_ID),
+        )
+    })
+}
+
+pub(crate) fn get_admin_approvals(
+) -> ApprovalStore<AdminOperation, VirtualMemory<DefaultMemoryImpl>> {
+    MEMORY_MANAGER.with(|mm| {
+        ApprovalStore::new(
+            mm.get(APPROVAL_SETTINGS_MEMORY_ID),
+            mm.get(APPROVAL_PROPOSALS_MEMORY_ID),
+        )
+    })
+}
+
+pub(crate) fn get_config_guard() -> ConfigGuard<VirtualMemory<DefaultMemoryImpl>> {
+    MEMORY_MANAGER.with(|mm| ConfigGuard::new(mm.get(CONFIG_REVISION_MEMORY_ID)))
+}
+
+pub(crate) fn get_soft_cap_store() -> SoftCapStore<VirtualMemory<DefaultMemoryImpl>> {
+    MEMORY_MANAGER.with(|mm| {
+        SoftCapStore::new(
+            mm.get(SOFT_CAP_LANES_MEMORY_ID),
+            mm.get(SOFT_CAP_QUEUE_MEMORY_ID),
+        )
+    })
+}
+
+pub(crate) fn get_operation_archive() -> OperationArchive<VirtualMemory<DefaultMemoryImpl>> {
+    MEMORY_MANAGER.with(|mm| OperationArchive::new(mm.get(OPERATION_ARCHIVE_MEMORY_ID)))
+}
+
+pub(crate) fn get_address_registry() -> AddressRegistry<VirtualMemory<DefaultMemoryImpl>> {
+    MEMORY_MANAGER.with(|mm| {
+        AddressRegistry::new(
+            mm.get(ADDRESS_INDICES_MEMORY_ID),
+            mm.get(ADDRESS_INDICES_LOOKUP_MEMORY_ID),
+        )
+    })
+}
+
+pub(crate) fn get_fee_priorities() -> FeePriorities<VirtualMemory<DefaultMemoryImpl>> {
+    MEMORY_MANAGER.with(|mm| FeePriorities::new(mm.get(FEE_PRIORITIES_MEMORY_ID)))
+}
+
+pub(crate) fn get_notified_events() -> NotifiedEvents<VirtualMemory<DefaultMemoryImpl>> {
+    MEMORY_MANAGER.with(|mm| NotifiedEvents::new(mm.get(NOTIFIED_EVENTS_MEMORY_ID)))
+}
+
+pub(crate) fn get_rune_limits_store() -> RuneLimitsStore<VirtualMemory<DefaultMemoryImpl>> {
+    MEMORY_MANAGER.with(|mm| RuneLimitsStore::new(mm.get(RUNE_LIMITS_MEMORY_ID)))
+}
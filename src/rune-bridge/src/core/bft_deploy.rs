@@ -0,0 +1,101 @@
+//! Deployment of the BftBridge contract with the EVM address of the bridge as the minter.
+
+use std::cell::RefCell;
+
+use did::H160;
+use eth_signer::sign_strategy::TransactionSigner;
+use ic_stable_structures::CellStructure;
+use minter_contract_utils::bft_bridge_deploy::{
+    check_deployment, BftBridgeDeployStatus, BftBridgeDeployment,
+};
+
+use crate::state::State;
+
+/// Sends the deployment transactions of the BftBridge. Rune bridge mints wrapped tokens, so the
+/// contract is deployed on the wrapped side.
+pub async fn deploy_bft_bridge(
+    state: &RefCell<State>,
+    fee_charge: H160,
+) -> minter_did::error::Result<BftBridgeDeployStatus> {
+    if let Some(status) = state.borrow().bft_deploy_status() {
+        return Err(minter_did::error::Error::Internal(format!(
+            "BftBridge deployment already started: {status:?}"
+        )));
+    }
+
+    let signer = state.borrow().signer().get().clone();
+    let sender = signer
+        .get_address()
+        .await
+        .map_err(|err| minter_did::error::Error::Internal(format!("{err:?}")))?;
+
+    let (evm_info, evm_params, gas_price) = {
+        let state = state.borrow();
+        let evm_params = state.get_evm_params().clone().ok_or_else(|| {
+            minter_did::error::Error::Internal("EVM params are not initialized".into())
+        })?;
+        let gas_price = state
+            .gas_price()
+            .gas_price(evm_params.gas_price.clone(), state.clock().now());
+        (state.get_evm_info(), evm_params, gas_price)
+    };
+
+    let deployment = BftBridgeDeployment::new(
+        sender.0,
+        evm_params.nonce,
+        gas_price.into(),
+        evm_params.chain_id,
+        fee_charge.0,
+        true,
+    )
+    .map_err(minter_did::error::Error::Internal)?;
+
+    let client = evm_info.link.get_json_rpc_client();
+    let result = deployment.send(&signer, &client).await;
+
+    // The nonces of the sent transactions are used even if the deployment failed halfway.
+    let sent_transactions = match &result {
+        Ok(_) => 2,
+        Err(err) => err.sent_transactions,
+    };
+    let mut state = state.borrow_mut();
+    state.update_evm_params(|p| {
+        if let Some(params) = p.as_mut() {
+            params.nonce += sent_transactions;
+        }
+    });
+
+    let status = result.map_err(|err| minter_did::error::Error::Internal(err.reason))?;
+    state.set_bft_deploy_status(status.clone());
+    Ok(status)
+}
+
+/// Updates the status of the started deployment, and sets the deployed contract as the bridge
+/// contract.
+pub async fn check_bft_bridge_deployment(
+    state: &RefCell<State>,
+) -> minter_did::error::Result<BftBridgeDeployStatus> {
+    let (status, evm_info) = {
+        let state = state.borrow();
+        let status = state.bft_deploy_status().cloned().ok_or_else(|| {
+            minter_did::error::Error::Internal("BftBridge deployment is not started".into())
+        })?;
+        (status, state.get_evm_info())
+    };
+
+    let client = evm_info.link.get_json_rpc_client();
+    let status = check_deployment(&client, status)
+        .await
+        .map_err(minter_did::error::Error::Internal)?;
+
+    let mut state = state.borrow_mut();
+    if status.is_deployed() && &state.bft_config.bridge_address != status.proxy() {
+        log::info!("BftBridge deployed at {}", status.proxy());
+        let mut bft_config = state.bft_config.clone();
+        bft_config.bridge_address = status.proxy().clone();
+        state.configure_bft(bft_config);
+    }
+    state.set_bft_deploy_status(status.clone());
+
+    Ok(status)
+}
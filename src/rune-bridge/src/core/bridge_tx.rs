@@ -0,0 +1,113 @@
+//! Transactions of the bridge to the BftBridge contract.
+
+use std::cell::RefCell;
+
+use did::H256;
+use eth_signer::sign_strategy::TransactionSigner;
+use ic_stable_structures::CellStructure;
+use minter_contract_utils::burn_permit::BurnPermitError;
+use minter_contract_utils::gas_limits::GasOperation;
+
+use crate::interface::DepositError;
+use crate::state::State;
+
+#[derive(Debug)]
+pub(crate) enum BridgeTxError {
+    NotInitialized,
+    Sign(String),
+    Evm(String),
+}
+
+impl From<BridgeTxError> for DepositError {
+    fn from(err: BridgeTxError) -> Self {
+        match err {
+            BridgeTxError::NotInitialized => DepositError::NotInitialized,
+            BridgeTxError::Sign(err) => DepositError::Sign(err),
+            BridgeTxError::Evm(err) => DepositError::Evm(err),
+        }
+    }
+}
+
+impl From<BridgeTxError> for BurnPermitError {
+    fn from(err: BridgeTxError) -> Self {
+        match err {
+            BridgeTxError::NotInitialized => BurnPermitError::NotInitialized,
+            BridgeTxError::Sign(err) => BurnPermitError::Sign(err),
+            BridgeTxError::Evm(err) => BurnPermitError::Evm(err),
+        }
+    }
+}
+
+/// Signs the transaction to the BftBridge contract built by `build_tx` from the sender, bridge
+/// address, nonce, gas price, gas limit of the `operation` and chain id, and sends it to the EVM.
+pub(crate) async fn send_bridge_transaction(
+    state: &RefCell<State>,
+    operation: GasOperation,
+    build_tx: impl FnOnce(
+        ethers_core::types::H160,
+        ethers_core::types::H160,
+        ethers_core::types::U256,
+        ethers_core::types::U256,
+        u64,
+        u32,
+    ) -> ethers_core::types::Transaction,
+) -> Result<H256, BridgeTxError> {
+    let signer = state.borrow().signer().get().clone();
+    let sender = signer
+        .get_address()
+        .await
+        .map_err(|err| BridgeTxError::Sign(format!("{err:?}")))?;
+
+    let (evm_info, evm_params, gas_price, gas_limit) = {
+        let state = state.borrow();
+
+        let evm_info = state.get_evm_info();
+        let evm_params = state
+            .get_evm_params()
+            .clone()
+            .ok_or(BridgeTxError::NotInitialized)?;
+        let gas_price = state
+            .gas_price()
+            .gas_price(evm_params.gas_price.clone(), state.clock().now());
+        let gas_limit = state.gas_limits().gas_limit(operation);
+
+        (evm_info, evm_params, gas_price, gas_limit)
+    };
+
+    let mut tx = build_tx(
+        sender.0,
+        evm_info.bridge_contract.0,
+        evm_params.nonce.into(),
+        gas_price.into(),
+        gas_limit,
+        evm_params.chain_id as _,
+    );
+
+    let signature = signer
+        .sign_transaction(&(&tx).into())
+        .await
+        .map_err(|err| BridgeTxError::Sign(format!("{err:?}")))?;
+
+    tx.r = signature.r.0;
+    tx.s = signature.s.0;
+    tx.v = signature.v.0;
+    tx.hash = tx.hash();
+
+    let client = evm_info.link.get_json_rpc_client();
+    let id = client.send_raw_transaction(tx).await.map_err(|err| {
+        let err = format!("{err:?}");
+        state
+            .borrow_mut()
+            .gas_limits_mut()
+            .on_transaction_error(operation, &err);
+        BridgeTxError::Evm(err)
+    })?;
+
+    state.borrow_mut().update_evm_params(|p| {
+        if let Some(params) = p.as_mut() {
+            params.nonce += 1;
+        }
+    });
+
+    Ok(id.into())
+}
@@ -0,0 +1,60 @@
+//! Relay of the rune token burns approved with ERC-2612 permits.
+
+use std::cell::RefCell;
+
+use did::H256;
+use eth_signer::sign_strategy::TransactionSigner;
+use ic_stable_structures::CellStructure;
+use minter_contract_utils::bft_bridge_api::burn_with_permit_transaction;
+use minter_contract_utils::btc_address::parse_btc_address_bytes;
+use minter_contract_utils::burn_permit::{BurnPermit, BurnPermitError};
+use minter_contract_utils::gas_limits::GasOperation;
+
+use crate::canister::get_withdrawal_allowlist;
+use crate::core::bridge_tx::send_bridge_transaction;
+use crate::state::State;
+
+/// Sends the burn of the `permit` to the BftBridge. The burn is withdrawn as any other burn
+/// once the bridge collects its event, so the `recipient_id` must be the withdrawal address.
+pub async fn burn_with_permit(
+    state: &RefCell<State>,
+    permit: BurnPermit,
+) -> Result<H256, BurnPermitError> {
+    let now = state.borrow().clock().now_secs();
+    permit.check_deadline(now)?;
+    let network = state.borrow().network();
+    let address = parse_btc_address_bytes(&permit.recipient_id, network)
+        .map_err(|err| BurnPermitError::Rejected(format!("Invalid withdrawal address: {err:?}")))?;
+    // A burn to another address would not be withdrawn, so it is not relayed.
+    if !get_withdrawal_allowlist().allows(&permit.from, &address.to_string()) {
+        return Err(BurnPermitError::Rejected(format!(
+            "Withdrawal address {address} is not in the allowlist of the permit holder"
+        )));
+    }
+
+    let signer = state.borrow().signer().get().clone();
+    let sender = signer
+        .get_address()
+        .await
+        .map_err(|err| BurnPermitError::Sign(format!("{err:?}")))?;
+    let evm_info = state.borrow().get_evm_info();
+    let client = evm_info.link.get_json_rpc_client();
+    permit
+        .simulate(&client, &sender, &evm_info.bridge_contract)
+        .await?;
+
+    // The nonce and the gas price are read after the simulation, so the transactions sent by the
+    // bridge meanwhile are taken into account.
+    let id = send_bridge_transaction(
+        state,
+        GasOperation::BurnWithPermit,
+        |sender, bridge, nonce, gas_price, gas_limit, chain_id| {
+            burn_with_permit_transaction(
+                sender, bridge, nonce, gas_price, gas_limit, &permit, chain_id,
+            )
+        },
+    )
+    .await?;
+
+    Ok(id)
+}
@@ -0,0 +1,247 @@
+//! Selection of the rune utxos spent by a withdrawal.
+//!
+//! A withdrawal may need more runes than any single utxo of the bridge holds, so its inputs are
+//! chosen among all the utxos carrying the withdrawn rune. The runes of the selected inputs above
+//! the withdrawn amount are not allocated by the withdrawal edict, so they go to the rune change
+//! output of the bridge.
+
+use candid::{CandidType, Deserialize};
+use ord_rs::wallet::TxInputInfo;
+
+use crate::interface::WithdrawError;
+
+/// Maximum number of the branches explored by the branch-and-bound search.
+const BNB_MAX_TRIES: u32 = 100_000;
+
+/// Strategy of choosing the rune utxos of a withdrawal.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, CandidType, Deserialize)]
+pub enum CoinSelectionStrategy {
+    /// Spends the utxos with the largest rune amounts first, so the transaction has the fewest
+    /// inputs.
+    LargestFirst,
+    /// Looks for utxos holding exactly the withdrawn amount, so the transaction has no rune
+    /// change. Falls back to [`CoinSelectionStrategy::LargestFirst`] if there are no such utxos.
+    #[default]
+    BranchAndBound,
+}
+
+/// Utxo with its amount of the withdrawn rune.
+#[derive(Debug, Clone)]
+pub struct RuneInput {
+    pub input: TxInputInfo,
+    pub amount: u128,
+}
+
+#[derive(Debug, Clone)]
+pub struct RuneSelection {
+    pub inputs: Vec<TxInputInfo>,
+    /// Runes of the selected inputs returned to the bridge.
+    pub change: u128,
+}
+
+impl CoinSelectionStrategy {
+    /// Selects the `candidates` holding at least `requested` runes in total.
+    pub fn select(
+        self,
+        mut candidates: Vec<RuneInput>,
+        requested: u128,
+    ) -> Result<RuneSelection, WithdrawError> {
+        candidates.retain(|candidate| candidate.amount > 0);
+        candidates.sort_by(|a, b| b.amount.cmp(&a.amount));
+
+        let available = candidates
+            .iter()
+            .fold(0u128, |acc, candidate| acc.saturating_add(candidate.amount));
+        if available < requested {
+            return Err(WithdrawError::InsufficientFunds {
+                available,
+                requested,
+            });
+        }
+
+        let amounts: Vec<u128> = candidates
+            .iter()
+            .map(|candidate| candidate.amount)
+            .collect();
+        let selected = match self {
+            Self::LargestFirst => largest_first(&amounts, requested),
+            Self::BranchAndBound => branch_and_bound(&amounts, requested)
+                .unwrap_or_else(|| largest_first(&amounts, requested)),
+        };
+
+        let total = selected
+            .iter()
+            .fold(0u128, |acc, &index| acc.saturating_add(amounts[index]));
+        let inputs = selected
+            .into_iter()
+            .map(|index| candidates[index].input.clone())
+            .collect();
+
+        Ok(RuneSelection {
+            inputs,
+            change: total - requested,
+        })
+    }
+}
+
+/// Indices of the first `amounts` covering the `target`. The amounts are sorted descending.
+fn largest_first(amounts: &[u128], target: u128) -> Vec<usize> {
+    let mut total = 0u128;
+    amounts
+        .iter()
+        .enumerate()
+        .take_while(|(_, &amount)| {
+            let needed = total < target;
+            total = total.saturating_add(amount);
+            needed
+        })
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Indices of `amounts` summing up to exactly the `target`, if they are found within
+/// [`BNB_MAX_TRIES`] branches. The amounts are sorted descending.
+fn branch_and_bound(amounts: &[u128], target: u128) -> Option<Vec<usize>> {
+    // `remaining[i]` is the total of the amounts starting from `i`.
+    let mut remaining = vec![0u128; amounts.len() + 1];
+    for index in (0..amounts.len()).rev() {
+        remaining[index] = remaining[index + 1].saturating_add(amounts[index]);
+    }
+
+    let mut search = BranchAndBound {
+        amounts,
+        remaining: &remaining,
+        target,
+        selected: vec![],
+        tries: 0,
+    };
+    search.explore(0, 0).then_some(search.selected)
+}
+
+struct BranchAndBound<'a> {
+    amounts: &'a [u128],
+    remaining: &'a [u128],
+    target: u128,
+    selected: Vec<usize>,
+    tries: u32,
+}
+
+impl BranchAndBound<'_> {
+    fn explore(&mut self, index: usize, total: u128) -> bool {
+        if total == self.target {
+            return true;
+        }
+        if index == self.amounts.len()
+            || self.tries >= BNB_MAX_TRIES
+            || total.saturating_add(self.remaining[index]) < self.target
+        {
+            return false;
+        }
+        self.tries += 1;
+
+        let with_current = total.saturating_add(self.amounts[index]);
+        if with_current <= self.target {
+            self.selected.push(index);
+            if self.explore(index + 1, with_current) {
+                return true;
+            }
+            self.selected.pop();
+        }
+
+        self.explore(index + 1, total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::bip32::DerivationPath;
+    use bitcoin::hashes::Hash;
+    use bitcoin::{Amount, OutPoint, ScriptBuf, TxOut, Txid};
+
+    use super::*;
+
+    fn candidate(id: u8, amount: u128) -> RuneInput {
+        RuneInput {
+            input: TxInputInfo {
+                outpoint: OutPoint {
+                    txid: Txid::from_byte_array([id; 32]),
+                    vout: 0,
+                },
+                tx_out: TxOut {
+                    value: Amount::from_sat(10_000),
+                    script_pubkey: ScriptBuf::new(),
+                },
+                derivation_path: DerivationPath::master(),
+            },
+            amount,
+        }
+    }
+
+    fn selected_ids(selection: &RuneSelection) -> Vec<u8> {
+        let mut ids: Vec<u8> = selection
+            .inputs
+            .iter()
+            .map(|input| input.outpoint.txid.to_byte_array()[0])
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn largest_first_combines_utxos() {
+        let candidates = vec![candidate(1, 30), candidate(2, 100), candidate(3, 50)];
+
+        let selection = CoinSelectionStrategy::LargestFirst
+            .select(candidates, 120)
+            .unwrap();
+
+        assert_eq!(selected_ids(&selection), vec![2, 3]);
+        assert_eq!(selection.change, 30);
+    }
+
+    #[test]
+    fn branch_and_bound_finds_exact_match() {
+        let candidates = vec![
+            candidate(1, 30),
+            candidate(2, 100),
+            candidate(3, 50),
+            candidate(4, 40),
+        ];
+
+        let selection = CoinSelectionStrategy::BranchAndBound
+            .select(candidates, 120)
+            .unwrap();
+
+        assert_eq!(selected_ids(&selection), vec![1, 3, 4]);
+        assert_eq!(selection.change, 0);
+    }
+
+    #[test]
+    fn branch_and_bound_falls_back_to_largest_first() {
+        let candidates = vec![candidate(1, 70), candidate(2, 70), candidate(3, 0)];
+
+        let selection = CoinSelectionStrategy::BranchAndBound
+            .select(candidates, 100)
+            .unwrap();
+
+        assert_eq!(selected_ids(&selection), vec![1, 2]);
+        assert_eq!(selection.change, 40);
+    }
+
+    #[test]
+    fn insufficient_runes_are_reported() {
+        let candidates = vec![candidate(1, 70), candidate(2, 20)];
+
+        let err = CoinSelectionStrategy::default()
+            .select(candidates, 100)
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            WithdrawError::InsufficientFunds {
+                available: 90,
+                requested: 100,
+            }
+        ));
+    }
+}
@@ -9,28 +9,48 @@ use bitcoin::hashes::Hash;
 use bitcoin::{Address, Network};
 use candid::{CandidType, Deserialize};
 use did::{H160, H256};
-use eth_signer::sign_strategy::TransactionSigner;
 use ic_exports::ic_cdk::api::management_canister::bitcoin::{GetUtxosResponse, Utxo};
-use ic_exports::ic_kit::ic;
 use ic_stable_structures::CellStructure;
 use ic_task_scheduler::scheduler::TaskScheduler;
 use ic_task_scheduler::task::TaskOptions;
+use minter_contract_utils::btc_address::parse_btc_address;
+use minter_contract_utils::btc_confirmations::utxo_confirmations;
+use minter_contract_utils::gas_limits::GasOperation;
+use minter_contract_utils::health::EVM_RPC;
+use minter_contract_utils::mint_completion::{DepositStatus, MintTx};
+use minter_contract_utils::mint_order_codec::decode_stored_mint_order;
 use minter_contract_utils::operation_store::MinterOperationId;
+use minter_contract_utils::soft_caps::{Admission, Lane};
 use minter_did::id256::Id256;
 use minter_did::order::{MintOrder, SignedMintOrder};
 
-use crate::canister::{get_operations_store, get_scheduler, get_state};
+use crate::canister::{
+    get_address_registry, get_bridged_balances, get_operations_store, get_rune_limits_store,
+    get_scheduler, get_soft_cap_store, get_state,
+};
+use crate::core::bridge_tx::send_bridge_transaction;
 use crate::core::index_provider::{OrdIndexProvider, RuneIndexProvider};
+use crate::core::refund::BtcRefundStatus;
+use crate::core::screening::{self, ScreeningError};
 use crate::core::utxo_provider::{IcUtxoProvider, UtxoProvider};
-use crate::interface::DepositError;
+use crate::interface::{CancelDepositError, DepositError};
 use crate::key::BtcSignerType;
 use crate::operation::{OperationState, RuneOperationStore};
 use crate::rune_info::{RuneInfo, RuneName};
 use crate::scheduler::{PersistentScheduler, RuneBridgeTask};
-use crate::state::State;
+use crate::state::{State, INDEXER};
 
 static NONCE: AtomicU32 = AtomicU32::new(0);
 
+/// Interval of the checks for the abandoned deposits.
+pub const DEPOSIT_GC_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+thread_local! {
+    /// Utxos of the deposit requests signing their mint orders, so the concurrent requests of an
+    /// address don't deposit them twice before they are marked as used in the ledger.
+    static SIGNING_UTXOS: RefCell<HashMap<(Vec<u8>, u32), MinterOperationId>> = RefCell::default();
+}
+
 #[derive(Debug, Clone, CandidType, Deserialize)]
 pub enum DepositRequestStatus {
     /// Deposit request received but is not yet executed.
@@ -60,6 +80,16 @@ pub enum DepositRequestStatus {
         requested_amounts: HashMap<RuneName, u128>,
         actual_amounts: HashMap<RuneName, u128>,
     },
+    /// The deposit was rejected by the screening provider or by the admin. Deposit operation is
+    /// cancelled.
+    ScreeningRejected {
+        reason: String,
+    },
+    /// The deposit exceeds the hourly soft cap of the deposits and waits in the queue. It is
+    /// resumed when the queue is drained up to it.
+    Queued {
+        queued_at: u64,
+    },
     /// Mint orders are signed by the canister but are not sent to the BftBridge. The user may attempt
     /// to send them by themselves or wait for the canister to retry the operation.
     MintOrdersCreated {
@@ -67,10 +97,38 @@ pub enum DepositRequestStatus {
     },
     Minted {
         amounts: Vec<(RuneName, u128, H256)>,
+        /// Transactions of the `Minted` events of the mint orders. `None` for the deposits
+        /// completed before the transactions were recorded.
+        mint_txs: Option<Vec<MintTx>>,
     },
     InternalError {
         details: String,
     },
+    /// The deposit was cancelled by its recipient or by the admin before its mint orders were
+    /// signed. The deposited utxos are left unreserved and can be deposited by a new request.
+    Cancelled {
+        cancelled_at: u64,
+    },
+    /// The mint orders of the deposit were not signed before the deposit expiry, e.g. because its
+    /// transaction was replaced by a conflicting one and never got the confirmations. Like for the
+    /// cancelled deposits, the utxos can be deposited by a new request.
+    Abandoned {
+        abandoned_at: u64,
+        /// What the deposit was waiting for.
+        details: String,
+    },
+}
+
+/// Counters of the deposits abandoned since the canister start.
+#[derive(Debug, Default, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct DepositGcStats {
+    pub abandoned_total: u64,
+    /// Timestamp (nanoseconds) of the last check.
+    pub last_run_at: Option<u64>,
+    /// Deposits abandoned by the last check.
+    pub last_abandoned: u32,
+    /// Deposits left pending by the last check.
+    pub pending: u32,
 }
 
 #[derive(Debug, Clone, CandidType, Deserialize)]
@@ -93,6 +151,8 @@ pub enum MintOrderStatus {
     },
     Completed {
         tx_id: H256,
+        /// Number of the block with the `Minted` event of the order.
+        block_number: Option<u64>,
     },
 }
 
@@ -102,6 +162,16 @@ pub struct RuneDepositPayload {
     pub requested_amounts: Option<HashMap<RuneName, u128>>,
     pub request_ts: u64,
     pub status: DepositRequestStatus,
+    /// BTC address to send the deposited BTC to after the wrapped tokens are minted.
+    pub refund_address: Option<String>,
+    pub refund: Option<BtcRefundStatus>,
+    /// Chain id of the EVM to mint the wrapped tokens on. The chain id of the BftBridge config is
+    /// used if it is not set.
+    pub dst_chain_id: Option<u32>,
+    /// Funding transactions announced with the request, as hex encoded ids shown by block
+    /// explorers. Only their utxos are deposited by the request. A request without announced
+    /// transactions deposits the utxos at the address in the order the requests are made.
+    pub funding_txids: Option<Vec<String>>,
 }
 
 impl RuneDepositPayload {
@@ -112,15 +182,95 @@ impl RuneDepositPayload {
         }
     }
 
+    /// Remembers the deposit utxos to be refunded, if the request has a refund address.
+    fn with_refund_utxos(self, utxos: &[Utxo]) -> Self {
+        match self.refund_address {
+            Some(_) => Self {
+                refund: Some(BtcRefundStatus::Pending {
+                    utxos: utxos.to_vec(),
+                }),
+                ..self
+            },
+            None => self,
+        }
+    }
+
+    /// Progress of the deposit in the form common for the bridges.
+    pub fn deposit_status(&self) -> DepositStatus {
+        match &self.status {
+            DepositRequestStatus::Scheduled
+            | DepositRequestStatus::WaitingForInputs { .. }
+            | DepositRequestStatus::WaitingForConfirmations { .. }
+            | DepositRequestStatus::Queued { .. }
+            | DepositRequestStatus::MintOrdersCreated { .. } => DepositStatus::Pending,
+            DepositRequestStatus::Minted { amounts, mint_txs } => DepositStatus::Completed {
+                mints: mint_txs.clone().unwrap_or_else(|| {
+                    amounts
+                        .iter()
+                        .map(|(_, _, tx_id)| MintTx {
+                            tx_hash: tx_id.clone(),
+                            block_number: None,
+                        })
+                        .collect()
+                }),
+            },
+            status @ (DepositRequestStatus::NothingToDeposit { .. }
+            | DepositRequestStatus::InvalidAmounts { .. }
+            | DepositRequestStatus::ScreeningRejected { .. }
+            | DepositRequestStatus::InternalError { .. }
+            | DepositRequestStatus::Cancelled { .. }
+            | DepositRequestStatus::Abandoned { .. }) => DepositStatus::Failed {
+                reason: format!("{status:?}"),
+            },
+        }
+    }
+
+    /// Whether the request is looking for its utxos at the deposit address.
+    fn is_collecting_utxos(&self) -> bool {
+        matches!(
+            self.status,
+            DepositRequestStatus::Scheduled
+                | DepositRequestStatus::WaitingForInputs { .. }
+                | DepositRequestStatus::WaitingForConfirmations { .. }
+                | DepositRequestStatus::Queued { .. }
+        )
+    }
+
+    fn is_funded_by(&self, utxo: &Utxo) -> bool {
+        let txid = screening::display_txid(&utxo.outpoint.txid);
+        self.funding_txids
+            .iter()
+            .flatten()
+            .any(|funding_txid| *funding_txid == txid)
+    }
+
     pub fn is_complete(&self) -> bool {
         matches!(
             self.status,
             DepositRequestStatus::NothingToDeposit { .. }
                 | DepositRequestStatus::InvalidAmounts { .. }
+                | DepositRequestStatus::ScreeningRejected { .. }
                 | DepositRequestStatus::Minted { .. }
                 | DepositRequestStatus::InternalError { .. }
+                | DepositRequestStatus::Cancelled { .. }
+                | DepositRequestStatus::Abandoned { .. }
         )
     }
+
+    /// Description of the pending deposit for the `Abandoned` status.
+    fn pending_details(&self) -> String {
+        match &self.status {
+            DepositRequestStatus::WaitingForConfirmations {
+                current_min_confirmations,
+                required_confirmations,
+                ..
+            } => format!(
+                "utxos got {current_min_confirmations} of {required_confirmations} confirmations"
+            ),
+            DepositRequestStatus::WaitingForInputs { .. } => "no utxos found".to_string(),
+            status => format!("deposit was not processed, last status {status:?}"),
+        }
+    }
 }
 
 pub(crate) struct RuneDeposit<
@@ -168,14 +318,37 @@ impl<UTXO: UtxoProvider, INDEX: RuneIndexProvider> RuneDeposit<UTXO, INDEX> {
         &mut self,
         dst_address: H160,
         amounts: Option<HashMap<RuneName, u128>>,
+        refund_address: Option<String>,
+        dst_chain_id: Option<u32>,
+        funding_txids: Option<Vec<String>>,
     ) -> MinterOperationId {
+        let (refund_address, refund) =
+            match refund_address.map(|address| parse_btc_address(&address, self.network)) {
+                Some(Ok(address)) => (Some(address.to_string()), None),
+                Some(Err(err)) => {
+                    log::warn!("Invalid refund address in the deposit request: {err}");
+                    (None, Some(BtcRefundStatus::InvalidAddress(err)))
+                }
+                None => (None, None),
+            };
+
+        get_address_registry().register(&dst_address);
         let id = self.operation_store.new_operation(
             dst_address.clone(),
             OperationState::Deposit(RuneDepositPayload {
                 dst_address: dst_address.clone(),
                 requested_amounts: amounts,
-                request_ts: ic::time(),
+                request_ts: self.now(),
                 status: DepositRequestStatus::Scheduled,
+                refund_address,
+                refund,
+                dst_chain_id,
+                funding_txids: funding_txids.map(|txids| {
+                    txids
+                        .into_iter()
+                        .map(|txid| txid.trim().to_lowercase())
+                        .collect()
+                }),
             }),
         );
 
@@ -210,61 +383,252 @@ impl<UTXO: UtxoProvider, INDEX: RuneIndexProvider> RuneDeposit<UTXO, INDEX> {
         }
     }
 
-    pub fn complete_mint_request(&mut self, dst_address: H160, order_nonce: u32) {
+    /// Cancels the deposit which is not signed yet, e.g. waiting for confirmations of its utxos.
+    /// `sender` is the EVM address which requested the cancellation and must be the recipient of
+    /// the deposit. It is `None` if the cancellation is requested by the admin.
+    ///
+    /// The deposit utxos are reserved together with signing of the mint orders, so a deposit
+    /// which can be cancelled holds no reserved utxos. Once the orders are signed they may be
+    /// sent to the BftBridge by the user, so such deposits cannot be cancelled.
+    pub fn cancel_deposit(
+        &mut self,
+        request_id: MinterOperationId,
+        sender: Option<&H160>,
+    ) -> Result<(), CancelDepositError> {
+        let Some(OperationState::Deposit(payload)) = self.operation_store.get(request_id) else {
+            return Err(CancelDepositError::NotFound);
+        };
+
+        if sender.is_some_and(|sender| *sender != payload.dst_address) {
+            return Err(CancelDepositError::NotAuthorized);
+        }
+
+        match payload.status {
+            DepositRequestStatus::Scheduled
+            | DepositRequestStatus::WaitingForInputs { .. }
+            | DepositRequestStatus::WaitingForConfirmations { .. } => {}
+            DepositRequestStatus::Queued { .. } => {
+                get_soft_cap_store().remove(&request_id.to_string());
+            }
+            DepositRequestStatus::MintOrdersCreated { .. }
+            | DepositRequestStatus::Minted { .. } => {
+                return Err(CancelDepositError::MintOrdersSigned)
+            }
+            _ => return Err(CancelDepositError::Finished),
+        }
+
+        self.update_request_status(
+            request_id,
+            payload,
+            DepositRequestStatus::Cancelled {
+                cancelled_at: self.now(),
+            },
+        );
+        log::info!("Deposit request {request_id} is cancelled.");
+
+        Ok(())
+    }
+
+    /// Abandons the deposits whose mint orders are not signed after the deposit expiry since
+    /// their request. An abandoned deposit stops taking the utxos at its address, so a later
+    /// request of the address deposits them. The queued deposits wait for the soft caps and the
+    /// deposits being signed hold their utxos, so both are left pending. Returns the number of
+    /// the abandoned deposits.
+    pub fn abandon_expired_deposits(&mut self) -> u32 {
+        let now = self.now();
+        let expiry = self.state.borrow().deposit_expiry().as_nanos() as u64;
+
+        let mut abandoned = 0;
+        let mut pending = 0;
+        for (request_id, operation) in self.operation_store.get_incomplete() {
+            let OperationState::Deposit(payload) = operation else {
+                continue;
+            };
+
+            let is_unsigned = matches!(
+                payload.status,
+                DepositRequestStatus::Scheduled
+                    | DepositRequestStatus::WaitingForInputs { .. }
+                    | DepositRequestStatus::WaitingForConfirmations { .. }
+            );
+            if !is_unsigned
+                || now <= payload.request_ts.saturating_add(expiry)
+                || UtxoClaim::is_held_by(request_id)
+            {
+                pending += 1;
+                continue;
+            }
+
+            let details = payload.pending_details();
+            log::info!("Deposit request {request_id} is abandoned: {details}.");
+            self.update_request_status(
+                request_id,
+                payload,
+                DepositRequestStatus::Abandoned {
+                    abandoned_at: now,
+                    details,
+                },
+            );
+            abandoned += 1;
+        }
+
+        let mut state = self.state.borrow_mut();
+        let stats = state.deposit_gc_stats_mut();
+        stats.abandoned_total += abandoned as u64;
+        stats.last_run_at = Some(now);
+        stats.last_abandoned = abandoned;
+        stats.pending = pending;
+
+        abandoned
+    }
+
+    /// Marks the mint order with the `order_nonce` as completed by the `mint_tx`, in which the
+    /// `Minted` event of the order was emitted. The order may be sent by the bridge or by the
+    /// user. The deposit is completed once all its orders are completed.
+    pub fn complete_mint_request(
+        &mut self,
+        dst_address: H160,
+        order_nonce: u32,
+        mint_tx: Option<MintTx>,
+    ) {
         let requests = self.operation_store.get_for_address(&dst_address);
         for (request_id, request) in requests {
-            if let OperationState::Deposit(payload) = request {
-                if let DepositRequestStatus::MintOrdersCreated { mut orders } =
-                    payload.status.clone()
-                {
-                    let mut is_updated = false;
-                    for order in &mut orders {
-                        if let MintOrderStatus::Sent { nonce, tx_id, .. } = &mut order.status {
-                            if *nonce == order_nonce {
-                                order.status = MintOrderStatus::Completed {
-                                    tx_id: tx_id.clone(),
-                                };
-                                is_updated = true;
-                            }
-                        }
-                    }
+            let OperationState::Deposit(payload) = request else {
+                continue;
+            };
+            let DepositRequestStatus::MintOrdersCreated { mut orders } = payload.status.clone()
+            else {
+                continue;
+            };
 
-                    if is_updated {
-                        if orders.iter().all(|order_info| {
-                            matches!(order_info.status, MintOrderStatus::Completed { .. })
-                        }) {
-                            self.complete_deposit_request(request_id, payload, orders)
-                        } else {
-                            self.operation_store
-                                .update(request_id, OperationState::Deposit(payload));
-                        }
+            let mut is_updated = false;
+            for order in &mut orders {
+                let (nonce, mint_order, sent_tx_id) = match &order.status {
+                    MintOrderStatus::Created { nonce, mint_order } => (*nonce, *mint_order, None),
+                    MintOrderStatus::Sent {
+                        nonce,
+                        mint_order,
+                        tx_id,
+                    } => (*nonce, *mint_order, Some(tx_id.clone())),
+                    MintOrderStatus::Completed { .. } => continue,
+                };
 
-                        break;
-                    }
+                if nonce != order_nonce {
+                    continue;
                 }
+
+                Self::add_bridged_balance(&payload.dst_address, order, &mint_order);
+
+                let (tx_id, block_number) = match (&mint_tx, sent_tx_id) {
+                    (Some(mint_tx), _) => (mint_tx.tx_hash.clone(), mint_tx.block_number),
+                    (None, Some(tx_id)) => (tx_id, None),
+                    (None, None) => (H256::default(), None),
+                };
+                order.status = MintOrderStatus::Completed {
+                    tx_id,
+                    block_number,
+                };
+                is_updated = true;
+            }
+
+            if is_updated {
+                if orders.iter().all(|order_info| {
+                    matches!(order_info.status, MintOrderStatus::Completed { .. })
+                }) {
+                    self.complete_deposit_request(request_id, payload, orders)
+                } else {
+                    self.update_request_status(
+                        request_id,
+                        payload,
+                        DepositRequestStatus::MintOrdersCreated { orders },
+                    );
+                }
+
+                break;
             }
         }
     }
 
+    fn add_bridged_balance(
+        dst_address: &H160,
+        order: &MintOrderDetails,
+        mint_order: &SignedMintOrder,
+    ) {
+        let wrapped_amount = match decode_stored_mint_order(mint_order)
+            .map(|mint_order| u128::try_from(mint_order.amount.0))
+        {
+            Ok(Ok(amount)) => amount,
+            result => {
+                log::error!("Failed to get the wrapped amount of the mint order: {result:?}");
+                0
+            }
+        };
+
+        get_bridged_balances().add_deposit(
+            dst_address,
+            order.rune_name,
+            order.amount,
+            wrapped_amount,
+        );
+        get_rune_limits_store().add_bridged(order.rune_name, order.amount);
+    }
+
     fn complete_deposit_request(
         &mut self,
         request_id: MinterOperationId,
         request: RuneDepositPayload,
         orders: Vec<MintOrderDetails>,
     ) {
-        self.update_request_status(request_id, request, DepositRequestStatus::Minted { amounts: orders.into_iter().map(|order_details| {
-            let name = order_details.rune_name;
-            let amount = order_details.amount;
-            let tx_id = match order_details.status {
-                MintOrderStatus::Completed { tx_id } => tx_id,
+        let has_pending_refund = matches!(request.refund, Some(BtcRefundStatus::Pending { .. }));
+
+        let mut amounts = Vec::with_capacity(orders.len());
+        let mut mint_txs = Vec::with_capacity(orders.len());
+        for order_details in orders {
+            let (tx_id, block_number) = match order_details.status {
+                MintOrderStatus::Completed {
+                    tx_id,
+                    block_number,
+                } => (tx_id, block_number),
                 s => {
-                    log::error!("Invalid state of the mint order when completing deposit request: {s:?}");
-                    H256::default()
-                },
+                    log::error!(
+                        "Invalid state of the mint order when completing deposit request: {s:?}"
+                    );
+                    (H256::default(), None)
+                }
             };
 
-            (name, amount, tx_id)
-        }).collect() })
+            mint_txs.push(MintTx {
+                tx_hash: tx_id.clone(),
+                block_number,
+            });
+            amounts.push((order_details.rune_name, order_details.amount, tx_id));
+        }
+
+        self.update_request_status(
+            request_id,
+            request,
+            DepositRequestStatus::Minted {
+                amounts,
+                mint_txs: Some(mint_txs),
+            },
+        );
+
+        if has_pending_refund {
+            self.schedule_refund(request_id);
+        }
+    }
+
+    fn schedule_refund(&self, request_id: MinterOperationId) {
+        const REFUND_RETRY_INTERVAL_SECS: u32 = 60;
+        const REFUND_MAX_RETRIES: u32 = 10;
+
+        self.scheduler.borrow_mut().append_task(
+            RuneBridgeTask::RefundChange(request_id).into_scheduled(
+                TaskOptions::new()
+                    .with_max_retries_policy(REFUND_MAX_RETRIES)
+                    .with_fixed_backoff_policy(REFUND_RETRY_INTERVAL_SECS),
+            ),
+        );
     }
 
     async fn execute_request_step(
@@ -275,12 +639,22 @@ impl<UTXO: UtxoProvider, INDEX: RuneIndexProvider> RuneDeposit<UTXO, INDEX> {
         match request.status.clone() {
             DepositRequestStatus::Scheduled
             | DepositRequestStatus::WaitingForInputs { .. }
-            | DepositRequestStatus::WaitingForConfirmations { .. } => {
+            | DepositRequestStatus::WaitingForConfirmations { .. }
+            | DepositRequestStatus::Queued { .. } => {
                 self.prepare_mint_orders(request_id, request).await
             }
             DepositRequestStatus::NothingToDeposit { .. } => ControlFlow::Break(()),
             DepositRequestStatus::InvalidAmounts { .. } => ControlFlow::Break(()),
+            DepositRequestStatus::ScreeningRejected { .. } => ControlFlow::Break(()),
             DepositRequestStatus::MintOrdersCreated { orders } => {
+                if !self.is_sent_by_bridge(&request) {
+                    log::trace!(
+                        "Mint orders of request {request_id} are to be sent to chain {:?} by the user.",
+                        request.dst_chain_id
+                    );
+                    return ControlFlow::Break(());
+                }
+
                 let mut updated = vec![];
                 let mut has_changes = false;
                 for order_info in orders {
@@ -290,7 +664,24 @@ impl<UTXO: UtxoProvider, INDEX: RuneIndexProvider> RuneDeposit<UTXO, INDEX> {
                         status,
                     } = order_info;
                     if let MintOrderStatus::Created { mint_order, nonce } = status {
-                        if let Ok(tx_id) = self.send_mint_order(&mint_order).await {
+                        // Older BftBridge proxies have no `isNonceUsed` and revert the check.
+                        // The bridge rejects a used order itself, so an unknown order is sent.
+                        let used = match self.is_mint_order_used(&mint_order).await {
+                            Ok(used) => used,
+                            Err(err) => {
+                                log::warn!(
+                                    "Failed to check mint order {nonce} of request {request_id}, sending it: {err:?}"
+                                );
+                                false
+                            }
+                        };
+
+                        if used {
+                            // The `Minted` event of the order completes it.
+                            log::info!(
+                                "Mint order {nonce} of request {request_id} is minted already, skipping the transaction"
+                            );
+                        } else if let Ok(tx_id) = self.send_mint_order(&mint_order).await {
                             updated.push(MintOrderDetails {
                                 status: MintOrderStatus::Sent {
                                     mint_order,
@@ -325,6 +716,8 @@ impl<UTXO: UtxoProvider, INDEX: RuneIndexProvider> RuneDeposit<UTXO, INDEX> {
             }
             DepositRequestStatus::Minted { .. } => ControlFlow::Break(()),
             DepositRequestStatus::InternalError { .. } => ControlFlow::Break(()),
+            DepositRequestStatus::Cancelled { .. } => ControlFlow::Break(()),
+            DepositRequestStatus::Abandoned { .. } => ControlFlow::Break(()),
         }
     }
 
@@ -335,10 +728,25 @@ impl<UTXO: UtxoProvider, INDEX: RuneIndexProvider> RuneDeposit<UTXO, INDEX> {
     ) -> ControlFlow<(), ()> {
         log::trace!("Preparing mint orders for operation {request_id}");
 
+        let now = self.now();
+        if !self.state.borrow_mut().circuit_breaker_mut().allows(now) {
+            self.wait_for_inputs(
+                request_id,
+                DepositRequestStatus::InternalError {
+                    details: "Deposits are paused by the circuit breaker.".to_string(),
+                },
+            );
+            return ControlFlow::Break(());
+        }
+
         let dst_address = &request.dst_address;
         let transit_address = self.get_transit_address(dst_address).await;
 
-        let utxos_response = match self.get_deposit_utxos(&transit_address).await {
+        let utxos_response = self
+            .get_deposit_utxos(&transit_address)
+            .await
+            .map(|utxos_response| self.session_utxos(request_id, &request, utxos_response));
+        let utxos_response = match utxos_response {
             Ok(utxos_response) if utxos_response.utxos.is_empty() => {
                 self.wait_for_inputs(
                     request_id,
@@ -361,20 +769,37 @@ impl<UTXO: UtxoProvider, INDEX: RuneIndexProvider> RuneDeposit<UTXO, INDEX> {
             }
         };
 
-        match self.validate_utxo_confirmations(&utxos_response) {
-            Ok(_) => {}
-            Err(min_confirmations) => {
-                self.wait_for_confirmations(request_id, utxos_response, min_confirmations);
-                return ControlFlow::Break(());
-            }
+        // The deposited runes are known only from the indexer, so the utxos wait for the
+        // confirmations required for any deposit before it is asked, and for the tier of the
+        // deposited runes after.
+        let min_confirmations = self.state.borrow().confirmation_policy().min_confirmations;
+        if let Err((current_confirmations, required_confirmations)) =
+            self.validate_utxo_confirmations(&utxos_response, min_confirmations)
+        {
+            self.wait_for_confirmations(
+                request_id,
+                utxos_response,
+                current_confirmations,
+                required_confirmations,
+            );
+            return ControlFlow::Break(());
         }
 
-        let utxos = utxos_response.utxos;
+        let mint_amounts = match self.check_indexer_height(utxos_response.tip_height).await {
+            Ok(()) => {
+                self.get_mint_amounts(&utxos_response.utxos, &request.requested_amounts)
+                    .await
+            }
+            Err(err) => Err(err),
+        };
+        if !matches!(mint_amounts, Err(DepositError::InvalidAmounts { .. })) {
+            self.record_call(
+                INDEXER,
+                !matches!(mint_amounts, Err(DepositError::Unavailable(_))),
+            );
+        }
 
-        let (rune_info_amounts, used_utxos) = match self
-            .get_mint_amounts(&utxos, &request.requested_amounts)
-            .await
-        {
+        let (rune_info_amounts, used_utxos) = match mint_amounts {
             Ok((amounts, _)) if amounts.is_empty() => {
                 log::trace!("No runes found in the input utxos for request {request_id}.");
 
@@ -404,7 +829,26 @@ impl<UTXO: UtxoProvider, INDEX: RuneIndexProvider> RuneDeposit<UTXO, INDEX> {
             used_utxos
         );
 
-        if self.has_used_utxos(&used_utxos) {
+        let deposit_value = rune_deposit_value(&rune_info_amounts);
+        let required_confirmations = self
+            .state
+            .borrow()
+            .confirmation_policy()
+            .required_confirmations(deposit_value);
+        if let Err((current_confirmations, required_confirmations)) =
+            self.validate_utxo_confirmations(&utxos_response, required_confirmations)
+        {
+            self.wait_for_confirmations(
+                request_id,
+                utxos_response,
+                current_confirmations,
+                required_confirmations,
+            );
+            return ControlFlow::Break(());
+        }
+
+        let claim = UtxoClaim::new(request_id, &used_utxos);
+        if self.has_used_utxos(&used_utxos) || claim.is_none() {
             self.wait_for_inputs(
                 request_id,
                 DepositRequestStatus::InternalError {
@@ -414,8 +858,78 @@ impl<UTXO: UtxoProvider, INDEX: RuneIndexProvider> RuneDeposit<UTXO, INDEX> {
             return ControlFlow::Break(());
         }
 
+        match self.screen_deposit(&request.dst_address, &used_utxos).await {
+            Ok(()) => {}
+            Err(ScreeningError::Rejected(reason)) => {
+                self.update_request_status(
+                    request_id,
+                    request,
+                    DepositRequestStatus::ScreeningRejected { reason },
+                );
+                return ControlFlow::Break(());
+            }
+            Err(ScreeningError::Unavailable(details)) => {
+                self.wait_for_inputs(
+                    request_id,
+                    DepositRequestStatus::InternalError {
+                        details: format!("Screening provider is unavailable: {details}"),
+                    },
+                );
+                return ControlFlow::Break(());
+            }
+        }
+
+        if let Err(err) = self
+            .check_min_amounts(&rune_info_amounts)
+            .and_then(|()| self.check_exact_amounts(&rune_info_amounts))
+        {
+            self.wait_for_inputs(
+                request_id,
+                DepositRequestStatus::InternalError {
+                    details: format!("{err:?}"),
+                },
+            );
+            return ControlFlow::Break(());
+        }
+
+        if let Err(err) = self.check_supply_caps(&rune_info_amounts).await {
+            self.wait_for_inputs(
+                request_id,
+                DepositRequestStatus::InternalError {
+                    details: format!("{err:?}"),
+                },
+            );
+            return ControlFlow::Break(());
+        }
+
+        // Amounts of different runes are not comparable, so the soft cap counts the deposits.
+        let admission = get_soft_cap_store().admit(
+            &request_id.to_string(),
+            Lane::Deposit,
+            1,
+            vec![],
+            self.now(),
+        );
+        if let Admission::Queued(position) = admission {
+            log::trace!("Deposit request {request_id} is queued: {position:?}");
+            if !matches!(request.status, DepositRequestStatus::Queued { .. }) {
+                self.update_request_status(
+                    request_id,
+                    request,
+                    DepositRequestStatus::Queued {
+                        queued_at: position.queued_at,
+                    },
+                );
+            }
+            return ControlFlow::Break(());
+        }
+
         let mint_order_details = match self
-            .create_mint_orders(&request.dst_address, &rune_info_amounts)
+            .create_mint_orders(
+                &request.dst_address,
+                request.dst_chain_id,
+                &rune_info_amounts,
+            )
             .await
         {
             Ok(v) => v,
@@ -430,18 +944,78 @@ impl<UTXO: UtxoProvider, INDEX: RuneIndexProvider> RuneDeposit<UTXO, INDEX> {
             }
         };
 
+        // The request may be cancelled while the mint orders are signed. The signed orders are
+        // dropped then, and the utxos are not reserved.
+        if self.is_dropped(request_id) {
+            log::trace!("Deposit request {request_id} was cancelled during signing.");
+            return ControlFlow::Break(());
+        }
+
         self.update_request_status(
             request_id,
-            request,
+            request.with_refund_utxos(&used_utxos),
             DepositRequestStatus::MintOrdersCreated {
                 orders: mint_order_details,
             },
         );
         self.mark_used_utxos(&used_utxos, &transit_address);
+        drop(claim);
 
         ControlFlow::Continue(())
     }
 
+    /// Leaves the utxos deposited by the request, out of the unused utxos at its deposit address.
+    ///
+    /// A request with announced funding transactions takes only their utxos. The other utxos go
+    /// to the earliest request of the address without announced transactions, so the later
+    /// requests wait until it is signed. The utxos being signed by other requests are skipped.
+    fn session_utxos(
+        &self,
+        request_id: MinterOperationId,
+        request: &RuneDepositPayload,
+        mut utxos_response: GetUtxosResponse,
+    ) -> GetUtxosResponse {
+        let other_sessions: Vec<_> = self
+            .operation_store
+            .get_for_address(&request.dst_address)
+            .into_iter()
+            .filter_map(|(id, operation)| match operation {
+                OperationState::Deposit(payload)
+                    if id != request_id && payload.is_collecting_utxos() =>
+                {
+                    Some((id, payload))
+                }
+                _ => None,
+            })
+            .collect();
+
+        if request.funding_txids.is_some() {
+            utxos_response
+                .utxos
+                .retain(|utxo| request.is_funded_by(utxo));
+        } else if other_sessions
+            .iter()
+            .any(|(id, payload)| payload.funding_txids.is_none() && *id < request_id)
+        {
+            log::trace!(
+                "Deposit request {request_id} waits for the earlier requests of the address."
+            );
+            utxos_response.utxos.clear();
+        } else {
+            utxos_response.utxos.retain(|utxo| {
+                !other_sessions
+                    .iter()
+                    .any(|(_, payload)| payload.is_funded_by(utxo))
+            });
+        }
+
+        utxos_response
+            .utxos
+            .retain(|utxo| !UtxoClaim::is_claimed_by_other(request_id, utxo));
+
+        utxos_response
+    }
+
     fn wait_for_inputs(
         &mut self,
         request_id: MinterOperationId,
@@ -471,13 +1045,14 @@ impl<UTXO: UtxoProvider, INDEX: RuneIndexProvider> RuneDeposit<UTXO, INDEX> {
                     0
                 };
 
+            let now = self.now();
             self.update_request_status(
                 request_id,
                 payload.clone(),
                 DepositRequestStatus::WaitingForInputs {
                     requested_at: payload.request_ts,
-                    current_ts: ic::time(),
-                    next_retry_at: ic::time() + self.deposit_retry_interval().as_nanos() as u64,
+                    current_ts: now,
+                    next_retry_at: now + self.deposit_retry_interval().as_nanos() as u64,
                     waiting_until: payload.request_ts + self.request_timeout().as_nanos() as u64,
                     block_height,
                 },
@@ -488,7 +1063,11 @@ impl<UTXO: UtxoProvider, INDEX: RuneIndexProvider> RuneDeposit<UTXO, INDEX> {
     }
 
     fn is_request_timed_out(&self, request: &RuneDepositPayload) -> bool {
-        ic::time() > request.request_ts + self.request_timeout().as_nanos() as u64
+        self.now() > request.request_ts + self.request_timeout().as_nanos() as u64
+    }
+
+    fn now(&self) -> u64 {
+        self.state.borrow().clock().now()
     }
 
     fn request_timeout(&self) -> Duration {
@@ -501,15 +1080,12 @@ impl<UTXO: UtxoProvider, INDEX: RuneIndexProvider> RuneDeposit<UTXO, INDEX> {
         RETRY_INTERVAL
     }
 
-    fn min_confirmations(&self) -> u32 {
-        self.state.borrow().min_confirmations()
-    }
-
     fn wait_for_confirmations(
         &mut self,
         request_id: MinterOperationId,
         utxos_response: GetUtxosResponse,
         current_min_confirmations: u32,
+        required_confirmations: u32,
     ) {
         let Some(request) = self.operation_store.get(request_id) else {
             log::error!("Deposit request {request_id} was unexpectedly removed from the store.");
@@ -527,7 +1103,7 @@ impl<UTXO: UtxoProvider, INDEX: RuneIndexProvider> RuneDeposit<UTXO, INDEX> {
             DepositRequestStatus::WaitingForConfirmations {
                 utxos: utxos_response.utxos,
                 current_min_confirmations,
-                required_confirmations: self.min_confirmations(),
+                required_confirmations,
                 block_height: utxos_response.tip_height,
             },
         );
@@ -555,12 +1131,31 @@ impl<UTXO: UtxoProvider, INDEX: RuneIndexProvider> RuneDeposit<UTXO, INDEX> {
         request: RuneDepositPayload,
         new_status: DepositRequestStatus,
     ) {
+        if self.is_dropped(request_id) {
+            log::trace!(
+                "Deposit request {request_id} is cancelled or abandoned, ignoring status {new_status:?}."
+            );
+            return;
+        }
+
         let updated_request = request.with_status(new_status);
         log::trace!("Changing status of deposit request: {request_id}: {updated_request:?}");
         self.operation_store
             .update(request_id, OperationState::Deposit(updated_request));
     }
 
+    /// Whether the request is cancelled or abandoned, so its processing must stop.
+    fn is_dropped(&self, request_id: MinterOperationId) -> bool {
+        matches!(
+            self.operation_store.get(request_id),
+            Some(OperationState::Deposit(RuneDepositPayload {
+                status: DepositRequestStatus::Cancelled { .. }
+                    | DepositRequestStatus::Abandoned { .. },
+                ..
+            }))
+        )
+    }
+
     pub async fn get_deposit_utxos(
         &self,
         transit_address: &Address,
@@ -589,27 +1184,112 @@ impl<UTXO: UtxoProvider, INDEX: RuneIndexProvider> RuneDeposit<UTXO, INDEX> {
             .await
     }
 
-    fn validate_utxo_confirmations(&self, utxo_info: &GetUtxosResponse) -> Result<(), u32> {
-        let min_confirmations = self.state.borrow().min_confirmations();
+    /// Checks if the deposit utxos have at least `min_confirmations`. If not, returns the current
+    /// minimum and the required number of confirmations.
+    fn validate_utxo_confirmations(
+        &self,
+        utxo_info: &GetUtxosResponse,
+        min_confirmations: u32,
+    ) -> Result<(), (u32, u32)> {
         let utxo_min_confirmations = utxo_info
             .utxos
             .iter()
-            .map(|utxo| utxo_info.tip_height - utxo.height + 1)
+            .map(|utxo| utxo_confirmations(utxo_info.tip_height, utxo.height))
             .min()
             .unwrap_or_default();
 
         if min_confirmations > utxo_min_confirmations {
-            Err(utxo_min_confirmations)
+            Err((utxo_min_confirmations, min_confirmations))
         } else {
             log::trace!(
                 "Current utxo confirmations {} satisfies minimum {}. Proceeding.",
                 utxo_min_confirmations,
-                min_confirmations
+                min_confirmations,
             );
             Ok(())
         }
     }
 
+    async fn screen_deposit(
+        &self,
+        dst_address: &H160,
+        utxos: &[Utxo],
+    ) -> Result<(), ScreeningError> {
+        let config = self.state.borrow().screening_config().clone();
+        screening::screen_deposit(
+            &config,
+            |subject| self.state.borrow().screening_overrides().get(subject),
+            dst_address,
+            utxos,
+        )
+        .await
+    }
+
+    /// Checks the deposited amounts of the capped runes against their circulating supply. The
+    /// supply is requested from the indexer only for the capped runes.
+    fn check_min_amounts(&self, rune_amounts: &[(RuneInfo, u128)]) -> Result<(), DepositError> {
+        let limits = get_rune_limits_store();
+        for (rune_info, amount) in rune_amounts {
+            limits
+                .check_min_deposit(rune_info.name(), *amount)
+                .map_err(|below| DepositError::AmountBelowMinimum {
+                    rune_name: rune_info.name(),
+                    min_amount: below.min_amount,
+                    amount: below.amount,
+                })?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the deposited amounts are converted into the wrapped token units without a
+    /// remainder, so no deposited units are left unminted.
+    fn check_exact_amounts(&self, rune_amounts: &[(RuneInfo, u128)]) -> Result<(), DepositError> {
+        let state = self.state.borrow();
+        for (rune_info, amount) in rune_amounts {
+            let scaled = state
+                .amount_scaling(rune_info)
+                .rune_to_token(*amount)
+                .map_err(DepositError::Scaling)?;
+            if scaled.remainder > 0 {
+                return Err(DepositError::InexactAmount {
+                    rune_name: rune_info.name(),
+                    amount: *amount,
+                    remainder: scaled.remainder,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn check_supply_caps(
+        &self,
+        rune_amounts: &[(RuneInfo, u128)],
+    ) -> Result<(), DepositError> {
+        for (rune_info, amount) in rune_amounts {
+            if get_rune_limits_store()
+                .get(rune_info.name())
+                .supply_cap_bps
+                .is_none()
+            {
+                continue;
+            }
+
+            let supply = self.index_provider.get_rune_supply(rune_info.id()).await?;
+            get_rune_limits_store()
+                .check(rune_info.name(), *amount, &supply)
+                .map_err(|exceeded| DepositError::SupplyCapExceeded {
+                    rune_name: rune_info.name(),
+                    cap: exceeded.cap,
+                    bridged: exceeded.bridged,
+                    requested: exceeded.requested,
+                })?;
+        }
+
+        Ok(())
+    }
+
     async fn fill_rune_infos(
         &self,
         rune_amounts: &HashMap<RuneName, u128>,
@@ -669,9 +1349,18 @@ impl<UTXO: UtxoProvider, INDEX: RuneIndexProvider> RuneDeposit<UTXO, INDEX> {
         Some(infos)
     }
 
+    /// Returns `false` if the mint orders of the request are for another EVM than the one the
+    /// bridge is connected to.
+    fn is_sent_by_bridge(&self, request: &RuneDepositPayload) -> bool {
+        request.dst_chain_id.map_or(true, |chain_id| {
+            chain_id == self.state.borrow().erc20_chain_id()
+        })
+    }
+
     async fn create_mint_order(
         &self,
         eth_address: &H160,
+        dst_chain_id: Option<u32>,
         amount: u128,
         rune_info: RuneInfo,
         nonce: u32,
@@ -681,14 +1370,26 @@ impl<UTXO: UtxoProvider, INDEX: RuneIndexProvider> RuneDeposit<UTXO, INDEX> {
         let (signer, mint_order) = {
             let state_ref = self.state.borrow();
 
-            let sender_chain_id = state_ref.btc_chain_id();
+            let scaling = state_ref.amount_scaling(&rune_info);
+            let scaled = scaling
+                .rune_to_token(amount)
+                .map_err(DepositError::Scaling)?;
+            if scaled.remainder > 0 {
+                return Err(DepositError::InexactAmount {
+                    rune_name: rune_info.name(),
+                    amount,
+                    remainder: scaled.remainder,
+                });
+            }
+
+            let sender_chain_id = state_ref.rune_chain_id();
             let sender = Id256::from_evm_address(eth_address, sender_chain_id);
             let src_token = Id256::from(rune_info.id());
 
-            let recipient_chain_id = state_ref.erc20_chain_id();
+            let recipient_chain_id = state_ref.recipient_chain_id(dst_chain_id)?;
 
             let mint_order = MintOrder {
-                amount: amount.into(),
+                amount: scaled.amount.into(),
                 sender,
                 src_token,
                 recipient: eth_address.clone(),
@@ -698,7 +1399,7 @@ impl<UTXO: UtxoProvider, INDEX: RuneIndexProvider> RuneDeposit<UTXO, INDEX> {
                 recipient_chain_id,
                 name: rune_info.name_array(),
                 symbol: rune_info.symbol_array(),
-                decimals: rune_info.decimals(),
+                decimals: scaling.token_decimals(),
                 approve_spender: Default::default(),
                 approve_amount: Default::default(),
                 fee_payer: H160::default(),
@@ -720,13 +1421,14 @@ impl<UTXO: UtxoProvider, INDEX: RuneIndexProvider> RuneDeposit<UTXO, INDEX> {
     async fn create_mint_orders(
         &self,
         eth_address: &H160,
+        dst_chain_id: Option<u32>,
         rune_amounts: &[(RuneInfo, u128)],
     ) -> Result<Vec<MintOrderDetails>, DepositError> {
         let mut result = vec![];
         for (rune_info, amount) in rune_amounts {
             let nonce = self.get_nonce();
             let mint_order = self
-                .create_mint_order(eth_address, *amount, *rune_info, nonce)
+                .create_mint_order(eth_address, dst_chain_id, *amount, *rune_info, nonce)
                 .await?;
             result.push(MintOrderDetails {
                 rune_name: rune_info.name,
@@ -738,61 +1440,62 @@ impl<UTXO: UtxoProvider, INDEX: RuneIndexProvider> RuneDeposit<UTXO, INDEX> {
         Ok(result)
     }
 
-    async fn send_mint_order(&self, mint_order: &SignedMintOrder) -> Result<H256, DepositError> {
-        log::trace!("Sending mint transaction");
-
-        let signer = self.state.borrow().signer().get().clone();
-        let sender = signer
-            .get_address()
-            .await
-            .map_err(|err| DepositError::Sign(format!("{err:?}")))?;
-
-        let (evm_info, evm_params) = {
-            let state = self.state.borrow();
-
-            let evm_info = state.get_evm_info();
-            let evm_params = state
-                .get_evm_params()
-                .clone()
-                .ok_or(DepositError::NotInitialized)?;
+    /// Checks whether the BftBridge has minted the order already, by a transaction whose result
+    /// was lost or by the user, so it must not be sent again.
+    async fn is_mint_order_used(&self, mint_order: &SignedMintOrder) -> Result<bool, DepositError> {
+        let (order, _) = MintOrder::decode_signed(&mint_order.to_vec())
+            .ok_or_else(|| DepositError::Evm("invalid signed mint order".into()))?;
 
-            (evm_info, evm_params)
-        };
-
-        let mut tx = minter_contract_utils::bft_bridge_api::mint_transaction(
-            sender.0,
+        let evm_info = self.state.borrow().get_evm_info();
+        let client = evm_info.link.get_json_rpc_client();
+        let result = minter_contract_utils::bft_bridge_api::is_mint_order_used(
+            &client,
             evm_info.bridge_contract.0,
-            evm_params.nonce.into(),
-            evm_params.gas_price.into(),
-            &mint_order.to_vec(),
-            evm_params.chain_id as _,
-        );
+            &order.sender,
+            order.nonce,
+        )
+        .await;
+        // A revert of an older BftBridge without `isNonceUsed` is not an outage of the EVM, so
+        // only the answered checks are recorded.
+        if result.is_ok() {
+            self.record_call(EVM_RPC, true);
+        }
 
-        let signature = signer
-            .sign_transaction(&(&tx).into())
-            .await
-            .map_err(|err| DepositError::Sign(format!("{err:?}")))?;
+        result.map_err(|err| DepositError::Evm(format!("{err:?}")))
+    }
 
-        tx.r = signature.r.0;
-        tx.s = signature.s.0;
-        tx.v = signature.v.0;
-        tx.hash = tx.hash();
+    /// Records the outcome of a call to the `dependency` in the circuit breaker.
+    fn record_call(&self, dependency: &str, success: bool) {
+        let now = self.now();
+        self.state
+            .borrow_mut()
+            .circuit_breaker_mut()
+            .record(dependency, success, now);
+    }
 
-        let client = evm_info.link.get_json_rpc_client();
-        let id = client
-            .send_raw_transaction(tx)
-            .await
-            .map_err(|err| DepositError::Evm(format!("{err:?}")))?;
+    async fn send_mint_order(&self, mint_order: &SignedMintOrder) -> Result<H256, DepositError> {
+        log::trace!("Sending mint transaction");
 
-        self.state.borrow_mut().update_evm_params(|p| {
-            if let Some(params) = p.as_mut() {
-                params.nonce += 1;
-            }
-        });
+        let id = send_bridge_transaction(
+            &self.state,
+            GasOperation::Mint,
+            |sender, bridge, nonce, gas_price, gas_limit, chain_id| {
+                minter_contract_utils::bft_bridge_api::mint_transaction(
+                    sender,
+                    bridge,
+                    nonce,
+                    gas_price,
+                    gas_limit,
+                    &mint_order.to_vec(),
+                    chain_id,
+                )
+            },
+        )
+        .await?;
 
         log::trace!("Mint transaction sent");
 
-        Ok(id.into())
+        Ok(id)
     }
 
     fn filter_out_used_utxos(&self, get_utxos_response: &mut GetUtxosResponse) {
@@ -819,10 +1522,27 @@ impl<UTXO: UtxoProvider, INDEX: RuneIndexProvider> RuneDeposit<UTXO, INDEX> {
 
     fn mark_used_utxos(&self, utxos: &[Utxo], address: &Address) {
         let mut state = self.state.borrow_mut();
+        let now = state.clock().now();
         let ledger = state.ledger_mut();
         for utxo in utxos {
-            ledger.mark_as_used((&utxo.outpoint).into(), address.clone());
+            ledger.mark_as_used((&utxo.outpoint).into(), address.clone(), now);
+        }
+    }
+
+    /// Checks that the indexer is at most `indexer_max_lag` blocks behind the bitcoin `tip`, so
+    /// the rune balances it reports include the runes of the deposit utxos.
+    pub async fn check_indexer_height(&self, tip: u32) -> Result<(), DepositError> {
+        let max_lag = self.state.borrow().indexer_max_lag();
+        let indexer_height = self.index_provider.get_block_height().await?;
+        if indexer_height.saturating_add(max_lag) < tip {
+            log::warn!("Indexer at height {indexer_height} is behind the bitcoin tip {tip}");
+            return Err(DepositError::IndexerBehind {
+                indexer_height,
+                tip,
+            });
         }
+
+        Ok(())
     }
 
     pub async fn get_mint_amounts(
@@ -865,3 +1585,499 @@ impl<UTXO: UtxoProvider, INDEX: RuneIndexProvider> RuneDeposit<UTXO, INDEX> {
         Ok((rune_info_amounts, used_utxos))
     }
 }
+
+/// Value of a deposit the confirmation tier is selected by: the largest amount of a deposited
+/// rune, since the amounts of different runes are not comparable.
+fn rune_deposit_value(rune_amounts: &[(RuneInfo, u128)]) -> u64 {
+    rune_amounts
+        .iter()
+        .map(|(_, amount)| u64::try_from(*amount).unwrap_or(u64::MAX))
+        .max()
+        .unwrap_or_default()
+}
+
+/// Utxos claimed by a deposit request while its mint orders are signed. The claim is released
+/// when dropped.
+struct UtxoClaim {
+    outpoints: Vec<(Vec<u8>, u32)>,
+}
+
+impl UtxoClaim {
+    /// Claims the utxos for the request. Returns `None` if any of them is claimed by another one.
+    fn new(request_id: MinterOperationId, utxos: &[Utxo]) -> Option<Self> {
+        if utxos
+            .iter()
+            .any(|utxo| Self::is_claimed_by_other(request_id, utxo))
+        {
+            return None;
+        }
+
+        let outpoints: Vec<_> = utxos
+            .iter()
+            .map(|utxo| (utxo.outpoint.txid.clone(), utxo.outpoint.vout))
+            .collect();
+        SIGNING_UTXOS.with(|claims| {
+            let mut claims = claims.borrow_mut();
+            for outpoint in &outpoints {
+                claims.insert(outpoint.clone(), request_id);
+            }
+        });
+
+        Some(Self { outpoints })
+    }
+
+    /// Whether the request holds claims, i.e. its mint orders are being signed.
+    fn is_held_by(request_id: MinterOperationId) -> bool {
+        SIGNING_UTXOS.with(|claims| {
+            claims
+                .borrow()
+                .values()
+                .any(|claimed_by| *claimed_by == request_id)
+        })
+    }
+
+    fn is_claimed_by_other(request_id: MinterOperationId, utxo: &Utxo) -> bool {
+        SIGNING_UTXOS.with(|claims| {
+            claims
+                .borrow()
+                .get(&(utxo.outpoint.txid.clone(), utxo.outpoint.vout))
+                .is_some_and(|claimed_by| *claimed_by != request_id)
+        })
+    }
+}
+
+impl Drop for UtxoClaim {
+    fn drop(&mut self) {
+        SIGNING_UTXOS.with(|claims| {
+            let mut claims = claims.borrow_mut();
+            for outpoint in &self.outpoints {
+                claims.remove(outpoint);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitcoin::PrivateKey;
+    use ic_exports::ic_cdk::api::management_canister::bitcoin::Outpoint;
+    use ic_exports::ic_kit::MockContext;
+    use minter_contract_utils::clock::{Clock, MockClock};
+    use ord_rs::wallet::LocalSigner;
+
+    use super::*;
+    use crate::core::http_outcall::mock::MockHttpOutcall;
+    use crate::core::utxo_provider::mock::MockUtxoProvider;
+
+    const INDEXER_URL: &str = "https://indexer.com";
+
+    fn rune_name() -> RuneName {
+        RuneName::from_str("TESTRUNE").unwrap()
+    }
+
+    fn payload(status: DepositRequestStatus) -> RuneDepositPayload {
+        RuneDepositPayload {
+            dst_address: H160::from_slice(&[1; 20]),
+            requested_amounts: None,
+            request_ts: 0,
+            status,
+            refund_address: None,
+            refund: None,
+            dst_chain_id: None,
+            funding_txids: None,
+        }
+    }
+
+    #[test]
+    fn deposit_value_is_largest_rune_amount() {
+        let rune_info = RuneInfo {
+            name: rune_name(),
+            decimals: 0,
+            block: 1,
+            tx: 1,
+        };
+
+        assert_eq!(rune_deposit_value(&[]), 0);
+        assert_eq!(
+            rune_deposit_value(&[(rune_info, 100), (rune_info, 300), (rune_info, 200)]),
+            300
+        );
+        assert_eq!(rune_deposit_value(&[(rune_info, u128::MAX)]), u64::MAX);
+    }
+
+    #[test]
+    fn minted_deposit_is_completed_with_mint_txs() {
+        let mint_tx = MintTx {
+            tx_hash: H256::from_slice(&[2; 32]),
+            block_number: Some(10),
+        };
+        let minted = payload(DepositRequestStatus::Minted {
+            amounts: vec![(rune_name(), 100, mint_tx.tx_hash.clone())],
+            mint_txs: Some(vec![mint_tx.clone()]),
+        });
+
+        assert_eq!(
+            minted.deposit_status(),
+            DepositStatus::Completed {
+                mints: vec![mint_tx]
+            }
+        );
+    }
+
+    #[test]
+    fn deposit_minted_before_recording_txs_has_no_block_numbers() {
+        let tx_id = H256::from_slice(&[3; 32]);
+        let minted = payload(DepositRequestStatus::Minted {
+            amounts: vec![(rune_name(), 100, tx_id.clone())],
+            mint_txs: None,
+        });
+
+        assert_eq!(
+            minted.deposit_status(),
+            DepositStatus::Completed {
+                mints: vec![MintTx {
+                    tx_hash: tx_id,
+                    block_number: None,
+                }]
+            }
+        );
+    }
+
+    #[test]
+    fn unfinished_and_failed_deposits() {
+        assert_eq!(
+            payload(DepositRequestStatus::MintOrdersCreated { orders: vec![] }).deposit_status(),
+            DepositStatus::Pending
+        );
+        assert!(matches!(
+            payload(DepositRequestStatus::ScreeningRejected {
+                reason: "sanctioned".into()
+            })
+            .deposit_status(),
+            DepositStatus::Failed { .. }
+        ));
+    }
+
+    fn signer() -> BtcSignerType {
+        BtcSignerType::Local(LocalSigner::new(
+            PrivateKey::from_slice(&[1; 32], Network::Regtest).unwrap(),
+        ))
+    }
+
+    fn rune_utxo(txid_byte: u8) -> Utxo {
+        Utxo {
+            outpoint: Outpoint {
+                txid: vec![txid_byte; 32],
+                vout: 0,
+            },
+            value: 10_000,
+            height: 1,
+        }
+    }
+
+    fn indexer_with_runes(utxos: &[Utxo], amount: u128) -> MockHttpOutcall {
+        let mut http = MockHttpOutcall::default()
+            .with_response(
+                &format!("{INDEXER_URL}/runes"),
+                r#"{"entries":[["1:1",{"spaced_rune":"TEST•RUNE","divisibility":0}]]}"#,
+            )
+            .with_response(&format!("{INDEXER_URL}/blockheight"), "11");
+        for utxo in utxos {
+            http = http.with_response(
+                &format!("{INDEXER_URL}/output/{}:0", hex::encode(&utxo.outpoint.txid)),
+                format!(
+                    r#"{{"address":"bcrt1q","runes":[["TEST•RUNE",{{"amount":{amount},"divisibility":0,"symbol":null}}]],"spent":false}}"#
+                ),
+            );
+        }
+
+        http
+    }
+
+    fn deposit(
+        utxo_provider: MockUtxoProvider,
+        http: MockHttpOutcall,
+    ) -> RuneDeposit<MockUtxoProvider, OrdIndexProvider<MockHttpOutcall>> {
+        RuneDeposit {
+            state: get_state(),
+            scheduler: get_scheduler(),
+            network: Network::Regtest,
+            signer: signer(),
+            utxo_provider,
+            index_provider: OrdIndexProvider::with_http(INDEXER_URL.to_string(), http),
+            operation_store: get_operations_store(),
+        }
+    }
+
+    #[tokio::test]
+    async fn mint_amounts_are_collected_from_indexer() {
+        MockContext::new().inject();
+
+        let dst_address = H160::from_slice(&[1; 20]);
+        let transit_address = signer()
+            .get_transit_address(&dst_address, Network::Regtest)
+            .await;
+        let utxos = vec![rune_utxo(1), rune_utxo(2)];
+        let deposit = deposit(
+            MockUtxoProvider::default().with_utxos(&transit_address, utxos.clone(), 12),
+            indexer_with_runes(&utxos, 100),
+        );
+
+        let deposit_utxos = deposit.get_deposit_utxos(&transit_address).await.unwrap();
+        assert_eq!(deposit_utxos.utxos, utxos);
+        assert_eq!(deposit_utxos.tip_height, 12);
+
+        // The indexer at height 11 is within the allowed lag of one block.
+        deposit.check_indexer_height(12).await.unwrap();
+        assert!(matches!(
+            deposit.check_indexer_height(13).await,
+            Err(DepositError::IndexerBehind {
+                indexer_height: 11,
+                tip: 13
+            })
+        ));
+
+        let (amounts, used_utxos) = deposit.get_mint_amounts(&utxos, &None).await.unwrap();
+        assert_eq!(used_utxos, utxos);
+        assert_eq!(amounts.len(), 1);
+        assert_eq!(amounts[0].0.name(), rune_name());
+        assert_eq!(amounts[0].1, 200);
+        assert!(get_state().borrow().runes().contains_key(&rune_name()));
+
+        let requested = Some(HashMap::from([(rune_name(), 100)]));
+        assert!(matches!(
+            deposit.get_mint_amounts(&utxos, &requested).await,
+            Err(DepositError::InvalidAmounts { .. })
+        ));
+    }
+
+    #[test]
+    fn utxos_are_attributed_to_deposit_sessions() {
+        MockContext::new().inject();
+
+        let dst_address = H160::from_slice(&[1; 20]);
+        let mut deposit = deposit(MockUtxoProvider::default(), MockHttpOutcall::default());
+        let utxos = vec![rune_utxo(1), rune_utxo(2)];
+        let announced_txid = screening::display_txid(&utxos[1].outpoint.txid);
+
+        let first = deposit.create_deposit_request(dst_address.clone(), None, None, None, None);
+        let announced = deposit.create_deposit_request(
+            dst_address.clone(),
+            None,
+            None,
+            None,
+            Some(vec![announced_txid.to_uppercase()]),
+        );
+        let second = deposit.create_deposit_request(dst_address.clone(), None, None, None, None);
+
+        let session_utxos = |request_id| {
+            let Some(OperationState::Deposit(request)) = deposit.operation_store.get(request_id)
+            else {
+                panic!("deposit request {request_id} not found");
+            };
+            let response = GetUtxosResponse {
+                utxos: utxos.clone(),
+                tip_block_hash: vec![],
+                tip_height: 12,
+                next_page: None,
+            };
+            deposit.session_utxos(request_id, &request, response).utxos
+        };
+
+        assert_eq!(session_utxos(announced), vec![utxos[1].clone()]);
+        assert_eq!(session_utxos(first), vec![utxos[0].clone()]);
+        assert!(session_utxos(second).is_empty());
+
+        let claim = UtxoClaim::new(announced, &utxos[1..]);
+        assert!(claim.is_some());
+        assert!(UtxoClaim::new(second, &utxos).is_none());
+        drop(claim);
+        assert!(UtxoClaim::new(second, &utxos).is_some());
+    }
+
+    fn deposit_status<UTXO: UtxoProvider, INDEX: RuneIndexProvider>(
+        deposit: &RuneDeposit<UTXO, INDEX>,
+        request_id: MinterOperationId,
+    ) -> DepositRequestStatus {
+        match deposit.operation_store.get(request_id) {
+            Some(OperationState::Deposit(payload)) => payload.status,
+            other => panic!("unexpected operation {other:?}"),
+        }
+    }
+
+    #[test]
+    fn waiting_deposit_is_cancelled_by_recipient() {
+        MockContext::new().inject();
+
+        let dst_address = H160::from_slice(&[1; 20]);
+        let mut deposit = deposit(MockUtxoProvider::default(), MockHttpOutcall::default());
+        let request_id =
+            deposit.create_deposit_request(dst_address.clone(), None, None, None, None);
+
+        assert_eq!(
+            deposit.cancel_deposit(request_id, Some(&H160::from_slice(&[2; 20]))),
+            Err(CancelDepositError::NotAuthorized)
+        );
+        assert_eq!(
+            deposit.cancel_deposit(request_id, Some(&dst_address)),
+            Ok(())
+        );
+        assert_eq!(
+            deposit.cancel_deposit(request_id, None),
+            Err(CancelDepositError::Finished)
+        );
+
+        deposit.wait_for_inputs(
+            request_id,
+            DepositRequestStatus::NothingToDeposit { block_height: 1 },
+        );
+        let status = deposit_status(&deposit, request_id);
+        assert!(matches!(status, DepositRequestStatus::Cancelled { .. }));
+        assert!(payload(status).is_complete());
+    }
+
+    #[test]
+    fn waiting_deposit_times_out_by_clock() {
+        MockContext::new().inject();
+
+        let clock = MockClock::new(1_000);
+        get_state().borrow_mut().clock = Rc::new(clock.clone());
+        let mut deposit = deposit(MockUtxoProvider::default(), MockHttpOutcall::default());
+        let request_id =
+            deposit.create_deposit_request(H160::from_slice(&[1; 20]), None, None, None, None);
+        let bail_status = DepositRequestStatus::NothingToDeposit { block_height: 1 };
+
+        clock.advance(Duration::from_secs(60));
+        deposit.wait_for_inputs(request_id, bail_status.clone());
+        let DepositRequestStatus::WaitingForInputs {
+            requested_at,
+            current_ts,
+            next_retry_at,
+            waiting_until,
+            ..
+        } = deposit_status(&deposit, request_id)
+        else {
+            panic!("deposit is not waiting for inputs");
+        };
+        assert_eq!(requested_at, 1_000);
+        assert_eq!(current_ts, clock.now());
+        assert_eq!(
+            next_retry_at,
+            clock.now() + deposit.deposit_retry_interval().as_nanos() as u64
+        );
+        assert_eq!(
+            waiting_until,
+            1_000 + deposit.request_timeout().as_nanos() as u64
+        );
+
+        clock.set(waiting_until + 1);
+        deposit.wait_for_inputs(request_id, bail_status);
+        assert!(matches!(
+            deposit_status(&deposit, request_id),
+            DepositRequestStatus::NothingToDeposit { block_height: 1 }
+        ));
+    }
+
+    #[test]
+    fn unsigned_deposit_is_abandoned_after_expiry() {
+        MockContext::new().inject();
+
+        let clock = MockClock::new(1_000);
+        get_state().borrow_mut().clock = Rc::new(clock.clone());
+        let mut deposit = deposit(MockUtxoProvider::default(), MockHttpOutcall::default());
+        let waiting =
+            deposit.create_deposit_request(H160::from_slice(&[1; 20]), None, None, None, None);
+        let mut request = match deposit.operation_store.get(waiting) {
+            Some(OperationState::Deposit(request)) => request,
+            other => panic!("unexpected operation {other:?}"),
+        };
+        request.status = DepositRequestStatus::WaitingForConfirmations {
+            utxos: vec![],
+            current_min_confirmations: 0,
+            required_confirmations: 12,
+            block_height: 100,
+        };
+        deposit
+            .operation_store
+            .update(waiting, OperationState::Deposit(request));
+
+        let expiry = get_state().borrow().deposit_expiry();
+        clock.advance(expiry);
+        let recent =
+            deposit.create_deposit_request(H160::from_slice(&[2; 20]), None, None, None, None);
+        assert_eq!(deposit.abandon_expired_deposits(), 0);
+
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(deposit.abandon_expired_deposits(), 1);
+        let status = deposit_status(&deposit, waiting);
+        let DepositRequestStatus::Abandoned { details, .. } = &status else {
+            panic!("deposit is not abandoned: {status:?}");
+        };
+        assert_eq!(details, "utxos got 0 of 12 confirmations");
+        assert!(payload(status).is_complete());
+        assert!(matches!(
+            deposit_status(&deposit, recent),
+            DepositRequestStatus::Scheduled
+        ));
+
+        // The abandoned deposit ignores the late updates.
+        deposit.wait_for_inputs(
+            waiting,
+            DepositRequestStatus::NothingToDeposit { block_height: 1 },
+        );
+        assert!(matches!(
+            deposit_status(&deposit, waiting),
+            DepositRequestStatus::Abandoned { .. }
+        ));
+
+        let stats = get_state().borrow().deposit_gc_stats().clone();
+        assert_eq!(stats.abandoned_total, 1);
+        assert_eq!(stats.last_abandoned, 1);
+        assert_eq!(stats.pending, 1);
+        assert_eq!(stats.last_run_at, Some(clock.now()));
+    }
+
+    #[test]
+    fn signed_deposit_is_not_cancelled() {
+        MockContext::new().inject();
+
+        let mut deposit = deposit(MockUtxoProvider::default(), MockHttpOutcall::default());
+        let request_id = deposit.operation_store.new_operation(
+            H160::from_slice(&[1; 20]),
+            OperationState::Deposit(payload(DepositRequestStatus::MintOrdersCreated {
+                orders: vec![],
+            })),
+        );
+
+        assert_eq!(
+            deposit.cancel_deposit(request_id, None),
+            Err(CancelDepositError::MintOrdersSigned)
+        );
+        assert!(matches!(
+            deposit_status(&deposit, request_id),
+            DepositRequestStatus::MintOrdersCreated { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn deposit_without_runes_is_rejected() {
+        MockContext::new().inject();
+
+        let utxos = vec![rune_utxo(3)];
+        let http = MockHttpOutcall::default().with_response(
+            &format!(
+                "{INDEXER_URL}/output/{}:0",
+                hex::encode(&utxos[0].outpoint.txid)
+            ),
+            r#"{"address":"bcrt1q","runes":[],"spent":false}"#,
+        );
+        let deposit = deposit(MockUtxoProvider::default(), http);
+
+        assert!(matches!(
+            deposit.get_mint_amounts(&utxos, &None).await,
+            Err(DepositError::NoRunesToDeposit)
+        ));
+    }
+}
@@ -0,0 +1,380 @@
+//! HTTPS outcalls of the bridge, e.g. to the ord indexer or to the screening API.
+//!
+//! The requests are made through the [`HttpOutcall`] trait, so the code building the requests
+//! and parsing the responses can be tested natively with [`mock::MockHttpOutcall`] instead of the
+//! management canister.
+//!
+//! The size of a response is not known in advance, and the cost of an outcall grows with its
+//! `max_response_bytes`, so [`request_json`] starts with a small limit and repeats the request
+//! with a larger one while the response doesn't fit, up to the hard cap of the [`OutcallPolicy`].
+//! The responses may be gzip compressed.
+
+use std::fmt;
+use std::io::Read;
+
+use candid::Nat;
+use flate2::read::GzDecoder;
+use ic_exports::ic_cdk::api::management_canister::http_request::{
+    http_request, CanisterHttpRequestArgument, HttpHeader, HttpResponse,
+};
+use serde::de::DeserializeOwned;
+
+/// Maximum response size of an outcall allowed by the IC.
+pub const MAX_OUTCALL_RESPONSE_BYTES: u64 = 2_000_000;
+
+/// Cycles attached to an outcall on top of the cost of its response bytes.
+const BASE_OUTCALL_CYCLES: u128 = 400_000_000;
+/// Cost of a byte of the response limit on a 13 node subnet.
+const RESPONSE_BYTE_CYCLES: u128 = 10_400;
+
+/// Growth factor of the response limit between the attempts.
+const RESPONSE_BYTES_MULTIPLIER: u64 = 4;
+
+pub(crate) trait HttpOutcall {
+    async fn request(
+        &self,
+        request: CanisterHttpRequestArgument,
+        cycles: u128,
+    ) -> Result<HttpResponse, String>;
+}
+
+/// Outcalls through the management canister.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct IcHttpOutcall;
+
+impl HttpOutcall for IcHttpOutcall {
+    async fn request(
+        &self,
+        request: CanisterHttpRequestArgument,
+        cycles: u128,
+    ) -> Result<HttpResponse, String> {
+        http_request(request, cycles)
+            .await
+            .map(|(response,)| response)
+            .map_err(|(code, message)| format!("{code:?}: {message}"))
+    }
+}
+
+/// Size limits and retries of the outcalls to a service.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutcallPolicy {
+    /// Response limit of the first attempt.
+    pub initial_response_bytes: u64,
+    /// Hard cap of the response limit. The responses are decompressed up to the same size.
+    pub max_response_bytes: u64,
+    /// Number of times a timed out request is repeated.
+    pub timeout_retries: u32,
+    /// Whether the service is asked for gzip compressed responses.
+    pub gzip: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutcallError {
+    /// The response doesn't fit the hard cap of the policy.
+    ResponseTooLarge {
+        max_response_bytes: u64,
+    },
+    Timeout(String),
+    /// The request was rejected, or the service responded with an error status.
+    Failed(String),
+    /// The response body is not a valid gzip stream or JSON of the expected type.
+    Decode(String),
+}
+
+impl fmt::Display for OutcallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ResponseTooLarge { max_response_bytes } => {
+                write!(f, "response exceeds {max_response_bytes} bytes")
+            }
+            Self::Timeout(message) => write!(f, "request timed out: {message}"),
+            Self::Failed(message) => write!(f, "request failed: {message}"),
+            Self::Decode(message) => write!(f, "unexpected response: {message}"),
+        }
+    }
+}
+
+impl OutcallError {
+    /// Classifies the rejection of an outcall by its message.
+    fn from_reject(message: String, max_response_bytes: u64) -> Self {
+        let lowercase = message.to_lowercase();
+        if lowercase.contains("size limit") || lowercase.contains("exceeds") {
+            Self::ResponseTooLarge { max_response_bytes }
+        } else if lowercase.contains("timeout") || lowercase.contains("timed out") {
+            Self::Timeout(message)
+        } else {
+            Self::Failed(message)
+        }
+    }
+}
+
+/// Cycles to attach to an outcall with the response limit.
+pub fn outcall_cycles(max_response_bytes: u64) -> u128 {
+    BASE_OUTCALL_CYCLES + RESPONSE_BYTE_CYCLES * u128::from(max_response_bytes)
+}
+
+/// Makes the `request` following the `policy` and decodes its JSON response.
+pub(crate) async fn request_json<R: DeserializeOwned>(
+    http: &impl HttpOutcall,
+    request: CanisterHttpRequestArgument,
+    policy: &OutcallPolicy,
+) -> Result<R, OutcallError> {
+    let body = request_body(http, request, policy).await?;
+    serde_json::from_slice(&body).map_err(|err| OutcallError::Decode(err.to_string()))
+}
+
+/// Makes the `request` following the `policy` and returns the decompressed body of a successful
+/// response.
+pub(crate) async fn request_body(
+    http: &impl HttpOutcall,
+    mut request: CanisterHttpRequestArgument,
+    policy: &OutcallPolicy,
+) -> Result<Vec<u8>, OutcallError> {
+    let max_response_bytes = policy.max_response_bytes.min(MAX_OUTCALL_RESPONSE_BYTES);
+    let mut response_bytes = policy.initial_response_bytes.min(max_response_bytes);
+    let mut timeouts = 0;
+    if policy.gzip {
+        request.headers.push(HttpHeader {
+            name: "Accept-Encoding".to_string(),
+            value: "gzip".to_string(),
+        });
+    }
+
+    let response = loop {
+        request.max_response_bytes = Some(response_bytes);
+        let result = http
+            .request(request.clone(), outcall_cycles(response_bytes))
+            .await
+            .map_err(|message| OutcallError::from_reject(message, response_bytes));
+
+        match result {
+            Ok(response) => break response,
+            Err(OutcallError::ResponseTooLarge { .. }) if response_bytes < max_response_bytes => {
+                response_bytes = response_bytes
+                    .saturating_mul(RESPONSE_BYTES_MULTIPLIER)
+                    .min(max_response_bytes);
+                log::debug!(
+                    "Response of {} exceeds the limit, retrying with {response_bytes} bytes",
+                    request.url
+                );
+            }
+            Err(OutcallError::Timeout(message)) if timeouts < policy.timeout_retries => {
+                timeouts += 1;
+                log::debug!("Request to {} timed out: {message}, retrying", request.url);
+            }
+            Err(err) => return Err(err),
+        }
+    };
+
+    if !(Nat::from(200u64)..Nat::from(300u64)).contains(&response.status) {
+        return Err(OutcallError::Failed(format!(
+            "status {}: {}",
+            response.status,
+            String::from_utf8_lossy(&response.body)
+        )));
+    }
+
+    if is_gzip(&response) {
+        decompress(&response.body, max_response_bytes)
+    } else {
+        Ok(response.body)
+    }
+}
+
+fn is_gzip(response: &HttpResponse) -> bool {
+    response.headers.iter().any(|header| {
+        header.name.eq_ignore_ascii_case("content-encoding")
+            && header.value.eq_ignore_ascii_case("gzip")
+    })
+}
+
+fn decompress(body: &[u8], max_bytes: u64) -> Result<Vec<u8>, OutcallError> {
+    let mut decompressed = Vec::new();
+    GzDecoder::new(body)
+        .take(max_bytes + 1)
+        .read_to_end(&mut decompressed)
+        .map_err(|err| OutcallError::Decode(format!("invalid gzip body: {err}")))?;
+    if decompressed.len() as u64 > max_bytes {
+        return Err(OutcallError::ResponseTooLarge {
+            max_response_bytes: max_bytes,
+        });
+    }
+
+    Ok(decompressed)
+}
+
+#[cfg(test)]
+pub(crate) mod mock {
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    use super::*;
+
+    /// Responds to the requests with the bodies registered for their urls, and fails the
+    /// requests to the other urls. Like the IC, rejects the requests with a response larger than
+    /// their `max_response_bytes`.
+    #[derive(Debug, Default)]
+    pub struct MockHttpOutcall {
+        responses: HashMap<String, (Vec<HttpHeader>, Vec<u8>)>,
+        requests: RefCell<Vec<CanisterHttpRequestArgument>>,
+    }
+
+    impl MockHttpOutcall {
+        pub fn with_response(mut self, url: &str, body: impl Into<Vec<u8>>) -> Self {
+            self.responses
+                .insert(url.to_string(), (vec![], body.into()));
+            self
+        }
+
+        /// Registers a gzip compressed response with the `body`.
+        pub fn with_gzip_response(mut self, url: &str, body: impl AsRef<[u8]>) -> Self {
+            use std::io::Write;
+
+            let mut encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(body.as_ref()).unwrap();
+            let headers = vec![HttpHeader {
+                name: "Content-Encoding".to_string(),
+                value: "gzip".to_string(),
+            }];
+            self.responses
+                .insert(url.to_string(), (headers, encoder.finish().unwrap()));
+            self
+        }
+
+        /// Requests received by the mock, in the order they were made.
+        pub fn requests(&self) -> Vec<CanisterHttpRequestArgument> {
+            self.requests.borrow().clone()
+        }
+    }
+
+    impl HttpOutcall for MockHttpOutcall {
+        async fn request(
+            &self,
+            request: CanisterHttpRequestArgument,
+            _cycles: u128,
+        ) -> Result<HttpResponse, String> {
+            let response = self.responses.get(&request.url).cloned();
+            let url = request.url.clone();
+            let max_response_bytes = request.max_response_bytes;
+            self.requests.borrow_mut().push(request);
+
+            let (headers, body) = response.ok_or_else(|| format!("no response for {url}"))?;
+            if let Some(max_response_bytes) = max_response_bytes {
+                if body.len() as u64 > max_response_bytes {
+                    return Err(format!(
+                        "SysFatal: Http body exceeds size limit of {max_response_bytes} bytes."
+                    ));
+                }
+            }
+
+            Ok(HttpResponse {
+                status: 200u64.into(),
+                headers,
+                body,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ic_exports::ic_cdk::api::management_canister::http_request::HttpMethod;
+
+    use super::mock::MockHttpOutcall;
+    use super::*;
+
+    const URL: &str = "https://service.com/data";
+
+    const POLICY: OutcallPolicy = OutcallPolicy {
+        initial_response_bytes: 100,
+        max_response_bytes: 2_000,
+        timeout_retries: 1,
+        gzip: false,
+    };
+
+    fn request() -> CanisterHttpRequestArgument {
+        CanisterHttpRequestArgument {
+            url: URL.to_string(),
+            max_response_bytes: None,
+            method: HttpMethod::GET,
+            headers: vec![],
+            body: None,
+            transform: None,
+        }
+    }
+
+    fn large_body(len: usize) -> String {
+        format!("\"{}\"", "a".repeat(len - 2))
+    }
+
+    #[tokio::test]
+    async fn response_limit_grows_up_to_the_cap() {
+        let body = large_body(1_000);
+        let http = MockHttpOutcall::default().with_response(URL, body.clone());
+
+        let response: String = request_json(&http, request(), &POLICY).await.unwrap();
+        assert_eq!(response.len(), 998);
+        let limits: Vec<_> = http
+            .requests()
+            .iter()
+            .map(|request| request.max_response_bytes)
+            .collect();
+        assert_eq!(limits, vec![Some(100), Some(400), Some(1_600)]);
+
+        let http = MockHttpOutcall::default().with_response(URL, large_body(3_000));
+        assert_eq!(
+            request_json::<String>(&http, request(), &POLICY).await,
+            Err(OutcallError::ResponseTooLarge {
+                max_response_bytes: 2_000
+            })
+        );
+        assert_eq!(http.requests().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn gzip_responses_are_decompressed() {
+        let policy = OutcallPolicy {
+            gzip: true,
+            ..POLICY
+        };
+        let http = MockHttpOutcall::default().with_gzip_response(URL, large_body(1_500));
+
+        let response: String = request_json(&http, request(), &policy).await.unwrap();
+        assert_eq!(response.len(), 1_498);
+        let requests = http.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0]
+            .headers
+            .iter()
+            .any(|header| header.name == "Accept-Encoding" && header.value == "gzip"));
+
+        let http = MockHttpOutcall::default().with_gzip_response(URL, large_body(2_500));
+        assert_eq!(
+            request_json::<String>(&http, request(), &policy).await,
+            Err(OutcallError::ResponseTooLarge {
+                max_response_bytes: 2_000
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn failures_are_classified() {
+        let http = MockHttpOutcall::default().with_response(URL, "not json");
+        assert!(matches!(
+            request_json::<String>(&http, request(), &POLICY).await,
+            Err(OutcallError::Decode(_))
+        ));
+
+        let http = MockHttpOutcall::default();
+        assert!(matches!(
+            request_json::<String>(&http, request(), &POLICY).await,
+            Err(OutcallError::Failed(_))
+        ));
+
+        assert!(matches!(
+            OutcallError::from_reject("SysTransient: Timeout expired".into(), 100),
+            OutcallError::Timeout(_)
+        ));
+    }
+}
@@ -2,30 +2,51 @@ use std::collections::HashMap;
 
 use ic_exports::ic_cdk::api::management_canister::bitcoin::{Outpoint, Utxo};
 use ic_exports::ic_cdk::api::management_canister::http_request::{
-    http_request, CanisterHttpRequestArgument, HttpHeader, HttpMethod,
+    CanisterHttpRequestArgument, HttpHeader, HttpMethod,
 };
 use ordinals::{RuneId, SpacedRune};
 use serde::de::DeserializeOwned;
 use serde::Deserialize;
 
+use crate::core::http_outcall::{self, HttpOutcall, IcHttpOutcall, OutcallError, OutcallPolicy};
 use crate::interface::{DepositError, OutputResponse};
 use crate::rune_info::RuneName;
+use crate::rune_limits::RuneSupply;
 
 pub(crate) trait RuneIndexProvider {
     async fn get_rune_amounts(&self, utxo: &Utxo) -> Result<HashMap<RuneName, u128>, DepositError>;
     async fn get_rune_list(&self) -> Result<Vec<(RuneId, SpacedRune, u8)>, DepositError>;
+    /// Checks if the rune can be minted in the next block according to its open mint terms.
+    async fn is_mintable(&self, rune_id: RuneId) -> Result<bool, DepositError>;
+    async fn get_rune_supply(&self, rune_id: RuneId) -> Result<RuneSupply, DepositError>;
+    /// Height of the latest block processed by the indexer.
+    async fn get_block_height(&self) -> Result<u32, DepositError>;
 }
 
-const CYCLES_PER_HTTP_REQUEST: u128 = 500_000_000;
-const MAX_RESPONSE_BYTES: u64 = 10_000;
+/// Most of the indexer responses fit the initial limit, but the outputs with many runes and the
+/// rune lists may take much more.
+const INDEXER_OUTCALL_POLICY: OutcallPolicy = OutcallPolicy {
+    initial_response_bytes: 10_000,
+    max_response_bytes: 1_000_000,
+    timeout_retries: 1,
+    gzip: true,
+};
 
-pub struct OrdIndexProvider {
+pub struct OrdIndexProvider<HTTP = IcHttpOutcall> {
     indexer_url: String,
+    http: HTTP,
 }
 
 impl OrdIndexProvider {
     pub fn new(indexer_url: String) -> Self {
-        Self { indexer_url }
+        Self::with_http(indexer_url, IcHttpOutcall)
+    }
+}
+
+#[allow(private_bounds)]
+impl<HTTP: HttpOutcall> OrdIndexProvider<HTTP> {
+    pub fn with_http(indexer_url: String, http: HTTP) -> Self {
+        Self { indexer_url, http }
     }
 
     fn indexer_url(&self) -> &str {
@@ -40,7 +61,7 @@ impl OrdIndexProvider {
 
         let request_params = CanisterHttpRequestArgument {
             url,
-            max_response_bytes: Some(MAX_RESPONSE_BYTES),
+            max_response_bytes: None,
             method: HttpMethod::GET,
             headers: vec![HttpHeader {
                 name: "Accept".to_string(),
@@ -50,21 +71,21 @@ impl OrdIndexProvider {
             transform: None,
         };
 
-        let result = http_request(request_params, CYCLES_PER_HTTP_REQUEST)
+        let body = http_outcall::request_body(&self.http, request_params, &INDEXER_OUTCALL_POLICY)
             .await
-            .map_err(|err| DepositError::Unavailable(format!("Indexer unavailable: {err:?}")))?
-            .0;
+            .map_err(|err| {
+                log::warn!("Indexer request to {uri} failed: {err}");
+                DepositError::Unavailable(format!("Indexer unavailable: {err}"))
+            })?;
 
-        log::trace!(
-            "Indexer responded with: {} {:?} BODY: {}",
-            result.status,
-            result.headers,
-            String::from_utf8_lossy(&result.body)
-        );
+        log::trace!("Indexer responded with: {}", String::from_utf8_lossy(&body));
 
-        serde_json::from_slice(&result.body).map_err(|err| {
+        serde_json::from_slice(&body).map_err(|err| {
             log::error!("Failed to get rune balance from the indexer: {err:?}");
-            DepositError::Unavailable(format!("Unexpected response from indexer: {err:?}"))
+            DepositError::Unavailable(format!(
+                "Unexpected response from indexer: {}",
+                OutcallError::Decode(err.to_string())
+            ))
         })
     }
 
@@ -72,9 +93,17 @@ impl OrdIndexProvider {
         let outpoint = format_outpoint(&utxo.outpoint);
         self.http_request(&format!("output/{outpoint}")).await
     }
+
+    /// Checks that the indexer is reachable by requesting its latest indexed block height.
+    pub async fn check_availability(&self) -> Result<(), String> {
+        self.http_request::<u64>("blockheight")
+            .await
+            .map(|_| ())
+            .map_err(|err| format!("{err:?}"))
+    }
 }
 
-impl RuneIndexProvider for OrdIndexProvider {
+impl<HTTP: HttpOutcall> RuneIndexProvider for OrdIndexProvider<HTTP> {
     async fn get_rune_amounts(&self, utxo: &Utxo) -> Result<HashMap<RuneName, u128>, DepositError> {
         log::trace!(
             "Requesting rune balances for utxo {}:",
@@ -119,6 +148,54 @@ impl RuneIndexProvider for OrdIndexProvider {
             .map(|(rune_id, info)| (rune_id, info.spaced_rune, info.divisibility))
             .collect())
     }
+
+    async fn is_mintable(&self, rune_id: RuneId) -> Result<bool, DepositError> {
+        #[derive(Debug, Clone, Deserialize)]
+        struct RuneResponse {
+            mintable: bool,
+        }
+
+        let response: RuneResponse = self.http_request(&format!("rune/{rune_id}")).await?;
+
+        Ok(response.mintable)
+    }
+
+    async fn get_rune_supply(&self, rune_id: RuneId) -> Result<RuneSupply, DepositError> {
+        #[derive(Debug, Clone, Deserialize)]
+        struct MintTerms {
+            amount: Option<u128>,
+        }
+
+        #[derive(Debug, Clone, Deserialize)]
+        struct RuneEntry {
+            burned: u128,
+            mints: u128,
+            premine: u128,
+            terms: Option<MintTerms>,
+        }
+
+        #[derive(Debug, Clone, Deserialize)]
+        struct RuneResponse {
+            entry: RuneEntry,
+        }
+
+        let response: RuneResponse = self.http_request(&format!("rune/{rune_id}")).await?;
+        let entry = response.entry;
+        let mint_amount = entry
+            .terms
+            .and_then(|terms| terms.amount)
+            .unwrap_or_default();
+
+        Ok(RuneSupply {
+            premine: entry.premine,
+            minted: entry.mints.saturating_mul(mint_amount),
+            burned: entry.burned,
+        })
+    }
+
+    async fn get_block_height(&self) -> Result<u32, DepositError> {
+        self.http_request("blockheight").await
+    }
 }
 
 fn format_outpoint(outpoint: &Outpoint) -> String {
@@ -134,7 +211,137 @@ fn format_outpoint(outpoint: &Outpoint) -> String {
 
 #[cfg(test)]
 mod tests {
+    use std::str::FromStr;
+
     use super::*;
+    use crate::core::http_outcall::mock::MockHttpOutcall;
+
+    const INDEXER_URL: &str = "https://indexer.com";
+
+    fn provider(http: MockHttpOutcall) -> OrdIndexProvider<MockHttpOutcall> {
+        OrdIndexProvider::with_http(INDEXER_URL.to_string(), http)
+    }
+
+    #[tokio::test]
+    async fn rune_amounts_are_parsed_from_outputs() {
+        let utxo = Utxo {
+            outpoint: Outpoint {
+                txid: vec![1; 32],
+                vout: 0,
+            },
+            value: 10_000,
+            height: 0,
+        };
+        let url = format!("{INDEXER_URL}/output/{}", format_outpoint(&utxo.outpoint));
+        let http = MockHttpOutcall::default().with_response(
+            &url,
+            r#"{"address":"bc1q","runes":[["TEST•RUNE",{"amount":100,"divisibility":2,"symbol":null}]],"spent":false}"#,
+        );
+        let provider = provider(http);
+
+        let amounts = provider.get_rune_amounts(&utxo).await.unwrap();
+        assert_eq!(
+            amounts,
+            HashMap::from([(RuneName::from_str("TESTRUNE").unwrap(), 100)])
+        );
+        assert_eq!(provider.http.requests()[0].method, HttpMethod::GET);
+    }
+
+    #[tokio::test]
+    async fn rune_list_and_mint_terms_are_parsed() {
+        let http = MockHttpOutcall::default()
+            .with_response(
+                &format!("{INDEXER_URL}/runes"),
+                r#"{"entries":[["840000:1",{"spaced_rune":"TEST•RUNE","divisibility":2}]]}"#,
+            )
+            .with_response(
+                &format!("{INDEXER_URL}/rune/840000:1"),
+                r#"{"mintable":true}"#,
+            );
+        let provider = provider(http);
+
+        let runes = provider.get_rune_list().await.unwrap();
+        assert_eq!(
+            runes,
+            vec![(
+                RuneId {
+                    block: 840000,
+                    tx: 1
+                },
+                SpacedRune::from_str("TEST•RUNE").unwrap(),
+                2
+            )]
+        );
+        assert!(provider.is_mintable(runes[0].0).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn rune_supply_is_parsed_from_entry() {
+        let http = MockHttpOutcall::default().with_response(
+            &format!("{INDEXER_URL}/rune/840000:1"),
+            r#"{"entry":{"burned":50,"divisibility":2,"mints":10,"premine":1000,"spaced_rune":"TEST•RUNE","terms":{"amount":100,"cap":20}},"mintable":true}"#,
+        );
+        let provider = provider(http);
+        let rune_id = RuneId {
+            block: 840000,
+            tx: 1,
+        };
+
+        let supply = provider.get_rune_supply(rune_id).await.unwrap();
+        assert_eq!(
+            supply,
+            RuneSupply {
+                premine: 1000,
+                minted: 1000,
+                burned: 50,
+            }
+        );
+        assert_eq!(supply.circulating(), 1950);
+    }
+
+    #[tokio::test]
+    async fn large_outputs_are_fetched_with_larger_limit() {
+        let utxo = Utxo {
+            outpoint: Outpoint {
+                txid: vec![2; 32],
+                vout: 1,
+            },
+            value: 10_000,
+            height: 0,
+        };
+        let runes: Vec<String> = (0..300)
+            .map(|i| {
+                let rune = ordinals::Rune(u128::from(i) + 100_000).to_string();
+                format!(r#"["{rune}",{{"amount":{i},"divisibility":0,"symbol":null}}]"#)
+            })
+            .collect();
+        let body = format!(
+            r#"{{"address":"bc1q","runes":[{}],"spent":false}}"#,
+            runes.join(",")
+        );
+        assert!(body.len() as u64 > INDEXER_OUTCALL_POLICY.initial_response_bytes);
+
+        let url = format!("{INDEXER_URL}/output/{}", format_outpoint(&utxo.outpoint));
+        let provider = provider(MockHttpOutcall::default().with_response(&url, body));
+
+        let amounts = provider.get_rune_amounts(&utxo).await.unwrap();
+        assert_eq!(amounts.len(), 300);
+        assert_eq!(provider.http.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn unreachable_indexer_is_unavailable() {
+        let provider = provider(MockHttpOutcall::default());
+        assert!(matches!(
+            provider.get_rune_list().await,
+            Err(DepositError::Unavailable(_))
+        ));
+        assert!(provider.check_availability().await.is_err());
+        assert!(matches!(
+            provider.get_block_height().await,
+            Err(DepositError::Unavailable(_))
+        ));
+    }
 
     #[test]
     fn ic_outpoint_formatting() {
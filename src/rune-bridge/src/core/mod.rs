@@ -5,8 +5,17 @@ use serde::Deserialize;
 
 use crate::rune_info::RuneName;
 
+pub mod bft_deploy;
+pub mod bridge_tx;
+pub mod burn_permit;
+pub mod coin_selection;
 pub mod deposit;
+pub mod http_outcall;
 pub mod index_provider;
+pub mod open_mint;
+pub mod refund;
+pub mod rescan;
+pub mod screening;
 pub mod utxo_provider;
 pub mod withdrawal;
 
@@ -0,0 +1,363 @@
+//! Minting of runes with open mint terms by the bridge on behalf of the user.
+//!
+//! The user sends BTC to their deposit address and asks the bridge to mint a rune. The bridge
+//! spends the BTC utxos of the deposit address in a transaction with a mint runestone, which sends
+//! the minted runes and the BTC change back to the same address. A deposit request is then created
+//! for the address, so the minted runes are wrapped to the user's EVM address as soon as the mint
+//! transaction is confirmed.
+//!
+//! The mint spends the BTC of the user, so it is requested with the `OPEN_MINT_TYPE` notification
+//! sent through the BftBridge from the EVM address of the deposit, or by the admin.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use bitcoin::absolute::LockTime;
+use bitcoin::hashes::Hash;
+use bitcoin::transaction::Version;
+use bitcoin::{
+    Address, Amount, FeeRate, Network, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut,
+    Txid, Witness,
+};
+use did::H160;
+use ord_rs::wallet::{ScriptType, TxInputInfo};
+use ord_rs::OrdTransactionBuilder;
+use ordinals::{RuneId, Runestone};
+
+use crate::core::index_provider::{OrdIndexProvider, RuneIndexProvider};
+use crate::core::refund::p2wpkh_tx_fee;
+use crate::core::utxo_provider::{IcUtxoProvider, UtxoProvider};
+use crate::fee_priority::FeePriority;
+use minter_contract_utils::operation_store::MinterOperationId;
+
+use crate::core::deposit::RuneDeposit;
+use crate::interface::{OpenMintError, WithdrawError};
+use crate::key::{get_derivation_path, BtcSignerType};
+use crate::ledger::UtxoKey;
+use crate::state::State;
+
+const RUNESTONE_OUTPUT_INDEX: usize = 0;
+/// The runestone points the minted runes to this output.
+const RUNE_OUTPUT_INDEX: usize = 1;
+const CHANGE_OUTPUT_INDEX: usize = 2;
+
+pub(crate) struct OpenMint<
+    UTXO: UtxoProvider = IcUtxoProvider,
+    INDEX: RuneIndexProvider = OrdIndexProvider,
+> {
+    state: Rc<RefCell<State>>,
+    network: Network,
+    signer: BtcSignerType,
+    utxo_provider: UTXO,
+    index_provider: INDEX,
+}
+
+impl OpenMint<IcUtxoProvider, OrdIndexProvider> {
+    pub fn new(state: Rc<RefCell<State>>) -> Self {
+        let state_ref = state.borrow();
+
+        let network = state_ref.network();
+        let ic_network = state_ref.ic_btc_network();
+        let indexer_url = state_ref.indexer_url();
+        let signer = state_ref.btc_signer();
+
+        drop(state_ref);
+
+        Self {
+            state,
+            network,
+            signer,
+            utxo_provider: IcUtxoProvider::new(ic_network),
+            index_provider: OrdIndexProvider::new(indexer_url),
+        }
+    }
+}
+
+impl<UTXO: UtxoProvider, INDEX: RuneIndexProvider> OpenMint<UTXO, INDEX> {
+    /// Sends a transaction minting the `rune_id` to the deposit address of the `eth_address`,
+    /// paid with the BTC at that address.
+    pub async fn mint(
+        &self,
+        eth_address: &H160,
+        rune_id: RuneId,
+    ) -> Result<Transaction, OpenMintError> {
+        if !self
+            .index_provider
+            .is_mintable(rune_id)
+            .await
+            .map_err(OpenMintError::Deposit)?
+        {
+            return Err(OpenMintError::NotMintable);
+        }
+
+        let deposit_address = self
+            .signer
+            .get_transit_address(eth_address, self.network)
+            .await;
+        let inputs = self.funding_inputs(eth_address, &deposit_address).await?;
+
+        let postage = self.state.borrow().withdrawal_postage();
+        let fee_rate = self
+            .utxo_provider
+            .get_fee_rate(FeePriority::Standard)
+            .await
+            .map_err(OpenMintError::Transaction)?;
+        let unsigned_tx = build_mint_tx(rune_id, &inputs, &deposit_address, postage, fee_rate)?;
+
+        let public_key = self.state.borrow().public_key();
+        let wallet = self.state.borrow().wallet();
+        let tx = OrdTransactionBuilder::new(public_key, ScriptType::P2WSH, wallet)
+            .sign_transaction(&unsigned_tx, &inputs)
+            .await
+            .map_err(|err| {
+                log::error!("Failed to sign mint transaction: {err:?}");
+                OpenMintError::Transaction(WithdrawError::TransactionSigning)
+            })?;
+
+        self.utxo_provider
+            .send_tx(&tx)
+            .await
+            .map_err(OpenMintError::Transaction)?;
+
+        let mut state = self.state.borrow_mut();
+        let now = state.clock().now();
+        let ledger = state.ledger_mut();
+        for input in &inputs {
+            ledger.mark_as_used(input.outpoint.into(), deposit_address.clone(), now);
+        }
+
+        Ok(tx)
+    }
+
+    /// Mints the `rune_id` for the `eth_address` and creates the deposit request wrapping the
+    /// minted runes to the EVM with the `dst_chain_id`. Returns the id of the deposit operation,
+    /// the deposit task is to be appended by the caller.
+    pub async fn mint_and_request_deposit(
+        &self,
+        eth_address: H160,
+        rune_id: RuneId,
+        dst_chain_id: Option<u32>,
+    ) -> Result<MinterOperationId, OpenMintError> {
+        self.state
+            .borrow()
+            .recipient_chain_id(dst_chain_id)
+            .map_err(OpenMintError::Deposit)?;
+
+        let tx = self.mint(&eth_address, rune_id).await?;
+        log::info!(
+            "Sent transaction {} minting rune {rune_id} for address {}",
+            tx.txid(),
+            hex::encode(eth_address.0)
+        );
+
+        Ok(RuneDeposit::get().create_deposit_request(eth_address, None, None, dst_chain_id, None))
+    }
+
+    /// Utxos of the deposit address which can pay for the mint. Utxos holding runes belong to
+    /// deposits and are not spent.
+    async fn funding_inputs(
+        &self,
+        eth_address: &H160,
+        deposit_address: &Address,
+    ) -> Result<Vec<TxInputInfo>, OpenMintError> {
+        let utxos = self
+            .utxo_provider
+            .get_utxos(deposit_address)
+            .await
+            .map_err(OpenMintError::Deposit)?
+            .utxos;
+        let derivation_path = get_derivation_path(eth_address);
+        let mut inputs = vec![];
+        for utxo in utxos {
+            if self
+                .state
+                .borrow()
+                .ledger()
+                .is_used(&UtxoKey::from(&utxo.outpoint))
+            {
+                continue;
+            }
+
+            let rune_amounts = self
+                .index_provider
+                .get_rune_amounts(&utxo)
+                .await
+                .map_err(OpenMintError::Deposit)?;
+            if !rune_amounts.is_empty() {
+                continue;
+            }
+
+            inputs.push(TxInputInfo {
+                outpoint: OutPoint {
+                    txid: Txid::from_slice(&utxo.outpoint.txid).expect("invalid tx id"),
+                    vout: utxo.outpoint.vout,
+                },
+                tx_out: TxOut {
+                    value: Amount::from_sat(utxo.value),
+                    script_pubkey: deposit_address.script_pubkey(),
+                },
+                derivation_path: derivation_path.clone(),
+            });
+        }
+
+        Ok(inputs)
+    }
+}
+
+/// Builds an unsigned transaction spending the `inputs` with a runestone minting the `rune_id`.
+/// The minted runes are sent with `postage` sats to the `deposit_address`, and the BTC change
+/// returns to the same address.
+fn build_mint_tx(
+    rune_id: RuneId,
+    inputs: &[TxInputInfo],
+    deposit_address: &Address,
+    postage: u64,
+    fee_rate: FeeRate,
+) -> Result<Transaction, OpenMintError> {
+    let runestone = Runestone {
+        mint: Some(rune_id),
+        pointer: Some(RUNE_OUTPUT_INDEX as u32),
+        ..Default::default()
+    };
+
+    let mut tx = Transaction {
+        version: Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: inputs
+            .iter()
+            .map(|input| TxIn {
+                previous_output: input.outpoint,
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                witness: Witness::new(),
+            })
+            .collect(),
+        output: vec![
+            TxOut {
+                value: Amount::ZERO,
+                // `ordinals` depends on another version of `bitcoin`, so the script is converted
+                // through its bytes.
+                script_pubkey: ScriptBuf::from_bytes(runestone.encipher().into_bytes()),
+            },
+            TxOut {
+                value: Amount::from_sat(postage),
+                script_pubkey: deposit_address.script_pubkey(),
+            },
+            TxOut {
+                value: Amount::ZERO,
+                script_pubkey: deposit_address.script_pubkey(),
+            },
+        ],
+    };
+
+    let fee = p2wpkh_tx_fee(&tx, fee_rate).map_err(OpenMintError::Transaction)?;
+    let dust = deposit_address.script_pubkey().dust_value().to_sat();
+    let available: u64 = inputs.iter().map(|input| input.tx_out.value.to_sat()).sum();
+    let required = postage + fee + dust;
+    if available < required {
+        return Err(OpenMintError::NotEnoughBtc {
+            available,
+            required,
+        });
+    }
+
+    tx.output[CHANGE_OUTPUT_INDEX].value = Amount::from_sat(available - postage - fee);
+
+    Ok(tx)
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::bip32::DerivationPath;
+    use bitcoin::Script;
+
+    use super::*;
+
+    const RUNE_ID: RuneId = RuneId {
+        block: 840_000,
+        tx: 3,
+    };
+
+    fn deposit_address() -> Address {
+        Address::p2wsh(Script::from_bytes(&[0]), Network::Regtest)
+    }
+
+    fn input(vout: u32, value: u64) -> TxInputInfo {
+        TxInputInfo {
+            outpoint: OutPoint {
+                txid: Txid::from_slice(&[2; 32]).unwrap(),
+                vout,
+            },
+            tx_out: TxOut {
+                value: Amount::from_sat(value),
+                script_pubkey: deposit_address().script_pubkey(),
+            },
+            derivation_path: DerivationPath::master(),
+        }
+    }
+
+    #[test]
+    fn mint_tx_layout() {
+        let fee_rate = FeeRate::from_sat_per_vb(3).unwrap();
+        let inputs = [input(0, 30_000), input(1, 20_000)];
+        let tx = build_mint_tx(RUNE_ID, &inputs, &deposit_address(), 10_000, fee_rate).unwrap();
+
+        assert_eq!(tx.input.len(), 2);
+        assert_eq!(tx.output.len(), 3);
+        assert!(tx.output[RUNESTONE_OUTPUT_INDEX]
+            .script_pubkey
+            .is_op_return());
+
+        let runestone = Runestone {
+            mint: Some(RUNE_ID),
+            pointer: Some(RUNE_OUTPUT_INDEX as u32),
+            ..Default::default()
+        };
+        assert_eq!(
+            tx.output[RUNESTONE_OUTPUT_INDEX].script_pubkey.as_bytes(),
+            runestone.encipher().as_bytes()
+        );
+
+        assert_eq!(tx.output[RUNE_OUTPUT_INDEX].value.to_sat(), 10_000);
+        let fee = p2wpkh_tx_fee(&tx, fee_rate).unwrap();
+        assert_eq!(
+            tx.output[CHANGE_OUTPUT_INDEX].value.to_sat(),
+            50_000 - 10_000 - fee
+        );
+    }
+
+    #[test]
+    fn not_enough_btc_for_mint() {
+        let fee_rate = FeeRate::from_sat_per_vb(3).unwrap();
+        let result = build_mint_tx(
+            RUNE_ID,
+            &[input(0, 10_500)],
+            &deposit_address(),
+            10_000,
+            fee_rate,
+        );
+
+        assert!(matches!(
+            result,
+            Err(OpenMintError::NotEnoughBtc {
+                available: 10_500,
+                required
+            }) if required > 10_500
+        ));
+    }
+
+    #[test]
+    fn mint_without_inputs_is_rejected() {
+        let result = build_mint_tx(
+            RUNE_ID,
+            &[],
+            &deposit_address(),
+            10_000,
+            FeeRate::from_sat_per_vb(1).unwrap(),
+        );
+        assert!(matches!(
+            result,
+            Err(OpenMintError::NotEnoughBtc { available: 0, .. })
+        ));
+    }
+}
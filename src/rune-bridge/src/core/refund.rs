@@ -0,0 +1,435 @@
+//! Return of the BTC deposited together with the runes to the user.
+//!
+//! Deposit utxos usually carry more BTC than needed to keep the runes. If the user specified a
+//! refund address in the deposit request, after the wrapped tokens are minted the bridge spends
+//! the deposit utxos: the runes are moved to a postage output owned by the bridge and the
+//! remaining BTC, minus the transaction fee, is sent to the refund address.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use bitcoin::absolute::LockTime;
+use bitcoin::hashes::Hash;
+use bitcoin::transaction::Version;
+use bitcoin::{
+    Address, Amount, FeeRate, Network, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut,
+    Txid, Witness,
+};
+use candid::{CandidType, Deserialize};
+use did::H160;
+use ic_exports::ic_cdk::api::management_canister::bitcoin::{Outpoint, Utxo};
+use minter_contract_utils::btc_address::{parse_btc_address, BtcAddressError};
+use minter_contract_utils::operation_store::MinterOperationId;
+use ord_rs::wallet::{ScriptType, TxInputInfo};
+use ord_rs::OrdTransactionBuilder;
+
+use crate::canister::{get_operations_store, get_tx_journal};
+use crate::core::deposit::{DepositRequestStatus, RuneDepositPayload};
+use crate::core::utxo_provider::{IcUtxoProvider, UtxoProvider};
+use crate::core::withdrawal::DidTransaction;
+use crate::fee_priority::FeePriority;
+use crate::interface::WithdrawError;
+use crate::key::{get_derivation_path, get_derivation_path_ic, BtcSignerType};
+use crate::operation::{OperationState, RuneOperationStore};
+use crate::state::State;
+
+/// Index of the output carrying the deposited runes to the bridge. Runes of a transaction
+/// without a runestone are transferred to the first non-`OP_RETURN` output.
+const RUNE_OUTPUT_INDEX: usize = 0;
+const REFUND_OUTPUT_INDEX: usize = 1;
+
+/// Witness of a P2WPKH input (signature and public key) in virtual bytes.
+const P2WPKH_WITNESS_VSIZE: u64 = 27;
+
+#[derive(Debug, Clone, CandidType, Deserialize)]
+pub enum BtcRefundStatus {
+    /// The refund address of the request cannot be used. The BTC stays with the bridge.
+    InvalidAddress(BtcAddressError),
+    /// Mint orders are created for the deposit utxos. The refund is sent after the wrapped
+    /// tokens are minted.
+    Pending {
+        utxos: Vec<Utxo>,
+    },
+    /// The BTC left after paying the postage and the fee is below the refund threshold, so no
+    /// refund transaction is sent.
+    BelowThreshold {
+        change: u64,
+        threshold: u64,
+    },
+    TxSent {
+        transaction: DidTransaction,
+    },
+}
+
+pub(crate) struct BtcRefund<UTXO: UtxoProvider> {
+    state: Rc<RefCell<State>>,
+    utxo_provider: UTXO,
+    signer: BtcSignerType,
+    network: Network,
+    operation_store: RuneOperationStore,
+}
+
+impl BtcRefund<IcUtxoProvider> {
+    pub fn new(state: Rc<RefCell<State>>) -> Self {
+        let state_ref = state.borrow();
+
+        let network = state_ref.network();
+        let ic_network = state_ref.ic_btc_network();
+        let signer = state_ref.btc_signer();
+
+        drop(state_ref);
+
+        Self {
+            state,
+            network,
+            signer,
+            utxo_provider: IcUtxoProvider::new(ic_network),
+            operation_store: get_operations_store(),
+        }
+    }
+}
+
+impl<UTXO: UtxoProvider> BtcRefund<UTXO> {
+    /// Sends the BTC change of the completed deposit to its refund address.
+    pub async fn refund(&mut self, operation_id: MinterOperationId) -> Result<(), WithdrawError> {
+        let Some(OperationState::Deposit(payload)) = self.operation_store.get(operation_id) else {
+            return Err(WithdrawError::InternalError(format!(
+                "Operation {operation_id} is not a deposit operation"
+            )));
+        };
+
+        let (Some(refund_address), Some(BtcRefundStatus::Pending { utxos })) =
+            (&payload.refund_address, &payload.refund)
+        else {
+            log::warn!("Deposit {operation_id} has no pending refund: {payload:?}");
+            return Ok(());
+        };
+
+        if !matches!(payload.status, DepositRequestStatus::Minted { .. }) {
+            return Err(WithdrawError::InternalError(format!(
+                "Attempted to refund BTC of deposit {operation_id} which is not minted: {payload:?}"
+            )));
+        }
+
+        let refund_address = parse_btc_address(refund_address, self.network)
+            .map_err(WithdrawError::InvalidAddress)?;
+        let transit_address = self
+            .signer
+            .get_transit_address(&payload.dst_address, self.network)
+            .await;
+        let rune_address = self
+            .signer
+            .get_transit_address(&H160::default(), self.network)
+            .await;
+
+        let derivation_path = get_derivation_path(&payload.dst_address);
+        let inputs: Vec<_> = utxos
+            .iter()
+            .map(|utxo| TxInputInfo {
+                outpoint: OutPoint {
+                    txid: Txid::from_slice(&utxo.outpoint.txid).expect("invalid tx id"),
+                    vout: utxo.outpoint.vout,
+                },
+                tx_out: TxOut {
+                    value: Amount::from_sat(utxo.value),
+                    script_pubkey: transit_address.script_pubkey(),
+                },
+                derivation_path: derivation_path.clone(),
+            })
+            .collect();
+
+        let tx = match get_tx_journal().get(operation_id) {
+            // The refund was signed, but the deposit did not record it as sent, so the same
+            // transaction is broadcast again.
+            Some(entry) => {
+                log::info!(
+                    "Broadcasting signed refund transaction {} of deposit {operation_id} again",
+                    entry.txid()
+                );
+                entry.transaction()
+            }
+            None => {
+                let (postage, threshold) = {
+                    let state = self.state.borrow();
+                    (state.withdrawal_postage(), state.refund_threshold())
+                };
+                let fee_rate = self
+                    .utxo_provider
+                    .get_fee_rate(FeePriority::Standard)
+                    .await?;
+
+                let unsigned_tx =
+                    build_refund_tx(&inputs, &rune_address, &refund_address, postage, fee_rate)?;
+                let change = unsigned_tx.output[REFUND_OUTPUT_INDEX].value.to_sat();
+                if change < threshold {
+                    log::info!(
+                        "BTC change {change} of deposit {operation_id} is below the refund threshold {threshold}"
+                    );
+                    self.update_refund_status(
+                        operation_id,
+                        payload,
+                        BtcRefundStatus::BelowThreshold { change, threshold },
+                    );
+                    return Ok(());
+                }
+
+                let public_key = self.state.borrow().public_key();
+                let wallet = self.state.borrow().wallet();
+                let tx = OrdTransactionBuilder::new(public_key, ScriptType::P2WSH, wallet)
+                    .sign_transaction(&unsigned_tx, &inputs)
+                    .await
+                    .map_err(|err| {
+                        log::error!("Failed to sign refund transaction: {err:?}");
+                        WithdrawError::TransactionSigning
+                    })?;
+
+                let now = self.state.borrow().clock().now();
+                get_tx_journal().record(operation_id, &tx, now);
+                tx
+            }
+        };
+        let change = tx.output[REFUND_OUTPUT_INDEX].value.to_sat();
+
+        self.utxo_provider.send_tx(&tx).await?;
+
+        let rune_utxo = Utxo {
+            outpoint: Outpoint {
+                txid: tx.txid().as_byte_array().to_vec(),
+                vout: RUNE_OUTPUT_INDEX as u32,
+            },
+            value: tx.output[RUNE_OUTPUT_INDEX].value.to_sat(),
+            height: 0,
+        };
+
+        {
+            let mut state = self.state.borrow_mut();
+            let now = state.clock().now();
+            let ledger = state.ledger_mut();
+            for input in &inputs {
+                ledger.mark_as_used(input.outpoint.into(), transit_address.clone(), now);
+            }
+            ledger.deposit(
+                &[rune_utxo],
+                &rune_address,
+                get_derivation_path_ic(&H160::default()),
+            );
+        }
+
+        log::info!(
+            "Sent refund transaction {} with {change} sats for deposit {operation_id}",
+            tx.txid()
+        );
+
+        self.update_refund_status(
+            operation_id,
+            payload,
+            BtcRefundStatus::TxSent {
+                transaction: DidTransaction::from(tx),
+            },
+        );
+        get_tx_journal().remove(operation_id);
+
+        Ok(())
+    }
+
+    fn update_refund_status(
+        &mut self,
+        operation_id: MinterOperationId,
+        payload: RuneDepositPayload,
+        refund: BtcRefundStatus,
+    ) {
+        self.operation_store.update(
+            operation_id,
+            OperationState::Deposit(RuneDepositPayload {
+                refund: Some(refund),
+                ..payload
+            }),
+        );
+    }
+}
+
+/// Estimates the fee of the unsigned transaction `tx` spending P2WPKH inputs after it is signed.
+pub(crate) fn p2wpkh_tx_fee(tx: &Transaction, fee_rate: FeeRate) -> Result<u64, WithdrawError> {
+    // Segwit marker and flag take half a virtual byte, so one byte is added for them.
+    let vsize = tx.vsize() as u64 + tx.input.len() as u64 * P2WPKH_WITNESS_VSIZE + 1;
+    fee_rate
+        .fee_vb(vsize)
+        .map(|fee| fee.to_sat())
+        .ok_or(WithdrawError::TransactionCreation)
+}
+
+/// Builds an unsigned transaction spending the deposit `inputs`, which sends `postage` sats with
+/// all the runes to the `rune_address` and the rest of the BTC, minus the fee, to the
+/// `refund_address`.
+fn build_refund_tx(
+    inputs: &[TxInputInfo],
+    rune_address: &Address,
+    refund_address: &Address,
+    postage: u64,
+    fee_rate: FeeRate,
+) -> Result<Transaction, WithdrawError> {
+    if inputs.is_empty() {
+        return Err(WithdrawError::NoInputs);
+    }
+
+    let mut tx = Transaction {
+        version: Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: inputs
+            .iter()
+            .map(|input| TxIn {
+                previous_output: input.outpoint,
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                witness: Witness::new(),
+            })
+            .collect(),
+        output: vec![
+            TxOut {
+                value: Amount::from_sat(postage),
+                script_pubkey: rune_address.script_pubkey(),
+            },
+            TxOut {
+                value: Amount::ZERO,
+                script_pubkey: refund_address.script_pubkey(),
+            },
+        ],
+    };
+
+    let fee = p2wpkh_tx_fee(&tx, fee_rate)?;
+    let inputs_value: u64 = inputs.iter().map(|input| input.tx_out.value.to_sat()).sum();
+    let Some(change) = inputs_value
+        .checked_sub(postage)
+        .and_then(|value| value.checked_sub(fee))
+    else {
+        log::info!("Deposit value {inputs_value} doesn't cover postage {postage} and fee {fee}");
+        return Err(WithdrawError::NotEnoughBtc {
+            available: inputs_value,
+            required: postage.saturating_add(fee),
+        });
+    };
+
+    let dust = refund_address.script_pubkey().dust_value().to_sat();
+    if change < dust {
+        return Err(WithdrawError::AmountBelowDust {
+            value: change,
+            dust,
+        });
+    }
+
+    tx.output[REFUND_OUTPUT_INDEX].value = Amount::from_sat(change);
+
+    Ok(tx)
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::bip32::DerivationPath;
+    use bitcoin::Script;
+
+    use super::*;
+
+    fn rune_address() -> Address {
+        Address::p2wsh(Script::from_bytes(&[0]), Network::Regtest)
+    }
+
+    fn refund_address() -> Address {
+        Address::p2wsh(Script::from_bytes(&[1]), Network::Regtest)
+    }
+
+    fn input(vout: u32, value: u64) -> TxInputInfo {
+        TxInputInfo {
+            outpoint: OutPoint {
+                txid: Txid::from_slice(&[1; 32]).unwrap(),
+                vout,
+            },
+            tx_out: TxOut {
+                value: Amount::from_sat(value),
+                script_pubkey: rune_address().script_pubkey(),
+            },
+            derivation_path: DerivationPath::master(),
+        }
+    }
+
+    #[test]
+    fn runes_go_to_the_first_output_and_change_to_refund_address() {
+        let inputs = [input(0, 50_000), input(1, 30_000)];
+        let fee_rate = FeeRate::from_sat_per_vb(2).unwrap();
+        let tx = build_refund_tx(
+            &inputs,
+            &rune_address(),
+            &refund_address(),
+            10_000,
+            fee_rate,
+        )
+        .unwrap();
+
+        assert_eq!(tx.input.len(), 2);
+        assert_eq!(tx.output.len(), 2);
+        assert_eq!(
+            tx.output[RUNE_OUTPUT_INDEX].script_pubkey,
+            rune_address().script_pubkey()
+        );
+        assert_eq!(tx.output[RUNE_OUTPUT_INDEX].value.to_sat(), 10_000);
+        assert_eq!(
+            tx.output[REFUND_OUTPUT_INDEX].script_pubkey,
+            refund_address().script_pubkey()
+        );
+
+        let fee = 80_000 - 10_000 - tx.output[REFUND_OUTPUT_INDEX].value.to_sat();
+        let signed_vsize = tx.vsize() as u64 + 2 * P2WPKH_WITNESS_VSIZE + 1;
+        assert_eq!(fee, signed_vsize * 2);
+    }
+
+    #[test]
+    fn deposit_not_covering_postage_and_fee_is_rejected() {
+        let fee_rate = FeeRate::from_sat_per_vb(10).unwrap();
+        let result = build_refund_tx(
+            &[input(0, 10_500)],
+            &rune_address(),
+            &refund_address(),
+            10_000,
+            fee_rate,
+        );
+        assert!(matches!(
+            result,
+            Err(WithdrawError::NotEnoughBtc {
+                available: 10_500,
+                required,
+            }) if required > 10_500
+        ));
+    }
+
+    #[test]
+    fn dust_change_is_rejected() {
+        let fee_rate = FeeRate::from_sat_per_vb(1).unwrap();
+        let inputs = [input(0, 10_000)];
+        let tx =
+            build_refund_tx(&inputs, &rune_address(), &refund_address(), 5_000, fee_rate).unwrap();
+        let fee = 5_000 - tx.output[REFUND_OUTPUT_INDEX].value.to_sat();
+
+        let result = build_refund_tx(
+            &[input(0, 5_000 + fee + 100)],
+            &rune_address(),
+            &refund_address(),
+            5_000,
+            fee_rate,
+        );
+        assert!(matches!(
+            result,
+            Err(WithdrawError::AmountBelowDust { value: 100, .. })
+        ));
+    }
+
+    #[test]
+    fn empty_inputs_are_rejected() {
+        let result = build_refund_tx(
+            &[],
+            &rune_address(),
+            &refund_address(),
+            10_000,
+            FeeRate::from_sat_per_vb(1).unwrap(),
+        );
+        assert!(matches!(result, Err(WithdrawError::NoInputs)));
+    }
+}
@@ -0,0 +1,262 @@
+//! Rescan of the registered addresses to rebuild the utxo ledger from the chain data.
+//!
+//! The admin schedules a rescan of an index range of the [`AddressRegistry`]. The utxos of the
+//! own address of the bridge unknown to the ledger are added back to it. The utxos found at the
+//! deposit addresses of the users can only be wrapped by a deposit request, so they are reported
+//! to the admin instead. Like a BIP-44 wallet recovery, the scan stops after [`GAP_LIMIT`]
+//! consecutive addresses without utxos.
+//!
+//! [`AddressRegistry`]: crate::address_registry::AddressRegistry
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use bitcoin::Network;
+use candid::{CandidType, Deserialize};
+use did::H160;
+use ic_exports::ic_cdk::api::management_canister::bitcoin::Utxo;
+
+use crate::address_registry::BRIDGE_ADDRESS_INDEX;
+use crate::canister::get_address_registry;
+use crate::core::utxo_provider::{IcUtxoProvider, UtxoProvider};
+use crate::interface::DepositError;
+use crate::key::{get_derivation_path_ic, BtcSignerType};
+use crate::state::State;
+
+/// Number of consecutive addresses without utxos after which the scan stops.
+pub const GAP_LIMIT: u32 = 20;
+/// Maximum number of addresses scanned by one rescan.
+pub const MAX_RESCAN_RANGE: u32 = 1_000;
+
+/// Utxos at a deposit address of a user which are neither deposited nor in the ledger.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct UnrecordedUtxos {
+    pub index: u32,
+    pub eth_address: H160,
+    pub btc_address: String,
+    pub utxo_count: u64,
+    /// Total value of the utxos in satoshi.
+    pub value: u64,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct RescanReport {
+    pub from_index: u32,
+    /// Index after the last scanned address.
+    pub next_index: u32,
+    /// `true` if the scan stopped at the gap limit before the end of the range.
+    pub gap_limit_reached: bool,
+    /// Utxos of the bridge address added to the ledger.
+    pub recovered_utxos: u64,
+    pub unrecorded: Vec<UnrecordedUtxos>,
+    /// Timestamp (nanoseconds) of the scan completion.
+    pub completed_at: u64,
+}
+
+pub(crate) struct AddressRescan<UTXO: UtxoProvider = IcUtxoProvider> {
+    state: Rc<RefCell<State>>,
+    utxo_provider: UTXO,
+    signer: BtcSignerType,
+    network: Network,
+}
+
+impl AddressRescan<IcUtxoProvider> {
+    pub fn new(state: Rc<RefCell<State>>) -> Self {
+        let state_ref = state.borrow();
+
+        let network = state_ref.network();
+        let ic_network = state_ref.ic_btc_network();
+        let signer = state_ref.btc_signer();
+
+        drop(state_ref);
+
+        Self {
+            state,
+            network,
+            signer,
+            utxo_provider: IcUtxoProvider::new(ic_network),
+        }
+    }
+}
+
+impl<UTXO: UtxoProvider> AddressRescan<UTXO> {
+    /// Scans the registered addresses with the indices in `from_index..to_index`.
+    pub async fn rescan(
+        &self,
+        from_index: u32,
+        to_index: u32,
+    ) -> Result<RescanReport, DepositError> {
+        let registry = get_address_registry();
+        let mut report = RescanReport {
+            from_index,
+            next_index: from_index,
+            ..Default::default()
+        };
+
+        let mut empty_addresses = 0;
+        for index in from_index..to_index.min(registry.len()) {
+            if empty_addresses >= GAP_LIMIT {
+                report.gap_limit_reached = true;
+                break;
+            }
+
+            let Some(eth_address) = registry.get(index) else {
+                break;
+            };
+            let btc_address = self
+                .signer
+                .get_transit_address(&eth_address, self.network)
+                .await;
+            let utxos = self.utxo_provider.get_utxos(&btc_address).await?.utxos;
+            report.next_index = index + 1;
+
+            if utxos.is_empty() {
+                empty_addresses += 1;
+                continue;
+            }
+            empty_addresses = 0;
+
+            let unknown: Vec<Utxo> = {
+                let state = self.state.borrow();
+                utxos
+                    .into_iter()
+                    .filter(|utxo| !state.ledger().contains(&(&utxo.outpoint).into()))
+                    .collect()
+            };
+            if unknown.is_empty() {
+                continue;
+            }
+
+            if index == BRIDGE_ADDRESS_INDEX {
+                log::info!(
+                    "Rescan found {} bridge utxos missing from the ledger",
+                    unknown.len()
+                );
+                self.state.borrow_mut().ledger_mut().deposit(
+                    &unknown,
+                    &btc_address,
+                    get_derivation_path_ic(&eth_address),
+                );
+                report.recovered_utxos += unknown.len() as u64;
+            } else {
+                report.unrecorded.push(UnrecordedUtxos {
+                    index,
+                    eth_address,
+                    btc_address: btc_address.to_string(),
+                    utxo_count: unknown.len() as u64,
+                    value: unknown
+                        .iter()
+                        .fold(0u64, |acc, utxo| acc.saturating_add(utxo.value)),
+                });
+            }
+        }
+
+        report.completed_at = self.state.borrow().clock().now();
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::PrivateKey;
+    use ic_exports::ic_cdk::api::management_canister::bitcoin::Outpoint;
+    use ic_exports::ic_kit::MockContext;
+    use ord_rs::wallet::LocalSigner;
+
+    use super::*;
+    use crate::canister::get_state;
+    use crate::core::utxo_provider::mock::MockUtxoProvider;
+
+    fn signer() -> BtcSignerType {
+        BtcSignerType::Local(LocalSigner::new(
+            PrivateKey::from_slice(&[1; 32], Network::Regtest).unwrap(),
+        ))
+    }
+
+    fn utxo(txid_byte: u8, value: u64) -> Utxo {
+        Utxo {
+            outpoint: Outpoint {
+                txid: vec![txid_byte; 32],
+                vout: 0,
+            },
+            value,
+            height: 1,
+        }
+    }
+
+    fn rescan(utxo_provider: MockUtxoProvider) -> AddressRescan<MockUtxoProvider> {
+        AddressRescan {
+            state: get_state(),
+            utxo_provider,
+            signer: signer(),
+            network: Network::Regtest,
+        }
+    }
+
+    #[tokio::test]
+    async fn bridge_utxos_are_recovered_and_user_utxos_reported() {
+        MockContext::new().inject();
+
+        let user = H160::from_slice(&[1; 20]);
+        let user_index = get_address_registry().register(&user);
+        let bridge_address = signer()
+            .get_transit_address(&H160::default(), Network::Regtest)
+            .await;
+        let user_address = signer().get_transit_address(&user, Network::Regtest).await;
+
+        let provider = MockUtxoProvider::default()
+            .with_utxos(&bridge_address, vec![utxo(1, 5_000)], 10)
+            .with_utxos(&user_address, vec![utxo(2, 7_000), utxo(3, 3_000)], 10);
+        let report = rescan(provider).rescan(0, 100).await.unwrap();
+
+        assert_eq!(report.recovered_utxos, 1);
+        assert_eq!(report.next_index, user_index + 1);
+        assert!(!report.gap_limit_reached);
+        assert_eq!(
+            report.unrecorded,
+            vec![UnrecordedUtxos {
+                index: user_index,
+                eth_address: user,
+                btc_address: user_address.to_string(),
+                utxo_count: 2,
+                value: 10_000,
+            }]
+        );
+        assert!(get_state()
+            .borrow()
+            .ledger()
+            .contains(&(&utxo(1, 5_000).outpoint).into()));
+
+        let report = rescan(MockUtxoProvider::default().with_utxos(
+            &bridge_address,
+            vec![utxo(1, 5_000)],
+            10,
+        ))
+        .rescan(0, 1)
+        .await
+        .unwrap();
+        assert_eq!(report.recovered_utxos, 0);
+    }
+
+    #[tokio::test]
+    async fn scan_stops_at_gap_limit() {
+        MockContext::new().inject();
+
+        let registry_len = {
+            let mut registry = get_address_registry();
+            for byte in 1..=GAP_LIMIT as u8 + 5 {
+                registry.register(&H160::from_slice(&[byte; 20]));
+            }
+            registry.len()
+        };
+
+        let report = rescan(MockUtxoProvider::default())
+            .rescan(0, registry_len)
+            .await
+            .unwrap();
+
+        assert!(report.gap_limit_reached);
+        assert_eq!(report.next_index, GAP_LIMIT);
+        assert!(report.unrecorded.is_empty());
+    }
+}
@@ -0,0 +1,395 @@
+//! Screening of deposits by an external KYT service before mint orders are created for them.
+
+use candid::{CandidType, Principal};
+use did::H160;
+use ic_exports::ic_cdk;
+use ic_exports::ic_cdk::api::management_canister::bitcoin::Utxo;
+use ic_exports::ic_cdk::api::management_canister::http_request::{
+    CanisterHttpRequestArgument, HttpHeader, HttpMethod,
+};
+use ic_stable_structures::stable_structures::DefaultMemoryImpl;
+use ic_stable_structures::{BTreeMapStructure, StableBTreeMap, VirtualMemory};
+use serde::{Deserialize, Serialize};
+
+use crate::core::http_outcall::{HttpOutcall, IcHttpOutcall};
+use crate::memory::{MEMORY_MANAGER, SCREENING_OVERRIDES_MEMORY_ID};
+
+/// Method of the KYT canister used to screen deposits:
+/// `screen_deposit : (ScreeningRequest) -> (ScreeningVerdict)`.
+pub const KYT_CANISTER_METHOD: &str = "screen_deposit";
+
+const CYCLES_PER_HTTP_REQUEST: u128 = 500_000_000;
+const MAX_RESPONSE_BYTES: u64 = 2_000;
+
+/// Service used to screen deposits.
+#[derive(Debug, Default, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub enum ScreeningProvider {
+    /// Deposits are not screened.
+    #[default]
+    Disabled,
+    /// KYT canister implementing the `screen_deposit` method.
+    KytCanister(Principal),
+    /// HTTPS API accepting a `POST` request with JSON encoded [`ScreeningRequest`] and responding
+    /// with JSON `{ "allowed": bool, "reason": string | null }`.
+    Https(String),
+}
+
+/// Behaviour of the bridge if the screening provider cannot be reached.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, CandidType, Deserialize)]
+pub enum ScreeningFailMode {
+    /// The deposit is processed as if it was allowed.
+    Open,
+    /// The deposit is postponed until the provider is available.
+    #[default]
+    Closed,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct ScreeningConfig {
+    pub provider: ScreeningProvider,
+    pub fail_mode: ScreeningFailMode,
+}
+
+impl ScreeningConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        match &self.provider {
+            ScreeningProvider::Https(url) if !url.starts_with("https") => Err(format!(
+                "Screening url must specify https url, but given value is: {url}"
+            )),
+            ScreeningProvider::KytCanister(principal) if *principal == Principal::anonymous() => {
+                Err("Screening canister principal is anonymous".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Information about a deposit utxo sent to the screening provider.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Serialize, Deserialize)]
+pub struct ScreeningRequest {
+    /// Funding transaction id in the standard hex representation.
+    pub txid: String,
+    pub vout: u32,
+    /// Value of the utxo in satoshi.
+    pub value: u64,
+    /// EVM address the wrapped tokens are minted to.
+    #[serde(with = "minter_contract_utils::hex_serde::h160")]
+    pub recipient: H160,
+}
+
+impl ScreeningRequest {
+    pub fn new(utxo: &Utxo, recipient: &H160) -> Self {
+        Self {
+            txid: display_txid(&utxo.outpoint.txid),
+            vout: utxo.outpoint.vout,
+            value: utxo.value,
+            recipient: recipient.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub enum ScreeningVerdict {
+    Allowed,
+    Rejected { reason: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScreeningError {
+    /// The deposit must not be processed.
+    Rejected(String),
+    /// The screening provider is not available and the canister works in fail-closed mode.
+    Unavailable(String),
+}
+
+/// Screening decisions set by the admin which take priority over the provider verdicts.
+///
+/// Keys are either funding transaction ids or hex encoded EVM recipient addresses.
+pub struct ScreeningOverrides {
+    overrides: StableBTreeMap<String, bool, VirtualMemory<DefaultMemoryImpl>>,
+}
+
+impl Default for ScreeningOverrides {
+    fn default() -> Self {
+        Self {
+            overrides: StableBTreeMap::new(
+                MEMORY_MANAGER.with(|mm| mm.get(SCREENING_OVERRIDES_MEMORY_ID)),
+            ),
+        }
+    }
+}
+
+impl ScreeningOverrides {
+    pub fn set(&mut self, subject: &str, allowed: bool) {
+        self.overrides.insert(normalize_subject(subject), allowed);
+    }
+
+    pub fn remove(&mut self, subject: &str) {
+        self.overrides.remove(&normalize_subject(subject));
+    }
+
+    pub fn get(&self, subject: &str) -> Option<bool> {
+        self.overrides.get(&normalize_subject(subject))
+    }
+
+    pub fn get_all(&self) -> Vec<(String, bool)> {
+        self.overrides.iter().collect()
+    }
+}
+
+fn normalize_subject(subject: &str) -> String {
+    subject.trim().trim_start_matches("0x").to_ascii_lowercase()
+}
+
+/// Screens all deposit utxos to be minted to the `recipient`.
+///
+/// Admin overrides are checked first: the recipient override applies to all the utxos, and a
+/// transaction override applies to the utxos of that transaction. Only the utxos without an
+/// override are sent to the provider.
+pub async fn screen_deposit(
+    config: &ScreeningConfig,
+    overrides: impl Fn(&str) -> Option<bool>,
+    recipient: &H160,
+    utxos: &[Utxo],
+) -> Result<(), ScreeningError> {
+    match overrides(&hex::encode(recipient.0)) {
+        Some(true) => return Ok(()),
+        Some(false) => {
+            return Err(ScreeningError::Rejected(
+                "recipient address is blocked".to_string(),
+            ))
+        }
+        None => {}
+    }
+
+    for utxo in utxos {
+        let request = ScreeningRequest::new(utxo, recipient);
+        match overrides(&request.txid) {
+            Some(true) => continue,
+            Some(false) => {
+                return Err(ScreeningError::Rejected(format!(
+                    "transaction {} is blocked",
+                    request.txid
+                )))
+            }
+            None => {}
+        }
+
+        match request_verdict(&config.provider, &request).await {
+            Ok(ScreeningVerdict::Allowed) => {}
+            Ok(ScreeningVerdict::Rejected { reason }) => {
+                log::info!(
+                    "Deposit utxo {}:{} rejected: {reason}",
+                    request.txid,
+                    request.vout
+                );
+                return Err(ScreeningError::Rejected(reason));
+            }
+            Err(err) if config.fail_mode == ScreeningFailMode::Open => {
+                log::warn!(
+                    "Screening provider is unavailable, allowing utxo {}:{}: {err}",
+                    request.txid,
+                    request.vout
+                );
+            }
+            Err(err) => return Err(ScreeningError::Unavailable(err)),
+        }
+    }
+
+    Ok(())
+}
+
+async fn request_verdict(
+    provider: &ScreeningProvider,
+    request: &ScreeningRequest,
+) -> Result<ScreeningVerdict, String> {
+    match provider {
+        ScreeningProvider::Disabled => Ok(ScreeningVerdict::Allowed),
+        ScreeningProvider::KytCanister(canister) => {
+            ic_cdk::call::<_, (ScreeningVerdict,)>(*canister, KYT_CANISTER_METHOD, (request,))
+                .await
+                .map(|(verdict,)| verdict)
+                .map_err(|(code, msg)| format!("KYT canister call failed: {code:?} {msg}"))
+        }
+        ScreeningProvider::Https(url) => https_verdict(&IcHttpOutcall, url, request).await,
+    }
+}
+
+async fn https_verdict(
+    http: &impl HttpOutcall,
+    url: &str,
+    request: &ScreeningRequest,
+) -> Result<ScreeningVerdict, String> {
+    #[derive(Debug, Deserialize)]
+    struct HttpsScreeningResponse {
+        allowed: bool,
+        reason: Option<String>,
+    }
+
+    let body = serde_json::to_vec(request).map_err(|err| err.to_string())?;
+    let request_params = CanisterHttpRequestArgument {
+        url: url.to_string(),
+        max_response_bytes: Some(MAX_RESPONSE_BYTES),
+        method: HttpMethod::POST,
+        headers: vec![
+            HttpHeader {
+                name: "Accept".to_string(),
+                value: "application/json".to_string(),
+            },
+            HttpHeader {
+                name: "Content-Type".to_string(),
+                value: "application/json".to_string(),
+            },
+        ],
+        body: Some(body),
+        transform: None,
+    };
+
+    let response = http
+        .request(request_params, CYCLES_PER_HTTP_REQUEST)
+        .await
+        .map_err(|err| format!("Screening API unavailable: {err}"))?;
+
+    let response: HttpsScreeningResponse = serde_json::from_slice(&response.body)
+        .map_err(|err| format!("Unexpected response from screening API: {err:?}"))?;
+
+    Ok(if response.allowed {
+        ScreeningVerdict::Allowed
+    } else {
+        ScreeningVerdict::Rejected {
+            reason: response
+                .reason
+                .unwrap_or_else(|| "rejected by screening API".to_string()),
+        }
+    })
+}
+
+/// IC management canister returns tx ids in reversed byte order, so they are reversed back to
+/// get the representation used by block explorers and KYT services.
+pub(crate) fn display_txid(txid: &[u8]) -> String {
+    hex::encode(txid.iter().copied().rev().collect::<Vec<u8>>())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use ic_exports::ic_cdk::api::management_canister::bitcoin::Outpoint;
+
+    use super::*;
+    use crate::core::http_outcall::mock::MockHttpOutcall;
+
+    fn utxo(txid_byte: u8) -> Utxo {
+        Utxo {
+            outpoint: Outpoint {
+                txid: vec![txid_byte; 32],
+                vout: 0,
+            },
+            value: 10_000,
+            height: 0,
+        }
+    }
+
+    async fn screen(
+        overrides: HashMap<String, bool>,
+        recipient: &H160,
+        utxos: &[Utxo],
+    ) -> Result<(), ScreeningError> {
+        let config = ScreeningConfig::default();
+        screen_deposit(
+            &config,
+            |subject| overrides.get(subject).copied(),
+            recipient,
+            utxos,
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn disabled_provider_allows_deposits() {
+        assert_eq!(
+            screen(HashMap::new(), &H160::default(), &[utxo(1)]).await,
+            Ok(())
+        );
+    }
+
+    #[tokio::test]
+    async fn blocked_recipient_is_rejected() {
+        let recipient = H160::from_slice(&[2; 20]);
+        let overrides = HashMap::from([(hex::encode(recipient.0), false)]);
+        assert!(matches!(
+            screen(overrides, &recipient, &[utxo(1)]).await,
+            Err(ScreeningError::Rejected(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn blocked_transaction_is_rejected() {
+        let utxos = [utxo(1), utxo(3)];
+        let overrides = HashMap::from([(display_txid(&utxos[1].outpoint.txid), false)]);
+        assert!(matches!(
+            screen(overrides, &H160::default(), &utxos).await,
+            Err(ScreeningError::Rejected(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn allowed_recipient_skips_transaction_overrides() {
+        let recipient = H160::from_slice(&[2; 20]);
+        let utxos = [utxo(1)];
+        let overrides = HashMap::from([
+            (hex::encode(recipient.0), true),
+            (display_txid(&utxos[0].outpoint.txid), false),
+        ]);
+        assert_eq!(screen(overrides, &recipient, &utxos).await, Ok(()));
+    }
+
+    #[tokio::test]
+    async fn https_verdict_is_parsed() {
+        const URL: &str = "https://kyt.com/screen";
+        let request = ScreeningRequest::new(&utxo(1), &H160::default());
+
+        let http = MockHttpOutcall::default()
+            .with_response(URL, r#"{"allowed":false,"reason":"sanctioned"}"#);
+        assert_eq!(
+            https_verdict(&http, URL, &request).await,
+            Ok(ScreeningVerdict::Rejected {
+                reason: "sanctioned".to_string()
+            })
+        );
+
+        let sent = &http.requests()[0];
+        assert_eq!(sent.method, HttpMethod::POST);
+        assert_eq!(sent.body, Some(serde_json::to_vec(&request).unwrap()));
+
+        let http = MockHttpOutcall::default().with_response(URL, r#"{"allowed":true}"#);
+        assert_eq!(
+            https_verdict(&http, URL, &request).await,
+            Ok(ScreeningVerdict::Allowed)
+        );
+        assert!(https_verdict(&MockHttpOutcall::default(), URL, &request)
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn subject_normalization() {
+        assert_eq!(normalize_subject(" 0xABcd "), "abcd");
+        assert_eq!(normalize_subject("abcd"), "abcd");
+    }
+
+    #[test]
+    fn https_provider_requires_https_url() {
+        let config = ScreeningConfig {
+            provider: ScreeningProvider::Https("http://kyt.com".to_string()),
+            fail_mode: ScreeningFailMode::Closed,
+        };
+        assert!(config.validate().is_err());
+
+        let config = ScreeningConfig {
+            provider: ScreeningProvider::Https("https://kyt.com".to_string()),
+            fail_mode: ScreeningFailMode::Open,
+        };
+        assert!(config.validate().is_ok());
+    }
+}
@@ -6,11 +6,12 @@ use ic_exports::ic_cdk::api::management_canister::bitcoin::{
     SendTransactionRequest,
 };
 
+use crate::fee_priority::FeePriority;
 use crate::interface::{DepositError, WithdrawError};
 
 pub(crate) trait UtxoProvider {
     async fn get_utxos(&self, address: &Address) -> Result<GetUtxosResponse, DepositError>;
-    async fn get_fee_rate(&self) -> Result<FeeRate, WithdrawError>;
+    async fn get_fee_rate(&self, priority: FeePriority) -> Result<FeeRate, WithdrawError>;
     async fn send_tx(&self, transaction: &Transaction) -> Result<(), WithdrawError>;
 }
 
@@ -51,7 +52,7 @@ impl UtxoProvider for IcUtxoProvider {
         Ok(response)
     }
 
-    async fn get_fee_rate(&self) -> Result<FeeRate, WithdrawError> {
+    async fn get_fee_rate(&self, priority: FeePriority) -> Result<FeeRate, WithdrawError> {
         let args = GetCurrentFeePercentilesRequest {
             network: self.network,
         };
@@ -63,24 +64,26 @@ impl UtxoProvider for IcUtxoProvider {
             })?
             .0;
 
-        let middle_percentile = if response.is_empty() {
-            match self.network {
+        let fee_rate = match priority.fee_rate(&response) {
+            Some(fee_rate) => fee_rate,
+            None => match self.network {
                 BitcoinNetwork::Regtest => DEFAULT_REGTEST_FEE,
                 _ => {
                     log::error!("Empty response for fee rate request");
                     return Err(WithdrawError::FeeRateRequest);
                 }
-            }
-        } else {
-            response[response.len() / 2]
+            },
         };
 
         log::trace!("Received fee rate percentiles: {response:?}");
 
-        log::info!("Using fee rate {}", middle_percentile / 1000);
+        log::info!(
+            "Using fee rate {} for {priority:?} priority",
+            fee_rate / 1000
+        );
 
-        FeeRate::from_sat_per_vb(middle_percentile / 1000).ok_or_else(|| {
-            log::error!("Invalid fee rate received from IC: {middle_percentile}");
+        FeeRate::from_sat_per_vb(fee_rate / 1000).ok_or_else(|| {
+            log::error!("Invalid fee rate received from IC: {fee_rate}");
             WithdrawError::FeeRateRequest
         })
     }
@@ -119,3 +122,52 @@ impl UtxoProvider for IcUtxoProvider {
         Ok(())
     }
 }
+
+#[cfg(test)]
+pub(crate) mod mock {
+    use std::collections::HashMap;
+
+    use ic_exports::ic_cdk::api::management_canister::bitcoin::Utxo;
+
+    use super::*;
+
+    /// Bitcoin API with the utxos set by the test. The fee rate is the one the IC uses for
+    /// regtest, and the transactions are accepted without being sent anywhere.
+    #[derive(Debug, Default)]
+    pub struct MockUtxoProvider {
+        utxos: HashMap<String, Vec<Utxo>>,
+        tip_height: u32,
+    }
+
+    impl MockUtxoProvider {
+        pub fn with_utxos(mut self, address: &Address, utxos: Vec<Utxo>, tip_height: u32) -> Self {
+            self.utxos.insert(address.to_string(), utxos);
+            self.tip_height = tip_height;
+            self
+        }
+    }
+
+    impl UtxoProvider for MockUtxoProvider {
+        async fn get_utxos(&self, address: &Address) -> Result<GetUtxosResponse, DepositError> {
+            Ok(GetUtxosResponse {
+                utxos: self
+                    .utxos
+                    .get(&address.to_string())
+                    .cloned()
+                    .unwrap_or_default(),
+                tip_block_hash: vec![],
+                tip_height: self.tip_height,
+                next_page: None,
+            })
+        }
+
+        async fn get_fee_rate(&self, _priority: FeePriority) -> Result<FeeRate, WithdrawError> {
+            FeeRate::from_sat_per_vb(DEFAULT_REGTEST_FEE / 1000)
+                .ok_or(WithdrawError::FeeRateRequest)
+        }
+
+        async fn send_tx(&self, _transaction: &Transaction) -> Result<(), WithdrawError> {
+            Ok(())
+        }
+    }
+}
@@ -9,22 +9,36 @@ use candid::types::{Serializer, Type};
 use candid::{CandidType, Deserialize};
 use did::H160;
 use ic_exports::ic_cdk::api::management_canister::bitcoin::{Outpoint, Utxo};
-use ic_exports::ic_kit::ic;
 use minter_contract_utils::bft_bridge_api::BurntEventData;
+use minter_contract_utils::btc_address::parse_btc_address_bytes;
+use minter_contract_utils::btc_confirmations::{
+    poll_watched, ConfirmationSource, WatchEvent, WatchedOutput,
+};
+use minter_contract_utils::derivation_path::DerivationPath as IcDerivationPath;
 use minter_contract_utils::operation_store::MinterOperationId;
+use minter_contract_utils::operation_trace;
+use minter_contract_utils::soft_caps::{Admission, Lane};
 use minter_did::id256::Id256;
 use ord_rs::wallet::{CreateEdictTxArgs, ScriptType, TxInputInfo};
 use ord_rs::OrdTransactionBuilder;
 use ordinals::RuneId;
 use serde::Deserializer;
 
-use crate::canister::get_operations_store;
+use crate::canister::{
+    get_bridged_balances, get_confirmation_watcher, get_fee_priorities, get_operations_store,
+    get_rune_limits_store, get_soft_cap_store, get_tx_journal, get_withdrawal_allowlist,
+    get_withdrawal_credits,
+};
+use crate::core::coin_selection::{RuneInput, RuneSelection};
+use crate::core::index_provider::{OrdIndexProvider, RuneIndexProvider};
 use crate::core::utxo_provider::{IcUtxoProvider, UtxoProvider};
-use crate::interface::WithdrawError;
+use crate::fee_priority::FeePriority;
+use crate::interface::{PreviewInput, PreviewOutput, WithdrawError, WithdrawalPreview};
 use crate::key::{get_derivation_path, get_derivation_path_ic, BtcSignerType};
 use crate::operation::{OperationState, RuneOperationStore};
 use crate::rune_info::RuneInfo;
 use crate::state::State;
+use crate::task::AVG_BLOCK_TIME;
 
 #[derive(Debug, Clone, CandidType, Deserialize)]
 pub struct RuneWithdrawalPayload {
@@ -34,6 +48,11 @@ pub struct RuneWithdrawalPayload {
     sender: H160,
     dst_address: String,
     status: WithdrawalStatus,
+    /// Wrapped token units which cannot be represented in rune units and so are not withdrawn.
+    /// They are credited to the next withdrawal of the rune by the sender.
+    token_remainder: Option<u128>,
+    /// Fee priority selected by the sender for their withdrawals when the tokens were burnt.
+    fee_priority: Option<FeePriority>,
 }
 
 impl RuneWithdrawalPayload {
@@ -46,19 +65,20 @@ impl RuneWithdrawalPayload {
             ..
         } = burnt_event_data;
 
-        let amount = amount.0.as_u128();
+        let Ok(amount) = u128::try_from(amount.0) else {
+            return Self::invalid(format!("Burnt amount {amount:?} does not fit into u128"));
+        };
 
-        let Ok(address_string) = String::from_utf8(recipient_id.clone()) else {
-            return Self::invalid(format!(
-                "Failed to decode recipient address from raw data: {recipient_id:?}"
-            ));
+        let address = match parse_btc_address_bytes(&recipient_id, state.network()) {
+            Ok(address) => address,
+            Err(err) => return Self::invalid(format!("Invalid recipient address: {err}")),
         };
 
-        let Ok(address) = Address::from_str(&address_string) else {
+        if !get_withdrawal_allowlist().allows(&sender, &address.to_string()) {
             return Self::invalid(format!(
-                "Failed to decode recipient address from string: {address_string}"
+                "Recipient address {address} is not in the withdrawal allowlist of {sender:?}"
             ));
-        };
+        }
 
         let Some(token_id) = Id256::from_slice(&to_token) else {
             return Self::invalid(format!(
@@ -81,13 +101,57 @@ impl RuneWithdrawalPayload {
             ));
         };
 
+        // The units left over by the previous burns of the sender are withdrawn with this one.
+        let credit = get_withdrawal_credits().get(&sender, rune_info.name());
+        let scaled = match state
+            .amount_scaling(&rune_info)
+            .token_to_rune(amount.saturating_add(credit))
+        {
+            Ok(scaled) => scaled,
+            Err(err) => {
+                return Self::invalid(format!(
+                    "Failed to convert {amount} wrapped token units into rune units: {err:?}"
+                ))
+            }
+        };
+
+        if scaled.amount == 0 {
+            get_withdrawal_credits().set(&sender, rune_info.name(), scaled.remainder);
+            return Self::invalid(format!(
+                "Burnt amount {amount} is less than one unit of rune {} and is credited to the next withdrawal",
+                rune_info.name()
+            ));
+        }
+
+        if let Err(below) =
+            get_rune_limits_store().check_min_withdrawal(rune_info.name(), scaled.amount)
+        {
+            return Self::invalid(format!(
+                "Burnt amount of {} rune {} units is below the minimum withdrawal amount {}",
+                below.amount,
+                rune_info.name(),
+                below.min_amount
+            ));
+        }
+
+        if scaled.remainder > 0 {
+            log::info!(
+                "{} wrapped token units cannot be represented as rune {} units and are credited to the next withdrawal of {sender:?}",
+                scaled.remainder,
+                rune_info.name()
+            );
+        }
+        get_withdrawal_credits().set(&sender, rune_info.name(), scaled.remainder);
+
         Self {
             rune_info,
-            amount,
-            request_ts: ic::time(),
-            sender,
-            dst_address: address.assume_checked().to_string(),
+            amount: scaled.amount,
+            request_ts: state.clock().now(),
+            dst_address: address.to_string(),
             status: WithdrawalStatus::Scheduled,
+            token_remainder: (scaled.remainder > 0).then_some(scaled.remainder),
+            fee_priority: get_fee_priorities().get(&sender),
+            sender,
         }
     }
 
@@ -99,6 +163,8 @@ impl RuneWithdrawalPayload {
             sender: Default::default(),
             dst_address: "".to_string(),
             status: WithdrawalStatus::InvalidRequest(reason),
+            token_remainder: None,
+            fee_priority: None,
         }
     }
 
@@ -117,7 +183,9 @@ impl RuneWithdrawalPayload {
     pub fn is_complete(&self) -> bool {
         matches!(
             self.status,
-            WithdrawalStatus::TxSent { .. } | WithdrawalStatus::InvalidRequest(_)
+            WithdrawalStatus::TxConfirmed { .. }
+                | WithdrawalStatus::ConfirmationTimedOut { .. }
+                | WithdrawalStatus::InvalidRequest(_)
         )
     }
 }
@@ -126,13 +194,37 @@ impl RuneWithdrawalPayload {
 pub enum WithdrawalStatus {
     InvalidRequest(String),
     Scheduled,
-    TxSigned { transaction: DidTransaction },
-    TxSent { transaction: DidTransaction },
+    /// The withdrawal exceeds the hourly soft cap of the withdrawals and waits in the queue.
+    Queued {
+        queued_at: u64,
+    },
+    TxSigned {
+        transaction: DidTransaction,
+    },
+    /// The transaction is sent and the bridge waits for the confirmations of its change output.
+    TxSent {
+        transaction: DidTransaction,
+    },
+    TxConfirmed {
+        transaction: DidTransaction,
+        confirmations: u32,
+    },
+    /// The transaction is not confirmed within the watch timeout, e.g. it is dropped from the
+    /// mempool.
+    ConfirmationTimedOut {
+        transaction: DidTransaction,
+    },
 }
 
 #[derive(Debug, Clone)]
 pub struct DidTransaction(Transaction);
 
+impl From<Transaction> for DidTransaction {
+    fn from(tx: Transaction) -> Self {
+        Self(tx)
+    }
+}
+
 impl CandidType for DidTransaction {
     fn _ty() -> Type {
         <Vec<u8> as CandidType>::_ty()
@@ -192,6 +284,10 @@ impl Withdrawal<IcUtxoProvider> {
 }
 
 impl<UTXO: UtxoProvider> Withdrawal<UTXO> {
+    fn now(&self) -> u64 {
+        self.state.borrow().clock().now()
+    }
+
     pub async fn withdraw(
         &mut self,
         operation_id: MinterOperationId,
@@ -215,80 +311,115 @@ impl<UTXO: UtxoProvider> Withdrawal<UTXO> {
             amount,
             sender,
             status,
+            fee_priority,
             ..
         } = payload.clone();
 
-        if !matches!(status, WithdrawalStatus::Scheduled) {
-            return Err(WithdrawError::InternalError(format!("Attempted to initiate withdrawal flow for operation {operation_id} but it was not in `Scheduled` state: {operation:?}")));
-        }
+        let (tx, used_outpoints) = match status {
+            WithdrawalStatus::Scheduled | WithdrawalStatus::Queued { .. } => {
+                // Rune amounts are not comparable, so the soft cap counts the withdrawals.
+                let admission = get_soft_cap_store().admit(
+                    &operation_id.to_string(),
+                    Lane::Withdrawal,
+                    1,
+                    vec![],
+                    self.now(),
+                );
+                if let Admission::Queued(position) = admission {
+                    if matches!(status, WithdrawalStatus::Scheduled) {
+                        self.operation_store.update(
+                            operation_id,
+                            OperationState::Withdrawal(payload.clone().with_status(
+                                WithdrawalStatus::Queued {
+                                    queued_at: position.queued_at,
+                                },
+                            )),
+                        );
+                    }
+                    return Err(WithdrawError::Queued {
+                        position: position.position,
+                        eta: position.eta,
+                    });
+                }
 
-        let (_, mut utxos) = self.state.borrow().ledger().load_unspent_utxos();
-        let funding_address = self.get_transit_address(&sender).await;
-        let mut funding_utxos: Vec<_> = self
-            .utxo_provider
-            .get_utxos(&funding_address)
-            .await
-            .map_err(|_e| WithdrawError::NoInputs)?
-            .utxos
-            .into_iter()
-            .map(|utxo| TxInputInfo {
-                outpoint: OutPoint {
-                    txid: Txid::from_slice(&utxo.outpoint.txid).unwrap(),
-                    vout: utxo.outpoint.vout,
-                },
-                tx_out: TxOut {
-                    value: Amount::from_sat(utxo.value),
-                    script_pubkey: funding_address.script_pubkey(),
-                },
-                derivation_path: get_derivation_path(&sender),
-            })
-            .collect();
+                let (tx, utxos) = self
+                    .sign_withdraw_transaction(
+                        amount,
+                        &dst_address,
+                        &sender,
+                        &rune_info,
+                        fee_priority.unwrap_or_default(),
+                    )
+                    .await?;
 
-        utxos.append(&mut funding_utxos);
+                get_tx_journal().record(operation_id, &tx, self.now());
+                self.operation_store.update(
+                    operation_id,
+                    OperationState::Withdrawal(payload.clone().with_status(
+                        WithdrawalStatus::TxSigned {
+                            transaction: DidTransaction(tx.clone()),
+                        },
+                    )),
+                );
 
-        let tx = self
-            .build_withdraw_transaction(
-                amount,
-                dst_address.clone(),
-                funding_address,
-                rune_info.id(),
-                utxos.clone(),
-            )
-            .await?;
+                let outpoints = utxos.into_iter().map(|utxo| utxo.outpoint).collect();
+                (tx, outpoints)
+            }
+            // The transaction was signed, but the operation did not record it as sent, so it is
+            // broadcast again instead of signing a new one spending the same utxos.
+            WithdrawalStatus::TxSigned { transaction } => {
+                let tx = get_tx_journal()
+                    .get(operation_id)
+                    .map(|entry| entry.transaction())
+                    .unwrap_or(transaction.0);
+                log::info!(
+                    "Broadcasting signed withdrawal transaction {} of operation {operation_id} again",
+                    tx.txid()
+                );
 
-        self.operation_store.update(
-            operation_id,
-            OperationState::Withdrawal(payload.clone().with_status(WithdrawalStatus::TxSigned {
-                transaction: DidTransaction(tx.clone()),
-            })),
-        );
+                let outpoints = tx.input.iter().map(|input| input.previous_output).collect();
+                (tx, outpoints)
+            }
+            _ => {
+                return Err(WithdrawError::InternalError(format!("Attempted to initiate withdrawal flow for operation {operation_id} but it was not in `Scheduled`, `Queued` or `TxSigned` state: {operation:?}")));
+            }
+        };
 
         self.utxo_provider.send_tx(&tx).await?;
 
         {
             let mut state = self.state.borrow_mut();
+            let now = state.clock().now();
             let ledger = state.ledger_mut();
-            for utxo in utxos {
-                ledger.mark_as_used(utxo.outpoint.into(), dst_address.clone());
+            for outpoint in used_outpoints {
+                ledger.mark_as_used(outpoint.into(), dst_address.clone(), now);
             }
         }
 
+        // The credited units are counted once they are withdrawn.
+        let burnt_amount = self
+            .state
+            .borrow()
+            .amount_scaling(&rune_info)
+            .rune_to_token(amount)
+            .map(|scaled| scaled.amount)
+            .unwrap_or_default();
+        get_bridged_balances().sub_withdrawal(&sender, rune_info.name, amount, burnt_amount);
+        get_rune_limits_store().sub_bridged(rune_info.name, amount);
+
         let change_address = self.get_change_address().await;
 
-        const CHANGE_OUTPOINT_INDEX: usize = 1;
-        // Make sure that the transaction builder code is not change and the change outpoint
-        // is where we expect it to be. If not, panic until the code of the canister is fixed.
-        assert_eq!(
-            tx.output[CHANGE_OUTPOINT_INDEX].script_pubkey,
-            change_address.script_pubkey()
-        );
+        // The transaction is built by the canister, so a missing change output means the code of
+        // the transaction builder is changed. Panic until the code of the canister is fixed.
+        let change_index = Self::btc_change_index(&tx, &change_address)
+            .expect("withdraw transaction must have a change output");
 
         let change_utxo = Utxo {
             outpoint: Outpoint {
                 txid: tx.txid().as_byte_array().to_vec(),
-                vout: CHANGE_OUTPOINT_INDEX as u32,
+                vout: change_index as u32,
             },
-            value: tx.output[CHANGE_OUTPOINT_INDEX].value.to_sat(),
+            value: tx.output[change_index].value.to_sat(),
             height: 0,
         };
 
@@ -304,10 +435,127 @@ impl<UTXO: UtxoProvider> Withdrawal<UTXO> {
                 transaction: DidTransaction(tx.clone()),
             })),
         );
+        get_tx_journal().remove(operation_id);
+
+        let required_confirmations = self.state.borrow().min_confirmations();
+        get_confirmation_watcher().watch(
+            operation_id,
+            WatchedOutput {
+                txid: tx.txid().as_byte_array().to_vec(),
+                vout: change_index as u32,
+                address: change_address.to_string(),
+                required_confirmations,
+                registered_at: self.now(),
+            },
+        );
 
         Ok(tx.txid())
     }
 
+    /// Completes the sent withdrawals whose change outputs are confirmed by the `source`, or
+    /// are not confirmed within the watch timeout.
+    pub async fn check_confirmations(&mut self, source: &impl ConfirmationSource) {
+        let watched = get_confirmation_watcher().watched();
+        for (operation_id, event) in poll_watched(source, watched, self.now()).await {
+            get_confirmation_watcher().unwatch(operation_id);
+
+            let Some(OperationState::Withdrawal(payload)) = self.operation_store.get(operation_id)
+            else {
+                log::warn!("Watched withdrawal {operation_id} is not found");
+                continue;
+            };
+            let WithdrawalStatus::TxSent { transaction } = payload.status.clone() else {
+                log::warn!("Watched withdrawal {operation_id} is not in `TxSent` state");
+                continue;
+            };
+
+            let status = match event {
+                WatchEvent::Confirmed { confirmations } => {
+                    operation_trace::record(
+                        operation_id,
+                        log::Level::Info,
+                        format!("event=confirmed confirmations={confirmations}"),
+                    );
+                    WithdrawalStatus::TxConfirmed {
+                        transaction,
+                        confirmations,
+                    }
+                }
+                WatchEvent::TimedOut => {
+                    operation_trace::record(
+                        operation_id,
+                        log::Level::Warn,
+                        "event=confirmation_timed_out",
+                    );
+                    WithdrawalStatus::ConfirmationTimedOut { transaction }
+                }
+            };
+            self.operation_store.update(
+                operation_id,
+                OperationState::Withdrawal(payload.with_status(status)),
+            );
+        }
+    }
+
+    /// Builds and signs the withdrawal transaction from the canister utxos and the BTC at the
+    /// transit address of the `sender`. Returns the transaction and the utxos it may spend.
+    async fn sign_withdraw_transaction(
+        &self,
+        amount: u128,
+        dst_address: &Address,
+        sender: &H160,
+        rune_info: &RuneInfo,
+        fee_priority: FeePriority,
+    ) -> Result<(Transaction, Vec<TxInputInfo>), WithdrawError> {
+        let (_, ledger_utxos) = self.state.borrow().ledger().load_unspent_utxos();
+        let selection = self
+            .select_inputs(&self.index_provider(), rune_info, amount, ledger_utxos)
+            .await?;
+        log::debug!(
+            "Selected {} inputs for the withdrawal of {amount} {}, rune change: {}",
+            selection.inputs.len(),
+            rune_info.name,
+            selection.change
+        );
+
+        let mut utxos = selection.inputs;
+        let funding_address = self.get_transit_address(sender).await;
+        let mut funding_utxos: Vec<_> = self
+            .utxo_provider
+            .get_utxos(&funding_address)
+            .await
+            .map_err(|_e| WithdrawError::NoInputs)?
+            .utxos
+            .into_iter()
+            .map(|utxo| TxInputInfo {
+                outpoint: OutPoint {
+                    txid: Txid::from_slice(&utxo.outpoint.txid).unwrap(),
+                    vout: utxo.outpoint.vout,
+                },
+                tx_out: TxOut {
+                    value: Amount::from_sat(utxo.value),
+                    script_pubkey: funding_address.script_pubkey(),
+                },
+                derivation_path: get_derivation_path(sender),
+            })
+            .collect();
+
+        utxos.append(&mut funding_utxos);
+
+        let tx = self
+            .build_withdraw_transaction(
+                amount,
+                dst_address.clone(),
+                funding_address,
+                rune_info.id(),
+                utxos.clone(),
+                fee_priority,
+            )
+            .await?;
+
+        Ok((tx, utxos))
+    }
+
     async fn get_transit_address(&self, eth_address: &H160) -> Address {
         self.signer
             .get_transit_address(eth_address, self.network)
@@ -321,20 +569,221 @@ impl<UTXO: UtxoProvider> Withdrawal<UTXO> {
         change_address: Address,
         rune: RuneId,
         inputs: Vec<TxInputInfo>,
+        fee_priority: FeePriority,
     ) -> Result<Transaction, WithdrawError> {
         if inputs.is_empty() {
             return Err(WithdrawError::NoInputs);
         }
 
+        let builder = self.tx_builder();
+        let args = self
+            .edict_tx_args(
+                amount,
+                dst_address,
+                change_address,
+                rune,
+                inputs,
+                fee_priority,
+            )
+            .await?;
+        let unsigned_tx = self.create_unsigned_tx(&builder, &args)?;
+        let signed_tx = builder
+            .sign_transaction(&unsigned_tx, &args.inputs)
+            .await
+            .map_err(|err| {
+                log::error!("Failed to sign withdraw transaction: {err:?}");
+                WithdrawError::TransactionSigning
+            })?;
+
+        Ok(signed_tx)
+    }
+
+    /// Builds the withdrawal transaction from the canister utxos without signing or sending it,
+    /// and returns its inputs, outputs and fee.
+    pub async fn preview_withdraw(
+        &self,
+        amount: u128,
+        dst_address: Address,
+        rune: RuneId,
+        fee_priority: FeePriority,
+    ) -> Result<WithdrawalPreview, WithdrawError> {
+        self.preview_withdraw_with(
+            &self.index_provider(),
+            amount,
+            dst_address,
+            rune,
+            fee_priority,
+        )
+        .await
+    }
+
+    async fn preview_withdraw_with(
+        &self,
+        index_provider: &impl RuneIndexProvider,
+        amount: u128,
+        dst_address: Address,
+        rune: RuneId,
+        fee_priority: FeePriority,
+    ) -> Result<WithdrawalPreview, WithdrawError> {
+        let (_, ledger_utxos) = self.state.borrow().ledger().load_unspent_utxos();
+        if ledger_utxos.is_empty() {
+            return Err(WithdrawError::NoInputs);
+        }
+
+        let rune_info = self.state.borrow().rune_info(rune).ok_or_else(|| {
+            WithdrawError::InternalError(format!("rune {rune} is not in the list of runes"))
+        })?;
+        get_rune_limits_store()
+            .check_min_withdrawal(rune_info.name(), amount)
+            .map_err(|below| WithdrawError::AmountBelowMinimum {
+                min_amount: below.min_amount,
+                amount: below.amount,
+            })?;
+        let selection = self
+            .select_inputs(index_provider, &rune_info, amount, ledger_utxos)
+            .await?;
+        let rune_change = selection.change;
+        let inputs = selection.inputs;
+
+        let change_address = self.get_change_address().await;
+        let builder = self.tx_builder();
+        let args = self
+            .edict_tx_args(
+                amount,
+                dst_address,
+                change_address,
+                rune,
+                inputs,
+                fee_priority,
+            )
+            .await?;
+        let unsigned_tx = self.create_unsigned_tx(&builder, &args)?;
+
+        Ok(self.withdrawal_preview(&unsigned_tx, &args, rune_change, fee_priority))
+    }
+
+    /// Describes the unsigned withdrawal transaction `unsigned_tx` built with the `args`.
+    fn withdrawal_preview(
+        &self,
+        unsigned_tx: &Transaction,
+        args: &CreateEdictTxArgs,
+        rune_change: u128,
+        fee_priority: FeePriority,
+    ) -> WithdrawalPreview {
+        let inputs: Vec<_> = unsigned_tx
+            .input
+            .iter()
+            .filter_map(|tx_in| {
+                args.inputs
+                    .iter()
+                    .find(|input| input.outpoint == tx_in.previous_output)
+            })
+            .map(|input| PreviewInput {
+                outpoint: Outpoint {
+                    txid: input.outpoint.txid.as_byte_array().to_vec(),
+                    vout: input.outpoint.vout,
+                },
+                value: input.tx_out.value.to_sat(),
+            })
+            .collect();
+        let outputs: Vec<_> = unsigned_tx
+            .output
+            .iter()
+            .map(|tx_out| PreviewOutput {
+                address: Address::from_script(&tx_out.script_pubkey, self.network)
+                    .ok()
+                    .map(|address| address.to_string()),
+                value: tx_out.value.to_sat(),
+            })
+            .collect();
+
+        let inputs_value: u64 = inputs.iter().map(|input| input.value).sum();
+        let outputs_value: u64 = outputs.iter().map(|output| output.value).sum();
+        let min_confirmations = self.state.borrow().min_confirmations();
+
+        WithdrawalPreview {
+            inputs,
+            outputs,
+            fee: inputs_value.saturating_sub(outputs_value),
+            fee_priority,
+            fee_rate_sat_per_vb: args.fee_rate.to_sat_per_vb_ceil(),
+            rune_change,
+            estimated_confirmation_time_secs: (AVG_BLOCK_TIME * min_confirmations.max(1)).as_secs(),
+        }
+    }
+
+    /// Chooses the ledger `utxos` to spend for the withdrawal of `amount` runes. The utxos
+    /// carrying the rune are selected with the configured strategy, the utxos without runes are
+    /// all spent to pay the fee, and the utxos with other runes are left untouched.
+    async fn select_inputs(
+        &self,
+        index_provider: &impl RuneIndexProvider,
+        rune_info: &RuneInfo,
+        amount: u128,
+        utxos: Vec<TxInputInfo>,
+    ) -> Result<RuneSelection, WithdrawError> {
+        let mut btc_inputs = vec![];
+        let mut candidates = vec![];
+        for input in utxos {
+            let utxo = Utxo {
+                outpoint: Outpoint {
+                    txid: input.outpoint.txid.as_byte_array().to_vec(),
+                    vout: input.outpoint.vout,
+                },
+                value: input.tx_out.value.to_sat(),
+                height: 0,
+            };
+            let rune_amounts = index_provider
+                .get_rune_amounts(&utxo)
+                .await
+                .map_err(|err| {
+                    WithdrawError::InternalError(format!(
+                        "failed to get rune amounts of utxo {}: {err:?}",
+                        input.outpoint
+                    ))
+                })?;
+
+            if rune_amounts.is_empty() {
+                btc_inputs.push(input);
+            } else if let Some(&rune_amount) = rune_amounts.get(&rune_info.name) {
+                candidates.push(RuneInput {
+                    input,
+                    amount: rune_amount,
+                });
+            }
+        }
+
+        let strategy = self.state.borrow().coin_selection();
+        let mut selection = strategy.select(candidates, amount)?;
+        selection.inputs.append(&mut btc_inputs);
+
+        Ok(selection)
+    }
+
+    fn index_provider(&self) -> OrdIndexProvider {
+        OrdIndexProvider::new(self.state.borrow().indexer_url())
+    }
+
+    fn tx_builder(&self) -> OrdTransactionBuilder {
         let public_key = self.state.borrow().public_key();
         let wallet = self.state.borrow().wallet();
 
-        let builder = OrdTransactionBuilder::new(public_key, ScriptType::P2WSH, wallet);
+        OrdTransactionBuilder::new(public_key, ScriptType::P2WSH, wallet)
+    }
 
+    async fn edict_tx_args(
+        &self,
+        amount: u128,
+        dst_address: Address,
+        change_address: Address,
+        rune: RuneId,
+        inputs: Vec<TxInputInfo>,
+        fee_priority: FeePriority,
+    ) -> Result<CreateEdictTxArgs, WithdrawError> {
         let rune_change_address = self.get_change_address().await;
-        let fee_rate = self.utxo_provider.get_fee_rate().await?;
+        let fee_rate = self.utxo_provider.get_fee_rate(fee_priority).await?;
 
-        let args = CreateEdictTxArgs {
+        Ok(CreateEdictTxArgs {
             rune,
             inputs,
             destination: dst_address,
@@ -342,27 +791,422 @@ impl<UTXO: UtxoProvider> Withdrawal<UTXO> {
             rune_change_address,
             amount,
             fee_rate,
-        };
-        let unsigned_tx = builder.create_edict_transaction(&args).map_err(|err| {
+        })
+    }
+
+    fn create_unsigned_tx(
+        &self,
+        builder: &OrdTransactionBuilder,
+        args: &CreateEdictTxArgs,
+    ) -> Result<Transaction, WithdrawError> {
+        let mut tx = builder.create_edict_transaction(args).map_err(|err| {
             log::warn!("Failed to create withdraw transaction: {err:?}");
             WithdrawError::TransactionCreation
         })?;
-        let signed_tx = builder
-            .sign_transaction(&unsigned_tx, &args.inputs)
-            .await
-            .map_err(|err| {
-                log::error!("Failed to sign withdraw transaction: {err:?}");
-                WithdrawError::TransactionSigning
-            })?;
 
-        Ok(signed_tx)
+        let postage = self.state.borrow().withdrawal_postage();
+        Self::apply_postage(&mut tx, &args.destination, &args.change_address, postage)?;
+
+        Ok(tx)
+    }
+
+    /// Returns the index of the BTC change output of the withdraw transaction. The rune change
+    /// output may pay to the same address, but it always precedes the BTC change output.
+    fn btc_change_index(tx: &Transaction, change_address: &Address) -> Option<usize> {
+        let change_script = change_address.script_pubkey();
+        tx.output
+            .iter()
+            .rposition(|tx_out| tx_out.script_pubkey == change_script)
+    }
+
+    /// Sets the value of the destination output to `postage`, taking the difference from (or
+    /// returning it to) the BTC change output, and checks that the destination output is not dust.
+    fn apply_postage(
+        tx: &mut Transaction,
+        destination: &Address,
+        change_address: &Address,
+        postage: u64,
+    ) -> Result<(), WithdrawError> {
+        let dst_script = destination.script_pubkey();
+        let dust = dst_script.dust_value().to_sat();
+        if postage < dust {
+            return Err(WithdrawError::AmountBelowDust {
+                value: postage,
+                dust,
+            });
+        }
+
+        let Some(dst_index) = tx
+            .output
+            .iter()
+            .position(|tx_out| tx_out.script_pubkey == dst_script)
+        else {
+            log::error!("Destination output is not found in the withdraw transaction: {tx:?}");
+            return Err(WithdrawError::TransactionCreation);
+        };
+
+        let Some(change_index) = Self::btc_change_index(tx, change_address)
+            .filter(|change_index| *change_index != dst_index)
+        else {
+            log::error!("Change output is not found in the withdraw transaction: {tx:?}");
+            return Err(WithdrawError::TransactionCreation);
+        };
+
+        let current = tx.output[dst_index].value.to_sat();
+        let change = tx.output[change_index].value.to_sat();
+        let available = change + current;
+        let Some(new_change) = available.checked_sub(postage) else {
+            log::warn!("Not enough BTC to pay {postage} sats postage, change value: {change}");
+            return Err(WithdrawError::NotEnoughBtc {
+                available,
+                required: postage,
+            });
+        };
+
+        let change_dust = tx.output[change_index].script_pubkey.dust_value().to_sat();
+        if new_change < change_dust {
+            return Err(WithdrawError::AmountBelowDust {
+                value: new_change,
+                dust: change_dust,
+            });
+        }
+
+        tx.output[dst_index].value = Amount::from_sat(postage);
+        tx.output[change_index].value = Amount::from_sat(new_change);
+
+        Ok(())
     }
 
     async fn get_change_address(&self) -> Address {
         self.get_transit_address(&H160::default()).await
     }
 
-    fn get_change_derivation_path(&self) -> Vec<Vec<u8>> {
+    fn get_change_derivation_path(&self) -> IcDerivationPath {
         get_derivation_path_ic(&H160::default())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::absolute::LockTime;
+    use bitcoin::transaction::Version;
+    use bitcoin::{FeeRate, PrivateKey, Script, ScriptBuf, TxIn};
+    use ic_exports::ic_kit::MockContext;
+    use ord_rs::wallet::LocalSigner;
+
+    use super::*;
+    use crate::canister::get_state;
+    use crate::core::http_outcall::mock::MockHttpOutcall;
+    use crate::core::utxo_provider::mock::MockUtxoProvider;
+    use crate::rune_info::RuneName;
+
+    const INDEXER_URL: &str = "https://indexer.com";
+
+    fn change_address() -> Address {
+        Address::p2wsh(Script::from_bytes(&[0]), Network::Regtest)
+    }
+
+    fn dst_address() -> Address {
+        Address::p2wsh(Script::from_bytes(&[1]), Network::Regtest)
+    }
+
+    fn tx_out(address: &Address, value: u64) -> TxOut {
+        TxOut {
+            value: Amount::from_sat(value),
+            script_pubkey: address.script_pubkey(),
+        }
+    }
+
+    fn withdraw_tx(btc_change: u64, dst_value: u64) -> Transaction {
+        Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![],
+            output: vec![
+                tx_out(&change_address(), 10_000),
+                tx_out(&change_address(), btc_change),
+                tx_out(&dst_address(), dst_value),
+                TxOut {
+                    value: Amount::ZERO,
+                    script_pubkey: ScriptBuf::new_op_return(&[]),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn postage_is_moved_from_change() {
+        let mut tx = withdraw_tx(50_000, 10_000);
+        Withdrawal::<IcUtxoProvider>::apply_postage(
+            &mut tx,
+            &dst_address(),
+            &change_address(),
+            4_000,
+        )
+        .unwrap();
+
+        assert_eq!(tx.output[2].value.to_sat(), 4_000);
+        assert_eq!(tx.output[1].value.to_sat(), 56_000);
+        assert_eq!(tx.output[0].value.to_sat(), 10_000);
+
+        Withdrawal::<IcUtxoProvider>::apply_postage(
+            &mut tx,
+            &dst_address(),
+            &change_address(),
+            20_000,
+        )
+        .unwrap();
+        assert_eq!(tx.output[2].value.to_sat(), 20_000);
+        assert_eq!(tx.output[1].value.to_sat(), 40_000);
+    }
+
+    #[test]
+    fn change_output_is_found_by_script() {
+        let mut tx = withdraw_tx(50_000, 10_000);
+        let dst_output = tx.output.remove(2);
+        tx.output.insert(0, dst_output);
+        Withdrawal::<IcUtxoProvider>::apply_postage(
+            &mut tx,
+            &dst_address(),
+            &change_address(),
+            4_000,
+        )
+        .unwrap();
+
+        assert_eq!(tx.output[0].value.to_sat(), 4_000);
+        assert_eq!(tx.output[1].value.to_sat(), 10_000);
+        assert_eq!(tx.output[2].value.to_sat(), 56_000);
+    }
+
+    #[test]
+    fn postage_below_dust_is_rejected() {
+        let mut tx = withdraw_tx(50_000, 10_000);
+        let dust = dst_address().script_pubkey().dust_value().to_sat();
+
+        let result = Withdrawal::<IcUtxoProvider>::apply_postage(
+            &mut tx,
+            &dst_address(),
+            &change_address(),
+            dust - 1,
+        );
+        assert!(matches!(
+            result,
+            Err(WithdrawError::AmountBelowDust { value, dust: d }) if value == dust - 1 && d == dust
+        ));
+    }
+
+    #[test]
+    fn postage_exceeding_change_is_rejected() {
+        let mut tx = withdraw_tx(1_000, 10_000);
+        let result = Withdrawal::<IcUtxoProvider>::apply_postage(
+            &mut tx,
+            &dst_address(),
+            &change_address(),
+            20_000,
+        );
+        assert!(matches!(
+            result,
+            Err(WithdrawError::NotEnoughBtc {
+                available: 11_000,
+                required: 20_000
+            })
+        ));
+    }
+
+    fn ledger_input(id: u8) -> TxInputInfo {
+        TxInputInfo {
+            outpoint: OutPoint {
+                txid: Txid::from_byte_array([id; 32]),
+                vout: 0,
+            },
+            tx_out: tx_out(&change_address(), 10_000),
+            derivation_path: get_derivation_path(&H160::default()),
+        }
+    }
+
+    fn output_response(runes: &[(&str, u128)]) -> String {
+        let runes: Vec<String> = runes
+            .iter()
+            .map(|(rune, amount)| {
+                format!(r#"["{rune}",{{"amount":{amount},"divisibility":0,"symbol":null}}]"#)
+            })
+            .collect();
+        format!(
+            r#"{{"address":"bc1q","runes":[{}],"spent":false}}"#,
+            runes.join(",")
+        )
+    }
+
+    fn index_provider(utxos: &[(u8, String)]) -> OrdIndexProvider<MockHttpOutcall> {
+        let mut http = MockHttpOutcall::default();
+        for (id, response) in utxos {
+            let url = format!("{INDEXER_URL}/output/{}", ledger_input(*id).outpoint);
+            http = http.with_response(&url, response.as_str());
+        }
+        OrdIndexProvider::with_http(INDEXER_URL.to_string(), http)
+    }
+
+    fn test_withdrawal() -> Withdrawal<MockUtxoProvider> {
+        Withdrawal {
+            state: get_state(),
+            utxo_provider: MockUtxoProvider::default(),
+            signer: BtcSignerType::Local(LocalSigner::new(
+                PrivateKey::from_slice(&[1; 32], Network::Regtest).unwrap(),
+            )),
+            network: Network::Regtest,
+            operation_store: get_operations_store(),
+        }
+    }
+
+    fn test_rune_info() -> RuneInfo {
+        RuneInfo {
+            name: RuneName::from_str("TESTRUNE").unwrap(),
+            decimals: 0,
+            block: 1,
+            tx: 1,
+        }
+    }
+
+    #[tokio::test]
+    async fn withdrawal_combines_rune_utxos() {
+        MockContext::new().inject();
+
+        let utxos = [
+            (1, output_response(&[("TEST•RUNE", 60)])),
+            (2, output_response(&[("TEST•RUNE", 50)])),
+            (3, output_response(&[("TEST•RUNE", 30)])),
+            (4, output_response(&[])),
+            (5, output_response(&[("OTHER•RUNE", 500)])),
+        ];
+        let index_provider = index_provider(&utxos);
+        let withdrawal = test_withdrawal();
+        let rune_info = test_rune_info();
+        let ledger_utxos = utxos.iter().map(|(id, _)| ledger_input(*id)).collect();
+
+        let selection = withdrawal
+            .select_inputs(&index_provider, &rune_info, 100, ledger_utxos)
+            .await
+            .unwrap();
+        let mut selected: Vec<u8> = selection
+            .inputs
+            .iter()
+            .map(|input| input.outpoint.txid.to_byte_array()[0])
+            .collect();
+        selected.sort();
+        assert_eq!(selected, vec![1, 2, 4]);
+        assert_eq!(selection.change, 10);
+
+        let ledger_utxos = utxos.iter().map(|(id, _)| ledger_input(*id)).collect();
+        let result = withdrawal
+            .select_inputs(&index_provider, &rune_info, 200, ledger_utxos)
+            .await;
+        assert!(matches!(
+            result,
+            Err(WithdrawError::InsufficientFunds {
+                available: 140,
+                requested: 200,
+            })
+        ));
+    }
+
+    #[test]
+    fn preview_reports_fee_and_amounts() {
+        MockContext::new().inject();
+
+        let inputs = vec![ledger_input(1), ledger_input(4)];
+        let mut tx = withdraw_tx(5_000, 4_000);
+        tx.input = inputs
+            .iter()
+            .map(|input| TxIn {
+                previous_output: input.outpoint,
+                ..Default::default()
+            })
+            .collect();
+        let args = CreateEdictTxArgs {
+            rune: test_rune_info().id(),
+            inputs,
+            destination: dst_address(),
+            change_address: change_address(),
+            rune_change_address: change_address(),
+            amount: 100,
+            fee_rate: FeeRate::from_sat_per_vb(3).unwrap(),
+        };
+
+        let preview = test_withdrawal().withdrawal_preview(&tx, &args, 10, FeePriority::Fast);
+        let input_values: Vec<u64> = preview.inputs.iter().map(|input| input.value).collect();
+        assert_eq!(input_values, vec![10_000, 10_000]);
+        assert_eq!(preview.inputs[0].outpoint.txid, vec![1; 32]);
+
+        let output_values: Vec<u64> = preview.outputs.iter().map(|output| output.value).collect();
+        assert_eq!(output_values, vec![10_000, 5_000, 4_000, 0]);
+        assert_eq!(preview.outputs[2].address, Some(dst_address().to_string()));
+        assert_eq!(preview.outputs[3].address, None);
+
+        assert_eq!(preview.fee, 1_000);
+        assert_eq!(preview.fee_rate_sat_per_vb, 3);
+        assert_eq!(preview.fee_priority, FeePriority::Fast);
+        assert_eq!(preview.rune_change, 10);
+    }
+
+    #[tokio::test]
+    async fn preview_without_enough_runes_is_rejected() {
+        MockContext::new().inject();
+
+        let utxos = [
+            (1, output_response(&[("TEST•RUNE", 60)])),
+            (2, output_response(&[("TEST•RUNE", 50)])),
+        ];
+        let index_provider = index_provider(&utxos);
+        let withdrawal = test_withdrawal();
+        let rune_info = test_rune_info();
+
+        let result = withdrawal
+            .preview_withdraw_with(
+                &index_provider,
+                100,
+                dst_address(),
+                rune_info.id(),
+                FeePriority::Standard,
+            )
+            .await;
+        assert!(matches!(result, Err(WithdrawError::NoInputs)));
+
+        {
+            let state = get_state();
+            let mut state = state.borrow_mut();
+            state.update_rune_list([(rune_info.name, rune_info)].into());
+            for (id, _) in &utxos {
+                let utxo = Utxo {
+                    outpoint: Outpoint {
+                        txid: vec![*id; 32],
+                        vout: 0,
+                    },
+                    value: 10_000,
+                    height: 0,
+                };
+                state.ledger_mut().deposit(
+                    &[utxo],
+                    &change_address(),
+                    get_derivation_path_ic(&H160::default()),
+                );
+            }
+        }
+
+        let result = withdrawal
+            .preview_withdraw_with(
+                &index_provider,
+                200,
+                dst_address(),
+                rune_info.id(),
+                FeePriority::Standard,
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(WithdrawError::InsufficientFunds {
+                available: 110,
+                requested: 200,
+            })
+        ));
+    }
+}
@@ -0,0 +1,116 @@
+//! Fee priorities of the withdrawal transactions.
+//!
+//! A user selects the priority of their withdrawals with the `FEE_PRIORITY_TYPE` notification sent
+//! through the BftBridge before burning the wrapped tokens. The priority picks the percentile of
+//! the current fee rates used for the withdrawal transaction. The fee is paid from the BTC at the
+//! transit address of the user, so a faster withdrawal costs the user a higher fee.
+
+use std::borrow::Cow;
+
+use candid::{CandidType, Decode, Deserialize, Encode};
+use did::H160;
+use ic_stable_structures::stable_structures::Memory;
+use ic_stable_structures::{BTreeMapStructure, Bound, StableBTreeMap, Storable};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, CandidType, Deserialize)]
+pub enum FeePriority {
+    /// 25th percentile of the current fee rates.
+    Economy,
+    /// Median of the current fee rates.
+    #[default]
+    Standard,
+    /// 90th percentile of the current fee rates.
+    Fast,
+}
+
+impl FeePriority {
+    fn percentile(self) -> usize {
+        match self {
+            Self::Economy => 25,
+            Self::Standard => 50,
+            Self::Fast => 90,
+        }
+    }
+
+    /// Fee rate of the priority among the fee rate `percentiles` sorted ascending, as returned
+    /// by the IC Bitcoin API. Returns `None` if there are no percentiles.
+    pub fn fee_rate(self, percentiles: &[u64]) -> Option<u64> {
+        let index =
+            (percentiles.len() * self.percentile() / 100).min(percentiles.len().checked_sub(1)?);
+        percentiles.get(index).copied()
+    }
+}
+
+impl Storable for FeePriority {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("serialization failed"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).expect("deserialization failed")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// Fee priorities selected by the users for their withdrawals.
+pub struct FeePriorities<M: Memory> {
+    priorities: StableBTreeMap<H160, FeePriority, M>,
+}
+
+impl<M: Memory> FeePriorities<M> {
+    pub fn new(memory: M) -> Self {
+        Self {
+            priorities: StableBTreeMap::new(memory),
+        }
+    }
+
+    /// Priority of the withdrawals of the `sender`, if they selected one.
+    pub fn get(&self, sender: &H160) -> Option<FeePriority> {
+        self.priorities.get(sender)
+    }
+
+    /// Sets the priority of the withdrawals of the `sender`. The default priority is not stored.
+    pub fn set(&mut self, sender: &H160, priority: FeePriority) {
+        if priority == FeePriority::default() {
+            self.priorities.remove(sender);
+        } else {
+            self.priorities.insert(sender.clone(), priority);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ic_stable_structures::VectorMemory;
+
+    use super::*;
+
+    #[test]
+    fn fee_rate_is_taken_from_priority_percentile() {
+        let percentiles: Vec<u64> = (0..101).map(|i| i * 1_000).collect();
+
+        assert_eq!(FeePriority::Economy.fee_rate(&percentiles), Some(25_000));
+        assert_eq!(
+            FeePriority::Standard.fee_rate(&percentiles),
+            Some(percentiles[percentiles.len() / 2])
+        );
+        assert_eq!(FeePriority::Fast.fee_rate(&percentiles), Some(90_000));
+
+        assert_eq!(FeePriority::Fast.fee_rate(&[5_000]), Some(5_000));
+        assert_eq!(FeePriority::Economy.fee_rate(&[]), None);
+    }
+
+    #[test]
+    fn sender_priorities_are_stored() {
+        let mut priorities = FeePriorities::new(VectorMemory::default());
+        let sender = H160::from_slice(&[1; 20]);
+        assert_eq!(priorities.get(&sender), None);
+
+        priorities.set(&sender, FeePriority::Fast);
+        assert_eq!(priorities.get(&sender), Some(FeePriority::Fast));
+
+        priorities.set(&sender, FeePriority::Standard);
+        assert_eq!(priorities.get(&sender), None);
+    }
+}
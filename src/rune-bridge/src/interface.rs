@@ -1,13 +1,70 @@
 use std::collections::HashMap;
 
 use candid::CandidType;
-use did::H256;
+use did::{H160, H256};
+use ic_exports::ic_cdk::api::management_canister::bitcoin::Outpoint;
+use minter_contract_utils::admin_approvals::ApprovalConfig;
+use minter_contract_utils::btc_address::BtcAddressError;
+use minter_contract_utils::circuit_breaker::CircuitBreakerConfig;
+use minter_contract_utils::confirmation_policy::ConfirmationTier;
+use minter_contract_utils::gas_limits::GasLimits;
+use minter_contract_utils::operation_archive::ArchiveConfig;
+use minter_contract_utils::operation_store::MinterOperationId;
+use minter_contract_utils::soft_caps::Lane;
+use minter_contract_utils::task_limits::TaskLimits;
 use minter_did::order::SignedMintOrder;
 use ordinals::{Pile, SpacedRune};
 use serde::Deserialize;
 
 use crate::core::deposit::RuneDepositPayload;
+use crate::fee_priority::FeePriority;
 use crate::rune_info::RuneName;
+use crate::scaling::ScalingError;
+use crate::state::{BftBridgeConfig, RuneBridgeConfig};
+
+/// Dangerous operation applied through an approved proposal when the approvals are enabled.
+#[derive(Debug, Clone, CandidType, Deserialize)]
+pub enum AdminOperation {
+    Reconfigure {
+        config: RuneBridgeConfig,
+        nonce: u64,
+    },
+    SetCircuitBreakerConfig(CircuitBreakerConfig),
+    SetApprovalConfig(Option<ApprovalConfig>),
+    ConfigureEcdsa,
+    ConfigureBftBridge(BftBridgeConfig),
+    DeployBftBridge {
+        fee_charge: H160,
+    },
+    SetTaskLimits(TaskLimits),
+    SetGasLimits(GasLimits),
+    SetScreeningOverride {
+        subject: String,
+        allowed: bool,
+    },
+    RemoveScreeningOverride(String),
+    SetOperationArchive(Option<ArchiveConfig>),
+    MintAndBridgeRunes {
+        eth_address: H160,
+        rune_id: RuneIdDid,
+        dst_chain_id: Option<u32>,
+    },
+    SetWithdrawalAllowlistMode(bool),
+    SetRuneSupplyCap {
+        rune_name: String,
+        supply_cap_bps: Option<u32>,
+    },
+    SetRuneMinAmounts {
+        rune_name: String,
+        min_deposit_amount: Option<u128>,
+        min_withdrawal_amount: Option<u128>,
+    },
+    SetSoftCap {
+        lane: Lane,
+        cap_per_hour: Option<u128>,
+    },
+    CancelDeposit(MinterOperationId),
+}
 
 #[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
 pub struct PendingUtxo {}
@@ -84,9 +141,53 @@ pub enum DepositError {
         min_confirmations: u32,
         current_confirmations: u32,
     },
+    /// The indexer is too far behind the bitcoin tip to report the runes of the deposit utxos.
+    IndexerBehind {
+        indexer_height: u32,
+        tip: u32,
+    },
     /// Error while signing the mint order.
     Sign(String),
     Evm(String),
+    /// Deposited amount cannot be converted into wrapped token units.
+    Scaling(ScalingError),
+    /// The given BTC address cannot be parsed or belongs to another network.
+    InvalidAddress(BtcAddressError),
+    /// The requested destination chain is not in the allowlist of the bridge.
+    ChainNotAllowed {
+        chain_id: u32,
+    },
+    /// The deposit would take the rune amount held by the bridge over its supply cap.
+    SupplyCapExceeded {
+        rune_name: RuneName,
+        cap: u128,
+        bridged: u128,
+        requested: u128,
+    },
+    /// The deposited amount of the rune is below the minimum deposit amount.
+    AmountBelowMinimum {
+        rune_name: RuneName,
+        min_amount: u128,
+        amount: u128,
+    },
+    /// The deposited amount of the rune has `remainder` units which cannot be represented in the
+    /// wrapped token, so the deposit is not minted until it is topped up to a whole token unit.
+    InexactAmount {
+        rune_name: RuneName,
+        amount: u128,
+        remainder: u128,
+    },
+}
+
+#[derive(Debug, Clone, Copy, CandidType, Deserialize, PartialEq, Eq)]
+pub enum CancelDepositError {
+    NotFound,
+    /// The cancellation is requested by neither the recipient of the deposit nor the admin.
+    NotAuthorized,
+    /// Mint orders of the deposit are signed and may be already sent to the BftBridge.
+    MintOrdersSigned,
+    /// The deposit is already completed or failed.
+    Finished,
 }
 
 #[derive(Debug, Clone, CandidType, Deserialize)]
@@ -98,9 +199,105 @@ pub enum WithdrawError {
     TransactionSending,
     FeeRateRequest,
     ChangeAddress,
+    /// Value of the destination output is below the dust limit for its script type.
+    AmountBelowDust {
+        value: u64,
+        dust: u64,
+    },
+    /// The utxos of the bridge hold fewer runes than the withdrawal requests.
+    InsufficientFunds {
+        available: u128,
+        requested: u128,
+    },
+    /// BTC of the transaction inputs is not enough to pay the postage and the fee.
+    NotEnoughBtc {
+        available: u64,
+        required: u64,
+    },
+    /// Destination address cannot be parsed or belongs to another network.
+    InvalidAddress(BtcAddressError),
+    /// The withdrawn amount of the rune is below the minimum withdrawal amount.
+    AmountBelowMinimum {
+        min_amount: u128,
+        amount: u128,
+    },
+    /// The withdrawal exceeds the hourly soft cap and is queued. It is sent when the queue is
+    /// drained up to it.
+    Queued {
+        position: u64,
+        eta: Option<u64>,
+    },
     InternalError(String),
 }
 
+#[derive(Debug, Clone, CandidType, Deserialize)]
+pub enum OpenMintError {
+    /// The caller may not spend the BTC of the deposit address.
+    NotAuthorized,
+    /// The rune has no open mint terms, or its mint cap or block range is exceeded.
+    NotMintable,
+    /// BTC at the deposit address is not enough to pay the postage and the fee of the mint
+    /// transaction.
+    NotEnoughBtc {
+        available: u64,
+        required: u64,
+    },
+    Deposit(DepositError),
+    Transaction(WithdrawError),
+}
+
+/// Withdrawal transaction input as it would be used by the canister.
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct PreviewInput {
+    pub outpoint: Outpoint,
+    pub value: u64,
+}
+
+/// Withdrawal transaction output. `address` is `None` for outputs without an address, e.g. the
+/// runestone output.
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct PreviewOutput {
+    pub address: Option<String>,
+    pub value: u64,
+}
+
+/// Simulated withdrawal transaction that is not signed nor sent to the BTC network.
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct WithdrawalPreview {
+    pub inputs: Vec<PreviewInput>,
+    pub outputs: Vec<PreviewOutput>,
+    /// Transaction fee in satoshi.
+    pub fee: u64,
+    /// Fee priority the transaction is built with.
+    pub fee_priority: FeePriority,
+    /// Fee rate of the priority in satoshi per virtual byte.
+    pub fee_rate_sat_per_vb: u64,
+    /// Runes of the selected inputs above the withdrawn amount, returned to the bridge.
+    pub rune_change: u128,
+    /// Estimated time until the transaction receives the number of confirmations required by the
+    /// canister.
+    pub estimated_confirmation_time_secs: u64,
+}
+
+/// Requirements of the bridge to the deposits, so the wallets can check a deposit before it is
+/// sent.
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct DepositRequirements {
+    /// Minimum value in satoshi of the BTC sent with the runes, which pays the deposit fee.
+    pub min_btc_amount: u64,
+    /// Confirmations required for deposits of any size.
+    pub min_confirmations: u32,
+    /// Additional confirmations required for larger deposits.
+    pub confirmation_tiers: Vec<ConfirmationTier>,
+    /// Estimated time after the deposit transaction is sent until the indexer reports it with the
+    /// required confirmations.
+    pub estimated_indexer_latency_secs: u64,
+    /// Smallest amount of the rune in rune units accepted for a deposit: it is not below the
+    /// minimum deposit amount of the rune and is minted as at least one unit of the wrapped token.
+    /// `None` if the rune is not known to the bridge yet.
+    pub min_rune_amount: Option<u128>,
+}
+
 #[derive(Debug, Copy, Clone, CandidType, Deserialize, Hash, PartialEq, Eq)]
 pub struct RuneIdDid {
     pub block_id: u64,
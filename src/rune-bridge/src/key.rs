@@ -1,3 +1,11 @@
+//! Keys of the canister.
+//!
+//! The master ECDSA public key is fetched from the management canister with
+//! `admin_configure_ecdsa`, and the keys of the deposit addresses are derived from it locally.
+//! Both are kept in the [`PublicKeyCache`] in stable memory, so the master key is fetched once per
+//! key id, also across the upgrades, and the key of a derivation path is derived once.
+
+use std::borrow::Cow;
 use std::cell::RefCell;
 
 use async_trait::async_trait;
@@ -5,16 +13,21 @@ use bitcoin::bip32::{ChildNumber, DerivationPath, Xpub};
 use bitcoin::secp256k1::ecdsa::Signature;
 use bitcoin::secp256k1::{Error, Message, Secp256k1};
 use bitcoin::{Address, Network, PublicKey};
+use candid::{CandidType, Decode, Deserialize, Encode};
 use did::H160;
-use ic_exports::ic_cdk::api::management_canister::ecdsa::{sign_with_ecdsa, SignWithEcdsaArgument};
+use ic_exports::ic_cdk::api::management_canister::ecdsa::{
+    sign_with_ecdsa, EcdsaKeyId, EcdsaPublicKeyResponse, SignWithEcdsaArgument,
+};
+use ic_stable_structures::stable_structures::Memory;
+use ic_stable_structures::{BTreeMapStructure, Bound, StableBTreeMap, Storable};
+use minter_contract_utils::derivation_path::DerivationPath as IcDerivationPath;
 use ord_rs::wallet::LocalSigner;
 use ord_rs::BtcTxSigner;
 
+use crate::canister::get_public_key_cache;
 use crate::interface::GetAddressError;
 use crate::state::{MasterKey, State};
 
-pub const DERIVATION_PATH_PREFIX: u8 = 7;
-
 pub struct IcBtcSigner {
     master_key: MasterKey,
     network: Network,
@@ -34,20 +47,9 @@ impl IcBtcSigner {
 #[async_trait]
 impl BtcTxSigner for IcBtcSigner {
     async fn ecdsa_public_key(&self, derivation_path: &DerivationPath) -> PublicKey {
-        let x_public_key = Xpub {
-            network: self.network,
-            depth: 0,
-            parent_fingerprint: Default::default(),
-            child_number: ChildNumber::from_normal_idx(0).expect("Failed to create child number"),
-            public_key: self.master_key.public_key.inner,
-            chain_code: self.master_key.chain_code,
-        };
-        let public_key = x_public_key
-            .derive_pub(&Secp256k1::new(), derivation_path)
+        get_public_key_cache()
+            .public_key(&self.master_key, self.network, derivation_path)
             .expect("Failed to derive public key")
-            .public_key;
-
-        PublicKey::from(public_key)
     }
 
     async fn sign_with_ecdsa(
@@ -130,58 +132,266 @@ pub fn get_transit_address(
     eth_address: &H160,
 ) -> Result<Address, GetAddressError> {
     let state = state.borrow();
-    let public_key = state.public_key();
-    let chain_code = state.chain_code();
-    let x_public_key = Xpub {
-        network: state.network(),
-        depth: 0,
-        parent_fingerprint: Default::default(),
-        child_number: ChildNumber::from_normal_idx(0).map_err(|_| GetAddressError::Derivation)?,
-        public_key: public_key.inner,
-        chain_code,
+    let master_key = MasterKey {
+        public_key: state.public_key(),
+        chain_code: state.chain_code(),
+        key_id: state.ecdsa_key_id(),
     };
     let derivation_path = get_derivation_path(eth_address);
-    let public_key = x_public_key
-        .derive_pub(&Secp256k1::new(), &derivation_path)
-        .map_err(|_| GetAddressError::Derivation)?
-        .public_key;
+    let public_key = get_public_key_cache()
+        .public_key(&master_key, state.network(), &derivation_path)
+        .map_err(|_| GetAddressError::Derivation)?;
 
-    Ok(Address::p2wpkh(&public_key.into(), state.network())
+    Ok(Address::p2wpkh(&public_key, state.network())
         .expect("used uncompressed public key to derive address"))
 }
 
-pub fn get_derivation_path_ic(eth_address: &H160) -> Vec<Vec<u8>> {
-    let mut bytes = vec![DERIVATION_PATH_PREFIX];
-    bytes.append(&mut eth_address.0 .0.to_vec());
+pub fn get_derivation_path_ic(eth_address: &H160) -> IcDerivationPath {
+    IcDerivationPath::from_eth_address(eth_address)
+}
+
+pub fn get_derivation_path(eth_address: &H160) -> DerivationPath {
+    DerivationPath::from(&get_derivation_path_ic(eth_address))
+}
+
+fn derivation_path_to_ic(derivation_path: DerivationPath) -> Vec<Vec<u8>> {
+    let vec: Vec<_> = derivation_path.into();
+    vec.into_iter()
+        .map(|child| u32::from(child).to_be_bytes().to_vec())
+        .collect()
+}
+
+/// Stable memory key of a derivation path: the child numbers in big endian.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct DerivationPathKey(Vec<u8>);
+
+impl DerivationPathKey {
+    /// Maximum depth of a cached derivation path.
+    const MAX_DEPTH: usize = 32;
+
+    /// Key of the master key.
+    const MASTER: Self = Self(Vec::new());
+
+    fn new(derivation_path: &DerivationPath) -> Option<Self> {
+        if derivation_path.len() > Self::MAX_DEPTH {
+            return None;
+        }
+
+        Some(Self(
+            derivation_path
+                .into_iter()
+                .flat_map(|child| u32::from(*child).to_be_bytes())
+                .collect(),
+        ))
+    }
+}
+
+impl Storable for DerivationPathKey {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Borrowed(&self.0)
+    }
 
-    let mut dp = vec![];
-    for slice in bytes.chunks_exact(3) {
-        let mut part = vec![0];
-        part.append(&mut slice.to_vec());
-        dp.push(part);
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Self(bytes.into_owned())
     }
 
-    dp
+    const BOUND: Bound = Bound::Bounded {
+        max_size: (Self::MAX_DEPTH * 4) as u32,
+        is_fixed_size: false,
+    };
 }
 
-pub fn get_derivation_path(eth_address: &H160) -> DerivationPath {
-    ic_dp_to_derivation_path(&get_derivation_path_ic(eth_address))
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+struct CachedPublicKey {
+    /// ECDSA key id the key is derived from.
+    key_id: String,
+    public_key: Vec<u8>,
+    chain_code: Vec<u8>,
 }
 
-pub fn ic_dp_to_derivation_path(ic_derivation_path: &[Vec<u8>]) -> DerivationPath {
-    let mut parts = vec![];
-    for part in ic_derivation_path.iter() {
-        let child_idx = u32::from_be_bytes(part[..].try_into().unwrap());
-        let child = ChildNumber::from_normal_idx(child_idx).unwrap();
-        parts.push(child);
+impl Storable for CachedPublicKey {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("serialization failed"))
     }
 
-    DerivationPath::from(parts)
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).expect("deserialization failed")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
 }
 
-fn derivation_path_to_ic(derivation_path: DerivationPath) -> Vec<Vec<u8>> {
-    let vec: Vec<_> = derivation_path.into();
-    vec.into_iter()
-        .map(|child| u32::from(child).to_be_bytes().to_vec())
-        .collect()
+/// Public keys of the canister keyed by their derivation path. The master key is stored under
+/// the empty path.
+///
+/// The keys are stored with the ECDSA key id they belong to and are not returned for another
+/// key id. Setting a different master key drops the keys derived from the previous one.
+pub struct PublicKeyCache<M: Memory> {
+    keys: StableBTreeMap<DerivationPathKey, CachedPublicKey, M>,
+}
+
+impl<M: Memory> PublicKeyCache<M> {
+    pub fn new(memory: M) -> Self {
+        Self {
+            keys: StableBTreeMap::new(memory),
+        }
+    }
+
+    /// Master key of the `key_id` fetched from the management canister before.
+    pub fn master_key(&self, key_id: &EcdsaKeyId) -> Option<EcdsaPublicKeyResponse> {
+        self.keys
+            .get(&DerivationPathKey::MASTER)
+            .filter(|key| key.key_id == key_id_label(key_id))
+            .map(|key| EcdsaPublicKeyResponse {
+                public_key: key.public_key,
+                chain_code: key.chain_code,
+            })
+    }
+
+    /// Stores the master key of the `key_id`. The cached keys are dropped if the master key or its
+    /// key id changes.
+    pub fn set_master_key(&mut self, key_id: &EcdsaKeyId, master_key: &EcdsaPublicKeyResponse) {
+        let entry = CachedPublicKey {
+            key_id: key_id_label(key_id),
+            public_key: master_key.public_key.clone(),
+            chain_code: master_key.chain_code.clone(),
+        };
+        if self.keys.get(&DerivationPathKey::MASTER).as_ref() == Some(&entry) {
+            return;
+        }
+
+        let keys: Vec<DerivationPathKey> = self.keys.iter().map(|(key, _)| key).collect();
+        for key in keys {
+            self.keys.remove(&key);
+        }
+        self.keys.insert(DerivationPathKey::MASTER, entry);
+    }
+
+    /// Public key of the `derivation_path` derived from the `master_key`. The key is derived on
+    /// the first request and cached.
+    pub fn public_key(
+        &mut self,
+        master_key: &MasterKey,
+        network: Network,
+        derivation_path: &DerivationPath,
+    ) -> Result<PublicKey, bitcoin::bip32::Error> {
+        let key_id = key_id_label(&master_key.key_id);
+        let path_key = DerivationPathKey::new(derivation_path);
+        if let Some(cached) = path_key
+            .as_ref()
+            .and_then(|path_key| self.keys.get(path_key))
+            .filter(|cached| cached.key_id == key_id)
+        {
+            if let Ok(public_key) = PublicKey::from_slice(&cached.public_key) {
+                return Ok(public_key);
+            }
+        }
+
+        let x_public_key = Xpub {
+            network,
+            depth: 0,
+            parent_fingerprint: Default::default(),
+            child_number: ChildNumber::from_normal_idx(0)?,
+            public_key: master_key.public_key.inner,
+            chain_code: master_key.chain_code,
+        };
+        let derived = x_public_key.derive_pub(&Secp256k1::new(), derivation_path)?;
+        let public_key = PublicKey::from(derived.public_key);
+
+        if let Some(path_key) = path_key.filter(|path_key| *path_key != DerivationPathKey::MASTER) {
+            self.keys.insert(
+                path_key,
+                CachedPublicKey {
+                    key_id,
+                    public_key: public_key.to_bytes(),
+                    chain_code: derived.chain_code.to_bytes().to_vec(),
+                },
+            );
+        }
+
+        Ok(public_key)
+    }
+
+    pub fn len(&self) -> u64 {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.len() == 0
+    }
+}
+
+fn key_id_label(key_id: &EcdsaKeyId) -> String {
+    format!("{:?}:{}", key_id.curve, key_id.name)
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::bip32::ChainCode;
+    use ic_exports::ic_cdk::api::management_canister::ecdsa::EcdsaCurve;
+    use ic_stable_structures::VectorMemory;
+
+    use super::*;
+
+    fn key_id(name: &str) -> EcdsaKeyId {
+        EcdsaKeyId {
+            curve: EcdsaCurve::Secp256k1,
+            name: name.to_string(),
+        }
+    }
+
+    fn master_key_response(seed: u8) -> EcdsaPublicKeyResponse {
+        let secret_key = bitcoin::secp256k1::SecretKey::from_slice(&[seed; 32]).unwrap();
+        let public_key =
+            bitcoin::secp256k1::PublicKey::from_secret_key(&Secp256k1::new(), &secret_key);
+        EcdsaPublicKeyResponse {
+            public_key: public_key.serialize().to_vec(),
+            chain_code: vec![seed; 32],
+        }
+    }
+
+    fn master_key(key_id: &EcdsaKeyId, response: &EcdsaPublicKeyResponse) -> MasterKey {
+        MasterKey {
+            public_key: PublicKey::from_slice(&response.public_key).unwrap(),
+            chain_code: ChainCode::try_from(response.chain_code.as_slice()).unwrap(),
+            key_id: key_id.clone(),
+        }
+    }
+
+    #[test]
+    fn derived_keys_are_cached_per_master_key() {
+        let mut cache = PublicKeyCache::new(VectorMemory::default());
+        let key_id = key_id("key_1");
+        let response = master_key_response(1);
+        assert_eq!(cache.master_key(&key_id), None);
+
+        cache.set_master_key(&key_id, &response);
+        assert_eq!(cache.master_key(&key_id), Some(response.clone()));
+        assert_eq!(cache.master_key(&self::key_id("key_2")), None);
+
+        let master = master_key(&key_id, &response);
+        let path = get_derivation_path(&H160::from_slice(&[3; 20]));
+        let public_key = cache.public_key(&master, Network::Regtest, &path).unwrap();
+        assert_eq!(cache.len(), 2);
+        assert_eq!(
+            cache.public_key(&master, Network::Regtest, &path).unwrap(),
+            public_key
+        );
+        assert_eq!(cache.len(), 2);
+
+        // Setting the same master key again keeps the derived keys.
+        cache.set_master_key(&key_id, &response);
+        assert_eq!(cache.len(), 2);
+
+        let other_response = master_key_response(2);
+        cache.set_master_key(&key_id, &other_response);
+        assert_eq!(cache.len(), 1);
+        let other_master = master_key(&key_id, &other_response);
+        assert_ne!(
+            cache
+                .public_key(&other_master, Network::Regtest, &path)
+                .unwrap(),
+            public_key
+        );
+    }
 }
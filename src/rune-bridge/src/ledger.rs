@@ -7,15 +7,24 @@ use bitcoin::hashes::sha256d::Hash;
 use bitcoin::{Address, Amount, Network, OutPoint, TxOut, Txid};
 use candid::{CandidType, Decode, Encode};
 use ic_exports::ic_cdk::api::management_canister::bitcoin::{Outpoint, Utxo};
-use ic_exports::ic_kit::ic;
 use ic_stable_structures::stable_structures::DefaultMemoryImpl;
 use ic_stable_structures::{BTreeMapStructure, Bound, StableBTreeMap, Storable, VirtualMemory};
+use minter_contract_utils::certified_data::{self, RESERVES_LABEL};
+use minter_contract_utils::derivation_path::DerivationPath as IcDerivationPath;
 use ord_rs::wallet::TxInputInfo;
 use serde::Deserialize;
 
-use crate::key::{ic_dp_to_derivation_path, IcBtcSigner};
+use crate::key::IcBtcSigner;
 use crate::memory::{LEDGER_MEMORY_ID, MEMORY_MANAGER, USED_UTXOS_REGISTRY_MEMORY_ID};
 
+/// BTC held by the bridge in the utxos of the ledger, including the utxos used by the pending
+/// transactions.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, CandidType, Deserialize)]
+pub struct Reserves {
+    pub btc_balance: u64,
+    pub utxo_count: u64,
+}
+
 /// Data structure to keep track of utxos owned by the canister.
 pub struct UtxoLedger {
     utxo_storage: StableBTreeMap<UtxoKey, UtxoDetails, VirtualMemory<DefaultMemoryImpl>>,
@@ -142,7 +151,12 @@ impl Storable for UsedUtxoDetails {
 
 impl UtxoLedger {
     /// Adds the utxo to the store.
-    pub fn deposit(&mut self, utxos: &[Utxo], address: &Address, derivation_path: Vec<Vec<u8>>) {
+    pub fn deposit(
+        &mut self,
+        utxos: &[Utxo],
+        address: &Address,
+        derivation_path: IcDerivationPath,
+    ) {
         let script = address.script_pubkey();
         for utxo in utxos {
             self.utxo_storage.insert(
@@ -150,7 +164,7 @@ impl UtxoLedger {
                 UtxoDetails {
                     value: utxo.value,
                     script_buf: script.clone().into_bytes(),
-                    derivation_path: derivation_path.clone(),
+                    derivation_path: derivation_path.as_ic().to_vec(),
                 },
             );
 
@@ -161,6 +175,8 @@ impl UtxoLedger {
                 utxo.value
             );
         }
+
+        self.certify_reserves();
     }
 
     /// Lists all unspent utxos in the store.
@@ -168,8 +184,16 @@ impl UtxoLedger {
         self.utxo_storage
             .iter()
             .filter(|(key, _)| !self.used_utxos_registry.contains_key(key))
-            .map(|(key, details)| {
-                (
+            .filter_map(|(key, details)| {
+                let derivation_path = match IcDerivationPath::from_ic(details.derivation_path) {
+                    Ok(path) => path,
+                    Err(err) => {
+                        log::error!("Utxo {key} has invalid derivation path: {err}");
+                        return None;
+                    }
+                };
+
+                Some((
                     key,
                     TxInputInfo {
                         outpoint: OutPoint {
@@ -180,19 +204,19 @@ impl UtxoLedger {
                             value: Amount::from_sat(details.value),
                             script_pubkey: details.script_buf.into(),
                         },
-                        derivation_path: ic_dp_to_derivation_path(&details.derivation_path),
+                        derivation_path: (&derivation_path).into(),
                     },
-                )
+                ))
             })
             .unzip()
     }
 
-    /// Marks the utxo as used.
-    pub fn mark_as_used(&mut self, key: UtxoKey, address: Address) {
+    /// Marks the utxo as used at the time `now`.
+    pub fn mark_as_used(&mut self, key: UtxoKey, address: Address, now: u64) {
         self.used_utxos_registry.insert(
             key,
             UsedUtxoDetails {
-                used_at: ic::time(),
+                used_at: now,
                 owner_address: address.to_string(),
             },
         );
@@ -200,6 +224,16 @@ impl UtxoLedger {
         log::trace!("Utxo {key} is marked as used.");
     }
 
+    /// Checks whether the utxo is stored in the ledger or marked as used.
+    pub fn contains(&self, key: &UtxoKey) -> bool {
+        self.utxo_storage.contains_key(key) || self.used_utxos_registry.contains_key(key)
+    }
+
+    /// Checks whether the utxo is marked as used.
+    pub fn is_used(&self, key: &UtxoKey) -> bool {
+        self.used_utxos_registry.contains_key(key)
+    }
+
     /// Lists all used utxos in the store.
     pub fn load_used_utxos(&self) -> Vec<(UtxoKey, UsedUtxoDetails)> {
         self.used_utxos_registry.iter().collect()
@@ -211,6 +245,7 @@ impl UtxoLedger {
     pub fn remove_spent_utxo(&mut self, key: &UtxoKey) {
         self.utxo_storage.remove(key);
         self.used_utxos_registry.remove(key);
+        self.certify_reserves();
     }
 
     /// Removes the unspent utxo from the store.
@@ -218,6 +253,20 @@ impl UtxoLedger {
     pub fn remove_unspent_utxo(&mut self, key: &UtxoKey) {
         self.used_utxos_registry.remove(key);
     }
+
+    pub fn reserves(&self) -> Reserves {
+        self.utxo_storage
+            .iter()
+            .fold(Reserves::default(), |reserves, (_, details)| Reserves {
+                btc_balance: reserves.btc_balance.saturating_add(details.value),
+                utxo_count: reserves.utxo_count + 1,
+            })
+    }
+
+    /// Certifies the current reserves, see [`certified_data`].
+    pub fn certify_reserves(&self) {
+        certified_data::certify_value(RESERVES_LABEL, &self.reserves());
+    }
 }
 
 #[cfg(test)]
@@ -265,7 +314,7 @@ mod tests {
         let value = UtxoDetails {
             value: 100500,
             script_buf: address.script_pubkey().to_bytes(),
-            derivation_path,
+            derivation_path: derivation_path.into_ic(),
         };
 
         let serialized = value.to_bytes();
@@ -323,7 +372,7 @@ mod tests {
         state
             .borrow_mut()
             .ledger_mut()
-            .deposit(&[utxo], &address, vec![]);
+            .deposit(&[utxo], &address, Default::default());
 
         // list unspent
         let (keys, _) = state.borrow().ledger().load_unspent_utxos();
@@ -332,6 +381,51 @@ mod tests {
         assert_eq!(keys[0].vout, 1);
     }
 
+    #[test]
+    fn test_should_certify_reserves() {
+        MockContext::new().inject();
+        let address = Address::from_str("bc1quyjp8qxkdc22cej962xaydd5arm7trwtcnkzks")
+            .unwrap()
+            .assume_checked();
+
+        let utxo = |txid: u8, value: u64| Utxo {
+            outpoint: Outpoint {
+                txid: vec![txid; 32],
+                vout: 0,
+            },
+            value,
+            height: 0,
+        };
+
+        let state = get_state();
+        state.borrow_mut().ledger_mut().deposit(
+            &[utxo(0xaa, 1000), utxo(0xbb, 500)],
+            &address,
+            Default::default(),
+        );
+
+        let reserves = state.borrow().ledger().reserves();
+        assert_eq!(
+            reserves,
+            Reserves {
+                btc_balance: 1500,
+                utxo_count: 2,
+            }
+        );
+        assert!(certified_data::leaves().contains(&(
+            RESERVES_LABEL.to_string(),
+            certified_data::value_hash(&reserves)
+        )));
+
+        let (keys, _) = state.borrow().ledger().load_unspent_utxos();
+        state.borrow_mut().ledger_mut().remove_spent_utxo(&keys[0]);
+        assert_eq!(state.borrow().ledger().reserves().utxo_count, 1);
+        assert!(certified_data::leaves().contains(&(
+            RESERVES_LABEL.to_string(),
+            certified_data::value_hash(&state.borrow().ledger().reserves())
+        )));
+    }
+
     #[test]
     fn test_should_mark_used_utxo() {
         MockContext::new().inject();
@@ -352,14 +446,14 @@ mod tests {
         state
             .borrow_mut()
             .ledger_mut()
-            .deposit(&[utxo], &address, vec![]);
+            .deposit(&[utxo], &address, Default::default());
 
         let (keys, _) = state.borrow().ledger().load_unspent_utxos();
 
         state
             .borrow_mut()
             .ledger_mut()
-            .mark_as_used(keys[0], address.clone());
+            .mark_as_used(keys[0], address.clone(), 0);
 
         let used_utxos = state.borrow().ledger().load_used_utxos();
         assert_eq!(used_utxos.len(), 1);
@@ -397,13 +491,14 @@ mod tests {
         state
             .borrow_mut()
             .ledger_mut()
-            .deposit(&utxos, &address, vec![]);
+            .deposit(&utxos, &address, Default::default());
 
         // mark first as spent
-        state
-            .borrow_mut()
-            .ledger_mut()
-            .mark_as_used(UtxoKey::from(&utxos[0].outpoint), address.clone());
+        state.borrow_mut().ledger_mut().mark_as_used(
+            UtxoKey::from(&utxos[0].outpoint),
+            address.clone(),
+            0,
+        );
 
         // load unspent
         let (keys, _) = state.borrow().ledger().load_unspent_utxos();
@@ -416,6 +511,14 @@ mod tests {
         assert_eq!(used_utxos.len(), 1);
         assert_eq!(used_utxos[0].0.tx_id.to_vec(), utxos[0].outpoint.txid);
         assert_eq!(used_utxos[0].0.vout, utxos[0].outpoint.vout);
+        assert!(state
+            .borrow()
+            .ledger()
+            .is_used(&UtxoKey::from(&utxos[0].outpoint)));
+        assert!(!state
+            .borrow()
+            .ledger()
+            .is_used(&UtxoKey::from(&utxos[1].outpoint)));
     }
 
     #[test]
@@ -448,13 +551,14 @@ mod tests {
         state
             .borrow_mut()
             .ledger_mut()
-            .deposit(&utxos, &address, vec![]);
+            .deposit(&utxos, &address, Default::default());
 
         // mark first as spent
-        state
-            .borrow_mut()
-            .ledger_mut()
-            .mark_as_used(UtxoKey::from(&utxos[0].outpoint), address.clone());
+        state.borrow_mut().ledger_mut().mark_as_used(
+            UtxoKey::from(&utxos[0].outpoint),
+            address.clone(),
+            0,
+        );
 
         // remove spent
         state
@@ -502,13 +606,14 @@ mod tests {
         state
             .borrow_mut()
             .ledger_mut()
-            .deposit(&utxos, &address, vec![]);
+            .deposit(&utxos, &address, Default::default());
 
         // mark first as spent
-        state
-            .borrow_mut()
-            .ledger_mut()
-            .mark_as_used(UtxoKey::from(&utxos[0].outpoint), address.clone());
+        state.borrow_mut().ledger_mut().mark_as_used(
+            UtxoKey::from(&utxos[0].outpoint),
+            address.clone(),
+            0,
+        );
 
         // remove spent
         state
@@ -1,14 +1,21 @@
+pub mod address_registry;
+pub mod balances;
 pub mod canister;
 pub mod core;
+pub mod fee_priority;
 pub mod interface;
 pub mod key;
 pub mod ledger;
 pub mod memory;
 pub mod operation;
 pub mod rune_info;
+pub mod rune_limits;
+pub mod scaling;
 pub mod scheduler;
 pub mod state;
 pub mod task;
+pub mod tx_journal;
+pub mod withdrawal_credits;
 
 use ic_metrics::Metrics;
 
@@ -18,10 +25,6 @@ const EVM_INFO_INITIALIZATION_RETRIES: u32 = 5;
 const EVM_INFO_INITIALIZATION_RETRY_DELAY_SEC: u32 = 2;
 const EVM_INFO_INITIALIZATION_RETRY_MULTIPLIER: u32 = 2;
 
-const MAINNET_CHAIN_ID: u32 = 0;
-const TESTNET_CHAIN_ID: u32 = 1;
-const REGTEST_CHAIN_ID: u32 = 2;
-
 pub fn idl() -> String {
     let btc_bridge_idl = RuneBridge::idl();
     let mut metrics_idl = <RuneBridge as Metrics>::get_idl();
@@ -11,6 +11,65 @@ pub const USED_UTXOS_REGISTRY_MEMORY_ID: MemoryId = MemoryId::new(6);
 pub const OPERATIONS_MEMORY_ID: MemoryId = MemoryId::new(7);
 pub const OPERATIONS_LOG_MEMORY_ID: MemoryId = MemoryId::new(8);
 pub const OPERATIONS_MAP_MEMORY_ID: MemoryId = MemoryId::new(9);
+pub const SCREENING_OVERRIDES_MEMORY_ID: MemoryId = MemoryId::new(10);
+pub const BRIDGE_TX_LOG_MEMORY_ID: MemoryId = MemoryId::new(11);
+pub const BRIDGED_BALANCES_MEMORY_ID: MemoryId = MemoryId::new(12);
+pub const TX_JOURNAL_MEMORY_ID: MemoryId = MemoryId::new(13);
+pub const ADDRESS_INDICES_MEMORY_ID: MemoryId = MemoryId::new(14);
+pub const ADDRESS_INDICES_LOOKUP_MEMORY_ID: MemoryId = MemoryId::new(15);
+pub const FEE_PRIORITIES_MEMORY_ID: MemoryId = MemoryId::new(16);
+pub const CONFIRMATION_WATCHER_MEMORY_ID: MemoryId = MemoryId::new(17);
+pub const RUNE_LIMITS_MEMORY_ID: MemoryId = MemoryId::new(18);
+pub const NOTIFIED_EVENTS_MEMORY_ID: MemoryId = MemoryId::new(19);
+pub const PUBLIC_KEY_CACHE_MEMORY_ID: MemoryId = MemoryId::new(20);
+pub const WITHDRAWAL_ALLOWLIST_MEMORY_ID: MemoryId = MemoryId::new(21);
+pub const WITHDRAWAL_ALLOWLIST_MODE_MEMORY_ID: MemoryId = MemoryId::new(22);
+pub const OPERATION_ARCHIVE_MEMORY_ID: MemoryId = MemoryId::new(23);
+pub const SOFT_CAP_LANES_MEMORY_ID: MemoryId = MemoryId::new(24);
+pub const SOFT_CAP_QUEUE_MEMORY_ID: MemoryId = MemoryId::new(25);
+pub const CONFIG_REVISION_MEMORY_ID: MemoryId = MemoryId::new(26);
+pub const APPROVAL_SETTINGS_MEMORY_ID: MemoryId = MemoryId::new(27);
+pub const APPROVAL_PROPOSALS_MEMORY_ID: MemoryId = MemoryId::new(28);
+pub const TASK_LIMITS_MEMORY_ID: MemoryId = MemoryId::new(29);
+pub const WITHDRAWAL_CREDITS_MEMORY_ID: MemoryId = MemoryId::new(30);
+
+/// Stable structures reported by the `canister_status_info` query.
+pub const STABLE_STRUCTURES: &[(&str, MemoryId)] = &[
+    ("config", CONFIG_MEMORY_ID),
+    ("pending_tasks", PENDING_TASKS_MEMORY_ID),
+    ("signer", SIGNER_MEMORY_ID),
+    ("logger_settings", LOGGER_SETTINGS_MEMORY_ID),
+    ("burn_request", BURN_REQUEST_MEMORY_ID),
+    ("ledger", LEDGER_MEMORY_ID),
+    ("used_utxos_registry", USED_UTXOS_REGISTRY_MEMORY_ID),
+    ("operations", OPERATIONS_MEMORY_ID),
+    ("operations_log", OPERATIONS_LOG_MEMORY_ID),
+    ("operations_map", OPERATIONS_MAP_MEMORY_ID),
+    ("screening_overrides", SCREENING_OVERRIDES_MEMORY_ID),
+    ("bridge_tx_log", BRIDGE_TX_LOG_MEMORY_ID),
+    ("bridged_balances", BRIDGED_BALANCES_MEMORY_ID),
+    ("tx_journal", TX_JOURNAL_MEMORY_ID),
+    ("address_indices", ADDRESS_INDICES_MEMORY_ID),
+    ("address_indices_lookup", ADDRESS_INDICES_LOOKUP_MEMORY_ID),
+    ("fee_priorities", FEE_PRIORITIES_MEMORY_ID),
+    ("confirmation_watcher", CONFIRMATION_WATCHER_MEMORY_ID),
+    ("rune_limits", RUNE_LIMITS_MEMORY_ID),
+    ("notified_events", NOTIFIED_EVENTS_MEMORY_ID),
+    ("public_key_cache", PUBLIC_KEY_CACHE_MEMORY_ID),
+    ("withdrawal_allowlist", WITHDRAWAL_ALLOWLIST_MEMORY_ID),
+    (
+        "withdrawal_allowlist_mode",
+        WITHDRAWAL_ALLOWLIST_MODE_MEMORY_ID,
+    ),
+    ("operation_archive", OPERATION_ARCHIVE_MEMORY_ID),
+    ("soft_cap_lanes", SOFT_CAP_LANES_MEMORY_ID),
+    ("soft_cap_queue", SOFT_CAP_QUEUE_MEMORY_ID),
+    ("config_revision", CONFIG_REVISION_MEMORY_ID),
+    ("approval_settings", APPROVAL_SETTINGS_MEMORY_ID),
+    ("approval_proposals", APPROVAL_PROPOSALS_MEMORY_ID),
+    ("task_limits", TASK_LIMITS_MEMORY_ID),
+    ("withdrawal_credits", WITHDRAWAL_CREDITS_MEMORY_ID),
+];
 
 thread_local! {
     pub static MEMORY_MANAGER: IcMemoryManager<DefaultMemoryImpl> = IcMemoryManager::init(DefaultMemoryImpl::default());
@@ -0,0 +1,340 @@
+//! Limits of the rune amounts bridged: caps of the amounts held by the bridge, as fractions of
+//! the circulating supply, and minimum amounts of the deposits and withdrawals.
+//!
+//! The supply of a rune is not fixed by its etching: a premine is created at once, the open mint
+//! terms add to it, and the runes sent to `OP_RETURN` outputs or left unallocated are burned. The
+//! circulating supply is read from the indexer when a deposit of a capped rune is signed, and
+//! the mint orders are not created if the bridge would hold more than the configured fraction of
+//! it.
+//!
+//! The bridged amounts are updated when the deposits are completed and when the withdrawals are
+//! sent, like the [`crate::balances`]. The deposits completed before the caps were introduced
+//! are not counted.
+//!
+//! Tiny amounts leave dust wrapped balances on the EVM and dust rune utxos on withdrawals, so the
+//! admin can set the minimum amounts per rune. A deposit below the minimum waits for more runes
+//! at the deposit address, and a burn below the minimum is not withdrawn.
+
+use std::borrow::Cow;
+
+use candid::{CandidType, Decode, Deserialize, Encode};
+use ic_stable_structures::stable_structures::Memory;
+use ic_stable_structures::{BTreeMapStructure, Bound, StableBTreeMap, Storable};
+
+use crate::rune_info::RuneName;
+
+/// Supply caps are given in basis points of the circulating supply.
+pub const MAX_SUPPLY_CAP_BPS: u32 = 10_000;
+
+/// Supply of a rune as reported by the indexer, in rune units.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, CandidType, Deserialize)]
+pub struct RuneSupply {
+    pub premine: u128,
+    /// Amount minted with the open mint terms.
+    pub minted: u128,
+    pub burned: u128,
+}
+
+impl RuneSupply {
+    pub fn circulating(&self) -> u128 {
+        self.premine
+            .saturating_add(self.minted)
+            .saturating_sub(self.burned)
+    }
+
+    /// Fraction of the circulating supply given in basis points.
+    pub fn fraction(&self, bps: u32) -> u128 {
+        let circulating = self.circulating();
+        let bps = u128::from(bps.min(MAX_SUPPLY_CAP_BPS));
+        let max_bps = u128::from(MAX_SUPPLY_CAP_BPS);
+        circulating / max_bps * bps + circulating % max_bps * bps / max_bps
+    }
+}
+
+/// Limits of a rune returned by the `get_rune_limits` query.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct RuneLimits {
+    pub rune_name: RuneName,
+    /// Maximum share of the circulating supply held by the bridge, in basis points. `None` if the
+    /// rune is not capped.
+    pub supply_cap_bps: Option<u32>,
+    /// Rune units deposited to the bridge and not withdrawn yet.
+    pub bridged_amount: u128,
+    /// Minimum amount of a deposit in rune units.
+    pub min_deposit_amount: Option<u128>,
+    /// Minimum amount of a withdrawal in rune units.
+    pub min_withdrawal_amount: Option<u128>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SupplyCapExceeded {
+    pub cap: u128,
+    pub bridged: u128,
+    pub requested: u128,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BelowMinAmount {
+    pub min_amount: u128,
+    pub amount: u128,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, CandidType, Deserialize)]
+struct RuneLimitsEntry {
+    supply_cap_bps: Option<u32>,
+    bridged_amount: u128,
+    min_deposit_amount: Option<u128>,
+    min_withdrawal_amount: Option<u128>,
+}
+
+impl RuneLimitsEntry {
+    fn into_limits(self, rune_name: RuneName) -> RuneLimits {
+        RuneLimits {
+            rune_name,
+            supply_cap_bps: self.supply_cap_bps,
+            bridged_amount: self.bridged_amount,
+            min_deposit_amount: self.min_deposit_amount,
+            min_withdrawal_amount: self.min_withdrawal_amount,
+        }
+    }
+}
+
+impl Storable for RuneLimitsEntry {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("serialization failed"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).expect("deserialization failed")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// Supply caps and bridged amounts of the runes, keyed by the rune value.
+pub struct RuneLimitsStore<M: Memory> {
+    entries: StableBTreeMap<u128, RuneLimitsEntry, M>,
+}
+
+impl<M: Memory> RuneLimitsStore<M> {
+    pub fn new(memory: M) -> Self {
+        Self {
+            entries: StableBTreeMap::new(memory),
+        }
+    }
+
+    pub fn get(&self, rune_name: RuneName) -> RuneLimits {
+        self.entries
+            .get(&rune_name.inner().0)
+            .unwrap_or_default()
+            .into_limits(rune_name)
+    }
+
+    /// Limits of all the runes which are limited or bridged.
+    pub fn list(&self) -> Vec<RuneLimits> {
+        self.entries
+            .iter()
+            .map(|(rune, entry)| entry.into_limits(ordinals::Rune(rune).into()))
+            .collect()
+    }
+
+    /// Sets the supply cap of the rune, or removes it if `supply_cap_bps` is `None`.
+    pub fn set_supply_cap(
+        &mut self,
+        rune_name: RuneName,
+        supply_cap_bps: Option<u32>,
+    ) -> Result<(), String> {
+        if let Some(bps) = supply_cap_bps {
+            if bps == 0 || bps > MAX_SUPPLY_CAP_BPS {
+                return Err(format!(
+                    "supply cap must be between 1 and {MAX_SUPPLY_CAP_BPS} basis points, got {bps}"
+                ));
+            }
+        }
+
+        self.update(rune_name, |entry| entry.supply_cap_bps = supply_cap_bps);
+        Ok(())
+    }
+
+    /// Sets the minimum amounts of the deposits and withdrawals of the rune. `None` removes the
+    /// minimum.
+    pub fn set_min_amounts(
+        &mut self,
+        rune_name: RuneName,
+        min_deposit_amount: Option<u128>,
+        min_withdrawal_amount: Option<u128>,
+    ) {
+        self.update(rune_name, |entry| {
+            entry.min_deposit_amount = min_deposit_amount;
+            entry.min_withdrawal_amount = min_withdrawal_amount;
+        });
+    }
+
+    pub fn check_min_deposit(
+        &self,
+        rune_name: RuneName,
+        amount: u128,
+    ) -> Result<(), BelowMinAmount> {
+        check_min_amount(self.get(rune_name).min_deposit_amount, amount)
+    }
+
+    pub fn check_min_withdrawal(
+        &self,
+        rune_name: RuneName,
+        amount: u128,
+    ) -> Result<(), BelowMinAmount> {
+        check_min_amount(self.get(rune_name).min_withdrawal_amount, amount)
+    }
+
+    pub fn add_bridged(&mut self, rune_name: RuneName, amount: u128) {
+        self.update(rune_name, |entry| {
+            entry.bridged_amount = entry.bridged_amount.saturating_add(amount)
+        });
+    }
+
+    /// Subtracts the withdrawn amount. The amounts deposited before the caps were introduced are
+    /// not counted, so the bridged amount is never taken below zero.
+    pub fn sub_bridged(&mut self, rune_name: RuneName, amount: u128) {
+        self.update(rune_name, |entry| {
+            entry.bridged_amount = entry.bridged_amount.saturating_sub(amount)
+        });
+    }
+
+    /// Checks that the bridge can take the `requested` amount of the rune with the `supply`.
+    pub fn check(
+        &self,
+        rune_name: RuneName,
+        requested: u128,
+        supply: &RuneSupply,
+    ) -> Result<(), SupplyCapExceeded> {
+        let limits = self.get(rune_name);
+        let Some(bps) = limits.supply_cap_bps else {
+            return Ok(());
+        };
+
+        let cap = supply.fraction(bps);
+        if limits.bridged_amount.saturating_add(requested) > cap {
+            return Err(SupplyCapExceeded {
+                cap,
+                bridged: limits.bridged_amount,
+                requested,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn update(&mut self, rune_name: RuneName, f: impl FnOnce(&mut RuneLimitsEntry)) {
+        let key = rune_name.inner().0;
+        let mut entry = self.entries.get(&key).unwrap_or_default();
+        f(&mut entry);
+        if entry == RuneLimitsEntry::default() {
+            self.entries.remove(&key);
+        } else {
+            self.entries.insert(key, entry);
+        }
+    }
+}
+
+fn check_min_amount(min_amount: Option<u128>, amount: u128) -> Result<(), BelowMinAmount> {
+    match min_amount {
+        Some(min_amount) if amount < min_amount => Err(BelowMinAmount { min_amount, amount }),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use ic_stable_structures::VectorMemory;
+
+    use super::*;
+
+    fn rune_name() -> RuneName {
+        RuneName::from_str("TESTRUNE").unwrap()
+    }
+
+    #[test]
+    fn circulating_supply_accounts_for_premine_and_burns() {
+        let supply = RuneSupply {
+            premine: 1_000,
+            minted: 9_000,
+            burned: 2_000,
+        };
+        assert_eq!(supply.circulating(), 8_000);
+        assert_eq!(supply.fraction(2_500), 2_000);
+        assert_eq!(supply.fraction(MAX_SUPPLY_CAP_BPS), 8_000);
+
+        let max = RuneSupply {
+            premine: u128::MAX,
+            ..Default::default()
+        };
+        assert_eq!(max.fraction(MAX_SUPPLY_CAP_BPS), u128::MAX);
+    }
+
+    #[test]
+    fn deposits_are_checked_against_cap() {
+        let mut store = RuneLimitsStore::new(VectorMemory::default());
+        let supply = RuneSupply {
+            premine: 10_000,
+            ..Default::default()
+        };
+        assert!(store.check(rune_name(), u128::MAX, &supply).is_ok());
+
+        store.set_supply_cap(rune_name(), Some(1_000)).unwrap();
+        store.add_bridged(rune_name(), 600);
+        assert!(store.check(rune_name(), 400, &supply).is_ok());
+        assert_eq!(
+            store.check(rune_name(), 401, &supply),
+            Err(SupplyCapExceeded {
+                cap: 1_000,
+                bridged: 600,
+                requested: 401,
+            })
+        );
+
+        store.sub_bridged(rune_name(), 1_000);
+        assert_eq!(
+            store.list(),
+            vec![RuneLimits {
+                rune_name: rune_name(),
+                supply_cap_bps: Some(1_000),
+                bridged_amount: 0,
+                min_deposit_amount: None,
+                min_withdrawal_amount: None,
+            }]
+        );
+
+        assert!(store.set_supply_cap(rune_name(), Some(0)).is_err());
+        assert!(store
+            .set_supply_cap(rune_name(), Some(MAX_SUPPLY_CAP_BPS + 1))
+            .is_err());
+        store.set_supply_cap(rune_name(), None).unwrap();
+        assert!(store.list().is_empty());
+    }
+
+    #[test]
+    fn amounts_below_minimum_are_rejected() {
+        let mut store = RuneLimitsStore::new(VectorMemory::default());
+        assert!(store.check_min_deposit(rune_name(), 1).is_ok());
+        assert!(store.check_min_withdrawal(rune_name(), 1).is_ok());
+
+        store.set_min_amounts(rune_name(), Some(100), Some(50));
+        assert!(store.check_min_deposit(rune_name(), 100).is_ok());
+        assert_eq!(
+            store.check_min_deposit(rune_name(), 99),
+            Err(BelowMinAmount {
+                min_amount: 100,
+                amount: 99,
+            })
+        );
+        assert!(store.check_min_withdrawal(rune_name(), 50).is_ok());
+        assert!(store.check_min_withdrawal(rune_name(), 49).is_err());
+        assert_eq!(store.list().len(), 1);
+
+        store.set_min_amounts(rune_name(), None, None);
+        assert!(store.check_min_deposit(rune_name(), 1).is_ok());
+        assert!(store.list().is_empty());
+    }
+}
@@ -0,0 +1,203 @@
+//! Conversion of amounts between rune units and wrapped token units.
+//!
+//! A rune with divisibility `d` has `10^d` units in one rune, and the wrapped ERC20 token with
+//! `decimals` has `10^decimals` units in one token. If the numbers differ, amounts must be
+//! multiplied or divided by the power of ten when crossing the bridge. On division the units which
+//! cannot be represented in the target token are returned as the remainder.
+
+use candid::CandidType;
+use serde::Deserialize;
+
+/// Amount converted to the target units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScaledAmount {
+    /// Amount in the target units.
+    pub amount: u128,
+    /// Part of the source amount which cannot be represented in the target units, in the source
+    /// units.
+    pub remainder: u128,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub enum ScalingError {
+    /// The converted amount doesn't fit into `u128`.
+    Overflow,
+    /// Difference between the decimals is too large to be represented as `u128` factor.
+    UnsupportedDecimals {
+        rune_decimals: u8,
+        token_decimals: u8,
+    },
+}
+
+/// Scaling between rune divisibility and wrapped token decimals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecimalScaling {
+    rune_decimals: u8,
+    token_decimals: u8,
+}
+
+impl DecimalScaling {
+    pub fn new(rune_decimals: u8, token_decimals: u8) -> Self {
+        Self {
+            rune_decimals,
+            token_decimals,
+        }
+    }
+
+    pub fn token_decimals(&self) -> u8 {
+        self.token_decimals
+    }
+
+    /// Converts rune units into wrapped token units.
+    pub fn rune_to_token(&self, amount: u128) -> Result<ScaledAmount, ScalingError> {
+        scale(amount, self.rune_decimals, self.token_decimals).ok_or_else(|| self.error())
+    }
+
+    /// Converts wrapped token units into rune units.
+    pub fn token_to_rune(&self, amount: u128) -> Result<ScaledAmount, ScalingError> {
+        scale(amount, self.token_decimals, self.rune_decimals).ok_or_else(|| self.error())
+    }
+
+    /// Smallest amount in rune units which is converted to at least one unit of the wrapped
+    /// token, or `None` if the decimals difference is not supported.
+    pub fn min_rune_amount(&self) -> Option<u128> {
+        if self.token_decimals >= self.rune_decimals {
+            Some(1)
+        } else {
+            self.factor()
+        }
+    }
+
+    fn error(&self) -> ScalingError {
+        if self.factor().is_none() {
+            ScalingError::UnsupportedDecimals {
+                rune_decimals: self.rune_decimals,
+                token_decimals: self.token_decimals,
+            }
+        } else {
+            ScalingError::Overflow
+        }
+    }
+
+    fn factor(&self) -> Option<u128> {
+        10u128.checked_pow(self.rune_decimals.abs_diff(self.token_decimals) as u32)
+    }
+}
+
+fn scale(amount: u128, from_decimals: u8, to_decimals: u8) -> Option<ScaledAmount> {
+    let factor = 10u128.checked_pow(from_decimals.abs_diff(to_decimals) as u32)?;
+    if to_decimals >= from_decimals {
+        Some(ScaledAmount {
+            amount: amount.checked_mul(factor)?,
+            remainder: 0,
+        })
+    } else {
+        Some(ScaledAmount {
+            amount: amount / factor,
+            remainder: amount % factor,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equal_decimals_keep_amount() {
+        for decimals in [0, 8, 18, 38, u8::MAX] {
+            let scaling = DecimalScaling::new(decimals, decimals);
+            let expected = ScaledAmount {
+                amount: u128::MAX,
+                remainder: 0,
+            };
+            assert_eq!(scaling.rune_to_token(u128::MAX), Ok(expected));
+            assert_eq!(scaling.token_to_rune(u128::MAX), Ok(expected));
+        }
+    }
+
+    #[test]
+    fn scaling_up_and_down() {
+        let scaling = DecimalScaling::new(2, 18);
+
+        assert_eq!(
+            scaling.rune_to_token(123),
+            Ok(ScaledAmount {
+                amount: 123 * 10u128.pow(16),
+                remainder: 0
+            })
+        );
+        assert_eq!(
+            scaling.token_to_rune(123 * 10u128.pow(16) + 42),
+            Ok(ScaledAmount {
+                amount: 123,
+                remainder: 42
+            })
+        );
+    }
+
+    #[test]
+    fn zero_divisibility_rune() {
+        let scaling = DecimalScaling::new(0, 8);
+
+        assert_eq!(scaling.rune_to_token(1).unwrap().amount, 100_000_000);
+        assert_eq!(
+            scaling.token_to_rune(99_999_999),
+            Ok(ScaledAmount {
+                amount: 0,
+                remainder: 99_999_999
+            })
+        );
+    }
+
+    #[test]
+    fn token_with_fewer_decimals_than_rune() {
+        let scaling = DecimalScaling::new(38, 18);
+
+        assert_eq!(
+            scaling.rune_to_token(10u128.pow(20) + 1),
+            Ok(ScaledAmount {
+                amount: 1,
+                remainder: 1
+            })
+        );
+        assert_eq!(scaling.token_to_rune(1).unwrap().amount, 10u128.pow(20));
+    }
+
+    #[test]
+    fn min_rune_amount() {
+        assert_eq!(DecimalScaling::new(2, 18).min_rune_amount(), Some(1));
+        assert_eq!(DecimalScaling::new(8, 8).min_rune_amount(), Some(1));
+        assert_eq!(
+            DecimalScaling::new(38, 18).min_rune_amount(),
+            Some(10u128.pow(20))
+        );
+        assert_eq!(DecimalScaling::new(u8::MAX, 0).min_rune_amount(), None);
+    }
+
+    #[test]
+    fn overflow_is_reported() {
+        let scaling = DecimalScaling::new(0, 18);
+        assert_eq!(
+            scaling.rune_to_token(u128::MAX),
+            Err(ScalingError::Overflow)
+        );
+        assert_eq!(
+            scaling.token_to_rune(u128::MAX).unwrap().remainder,
+            u128::MAX % 10u128.pow(18)
+        );
+    }
+
+    #[test]
+    fn unsupported_decimals_difference() {
+        let scaling = DecimalScaling::new(0, 39);
+        assert_eq!(
+            scaling.rune_to_token(1),
+            Err(ScalingError::UnsupportedDecimals {
+                rune_decimals: 0,
+                token_decimals: 39
+            })
+        );
+        assert!(DecimalScaling::new(0, 38).rune_to_token(1).is_ok());
+    }
+}
@@ -14,16 +14,36 @@ use ic_task_scheduler::scheduler::{Scheduler, TaskScheduler};
 use ic_task_scheduler::task::{InnerScheduledTask, ScheduledTask, Task, TaskOptions};
 use ic_task_scheduler::SchedulerError;
 use minter_contract_utils::bft_bridge_api::{BridgeEvent, MintedEventData, NotifyMinterEventData};
+use minter_contract_utils::bridge_tx_log::BridgeTransaction;
+use minter_contract_utils::btc_confirmations::IcUtxoConfirmations;
 use minter_contract_utils::evm_bridge::EvmParams;
+use minter_contract_utils::gas_price;
+use minter_contract_utils::health::{self, EVM_RPC, SIGNER};
+use minter_contract_utils::mint_completion::MintTx;
+use minter_contract_utils::operation_archive;
 use minter_contract_utils::operation_store::MinterOperationId;
+use minter_contract_utils::operation_trace;
+use minter_contract_utils::soft_caps::Lane;
+use minter_contract_utils::task_dedup::TaskKey;
+use minter_contract_utils::task_limits::TaskPriority;
+use ordinals::RuneId;
 use serde::{Deserialize, Serialize};
 
-use crate::canister::{get_operations_store, get_state};
+use crate::canister::{
+    get_bridge_tx_log, get_fee_priorities, get_notified_events, get_operation_archive,
+    get_operations_store, get_soft_cap_store, get_state,
+};
 use crate::core::deposit::RuneDeposit;
+use crate::core::index_provider::OrdIndexProvider;
+use crate::core::open_mint::OpenMint;
+use crate::core::refund::BtcRefund;
+use crate::core::rescan::AddressRescan;
 use crate::core::withdrawal::Withdrawal;
+use crate::fee_priority::FeePriority;
+use crate::interface::{RuneIdDid, WithdrawError};
 use crate::operation::OperationState;
 use crate::rune_info::RuneName;
-use crate::state::State;
+use crate::state::{State, INDEXER};
 
 pub type TasksStorage =
     StableBTreeMap<u32, InnerScheduledTask<RuneBridgeTask>, VirtualMemory<DefaultMemoryImpl>>;
@@ -37,8 +57,37 @@ pub enum RuneBridgeTask {
     InitEvmState,
     CollectEvmEvents,
     Deposit(MinterOperationId),
+    /// Completes the mint order of a `Minted` event collected before the mint transactions were
+    /// recorded. The new events are processed by [`RuneBridgeTask::CompleteMintOrder`].
     RemoveMintOrder(MintedEventData),
     Withdraw(MinterOperationId),
+    CheckHealth,
+    /// Sends the BTC change of the minted deposit to the refund address of the request.
+    RefundChange(MinterOperationId),
+    RefreshGasPrice,
+    /// Marks the mint order of a `Minted` event as completed by the transaction of the event.
+    CompleteMintOrder(MintedEventData, Option<MintTx>),
+    /// Rescans the registered deposit addresses with the indices in `from_index..to_index`.
+    RescanAddresses {
+        from_index: u32,
+        to_index: u32,
+    },
+    /// Completes the sent withdrawals whose transactions are confirmed.
+    WatchConfirmations,
+    /// Pushes a batch of the old completed operations to the archive canister.
+    ArchiveOperations,
+    /// Resumes the queued deposits and withdrawals which fit into the soft caps.
+    DrainSoftCapQueue,
+    /// Abandons the deposits which were not signed before the deposit expiry.
+    AbandonExpiredDeposits,
+    /// Mints the rune with open mint terms for the sender of the `OPEN_MINT_TYPE` notification
+    /// and deposits the minted runes.
+    OpenMint {
+        eth_address: H160,
+        rune_block: u64,
+        rune_tx: u32,
+        dst_chain_id: Option<u32>,
+    },
 }
 
 impl RuneBridgeTask {
@@ -46,6 +95,90 @@ impl RuneBridgeTask {
         ScheduledTask::with_options(self, options)
     }
 
+    /// Name of the task type used to configure the task limits.
+    pub fn task_type(&self) -> &'static str {
+        match self {
+            RuneBridgeTask::InitEvmState => "InitEvmState",
+            RuneBridgeTask::CollectEvmEvents => "CollectEvmEvents",
+            RuneBridgeTask::Deposit(_) => "Deposit",
+            RuneBridgeTask::RemoveMintOrder(_) => "RemoveMintOrder",
+            RuneBridgeTask::Withdraw(_) => "Withdraw",
+            RuneBridgeTask::CheckHealth => "CheckHealth",
+            RuneBridgeTask::RefundChange(_) => "RefundChange",
+            RuneBridgeTask::RefreshGasPrice => "RefreshGasPrice",
+            RuneBridgeTask::CompleteMintOrder(..) => "CompleteMintOrder",
+            RuneBridgeTask::RescanAddresses { .. } => "RescanAddresses",
+            RuneBridgeTask::WatchConfirmations => "WatchConfirmations",
+            RuneBridgeTask::ArchiveOperations => "ArchiveOperations",
+            RuneBridgeTask::DrainSoftCapQueue => "DrainSoftCapQueue",
+            RuneBridgeTask::AbandonExpiredDeposits => "AbandonExpiredDeposits",
+            RuneBridgeTask::OpenMint { .. } => "OpenMint",
+        }
+    }
+
+    /// Operation processed by the task, if any. It is the correlation id of the operation trace.
+    pub fn operation_id(&self) -> Option<MinterOperationId> {
+        match self {
+            RuneBridgeTask::Deposit(operation_id)
+            | RuneBridgeTask::Withdraw(operation_id)
+            | RuneBridgeTask::RefundChange(operation_id) => Some(*operation_id),
+            _ => None,
+        }
+    }
+
+    fn key(&self) -> TaskKey {
+        TaskKey::new(self.task_type(), self)
+    }
+
+    /// Appends the task to the scheduler, unless the same task is pending already. Returns
+    /// `false` if the task is not appended.
+    pub fn append_unique(
+        self,
+        scheduler: &(impl TaskScheduler<Self> + ?Sized),
+        options: TaskOptions,
+    ) -> bool {
+        if !get_state()
+            .borrow_mut()
+            .pending_tasks_mut()
+            .insert(self.key())
+        {
+            log::trace!("Task {} is pending already", self.task_type());
+            return false;
+        }
+
+        scheduler.append_task(self.into_scheduled(options));
+        true
+    }
+
+    /// Allows the task to be appended with [`RuneBridgeTask::append_unique`] again once it is
+    /// completed.
+    pub fn release(&self) {
+        get_state()
+            .borrow_mut()
+            .pending_tasks_mut()
+            .remove(&self.key());
+    }
+
+    fn default_priority(&self) -> TaskPriority {
+        match self {
+            RuneBridgeTask::InitEvmState
+            | RuneBridgeTask::CollectEvmEvents
+            | RuneBridgeTask::CheckHealth
+            | RuneBridgeTask::RefreshGasPrice => TaskPriority::High,
+            RuneBridgeTask::RemoveMintOrder(_)
+            | RuneBridgeTask::CompleteMintOrder(..)
+            | RuneBridgeTask::Withdraw(_)
+            | RuneBridgeTask::RefundChange(_)
+            | RuneBridgeTask::WatchConfirmations
+            | RuneBridgeTask::DrainSoftCapQueue => TaskPriority::Normal,
+            RuneBridgeTask::Deposit(_)
+            | RuneBridgeTask::RescanAddresses { .. }
+            | RuneBridgeTask::ArchiveOperations
+            | RuneBridgeTask::AbandonExpiredDeposits
+            | RuneBridgeTask::OpenMint { .. } => TaskPriority::Low,
+        }
+    }
+
     pub async fn init_evm_state() -> Result<(), SchedulerError> {
         let state = get_state();
         let client = state.borrow().get_evm_info().link.get_json_rpc_client();
@@ -80,16 +213,24 @@ impl RuneBridgeTask {
         };
 
         let client = evm_info.link.get_json_rpc_client();
-        let last_block = client.get_block_number().await.into_scheduler_result()?;
-
-        let logs = BridgeEvent::collect_logs(
-            &client,
-            params.next_block,
-            last_block,
-            evm_info.bridge_contract.0,
-        )
-        .await
-        .into_scheduler_result()?;
+        let collected = async {
+            let last_block = client.get_block_number().await?;
+            let logs = BridgeEvent::collect_logs(
+                &client,
+                params.next_block,
+                last_block,
+                evm_info.bridge_contract.0,
+            )
+            .await?;
+            anyhow::Ok((last_block, logs))
+        }
+        .await;
+        let now = state.borrow().clock().now();
+        state
+            .borrow_mut()
+            .circuit_breaker_mut()
+            .record(EVM_RPC, collected.is_ok(), now);
+        let (last_block, logs) = collected.into_scheduler_result()?;
 
         log::debug!("got {} logs from evm", logs.len());
 
@@ -108,6 +249,9 @@ impl RuneBridgeTask {
             });
         }
 
+        // The events notified with `notify_burn` are scheduled already.
+        let logs = get_notified_events().filter_scanned(logs, last_block + 1);
+
         log::trace!("appending logs to tasks");
 
         scheduler.append_tasks(
@@ -126,7 +270,103 @@ impl RuneBridgeTask {
         Ok(())
     }
 
-    fn task_by_log(log: Log, state: &RefCell<State>) -> Option<ScheduledTask<RuneBridgeTask>> {
+    /// Checks the dependencies of the bridge and records the results in the health monitor.
+    async fn check_health() -> Result<(), SchedulerError> {
+        let state = get_state();
+        let (evm_link, indexer_url, signer) = {
+            let state = state.borrow();
+            (
+                state.get_evm_info().link,
+                state.indexer_url(),
+                state.signer().get().clone(),
+            )
+        };
+
+        let evm_result = health::check_evm_link(&evm_link).await;
+        let indexer_result = OrdIndexProvider::new(indexer_url)
+            .check_availability()
+            .await;
+        let signer_result = health::check_signer(&signer).await;
+
+        let now = state.borrow().clock().now();
+        let mut state = state.borrow_mut();
+        let circuit_breaker = state.circuit_breaker_mut();
+        circuit_breaker.record(EVM_RPC, evm_result.is_ok(), now);
+        circuit_breaker.record(INDEXER, indexer_result.is_ok(), now);
+
+        let health = state.health_mut();
+        health.record(EVM_RPC, evm_result, now);
+        health.record(INDEXER, indexer_result, now);
+        health.record(SIGNER, signer_result, now);
+
+        Ok(())
+    }
+
+    /// Samples the gas price of the EVM and updates its estimate.
+    async fn refresh_gas_price() -> Result<(), SchedulerError> {
+        let state = get_state();
+        let client = state.borrow().get_evm_info().link.get_json_rpc_client();
+        let sample = gas_price::sample_gas_price(&client)
+            .await
+            .into_scheduler_result()?;
+
+        log::trace!("Sampled gas price: {sample:?}");
+        let now = state.borrow().clock().now();
+        state.borrow_mut().gas_price_mut().record(sample, now);
+
+        Ok(())
+    }
+
+    async fn archive_operations() -> Result<(), SchedulerError> {
+        let Some(config) = get_operation_archive().config() else {
+            return Ok(());
+        };
+
+        let batch =
+            get_operations_store().archive_candidates(config.keep_latest, config.batch_size);
+        if batch.is_empty() {
+            return Ok(());
+        }
+
+        operation_archive::push_batch(config.canister, &batch)
+            .await
+            .map_err(SchedulerError::TaskExecutionFailed)?;
+
+        let ids: Vec<MinterOperationId> = batch.iter().map(|operation| operation.id).collect();
+        get_operations_store().remove_archived(&ids);
+        log::info!(
+            "{} operations are moved to the archive {}",
+            ids.len(),
+            config.canister
+        );
+
+        Ok(())
+    }
+
+    fn drain_soft_cap_queue(
+        task_scheduler: Box<dyn 'static + TaskScheduler<Self>>,
+    ) -> Result<(), SchedulerError> {
+        let now = get_state().borrow().clock().now();
+        for entry in get_soft_cap_store().drain(now) {
+            let Ok(operation_id) = entry.ticket.parse::<u64>() else {
+                log::error!("Invalid ticket of a queued operation: {}", entry.ticket);
+                continue;
+            };
+
+            let task = match entry.lane {
+                Lane::Deposit => RuneBridgeTask::Deposit(operation_id.into()),
+                Lane::Withdrawal => RuneBridgeTask::Withdraw(operation_id.into()),
+            };
+            task.append_unique(&*task_scheduler, TaskOptions::default());
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn task_by_log(
+        log: Log,
+        state: &RefCell<State>,
+    ) -> Option<ScheduledTask<RuneBridgeTask>> {
         log::trace!("creating task from the log: {log:?}");
 
         const TASK_RETRY_DELAY_SECS: u32 = 5;
@@ -137,31 +377,82 @@ impl RuneBridgeTask {
             })
             .with_max_retries_policy(u32::MAX);
 
+        let mint_tx = MintTx::from_log(&log);
+        let evm_tx_hash = log.transaction_hash.map(Into::into);
+        let now = state.borrow().clock().now();
         match BridgeEvent::from_log(log).into_scheduler_result() {
             Ok(BridgeEvent::Burnt(burnt)) => {
+                get_bridge_tx_log().append(BridgeTransaction::burn(&burnt, evm_tx_hash, now));
                 log::debug!("Adding PrepareMintOrder task");
                 let operation_id = get_operations_store().new_operation(
                     burnt.sender.clone(),
                     OperationState::new_withdrawal(burnt, &state.borrow()),
                 );
+                operation_trace::record(
+                    operation_id,
+                    log::Level::Info,
+                    "event=burnt task=Withdraw status=scheduled",
+                );
                 let mint_order_task = RuneBridgeTask::Withdraw(operation_id);
                 return Some(mint_order_task.into_scheduled(options));
             }
             Ok(BridgeEvent::Minted(minted)) => {
-                log::debug!("Adding RemoveMintOrder task");
-                let remove_mint_order_task = RuneBridgeTask::RemoveMintOrder(minted);
-                return Some(remove_mint_order_task.into_scheduled(options));
+                get_bridge_tx_log().append(BridgeTransaction::mint(&minted, evm_tx_hash, now));
+                log::debug!("Adding CompleteMintOrder task");
+                let complete_mint_order_task = RuneBridgeTask::CompleteMintOrder(minted, mint_tx);
+                return Some(complete_mint_order_task.into_scheduled(options));
             }
             Ok(BridgeEvent::Notify(event)) => {
                 if let Some(notification) = RuneMinterNotification::decode(event) {
                     return match notification {
                         RuneMinterNotification::Deposit(payload) => {
-                            let request_id = RuneDeposit::get()
-                                .create_deposit_request(payload.dst_address, payload.amounts);
+                            if let Err(err) =
+                                state.borrow().recipient_chain_id(payload.dst_chain_id)
+                            {
+                                log::warn!("Deposit request is rejected: {err:?}");
+                                return None;
+                            }
+
+                            let request_id = RuneDeposit::get().create_deposit_request(
+                                payload.dst_address,
+                                payload.amounts,
+                                payload.refund_address,
+                                payload.dst_chain_id,
+                                payload.funding_txids,
+                            );
 
                             let deposit_task = RuneBridgeTask::Deposit(request_id);
                             Some(deposit_task.into_scheduled(TaskOptions::new()))
                         }
+                        RuneMinterNotification::CancelDeposit { payload, sender } => {
+                            if let Err(err) = RuneDeposit::get()
+                                .cancel_deposit(payload.operation_id, Some(&sender))
+                            {
+                                log::warn!(
+                                    "Deposit {} is not cancelled: {err:?}",
+                                    payload.operation_id
+                                );
+                            }
+                            None
+                        }
+                        RuneMinterNotification::OpenMint { payload, sender } => {
+                            let open_mint_task = RuneBridgeTask::OpenMint {
+                                eth_address: sender,
+                                rune_block: payload.rune_id.block_id,
+                                rune_tx: payload.rune_id.txid,
+                                dst_chain_id: payload.dst_chain_id,
+                            };
+                            Some(open_mint_task.into_scheduled(TaskOptions::new()))
+                        }
+                        RuneMinterNotification::SetFeePriority { payload, sender } => {
+                            log::debug!(
+                                "Withdrawals of {} use {:?} fee priority",
+                                hex::encode(sender.0),
+                                payload.fee_priority
+                            );
+                            get_fee_priorities().set(&sender, payload.fee_priority);
+                            None
+                        }
                     };
                 }
             }
@@ -171,8 +462,15 @@ impl RuneBridgeTask {
         None
     }
 
-    fn remove_mint_order(minted_event: MintedEventData) -> Result<(), SchedulerError> {
-        RuneDeposit::get().complete_mint_request(minted_event.recipient, minted_event.nonce);
+    fn complete_mint_order(
+        minted_event: MintedEventData,
+        mint_tx: Option<MintTx>,
+    ) -> Result<(), SchedulerError> {
+        RuneDeposit::get().complete_mint_request(
+            minted_event.recipient,
+            minted_event.nonce,
+            mint_tx,
+        );
 
         Ok(())
     }
@@ -182,6 +480,38 @@ impl Task for RuneBridgeTask {
     fn execute(
         &self,
         task_scheduler: Box<dyn 'static + TaskScheduler<Self>>,
+    ) -> Pin<Box<dyn Future<Output = Result<(), SchedulerError>>>> {
+        let permit = get_state()
+            .borrow()
+            .task_limiter()
+            .try_acquire(self.task_type(), self.default_priority());
+        let permit = match permit {
+            Ok(permit) => permit,
+            Err(reason) => {
+                log::debug!("Task {} is deferred: {reason}", self.task_type());
+                return Box::pin(futures::future::err(SchedulerError::TaskExecutionFailed(
+                    reason.error_message(),
+                )));
+            }
+        };
+
+        let task = self.run(task_scheduler);
+        let this = self.clone();
+        Box::pin(async move {
+            let _permit = permit;
+            let result = task.await;
+            if result.is_ok() {
+                this.release();
+            }
+            result
+        })
+    }
+}
+
+impl RuneBridgeTask {
+    fn run(
+        &self,
+        task_scheduler: Box<dyn 'static + TaskScheduler<Self>>,
     ) -> Pin<Box<dyn Future<Output = Result<(), SchedulerError>>>> {
         match self {
             RuneBridgeTask::InitEvmState => Box::pin(Self::init_evm_state()),
@@ -189,7 +519,7 @@ impl Task for RuneBridgeTask {
             RuneBridgeTask::Deposit(request_id) => Box::pin(Self::deposit(*request_id)),
             RuneBridgeTask::RemoveMintOrder(data) => {
                 let data = data.clone();
-                Box::pin(async move { Self::remove_mint_order(data) })
+                Box::pin(async move { Self::complete_mint_order(data, None) })
             }
             RuneBridgeTask::Withdraw(operation_id) => {
                 log::info!("ERC20 burn event received");
@@ -197,12 +527,98 @@ impl Task for RuneBridgeTask {
                 let operation_id = *operation_id;
                 Box::pin(async move {
                     let mut withdrawal = Withdrawal::new(get_state());
-                    let tx_id = withdrawal
-                        .withdraw(operation_id)
+                    let tx_id = match withdrawal.withdraw(operation_id).await {
+                        Ok(tx_id) => tx_id,
+                        Err(WithdrawError::Queued { position, eta }) => {
+                            log::info!(
+                                "Withdrawal {operation_id} is queued at position {position}, eta {eta:?}"
+                            );
+                            return Ok(());
+                        }
+                        Err(err) => {
+                            return Err(SchedulerError::TaskExecutionFailed(format!("{err:?}")))
+                        }
+                    };
+
+                    log::info!("Created withdrawal transaction: {tx_id}",);
+
+                    Ok(())
+                })
+            }
+            RuneBridgeTask::CheckHealth => Box::pin(Self::check_health()),
+            RuneBridgeTask::RefundChange(operation_id) => {
+                let operation_id = *operation_id;
+                Box::pin(async move {
+                    BtcRefund::new(get_state())
+                        .refund(operation_id)
+                        .await
+                        .map_err(|err| SchedulerError::TaskExecutionFailed(format!("{err:?}")))
+                })
+            }
+            RuneBridgeTask::RefreshGasPrice => Box::pin(Self::refresh_gas_price()),
+            RuneBridgeTask::CompleteMintOrder(data, mint_tx) => {
+                let data = data.clone();
+                let mint_tx = mint_tx.clone();
+                Box::pin(async move { Self::complete_mint_order(data, mint_tx) })
+            }
+            RuneBridgeTask::RescanAddresses {
+                from_index,
+                to_index,
+            } => {
+                let (from_index, to_index) = (*from_index, *to_index);
+                Box::pin(async move {
+                    let report = AddressRescan::new(get_state())
+                        .rescan(from_index, to_index)
                         .await
                         .map_err(|err| SchedulerError::TaskExecutionFailed(format!("{err:?}")))?;
 
-                    log::info!("Created withdrawal transaction: {tx_id}",);
+                    log::info!(
+                        "Rescanned addresses {from_index}..{}: {} utxos recovered, {} addresses with unrecorded utxos",
+                        report.next_index,
+                        report.recovered_utxos,
+                        report.unrecorded.len()
+                    );
+                    get_state().borrow_mut().set_last_rescan(report);
+
+                    Ok(())
+                })
+            }
+            RuneBridgeTask::WatchConfirmations => Box::pin(async move {
+                let source = IcUtxoConfirmations::new(get_state().borrow().ic_btc_network());
+                Withdrawal::new(get_state())
+                    .check_confirmations(&source)
+                    .await;
+
+                Ok(())
+            }),
+            RuneBridgeTask::ArchiveOperations => Box::pin(Self::archive_operations()),
+            RuneBridgeTask::DrainSoftCapQueue => {
+                Box::pin(async move { Self::drain_soft_cap_queue(task_scheduler) })
+            }
+            RuneBridgeTask::AbandonExpiredDeposits => Box::pin(async move {
+                RuneDeposit::get().abandon_expired_deposits();
+                Ok(())
+            }),
+            RuneBridgeTask::OpenMint {
+                eth_address,
+                rune_block,
+                rune_tx,
+                dst_chain_id,
+            } => {
+                let eth_address = eth_address.clone();
+                let rune_id = RuneId {
+                    block: *rune_block,
+                    tx: *rune_tx,
+                };
+                let dst_chain_id = *dst_chain_id;
+                Box::pin(async move {
+                    let operation_id = OpenMint::new(get_state())
+                        .mint_and_request_deposit(eth_address, rune_id, dst_chain_id)
+                        .await
+                        .map_err(|err| SchedulerError::TaskExecutionFailed(format!("{err:?}")))?;
+                    task_scheduler.append_task(
+                        RuneBridgeTask::Deposit(operation_id).into_scheduled(TaskOptions::new()),
+                    );
 
                     Ok(())
                 })
@@ -227,16 +643,62 @@ impl<T, E: ToString> IntoSchedulerError for Result<T, E> {
 
 pub enum RuneMinterNotification {
     Deposit(RuneDepositRequestData),
+    CancelDeposit {
+        payload: RuneCancelDepositData,
+        /// Sender of the notification transaction, which must be the recipient of the deposit.
+        sender: H160,
+    },
+    SetFeePriority {
+        payload: RuneFeePriorityData,
+        /// Sender of the notification transaction, whose withdrawals use the priority.
+        sender: H160,
+    },
+    OpenMint {
+        payload: RuneOpenMintData,
+        /// Sender of the notification transaction, whose deposit address pays for the mint.
+        sender: H160,
+    },
 }
 
 #[derive(Debug, Clone, CandidType, Deserialize)]
 pub struct RuneDepositRequestData {
     pub dst_address: H160,
     pub amounts: Option<HashMap<RuneName, u128>>,
+    /// BTC address to return the deposited BTC to, except the postage kept with the runes. The
+    /// BTC is returned only if its value after the fee is not less than the refund threshold of
+    /// the bridge.
+    pub refund_address: Option<String>,
+    /// Chain id of the EVM to mint the wrapped tokens on, if it is not the EVM of the bridge.
+    pub dst_chain_id: Option<u32>,
+    /// Ids of the funding transactions of the deposit, as shown by block explorers. If set, the
+    /// deposit takes only their utxos, so concurrent deposits to the same address don't race
+    /// for the utxos.
+    pub funding_txids: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize)]
+pub struct RuneCancelDepositData {
+    pub operation_id: MinterOperationId,
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize)]
+pub struct RuneOpenMintData {
+    pub rune_id: RuneIdDid,
+    /// Chain id of the EVM to mint the wrapped tokens on, if it is not the EVM of the bridge.
+    pub dst_chain_id: Option<u32>,
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize)]
+pub struct RuneFeePriorityData {
+    /// Priority of the withdrawals of the tokens burnt after the notification.
+    pub fee_priority: FeePriority,
 }
 
 impl RuneMinterNotification {
     pub const DEPOSIT_TYPE: u32 = 1;
+    pub const CANCEL_DEPOSIT_TYPE: u32 = 2;
+    pub const FEE_PRIORITY_TYPE: u32 = 3;
+    pub const OPEN_MINT_TYPE: u32 = 4;
 }
 
 impl RuneMinterNotification {
@@ -249,6 +711,38 @@ impl RuneMinterNotification {
                     None
                 }
             },
+            Self::CANCEL_DEPOSIT_TYPE => {
+                match Decode!(&event_data.user_data, RuneCancelDepositData) {
+                    Ok(payload) => Some(Self::CancelDeposit {
+                        payload,
+                        sender: event_data.tx_sender,
+                    }),
+                    Err(err) => {
+                        log::warn!("Failed to decode cancel deposit event data: {err:?}");
+                        None
+                    }
+                }
+            }
+            Self::FEE_PRIORITY_TYPE => match Decode!(&event_data.user_data, RuneFeePriorityData) {
+                Ok(payload) => Some(Self::SetFeePriority {
+                    payload,
+                    sender: event_data.tx_sender,
+                }),
+                Err(err) => {
+                    log::warn!("Failed to decode fee priority event data: {err:?}");
+                    None
+                }
+            },
+            Self::OPEN_MINT_TYPE => match Decode!(&event_data.user_data, RuneOpenMintData) {
+                Ok(payload) => Some(Self::OpenMint {
+                    payload,
+                    sender: event_data.tx_sender,
+                }),
+                Err(err) => {
+                    log::warn!("Failed to decode open mint event data: {err:?}");
+                    None
+                }
+            },
             t => {
                 log::warn!("Unknown minter notify event type: {t}");
                 None
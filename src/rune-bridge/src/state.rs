@@ -1,11 +1,12 @@
 use std::collections::HashMap;
+use std::rc::Rc;
 use std::time::Duration;
 
 use bitcoin::bip32::ChainCode;
 use bitcoin::{Network, PrivateKey, PublicKey};
 use candid::{CandidType, Deserialize, Principal};
 use did::H160;
-use eth_signer::sign_strategy::{SigningStrategy, TxSigner};
+use eth_signer::sign_strategy::{SigningStrategy, TransactionSigner, TxSigner};
 use ic_exports::ic_cdk::api::management_canister::bitcoin::BitcoinNetwork;
 use ic_exports::ic_cdk::api::management_canister::ecdsa::{
     EcdsaCurve, EcdsaKeyId, EcdsaPublicKeyResponse,
@@ -13,22 +14,49 @@ use ic_exports::ic_cdk::api::management_canister::ecdsa::{
 use ic_log::{init_log, LogSettings};
 use ic_stable_structures::stable_structures::DefaultMemoryImpl;
 use ic_stable_structures::{StableCell, VirtualMemory};
+use minter_contract_utils::bft_bridge_deploy::BftBridgeDeployStatus;
+use minter_contract_utils::btc_network::BtcNetwork;
+use minter_contract_utils::certified_data::{self, CONFIG_LABEL};
+use minter_contract_utils::circuit_breaker::{CircuitBreaker, CircuitBreakerConfig};
+use minter_contract_utils::clock::{Clock, IcClock};
+use minter_contract_utils::config_validation::{
+    format_config_errors, ConfigError, ConfigValidator,
+};
+use minter_contract_utils::confirmation_policy::{ConfirmationPolicy, ConfirmationTier};
 use minter_contract_utils::evm_bridge::{EvmInfo, EvmParams};
 use minter_contract_utils::evm_link::EvmLink;
+use minter_contract_utils::gas_limits::GasLimits;
+use minter_contract_utils::gas_price::GasPriceSampler;
+use minter_contract_utils::health::{self, HealthMonitor, EVM_RPC, SIGNER};
+use minter_contract_utils::task_dedup::PendingTasks;
+use minter_contract_utils::task_limits::{TaskLimiter, TaskLimits};
 use ord_rs::wallet::LocalSigner;
 use ord_rs::Wallet;
 use ordinals::RuneId;
 
+use crate::core::coin_selection::CoinSelectionStrategy;
+use crate::core::deposit::DepositGcStats;
+use crate::core::rescan::RescanReport;
+use crate::core::screening::{ScreeningConfig, ScreeningOverrides};
+use crate::interface::{DepositError, DepositRequirements};
 use crate::key::{BtcSignerType, IcBtcSigner};
 use crate::ledger::UtxoLedger;
-use crate::memory::{MEMORY_MANAGER, SIGNER_MEMORY_ID};
+use crate::memory::{MEMORY_MANAGER, SIGNER_MEMORY_ID, TASK_LIMITS_MEMORY_ID};
 use crate::rune_info::{RuneInfo, RuneName};
-use crate::{MAINNET_CHAIN_ID, REGTEST_CHAIN_ID, TESTNET_CHAIN_ID};
+use crate::scaling::DecimalScaling;
+use crate::task::AVG_BLOCK_TIME;
+
+/// Name of the `ord` indexer in the health report.
+pub const INDEXER: &str = "indexer";
 
 type SignerStorage = StableCell<TxSigner, VirtualMemory<DefaultMemoryImpl>>;
 
 const DEFAULT_DEPOSIT_FEE: u64 = 100_000;
 const DEFAULT_MEMPOOL_TIMEOUT: Duration = Duration::from_secs(24 * 60 * 60);
+const DEFAULT_WITHDRAWAL_POSTAGE: u64 = 10_000;
+const DEFAULT_REFUND_THRESHOLD: u64 = 10_000;
+const DEFAULT_DEPOSIT_EXPIRY: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+const DEFAULT_INDEXER_MAX_LAG: u32 = 1;
 
 pub struct State {
     pub(crate) config: RuneBridgeConfig,
@@ -38,6 +66,17 @@ pub struct State {
     pub(crate) master_key: Option<MasterKey>,
     pub(crate) ledger: UtxoLedger,
     pub(crate) runes: HashMap<RuneName, RuneInfo>,
+    pub(crate) screening_overrides: ScreeningOverrides,
+    pub(crate) task_limiter: TaskLimiter<VirtualMemory<DefaultMemoryImpl>>,
+    pub(crate) pending_tasks: PendingTasks,
+    pub(crate) health: HealthMonitor,
+    pub(crate) circuit_breaker: CircuitBreaker,
+    pub(crate) gas_price: GasPriceSampler,
+    pub(crate) gas_limits: GasLimits,
+    pub(crate) last_rescan: Option<RescanReport>,
+    pub(crate) deposit_gc: DepositGcStats,
+    pub(crate) bft_deploy_status: Option<BftBridgeDeployStatus>,
+    pub(crate) clock: Rc<dyn Clock>,
 }
 
 #[derive(Debug, Clone)]
@@ -69,27 +108,64 @@ impl Default for State {
             master_key: None,
             ledger: Default::default(),
             runes: Default::default(),
+            screening_overrides: Default::default(),
+            task_limiter: TaskLimiter::new(MEMORY_MANAGER.with(|mm| mm.get(TASK_LIMITS_MEMORY_ID))),
+            pending_tasks: Default::default(),
+            health: HealthMonitor::new(&[EVM_RPC, INDEXER, SIGNER]),
+            circuit_breaker: CircuitBreaker::default(),
+            gas_price: GasPriceSampler::default(),
+            gas_limits: GasLimits::default(),
+            last_rescan: None,
+            deposit_gc: DepositGcStats::default(),
+            bft_deploy_status: None,
+            clock: Rc::new(IcClock),
         }
     }
 }
 
-#[derive(Debug, CandidType, Deserialize)]
+#[derive(Debug, Clone, CandidType, Deserialize)]
 pub struct RuneBridgeConfig {
-    pub network: BitcoinNetwork,
+    pub network: BtcNetwork,
     pub evm_link: EvmLink,
     pub signing_strategy: SigningStrategy,
     pub admin: Principal,
     pub log_settings: LogSettings,
     pub min_confirmations: u32,
+    /// Additional confirmation requirements for larger deposits. The deposit amount is the
+    /// largest amount of a deposited rune, in the smallest units of the rune.
+    pub confirmation_tiers: Vec<ConfirmationTier>,
     pub indexer_url: String,
     pub deposit_fee: u64,
     pub mempool_timeout: Duration,
+    /// Value in satoshi of the output carrying the withdrawn runes to the destination address.
+    pub withdrawal_postage: u64,
+    /// Decimals of the wrapped tokens. If not set, wrapped tokens have the same decimals as the
+    /// divisibility of the rune. The value must not be changed after the first wrapped token is
+    /// created.
+    pub wrapped_token_decimals: Option<u8>,
+    /// Screening of deposits before minting wrapped tokens.
+    pub screening: ScreeningConfig,
+    /// Minimum value in satoshi of the BTC change returned to the refund address of a deposit.
+    /// Smaller change stays with the bridge.
+    pub refund_threshold: u64,
+    /// Chain ids of other EVMs the deposits can be minted to instead of the EVM of the BftBridge
+    /// config. The bridge only signs the mint orders for these chains, and the users send them to
+    /// the EVM themselves.
+    pub dst_chain_ids: Vec<u32>,
+    /// Strategy of choosing the rune utxos spent by the withdrawals.
+    pub coin_selection: CoinSelectionStrategy,
+    /// Time since the request after which a deposit whose mint orders are not signed yet is
+    /// abandoned, e.g. if its transaction never gets enough confirmations.
+    pub deposit_expiry: Duration,
+    /// Number of blocks the indexer may be behind the bitcoin tip for its rune balances to be
+    /// trusted. A lagging indexer does not see the runes of the recent deposit utxos.
+    pub indexer_max_lag: u32,
 }
 
 impl Default for RuneBridgeConfig {
     fn default() -> Self {
         Self {
-            network: BitcoinNetwork::Regtest,
+            network: BtcNetwork::Regtest,
             evm_link: EvmLink::default(),
             signing_strategy: SigningStrategy::Local {
                 private_key: [0; 32],
@@ -97,36 +173,160 @@ impl Default for RuneBridgeConfig {
             admin: Principal::management_canister(),
             log_settings: LogSettings::default(),
             min_confirmations: 12,
+            confirmation_tiers: vec![],
             indexer_url: String::new(),
             deposit_fee: DEFAULT_DEPOSIT_FEE,
             mempool_timeout: DEFAULT_MEMPOOL_TIMEOUT,
+            withdrawal_postage: DEFAULT_WITHDRAWAL_POSTAGE,
+            wrapped_token_decimals: None,
+            screening: ScreeningConfig::default(),
+            refund_threshold: DEFAULT_REFUND_THRESHOLD,
+            dst_chain_ids: vec![],
+            coin_selection: CoinSelectionStrategy::default(),
+            deposit_expiry: DEFAULT_DEPOSIT_EXPIRY,
+            indexer_max_lag: DEFAULT_INDEXER_MAX_LAG,
         }
     }
 }
 
 impl RuneBridgeConfig {
-    fn validate(&self) -> Result<(), String> {
-        if self.indexer_url.is_empty() {
-            return Err("Indexer url is empty".to_string());
+    /// Checks the configuration, returning all the errors found.
+    pub fn validate(&self) -> Result<(), Vec<ConfigError>> {
+        let mut validator = ConfigValidator::new();
+        validator
+            .principal("admin", self.admin)
+            .evm_link("evm_link", &self.evm_link)
+            .https_url("indexer_url", &self.indexer_url)
+            .check(
+                "confirmation_tiers",
+                ConfirmationPolicy::new(self.min_confirmations, self.confirmation_tiers.clone())
+                    .validate(),
+            )
+            .check("screening", self.screening.validate());
+
+        if self.withdrawal_postage == 0 {
+            validator.invalid("withdrawal_postage", "must be greater than zero");
         }
 
-        if !self.indexer_url.starts_with("https") {
-            return Err(format!(
-                "Indexer url must specify https url, but give value is: {}",
-                self.indexer_url
-            ));
+        if self.deposit_expiry.is_zero() {
+            validator.invalid("deposit_expiry", "must be greater than zero");
         }
 
-        Ok(())
+        if self.dst_chain_ids.contains(&0) {
+            validator.invalid("dst_chain_ids", "chain id must not be zero");
+        }
+        if let Some(chain_id) = self
+            .dst_chain_ids
+            .iter()
+            .find(|chain_id| !chain_registry::is_evm_chain_id(**chain_id))
+        {
+            validator.invalid(
+                "dst_chain_ids",
+                format!("{chain_id} is reserved for a non-EVM chain"),
+            );
+        }
+
+        validator.finish()
+    }
+
+    /// Checks the configuration and, if it is valid, checks the EVM of its link against the
+    /// current BftBridge configuration of the bridge, if any.
+    pub async fn validate_with_evm(
+        &self,
+        bft_config: &BftBridgeConfig,
+    ) -> Result<(), Vec<ConfigError>> {
+        self.validate()?;
+
+        if bft_config.erc20_chain_id == 0 {
+            let mut validator = ConfigValidator::new();
+            validator.check("evm_link", health::check_evm_link(&self.evm_link).await);
+            return validator.finish();
+        }
+
+        bft_config.validate_with_evm(&self.evm_link).await
     }
 }
 
-#[derive(Default, Debug, CandidType, Deserialize)]
+#[derive(Default, Debug, Clone, CandidType, Deserialize)]
 pub struct BftBridgeConfig {
     pub erc20_chain_id: u32,
     pub bridge_address: H160,
 }
 
+impl BftBridgeConfig {
+    /// Checks the configuration against the EVM at the `evm_link`: the chain id of the EVM and
+    /// the code of the bridge contract.
+    pub async fn validate_with_evm(&self, evm_link: &EvmLink) -> Result<(), Vec<ConfigError>> {
+        let client = evm_link.get_json_rpc_client();
+        let mut validator = ConfigValidator::new();
+        validator
+            .evm_chain_id("erc20_chain_id", &client, self.erc20_chain_id.into())
+            .await;
+        if self.bridge_address.0.is_zero() {
+            validator.evm_address("bridge_address", &self.bridge_address);
+        } else {
+            validator
+                .contract_code("bridge_address", &client, &self.bridge_address)
+                .await;
+        }
+
+        validator.finish()
+    }
+
+    /// Checks with `eth_call` that the BftBridge mints with the EVM address of the `signer`. The
+    /// contract must be checked with [`Self::validate_with_evm`] first.
+    pub async fn validate_wiring(
+        &self,
+        evm_link: &EvmLink,
+        signer: &TxSigner,
+    ) -> Result<(), Vec<ConfigError>> {
+        let client = evm_link.get_json_rpc_client();
+        let mut validator = ConfigValidator::new();
+        match signer.get_address().await {
+            Ok(minter_address) => {
+                validator
+                    .bridge_minter(
+                        "bridge_address",
+                        &client,
+                        &self.bridge_address,
+                        &minter_address,
+                    )
+                    .await;
+            }
+            Err(err) => {
+                validator.invalid(
+                    "bridge_address",
+                    format!("failed to get EVM address of the canister: {err}"),
+                );
+            }
+        }
+
+        validator.finish()
+    }
+}
+
+/// Configuration of the bridge returned by the `get_bridge_config` query. The signing strategy and
+/// the screening provider settings are not exposed.
+#[derive(Debug, Clone, CandidType, Deserialize)]
+pub struct BridgeConfigInfo {
+    pub network: BtcNetwork,
+    pub evm_link: EvmLink,
+    pub admin: Principal,
+    pub min_confirmations: u32,
+    pub confirmation_tiers: Vec<ConfirmationTier>,
+    pub indexer_url: String,
+    pub deposit_fee: u64,
+    pub withdrawal_postage: u64,
+    pub wrapped_token_decimals: Option<u8>,
+    pub refund_threshold: u64,
+    pub dst_chain_ids: Vec<u32>,
+    pub coin_selection: CoinSelectionStrategy,
+    pub deposit_expiry: Duration,
+    pub indexer_max_lag: u32,
+    pub erc20_chain_id: u32,
+    pub bridge_address: H160,
+}
+
 impl State {
     /// Returns id of the IC ECDSA key used by the canister.
     pub fn ecdsa_key_id(&self) -> EcdsaKeyId {
@@ -174,16 +374,12 @@ impl State {
 
     /// Returns BTC network the canister works with (IC style).
     pub fn ic_btc_network(&self) -> BitcoinNetwork {
-        self.config.network
+        self.config.network.ic_network()
     }
 
     /// Returns BTC network the canister works with (BTC style).
     pub fn network(&self) -> Network {
-        match self.config.network {
-            BitcoinNetwork::Mainnet => Network::Bitcoin,
-            BitcoinNetwork::Testnet => Network::Testnet,
-            BitcoinNetwork::Regtest => Network::Regtest,
-        }
+        self.config.network.network()
     }
 
     /// Minimum number of confirmations the canister requires to consider a transaction to be confirmed.
@@ -191,6 +387,14 @@ impl State {
         self.config.min_confirmations
     }
 
+    /// Confirmations requirements depending on the deposit size.
+    pub fn confirmation_policy(&self) -> ConfirmationPolicy {
+        ConfirmationPolicy::new(
+            self.config.min_confirmations,
+            self.config.confirmation_tiers.clone(),
+        )
+    }
+
     fn master_key(&self) -> MasterKey {
         self.master_key.clone().expect("ecdsa is not initialized")
     }
@@ -206,6 +410,11 @@ impl State {
         }
     }
 
+    /// Source of the time for the expiry, retry and timeout checks.
+    pub fn clock(&self) -> Rc<dyn Clock> {
+        self.clock.clone()
+    }
+
     /// Wallet to be used to sign transactions with the given derivation path.
     pub fn wallet(&self) -> Wallet {
         Wallet::new_with_signer(self.btc_signer())
@@ -254,15 +463,26 @@ impl State {
         self.bft_config.erc20_chain_id
     }
 
-    /// Chain id to be used for the rune.
-    pub fn btc_chain_id(&self) -> u32 {
-        match self.config.network {
-            BitcoinNetwork::Mainnet => MAINNET_CHAIN_ID,
-            BitcoinNetwork::Testnet => TESTNET_CHAIN_ID,
-            BitcoinNetwork::Regtest => REGTEST_CHAIN_ID,
+    /// Chain id of the EVM to mint the wrapped tokens on: the `dst_chain_id` requested by the user
+    /// if it is allowed by the configuration, or the chain id of the BftBridge config.
+    pub fn recipient_chain_id(&self, dst_chain_id: Option<u32>) -> Result<u32, DepositError> {
+        match dst_chain_id {
+            None => Ok(self.erc20_chain_id()),
+            Some(chain_id)
+                if chain_id == self.erc20_chain_id()
+                    || self.config.dst_chain_ids.contains(&chain_id) =>
+            {
+                Ok(chain_id)
+            }
+            Some(chain_id) => Err(DepositError::ChainNotAllowed { chain_id }),
         }
     }
 
+    /// Chain id of the runes of the bitcoin network of the bridge.
+    pub fn rune_chain_id(&self) -> u32 {
+        self.config.network.rune_chain_id()
+    }
+
     /// Returns EVM parameters.
     pub fn get_evm_params(&self) -> &Option<EvmParams> {
         &self.evm_params
@@ -278,19 +498,21 @@ impl State {
         self.config.admin
     }
 
-    /// Panics if the current caller is not admin of the canister.
-    pub fn check_admin(&self, caller: Principal) {
+    /// Returns `NotAuthorized` error if the current caller is not admin of the canister.
+    pub fn check_admin(&self, caller: Principal) -> minter_did::error::Result<()> {
         if caller != self.admin() {
-            panic!("access denied");
+            return Err(minter_did::error::Error::NotAuthorized);
         }
+
+        Ok(())
     }
 
-    /// Validates the given configuration and sets it to the state. Panics in case the configuration
-    /// is invalid.
-    pub fn configure(&mut self, config: RuneBridgeConfig) {
-        if let Err(err) = config.validate() {
-            panic!("Invalid configuration: {err}");
-        }
+    /// Validates the given configuration and sets it to the state. Fails if the configuration is
+    /// invalid, e.g. if its confirmation tiers are inconsistent.
+    pub fn configure(&mut self, config: RuneBridgeConfig) -> minter_did::error::Result<()> {
+        config
+            .validate()
+            .map_err(|errors| minter_did::error::Error::Internal(format_config_errors(&errors)))?;
 
         let signer = config
             .signing_strategy
@@ -304,6 +526,29 @@ impl State {
         init_log(&config.log_settings).expect("failed to init logger");
 
         self.config = config;
+        self.certify_config();
+
+        Ok(())
+    }
+
+    /// Replaces the configuration set by [`State::configure`], e.g. by `admin_reconfigure`. The
+    /// signer and the logger are kept, they are changed with their own endpoints. The network
+    /// cannot be changed, since the deposit addresses are derived for it.
+    pub fn reconfigure(&mut self, mut config: RuneBridgeConfig) -> minter_did::error::Result<()> {
+        config
+            .validate()
+            .map_err(|errors| minter_did::error::Error::Internal(format_config_errors(&errors)))?;
+        if config.network != self.config.network {
+            return Err(minter_did::error::Error::Internal(
+                "network of the bridge cannot be changed".to_string(),
+            ));
+        }
+
+        config.signing_strategy = self.config.signing_strategy.clone();
+        config.log_settings = self.config.log_settings.clone();
+        self.config = config;
+        self.certify_config();
+        Ok(())
     }
 
     /// Updates the ecdsa signing configuration with the given master key information.
@@ -323,15 +568,223 @@ impl State {
     /// Configures the link to BFT bridge contract.
     pub fn configure_bft(&mut self, bft_config: BftBridgeConfig) {
         self.bft_config = bft_config;
+        self.certify_config();
+    }
+
+    pub fn bft_config(&self) -> &BftBridgeConfig {
+        &self.bft_config
+    }
+
+    /// BftBridge deployment started by the bridge, if any.
+    pub fn bft_deploy_status(&self) -> Option<&BftBridgeDeployStatus> {
+        self.bft_deploy_status.as_ref()
+    }
+
+    pub fn set_bft_deploy_status(&mut self, status: BftBridgeDeployStatus) {
+        self.bft_deploy_status = Some(status);
+    }
+
+    pub fn config_info(&self) -> BridgeConfigInfo {
+        BridgeConfigInfo {
+            network: self.config.network,
+            evm_link: self.config.evm_link.clone(),
+            admin: self.config.admin,
+            min_confirmations: self.config.min_confirmations,
+            confirmation_tiers: self.config.confirmation_tiers.clone(),
+            indexer_url: self.config.indexer_url.clone(),
+            deposit_fee: self.config.deposit_fee,
+            withdrawal_postage: self.config.withdrawal_postage,
+            wrapped_token_decimals: self.config.wrapped_token_decimals,
+            refund_threshold: self.config.refund_threshold,
+            dst_chain_ids: self.config.dst_chain_ids.clone(),
+            coin_selection: self.config.coin_selection,
+            deposit_expiry: self.config.deposit_expiry,
+            indexer_max_lag: self.config.indexer_max_lag,
+            erc20_chain_id: self.bft_config.erc20_chain_id,
+            bridge_address: self.bft_config.bridge_address.clone(),
+        }
+    }
+
+    fn certify_config(&self) {
+        certified_data::certify_value(CONFIG_LABEL, &self.config_info());
+    }
+
+    /// Certifies the configuration and the reserves again, e.g. after an upgrade.
+    pub fn certify(&self) {
+        self.certify_config();
+        self.ledger.certify_reserves();
+    }
+
+    /// Sets priorities and concurrency limits of the scheduler tasks. Returns an error if the
+    /// limits are invalid.
+    pub fn configure_task_limits(&mut self, limits: TaskLimits) -> minter_did::error::Result<()> {
+        limits.validate().map_err(|err| {
+            minter_did::error::Error::Internal(format!("Invalid task limits: {err}"))
+        })?;
+
+        self.task_limiter.set_limits(limits);
+        Ok(())
+    }
+
+    /// Tracker of the scheduler tasks in flight.
+    pub fn task_limiter(&self) -> &TaskLimiter<VirtualMemory<DefaultMemoryImpl>> {
+        &self.task_limiter
+    }
+
+    /// Tasks appended to the scheduler only once until they are completed.
+    pub fn pending_tasks_mut(&mut self) -> &mut PendingTasks {
+        &mut self.pending_tasks
+    }
+
+    /// Results of the latest dependency checks.
+    pub fn health(&self) -> &HealthMonitor {
+        &self.health
+    }
+
+    pub fn health_mut(&mut self) -> &mut HealthMonitor {
+        &mut self.health
+    }
+
+    /// Error rates of the indexer and EVM RPC calls, pausing the deposits when they are too high.
+    pub fn circuit_breaker(&self) -> &CircuitBreaker {
+        &self.circuit_breaker
+    }
+
+    pub fn circuit_breaker_mut(&mut self) -> &mut CircuitBreaker {
+        &mut self.circuit_breaker
+    }
+
+    /// Sets the thresholds of the circuit breaker. Returns an error if they are invalid.
+    pub fn configure_circuit_breaker(
+        &mut self,
+        config: CircuitBreakerConfig,
+    ) -> minter_did::error::Result<()> {
+        self.circuit_breaker.configure(config).map_err(|err| {
+            minter_did::error::Error::Internal(format!("Invalid circuit breaker config: {err}"))
+        })
+    }
+
+    /// Gas price estimate of the EVM.
+    pub fn gas_price(&self) -> &GasPriceSampler {
+        &self.gas_price
+    }
+
+    pub fn gas_price_mut(&mut self) -> &mut GasPriceSampler {
+        &mut self.gas_price
+    }
+
+    /// Gas limits of the EVM transactions.
+    pub fn gas_limits(&self) -> &GasLimits {
+        &self.gas_limits
+    }
+
+    pub fn gas_limits_mut(&mut self) -> &mut GasLimits {
+        &mut self.gas_limits
+    }
+
+    /// Sets gas limits of the EVM transactions. Returns an error if the limits are invalid.
+    pub fn configure_gas_limits(&mut self, limits: GasLimits) -> minter_did::error::Result<()> {
+        limits.validate().map_err(|err| {
+            minter_did::error::Error::Internal(format!("Invalid gas limits: {err}"))
+        })?;
+
+        self.gas_limits = limits;
+        Ok(())
     }
 
     pub fn mempool_timeout(&self) -> Duration {
         self.config.mempool_timeout
     }
+
+    /// Requirements to the deposits of the `rune_name`.
+    pub fn deposit_requirements(&self, rune_name: Option<RuneName>) -> DepositRequirements {
+        let min_rune_amount = rune_name
+            .and_then(|name| self.runes.get(&name))
+            .and_then(|rune_info| self.amount_scaling(rune_info).min_rune_amount());
+
+        DepositRequirements {
+            min_btc_amount: self.deposit_fee(),
+            min_confirmations: self.config.min_confirmations,
+            confirmation_tiers: self.config.confirmation_tiers.clone(),
+            estimated_indexer_latency_secs: (AVG_BLOCK_TIME * self.config.min_confirmations.max(1))
+                .as_secs(),
+            min_rune_amount,
+        }
+    }
+
+    /// Scaling between units of the rune and units of its wrapped token.
+    pub fn amount_scaling(&self, rune_info: &RuneInfo) -> DecimalScaling {
+        DecimalScaling::new(
+            rune_info.decimals(),
+            self.config
+                .wrapped_token_decimals
+                .unwrap_or(rune_info.decimals()),
+        )
+    }
+
+    /// Value in satoshi of the rune output of withdrawal transactions.
+    pub fn withdrawal_postage(&self) -> u64 {
+        self.config.withdrawal_postage
+    }
+
+    /// Minimum BTC change of a deposit to be sent to its refund address.
+    pub fn refund_threshold(&self) -> u64 {
+        self.config.refund_threshold
+    }
+
+    /// Strategy of choosing the rune utxos of withdrawals.
+    pub fn coin_selection(&self) -> CoinSelectionStrategy {
+        self.config.coin_selection
+    }
+
+    /// Deposit screening configuration.
+    pub fn screening_config(&self) -> &ScreeningConfig {
+        &self.config.screening
+    }
+
+    /// Screening decisions set by the admin.
+    pub fn screening_overrides(&self) -> &ScreeningOverrides {
+        &self.screening_overrides
+    }
+
+    /// Mutable reference to the screening decisions set by the admin.
+    pub fn screening_overrides_mut(&mut self) -> &mut ScreeningOverrides {
+        &mut self.screening_overrides
+    }
+
+    /// Report of the last completed rescan of the deposit addresses.
+    pub fn last_rescan(&self) -> Option<&RescanReport> {
+        self.last_rescan.as_ref()
+    }
+
+    pub fn set_last_rescan(&mut self, report: RescanReport) {
+        self.last_rescan = Some(report);
+    }
+
+    /// Time after which the unsigned deposits are abandoned.
+    pub fn deposit_expiry(&self) -> Duration {
+        self.config.deposit_expiry
+    }
+
+    /// Number of blocks the indexer may be behind the bitcoin tip.
+    pub fn indexer_max_lag(&self) -> u32 {
+        self.config.indexer_max_lag
+    }
+
+    /// Counters of the abandoned deposits since the canister start.
+    pub fn deposit_gc_stats(&self) -> &DepositGcStats {
+        &self.deposit_gc
+    }
+
+    pub fn deposit_gc_stats_mut(&mut self) -> &mut DepositGcStats {
+        &mut self.deposit_gc
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::str::FromStr;
+
     use super::*;
 
     #[test]
@@ -358,4 +811,91 @@ mod tests {
 
         assert_eq!(state.indexer_url(), "https://url.com".to_string());
     }
+
+    #[test]
+    fn config_errors_are_collected() {
+        let config = RuneBridgeConfig {
+            admin: Principal::anonymous(),
+            indexer_url: "http://url.com".to_string(),
+            withdrawal_postage: 0,
+            ..Default::default()
+        };
+
+        let errors = config.validate().unwrap_err();
+        assert_eq!(errors.len(), 4, "{errors:?}");
+        assert!(errors.contains(&ConfigError::AnonymousPrincipal {
+            field: "admin".to_string()
+        }));
+        assert!(errors.contains(&ConfigError::InvalidUrl {
+            field: "indexer_url".to_string(),
+            url: "http://url.com".to_string(),
+            reason: "url must use https".to_string(),
+        }));
+
+        let config = RuneBridgeConfig {
+            evm_link: EvmLink::Ic(Principal::from_slice(&[1; 10])),
+            indexer_url: "https://url.com".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(config.validate(), Ok(()));
+    }
+
+    #[test]
+    fn deposit_requirements() {
+        let rune_name = RuneName::from_str("TESTRUNE").unwrap();
+        let mut state = State {
+            config: RuneBridgeConfig {
+                min_confirmations: 3,
+                deposit_fee: 20_000,
+                wrapped_token_decimals: Some(2),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        state.runes.insert(
+            rune_name,
+            RuneInfo {
+                name: rune_name,
+                decimals: 6,
+                block: 1,
+                tx: 1,
+            },
+        );
+
+        let requirements = state.deposit_requirements(Some(rune_name));
+        assert_eq!(requirements.min_btc_amount, 20_000);
+        assert_eq!(requirements.min_confirmations, 3);
+        assert_eq!(requirements.estimated_indexer_latency_secs, 3 * 600);
+        assert_eq!(requirements.min_rune_amount, Some(10_000));
+
+        let unknown = RuneName::from_str("UNKNOWN").unwrap();
+        assert_eq!(
+            state.deposit_requirements(Some(unknown)).min_rune_amount,
+            None
+        );
+        assert_eq!(state.deposit_requirements(None).min_rune_amount, None);
+    }
+
+    #[test]
+    fn recipient_chain_id_is_checked_against_allowlist() {
+        let state = State {
+            config: RuneBridgeConfig {
+                dst_chain_ids: vec![355113],
+                ..Default::default()
+            },
+            bft_config: BftBridgeConfig {
+                erc20_chain_id: 355110,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        assert_eq!(state.recipient_chain_id(None).unwrap(), 355110);
+        assert_eq!(state.recipient_chain_id(Some(355110)).unwrap(), 355110);
+        assert_eq!(state.recipient_chain_id(Some(355113)).unwrap(), 355113);
+        assert!(matches!(
+            state.recipient_chain_id(Some(1)),
+            Err(DepositError::ChainNotAllowed { chain_id: 1 })
+        ));
+    }
 }
@@ -12,7 +12,7 @@ use ic_exports::ic_kit::RejectionCode;
 use crate::ledger::UtxoKey;
 use crate::state::State;
 
-const AVG_BLOCK_TIME: Duration = Duration::from_secs(60 * 10); // 10 minutes
+pub(crate) const AVG_BLOCK_TIME: Duration = Duration::from_secs(60 * 10); // 10 minutes
 
 /// Task to remove used UTXOs from the ledger.
 /// It also remark as available to be spent those utxos which haven't been spent for some reason.
@@ -29,7 +29,7 @@ impl From<Rc<RefCell<State>>> for RemoveUsedUtxosTask {
 impl RemoveUsedUtxosTask {
     /// Run the task.
     pub async fn run(self) {
-        let time_now = Duration::from_nanos(ic_exports::ic_cdk::api::time());
+        let time_now = Duration::from_nanos(self.state.borrow().clock().now());
         let min_confirmations = self.state.borrow().min_confirmations();
         let minimum_confirmation_time = min_confirmations * AVG_BLOCK_TIME;
 
@@ -0,0 +1,164 @@
+//! Journal of the BTC transactions signed by the bridge and not yet confirmed as sent.
+//!
+//! A signed transaction is written to the journal before it is broadcast, and removed when the
+//! operation records the sent transaction. If the broadcast fails, or the canister traps after
+//! it, the retry of the operation finds the journaled transaction and broadcasts it again instead
+//! of building a new one, so an operation never produces two conflicting transactions.
+
+use std::borrow::Cow;
+
+use bitcoin::consensus::{Decodable, Encodable};
+use bitcoin::{Transaction, Txid};
+use candid::{CandidType, Decode, Encode};
+use ic_stable_structures::stable_structures::Memory;
+use ic_stable_structures::{BTreeMapStructure, Bound, StableBTreeMap, Storable};
+use minter_contract_utils::operation_store::MinterOperationId;
+use serde::Deserialize;
+
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct JournaledTx {
+    /// Consensus encoded signed transaction.
+    transaction: Vec<u8>,
+    /// Timestamp (nanoseconds) of the journal entry creation.
+    pub journaled_at: u64,
+}
+
+impl JournaledTx {
+    pub fn transaction(&self) -> Transaction {
+        Transaction::consensus_decode(&mut &self.transaction[..])
+            .expect("journaled transaction must be valid")
+    }
+
+    pub fn txid(&self) -> Txid {
+        self.transaction().txid()
+    }
+}
+
+impl Storable for JournaledTx {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("serialization failed"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).expect("deserialization failed")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+pub struct TxJournal<M: Memory> {
+    entries: StableBTreeMap<MinterOperationId, JournaledTx, M>,
+}
+
+impl<M: Memory> TxJournal<M> {
+    pub fn new(memory: M) -> Self {
+        Self {
+            entries: StableBTreeMap::new(memory),
+        }
+    }
+
+    /// Transaction signed for the operation and not recorded as sent yet.
+    pub fn get(&self, operation_id: MinterOperationId) -> Option<JournaledTx> {
+        self.entries.get(&operation_id)
+    }
+
+    /// Writes the signed `transaction` of the operation before it is broadcast. An operation has
+    /// at most one journaled transaction, so the existing entry is kept and returned if there is
+    /// one.
+    pub fn record(
+        &mut self,
+        operation_id: MinterOperationId,
+        transaction: &Transaction,
+        timestamp: u64,
+    ) -> JournaledTx {
+        if let Some(existing) = self.entries.get(&operation_id) {
+            log::warn!(
+                "Operation {operation_id} already has journaled transaction {}",
+                existing.txid()
+            );
+            return existing;
+        }
+
+        let mut bytes = vec![];
+        transaction
+            .consensus_encode(&mut bytes)
+            .expect("transaction encoding to vec never fails");
+        let entry = JournaledTx {
+            transaction: bytes,
+            journaled_at: timestamp,
+        };
+        self.entries.insert(operation_id, entry.clone());
+
+        entry
+    }
+
+    /// Removes the entry once the operation records the sent transaction.
+    pub fn remove(&mut self, operation_id: MinterOperationId) {
+        self.entries.remove(&operation_id);
+    }
+
+    pub fn len(&self) -> u64 {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::absolute::LockTime;
+    use bitcoin::transaction::Version;
+    use bitcoin::{Amount, ScriptBuf, TxOut};
+    use ic_stable_structures::VectorMemory;
+
+    use super::*;
+
+    fn operation_id(id: u64) -> MinterOperationId {
+        MinterOperationId::from_bytes(id.to_bytes())
+    }
+
+    fn transaction(value: u64) -> Transaction {
+        Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![],
+            output: vec![TxOut {
+                value: Amount::from_sat(value),
+                script_pubkey: ScriptBuf::new(),
+            }],
+        }
+    }
+
+    #[test]
+    fn journaled_transaction_is_returned_until_removed() {
+        let mut journal = TxJournal::new(VectorMemory::default());
+        let tx = transaction(1_000);
+
+        assert_eq!(journal.get(operation_id(1)), None);
+        journal.record(operation_id(1), &tx, 42);
+
+        let entry = journal.get(operation_id(1)).unwrap();
+        assert_eq!(entry.transaction(), tx);
+        assert_eq!(entry.txid(), tx.txid());
+        assert_eq!(entry.journaled_at, 42);
+        assert_eq!(journal.get(operation_id(2)), None);
+
+        journal.remove(operation_id(1));
+        assert!(journal.is_empty());
+    }
+
+    #[test]
+    fn existing_entry_is_not_replaced() {
+        let mut journal = TxJournal::new(VectorMemory::default());
+        let first = transaction(1_000);
+
+        journal.record(operation_id(1), &first, 1);
+        let entry = journal.record(operation_id(1), &transaction(2_000), 2);
+
+        assert_eq!(entry.txid(), first.txid());
+        assert_eq!(journal.get(operation_id(1)).unwrap().txid(), first.txid());
+        assert_eq!(journal.len(), 1);
+    }
+}
@@ -0,0 +1,115 @@
+//! Wrapped token units burnt by the users which could not be withdrawn as runes.
+//!
+//! If the wrapped token has more decimals than the rune, a burnt amount is withdrawn in whole rune
+//! units, and the wrapped token units left over are credited to the sender. The credit is added to
+//! the next burn of the same rune by the sender, so the units are withdrawn once they add up to a
+//! rune unit.
+
+use std::borrow::Cow;
+
+use candid::{CandidType, Decode, Encode};
+use did::H160;
+use ic_stable_structures::stable_structures::Memory;
+use ic_stable_structures::{BTreeMapStructure, Bound, StableBTreeMap, Storable};
+use serde::Deserialize;
+
+use crate::rune_info::RuneName;
+
+/// Wrapped token units of a rune credited to an address.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct WithdrawalCredit {
+    pub rune_name: RuneName,
+    pub token_amount: u128,
+}
+
+#[derive(Debug, Default, Clone, CandidType, Deserialize)]
+struct AddressCredits(Vec<WithdrawalCredit>);
+
+impl Storable for AddressCredits {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("serialization failed"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).expect("deserialization failed")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+pub struct WithdrawalCredits<M: Memory> {
+    credits: StableBTreeMap<H160, AddressCredits, M>,
+}
+
+impl<M: Memory> WithdrawalCredits<M> {
+    pub fn new(memory: M) -> Self {
+        Self {
+            credits: StableBTreeMap::new(memory),
+        }
+    }
+
+    /// Credits of all the runes of the address.
+    pub fn get_all(&self, address: &H160) -> Vec<WithdrawalCredit> {
+        self.credits.get(address).unwrap_or_default().0
+    }
+
+    /// Wrapped token units of the rune credited to the address.
+    pub fn get(&self, address: &H160, rune_name: RuneName) -> u128 {
+        self.get_all(address)
+            .into_iter()
+            .find(|credit| credit.rune_name == rune_name)
+            .map_or(0, |credit| credit.token_amount)
+    }
+
+    /// Replaces the credit of the rune of the address. The zero credits are not stored.
+    pub fn set(&mut self, address: &H160, rune_name: RuneName, token_amount: u128) {
+        let mut credits = self.credits.get(address).unwrap_or_default();
+        credits.0.retain(|credit| credit.rune_name != rune_name);
+        if token_amount > 0 {
+            credits.0.push(WithdrawalCredit {
+                rune_name,
+                token_amount,
+            });
+        }
+
+        if credits.0.is_empty() {
+            self.credits.remove(address);
+        } else {
+            self.credits.insert(address.clone(), credits);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use ic_stable_structures::VectorMemory;
+
+    use super::*;
+
+    fn rune(name: &str) -> RuneName {
+        RuneName::from_str(name).unwrap()
+    }
+
+    #[test]
+    fn credits_are_kept_per_rune() {
+        let mut credits = WithdrawalCredits::new(VectorMemory::default());
+        let address = H160::from_slice(&[1; 20]);
+
+        credits.set(&address, rune("FIRSTRUNE"), 42);
+        credits.set(&address, rune("SECONDRUNE"), 7);
+        credits.set(&address, rune("FIRSTRUNE"), 43);
+
+        assert_eq!(credits.get(&address, rune("FIRSTRUNE")), 43);
+        assert_eq!(credits.get(&address, rune("SECONDRUNE")), 7);
+        assert_eq!(
+            credits.get(&H160::from_slice(&[2; 20]), rune("FIRSTRUNE")),
+            0
+        );
+
+        credits.set(&address, rune("FIRSTRUNE"), 0);
+        credits.set(&address, rune("SECONDRUNE"), 0);
+        assert!(credits.get_all(&address).is_empty());
+    }
+}